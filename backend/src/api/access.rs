@@ -0,0 +1,104 @@
+//! Route guard keeping non-admin users to the servers they may manage, and to the actions
+//! they were granted on them (see `services::server_access`). Routes addressing a server, a
+//! backup or a backup job by `:id` are checked here; list, create and bulk endpoints filter
+//! in their handlers. Viewers are also kept from writing anything.
+
+use axum::{
+    extract::{FromRequestParts, MatchedPath, RawPathParams, Request, State},
+    http::Method,
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+
+use crate::api::auth::AuthUser;
+use crate::db::DbPool;
+use crate::error::AppError;
+use crate::services::server_access::{self, BACKUPS_MANAGE, BACKUPS_VIEW, CONSOLE_COMMAND, CONSOLE_VIEW, FILES_EDIT, MANAGE, ROLE_VIEWER, SERVER_CONTROL, VIEW};
+use crate::AppState;
+
+/// Permission needed on a `/servers/:id...` route, from what follows the id
+fn server_route_permission(method: &Method, tail: &str) -> &'static str {
+    match tail {
+        "/start" | "/stop" | "/restart" | "/kill" | "/suspend" | "/resume" => SERVER_CONTROL,
+        "/command" | "/command/exec" => CONSOLE_COMMAND,
+        "/files" | "/files/read" | "/files/write" | "/files/delete" | "/files/batch" => FILES_EDIT,
+        "/logs" | "/logs/tail" | "/logs/download" => CONSOLE_VIEW,
+        "/logs/compress" => FILES_EDIT,
+        // Holds the server password, as config.json does
+        "/game-config" if *method == Method::GET => FILES_EDIT,
+        // Other users' grants, and the full server bundle
+        "/permissions" | "/export" => MANAGE,
+        _ if *method == Method::GET => VIEW,
+        _ => MANAGE,
+    }
+}
+
+/// Server behind the `:id` of a matched route and the permission the route needs, `None` when
+/// the route isn't server-scoped
+async fn requirement(pool: &DbPool, method: &Method, route: &str, id: &str) -> Result<Option<(String, &'static str)>, AppError> {
+    // Asking for break-glass access is how a user reaches a server it can't manage
+    if route.ends_with("/servers/:id/elevation") {
+        return Ok(None);
+    }
+    if let Some((_, tail)) = route.split_once("/servers/:id") {
+        return Ok(Some((id.to_string(), server_route_permission(method, tail))));
+    }
+    if route.ends_with("/ws/console/:id") {
+        return Ok(Some((id.to_string(), CONSOLE_VIEW)));
+    }
+
+    let table = if route.contains("/backups/jobs/:id") {
+        "backup_jobs"
+    } else if route.contains("/backups/:id") {
+        "backups"
+    } else {
+        return Ok(None);
+    };
+    // Listing the entries reads the archive itself
+    let permission = if *method == Method::GET && !route.ends_with("/entries") { BACKUPS_VIEW } else { BACKUPS_MANAGE };
+    let server_id: Option<String> = sqlx::query_scalar(&format!("SELECT server_id FROM {} WHERE id = ?", table))
+        .bind(id)
+        .fetch_optional(pool)
+        .await?;
+    Ok(server_id.map(|server_id| (server_id, permission)))
+}
+
+/// Middleware rejecting requests the authenticated user isn't allowed to make on a server.
+/// Runs after `auth::require_auth`, which leaves the caller in the request extensions.
+pub async fn require_server_access(State(state): State<AppState>, req: Request, next: Next) -> Response {
+    let (mut parts, body) = req.into_parts();
+    let route = parts.extensions.get::<MatchedPath>().map(|p| p.as_str().to_string());
+    let id = RawPathParams::from_request_parts(&mut parts, &state)
+        .await
+        .ok()
+        .and_then(|params| params.iter().find(|(key, _)| *key == "id").map(|(_, value)| value.to_string()));
+    let auth = parts.extensions.get::<AuthUser>().cloned();
+    let method = parts.method.clone();
+    let req = Request::from_parts(parts, body);
+
+    let (Some(route), Some(id), Some(auth)) = (route, id, auth) else {
+        return next.run(req).await;
+    };
+    let checked = match requirement(&state.pool, &method, &route, &id).await {
+        Ok(Some((server_id, permission))) => server_access::require_permission(&state.pool, &auth, &server_id, permission).await,
+        Ok(None) => Ok(()),
+        Err(e) => Err(e),
+    };
+    match checked {
+        Ok(()) => next.run(req).await,
+        Err(e) => e.into_response(),
+    }
+}
+
+/// Middleware answering 403 to viewers on any method that could change something, except their
+/// own API tokens (read-only ones are the only useful kind for them). Runs after
+/// `auth::require_auth` and before `require_server_access`.
+pub async fn block_read_only_writes(req: Request, next: Next) -> Response {
+    let is_viewer = req.extensions().get::<AuthUser>().is_some_and(|auth| auth.role == ROLE_VIEWER);
+    let is_read = matches!(*req.method(), Method::GET | Method::HEAD | Method::OPTIONS);
+    let own_tokens = req.extensions().get::<MatchedPath>().is_some_and(|p| p.as_str().contains("/tokens"));
+    if is_viewer && !is_read && !own_tokens {
+        return AppError::Forbidden("auth.read_only".into()).into_response();
+    }
+    next.run(req).await
+}
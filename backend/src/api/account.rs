@@ -0,0 +1,163 @@
+//! Self-service account endpoints under `/auth/me`: a user downloads everything the panel keeps
+//! about them, or deletes their own account. The last admin can't delete theirs, nobody would
+//! be left to manage the panel.
+
+use axum::{
+    extract::State,
+    http::header,
+    response::{IntoResponse, Response},
+    routing::{delete, get},
+    Json, Router,
+};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use utoipa::{OpenApi, ToSchema};
+
+use crate::api::auth::AuthUser;
+use crate::error::AppError;
+use crate::services::{audit, sessions};
+use crate::AppState;
+
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/me/export", get(export_account))
+        .route("/me", delete(delete_account))
+}
+
+#[derive(OpenApi)]
+#[openapi(paths(export_account, delete_account))]
+pub(super) struct ApiDoc;
+
+#[derive(Debug, Serialize, FromRow)]
+struct Profile {
+    id: String,
+    username: String,
+    role: String,
+    is_active: bool,
+    language: String,
+    accent_color: String,
+    last_login: Option<String>,
+    last_ip: Option<String>,
+    allocated_servers: Option<String>,
+    totp_enabled: bool,
+    sso_linked: bool,
+    created_at: String,
+    updated_at: String,
+}
+
+#[derive(Debug, Serialize, FromRow)]
+struct ExportedToken {
+    id: String,
+    name: String,
+    scopes: String,
+    created_at: String,
+    last_used_at: Option<String>,
+    revoked_at: Option<String>,
+}
+
+#[derive(Debug, Serialize, FromRow)]
+struct ExportedPermission {
+    server_id: String,
+    permission: String,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct DeleteAccountRequest {
+    /// The username typed again, against deleting by accident
+    pub confirm: String,
+}
+
+/// Data changes to the account are made from a login session, never with an API token
+fn require_session(auth: &AuthUser) -> Result<(), AppError> {
+    if auth.api_token_id.is_some() {
+        return Err(AppError::Unauthorized("tokens.session_required".into()));
+    }
+    Ok(())
+}
+
+/// JSON file of the profile, sessions, API tokens, server permissions and audit entries of the
+/// caller. Secrets (password hash, second factor, token hashes) are left out.
+#[utoipa::path(get, path = "/auth/me/export", tag = "auth", responses((status = 200, body = serde_json::Value, description = "JSON attachment")))]
+async fn export_account(State(state): State<AppState>, auth: AuthUser) -> Result<Response, AppError> {
+    require_session(&auth)?;
+    let profile: Profile = sqlx::query_as(
+        r#"SELECT id, username, role,
+           COALESCE(is_active, 1) as is_active,
+           COALESCE(language, 'fr') as language,
+           COALESCE(accent_color, '#3A82F6') as accent_color,
+           last_login, last_ip, allocated_servers, totp_enabled,
+           oidc_subject IS NOT NULL as sso_linked,
+           created_at, updated_at
+           FROM users WHERE id = ?"#,
+    )
+    .bind(&auth.id)
+    .fetch_optional(&state.pool)
+    .await?
+    .ok_or_else(|| AppError::NotFound("users.not_found".into()))?;
+
+    let tokens: Vec<ExportedToken> = sqlx::query_as(
+        "SELECT id, name, scopes, created_at, last_used_at, revoked_at FROM api_tokens WHERE user_id = ? ORDER BY created_at"
+    )
+    .bind(&auth.id)
+    .fetch_all(&state.pool)
+    .await?;
+    let permissions: Vec<ExportedPermission> = sqlx::query_as(
+        "SELECT server_id, permission FROM server_permissions WHERE user_id = ? ORDER BY server_id, permission"
+    )
+    .bind(&auth.id)
+    .fetch_all(&state.pool)
+    .await?;
+
+    let export = serde_json::json!({
+        "exported_at": Utc::now().to_rfc3339(),
+        "profile": profile,
+        "sessions": sessions::active(&state.pool, &auth.id).await?,
+        "api_tokens": tokens,
+        "server_permissions": permissions,
+        "audit_log": audit::for_user(&state.pool, &auth.id, u32::MAX, None).await?,
+    });
+
+    let filename = format!("account-{}.json", auth.id);
+    Ok((
+        [(header::CONTENT_DISPOSITION, format!("attachment; filename=\"{}\"", filename))],
+        Json(export),
+    ).into_response())
+}
+
+/// Delete the caller's account along with its sessions, tokens and permissions. Audit entries
+/// stay, the trail of what happened on the panel outlives accounts.
+#[utoipa::path(
+    delete, path = "/auth/me", tag = "auth",
+    request_body = DeleteAccountRequest,
+    responses((status = 200, body = serde_json::Value), (status = 409, description = "`users.last_admin`"))
+)]
+async fn delete_account(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Json(body): Json<DeleteAccountRequest>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    require_session(&auth)?;
+    if body.confirm.trim() != auth.username {
+        return Err(AppError::BadRequest("users.delete_confirm_mismatch".into()));
+    }
+    if auth.is_admin() {
+        let admins: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM users WHERE role = 'admin' AND COALESCE(is_active, 1) = 1")
+            .fetch_one(&state.pool)
+            .await?;
+        if admins <= 1 {
+            return Err(AppError::Conflict("users.last_admin".into()));
+        }
+    }
+
+    // The rows go with the user, their ids must be revoked in the cache first
+    sessions::revoke_all(&state.pool, &auth.id).await?;
+    audit::record(&state.pool, Some(&auth.id), Some(&auth.username), audit::ACTION_ACCOUNT_DELETED, None, None).await;
+    sqlx::query("DELETE FROM users WHERE id = ?")
+        .bind(&auth.id)
+        .execute(&state.pool)
+        .await?;
+    tracing::info!("User {} deleted their account", auth.username);
+
+    Ok(Json(serde_json::json!({ "success": true })))
+}
@@ -1,36 +1,49 @@
 use axum::{
-    routing::{get, post, put},
-    extract::{State, FromRequestParts},
+    routing::{delete, get, post, put},
+    extract::{ConnectInfo, Path, Query, State, FromRequestParts, Request},
+    middleware::Next,
+    response::{IntoResponse, Response},
     Json, Router,
-    http::{StatusCode, HeaderMap, request::Parts},
+    http::{header, StatusCode, HeaderMap, request::Parts},
 };
+use std::net::SocketAddr;
 use axum::async_trait;
 use chrono::Utc;
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
 use uuid::Uuid;
+use utoipa::{IntoParams, OpenApi, ToSchema};
 
 use crate::{AppState, error::AppError};
+use crate::db::DbPool;
+use crate::services::{audit, login_throttle, mailer, password_reset, sessions, totp};
+use crate::services::sessions::Client;
+use crate::services::ip_bans::{self, Offense};
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct LoginRequest {
     pub username: String,
     pub password: String,
+    /// Authenticator code or backup code, for users with two-factor authentication
+    #[serde(default)]
+    pub totp_code: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct RegisterRequest {
     pub username: String,
     pub password: String,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct AuthResponse {
     pub token: String,
     pub user: UserInfo,
+    /// The session only reaches `/auth` until two-factor authentication is enabled
+    pub two_factor_setup_required: bool,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct UserInfo {
     pub id: String,
     pub username: String,
@@ -38,9 +51,11 @@ pub struct UserInfo {
     pub accent_color: Option<String>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct SetupStatus {
     pub needs_setup: bool,
+    /// Users can have a reset link emailed, SMTP is configured
+    pub email_reset: bool,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -50,6 +65,11 @@ pub struct Claims {
     pub role: String,
     pub accent_color: Option<String>,
     pub exp: i64,
+    #[serde(default)]
+    pub two_factor_setup_required: bool,
+    /// Session row of the token, absent from tokens issued before sessions were recorded
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sid: Option<String>,
 }
 
 pub fn routes() -> Router<AppState> {
@@ -59,9 +79,41 @@ pub fn routes() -> Router<AppState> {
         .route("/register", post(register))
         .route("/me", get(me))
         .route("/password", put(change_password))
+        .route("/password-reset", post(reset_password))
+        .route("/password-reset/request", post(request_password_reset))
+        .route("/2fa", get(two_factor_status))
+        .route("/2fa/setup", post(setup_two_factor))
+        .route("/2fa/enable", post(enable_two_factor))
+        .route("/2fa/disable", post(disable_two_factor))
+        .route("/2fa/backup-codes", post(regenerate_backup_codes))
+        .route("/sessions", get(list_sessions).delete(revoke_other_sessions))
+        .route("/sessions/:id", delete(revoke_session))
+        .nest("/oidc", super::oidc::routes())
+        .merge(super::account::routes())
 }
 
+#[derive(OpenApi)]
+#[openapi(paths(
+    check_setup_status,
+    login,
+    register,
+    me,
+    reset_password,
+    request_password_reset,
+    change_password,
+    two_factor_status,
+    setup_two_factor,
+    enable_two_factor,
+    disable_two_factor,
+    regenerate_backup_codes,
+    list_sessions,
+    revoke_session,
+    revoke_other_sessions,
+))]
+pub(super) struct ApiDoc;
+
 /// Check if first-time setup is needed (no users exist)
+#[utoipa::path(get, path = "/auth/status", tag = "auth", security(()), responses((status = 200, body = SetupStatus)))]
 async fn check_setup_status(State(state): State<AppState>) -> Result<Json<SetupStatus>, AppError> {
     let count: (i64,) = sqlx::query_as("SELECT COUNT(*) FROM users")
         .fetch_one(&state.pool)
@@ -69,18 +121,62 @@ async fn check_setup_status(State(state): State<AppState>) -> Result<Json<SetupS
 
     Ok(Json(SetupStatus {
         needs_setup: count.0 == 0,
+        email_reset: mailer::config(&state.pool).await.is_some(),
     }))
 }
 
+#[utoipa::path(
+    post, path = "/auth/login", tag = "auth", security(()),
+    request_body = LoginRequest,
+    responses(
+        (status = 200, body = AuthResponse),
+        (status = 401, description = "`auth.invalid_credentials`, `auth.totp_required` or `auth.invalid_totp`"),
+        (status = 429, description = "Too many failed attempts, retry after `Retry-After` seconds"),
+    )
+)]
 async fn login(
     State(state): State<AppState>,
+    connect_info: Option<ConnectInfo<SocketAddr>>,
+    headers: HeaderMap,
     Json(body): Json<LoginRequest>,
-) -> Result<Json<AuthResponse>, AppError> {
+) -> Result<Response, AppError> {
+    let client = Client::from_request(&headers, connect_info.as_ref());
+    let ip = connect_info.map(|ConnectInfo(addr)| addr.ip());
+    if let Some(wait) = login_throttle::retry_after(ip, &body.username) {
+        let secs = wait.as_secs().max(1);
+        let mut error = serde_json::to_value(AppError::TooManyRequests("auth.too_many_attempts".into()).body()).unwrap_or_default();
+        error["retry_after"] = secs.into();
+        let error = Json(error);
+        return Ok((StatusCode::TOO_MANY_REQUESTS, [(header::RETRY_AFTER, secs.to_string())], error).into_response());
+    }
+
+    match check_login(&state.pool, &body, &client).await {
+        Ok(response) => {
+            login_throttle::record_success(&body.username);
+            audit::record(&state.pool, Some(&response.user.id), Some(&response.user.username), audit::ACTION_LOGIN, None, client.ip.as_deref()).await;
+            Ok(Json(response).into_response())
+        }
+        Err(AppError::Unauthorized(key)) if key == "auth.invalid_credentials" || key == "auth.invalid_totp" => {
+            ip_bans::record_offense(&state.pool, ip, Offense::AuthFailure).await;
+            for lockout in login_throttle::record_failure(ip, &body.username) {
+                let detail = format!("{} locked for {}s", lockout.key, lockout.duration.as_secs());
+                tracing::warn!("Login lockout: {}", detail);
+                let ip = ip.map(|ip| ip.to_string());
+                audit::record(&state.pool, None, Some(&body.username), audit::ACTION_LOGIN_LOCKOUT, Some(&detail), ip.as_deref()).await;
+            }
+            Err(AppError::Unauthorized(key))
+        }
+        Err(e) => Err(e),
+    }
+}
+
+/// Credentials and second factor of a login attempt
+async fn check_login(pool: &DbPool, body: &LoginRequest, client: &Client) -> Result<AuthResponse, AppError> {
     let user: UserRow = sqlx::query_as(
         "SELECT id, username, password_hash, role, accent_color FROM users WHERE username = ?",
     )
     .bind(&body.username)
-    .fetch_optional(&state.pool)
+    .fetch_optional(pool)
     .await?
     .ok_or_else(|| AppError::Unauthorized("auth.invalid_credentials".into()))?;
 
@@ -89,30 +185,51 @@ async fn login(
     {
         return Err(AppError::Unauthorized("auth.invalid_credentials".into()));
     }
+    second_factor_login(pool, user, body.totp_code.as_deref(), client).await
+}
 
-    let token = create_token(&user)?;
+/// Session of a user whose first factor (password, identity provider) checked out. Users with
+/// two-factor authentication need an authenticator or backup code, admins without it get a
+/// setup-only session when `require_2fa_admin` is on.
+pub(crate) async fn second_factor_login(pool: &DbPool, user: UserRow, code: Option<&str>, client: &Client) -> Result<AuthResponse, AppError> {
+    let enabled = two_factor_enabled(pool, &user.id).await?;
+    if enabled {
+        let code = code.map(str::trim).filter(|c| !c.is_empty())
+            .ok_or_else(|| AppError::Unauthorized("auth.totp_required".into()))?;
+        check_second_factor(pool, &user.id, code).await?;
+    }
+    let setup_required = !enabled && two_factor_required(pool, &user.role).await;
 
-    Ok(Json(AuthResponse {
+    let token = open_session(pool, &user, setup_required, client).await?;
+
+    Ok(AuthResponse {
         token,
+        two_factor_setup_required: setup_required,
         user: UserInfo {
             id: user.id,
             username: user.username,
             role: user.role,
             accent_color: user.accent_color,
         },
-    }))
+    })
 }
 
+/// Create the first account, the admin. Closed once any user exists.
+#[utoipa::path(post, path = "/auth/register", tag = "auth", security(()), request_body = RegisterRequest, responses((status = 201, body = AuthResponse), (status = 403, description = "`auth.registration_closed`")))]
 async fn register(
     State(state): State<AppState>,
+    connect_info: Option<ConnectInfo<SocketAddr>>,
+    headers: HeaderMap,
     Json(body): Json<RegisterRequest>,
 ) -> Result<(StatusCode, Json<AuthResponse>), AppError> {
-    // Check if any users exist (first user becomes admin)
-    let count: (i64,) = sqlx::query_as("SELECT COUNT(*) FROM users")
+    // Only the first user registers, as the admin: later accounts are created by an admin
+    let has_users: bool = sqlx::query_scalar("SELECT EXISTS(SELECT 1 FROM users)")
         .fetch_one(&state.pool)
         .await?;
-
-    let role = if count.0 == 0 { "admin" } else { "user" };
+    if has_users {
+        return Err(AppError::Forbidden("auth.registration_closed".into()));
+    }
+    let role = "admin";
 
     // Get default accent color from settings
     let default_color: Option<(String,)> = sqlx::query_as(
@@ -128,8 +245,10 @@ async fn register(
     let id = Uuid::new_v4().to_string();
     let now = Utc::now().to_rfc3339();
 
-    sqlx::query(
-        "INSERT INTO users (id, username, password_hash, role, accent_color, created_at, updated_at) VALUES (?, ?, ?, ?, ?, ?, ?)",
+    // Checked in the insert itself, so two racing registrations can't both become admin
+    let inserted = sqlx::query(
+        "INSERT INTO users (id, username, password_hash, role, accent_color, created_at, updated_at)
+         SELECT ?, ?, ?, ?, ?, ?, ? WHERE NOT EXISTS (SELECT 1 FROM users)",
     )
     .bind(&id)
     .bind(&body.username)
@@ -140,6 +259,9 @@ async fn register(
     .bind(&now)
     .execute(&state.pool)
     .await?;
+    if inserted.rows_affected() == 0 {
+        return Err(AppError::Forbidden("auth.registration_closed".into()));
+    }
 
     let user = UserRow {
         id: id.clone(),
@@ -149,10 +271,12 @@ async fn register(
         accent_color: Some(accent_color.clone()),
     };
 
-    let token = create_token(&user)?;
+    let client = Client::from_request(&headers, connect_info.as_ref());
+    let token = open_session(&state.pool, &user, false, &client).await?;
 
     Ok((StatusCode::CREATED, Json(AuthResponse {
         token,
+        two_factor_setup_required: false,
         user: UserInfo {
             id: user.id,
             username: user.username,
@@ -162,6 +286,7 @@ async fn register(
     })))
 }
 
+#[utoipa::path(get, path = "/auth/me", tag = "auth", responses((status = 200, body = UserInfo)))]
 async fn me(auth: AuthUser) -> Result<Json<UserInfo>, AppError> {
     Ok(Json(UserInfo {
         id: auth.id,
@@ -171,11 +296,41 @@ async fn me(auth: AuthUser) -> Result<Json<UserInfo>, AppError> {
     }))
 }
 
+#[derive(Clone)]
 pub struct AuthUser {
     pub id: String,
     pub username: String,
     pub role: String,
     pub accent_color: Option<String>,
+    /// Set when the request authenticated with an API token instead of a session
+    pub api_token_id: Option<String>,
+    /// Admin session opened while two-factor authentication is required but not enabled
+    pub two_factor_setup_required: bool,
+    /// Login session of the JWT, see `services::sessions`
+    pub session_id: Option<String>,
+}
+
+impl AuthUser {
+    pub fn is_admin(&self) -> bool {
+        self.role == "admin"
+    }
+
+    /// Reject anyone but an admin: authenticated, so 403 rather than 401
+    pub fn require_admin(&self) -> Result<(), AppError> {
+        if self.is_admin() {
+            Ok(())
+        } else {
+            Err(AppError::Forbidden("auth.admin_required".into()))
+        }
+    }
+
+    /// `require_admin` for actions taken in the panel only, not with an API token
+    pub fn require_admin_session(&self) -> Result<(), AppError> {
+        if self.api_token_id.is_some() {
+            return Err(AppError::Unauthorized("tokens.session_required".into()));
+        }
+        self.require_admin()
+    }
 }
 
 #[async_trait]
@@ -186,6 +341,11 @@ where
     type Rejection = AppError;
 
     async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        // API tokens are resolved by the `tokens::authenticate` middleware
+        if let Some(user) = parts.extensions.get::<AuthUser>() {
+            return Ok(user.clone());
+        }
+
         let auth_header = parts.headers
             .get("Authorization")
             .and_then(|h| h.to_str().ok())
@@ -195,60 +355,241 @@ where
             .strip_prefix("Bearer ")
             .ok_or_else(|| AppError::Unauthorized("auth.invalid_auth_header".into()))?;
 
-        let secret = std::env::var("JWT_SECRET").unwrap_or_else(|_| "change-me-in-production".into());
-        
-        let token_data = jsonwebtoken::decode::<Claims>(
-            token,
-            &jsonwebtoken::DecodingKey::from_secret(secret.as_bytes()),
-            &jsonwebtoken::Validation::default(),
-        )
-        .map_err(|_| AppError::Unauthorized("auth.invalid_token".into()))?;
-
-        Ok(AuthUser {
-            id: token_data.claims.sub,
-            username: token_data.claims.username,
-            role: token_data.claims.role,
-            accent_color: token_data.claims.accent_color,
-        })
+        session_user(token)
+    }
+}
+
+/// User behind a session JWT
+fn session_user(token: &str) -> Result<AuthUser, AppError> {
+    let claims = decode_session(token)?;
+    if claims.sid.as_deref().is_some_and(sessions::is_revoked) {
+        return Err(AppError::Unauthorized("auth.session_revoked".into()));
+    }
+
+    Ok(AuthUser {
+        id: claims.sub,
+        username: claims.username,
+        role: claims.role,
+        accent_color: claims.accent_color,
+        api_token_id: None,
+        two_factor_setup_required: claims.two_factor_setup_required,
+        session_id: claims.sid,
+    })
+}
+
+/// Middleware rejecting requests without a valid session or API token. Browsers can't set
+/// headers on a WebSocket or an EventSource, so the session token may also come as `?token=`
+/// on an upgrade or an event stream.
+pub async fn require_auth(State(state): State<AppState>, mut req: Request, next: Next) -> Response {
+    // Already resolved by `tokens::authenticate`
+    if req.extensions().get::<AuthUser>().is_some() {
+        return next.run(req).await;
+    }
+
+    let is_upgrade = req.headers()
+        .get("Upgrade")
+        .and_then(|h| h.to_str().ok())
+        .is_some_and(|h| h.eq_ignore_ascii_case("websocket"));
+    let is_event_stream = req.headers()
+        .get("Accept")
+        .and_then(|h| h.to_str().ok())
+        .is_some_and(|h| h.contains("text/event-stream"));
+    let query_token = (is_upgrade || is_event_stream)
+        .then(|| req.uri().query())
+        .flatten()
+        .and_then(|q| q.split('&').find_map(|pair| pair.strip_prefix("token=")))
+        .map(str::to_string);
+
+    let user = match query_token {
+        Some(token) => session_user(&token),
+        None => {
+            let (mut parts, body) = req.into_parts();
+            let user = AuthUser::from_request_parts(&mut parts, &()).await;
+            req = Request::from_parts(parts, body);
+            user
+        }
+    };
+    match user {
+        Ok(user) if user.two_factor_setup_required => {
+            AppError::Unauthorized("auth.two_factor_setup_required".into()).into_response()
+        }
+        Ok(user) => {
+            if let Some(ref sid) = user.session_id {
+                sessions::touch(&state.pool, sid).await;
+            }
+            req.extensions_mut().insert(user);
+            next.run(req).await
+        }
+        Err(e) => e.into_response(),
     }
 }
 
 
 #[derive(Debug, FromRow)]
-struct UserRow {
-    id: String,
-    username: String,
-    password_hash: String,
-    role: String,
-    accent_color: Option<String>,
+pub(crate) struct UserRow {
+    pub id: String,
+    pub username: String,
+    pub password_hash: String,
+    pub role: String,
+    pub accent_color: Option<String>,
 }
 
-fn create_token(user: &UserRow) -> Result<String, AppError> {
-    let secret = std::env::var("JWT_SECRET").unwrap_or_else(|_| "change-me-in-production".into());
+/// Secret of installs that never set `JWT_SECRET`, refused outside dev mode
+pub const DEFAULT_JWT_SECRET: &str = "change-me-in-production";
+/// Session lifetime unless `JWT_TTL_HOURS` says otherwise
+const DEFAULT_JWT_TTL_HOURS: i64 = 24 * 7;
 
+fn jwt_secret() -> String {
+    std::env::var("JWT_SECRET").ok().filter(|s| !s.is_empty()).unwrap_or_else(|| DEFAULT_JWT_SECRET.into())
+}
+
+/// Secret being rotated out: sessions it signed stay valid until they expire
+fn previous_jwt_secret() -> Option<String> {
+    std::env::var("JWT_PREVIOUS_SECRET").ok().filter(|s| !s.is_empty())
+}
+
+fn jwt_ttl() -> chrono::Duration {
+    let hours = std::env::var("JWT_TTL_HOURS")
+        .ok()
+        .and_then(|v| v.parse::<i64>().ok())
+        .filter(|h| *h > 0)
+        .unwrap_or(DEFAULT_JWT_TTL_HOURS);
+    chrono::Duration::hours(hours)
+}
+
+/// Startup check: a production panel signing sessions with the well-known default secret would
+/// let anyone forge an admin session
+pub fn check_jwt_secret(dev_mode: bool) -> anyhow::Result<()> {
+    if jwt_secret() == DEFAULT_JWT_SECRET {
+        if !dev_mode {
+            anyhow::bail!("JWT_SECRET is not set: generate a random secret (e.g. `openssl rand -hex 32`) or set DEV_MODE=1");
+        }
+        tracing::warn!("⚠️ JWT_SECRET is not set, sessions are signed with the default secret (dev mode only)");
+    }
+    Ok(())
+}
+
+/// Claims of a session JWT signed with the current secret, or the previous one while it rotates out
+pub(crate) fn decode_session(token: &str) -> Result<Claims, AppError> {
+    let validation = jsonwebtoken::Validation::default();
+    let decode = |secret: &str| {
+        jsonwebtoken::decode::<Claims>(token, &jsonwebtoken::DecodingKey::from_secret(secret.as_bytes()), &validation)
+    };
+
+    decode(&jwt_secret())
+        .or_else(|e| match (e.kind(), previous_jwt_secret()) {
+            (jsonwebtoken::errors::ErrorKind::InvalidSignature, Some(previous)) => decode(&previous),
+            _ => Err(e),
+        })
+        .map(|data| data.claims)
+        .map_err(|_| AppError::Unauthorized("auth.invalid_token".into()))
+}
+
+/// Record a login session for `user` and sign its JWT
+pub(crate) async fn open_session(pool: &DbPool, user: &UserRow, two_factor_setup_required: bool, client: &Client) -> Result<String, AppError> {
+    let expires_at = Utc::now() + jwt_ttl();
+    let sid = sessions::open(pool, &user.id, client, expires_at).await?;
     let claims = Claims {
         sub: user.id.clone(),
         username: user.username.clone(),
         role: user.role.clone(),
         accent_color: user.accent_color.clone(),
-        exp: (Utc::now() + chrono::Duration::days(7)).timestamp(),
+        exp: expires_at.timestamp(),
+        two_factor_setup_required,
+        sid: Some(sid),
     };
 
     jsonwebtoken::encode(
         &jsonwebtoken::Header::default(),
         &claims,
-        &jsonwebtoken::EncodingKey::from_secret(secret.as_bytes()),
+        &jsonwebtoken::EncodingKey::from_secret(jwt_secret().as_bytes()),
     )
     .map_err(|e| AppError::Internal(e.to_string()))
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct ChangePasswordRequest {
     #[allow(dead_code)]
     pub current_password: Option<String>,
     pub new_password: String,
 }
 
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct ResetPasswordRequest {
+    pub token: String,
+    pub new_password: String,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct RequestPasswordResetRequest {
+    /// Username or email address
+    pub login: String,
+}
+
+/// Forgotten password: email a reset link to the account's address. The answer doesn't tell
+/// whether such an account exists, the email goes out in the background.
+#[utoipa::path(
+    post, path = "/auth/password-reset/request", tag = "auth", security(()),
+    request_body = RequestPasswordResetRequest,
+    responses((status = 200, body = serde_json::Value), (status = 404, description = "`auth.reset_email_disabled`, SMTP isn't configured"))
+)]
+async fn request_password_reset(
+    State(state): State<AppState>,
+    connect_info: Option<ConnectInfo<SocketAddr>>,
+    Json(body): Json<RequestPasswordResetRequest>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let config = mailer::config(&state.pool)
+        .await
+        .ok_or_else(|| AppError::NotFound("auth.reset_email_disabled".into()))?;
+    let login = body.login.trim().to_string();
+    let ip = connect_info.map(|ConnectInfo(addr)| addr.ip().to_string());
+
+    if !login.is_empty() {
+        tokio::spawn(async move {
+            match password_reset::email_link(&state.pool, &config, &login).await {
+                Ok(Some((user_id, username))) => {
+                    audit::record(&state.pool, Some(&user_id), Some(&username), audit::ACTION_PASSWORD_RESET_ISSUED, Some("by email"), ip.as_deref()).await;
+                }
+                Ok(None) => {}
+                Err(e) => tracing::warn!("Password reset email for {} failed: {:#}", login, e),
+            }
+        });
+    }
+
+    Ok(Json(serde_json::json!({
+        "success": true,
+        "message": "auth.reset_email_sent"
+    })))
+}
+
+/// Public end of a reset link: set a new password with the one-time token
+#[utoipa::path(post, path = "/auth/password-reset", tag = "auth", security(()), request_body = ResetPasswordRequest, responses((status = 200, body = serde_json::Value)))]
+async fn reset_password(
+    State(state): State<AppState>,
+    connect_info: Option<ConnectInfo<SocketAddr>>,
+    Json(body): Json<ResetPasswordRequest>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    if body.new_password.len() < 8 {
+        return Err(AppError::BadRequest("auth.password_length".into()));
+    }
+    let new_hash = bcrypt::hash(&body.new_password, bcrypt::DEFAULT_COST)
+        .map_err(|_| AppError::Internal("Password hashing failed".into()))?;
+
+    let (user_id, username) = password_reset::redeem(&state.pool, &body.token, &new_hash)
+        .await?
+        .ok_or_else(|| AppError::BadRequest("auth.invalid_reset_token".into()))?;
+
+    login_throttle::record_success(&username);
+    let ip = connect_info.map(|ConnectInfo(addr)| addr.ip().to_string());
+    audit::record(&state.pool, Some(&user_id), Some(&username), audit::ACTION_PASSWORD_RESET, None, ip.as_deref()).await;
+
+    Ok(Json(serde_json::json!({
+        "success": true,
+        "message": "auth.password_updated"
+    })))
+}
+
+#[utoipa::path(put, path = "/auth/password", tag = "auth", request_body = ChangePasswordRequest, responses((status = 200, body = serde_json::Value)))]
 async fn change_password(
     State(state): State<AppState>,
     headers: HeaderMap,
@@ -264,16 +605,7 @@ async fn change_password(
         .strip_prefix("Bearer ")
         .ok_or_else(|| AppError::Unauthorized("auth.invalid_auth_header".into()))?;
 
-    let secret = std::env::var("JWT_SECRET").unwrap_or_else(|_| "change-me-in-production".into());
-    
-    let token_data = jsonwebtoken::decode::<Claims>(
-        token,
-        &jsonwebtoken::DecodingKey::from_secret(secret.as_bytes()),
-        &jsonwebtoken::Validation::default(),
-    )
-    .map_err(|_| AppError::Unauthorized("auth.invalid_token".into()))?;
-
-    let user_id = token_data.claims.sub;
+    let user_id = decode_session(token)?.sub;
 
     // Validate new password length
     if body.new_password.len() < 8 {
@@ -303,3 +635,291 @@ async fn change_password(
         "message": "auth.password_updated"
     })))
 }
+
+// ============= Two-factor authentication =============
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct TwoFactorCodeRequest {
+    pub code: String,
+}
+
+pub(crate) async fn two_factor_enabled(pool: &DbPool, user_id: &str) -> Result<bool, sqlx::Error> {
+    sqlx::query_scalar("SELECT totp_enabled FROM users WHERE id = ?")
+        .bind(user_id)
+        .fetch_one(pool)
+        .await
+}
+
+/// Whether the `require_2fa_admin` setting makes two-factor authentication mandatory for `role`
+async fn two_factor_required(pool: &DbPool, role: &str) -> bool {
+    if role != "admin" {
+        return false;
+    }
+    let value: Option<String> = sqlx::query_scalar("SELECT value FROM settings WHERE key = 'require_2fa_admin'")
+        .fetch_optional(pool)
+        .await
+        .ok()
+        .flatten();
+    value.is_some_and(|v| v == "true")
+}
+
+/// Two-factor settings are changed from a login session, never with an API token
+fn require_session(auth: &AuthUser) -> Result<(), AppError> {
+    if auth.api_token_id.is_some() {
+        return Err(AppError::Unauthorized("tokens.session_required".into()));
+    }
+    Ok(())
+}
+
+#[derive(Debug, FromRow)]
+struct TwoFactorRow {
+    totp_enabled: bool,
+    totp_secret: Option<String>,
+    totp_backup_codes: Option<String>,
+    totp_last_step: Option<i64>,
+}
+
+async fn two_factor_row(pool: &DbPool, user_id: &str) -> Result<TwoFactorRow, AppError> {
+    sqlx::query_as("SELECT totp_enabled, totp_secret, totp_backup_codes, totp_last_step FROM users WHERE id = ?")
+        .bind(user_id)
+        .fetch_optional(pool)
+        .await?
+        .ok_or_else(|| AppError::NotFound("auth.user_not_found".into()))
+}
+
+fn backup_code_hashes(row: &TwoFactorRow) -> Vec<String> {
+    row.totp_backup_codes.as_deref().and_then(|c| serde_json::from_str(c).ok()).unwrap_or_default()
+}
+
+/// Accept an authenticator code, or use up a backup code
+async fn check_second_factor(pool: &DbPool, user_id: &str, code: &str) -> Result<(), AppError> {
+    let row = two_factor_row(pool, user_id).await?;
+    let secret = row.totp_secret.as_deref().unwrap_or_default();
+
+    if let Some(step) = totp::verify(secret, code, Utc::now().timestamp(), row.totp_last_step) {
+        sqlx::query("UPDATE users SET totp_last_step = ? WHERE id = ?")
+            .bind(step)
+            .bind(user_id)
+            .execute(pool)
+            .await?;
+        return Ok(());
+    }
+
+    let mut hashes = backup_code_hashes(&row);
+    let hash = totp::hash_backup_code(code);
+    let Some(index) = hashes.iter().position(|h| *h == hash) else {
+        return Err(AppError::Unauthorized("auth.invalid_totp".into()));
+    };
+    hashes.remove(index);
+    sqlx::query("UPDATE users SET totp_backup_codes = ? WHERE id = ?")
+        .bind(serde_json::to_string(&hashes).unwrap_or_else(|_| "[]".to_string()))
+        .bind(user_id)
+        .execute(pool)
+        .await?;
+    tracing::warn!("User {} logged in with a backup code, {} left", user_id, hashes.len());
+    Ok(())
+}
+
+/// Store fresh backup codes, returned in clear this once
+async fn store_backup_codes(pool: &DbPool, user_id: &str) -> Result<Vec<String>, AppError> {
+    let codes = totp::generate_backup_codes();
+    let hashes: Vec<String> = codes.iter().map(|c| totp::hash_backup_code(c)).collect();
+    sqlx::query("UPDATE users SET totp_backup_codes = ? WHERE id = ?")
+        .bind(serde_json::to_string(&hashes).unwrap_or_else(|_| "[]".to_string()))
+        .bind(user_id)
+        .execute(pool)
+        .await?;
+    Ok(codes)
+}
+
+#[utoipa::path(get, path = "/auth/2fa", tag = "auth", responses((status = 200, body = serde_json::Value)))]
+async fn two_factor_status(
+    State(state): State<AppState>,
+    auth: AuthUser,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let row = two_factor_row(&state.pool, &auth.id).await?;
+    Ok(Json(serde_json::json!({
+        "enabled": row.totp_enabled,
+        "required": two_factor_required(&state.pool, &auth.role).await,
+        "backup_codes_left": backup_code_hashes(&row).len(),
+    })))
+}
+
+/// Generate a secret to scan; it only protects the account once confirmed on `/2fa/enable`
+#[utoipa::path(post, path = "/auth/2fa/setup", tag = "auth", responses((status = 200, body = serde_json::Value, description = "Secret and `otpauth://` URL to enroll")))]
+async fn setup_two_factor(
+    State(state): State<AppState>,
+    auth: AuthUser,
+) -> Result<Json<serde_json::Value>, AppError> {
+    require_session(&auth)?;
+    if two_factor_row(&state.pool, &auth.id).await?.totp_enabled {
+        return Err(AppError::BadRequest("auth.totp_already_enabled".into()));
+    }
+
+    let secret = totp::generate_secret();
+    sqlx::query("UPDATE users SET totp_secret = ?, totp_last_step = NULL WHERE id = ?")
+        .bind(&secret)
+        .bind(&auth.id)
+        .execute(&state.pool)
+        .await?;
+
+    Ok(Json(serde_json::json!({
+        "secret": secret,
+        // Payload of the QR code to show
+        "otpauth_uri": totp::provisioning_uri(&auth.username, &secret),
+    })))
+}
+
+/// Confirm the scanned secret with a first code. The backup codes and a session no longer
+/// waiting for enrollment are returned.
+#[utoipa::path(post, path = "/auth/2fa/enable", tag = "auth", request_body = TwoFactorCodeRequest, responses((status = 200, body = serde_json::Value, description = "Backup codes and a new session token")))]
+async fn enable_two_factor(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    connect_info: Option<ConnectInfo<SocketAddr>>,
+    headers: HeaderMap,
+    Json(body): Json<TwoFactorCodeRequest>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    require_session(&auth)?;
+    let row = two_factor_row(&state.pool, &auth.id).await?;
+    if row.totp_enabled {
+        return Err(AppError::BadRequest("auth.totp_already_enabled".into()));
+    }
+    let secret = row.totp_secret.ok_or_else(|| AppError::BadRequest("auth.totp_not_set_up".into()))?;
+    let step = totp::verify(&secret, &body.code, Utc::now().timestamp(), None)
+        .ok_or_else(|| AppError::BadRequest("auth.invalid_totp".into()))?;
+
+    sqlx::query("UPDATE users SET totp_enabled = 1, totp_last_step = ?, updated_at = ? WHERE id = ?")
+        .bind(step)
+        .bind(Utc::now().to_rfc3339())
+        .bind(&auth.id)
+        .execute(&state.pool)
+        .await?;
+    let backup_codes = store_backup_codes(&state.pool, &auth.id).await?;
+
+    let user: UserRow = sqlx::query_as("SELECT id, username, password_hash, role, accent_color FROM users WHERE id = ?")
+        .bind(&auth.id)
+        .fetch_one(&state.pool)
+        .await?;
+    tracing::info!("Two-factor authentication enabled for {}", user.username);
+
+    // The new session replaces the one still waiting for enrollment
+    let token = open_session(&state.pool, &user, false, &Client::from_request(&headers, connect_info.as_ref())).await?;
+    if let Some(sid) = auth.session_id {
+        sessions::revoke(&state.pool, &[sid]).await?;
+    }
+
+    Ok(Json(serde_json::json!({
+        "success": true,
+        "backup_codes": backup_codes,
+        "token": token,
+    })))
+}
+
+#[utoipa::path(post, path = "/auth/2fa/disable", tag = "auth", request_body = TwoFactorCodeRequest, responses((status = 200, body = serde_json::Value)))]
+async fn disable_two_factor(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Json(body): Json<TwoFactorCodeRequest>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    require_session(&auth)?;
+    if !two_factor_row(&state.pool, &auth.id).await?.totp_enabled {
+        return Err(AppError::BadRequest("auth.totp_not_enabled".into()));
+    }
+    if two_factor_required(&state.pool, &auth.role).await {
+        return Err(AppError::BadRequest("auth.totp_required_for_role".into()));
+    }
+    check_second_factor(&state.pool, &auth.id, &body.code).await?;
+
+    sqlx::query(
+        "UPDATE users SET totp_enabled = 0, totp_secret = NULL, totp_backup_codes = NULL, totp_last_step = NULL, updated_at = ? WHERE id = ?"
+    )
+    .bind(Utc::now().to_rfc3339())
+    .bind(&auth.id)
+    .execute(&state.pool)
+    .await?;
+    tracing::info!("Two-factor authentication disabled for {}", auth.username);
+
+    Ok(Json(serde_json::json!({ "success": true })))
+}
+
+/// Replace the backup codes, e.g. once most are used
+#[utoipa::path(post, path = "/auth/2fa/backup-codes", tag = "auth", request_body = TwoFactorCodeRequest, responses((status = 200, body = serde_json::Value)))]
+async fn regenerate_backup_codes(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Json(body): Json<TwoFactorCodeRequest>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    require_session(&auth)?;
+    if !two_factor_row(&state.pool, &auth.id).await?.totp_enabled {
+        return Err(AppError::BadRequest("auth.totp_not_enabled".into()));
+    }
+    check_second_factor(&state.pool, &auth.id, &body.code).await?;
+    let backup_codes = store_backup_codes(&state.pool, &auth.id).await?;
+
+    Ok(Json(serde_json::json!({
+        "success": true,
+        "backup_codes": backup_codes,
+    })))
+}
+
+// ============= Sessions =============
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct SessionsQuery {
+    /// Admins may list another user's sessions
+    pub user_id: Option<String>,
+}
+
+fn session_json(session: sessions::Session, current: Option<&str>) -> serde_json::Value {
+    let is_current = current == Some(session.id.as_str());
+    let mut value = serde_json::to_value(session).unwrap_or_default();
+    value["current"] = serde_json::Value::Bool(is_current);
+    value
+}
+
+#[utoipa::path(get, path = "/auth/sessions", tag = "auth", params(SessionsQuery), responses((status = 200, body = [sessions::Session], description = "Each with a `current` flag")))]
+async fn list_sessions(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Query(query): Query<SessionsQuery>,
+) -> Result<Json<Vec<serde_json::Value>>, AppError> {
+    let user_id = query.user_id.unwrap_or_else(|| auth.id.clone());
+    if user_id != auth.id {
+        auth.require_admin()?;
+    }
+    let active = sessions::active(&state.pool, &user_id).await?;
+    Ok(Json(active.into_iter().map(|s| session_json(s, auth.session_id.as_deref())).collect()))
+}
+
+/// Log out one session of the caller, or of anyone for admins
+#[utoipa::path(delete, path = "/auth/sessions/{id}", tag = "auth", params(("id" = String, Path)), responses((status = 200, body = serde_json::Value), (status = 404)))]
+async fn revoke_session(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(id): Path<String>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let session = sessions::get(&state.pool, &id)
+        .await?
+        .filter(|s| s.user_id == auth.id || auth.is_admin())
+        .ok_or_else(|| AppError::NotFound("auth.session_not_found".into()))?;
+    sessions::revoke(&state.pool, &[session.id]).await?;
+    Ok(Json(serde_json::json!({ "success": true })))
+}
+
+/// Log out every other session of the caller, after a leaked token for instance
+#[utoipa::path(delete, path = "/auth/sessions", tag = "auth", responses((status = 200, body = serde_json::Value)))]
+async fn revoke_other_sessions(
+    State(state): State<AppState>,
+    auth: AuthUser,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let others: Vec<String> = sessions::active(&state.pool, &auth.id)
+        .await?
+        .into_iter()
+        .map(|s| s.id)
+        .filter(|id| Some(id.as_str()) != auth.session_id.as_deref())
+        .collect();
+    sessions::revoke(&state.pool, &others).await?;
+    Ok(Json(serde_json::json!({ "success": true, "revoked": others.len() })))
+}
@@ -1,43 +1,170 @@
 use axum::{
+    body::{Body, Bytes},
     routing::{get, post},
-    extract::{Path, Query, State},
+    extract::{DefaultBodyLimit, Multipart, Path, Query, State},
     Json, Router,
     http::StatusCode,
 };
+use futures::{Stream, StreamExt};
+use tokio::io::AsyncWriteExt;
 use chrono::Utc;
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
 use uuid::Uuid;
+use utoipa::{IntoParams, OpenApi, ToSchema};
 
 use crate::AppState;
+use crate::api::auth::AuthUser;
+use crate::db::DbPool;
 use crate::error::AppError;
+use crate::services::backup_crypto::{self, EncryptionKey};
+use crate::services::backup_quota;
+use crate::services::backup_service::{self, Codec, Progress};
+use crate::services::backup_transfer;
+use crate::services::remote_storage::{self, LOCATION_LOCAL};
+use crate::services::server_access;
+use crate::services::world_flush::{self, FlushCommands};
+use crate::services::events::{EventBus, PanelEvent};
+use crate::services::{operation_lock, snapshots, ProcessManager};
 
 pub fn routes() -> Router<AppState> {
     Router::new()
         .route("/", get(list_backups).post(create_backup))
+        .route("/bulk", post(bulk_create_backups).delete(bulk_delete_backups))
+        .route("/jobs/:id", get(get_backup_job))
+        .route("/import", post(import_backup_multipart).put(import_backup_stream).layer(DefaultBodyLimit::disable()))
         .route("/:id", get(get_backup).delete(delete_backup))
         .route("/:id/restore", post(restore_backup))
+        .route("/:id/verify", post(verify_backup))
+        .route("/:id/entries", get(list_backup_entries))
 }
 
-#[derive(Debug, Serialize)]
+#[derive(OpenApi)]
+#[openapi(paths(
+    list_backups,
+    create_backup,
+    get_backup_job,
+    bulk_create_backups,
+    bulk_delete_backups,
+    import_backup_multipart,
+    import_backup_stream,
+    get_backup,
+    delete_backup,
+    restore_backup,
+    verify_backup,
+    list_backup_entries,
+))]
+pub(super) struct ApiDoc;
+
+#[derive(Debug, Serialize, ToSchema)]
 pub struct BackupResponse {
     pub id: String,
     pub server_id: String,
     pub filename: String,
     pub size_bytes: i64,
     pub created_at: String,
+    /// "manual" or "snapshot" (taken automatically before a risky operation)
+    pub kind: String,
+    /// Operation a snapshot was taken before
+    pub label: Option<String>,
+    /// Archive format: "gzip", "zstd" or "none"
+    pub compression: String,
+    /// "local", "remote" (offsite only, downloaded on restore) or "both"
+    pub location: String,
+    /// Hex SHA-256 of the archive, recorded when it was written
+    pub sha256: Option<String>,
+    /// Result of the last `POST /backups/:id/verify`: "ok" or "corrupted"
+    pub integrity: Option<String>,
+    pub verified_at: Option<String>,
+    /// Sealed with AES-256-GCM, restoring it takes the key
+    pub encrypted: bool,
+    /// Older backups deleted to honour the server's `backup_max_backups`, on creation only
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pruned: Option<PruneReport>,
+}
+
+#[derive(Debug, Default, Serialize, ToSchema)]
+pub struct PruneReport {
+    pub deleted: usize,
+    pub reclaimed_bytes: i64,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct CreateBackupRequest {
     pub server_id: String,
+    /// Encrypts this backup with the given key (64 hex characters) instead of the configured one
+    pub encryption_key: Option<String>,
+}
+
+/// Optional body of verifications, for backups encrypted with a key of their own
+#[derive(Debug, Default, Deserialize, ToSchema)]
+pub struct BackupKeyRequest {
+    pub encryption_key: Option<String>,
+}
+
+/// Optional body of restores
+#[derive(Debug, Default, Deserialize, ToSchema)]
+pub struct RestoreBackupRequest {
+    pub encryption_key: Option<String>,
+    /// Files or directories to bring back, e.g. `universe` or `config.json`; the whole archive
+    /// when empty
+    #[serde(default)]
+    pub paths: Vec<String>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
 struct ListBackupsQuery {
     server_id: Option<String>,
+    label: Option<String>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct BulkBackupRequest {
+    /// Servers to back up, ignored when `all` is set
+    #[serde(default)]
+    pub server_ids: Vec<String>,
+    #[serde(default)]
+    pub all: bool,
+    /// Label put on every backup of the batch, generated when missing
+    pub label: Option<String>,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+struct BulkDeleteQuery {
+    server_id: Option<String>,
+    /// Only backups created more than this many days ago
+    older_than_days: Option<u32>,
+    /// Only backups without a label
+    #[serde(default)]
+    unlabeled: bool,
+    /// "manual" or "snapshot"
+    kind: Option<String>,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+struct ImportBackupQuery {
+    server_id: String,
+    label: Option<String>,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+struct BackupEntriesQuery {
+    /// Only entries under this path, e.g. `universe/worlds`
+    #[serde(default)]
+    prefix: String,
+    limit: Option<usize>,
+    /// For a backup encrypted with a key of its own
+    encryption_key: Option<String>,
 }
 
+/// Entries listed by default, the most `GET /backups/:id/entries` returns
+const DEFAULT_ENTRIES_LIMIT: usize = 5_000;
+const MAX_ENTRIES_LIMIT: usize = 50_000;
+
 #[derive(Debug, FromRow)]
 struct BackupRow {
     id: String,
@@ -45,26 +172,37 @@ struct BackupRow {
     filename: String,
     size_bytes: i64,
     created_at: String,
+    kind: String,
+    label: Option<String>,
+    compression: String,
+    location: String,
+    sha256: Option<String>,
+    integrity: Option<String>,
+    verified_at: Option<String>,
+    encrypted: bool,
 }
 
+#[utoipa::path(get, path = "/backups", tag = "backups", params(ListBackupsQuery), responses((status = 200, body = [BackupResponse])))]
 async fn list_backups(
     State(state): State<AppState>,
+    auth: AuthUser,
     Query(query): Query<ListBackupsQuery>,
 ) -> Result<Json<Vec<BackupResponse>>, AppError> {
-    let backups: Vec<BackupRow> = if let Some(server_id) = &query.server_id {
-        sqlx::query_as(
-            "SELECT id, server_id, filename, size_bytes, created_at FROM backups WHERE server_id = ? ORDER BY created_at DESC"
-        )
-        .bind(server_id)
-        .fetch_all(&state.pool)
-        .await?
-    } else {
-        sqlx::query_as(
-            "SELECT id, server_id, filename, size_bytes, created_at FROM backups ORDER BY created_at DESC"
-        )
-        .fetch_all(&state.pool)
-        .await?
-    };
+    let allowed = server_access::allowed_servers(&state.pool, &auth, server_access::BACKUPS_VIEW).await?;
+    let mut backups: Vec<BackupRow> = sqlx::query_as(
+        "SELECT id, server_id, filename, size_bytes, created_at, kind, label, compression, location, sha256, integrity, verified_at, encrypted FROM backups
+         WHERE (? IS NULL OR server_id = ?) AND (? IS NULL OR label = ?)
+         ORDER BY created_at DESC"
+    )
+    .bind(&query.server_id)
+    .bind(&query.server_id)
+    .bind(&query.label)
+    .bind(&query.label)
+    .fetch_all(&state.pool)
+    .await?;
+    if let Some(allowed) = allowed {
+        backups.retain(|b| allowed.contains(&b.server_id));
+    }
 
     let responses: Vec<BackupResponse> = backups
         .into_iter()
@@ -74,82 +212,673 @@ async fn list_backups(
             filename: b.filename,
             size_bytes: b.size_bytes,
             created_at: b.created_at,
+            kind: b.kind,
+            label: b.label,
+            compression: b.compression,
+            location: b.location,
+            sha256: b.sha256,
+            integrity: b.integrity,
+            verified_at: b.verified_at,
+            encrypted: b.encrypted,
+            pruned: None,
         })
         .collect();
 
     Ok(Json(responses))
 }
 
+pub const JOB_PENDING: &str = "pending";
+pub const JOB_RUNNING: &str = "running";
+pub const JOB_DONE: &str = "done";
+pub const JOB_FAILED: &str = "failed";
+
+#[derive(Debug, Serialize, FromRow, ToSchema)]
+pub struct BackupJob {
+    pub id: String,
+    pub server_id: String,
+    /// Id the backup gets once done, also the `backup_id` of its progress lines
+    pub backup_id: String,
+    pub status: String,
+    pub error: Option<String>,
+    pub created_at: String,
+    pub finished_at: Option<String>,
+}
+
+/// Start a backup in the background and return its job right away (202), to be polled on
+/// `GET /backups/jobs/:id`: archiving a large server takes minutes
+#[utoipa::path(post, path = "/backups", tag = "backups", request_body = CreateBackupRequest, responses((status = 202, body = serde_json::Value, description = "Job to poll on `/backups/jobs/{id}`")))]
 async fn create_backup(
     State(state): State<AppState>,
+    auth: AuthUser,
     Json(body): Json<CreateBackupRequest>,
-) -> Result<(StatusCode, Json<BackupResponse>), AppError> {
+) -> Result<(StatusCode, Json<serde_json::Value>), AppError> {
+    server_access::require_permission(&state.pool, &auth, &body.server_id, server_access::BACKUPS_MANAGE).await?;
     // Check server exists
     let server: Option<(String,)> = sqlx::query_as("SELECT working_dir FROM servers WHERE id = ?")
         .bind(&body.server_id)
         .fetch_optional(&state.pool)
         .await?;
 
-    if server.is_none() {
-        return Err(AppError::NotFound("Server not found".into()));
+    let Some((working_dir,)) = server else {
+        return Err(AppError::NotFound("servers.not_found".into()));
+    };
+    let key = parse_key(body.encryption_key.as_deref())?;
+    let guard = state.operations.try_acquire(&state.process_manager, &body.server_id, operation_lock::OP_BACKUP).await?;
+
+    let job_id = Uuid::new_v4().to_string();
+    let backup_id = Uuid::new_v4().to_string();
+    sqlx::query("INSERT INTO backup_jobs (id, server_id, backup_id, status, created_at) VALUES (?, ?, ?, ?, ?)")
+        .bind(&job_id)
+        .bind(&body.server_id)
+        .bind(&backup_id)
+        .bind(JOB_PENDING)
+        .bind(Utc::now().to_rfc3339())
+        .execute(&state.pool)
+        .await?;
+
+    let (job, backup, server_id) = (job_id.clone(), backup_id.clone(), body.server_id.clone());
+    tokio::spawn(async move {
+        set_job_status(&state.pool, &job, JOB_RUNNING, None).await;
+        let result = run_backup(&state, &backup, &server_id, &working_dir, None, key).await;
+        publish_backup(&state.events, &server_id, &backup, &result);
+        match result {
+            Ok(_) => set_job_status(&state.pool, &job, JOB_DONE, None).await,
+            Err(e) => {
+                tracing::error!("Backup job {} of server {} failed: {}", job, server_id, e);
+                set_job_status(&state.pool, &job, JOB_FAILED, Some(&e.to_string())).await;
+            }
+        }
+        drop(guard);
+    });
+
+    Ok((StatusCode::ACCEPTED, Json(serde_json::json!({
+        "success": true,
+        "job_id": job_id,
+        "backup_id": backup_id,
+        "status": JOB_PENDING,
+    }))))
+}
+
+async fn set_job_status(pool: &DbPool, job_id: &str, status: &str, error: Option<&str>) {
+    let finished_at = (status == JOB_DONE || status == JOB_FAILED).then(|| Utc::now().to_rfc3339());
+    if let Err(e) = sqlx::query("UPDATE backup_jobs SET status = ?, error = ?, finished_at = ? WHERE id = ?")
+        .bind(status)
+        .bind(error)
+        .bind(finished_at)
+        .bind(job_id)
+        .execute(pool)
+        .await
+    {
+        tracing::error!("Failed to update backup job {}: {}", job_id, e);
     }
+}
 
-    let id = Uuid::new_v4().to_string();
+#[utoipa::path(get, path = "/backups/jobs/{id}", tag = "backups", params(("id" = String, Path)), responses((status = 200, body = BackupJob), (status = 404)))]
+async fn get_backup_job(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<Json<BackupJob>, AppError> {
+    let job: BackupJob = sqlx::query_as(
+        "SELECT id, server_id, backup_id, status, error, created_at, finished_at FROM backup_jobs WHERE id = ?"
+    )
+    .bind(&id)
+    .fetch_optional(&state.pool)
+    .await?
+    .ok_or_else(|| AppError::NotFound("backups.job_not_found".into()))?;
+    Ok(Json(job))
+}
+
+/// Jobs cut short by a panel restart left a partial archive at most, flag them as failed
+pub async fn mark_interrupted_jobs(pool: &DbPool) {
+    let result = sqlx::query("UPDATE backup_jobs SET status = ?, error = 'interrupted', finished_at = ? WHERE status IN (?, ?)")
+        .bind(JOB_FAILED)
+        .bind(Utc::now().to_rfc3339())
+        .bind(JOB_PENDING)
+        .bind(JOB_RUNNING)
+        .execute(pool)
+        .await;
+
+    match result {
+        Ok(r) if r.rows_affected() > 0 => tracing::info!("Marked {} interrupted backup job(s) as failed", r.rows_affected()),
+        Ok(_) => {}
+        Err(e) => tracing::error!("Failed to check interrupted backup jobs: {}", e),
+    }
+}
+
+/// `[BACKUP_PROGRESS]: {json}` console lines of a backup or restore, sent at most once per percent
+struct ProgressReporter {
+    tx: tokio::sync::broadcast::Sender<(String, String)>,
+    server_id: String,
+    backup_id: String,
+    operation: &'static str,
+    last_percent: Option<u8>,
+    last: Progress,
+}
+
+impl ProgressReporter {
+    fn new(pm: &ProcessManager, server_id: &str, backup_id: &str, operation: &'static str) -> Self {
+        Self {
+            tx: pm.task_sender(),
+            server_id: server_id.to_string(),
+            backup_id: backup_id.to_string(),
+            operation,
+            last_percent: None,
+            last: Progress::default(),
+        }
+    }
+
+    fn report(&mut self, progress: Progress) {
+        self.last = progress;
+        let percent = progress.percent();
+        if self.last_percent != Some(percent) {
+            self.last_percent = Some(percent);
+            self.send("running");
+        }
+    }
+
+    /// "done" or "failed"
+    fn send(&self, status: &str) {
+        let line = serde_json::json!({
+            "operation": self.operation,
+            "backup_id": self.backup_id,
+            "status": status,
+            "files": self.last.files,
+            "bytes": self.last.bytes,
+            "total_bytes": self.last.total_bytes,
+            "percent": if status == "done" { 100 } else { self.last.percent() },
+        });
+        let _ = self.tx.send((self.server_id.clone(), format!("[BACKUP_PROGRESS]: {}", line)));
+    }
+}
+
+/// Archive a server directory into the backups directory and record it as a manual backup. It is
+/// encrypted with `key`, or the configured key when none is given.
+async fn run_backup(
+    state: &AppState,
+    id: &str,
+    server_id: &str,
+    working_dir: &str,
+    label: Option<&str>,
+    key: Option<EncryptionKey>,
+) -> Result<BackupResponse, AppError> {
+    let (pool, pm, backups_dir) = (&state.pool, &state.process_manager, state.settings.backups_dir.as_path());
+    let id = id.to_string();
     let now = Utc::now();
-    let filename = format!(
-        "backup_{}_{}.tar.gz",
-        body.server_id,
-        now.format("%Y%m%d_%H%M%S")
-    );
+    let compression = backup_service::configured_compression(pool).await;
+    let key = match key {
+        Some(key) => Some(key),
+        None => backup_crypto::configured_key(pool).await,
+    };
+    let mut extension = compression.codec.extension().to_string();
+    if key.is_some() {
+        extension = format!("{}.{}", extension, backup_crypto::EXTENSION);
+    }
+    let filename = backup_filename(pool, backups_dir, server_id, &extension, now).await?;
 
     // Create backups directory if not exists
-    let backups_dir = std::path::Path::new("backups");
     if !backups_dir.exists() {
         std::fs::create_dir_all(backups_dir).map_err(|e| AppError::Internal(format!("Failed to create backups dir: {}", e)))?;
     }
 
     let backup_path = backups_dir.join(&filename);
-    
-    // Create actual backup
-    let working_dir = &server.unwrap().0;
-    
+
+    // A running server saves its world first, so the archive doesn't catch half-written files
+    let flush = FlushCommands::load(pool, server_id).await;
+    world_flush::prepare(pm, server_id, &flush).await;
+
     // Call service
-    let size_bytes = crate::services::backup_service::create_archive(working_dir, backup_path.to_str().unwrap())
-        .map_err(|e| AppError::Internal(format!("Backup failed: {:?}", e)))?;
+    let source = working_dir.to_string();
+    let mut reporter = ProgressReporter::new(pm, server_id, &id, "backup");
+    let archived = tokio::task::spawn_blocking(move || {
+        let path = backup_path.to_string_lossy();
+        let result = backup_service::create_archive(&source, &path, compression, key.as_ref(), |p| reporter.report(p))
+            .and_then(|size| Ok((size, backup_service::checksum(&path)?)));
+        reporter.send(if result.is_ok() { "done" } else { "failed" });
+        result
+    })
+    .await;
+    world_flush::resume(pm, server_id, &flush).await;
+    let archived = archived.map_err(|e| AppError::Internal(format!("Backup task failed: {}", e)))?;
+    let (size_bytes, sha256) = archived.map_err(|e| AppError::Internal(format!("Backup failed: {:?}", e)))?;
 
     let created_at = now.to_rfc3339();
 
     sqlx::query(
-        "INSERT INTO backups (id, server_id, filename, size_bytes, created_at) VALUES (?, ?, ?, ?, ?)",
+        "INSERT INTO backups (id, server_id, filename, size_bytes, created_at, kind, label, compression, sha256, encrypted) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
     )
     .bind(&id)
-    .bind(&body.server_id)
+    .bind(server_id)
     .bind(&filename)
     .bind(size_bytes as i64)
     .bind(&created_at)
-    .execute(&state.pool)
+    .bind(snapshots::KIND_MANUAL)
+    .bind(label)
+    .bind(compression.codec.as_str())
+    .bind(&sha256)
+    .bind(key.is_some())
+    .execute(pool)
     .await?;
+    remote_storage::spawn_upload(pool.clone(), backups_dir.to_path_buf(), id.clone(), server_id.to_string(), filename.clone());
+    backup_transfer::spawn_push(pool.clone(), backups_dir.to_path_buf(), server_id.to_string(), filename.clone());
+
+    let pruned = prune_backups(pool, backups_dir, server_id).await?;
+    if pruned.deleted > 0 {
+        tracing::info!("Pruned {} old backups of server {} ({} bytes)", pruned.deleted, server_id, pruned.reclaimed_bytes);
+    }
+    // The newest backup of a server is never over quota, a server keeps at least one
+    backup_quota::enforce(pool, backups_dir).await;
 
-    Ok((StatusCode::CREATED, Json(BackupResponse {
+    Ok(BackupResponse {
         id,
-        server_id: body.server_id.clone(),
+        server_id: server_id.to_string(),
         filename,
         size_bytes: size_bytes as i64,
         created_at,
+        kind: snapshots::KIND_MANUAL.to_string(),
+        label: label.map(str::to_string),
+        compression: compression.codec.as_str().to_string(),
+        location: LOCATION_LOCAL.to_string(),
+        sha256: Some(sha256),
+        integrity: None,
+        verified_at: None,
+        encrypted: key.is_some(),
+        pruned: Some(pruned),
+    })
+}
+
+/// Publish the outcome of a backup job
+fn publish_backup(events: &EventBus, server_id: &str, backup_id: &str, result: &Result<BackupResponse, AppError>) {
+    events.publish(PanelEvent::BackupFinished {
+        server_id: server_id.to_string(),
+        backup_id: backup_id.to_string(),
+        filename: result.as_ref().ok().map(|b| b.filename.clone()),
+        size_bytes: result.as_ref().ok().map(|b| b.size_bytes),
+        label: result.as_ref().ok().and_then(|b| b.label.clone()),
+        error: result.as_ref().err().map(|e| e.to_string()),
+    });
+}
+
+/// File name of a new backup from the server's template, numbered when it is already taken
+async fn backup_filename(pool: &DbPool, backups_dir: &std::path::Path, server_id: &str, extension: &str, now: chrono::DateTime<Utc>) -> Result<String, AppError> {
+    let (name, prefix, template): (String, String, Option<String>) = sqlx::query_as(
+        "SELECT name, backup_prefix, backup_filename_template FROM servers WHERE id = ?"
+    )
+    .bind(server_id)
+    .fetch_optional(pool)
+    .await?
+    .ok_or_else(|| AppError::NotFound("servers.not_found".into()))?;
+    let template = template.filter(|t| !t.trim().is_empty()).unwrap_or_else(|| backup_service::DEFAULT_FILENAME_TEMPLATE.to_string());
+    let stem = backup_service::render_filename(&template, &prefix, &name, server_id, now);
+
+    let mut filename = format!("{}.{}", stem, extension);
+    for n in 2.. {
+        let taken: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM backups WHERE filename = ?")
+            .bind(&filename)
+            .fetch_one(pool)
+            .await?;
+        if taken == 0 && !backups_dir.join(&filename).exists() {
+            break;
+        }
+        filename = format!("{}_{}.{}", stem, n, extension);
+    }
+    Ok(filename)
+}
+
+/// Parse a key sent with a request, 64 hex characters
+fn parse_key(value: Option<&str>) -> Result<Option<EncryptionKey>, AppError> {
+    value
+        .map(|v| EncryptionKey::parse(v).ok_or_else(|| AppError::BadRequest("backups.invalid_encryption_key".into())))
+        .transpose()
+}
+
+/// Key opening a backup: none for a clear one, else the key sent with the request or the
+/// configured one
+async fn decryption_key(pool: &DbPool, encrypted: bool, provided: Option<&str>) -> Result<Option<EncryptionKey>, AppError> {
+    if !encrypted {
+        return Ok(None);
+    }
+    let key = match parse_key(provided)? {
+        Some(key) => Some(key),
+        None => backup_crypto::configured_key(pool).await,
+    };
+    key.map(Some).ok_or_else(|| AppError::BadRequest("backups.encryption_key_required".into()))
+}
+
+/// Delete the oldest manual backups of a server beyond its `backup_max_backups` (0 keeps them all).
+/// Snapshots have their own limit, see `snapshots::prune`.
+async fn prune_backups(pool: &DbPool, backups_dir: &std::path::Path, server_id: &str) -> Result<PruneReport, AppError> {
+    let max_backups: Option<i64> = sqlx::query_scalar("SELECT backup_max_backups FROM servers WHERE id = ?")
+        .bind(server_id)
+        .fetch_optional(pool)
+        .await?;
+    let mut report = PruneReport::default();
+    let Some(max_backups) = max_backups.filter(|max| *max > 0) else {
+        return Ok(report);
+    };
+
+    let old: Vec<(String, String, i64, String)> = sqlx::query_as(
+        "SELECT id, filename, size_bytes, location FROM backups WHERE server_id = ? AND kind = ? ORDER BY created_at DESC LIMIT -1 OFFSET ?"
+    )
+    .bind(server_id)
+    .bind(snapshots::KIND_MANUAL)
+    .bind(max_backups)
+    .fetch_all(pool)
+    .await?;
+
+    for (id, filename, size_bytes, location) in old {
+        let file_path = backups_dir.join(&filename);
+        if let Err(e) = tokio::fs::remove_file(&file_path).await {
+            if e.kind() != std::io::ErrorKind::NotFound {
+                tracing::warn!("Failed to delete backup file {:?}: {}", file_path, e);
+                continue;
+            }
+        }
+        remote_storage::delete_remote_copy(pool, &filename, &location).await;
+        sqlx::query("DELETE FROM backups WHERE id = ?").bind(&id).execute(pool).await?;
+        report.deleted += 1;
+        report.reclaimed_bytes += size_bytes;
+    }
+    Ok(report)
+}
+
+/// Back up several servers (or all of them) one after the other in the background. The batch
+/// label is returned so its backups can be followed with `GET /backups?label=...`.
+#[utoipa::path(post, path = "/backups/bulk", tag = "backups", request_body = BulkBackupRequest, responses((status = 202, body = serde_json::Value)))]
+async fn bulk_create_backups(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Json(body): Json<BulkBackupRequest>,
+) -> Result<(StatusCode, Json<serde_json::Value>), AppError> {
+    let servers: Vec<(String, String)> = if body.all {
+        // "All" means all the servers the caller manages
+        let mut servers: Vec<(String, String)> = sqlx::query_as("SELECT id, working_dir FROM servers ORDER BY name")
+            .fetch_all(&state.pool)
+            .await?;
+        if let Some(allowed) = server_access::allowed_servers(&state.pool, &auth, server_access::BACKUPS_MANAGE).await? {
+            servers.retain(|(id, _)| allowed.contains(id));
+        }
+        servers
+    } else {
+        let mut servers = Vec::with_capacity(body.server_ids.len());
+        for server_id in &body.server_ids {
+            server_access::require_permission(&state.pool, &auth, server_id, server_access::BACKUPS_MANAGE).await?;
+            let server: Option<(String, String)> = sqlx::query_as("SELECT id, working_dir FROM servers WHERE id = ?")
+                .bind(server_id)
+                .fetch_optional(&state.pool)
+                .await?;
+            servers.push(server.ok_or_else(|| AppError::NotFound("servers.not_found".into()))?);
+        }
+        servers
+    };
+    if servers.is_empty() {
+        return Err(AppError::BadRequest("backups.bulk_no_servers".into()));
+    }
+
+    let label = body.label
+        .map(|l| l.trim().to_string())
+        .filter(|l| !l.is_empty())
+        .unwrap_or_else(|| format!("bulk_{}", Utc::now().format("%Y%m%d_%H%M%S")));
+    let server_ids: Vec<String> = servers.iter().map(|(id, _)| id.clone()).collect();
+
+    let batch_label = label.clone();
+    tokio::spawn(async move {
+        // One archive at a time, so a network-wide backup doesn't saturate the disk
+        for (server_id, working_dir) in servers {
+            // Queued behind a restore or reinstall rather than skipped
+            let _guard = state.operations.acquire(&state.process_manager, &server_id, operation_lock::OP_BACKUP).await;
+            let backup_id = Uuid::new_v4().to_string();
+            let result = run_backup(&state, &backup_id, &server_id, &working_dir, Some(&batch_label), None).await;
+            publish_backup(&state.events, &server_id, &backup_id, &result);
+            if let Err(e) = result {
+                tracing::error!("Bulk backup {} of server {} failed: {}", batch_label, server_id, e);
+            }
+        }
+        tracing::info!("Bulk backup {} finished", batch_label);
+    });
+
+    Ok((StatusCode::ACCEPTED, Json(serde_json::json!({
+        "success": true,
+        "label": label,
+        "server_ids": server_ids,
+    }))))
+}
+
+/// Delete the backups matching every given filter, at least one of `older_than_days`,
+/// `unlabeled` and `kind` being required
+#[utoipa::path(delete, path = "/backups/bulk", tag = "backups", params(BulkDeleteQuery), responses((status = 200, body = serde_json::Value)))]
+async fn bulk_delete_backups(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Query(query): Query<BulkDeleteQuery>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    match &query.server_id {
+        Some(server_id) => server_access::require_permission(&state.pool, &auth, server_id, server_access::BACKUPS_MANAGE).await?,
+        // Deleting across servers is for admins
+        None => auth.require_admin()?,
+    }
+    if query.older_than_days.is_none() && !query.unlabeled && query.kind.is_none() {
+        return Err(AppError::BadRequest("backups.bulk_filter_required".into()));
+    }
+    if query.kind.as_deref().is_some_and(|k| k != snapshots::KIND_MANUAL && k != snapshots::KIND_SNAPSHOT) {
+        return Err(AppError::BadRequest("backups.invalid_kind".into()));
+    }
+
+    let cutoff = query.older_than_days.map(|days| (Utc::now() - chrono::Duration::days(days as i64)).to_rfc3339());
+    let backups: Vec<(String, String, i64, String)> = sqlx::query_as(
+        "SELECT id, filename, size_bytes, location FROM backups
+         WHERE (? IS NULL OR server_id = ?) AND (? IS NULL OR created_at < ?)
+         AND (? = 0 OR label IS NULL) AND (? IS NULL OR kind = ?)"
+    )
+    .bind(&query.server_id)
+    .bind(&query.server_id)
+    .bind(&cutoff)
+    .bind(&cutoff)
+    .bind(query.unlabeled as i32)
+    .bind(&query.kind)
+    .bind(&query.kind)
+    .fetch_all(&state.pool)
+    .await?;
+
+    let backups_dir = &state.settings.backups_dir;
+    let mut freed_bytes = 0;
+    for (id, filename, size_bytes, location) in &backups {
+        let file_path = backups_dir.join(filename);
+        if file_path.exists() {
+            if let Err(e) = std::fs::remove_file(&file_path) {
+                tracing::warn!("Failed to delete backup file {:?}: {}", file_path, e);
+                continue;
+            }
+        }
+        remote_storage::delete_remote_copy(&state.pool, filename, location).await;
+        sqlx::query("DELETE FROM backups WHERE id = ?")
+            .bind(id)
+            .execute(&state.pool)
+            .await?;
+        freed_bytes += size_bytes;
+    }
+
+    Ok(Json(serde_json::json!({
+        "success": true,
+        "deleted": backups.len(),
+        "freed_bytes": freed_bytes,
     })))
 }
 
+/// Import a `.tar.gz`, `.tar.zst` or plain `.tar` made elsewhere (another machine, another panel)
+/// as a manual backup of a server, sent as the `file` field of a multipart form. It can then be
+/// restored like any backup.
+#[utoipa::path(
+    post, path = "/backups/import", tag = "backups", params(ImportBackupQuery),
+    request_body(content = Vec<u8>, content_type = "multipart/form-data", description = "The archive in a `file` field"),
+    responses((status = 201, body = BackupResponse))
+)]
+async fn import_backup_multipart(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Query(query): Query<ImportBackupQuery>,
+    mut multipart: Multipart,
+) -> Result<(StatusCode, Json<BackupResponse>), AppError> {
+    server_exists(&state.pool, &query.server_id).await?;
+    server_access::require_permission(&state.pool, &auth, &query.server_id, server_access::BACKUPS_MANAGE).await?;
+
+    while let Some(field) = multipart.next_field().await.map_err(|e| AppError::BadRequest(format!("upload.invalid_multipart: {}", e)))? {
+        if field.name() != Some("file") {
+            continue;
+        }
+        let backup = import_archive(&state.pool, &state.settings.backups_dir, &query, field).await?;
+        return Ok((StatusCode::CREATED, Json(backup)));
+    }
+    Err(AppError::BadRequest("backups.import_missing_file".into()))
+}
+
+/// Same as the multipart import with the archive as the raw request body, which suits
+/// `curl -T` and chunked transfer encoding
+#[utoipa::path(
+    put, path = "/backups/import", tag = "backups", params(ImportBackupQuery),
+    request_body(content = Vec<u8>, content_type = "application/octet-stream"),
+    responses((status = 201, body = BackupResponse))
+)]
+async fn import_backup_stream(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Query(query): Query<ImportBackupQuery>,
+    body: Body,
+) -> Result<(StatusCode, Json<BackupResponse>), AppError> {
+    server_exists(&state.pool, &query.server_id).await?;
+    server_access::require_permission(&state.pool, &auth, &query.server_id, server_access::BACKUPS_MANAGE).await?;
+    let backup = import_archive(&state.pool, &state.settings.backups_dir, &query, body.into_data_stream()).await?;
+    Ok((StatusCode::CREATED, Json(backup)))
+}
+
+async fn server_exists(pool: &DbPool, server_id: &str) -> Result<(), AppError> {
+    let exists: Option<String> = sqlx::query_scalar("SELECT id FROM servers WHERE id = ?")
+        .bind(server_id)
+        .fetch_optional(pool)
+        .await?;
+    exists.map(|_| ()).ok_or_else(|| AppError::NotFound("servers.not_found".into()))
+}
+
+/// Stream an uploaded archive into the backups directory, check it reads through, then register it
+async fn import_archive<S, E>(pool: &DbPool, backups_dir: &std::path::Path, query: &ImportBackupQuery, stream: S) -> Result<BackupResponse, AppError>
+where
+    S: Stream<Item = Result<Bytes, E>>,
+    E: std::fmt::Display,
+{
+    let now = Utc::now();
+    let stem = format!("import_{}_{}", query.server_id, now.format("%Y%m%d_%H%M%S_%3f"));
+    tokio::fs::create_dir_all(backups_dir).await
+        .map_err(|e| AppError::Internal(format!("Failed to create backups dir: {}", e)))?;
+    let partial_path = backups_dir.join(format!("{}.part", stem));
+
+    let (size_bytes, codec) = match receive_archive(stream, &partial_path).await {
+        Ok(received) => received,
+        Err(e) => {
+            let _ = tokio::fs::remove_file(&partial_path).await;
+            return Err(e);
+        }
+    };
+
+    let check_path = partial_path.to_string_lossy().into_owned();
+    let verified = tokio::task::spawn_blocking(move || {
+        backup_service::verify_archive(&check_path, codec, None)?;
+        Ok::<_, backup_service::BackupError>(backup_service::checksum(&check_path)?)
+    })
+    .await
+    .map_err(|e| AppError::Internal(format!("Import task failed: {}", e)))?;
+    let sha256 = match verified {
+        Ok(sha256) => sha256,
+        Err(e) => {
+            tracing::warn!("Rejected imported backup for server {}: {:?}", query.server_id, e);
+            let _ = tokio::fs::remove_file(&partial_path).await;
+            return Err(AppError::BadRequest("backups.invalid_archive".into()));
+        }
+    };
+    let filename = format!("{}.{}", stem, codec.extension());
+    let backup_path = backups_dir.join(&filename);
+    tokio::fs::rename(&partial_path, &backup_path).await
+        .map_err(|e| AppError::Internal(format!("Failed to store imported backup: {}", e)))?;
+
+    let id = Uuid::new_v4().to_string();
+    let created_at = now.to_rfc3339();
+    let label = query.label.as_deref().map(str::trim).filter(|l| !l.is_empty());
+    sqlx::query(
+        "INSERT INTO backups (id, server_id, filename, size_bytes, created_at, kind, label, compression, sha256) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)",
+    )
+    .bind(&id)
+    .bind(&query.server_id)
+    .bind(&filename)
+    .bind(size_bytes as i64)
+    .bind(&created_at)
+    .bind(snapshots::KIND_MANUAL)
+    .bind(label)
+    .bind(codec.as_str())
+    .bind(&sha256)
+    .execute(pool)
+    .await?;
+    tracing::info!("Imported backup {} for server {} ({} bytes)", filename, query.server_id, size_bytes);
+    remote_storage::spawn_upload(pool.clone(), backups_dir.to_path_buf(), id.clone(), query.server_id.clone(), filename.clone());
+    backup_transfer::spawn_push(pool.clone(), backups_dir.to_path_buf(), query.server_id.clone(), filename.clone());
+
+    let pruned = prune_backups(pool, backups_dir, &query.server_id).await?;
+    backup_quota::enforce(pool, backups_dir).await;
+    Ok(BackupResponse {
+        id,
+        server_id: query.server_id.clone(),
+        filename,
+        size_bytes: size_bytes as i64,
+        created_at,
+        kind: snapshots::KIND_MANUAL.to_string(),
+        label: label.map(str::to_string),
+        compression: codec.as_str().to_string(),
+        location: LOCATION_LOCAL.to_string(),
+        sha256: Some(sha256),
+        integrity: None,
+        verified_at: None,
+        encrypted: false,
+        pruned: Some(pruned),
+    })
+}
+
+/// Write the upload to `path` chunk by chunk (world archives run into GBs), returning its size
+/// and the format recognised from its first bytes
+async fn receive_archive<S, E>(stream: S, path: &std::path::Path) -> Result<(u64, Codec), AppError>
+where
+    S: Stream<Item = Result<Bytes, E>>,
+    E: std::fmt::Display,
+{
+    let mut stream = std::pin::pin!(stream);
+    let mut file = tokio::fs::File::create(path).await
+        .map_err(|e| AppError::Internal(format!("Failed to create file: {}", e)))?;
+    let mut size = 0u64;
+    let mut magic = Vec::with_capacity(backup_service::SNIFF_LEN);
+    while let Some(chunk) = stream.next().await {
+        let chunk = chunk.map_err(|e| AppError::BadRequest(format!("upload.invalid_multipart: {}", e)))?;
+        if magic.len() < backup_service::SNIFF_LEN {
+            magic.extend(chunk.iter().take(backup_service::SNIFF_LEN - magic.len()));
+        }
+        file.write_all(&chunk).await
+            .map_err(|e| AppError::Internal(format!("Failed to write file: {}", e)))?;
+        size += chunk.len() as u64;
+    }
+    file.flush().await
+        .map_err(|e| AppError::Internal(format!("Failed to write file: {}", e)))?;
+
+    let codec = Codec::sniff(&magic).ok_or_else(|| AppError::BadRequest("backups.invalid_archive".into()))?;
+    Ok((size, codec))
+}
+
+#[utoipa::path(get, path = "/backups/{id}", tag = "backups", params(("id" = String, Path)), responses((status = 200, body = BackupResponse), (status = 404)))]
 async fn get_backup(
     State(state): State<AppState>,
     Path(id): Path<String>,
 ) -> Result<Json<BackupResponse>, AppError> {
     let backup: BackupRow = sqlx::query_as(
-        "SELECT id, server_id, filename, size_bytes, created_at FROM backups WHERE id = ?",
+        "SELECT id, server_id, filename, size_bytes, created_at, kind, label, compression, location, sha256, integrity, verified_at, encrypted FROM backups WHERE id = ?",
     )
     .bind(&id)
     .fetch_optional(&state.pool)
     .await?
-    .ok_or_else(|| AppError::NotFound("Backup not found".into()))?;
+    .ok_or_else(|| AppError::NotFound("backups.not_found".into()))?;
 
     Ok(Json(BackupResponse {
         id: backup.id,
@@ -157,25 +886,35 @@ async fn get_backup(
         filename: backup.filename,
         size_bytes: backup.size_bytes,
         created_at: backup.created_at,
+        kind: backup.kind,
+        label: backup.label,
+        compression: backup.compression,
+        location: backup.location,
+        sha256: backup.sha256,
+        integrity: backup.integrity,
+        verified_at: backup.verified_at,
+        encrypted: backup.encrypted,
+        pruned: None,
     }))
 }
 
+#[utoipa::path(delete, path = "/backups/{id}", tag = "backups", params(("id" = String, Path)), responses((status = 200, body = serde_json::Value), (status = 404)))]
 async fn delete_backup(
     State(state): State<AppState>,
     Path(id): Path<String>,
 ) -> Result<Json<serde_json::Value>, AppError> {
     // Get filename first to delete file
-    let backup: Option<(String,)> = sqlx::query_as("SELECT filename FROM backups WHERE id = ?")
+    let backup: Option<(String, String)> = sqlx::query_as("SELECT filename, location FROM backups WHERE id = ?")
         .bind(&id)
         .fetch_optional(&state.pool)
         .await?;
 
-    if let Some((filename,)) = backup {
-         let backups_dir = std::path::Path::new("backups");
-         let file_path = backups_dir.join(filename);
+    if let Some((filename, location)) = backup {
+         let file_path = state.settings.backups_dir.join(&filename);
          if file_path.exists() {
              std::fs::remove_file(file_path).map_err(|e| AppError::Internal(format!("Failed to delete backup file: {}", e)))?;
          }
+         remote_storage::delete_remote_copy(&state.pool, &filename, &location).await;
     }
 
     let result = sqlx::query("DELETE FROM backups WHERE id = ?")
@@ -184,40 +923,219 @@ async fn delete_backup(
         .await?;
 
     if result.rows_affected() == 0 {
-        return Err(AppError::NotFound("Backup not found".into()));
+        return Err(AppError::NotFound("backups.not_found".into()));
     }
 
     Ok(Json(serde_json::json!({ "success": true })))
 }
 
+#[utoipa::path(post, path = "/backups/{id}/restore", tag = "backups", params(("id" = String, Path)), request_body = Option<RestoreBackupRequest>, responses((status = 200, body = serde_json::Value)))]
 async fn restore_backup(
     State(state): State<AppState>,
     Path(id): Path<String>,
+    body: Option<Json<RestoreBackupRequest>>,
 ) -> Result<Json<serde_json::Value>, AppError> {
+    let Json(body) = body.unwrap_or_default();
+    let selected = body.paths.iter().map(|p| selected_path(p)).collect::<Result<Vec<_>, _>>()?;
     let backup: BackupRow = sqlx::query_as(
-        "SELECT id, server_id, filename, size_bytes, created_at FROM backups WHERE id = ?",
+        "SELECT id, server_id, filename, size_bytes, created_at, kind, label, compression, location, sha256, integrity, verified_at, encrypted FROM backups WHERE id = ?",
     )
     .bind(&id)
     .fetch_optional(&state.pool)
     .await?
-    .ok_or_else(|| AppError::NotFound("Backup not found".into()))?;
+    .ok_or_else(|| AppError::NotFound("backups.not_found".into()))?;
 
     // Get server working dir
     let server: (String,) = sqlx::query_as("SELECT working_dir FROM servers WHERE id = ?")
         .bind(&backup.server_id)
         .fetch_optional(&state.pool)
         .await?
-        .ok_or_else(|| AppError::NotFound("Server not found".into()))?;
+        .ok_or_else(|| AppError::NotFound("servers.not_found".into()))?;
+    let _guard = state.operations.try_acquire(&state.process_manager, &backup.server_id, operation_lock::OP_RESTORE).await?;
 
-    let backups_dir = std::path::Path::new("backups");
-    let file_path = backups_dir.join(&backup.filename);
-    
-    // Restore
-    crate::services::backup_service::extract_archive(file_path.to_str().unwrap(), &server.0)
-        .map_err(|e| AppError::Internal(format!("Restore failed: {:?}", e)))?;
+    let file_path = state.settings.backups_dir.join(&backup.filename);
+    if !file_path.exists() && backup.location != LOCATION_LOCAL {
+        fetch_remote_copy(&state.pool, &state.settings.backups_dir, &backup.id, &backup.filename).await?;
+    }
+
+    // A wrong key is caught here, before any file is overwritten
+    let key = decryption_key(&state.pool, backup.encrypted, body.encryption_key.as_deref()).await?;
+    if let Some(key) = key {
+        let path = file_path.clone();
+        tokio::task::spawn_blocking(move || backup_crypto::check_key(&path, &key))
+            .await
+            .map_err(|e| AppError::Internal(format!("Restore task failed: {}", e)))?
+            .map_err(|_| AppError::BadRequest("backups.decryption_failed".into()))?;
+    }
+
+    // Restore with the decoder the archive was written with
+    let codec = Codec::parse(&backup.compression).unwrap_or(Codec::Gzip);
+    let mut reporter = ProgressReporter::new(&state.process_manager, &backup.server_id, &backup.id, "restore");
+    let dest = server.0.clone();
+    let paths = selected.clone();
+    let restored = tokio::task::spawn_blocking(move || {
+        let result = backup_service::extract_archive(&file_path.to_string_lossy(), &dest, codec, key.as_ref(), &paths, |p| reporter.report(p));
+        reporter.send(if result.is_ok() { "done" } else { "failed" });
+        result
+    })
+    .await
+    .map_err(|e| AppError::Internal(format!("Restore task failed: {}", e)))?;
+    let restored = restored.map_err(|e| AppError::Internal(format!("Restore failed: {:?}", e)))?;
+    if !selected.is_empty() && restored == 0 {
+        return Err(AppError::NotFound("backups.paths_not_found".into()));
+    }
+
+    Ok(Json(serde_json::json!({
+        "success": true,
+        "message": format!("Restoring backup {} for server {}", backup.filename, backup.server_id),
+        "restored_entries": restored,
+        "paths": selected,
+    })))
+}
+
+/// A path to restore as stored in archives: relative, `/`-separated, no `..`
+fn selected_path(path: &str) -> Result<String, AppError> {
+    let path = path.trim().replace('\\', "/");
+    let path = path.trim_start_matches("./").trim_matches('/');
+    let escapes = path.split('/').any(|part| part == ".." || part.contains(':'));
+    if path.is_empty() || escapes {
+        return Err(AppError::BadRequest("backups.invalid_restore_path".into()));
+    }
+    Ok(path.to_string())
+}
+
+/// Re-hash a backup and read its archive through, recording whether it is still intact. A
+/// backup from before checksums were stored gets its hash recorded when it reads fine.
+#[utoipa::path(post, path = "/backups/{id}/verify", tag = "backups", params(("id" = String, Path)), request_body = Option<BackupKeyRequest>, responses((status = 200, body = serde_json::Value)))]
+async fn verify_backup(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    body: Option<Json<BackupKeyRequest>>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let Json(body) = body.unwrap_or_default();
+    let backup: BackupRow = sqlx::query_as(
+        "SELECT id, server_id, filename, size_bytes, created_at, kind, label, compression, location, sha256, integrity, verified_at, encrypted FROM backups WHERE id = ?",
+    )
+    .bind(&id)
+    .fetch_optional(&state.pool)
+    .await?
+    .ok_or_else(|| AppError::NotFound("backups.not_found".into()))?;
+
+    let file_path = state.settings.backups_dir.join(&backup.filename);
+    if !file_path.exists() && backup.location != LOCATION_LOCAL {
+        fetch_remote_copy(&state.pool, &state.settings.backups_dir, &backup.id, &backup.filename).await?;
+    }
+    if !file_path.exists() {
+        return Err(AppError::NotFound("backups.file_missing".into()));
+    }
+
+    let key = decryption_key(&state.pool, backup.encrypted, body.encryption_key.as_deref()).await?;
+    let codec = Codec::parse(&backup.compression).unwrap_or(Codec::Gzip);
+    let path = file_path.to_string_lossy().into_owned();
+    let (sha256, readable) = tokio::task::spawn_blocking(move || {
+        let sha256 = backup_service::checksum(&path)?;
+        Ok::<_, std::io::Error>((sha256, backup_service::verify_archive(&path, codec, key.as_ref())))
+    })
+    .await
+    .map_err(|e| AppError::Internal(format!("Verify task failed: {}", e)))?
+    .map_err(|e| AppError::Internal(format!("Failed to read backup: {}", e)))?;
+
+    let checksum_matches = backup.sha256.as_deref().map(|expected| expected == sha256);
+    let intact = readable.is_ok() && checksum_matches != Some(false);
+    if let Err(ref e) = readable {
+        tracing::warn!("Backup {} of server {} is unreadable: {:?}", backup.filename, backup.server_id, e);
+    } else if checksum_matches == Some(false) {
+        tracing::warn!("Backup {} of server {} no longer matches its checksum", backup.filename, backup.server_id);
+    }
+
+    let integrity = if intact { "ok" } else { "corrupted" };
+    let verified_at = Utc::now().to_rfc3339();
+    sqlx::query("UPDATE backups SET integrity = ?, verified_at = ?, sha256 = COALESCE(sha256, ?) WHERE id = ?")
+        .bind(integrity)
+        .bind(&verified_at)
+        .bind(intact.then_some(&sha256))
+        .bind(&backup.id)
+        .execute(&state.pool)
+        .await?;
 
     Ok(Json(serde_json::json!({
         "success": true,
-        "message": format!("Restoring backup {} for server {}", backup.filename, backup.server_id)
+        "id": backup.id,
+        "integrity": integrity,
+        "archive_readable": readable.is_ok(),
+        "checksum_matches": checksum_matches,
+        "sha256": sha256,
+        "verified_at": verified_at,
     })))
 }
+
+/// Files inside a backup (paths, sizes, mtimes), read from the archive without extracting it,
+/// to check what a restore would bring back
+#[utoipa::path(get, path = "/backups/{id}/entries", tag = "backups", params(("id" = String, Path), BackupEntriesQuery), responses((status = 200, body = serde_json::Value)))]
+async fn list_backup_entries(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Query(query): Query<BackupEntriesQuery>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let backup: BackupRow = sqlx::query_as(
+        "SELECT id, server_id, filename, size_bytes, created_at, kind, label, compression, location, sha256, integrity, verified_at, encrypted FROM backups WHERE id = ?",
+    )
+    .bind(&id)
+    .fetch_optional(&state.pool)
+    .await?
+    .ok_or_else(|| AppError::NotFound("backups.not_found".into()))?;
+
+    let file_path = state.settings.backups_dir.join(&backup.filename);
+    if !file_path.exists() && backup.location != LOCATION_LOCAL {
+        fetch_remote_copy(&state.pool, &state.settings.backups_dir, &backup.id, &backup.filename).await?;
+    }
+    if !file_path.exists() {
+        return Err(AppError::NotFound("backups.file_missing".into()));
+    }
+
+    let key = decryption_key(&state.pool, backup.encrypted, query.encryption_key.as_deref()).await?;
+    let codec = Codec::parse(&backup.compression).unwrap_or(Codec::Gzip);
+    let limit = query.limit.unwrap_or(DEFAULT_ENTRIES_LIMIT).clamp(1, MAX_ENTRIES_LIMIT);
+    let path = file_path.to_string_lossy().into_owned();
+    let prefix = query.prefix;
+    let listing = tokio::task::spawn_blocking(move || backup_service::list_entries(&path, codec, key.as_ref(), &prefix, limit))
+        .await
+        .map_err(|e| AppError::Internal(format!("Listing task failed: {}", e)))?
+        .map_err(|e| {
+            tracing::warn!("Failed to read backup {}: {:?}", backup.filename, e);
+            AppError::BadRequest("backups.unreadable_archive".into())
+        })?;
+
+    Ok(Json(serde_json::json!({
+        "success": true,
+        "id": backup.id,
+        "entries": listing.entries,
+        "total_files": listing.total_files,
+        "total_bytes": listing.total_bytes,
+        "truncated": listing.truncated,
+    })))
+}
+
+/// Download the offsite copy of a backup back into the backups directory, keeping it there afterwards
+async fn fetch_remote_copy(pool: &DbPool, backups_dir: &std::path::Path, backup_id: &str, filename: &str) -> Result<(), AppError> {
+    let config = remote_storage::load(pool).await
+        .ok_or_else(|| AppError::BadRequest("backups.remote_not_configured".into()))?;
+    tokio::fs::create_dir_all(backups_dir).await
+        .map_err(|e| AppError::Internal(format!("Failed to create backups dir: {}", e)))?;
+
+    let partial_path = backups_dir.join(format!("{}.part", filename));
+    if let Err(e) = config.download(filename, &partial_path).await {
+        let _ = tokio::fs::remove_file(&partial_path).await;
+        return Err(AppError::Internal(format!("Failed to download backup {}: {}", filename, e)));
+    }
+    tokio::fs::rename(&partial_path, backups_dir.join(filename)).await
+        .map_err(|e| AppError::Internal(format!("Failed to store downloaded backup: {}", e)))?;
+
+    sqlx::query("UPDATE backups SET location = ? WHERE id = ?")
+        .bind(remote_storage::LOCATION_BOTH)
+        .bind(backup_id)
+        .execute(pool)
+        .await?;
+    tracing::info!("Downloaded backup {} from bucket {}", filename, config.bucket);
+    Ok(())
+}
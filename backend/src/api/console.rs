@@ -2,22 +2,43 @@ use axum::{
     extract::{Path, State, ws::{Message, WebSocket, WebSocketUpgrade}},
     response::IntoResponse,
 };
-use tracing::{error, info};
+use tracing::{error, info, Instrument};
 use futures::{sink::SinkExt, stream::StreamExt};
 
 use crate::AppState;
+use crate::api::auth::AuthUser;
+use crate::services::{panel_metrics, request_id, server_access};
+use utoipa::OpenApi;
 
+#[derive(OpenApi)]
+#[openapi(paths(ws_handler))]
+pub(super) struct ApiDoc;
+
+#[utoipa::path(
+    get, path = "/ws/console/{id}", tag = "servers", params(("id" = String, Path), ("token" = Option<String>, Query, description = "Session token, browsers can't set the header")),
+    responses((status = 101, description = "WebSocket streaming the console"))
+)]
 pub async fn ws_handler(
     ws: WebSocketUpgrade,
     Path(server_id): Path<String>,
     State(state): State<AppState>,
+    auth: AuthUser,
 ) -> impl IntoResponse {
-    ws.on_upgrade(move |socket| handle_socket(socket, server_id, state))
+    // Watching the console is checked by the route guard, typing in it is a separate permission
+    let can_command = server_access::has_permission(&state.pool, &auth, &server_id, server_access::CONSOLE_COMMAND)
+        .await
+        .unwrap_or(false);
+    // The session outlives the upgrade request, its logs keep the request's ID
+    let request_id = request_id::current().unwrap_or_default();
+    let span = tracing::info_span!("console", request_id = %request_id, server_id = %server_id);
+    ws.on_upgrade(move |socket| request_id::scope(request_id, handle_socket(socket, server_id, state, can_command)).instrument(span))
 }
 
-async fn handle_socket(socket: WebSocket, server_id: String, state: AppState) {
+async fn handle_socket(socket: WebSocket, server_id: String, state: AppState, can_command: bool) {
+    let _connection = panel_metrics::WsConnection::open();
     let pm = state.process_manager;
-    let mut log_rx = pm.subscribe_logs(&server_id);
+    let mut log_rx = pm.subscribe_logs(&server_id).await;
+    let mut task_rx = pm.subscribe_tasks();
 
     info!("WebSocket connected for server: {}", server_id);
 
@@ -36,6 +57,9 @@ async fn handle_socket(socket: WebSocket, server_id: String, state: AppState) {
         tokio::spawn(async move {
             while let Some(Ok(msg)) = receiver.next().await {
                 match msg {
+                    Message::Text(_) if !can_command => {
+                        info!("Ignored console command on {}: not permitted", server_id);
+                    }
                     Message::Text(text) => {
                          // Client sending command to server
                          if let Err(e) = pm.send_command(&server_id, &text).await {
@@ -46,28 +70,47 @@ async fn handle_socket(socket: WebSocket, server_id: String, state: AppState) {
                     _ => {}
                 }
             }
-        })
+        }.in_current_span())
     };
 
     // Task to broadcast logs to client
-    let mut send_task = tokio::spawn(async move {
-        loop {
-            // Check for log messages
-            match log_rx.recv().await {
-                Ok(log_line) => {
-                    if sender.send(Message::Text(log_line)).await.is_err() {
-                        return; // Client disconnected
+    let mut send_task = {
+        let pm = pm.clone();
+        let server_id = server_id.clone();
+        tokio::spawn(async move {
+            // A stopped server has no log channel: task lines (backup progress) still come
+            // through, and the logs are picked up again once it starts
+            let mut logs_open = true;
+            let mut resubscribe = tokio::time::interval(std::time::Duration::from_secs(2));
+            loop {
+                let line = tokio::select! {
+                    log = log_rx.recv(), if logs_open => match log {
+                        Ok(log_line) => log_line,
+                        Err(tokio::sync::broadcast::error::RecvError::Lagged(_)) => continue, // Lagged, skip
+                        Err(tokio::sync::broadcast::error::RecvError::Closed) => {
+                            logs_open = false;
+                            continue;
+                        }
+                    },
+                    task = task_rx.recv() => match task {
+                        Ok((id, line)) if id == server_id => line,
+                        Ok(_) | Err(tokio::sync::broadcast::error::RecvError::Lagged(_)) => continue,
+                        Err(tokio::sync::broadcast::error::RecvError::Closed) => return,
+                    },
+                    _ = resubscribe.tick(), if !logs_open => {
+                        if pm.is_running(&server_id).await || pm.is_installing(&server_id).await {
+                            log_rx = pm.subscribe_logs(&server_id).await;
+                            logs_open = true;
+                        }
+                        continue;
                     }
-                }
-                Err(tokio::sync::broadcast::error::RecvError::Lagged(_)) => {
-                    // Lagged, skip
-                }
-                Err(tokio::sync::broadcast::error::RecvError::Closed) => {
-                    return; // Channel closed
+                };
+                if sender.send(Message::Text(line)).await.is_err() {
+                    return; // Client disconnected
                 }
             }
-        }
-    });
+        })
+    };
 
     // Wait for either task to finish
     tokio::select! {
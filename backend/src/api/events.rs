@@ -0,0 +1,72 @@
+//! Server-Sent Events stream of the event bus (`services::events`), so dashboards update
+//! without polling or one console WebSocket per server. The server list itself still comes
+//! from `GET /servers`; the stream only carries what changes afterwards.
+
+use std::collections::HashSet;
+use std::convert::Infallible;
+
+use axum::{
+    extract::State,
+    response::sse::{Event, KeepAlive, Sse},
+    routing::get,
+    Router,
+};
+use futures::Stream;
+use tokio::sync::broadcast::error::RecvError;
+use utoipa::OpenApi;
+
+use crate::api::auth::AuthUser;
+use crate::error::AppError;
+use crate::services::events::PanelEvent;
+use crate::services::server_access;
+use crate::AppState;
+
+pub fn routes() -> Router<AppState> {
+    Router::new().route("/", get(stream_events))
+}
+
+#[derive(OpenApi)]
+#[openapi(paths(stream_events), components(schemas(PanelEvent)))]
+pub(super) struct ApiDoc;
+
+/// Events of the servers the caller can view (`status`, `metrics`, `players`, `server_crashed`,
+/// `player_joined`...), each carrying a JSON `PanelEvent`. Backup outcomes stay with the
+/// backup permissions and aren't streamed. EventSource can't set headers: pass the session as
+/// `?token=`.
+#[utoipa::path(
+    get, path = "/events", tag = "servers",
+    responses((status = 200, content_type = "text/event-stream", body = PanelEvent))
+)]
+async fn stream_events(
+    State(state): State<AppState>,
+    auth: AuthUser,
+) -> Result<Sse<impl Stream<Item = Result<Event, Infallible>>>, AppError> {
+    // Resolved once: access granted later shows up on the next connection
+    let allowed: Option<HashSet<String>> = server_access::allowed_servers(&state.pool, &auth, server_access::VIEW).await?;
+    let rx = state.events.subscribe();
+
+    let stream = futures::stream::unfold((rx, allowed), |(mut rx, allowed)| async move {
+        loop {
+            match rx.recv().await {
+                Ok(event) => {
+                    if matches!(event, PanelEvent::BackupFinished { .. })
+                        || allowed.as_ref().is_some_and(|allowed| !allowed.contains(event.server_id()))
+                    {
+                        continue;
+                    }
+                    let sse = Event::default()
+                        .event(event.name())
+                        .json_data(&event)
+                        .unwrap_or_else(|_| Event::default().comment("unserializable event"));
+                    return Some((Ok(sse), (rx, allowed)));
+                }
+                // Too slow: missed events are skipped, the next ones still arrive
+                Err(RecvError::Lagged(skipped)) => {
+                    tracing::debug!("Event stream lagged, skipped {} events", skipped);
+                }
+                Err(RecvError::Closed) => return None,
+            }
+        }
+    });
+    Ok(Sse::new(stream).keep_alive(KeepAlive::default()))
+}
@@ -4,15 +4,23 @@ use axum::{
     Json, Router,
 };
 use serde::{Deserialize, Serialize};
+use std::io::ErrorKind;
 use std::path::PathBuf;
+use utoipa::{IntoParams, OpenApi};
 
 use crate::{AppState, error::AppError};
+use crate::api::auth::AuthUser;
+use crate::utils::sandbox;
 
 pub fn routes() -> Router<AppState> {
     Router::new()
         .route("/list", get(list_directory))
 }
 
+#[derive(OpenApi)]
+#[openapi(paths(list_directory))]
+pub(super) struct ApiDoc;
+
 #[derive(Debug, Serialize)]
 pub struct DirectoryEntry {
     pub name: String,
@@ -20,27 +28,53 @@ pub struct DirectoryEntry {
     pub is_dir: bool,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
 pub struct ListQuery {
     pub path: Option<String>,
 }
 
-async fn list_directory(Query(query): Query<ListQuery>) -> Result<Json<serde_json::Value>, AppError> {
-    let base_path = query.path.clone().unwrap_or_else(|| "/".to_string());
-    let path = PathBuf::from(&base_path);
+/// Directory the picker may browse, from `FILESYSTEM_ROOT` (the whole host when unset)
+fn picker_root() -> Option<PathBuf> {
+    std::env::var("FILESYSTEM_ROOT").ok().filter(|r| !r.trim().is_empty()).map(PathBuf::from)
+}
+
+/// Directory picker of the setup wizard and panel settings: it sees the host's layout, so only
+/// admins use it
+#[utoipa::path(get, path = "/filesystem/list", tag = "system", params(ListQuery), responses((status = 200, body = serde_json::Value)))]
+async fn list_directory(auth: AuthUser, Query(query): Query<ListQuery>) -> Result<Json<serde_json::Value>, AppError> {
+    auth.require_admin()?;
+
+    let root = picker_root();
+    let default_path = root.as_ref().map_or_else(|| "/".to_string(), |r| r.to_string_lossy().to_string());
+    let mut base_path = query.path.clone().filter(|p| !p.is_empty()).unwrap_or(default_path);
+    let mut path = PathBuf::from(&base_path);
 
     if !path.exists() {
-        return Err(AppError::NotFound(format!("Path not found: {}", base_path)));
+        return Err(AppError::NotFound(format!("files.not_found: {}", base_path)));
     }
 
+    // `..` and symlinks are resolved before comparing with the root
+    let at_root = match root {
+        Some(ref root) => {
+            path = sandbox::contain(root, &path).map_err(|e| match e.kind() {
+                ErrorKind::PermissionDenied => AppError::BadRequest("files.invalid_path".into()),
+                _ => AppError::Internal(format!("Failed to resolve path: {}", e)),
+            })?;
+            base_path = path.to_string_lossy().to_string();
+            std::fs::canonicalize(root).is_ok_and(|r| r == path)
+        }
+        None => base_path == "/",
+    };
+
     if !path.is_dir() {
-        return Err(AppError::BadRequest("Path is not a directory".into()));
+        return Err(AppError::BadRequest("files.not_a_directory".into()));
     }
 
     let mut entries: Vec<DirectoryEntry> = Vec::new();
 
     // Add parent directory if not at root
-    if base_path != "/" {
+    if !at_root {
         if let Some(parent) = path.parent() {
             entries.push(DirectoryEntry {
                 name: "..".to_string(),
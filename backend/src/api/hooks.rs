@@ -0,0 +1,322 @@
+//! Inbound hooks: a URL that CI or another tool can call to start, stop or restart a server, or
+//! send it a fixed console command. Calls carry no panel credentials; they are authenticated by
+//! an HMAC signature with the hook's secret (see `services::inbound_auth`), optionally from an
+//! allowed source address only.
+
+use std::net::SocketAddr;
+
+use axum::{
+    body::Bytes,
+    extract::{ConnectInfo, Path, State},
+    http::{HeaderMap, StatusCode},
+    routing::{get, patch, post},
+    Json, Router,
+};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+use utoipa::{OpenApi, ToSchema};
+
+use crate::api::auth::AuthUser;
+use crate::api::servers::handlers;
+use crate::api::servers::models::CommandRequest;
+use crate::db::DbPool;
+use crate::error::AppError;
+use crate::services::inbound_auth;
+use crate::AppState;
+
+/// Distinguishes hook secrets from API tokens
+const SECRET_PREFIX: &str = "dwh_";
+
+pub const ACTION_START: &str = "start";
+pub const ACTION_STOP: &str = "stop";
+pub const ACTION_RESTART: &str = "restart";
+pub const ACTION_COMMAND: &str = "command";
+
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/", get(list_hooks).post(create_hook))
+        .route("/:id", patch(update_hook).delete(delete_hook))
+        .route("/:id/rotate", post(rotate_secret))
+}
+
+#[derive(OpenApi)]
+#[openapi(paths(list_hooks, create_hook, update_hook, delete_hook, rotate_secret, trigger_hook))]
+pub(super) struct ApiDoc;
+
+#[derive(Debug, FromRow)]
+struct HookRow {
+    id: String,
+    name: String,
+    server_id: String,
+    action: String,
+    command: Option<String>,
+    /// Kept in clear: the panel needs it to check signatures
+    secret: String,
+    allowed_ips: String,
+    created_by: String,
+    created_at: String,
+    last_triggered_at: Option<String>,
+}
+
+impl HookRow {
+    fn allowed_ips(&self) -> Vec<String> {
+        serde_json::from_str(&self.allowed_ips).unwrap_or_default()
+    }
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct HookResponse {
+    pub id: String,
+    pub name: String,
+    pub server_id: String,
+    pub action: String,
+    pub command: Option<String>,
+    pub allowed_ips: Vec<String>,
+    pub created_by: String,
+    pub created_at: String,
+    pub last_triggered_at: Option<String>,
+    /// Only returned when the hook is created and when its secret is rotated
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub secret: Option<String>,
+}
+
+impl From<HookRow> for HookResponse {
+    fn from(row: HookRow) -> Self {
+        Self {
+            allowed_ips: row.allowed_ips(),
+            id: row.id,
+            name: row.name,
+            server_id: row.server_id,
+            action: row.action,
+            command: row.command,
+            created_by: row.created_by,
+            created_at: row.created_at,
+            last_triggered_at: row.last_triggered_at,
+            secret: None,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct CreateHookRequest {
+    pub name: String,
+    pub server_id: String,
+    /// `start`, `stop`, `restart` or `command`
+    pub action: String,
+    /// Console command sent by a `command` hook
+    pub command: Option<String>,
+    /// Addresses or CIDR ranges allowed to call the hook, any when empty
+    pub allowed_ips: Option<Vec<String>>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct UpdateHookRequest {
+    pub name: Option<String>,
+    pub command: Option<String>,
+    pub allowed_ips: Option<Vec<String>>,
+}
+
+fn new_secret() -> String {
+    format!("{}{}{}", SECRET_PREFIX, Uuid::new_v4().simple(), Uuid::new_v4().simple())
+}
+
+fn validate_allowed_ips(rules: &[String]) -> Result<String, AppError> {
+    if rules.iter().any(|r| !inbound_auth::is_valid_ip_rule(r)) {
+        return Err(AppError::BadRequest("hooks.invalid_ip_rule".into()));
+    }
+    let rules: Vec<&str> = rules.iter().map(|r| r.trim()).collect();
+    Ok(serde_json::to_string(&rules).unwrap_or_else(|_| "[]".into()))
+}
+
+async fn find_hook(pool: &DbPool, id: &str) -> Result<HookRow, AppError> {
+    sqlx::query_as("SELECT * FROM inbound_hooks WHERE id = ?")
+        .bind(id)
+        .fetch_optional(pool)
+        .await?
+        .ok_or_else(|| AppError::NotFound("hooks.not_found".into()))
+}
+
+#[utoipa::path(get, path = "/hooks", tag = "hooks", responses((status = 200, body = [HookResponse])))]
+async fn list_hooks(
+    State(state): State<AppState>,
+    auth: AuthUser,
+) -> Result<Json<Vec<HookResponse>>, AppError> {
+    auth.require_admin_session()?;
+    let hooks: Vec<HookRow> = sqlx::query_as("SELECT * FROM inbound_hooks ORDER BY created_at DESC")
+        .fetch_all(&state.pool)
+        .await?;
+    Ok(Json(hooks.into_iter().map(HookResponse::from).collect()))
+}
+
+#[utoipa::path(post, path = "/hooks", tag = "hooks", request_body = CreateHookRequest, responses((status = 201, body = HookResponse)))]
+async fn create_hook(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Json(body): Json<CreateHookRequest>,
+) -> Result<(StatusCode, Json<HookResponse>), AppError> {
+    auth.require_admin_session()?;
+    let name = body.name.trim();
+    if name.is_empty() {
+        return Err(AppError::BadRequest("hooks.name_required".into()));
+    }
+    if ![ACTION_START, ACTION_STOP, ACTION_RESTART, ACTION_COMMAND].contains(&body.action.as_str()) {
+        return Err(AppError::BadRequest("hooks.invalid_action".into()));
+    }
+    let command = body.command.as_deref().map(str::trim).filter(|c| !c.is_empty());
+    if body.action == ACTION_COMMAND && command.is_none() {
+        return Err(AppError::BadRequest("hooks.command_required".into()));
+    }
+    let allowed_ips = validate_allowed_ips(body.allowed_ips.as_deref().unwrap_or_default())?;
+
+    let exists: Option<String> = sqlx::query_scalar("SELECT id FROM servers WHERE id = ?")
+        .bind(&body.server_id)
+        .fetch_optional(&state.pool)
+        .await?;
+    if exists.is_none() {
+        return Err(AppError::NotFound("servers.not_found".into()));
+    }
+
+    let id = Uuid::new_v4().to_string();
+    let secret = new_secret();
+    sqlx::query(
+        "INSERT INTO inbound_hooks (id, name, server_id, action, command, secret, allowed_ips, created_by, created_at)
+         VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)",
+    )
+    .bind(&id)
+    .bind(name)
+    .bind(&body.server_id)
+    .bind(&body.action)
+    .bind(command.filter(|_| body.action == ACTION_COMMAND))
+    .bind(&secret)
+    .bind(&allowed_ips)
+    .bind(&auth.id)
+    .bind(Utc::now().to_rfc3339())
+    .execute(&state.pool)
+    .await?;
+
+    let mut response = HookResponse::from(find_hook(&state.pool, &id).await?);
+    response.secret = Some(secret);
+    Ok((StatusCode::CREATED, Json(response)))
+}
+
+#[utoipa::path(patch, path = "/hooks/{id}", tag = "hooks", params(("id" = String, Path)), request_body = UpdateHookRequest, responses((status = 200, body = HookResponse)))]
+async fn update_hook(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(id): Path<String>,
+    Json(body): Json<UpdateHookRequest>,
+) -> Result<Json<HookResponse>, AppError> {
+    auth.require_admin_session()?;
+    let hook = find_hook(&state.pool, &id).await?;
+
+    let allowed_ips = match body.allowed_ips.as_deref() {
+        Some(rules) => Some(validate_allowed_ips(rules)?),
+        None => None,
+    };
+    let command = body.command.as_deref().map(str::trim).filter(|c| !c.is_empty());
+    if body.command.is_some() && hook.action != ACTION_COMMAND {
+        return Err(AppError::BadRequest("hooks.command_not_applicable".into()));
+    }
+    if body.command.is_some() && command.is_none() {
+        return Err(AppError::BadRequest("hooks.command_required".into()));
+    }
+    let name = body.name.as_deref().map(str::trim).filter(|n| !n.is_empty());
+
+    sqlx::query(
+        "UPDATE inbound_hooks SET name = COALESCE(?, name), command = COALESCE(?, command),
+         allowed_ips = COALESCE(?, allowed_ips) WHERE id = ?",
+    )
+    .bind(name)
+    .bind(command)
+    .bind(allowed_ips)
+    .bind(&id)
+    .execute(&state.pool)
+    .await?;
+
+    Ok(Json(find_hook(&state.pool, &id).await?.into()))
+}
+
+#[utoipa::path(delete, path = "/hooks/{id}", tag = "hooks", params(("id" = String, Path)), responses((status = 200, body = serde_json::Value)))]
+async fn delete_hook(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(id): Path<String>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    auth.require_admin_session()?;
+    find_hook(&state.pool, &id).await?;
+
+    sqlx::query("DELETE FROM inbound_hooks WHERE id = ?").bind(&id).execute(&state.pool).await?;
+    sqlx::query("DELETE FROM inbound_nonces WHERE key_id = ?").bind(&id).execute(&state.pool).await?;
+
+    Ok(Json(serde_json::json!({ "success": true })))
+}
+
+/// Replace the secret, the previous one stops working at once
+#[utoipa::path(post, path = "/hooks/{id}/rotate", tag = "hooks", params(("id" = String, Path)), responses((status = 200, body = HookResponse)))]
+async fn rotate_secret(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(id): Path<String>,
+) -> Result<Json<HookResponse>, AppError> {
+    auth.require_admin_session()?;
+    find_hook(&state.pool, &id).await?;
+
+    let secret = new_secret();
+    sqlx::query("UPDATE inbound_hooks SET secret = ? WHERE id = ?")
+        .bind(&secret)
+        .bind(&id)
+        .execute(&state.pool)
+        .await?;
+
+    let mut response = HookResponse::from(find_hook(&state.pool, &id).await?);
+    response.secret = Some(secret);
+    Ok(Json(response))
+}
+
+/// The public endpoint: check the caller, then run the hook's action as the matching server
+/// endpoint would. The body is only covered by the signature, its content is ignored.
+#[utoipa::path(
+    post, path = "/hooks/{id}/trigger", tag = "hooks", security(()), params(("id" = String, Path)),
+    request_body(content = Vec<u8>, description = "Any payload, covered by the signature headers"),
+    responses((status = 200, body = serde_json::Value))
+)]
+pub async fn trigger_hook(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    connect_info: Option<ConnectInfo<SocketAddr>>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<Json<serde_json::Value>, AppError> {
+    // Unknown and misconfigured hooks answer like a bad signature, so ids can't be probed
+    let hook: HookRow = sqlx::query_as("SELECT * FROM inbound_hooks WHERE id = ?")
+        .bind(&id)
+        .fetch_optional(&state.pool)
+        .await?
+        .ok_or_else(|| AppError::Unauthorized("hooks.invalid_signature".into()))?;
+
+    let peer = connect_info.map(|ConnectInfo(addr)| addr.ip());
+    inbound_auth::verify(&state.pool, &hook.id, &hook.secret, &hook.allowed_ips(), peer, &headers, &body).await?;
+
+    sqlx::query("UPDATE inbound_hooks SET last_triggered_at = ? WHERE id = ?")
+        .bind(Utc::now().to_rfc3339())
+        .bind(&hook.id)
+        .execute(&state.pool)
+        .await?;
+
+    let server_id = Path(hook.server_id.clone());
+    let result = match hook.action.as_str() {
+        ACTION_START => handlers::start_server(State(state), None, server_id).await?,
+        ACTION_STOP => handlers::stop_server(State(state), None, server_id).await?,
+        ACTION_RESTART => handlers::restart_server(State(state), None, server_id).await?,
+        ACTION_COMMAND => {
+            let command = hook.command.clone().unwrap_or_default();
+            handlers::send_command(State(state), server_id, Json(CommandRequest { command })).await?
+        }
+        _ => return Err(AppError::BadRequest("hooks.invalid_action".into())),
+    };
+
+    tracing::info!("Hook {} ({}) ran {} on server {}", hook.name, hook.id, hook.action, hook.server_id);
+    Ok(result)
+}
@@ -0,0 +1,104 @@
+//! Everything that needs a human: one list instead of checking each server screen
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use axum::{extract::State, routing::get, Json, Router};
+use serde::Serialize;
+use utoipa::{OpenApi, ToSchema};
+
+use crate::api::auth::AuthUser;
+use crate::api::servers::models::{INSTALL_FAILED, INSTALL_PENDING};
+use crate::error::AppError;
+use crate::services::{history, server_access};
+use crate::AppState;
+
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/", get(get_inbox))
+}
+
+#[derive(OpenApi)]
+#[openapi(paths(get_inbox))]
+pub(super) struct ApiDoc;
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct InboxItem {
+    /// auth_required, crashed, install_failed, install_pending, missing_directory
+    #[schema(value_type = String)]
+    pub kind: &'static str,
+    pub server_id: String,
+    pub server_name: String,
+    /// Extra context, e.g. the exit code of a crash
+    pub detail: Option<String>,
+    /// When the situation started, if known
+    pub since: Option<String>,
+}
+
+#[utoipa::path(get, path = "/inbox", tag = "inbox", responses((status = 200, body = [InboxItem])))]
+async fn get_inbox(State(state): State<AppState>, auth: AuthUser) -> Result<Json<Vec<InboxItem>>, AppError> {
+    let mut servers: Vec<(String, String, String, String)> = sqlx::query_as(
+        "SELECT id, name, working_dir, install_state FROM servers ORDER BY name"
+    )
+    .fetch_all(&state.pool)
+    .await?;
+    // Only the servers the caller can see, their directories included
+    if let Some(allowed) = server_access::allowed_servers(&state.pool, &auth, server_access::VIEW).await? {
+        servers.retain(|(id, ..)| allowed.contains(id));
+    }
+
+    // Servers whose last lifecycle event is a crash
+    let crashes: HashMap<String, (Option<String>, String)> = sqlx::query_as::<_, (String, Option<String>, String)>(
+        "SELECT server_id, detail, created_at FROM server_events
+         WHERE id IN (SELECT MAX(id) FROM server_events WHERE event_type IN (?, ?, ?, ?) GROUP BY server_id)
+         AND event_type = ?"
+    )
+    .bind(history::EVENT_START)
+    .bind(history::EVENT_RESTART)
+    .bind(history::EVENT_STOP)
+    .bind(history::EVENT_CRASH)
+    .bind(history::EVENT_CRASH)
+    .fetch_all(&state.pool)
+    .await?
+    .into_iter()
+    .map(|(server_id, detail, created_at)| (server_id, (detail, created_at)))
+    .collect();
+
+    let pm = &state.process_manager;
+    let mut items = Vec::new();
+
+    for (id, name, working_dir, install_state) in servers {
+        let item = |kind: &'static str, detail: Option<String>, since: Option<String>| InboxItem {
+            kind,
+            server_id: id.clone(),
+            server_name: name.clone(),
+            detail,
+            since,
+        };
+
+        if !Path::new(&working_dir).exists() {
+            items.push(item("missing_directory", Some(working_dir.clone()), None));
+            continue;
+        }
+
+        if pm.is_auth_required(&id).await {
+            items.push(item("auth_required", None, None));
+        }
+
+        let installing = pm.is_installing(&id).await;
+        if install_state == INSTALL_FAILED && !installing {
+            items.push(item("install_failed", None, None));
+        } else if install_state == INSTALL_PENDING && !installing {
+            // Offline installs wait here for the server bundle upload
+            items.push(item("install_pending", None, None));
+        }
+
+        if let Some((detail, created_at)) = crashes.get(&id) {
+            if !pm.is_running(&id).await && !pm.is_queued(&id) {
+                items.push(item("crashed", detail.clone(), Some(created_at.clone())));
+            }
+        }
+    }
+
+    Ok(Json(items))
+}
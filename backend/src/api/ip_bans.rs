@@ -0,0 +1,100 @@
+//! Admin management of the IP denylist, and the middleware turning banned addresses away
+//! before any route runs.
+
+use std::net::SocketAddr;
+
+use axum::{
+    extract::{ConnectInfo, Path, Request, State},
+    http::StatusCode,
+    middleware::Next,
+    response::{IntoResponse, Response},
+    routing::{delete, get},
+    Json, Router,
+};
+use chrono::Utc;
+use serde::Deserialize;
+use utoipa::{OpenApi, ToSchema};
+
+use crate::api::auth::AuthUser;
+use crate::error::AppError;
+use crate::services::audit;
+use crate::services::ip_bans::{self, IpBan};
+use crate::AppState;
+
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/", get(list_bans).post(add_ban))
+        .route("/:id", delete(remove_ban))
+}
+
+#[derive(OpenApi)]
+#[openapi(paths(list_bans, add_ban, remove_ban))]
+pub(super) struct ApiDoc;
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct AddBanRequest {
+    /// Single address or CIDR network
+    pub address: String,
+    pub reason: Option<String>,
+    /// Permanent when absent
+    pub duration_minutes: Option<u32>,
+}
+
+
+/// Reject requests from banned addresses
+pub async fn block_banned(request: Request, next: Next) -> Response {
+    let peer = request.extensions().get::<ConnectInfo<SocketAddr>>().map(|ConnectInfo(addr)| addr.ip());
+    if peer.is_some_and(ip_bans::is_banned) {
+        return AppError::Unauthorized("auth.ip_banned".into()).into_response();
+    }
+    next.run(request).await
+}
+
+#[utoipa::path(get, path = "/ip-bans", tag = "ip-bans", responses((status = 200, body = [IpBan])))]
+async fn list_bans(State(state): State<AppState>, auth: AuthUser) -> Result<Json<Vec<IpBan>>, AppError> {
+    auth.require_admin()?;
+    Ok(Json(ip_bans::list(&state.pool).await?))
+}
+
+#[utoipa::path(post, path = "/ip-bans", tag = "ip-bans", request_body = AddBanRequest, responses((status = 201, body = IpBan)))]
+async fn add_ban(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    connect_info: Option<ConnectInfo<SocketAddr>>,
+    Json(body): Json<AddBanRequest>,
+) -> Result<(StatusCode, Json<IpBan>), AppError> {
+    auth.require_admin()?;
+    let network = ip_bans::parse_address(&body.address)
+        .ok_or_else(|| AppError::BadRequest("ip_bans.invalid_address".into()))?;
+    // The ban would apply before the admin could lift it
+    let peer = connect_info.map(|ConnectInfo(addr)| addr.ip());
+    if peer.is_some_and(|ip| network.contains(&ip)) {
+        return Err(AppError::BadRequest("ip_bans.own_address".into()));
+    }
+    if body.duration_minutes == Some(0) {
+        return Err(AppError::BadRequest("ip_bans.invalid_duration".into()));
+    }
+
+    let reason = body.reason.as_deref().map(str::trim).filter(|r| !r.is_empty());
+    let expires_at = body.duration_minutes.map(|m| Utc::now() + chrono::Duration::minutes(m.into()));
+    let ban = ip_bans::add(&state.pool, &network, reason, expires_at, false, Some(&auth.username)).await?;
+
+    let detail = format!("{} banned {}", ban.address, ban.expires_at.as_deref().map_or("permanently".to_string(), |e| format!("until {}", e)));
+    let peer = peer.map(|ip| ip.to_string());
+    audit::record(&state.pool, Some(&auth.id), Some(&auth.username), audit::ACTION_IP_BANNED, Some(&detail), peer.as_deref()).await;
+    Ok((StatusCode::CREATED, Json(ban)))
+}
+
+#[utoipa::path(delete, path = "/ip-bans/{id}", tag = "ip-bans", params(("id" = String, Path)), responses((status = 200, body = serde_json::Value), (status = 404)))]
+async fn remove_ban(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(id): Path<String>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    auth.require_admin()?;
+    if !ip_bans::remove(&state.pool, &id).await? {
+        return Err(AppError::NotFound("ip_bans.not_found".into()));
+    }
+    audit::record(&state.pool, Some(&auth.id), Some(&auth.username), audit::ACTION_IP_UNBANNED, Some(&id), None).await;
+    Ok(Json(serde_json::json!({ "success": true })))
+}
@@ -0,0 +1,19 @@
+//! `/metrics` for Prometheus, outside the versioned API so scrape configs keep the usual path.
+//! Admins only: a session or, more usefully for a scraper, an admin's read-only API token.
+
+use axum::{
+    extract::State,
+    http::header,
+    response::{IntoResponse, Response},
+};
+
+use crate::api::auth::AuthUser;
+use crate::error::AppError;
+use crate::services::panel_metrics;
+use crate::AppState;
+
+pub async fn metrics(State(state): State<AppState>, auth: AuthUser) -> Result<Response, AppError> {
+    auth.require_admin()?;
+    let body = panel_metrics::render(&state.pool, &state.process_manager).await?;
+    Ok(([(header::CONTENT_TYPE, "text/plain; version=0.0.4; charset=utf-8")], body).into_response())
+}
@@ -1,32 +1,68 @@
 use axum::{
-    routing::get,
+    middleware,
+    routing::{get, post},
     Router,
 };
 use crate::AppState;
+use crate::utils::http_cache;
 
+pub mod access;
+pub mod account;
 pub mod auth;
 pub mod backups;
 pub mod console;
+pub mod events;
 pub mod filesystem;
+pub mod hooks;
+pub mod inbox;
+pub mod ip_bans;
+pub mod metrics;
+pub mod oidc;
+pub mod openapi;
+pub mod outgoing_webhooks;
+pub mod rate_limit;
+pub mod role_templates;
+pub mod server_templates;
 pub mod servers;
 pub mod settings;
 pub mod setup;
 pub mod system;
+pub mod tokens;
 pub mod upload;
 pub mod users;
+pub mod visibility;
 pub mod webhook;
 
-pub fn routes() -> Router<AppState> {
-    Router::new()
+pub fn routes(state: AppState) -> Router<AppState> {
+    // Everything but these requires a session or an API token
+    let public = Router::new()
         .nest("/auth", auth::routes())
+        .nest("/setup", setup::routes())
+        .route("/hooks/:id/trigger", post(hooks::trigger_hook))
+        .route("/openapi.json", get(openapi::openapi_json));
+
+    Router::new()
         .nest("/backups", backups::routes())
+        .nest("/events", events::routes())
         .nest("/filesystem", filesystem::routes())
+        .nest("/hooks", hooks::routes())
+        .nest("/inbox", inbox::routes())
+        .nest("/ip-bans", ip_bans::routes())
+        .nest("/outgoing-webhooks", outgoing_webhooks::routes())
+        .nest("/role-templates", role_templates::routes())
+        .nest("/server-templates", server_templates::routes())
         .nest("/servers", servers::routes())
         .nest("/settings", settings::routes())
-        .nest("/setup", setup::routes())
         .nest("/system", system::routes())
+        .nest("/tokens", tokens::routes())
         .nest("/upload", upload::routes())
         .nest("/users", users::routes())
         .nest("/webhook", webhook::routes())
         .route("/ws/console/:id", get(console::ws_handler))
+        .route_layer(middleware::from_fn_with_state(state.clone(), access::require_server_access))
+        .route_layer(middleware::from_fn(access::block_read_only_writes))
+        .route_layer(middleware::from_fn_with_state(state, auth::require_auth))
+        .merge(public)
+        .layer(middleware::from_fn(visibility::shape_response))
+        .layer(middleware::from_fn(http_cache::invalidate_on_write))
 }
@@ -0,0 +1,283 @@
+//! Single sign-on endpoints under `/auth/oidc`: the login page asks `/config` whether to show the
+//! SSO button, `/login` sends the browser to the provider and `/callback` brings it back to
+//! `/login` with a panel session in the URL fragment (`#sso_token=` or `#sso_error=`). Users with
+//! two-factor authentication come back with `#sso_2fa=` instead, a ticket to post with their
+//! code to `/2fa`.
+
+use std::net::SocketAddr;
+
+use axum::{
+    extract::{ConnectInfo, Query, State},
+    http::{header, HeaderMap},
+    response::{IntoResponse, Redirect, Response},
+    routing::{get, post},
+    Json, Router,
+};
+use chrono::Utc;
+use serde::Deserialize;
+use uuid::Uuid;
+use utoipa::{IntoParams, OpenApi, ToSchema};
+
+use crate::api::auth::{second_factor_login, two_factor_enabled, AuthResponse, UserRow};
+use crate::db::DbPool;
+use crate::error::AppError;
+use crate::services::audit;
+use crate::services::oidc::{self, Identity, OidcConfig};
+use crate::services::sessions::Client;
+use crate::AppState;
+
+const USER_COLUMNS: &str = "id, username, password_hash, role, accent_color";
+/// Holds the `state` of the login the browser started, so a callback carrying someone else's
+/// state (login CSRF) is refused
+const STATE_COOKIE: &str = "draveur_oidc_state";
+const COOKIE_PATH: &str = "/api/v1/auth/oidc";
+
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/config", get(sso_config))
+        .route("/login", get(start_login))
+        .route("/callback", get(callback))
+        .route("/2fa", post(second_factor))
+}
+
+#[derive(OpenApi)]
+#[openapi(paths(sso_config, start_login, callback, second_factor))]
+pub(super) struct ApiDoc;
+
+fn state_cookie(value: &str, max_age_secs: u64, secure: bool) -> String {
+    format!(
+        "{}={}; Path={}; Max-Age={}; HttpOnly; SameSite=Lax{}",
+        STATE_COOKIE, value, COOKIE_PATH, max_age_secs, if secure { "; Secure" } else { "" }
+    )
+}
+
+fn cookie_state(headers: &HeaderMap) -> Option<&str> {
+    headers
+        .get_all(header::COOKIE)
+        .iter()
+        .filter_map(|h| h.to_str().ok())
+        .flat_map(|h| h.split(';'))
+        .filter_map(|c| c.trim().split_once('='))
+        .find(|(name, _)| *name == STATE_COOKIE)
+        .map(|(_, value)| value)
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+struct CallbackQuery {
+    code: Option<String>,
+    state: Option<String>,
+    error: Option<String>,
+}
+
+fn back_to_login(fragment: &str, value: &str) -> Redirect {
+    Redirect::to(&format!("/login#{}={}", fragment, value))
+}
+
+#[utoipa::path(get, path = "/auth/oidc/config", tag = "auth", security(()), responses((status = 200, body = serde_json::Value)))]
+async fn sso_config(State(state): State<AppState>) -> Json<serde_json::Value> {
+    let enabled = oidc::config(&state.pool).await.is_some();
+    Json(serde_json::json!({ "enabled": enabled }))
+}
+
+#[utoipa::path(get, path = "/auth/oidc/login", tag = "auth", security(()), responses((status = 303, description = "Redirect to the identity provider, setting the login's state cookie")))]
+async fn start_login(State(state): State<AppState>) -> Result<Response, AppError> {
+    let config = oidc::config(&state.pool)
+        .await
+        .ok_or_else(|| AppError::NotFound("auth.oidc_disabled".into()))?;
+    let (url, login_state) = oidc::authorization_url(&config).await.map_err(|e| {
+        tracing::warn!("OIDC login could not start: {:#}", e);
+        AppError::Internal(format!("OIDC provider unavailable: {}", e))
+    })?;
+    let secure = config.redirect_url.starts_with("https://");
+    let cookie = state_cookie(&login_state, oidc::LOGIN_TTL.as_secs(), secure);
+    Ok(([(header::SET_COOKIE, cookie)], Redirect::to(&url)).into_response())
+}
+
+#[utoipa::path(get, path = "/auth/oidc/callback", tag = "auth", security(()), params(CallbackQuery), responses((status = 303, description = "Redirect to `/login` with `#sso_token=`, `#sso_2fa=` or `#sso_error=`")))]
+async fn callback(
+    State(state): State<AppState>,
+    connect_info: Option<ConnectInfo<SocketAddr>>,
+    headers: HeaderMap,
+    Query(query): Query<CallbackQuery>,
+) -> Response {
+    // The state is good for one callback
+    let clear = state_cookie("", 0, false);
+    let redirect = sso_redirect(&state, connect_info, &headers, query).await;
+    ([(header::SET_COOKIE, clear)], redirect).into_response()
+}
+
+async fn sso_redirect(
+    state: &AppState,
+    connect_info: Option<ConnectInfo<SocketAddr>>,
+    headers: &HeaderMap,
+    query: CallbackQuery,
+) -> Redirect {
+    let Some(config) = oidc::config(&state.pool).await else {
+        return back_to_login("sso_error", "auth.oidc_disabled");
+    };
+    if let Some(error) = query.error {
+        tracing::info!("OIDC provider refused the login: {}", error);
+        return back_to_login("sso_error", "auth.oidc_failed");
+    }
+    let (Some(code), Some(login_state)) = (query.code, query.state) else {
+        return back_to_login("sso_error", "auth.oidc_failed");
+    };
+    if cookie_state(headers) != Some(login_state.as_str()) {
+        tracing::warn!("OIDC callback without the state cookie of its login");
+        return back_to_login("sso_error", "auth.oidc_failed");
+    }
+
+    let identity = match oidc::complete(&config, &code, &login_state).await {
+        Ok(identity) => identity,
+        Err(e) => {
+            tracing::warn!("OIDC login failed: {:#}", e);
+            return back_to_login("sso_error", "auth.oidc_failed");
+        }
+    };
+
+    let client = Client::from_request(headers, connect_info.as_ref());
+    let login = match panel_user(&state.pool, &config, &identity).await {
+        Ok(user) => sso_login(&state.pool, user, &client).await,
+        Err(e) => Err(e),
+    };
+    match login {
+        Ok(SsoLogin::Session(token)) => back_to_login("sso_token", &token),
+        Ok(SsoLogin::SecondFactor(ticket)) => back_to_login("sso_2fa", &ticket),
+        Err(AppError::BadRequest(key)) | Err(AppError::Unauthorized(key)) => back_to_login("sso_error", &key),
+        Err(e) => {
+            tracing::error!("OIDC login failed: {}", e);
+            back_to_login("sso_error", "auth.oidc_failed")
+        }
+    }
+}
+
+enum SsoLogin {
+    Session(String),
+    /// Ticket of a login waiting for the user's authenticator code
+    SecondFactor(String),
+}
+
+/// The provider vouched for the user, the panel's own second factor policy still applies
+async fn sso_login(pool: &DbPool, user: UserRow, client: &Client) -> Result<SsoLogin, AppError> {
+    if two_factor_enabled(pool, &user.id).await? {
+        return Ok(SsoLogin::SecondFactor(oidc::hold_for_second_factor(&user.id)));
+    }
+    let (id, username) = (user.id.clone(), user.username.clone());
+    let response = second_factor_login(pool, user, None, client).await?;
+    audit::record(pool, Some(&id), Some(&username), audit::ACTION_LOGIN, Some("sso"), client.ip.as_deref()).await;
+    Ok(SsoLogin::Session(response.token))
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct SsoSecondFactorRequest {
+    /// From the `#sso_2fa=` fragment
+    pub ticket: String,
+    /// Authenticator code or backup code
+    pub code: String,
+}
+
+/// Finish an SSO login of a user with two-factor authentication
+#[utoipa::path(
+    post, path = "/auth/oidc/2fa", tag = "auth", security(()),
+    request_body = SsoSecondFactorRequest,
+    responses((status = 200, body = AuthResponse), (status = 401, description = "`auth.invalid_totp`, or `auth.oidc_failed` once the login expired"))
+)]
+async fn second_factor(
+    State(state): State<AppState>,
+    connect_info: Option<ConnectInfo<SocketAddr>>,
+    headers: HeaderMap,
+    Json(body): Json<SsoSecondFactorRequest>,
+) -> Result<Json<AuthResponse>, AppError> {
+    let user_id = oidc::second_factor_attempt(&body.ticket)
+        .ok_or_else(|| AppError::Unauthorized("auth.oidc_failed".into()))?;
+    let user: UserRow = sqlx::query_as(&format!("SELECT {} FROM users WHERE id = ?", USER_COLUMNS))
+        .bind(&user_id)
+        .fetch_optional(&state.pool)
+        .await?
+        .ok_or_else(|| AppError::Unauthorized("auth.oidc_failed".into()))?;
+
+    let client = Client::from_request(&headers, connect_info.as_ref());
+    let (id, username) = (user.id.clone(), user.username.clone());
+    let response = second_factor_login(&state.pool, user, Some(&body.code), &client).await?;
+    oidc::release_second_factor(&body.ticket);
+    audit::record(&state.pool, Some(&id), Some(&username), audit::ACTION_LOGIN, Some("sso"), client.ip.as_deref()).await;
+    Ok(Json(response))
+}
+
+/// Account of an identity: the one it logged in with before, else a local account it may be
+/// linked to, else a new one with the default role
+async fn panel_user(pool: &DbPool, config: &OidcConfig, identity: &Identity) -> Result<UserRow, AppError> {
+    let known: Option<UserRow> = sqlx::query_as(&format!("SELECT {} FROM users WHERE oidc_subject = ?", USER_COLUMNS))
+        .bind(&identity.subject)
+        .fetch_optional(pool)
+        .await?;
+    if let Some(user) = known {
+        return Ok(user);
+    }
+
+    let candidates: Vec<&str> = [identity.username.as_deref(), identity.email.as_deref()].into_iter().flatten().collect();
+    for name in &candidates {
+        let local: Option<(String, Option<String>)> = sqlx::query_as(
+            "SELECT id, oidc_subject FROM users WHERE username = ? COLLATE NOCASE"
+        )
+        .bind(name)
+        .fetch_optional(pool)
+        .await?;
+        let Some((id, subject)) = local else {
+            continue;
+        };
+        // Anyone able to pick a matching name at the provider would take the account over
+        if subject.is_some() || !config.link_existing {
+            return Err(AppError::Unauthorized("auth.oidc_account_exists".into()));
+        }
+        sqlx::query("UPDATE users SET oidc_subject = ?, updated_at = ? WHERE id = ?")
+            .bind(&identity.subject)
+            .bind(Utc::now().to_rfc3339())
+            .bind(&id)
+            .execute(pool)
+            .await?;
+        let user: UserRow = sqlx::query_as(&format!("SELECT {} FROM users WHERE id = ?", USER_COLUMNS))
+            .bind(&id)
+            .fetch_one(pool)
+            .await?;
+        let detail = format!("subject {}", identity.subject);
+        audit::record(pool, Some(&user.id), Some(&user.username), audit::ACTION_SSO_LINKED, Some(&detail), None).await;
+        return Ok(user);
+    }
+
+    let username = candidates.first().map(|n| n.to_string())
+        .unwrap_or_else(|| format!("sso-{}", identity.subject.chars().take(12).collect::<String>()));
+    let default_color: Option<String> = sqlx::query_scalar("SELECT value FROM settings WHERE key = 'login_default_color'")
+        .fetch_optional(pool)
+        .await?;
+    // No local password: the account only logs in through the provider (or a reset link)
+    let password_hash = bcrypt::hash(Uuid::new_v4().to_string(), bcrypt::DEFAULT_COST)
+        .map_err(|_| AppError::Internal("Password hashing failed".into()))?;
+    let user = UserRow {
+        id: Uuid::new_v4().to_string(),
+        username,
+        password_hash,
+        role: config.default_role.clone(),
+        accent_color: Some(default_color.unwrap_or_else(|| "#3A82F6".to_string())),
+    };
+    let now = Utc::now().to_rfc3339();
+
+    sqlx::query(
+        "INSERT INTO users (id, username, password_hash, role, accent_color, oidc_subject, created_at, updated_at) VALUES (?, ?, ?, ?, ?, ?, ?, ?)",
+    )
+    .bind(&user.id)
+    .bind(&user.username)
+    .bind(&user.password_hash)
+    .bind(&user.role)
+    .bind(&user.accent_color)
+    .bind(&identity.subject)
+    .bind(&now)
+    .bind(&now)
+    .execute(pool)
+    .await?;
+
+    let detail = format!("subject {}, role {}", identity.subject, user.role);
+    audit::record(pool, Some(&user.id), Some(&user.username), audit::ACTION_SSO_PROVISIONED, Some(&detail), None).await;
+    Ok(user)
+}
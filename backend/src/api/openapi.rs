@@ -0,0 +1,94 @@
+//! OpenAPI description of the v1 API, generated from the handler annotations, and the Swagger
+//! UI browsing it at `/api-docs`.
+
+use axum::{Json, Router};
+use utoipa::openapi::security::{HttpAuthScheme, HttpBuilder, SecurityScheme};
+use utoipa::{Modify, OpenApi};
+use utoipa_swagger_ui::{Config, SwaggerUi};
+
+use crate::AppState;
+
+#[derive(OpenApi)]
+#[openapi(
+    info(
+        title = "Draveur Manager API",
+        description = "Game server panel API. Failed requests answer an `ErrorBody`: a stable `code` to branch on \
+                       (`SERVER_NOT_FOUND`), the translation `key` the frontend displays (`servers.not_found`) and \
+                       an optional `detail`."
+    ),
+    components(schemas(crate::error::ErrorBody, crate::error::ErrorCode)),
+    servers((url = "/api/v1")),
+    security(("bearer" = [])),
+    modifiers(&BearerAuth),
+    tags(
+        (name = "auth", description = "Login, second factor, sessions and the caller's account"),
+        (name = "setup", description = "First run of the panel"),
+        (name = "users"),
+        (name = "servers", description = "Servers, their lifecycle, access and console"),
+        (name = "files", description = "Files inside server directories"),
+        (name = "backups"),
+        (name = "hooks", description = "Inbound webhooks running server actions, outgoing webhooks receiving events"),
+        (name = "tokens", description = "API tokens"),
+        (name = "system", description = "Host, announcements, audit log and panel database"),
+        (name = "settings"),
+        (name = "ip-bans"),
+        (name = "role-templates"),
+    )
+)]
+pub struct ApiDoc;
+
+/// Sessions and API tokens are both sent as `Authorization: Bearer`
+struct BearerAuth;
+
+impl Modify for BearerAuth {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        let components = openapi.components.get_or_insert_with(Default::default);
+        let scheme = HttpBuilder::new()
+            .scheme(HttpAuthScheme::Bearer)
+            .description(Some("Session JWT from `/auth/login`, or an API token"))
+            .build();
+        components.add_security_scheme("bearer", SecurityScheme::Http(scheme));
+    }
+}
+
+/// The whole specification, each module documenting its own routes
+pub fn spec() -> utoipa::openapi::OpenApi {
+    let mut doc = ApiDoc::openapi();
+    for part in [
+        super::account::ApiDoc::openapi(),
+        super::auth::ApiDoc::openapi(),
+        super::backups::ApiDoc::openapi(),
+        super::console::ApiDoc::openapi(),
+        super::events::ApiDoc::openapi(),
+        super::filesystem::ApiDoc::openapi(),
+        super::hooks::ApiDoc::openapi(),
+        super::inbox::ApiDoc::openapi(),
+        super::ip_bans::ApiDoc::openapi(),
+        super::oidc::ApiDoc::openapi(),
+        super::outgoing_webhooks::ApiDoc::openapi(),
+        super::role_templates::ApiDoc::openapi(),
+        super::server_templates::ApiDoc::openapi(),
+        super::servers::ApiDoc::openapi(),
+        super::settings::ApiDoc::openapi(),
+        super::setup::ApiDoc::openapi(),
+        super::system::ApiDoc::openapi(),
+        super::tokens::ApiDoc::openapi(),
+        super::upload::ApiDoc::openapi(),
+        super::users::ApiDoc::openapi(),
+        super::webhook::ApiDoc::openapi(),
+    ] {
+        doc.merge(part);
+    }
+    doc
+}
+
+pub async fn openapi_json() -> Json<utoipa::openapi::OpenApi> {
+    Json(spec())
+}
+
+/// Swagger UI, served outside `/api/v1` so its assets skip the API middleware
+pub fn swagger_ui() -> Router<AppState> {
+    SwaggerUi::new("/api-docs")
+        .config(Config::from("/api/v1/openapi.json"))
+        .into()
+}
@@ -0,0 +1,266 @@
+//! Admin management of outgoing webhooks (see `services::outgoing_webhooks`): the URL, the
+//! events it receives and optionally the one server it is limited to. The signing secret is
+//! only shown on creation and rotation.
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    routing::{get, patch, post},
+    Json, Router,
+};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use utoipa::{OpenApi, ToSchema};
+
+use crate::api::auth::AuthUser;
+use crate::db::DbPool;
+use crate::error::AppError;
+use crate::services::outgoing_webhooks::{self, WebhookRow};
+use crate::AppState;
+
+/// Distinguishes outgoing webhook secrets from inbound hook secrets and API tokens
+const SECRET_PREFIX: &str = "dwo_";
+
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/", get(list_webhooks).post(create_webhook))
+        .route("/:id", patch(update_webhook).delete(delete_webhook))
+        .route("/:id/rotate", post(rotate_secret))
+        .route("/:id/test", post(test_webhook))
+}
+
+#[derive(OpenApi)]
+#[openapi(paths(list_webhooks, create_webhook, update_webhook, delete_webhook, rotate_secret, test_webhook))]
+pub(super) struct ApiDoc;
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct WebhookResponse {
+    pub id: String,
+    pub name: String,
+    pub url: String,
+    pub events: Vec<String>,
+    /// Every server when absent
+    pub server_id: Option<String>,
+    pub enabled: bool,
+    pub created_by: String,
+    pub created_at: String,
+    pub last_delivery_at: Option<String>,
+    /// HTTP status of the last delivery, or `error: <reason>`
+    pub last_delivery_status: Option<String>,
+    /// Only returned when the webhook is created and when its secret is rotated
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub secret: Option<String>,
+}
+
+impl From<WebhookRow> for WebhookResponse {
+    fn from(row: WebhookRow) -> Self {
+        Self {
+            events: row.events(),
+            id: row.id,
+            name: row.name,
+            url: row.url,
+            server_id: row.server_id,
+            enabled: row.enabled != 0,
+            created_by: row.created_by,
+            created_at: row.created_at,
+            last_delivery_at: row.last_delivery_at,
+            last_delivery_status: row.last_delivery_status,
+            secret: None,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct CreateWebhookRequest {
+    pub name: String,
+    /// `http` or `https` URL receiving the events
+    pub url: String,
+    /// Names from `server.started`, `server.stopped`, `server.crashed`, `backup.completed`,
+    /// `backup.failed`, `player.joined` and `player.left`
+    pub events: Vec<String>,
+    /// Limit the webhook to the events of one server
+    pub server_id: Option<String>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct UpdateWebhookRequest {
+    pub name: Option<String>,
+    pub url: Option<String>,
+    pub events: Option<Vec<String>>,
+    pub enabled: Option<bool>,
+}
+
+fn new_secret() -> String {
+    format!("{}{}{}", SECRET_PREFIX, Uuid::new_v4().simple(), Uuid::new_v4().simple())
+}
+
+fn validated_url(url: &str) -> Result<&str, AppError> {
+    let url = url.trim();
+    match reqwest::Url::parse(url) {
+        Ok(parsed) if matches!(parsed.scheme(), "http" | "https") && parsed.has_host() => Ok(url),
+        _ => Err(AppError::BadRequest("outgoing_webhooks.invalid_url".into())),
+    }
+}
+
+fn validated_events(events: &[String]) -> Result<String, AppError> {
+    let mut events: Vec<&str> = events.iter().map(|e| e.trim()).collect();
+    if events.iter().any(|e| !outgoing_webhooks::EVENTS.contains(e)) {
+        return Err(AppError::BadRequest("outgoing_webhooks.invalid_event".into()));
+    }
+    events.sort_unstable();
+    events.dedup();
+    if events.is_empty() {
+        return Err(AppError::BadRequest("outgoing_webhooks.events_required".into()));
+    }
+    Ok(serde_json::to_string(&events).unwrap_or_else(|_| "[]".into()))
+}
+
+async fn find_webhook(pool: &DbPool, id: &str) -> Result<WebhookRow, AppError> {
+    sqlx::query_as("SELECT * FROM outgoing_webhooks WHERE id = ?")
+        .bind(id)
+        .fetch_optional(pool)
+        .await?
+        .ok_or_else(|| AppError::NotFound("outgoing_webhooks.not_found".into()))
+}
+
+#[utoipa::path(get, path = "/outgoing-webhooks", tag = "hooks", responses((status = 200, body = [WebhookResponse])))]
+async fn list_webhooks(
+    State(state): State<AppState>,
+    auth: AuthUser,
+) -> Result<Json<Vec<WebhookResponse>>, AppError> {
+    auth.require_admin_session()?;
+    let webhooks: Vec<WebhookRow> = sqlx::query_as("SELECT * FROM outgoing_webhooks ORDER BY created_at DESC")
+        .fetch_all(&state.pool)
+        .await?;
+    Ok(Json(webhooks.into_iter().map(WebhookResponse::from).collect()))
+}
+
+#[utoipa::path(post, path = "/outgoing-webhooks", tag = "hooks", request_body = CreateWebhookRequest, responses((status = 201, body = WebhookResponse)))]
+async fn create_webhook(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Json(body): Json<CreateWebhookRequest>,
+) -> Result<(StatusCode, Json<WebhookResponse>), AppError> {
+    auth.require_admin_session()?;
+    let name = body.name.trim();
+    if name.is_empty() {
+        return Err(AppError::BadRequest("outgoing_webhooks.name_required".into()));
+    }
+    let url = validated_url(&body.url)?;
+    let events = validated_events(&body.events)?;
+
+    let server_id = body.server_id.as_deref().map(str::trim).filter(|s| !s.is_empty());
+    if let Some(server_id) = server_id {
+        let exists: Option<String> = sqlx::query_scalar("SELECT id FROM servers WHERE id = ?")
+            .bind(server_id)
+            .fetch_optional(&state.pool)
+            .await?;
+        if exists.is_none() {
+            return Err(AppError::NotFound("servers.not_found".into()));
+        }
+    }
+
+    let id = Uuid::new_v4().to_string();
+    let secret = new_secret();
+    sqlx::query(
+        "INSERT INTO outgoing_webhooks (id, name, url, secret, events, server_id, enabled, created_by, created_at)
+         VALUES (?, ?, ?, ?, ?, ?, 1, ?, ?)",
+    )
+    .bind(&id)
+    .bind(name)
+    .bind(url)
+    .bind(&secret)
+    .bind(&events)
+    .bind(server_id)
+    .bind(&auth.id)
+    .bind(Utc::now().to_rfc3339())
+    .execute(&state.pool)
+    .await?;
+
+    let mut response = WebhookResponse::from(find_webhook(&state.pool, &id).await?);
+    response.secret = Some(secret);
+    Ok((StatusCode::CREATED, Json(response)))
+}
+
+#[utoipa::path(patch, path = "/outgoing-webhooks/{id}", tag = "hooks", params(("id" = String, Path)), request_body = UpdateWebhookRequest, responses((status = 200, body = WebhookResponse)))]
+async fn update_webhook(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(id): Path<String>,
+    Json(body): Json<UpdateWebhookRequest>,
+) -> Result<Json<WebhookResponse>, AppError> {
+    auth.require_admin_session()?;
+    find_webhook(&state.pool, &id).await?;
+
+    let name = body.name.as_deref().map(str::trim).filter(|n| !n.is_empty());
+    let url = body.url.as_deref().map(validated_url).transpose()?;
+    let events = body.events.as_deref().map(validated_events).transpose()?;
+
+    sqlx::query(
+        "UPDATE outgoing_webhooks SET name = COALESCE(?, name), url = COALESCE(?, url),
+         events = COALESCE(?, events), enabled = COALESCE(?, enabled) WHERE id = ?",
+    )
+    .bind(name)
+    .bind(url)
+    .bind(events)
+    .bind(body.enabled)
+    .bind(&id)
+    .execute(&state.pool)
+    .await?;
+
+    Ok(Json(find_webhook(&state.pool, &id).await?.into()))
+}
+
+#[utoipa::path(delete, path = "/outgoing-webhooks/{id}", tag = "hooks", params(("id" = String, Path)), responses((status = 200, body = serde_json::Value)))]
+async fn delete_webhook(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(id): Path<String>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    auth.require_admin_session()?;
+    find_webhook(&state.pool, &id).await?;
+
+    sqlx::query("DELETE FROM outgoing_webhooks WHERE id = ?").bind(&id).execute(&state.pool).await?;
+    Ok(Json(serde_json::json!({ "success": true })))
+}
+
+/// Replace the secret, deliveries are signed with the new one at once
+#[utoipa::path(post, path = "/outgoing-webhooks/{id}/rotate", tag = "hooks", params(("id" = String, Path)), responses((status = 200, body = WebhookResponse)))]
+async fn rotate_secret(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(id): Path<String>,
+) -> Result<Json<WebhookResponse>, AppError> {
+    auth.require_admin_session()?;
+    find_webhook(&state.pool, &id).await?;
+
+    let secret = new_secret();
+    sqlx::query("UPDATE outgoing_webhooks SET secret = ? WHERE id = ?")
+        .bind(&secret)
+        .bind(&id)
+        .execute(&state.pool)
+        .await?;
+
+    let mut response = WebhookResponse::from(find_webhook(&state.pool, &id).await?);
+    response.secret = Some(secret);
+    Ok(Json(response))
+}
+
+/// Deliver a `test` event now, even to a disabled webhook, and report how the receiver answered
+#[utoipa::path(post, path = "/outgoing-webhooks/{id}/test", tag = "hooks", params(("id" = String, Path)), responses((status = 200, body = serde_json::Value)))]
+async fn test_webhook(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(id): Path<String>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    auth.require_admin_session()?;
+    let webhook = find_webhook(&state.pool, &id).await?;
+
+    let data = serde_json::json!({ "triggered_by": auth.username });
+    let payload = outgoing_webhooks::payload(&state.pool, outgoing_webhooks::EVENT_TEST, None, data).await;
+    Ok(Json(match outgoing_webhooks::deliver(&state.pool, &webhook, &payload).await {
+        Ok(status) => serde_json::json!({ "success": (200..300).contains(&status), "status": status }),
+        Err(error) => serde_json::json!({ "success": false, "error": error }),
+    }))
+}
@@ -0,0 +1,73 @@
+//! Middleware counting login, file write and console command requests against the
+//! `services::rate_limit` buckets, answering 429 with `Retry-After` once a client runs dry.
+
+use std::net::SocketAddr;
+
+use axum::{
+    extract::{ConnectInfo, Request, State},
+    http::{HeaderMap, HeaderValue, Method},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+
+use crate::api::auth::AuthUser;
+use crate::error::AppError;
+use crate::services::rate_limit::{Decision, BUCKET_AUTH, BUCKET_COMMANDS, BUCKET_FILES};
+use crate::AppState;
+
+/// Bucket of a request, `None` for the routes left unlimited
+fn bucket(method: &Method, path: &str) -> Option<&'static str> {
+    if *method == Method::GET || *method == Method::HEAD || *method == Method::OPTIONS {
+        return None;
+    }
+    let path = path.strip_prefix("/api/v1").unwrap_or(path);
+    let segments: Vec<&str> = path.trim_matches('/').split('/').collect();
+    match segments.as_slice() {
+        ["auth", ..] | ["setup"] => Some(BUCKET_AUTH),
+        ["servers", _, "files", ..] | ["servers", _, "install", "bundle"] | ["upload", ..] => Some(BUCKET_FILES),
+        ["servers", _, "command"] | ["servers", _, "command", "exec"] | ["hooks", _, "trigger"] => Some(BUCKET_COMMANDS),
+        _ => None,
+    }
+}
+
+/// API tokens have their own budget; everything else is counted per address
+fn client(request: &Request) -> Option<String> {
+    if let Some(token_id) = request.extensions().get::<AuthUser>().and_then(|u| u.api_token_id.as_deref()) {
+        return Some(format!("token:{}", token_id));
+    }
+    request.extensions()
+        .get::<ConnectInfo<SocketAddr>>()
+        .map(|ConnectInfo(addr)| format!("ip:{}", addr.ip()))
+}
+
+fn set_headers(headers: &mut HeaderMap, decision: &Decision) {
+    headers.insert("x-ratelimit-limit", HeaderValue::from(decision.limit));
+    headers.insert("x-ratelimit-remaining", HeaderValue::from(decision.remaining));
+    if !decision.allowed {
+        let secs = decision.retry_after.as_secs_f64().ceil().max(1.0) as u64;
+        headers.insert("retry-after", HeaderValue::from(secs));
+    }
+}
+
+/// Reject requests over their bucket's limit, runs after `tokens::authenticate`
+pub async fn limit(State(state): State<AppState>, request: Request, next: Next) -> Response {
+    let Some(bucket) = bucket(request.method(), request.uri().path()) else {
+        return next.run(request).await;
+    };
+    let Some(client) = client(&request) else {
+        return next.run(request).await;
+    };
+
+    let decision = state.rate_limiter.check(bucket, &client);
+    if decision.limit == 0 {
+        return next.run(request).await;
+    }
+    let mut response = if decision.allowed {
+        next.run(request).await
+    } else {
+        tracing::warn!("Rate limit of {} reached by {}", bucket, client);
+        AppError::TooManyRequests("errors.rate_limited".into()).into_response()
+    };
+    set_headers(response.headers_mut(), &decision);
+    response
+}
@@ -0,0 +1,202 @@
+//! Role templates: named sets of per-server permissions ("Moderator" = console view and
+//! commands) that admins define once and assign to users on each server. Editing a template
+//! changes the access of everyone it is assigned to.
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    routing::{get, put},
+    Json, Router,
+};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+use utoipa::{OpenApi, ToSchema};
+
+use crate::api::auth::AuthUser;
+use crate::db::DbPool;
+use crate::error::AppError;
+use crate::services::server_access;
+use crate::AppState;
+
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/", get(list_templates).post(create_template))
+        .route("/:id", put(update_template).delete(delete_template))
+}
+
+#[derive(OpenApi)]
+#[openapi(paths(list_templates, create_template, update_template, delete_template))]
+pub(super) struct ApiDoc;
+
+#[derive(Debug, FromRow)]
+struct TemplateRow {
+    id: String,
+    name: String,
+    description: Option<String>,
+    permissions: String,
+    created_at: String,
+    updated_at: String,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct RoleTemplate {
+    pub id: String,
+    pub name: String,
+    pub description: Option<String>,
+    pub permissions: Vec<String>,
+    /// Server and user pairs it is assigned to
+    pub assignments: i64,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct TemplateRequest {
+    pub name: Option<String>,
+    pub description: Option<String>,
+    /// Names from `server_access::PERMISSIONS`
+    pub permissions: Option<Vec<String>>,
+}
+
+
+fn validated_permissions(permissions: Vec<String>) -> Result<Vec<String>, AppError> {
+    server_access::normalize_permissions(permissions).map_err(|unknown| {
+        tracing::info!("Rejected unknown server permission {:?}", unknown);
+        AppError::BadRequest("servers.invalid_permission".into())
+    })
+}
+
+fn validated_name(name: &str) -> Result<&str, AppError> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(AppError::BadRequest("role_templates.name_required".into()));
+    }
+    Ok(name)
+}
+
+async fn fetch_template(pool: &DbPool, id: &str) -> Result<RoleTemplate, AppError> {
+    let row: TemplateRow = sqlx::query_as("SELECT * FROM role_templates WHERE id = ?")
+        .bind(id)
+        .fetch_optional(pool)
+        .await?
+        .ok_or_else(|| AppError::NotFound("role_templates.not_found".into()))?;
+    let assignments: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM server_role_assignments WHERE template_id = ?")
+        .bind(id)
+        .fetch_one(pool)
+        .await?;
+    Ok(RoleTemplate {
+        id: row.id,
+        name: row.name,
+        description: row.description,
+        permissions: serde_json::from_str(&row.permissions).unwrap_or_default(),
+        assignments,
+        created_at: row.created_at,
+        updated_at: row.updated_at,
+    })
+}
+
+/// Unique names are enforced by the table, case-insensitively
+fn name_taken(e: sqlx::Error) -> AppError {
+    match e {
+        sqlx::Error::Database(ref db) if db.is_unique_violation() => AppError::Conflict("role_templates.name_taken".into()),
+        e => e.into(),
+    }
+}
+
+#[utoipa::path(get, path = "/role-templates", tag = "role-templates", responses((status = 200, body = [RoleTemplate])))]
+async fn list_templates(State(state): State<AppState>, auth: AuthUser) -> Result<Json<Vec<RoleTemplate>>, AppError> {
+    auth.require_admin()?;
+    let ids: Vec<String> = sqlx::query_scalar("SELECT id FROM role_templates ORDER BY name")
+        .fetch_all(&state.pool)
+        .await?;
+    let mut templates = Vec::with_capacity(ids.len());
+    for id in ids {
+        templates.push(fetch_template(&state.pool, &id).await?);
+    }
+    Ok(Json(templates))
+}
+
+#[utoipa::path(post, path = "/role-templates", tag = "role-templates", request_body = TemplateRequest, responses((status = 201, body = RoleTemplate), (status = 409)))]
+async fn create_template(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Json(body): Json<TemplateRequest>,
+) -> Result<(StatusCode, Json<RoleTemplate>), AppError> {
+    auth.require_admin()?;
+    let name = validated_name(body.name.as_deref().unwrap_or_default())?;
+    let permissions = validated_permissions(body.permissions.unwrap_or_default())?;
+    let description = body.description.as_deref().map(str::trim).filter(|d| !d.is_empty());
+
+    let id = Uuid::new_v4().to_string();
+    let now = Utc::now().to_rfc3339();
+    sqlx::query(
+        "INSERT INTO role_templates (id, name, description, permissions, created_at, updated_at) VALUES (?, ?, ?, ?, ?, ?)"
+    )
+    .bind(&id)
+    .bind(name)
+    .bind(description)
+    .bind(serde_json::to_string(&permissions).unwrap_or_else(|_| "[]".into()))
+    .bind(&now)
+    .bind(&now)
+    .execute(&state.pool)
+    .await
+    .map_err(name_taken)?;
+
+    Ok((StatusCode::CREATED, Json(fetch_template(&state.pool, &id).await?)))
+}
+
+#[utoipa::path(put, path = "/role-templates/{id}", tag = "role-templates", params(("id" = String, Path)), request_body = TemplateRequest, responses((status = 200, body = RoleTemplate)))]
+async fn update_template(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(id): Path<String>,
+    Json(body): Json<TemplateRequest>,
+) -> Result<Json<RoleTemplate>, AppError> {
+    auth.require_admin()?;
+    let current = fetch_template(&state.pool, &id).await?;
+
+    let name = match body.name.as_deref() {
+        Some(name) => validated_name(name)?.to_string(),
+        None => current.name,
+    };
+    let permissions = match body.permissions {
+        Some(permissions) => validated_permissions(permissions)?,
+        None => current.permissions,
+    };
+    let description = match body.description.as_deref() {
+        Some(description) => Some(description.trim()).filter(|d| !d.is_empty()).map(str::to_string),
+        None => current.description,
+    };
+
+    sqlx::query("UPDATE role_templates SET name = ?, description = ?, permissions = ?, updated_at = ? WHERE id = ?")
+        .bind(&name)
+        .bind(&description)
+        .bind(serde_json::to_string(&permissions).unwrap_or_else(|_| "[]".into()))
+        .bind(Utc::now().to_rfc3339())
+        .bind(&id)
+        .execute(&state.pool)
+        .await
+        .map_err(name_taken)?;
+
+    Ok(Json(fetch_template(&state.pool, &id).await?))
+}
+
+/// Delete a template, along with its assignments
+#[utoipa::path(delete, path = "/role-templates/{id}", tag = "role-templates", params(("id" = String, Path)), responses((status = 200, body = serde_json::Value), (status = 404)))]
+async fn delete_template(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(id): Path<String>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    auth.require_admin()?;
+    let result = sqlx::query("DELETE FROM role_templates WHERE id = ?")
+        .bind(&id)
+        .execute(&state.pool)
+        .await?;
+    if result.rows_affected() == 0 {
+        return Err(AppError::NotFound("role_templates.not_found".into()));
+    }
+    Ok(Json(serde_json::json!({ "success": true })))
+}
@@ -0,0 +1,231 @@
+//! Server templates: presets of creation settings (game, memory, extra args, backup policy,
+//! Discord identity, auth mode...) that admins define once, so a server is created from just a
+//! name and a `template_id`. Settings are copied at creation, editing a template leaves
+//! existing servers alone.
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    routing::{get, put},
+    Json, Router,
+};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use sqlx::FromRow;
+use uuid::Uuid;
+use utoipa::{OpenApi, ToSchema};
+
+use crate::api::auth::AuthUser;
+use crate::api::servers::models::CreateServerRequest;
+use crate::db::DbPool;
+use crate::error::AppError;
+use crate::AppState;
+
+/// Fields identifying a server, never part of a preset
+const EXCLUDED_FIELDS: &[&str] = &["name", "working_dir", "template_id"];
+
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/", get(list_templates).post(create_template))
+        .route("/:id", put(update_template).delete(delete_template))
+}
+
+#[derive(OpenApi)]
+#[openapi(paths(list_templates, create_template, update_template, delete_template))]
+pub(super) struct ApiDoc;
+
+#[derive(Debug, FromRow)]
+struct TemplateRow {
+    id: String,
+    name: String,
+    description: Option<String>,
+    settings: String,
+    created_at: String,
+    updated_at: String,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ServerTemplate {
+    pub id: String,
+    pub name: String,
+    pub description: Option<String>,
+    /// Fields of a server creation request, e.g. `{"game_type": "hytale", "max_memory": "6G"}`
+    #[schema(value_type = Object)]
+    pub settings: Map<String, Value>,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct TemplateRequest {
+    pub name: Option<String>,
+    pub description: Option<String>,
+    #[schema(value_type = Option<Object>)]
+    pub settings: Option<Value>,
+}
+
+impl From<TemplateRow> for ServerTemplate {
+    fn from(row: TemplateRow) -> Self {
+        Self {
+            id: row.id,
+            name: row.name,
+            description: row.description,
+            settings: serde_json::from_str(&row.settings).unwrap_or_default(),
+            created_at: row.created_at,
+            updated_at: row.updated_at,
+        }
+    }
+}
+
+
+fn validated_name(name: &str) -> Result<&str, AppError> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(AppError::BadRequest("server_templates.name_required".into()));
+    }
+    Ok(name)
+}
+
+/// The preset without identifying or empty fields, rejected unless its values fit a creation
+/// request
+fn validated_settings(settings: Value) -> Result<Map<String, Value>, AppError> {
+    let Value::Object(mut settings) = settings else {
+        return Err(AppError::BadRequest("server_templates.invalid_settings".into()));
+    };
+    settings.retain(|key, value| !EXCLUDED_FIELDS.contains(&key.as_str()) && !value.is_null());
+
+    let mut probe = settings.clone();
+    for field in ["name", "game_type", "executable_path", "working_dir"] {
+        probe.entry(field).or_insert_with(|| Value::String(String::new()));
+    }
+    serde_json::from_value::<CreateServerRequest>(Value::Object(probe))
+        .map_err(|_| AppError::BadRequest("server_templates.invalid_settings".into()))?;
+    Ok(settings)
+}
+
+async fn fetch_template(pool: &DbPool, id: &str) -> Result<ServerTemplate, AppError> {
+    let row: TemplateRow = sqlx::query_as("SELECT * FROM server_templates WHERE id = ?")
+        .bind(id)
+        .fetch_optional(pool)
+        .await?
+        .ok_or_else(|| AppError::NotFound("server_templates.not_found".into()))?;
+    Ok(row.into())
+}
+
+/// Fill the fields a creation request leaves out or null with those of its `template_id`
+pub async fn apply(pool: &DbPool, request: &mut Map<String, Value>) -> Result<(), AppError> {
+    let template_id = match request.remove("template_id") {
+        Some(Value::String(id)) => id,
+        None | Some(Value::Null) => return Ok(()),
+        Some(_) => return Err(AppError::BadRequest("server_templates.not_found".into())),
+    };
+    let template = fetch_template(pool, &template_id).await?;
+    for (key, value) in template.settings {
+        let field = request.entry(key).or_insert(Value::Null);
+        if field.is_null() {
+            *field = value;
+        }
+    }
+    Ok(())
+}
+
+/// Unique names are enforced by the table, case-insensitively
+fn name_taken(e: sqlx::Error) -> AppError {
+    match e {
+        sqlx::Error::Database(ref db) if db.is_unique_violation() => AppError::Conflict("server_templates.name_taken".into()),
+        e => e.into(),
+    }
+}
+
+#[utoipa::path(get, path = "/server-templates", tag = "servers", responses((status = 200, body = [ServerTemplate])))]
+async fn list_templates(State(state): State<AppState>, auth: AuthUser) -> Result<Json<Vec<ServerTemplate>>, AppError> {
+    auth.require_admin()?;
+    let rows: Vec<TemplateRow> = sqlx::query_as("SELECT * FROM server_templates ORDER BY name")
+        .fetch_all(&state.pool)
+        .await?;
+    Ok(Json(rows.into_iter().map(ServerTemplate::from).collect()))
+}
+
+#[utoipa::path(post, path = "/server-templates", tag = "servers", request_body = TemplateRequest, responses((status = 201, body = ServerTemplate), (status = 409)))]
+async fn create_template(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Json(body): Json<TemplateRequest>,
+) -> Result<(StatusCode, Json<ServerTemplate>), AppError> {
+    auth.require_admin()?;
+    let name = validated_name(body.name.as_deref().unwrap_or_default())?;
+    let settings = validated_settings(body.settings.unwrap_or_else(|| Value::Object(Map::new())))?;
+    let description = body.description.as_deref().map(str::trim).filter(|d| !d.is_empty());
+
+    let id = Uuid::new_v4().to_string();
+    let now = Utc::now().to_rfc3339();
+    sqlx::query(
+        "INSERT INTO server_templates (id, name, description, settings, created_at, updated_at) VALUES (?, ?, ?, ?, ?, ?)"
+    )
+    .bind(&id)
+    .bind(name)
+    .bind(description)
+    .bind(Value::Object(settings).to_string())
+    .bind(&now)
+    .bind(&now)
+    .execute(&state.pool)
+    .await
+    .map_err(name_taken)?;
+
+    Ok((StatusCode::CREATED, Json(fetch_template(&state.pool, &id).await?)))
+}
+
+/// Fields left out keep their value, `settings` replaces the whole preset
+#[utoipa::path(put, path = "/server-templates/{id}", tag = "servers", params(("id" = String, Path)), request_body = TemplateRequest, responses((status = 200, body = ServerTemplate)))]
+async fn update_template(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(id): Path<String>,
+    Json(body): Json<TemplateRequest>,
+) -> Result<Json<ServerTemplate>, AppError> {
+    auth.require_admin()?;
+    let current = fetch_template(&state.pool, &id).await?;
+
+    let name = match body.name.as_deref() {
+        Some(name) => validated_name(name)?.to_string(),
+        None => current.name,
+    };
+    let settings = match body.settings {
+        Some(settings) => validated_settings(settings)?,
+        None => current.settings,
+    };
+    let description = match body.description.as_deref() {
+        Some(description) => Some(description.trim()).filter(|d| !d.is_empty()).map(str::to_string),
+        None => current.description,
+    };
+
+    sqlx::query("UPDATE server_templates SET name = ?, description = ?, settings = ?, updated_at = ? WHERE id = ?")
+        .bind(&name)
+        .bind(&description)
+        .bind(Value::Object(settings).to_string())
+        .bind(Utc::now().to_rfc3339())
+        .bind(&id)
+        .execute(&state.pool)
+        .await
+        .map_err(name_taken)?;
+
+    Ok(Json(fetch_template(&state.pool, &id).await?))
+}
+
+#[utoipa::path(delete, path = "/server-templates/{id}", tag = "servers", params(("id" = String, Path)), responses((status = 200, body = serde_json::Value), (status = 404)))]
+async fn delete_template(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(id): Path<String>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    auth.require_admin()?;
+    let result = sqlx::query("DELETE FROM server_templates WHERE id = ?")
+        .bind(&id)
+        .execute(&state.pool)
+        .await?;
+    if result.rows_affected() == 0 {
+        return Err(AppError::NotFound("server_templates.not_found".into()));
+    }
+    Ok(Json(serde_json::json!({ "success": true })))
+}
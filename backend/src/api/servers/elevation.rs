@@ -0,0 +1,253 @@
+//! Break-glass access endpoints: request, approve/deny, revoke and list temporary admin rights
+//! on a server (see `services::elevation`).
+
+use axum::{
+    extract::{Path, State},
+    Json,
+};
+use chrono::Utc;
+use uuid::Uuid;
+
+use crate::api::auth::AuthUser;
+use crate::db::DbPool;
+use crate::error::AppError;
+use crate::services::elevation::{self, ELEVATION_ACTIVE, ELEVATION_DENIED, ELEVATION_PENDING, ELEVATION_REVOKED};
+use crate::services::{discord_service, history};
+use crate::AppState;
+use super::models::{ElevationGrant, ElevationRequest};
+use super::transfers::username;
+
+const GRANT_COLUMNS: &str =
+    "id, server_id, user_id, reason, duration_minutes, status, requested_at, decided_by, expires_at, resolved_at";
+
+#[utoipa::path(post, path = "/servers/{id}/elevation", tag = "servers", params(("id" = String, Path)), request_body = ElevationRequest, responses((status = 200, body = ElevationGrant)))]
+pub async fn request_elevation(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(id): Path<String>,
+    Json(body): Json<ElevationRequest>,
+) -> Result<Json<ElevationGrant>, AppError> {
+    let exists: Option<String> = sqlx::query_scalar("SELECT id FROM servers WHERE id = ?")
+        .bind(&id)
+        .fetch_optional(&state.pool)
+        .await?;
+    if exists.is_none() {
+        return Err(AppError::NotFound("servers.not_found".into()));
+    }
+
+    if auth.is_admin() {
+        return Err(AppError::BadRequest("elevation.already_admin".into()));
+    }
+    if auth.api_token_id.is_some() {
+        return Err(AppError::Unauthorized("tokens.session_required".into()));
+    }
+    if !(1..=elevation::MAX_MINUTES).contains(&body.minutes) {
+        return Err(AppError::BadRequest("elevation.invalid_duration".into()));
+    }
+    let reason = body.reason.trim();
+    if reason.is_empty() {
+        return Err(AppError::BadRequest("elevation.reason_required".into()));
+    }
+
+    let now = Utc::now();
+    let open: i64 = sqlx::query_scalar(
+        "SELECT COUNT(*) FROM elevation_grants WHERE server_id = ? AND user_id = ?
+         AND (status = ? OR (status = ? AND expires_at > ?))"
+    )
+    .bind(&id)
+    .bind(&auth.id)
+    .bind(ELEVATION_PENDING)
+    .bind(ELEVATION_ACTIVE)
+    .bind(now.to_rfc3339())
+    .fetch_one(&state.pool)
+    .await?;
+    if open > 0 {
+        return Err(AppError::BadRequest("elevation.already_requested".into()));
+    }
+
+    // On-call users are pre-authorized: the grant starts right away
+    let on_call = elevation::on_call_users(&state.pool).await.contains(&auth.id);
+    let grant = ElevationGrant {
+        id: Uuid::new_v4().to_string(),
+        server_id: id.clone(),
+        user_id: auth.id.clone(),
+        reason: reason.to_string(),
+        duration_minutes: body.minutes as i64,
+        status: if on_call { ELEVATION_ACTIVE } else { ELEVATION_PENDING }.to_string(),
+        requested_at: now.to_rfc3339(),
+        decided_by: None,
+        expires_at: on_call.then(|| (now + chrono::Duration::minutes(body.minutes as i64)).to_rfc3339()),
+        resolved_at: None,
+    };
+
+    sqlx::query(&format!("INSERT INTO elevation_grants ({}) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)", GRANT_COLUMNS))
+        .bind(&grant.id)
+        .bind(&grant.server_id)
+        .bind(&grant.user_id)
+        .bind(&grant.reason)
+        .bind(grant.duration_minutes)
+        .bind(&grant.status)
+        .bind(&grant.requested_at)
+        .bind(&grant.decided_by)
+        .bind(&grant.expires_at)
+        .bind(&grant.resolved_at)
+        .execute(&state.pool)
+        .await?;
+
+    history::record_event(
+        &state.pool,
+        &id,
+        history::EVENT_ELEVATION_REQUESTED,
+        Some(&format!("{} ({} min): {}", auth.username, body.minutes, reason)),
+    ).await;
+
+    if on_call {
+        history::record_event(
+            &state.pool,
+            &id,
+            history::EVENT_ELEVATION_GRANTED,
+            Some(&format!("{} ({} min, on-call)", auth.username, body.minutes)),
+        ).await;
+    } else {
+        discord_service::notify_server(
+            &state.pool,
+            &id,
+            "🚨 Demande d'Accès d'Urgence",
+            &format!("**{}** demande un accès administrateur pendant {} min : {}", auth.username, body.minutes, reason),
+            discord_service::COLOR_WARNING,
+        ).await;
+    }
+
+    Ok(Json(grant))
+}
+
+/// Grants of a server, every user's for admins and the caller's own otherwise
+#[utoipa::path(get, path = "/servers/{id}/elevation", tag = "servers", params(("id" = String, Path)), responses((status = 200, body = [ElevationGrant])))]
+pub async fn list_elevations(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(id): Path<String>,
+) -> Result<Json<Vec<ElevationGrant>>, AppError> {
+    elevation::expire_due(&state.pool).await?;
+
+    let grants = if auth.is_admin() {
+        sqlx::query_as(&format!("SELECT {} FROM elevation_grants WHERE server_id = ? ORDER BY requested_at DESC", GRANT_COLUMNS))
+            .bind(&id)
+            .fetch_all(&state.pool)
+            .await?
+    } else {
+        sqlx::query_as(&format!("SELECT {} FROM elevation_grants WHERE server_id = ? AND user_id = ? ORDER BY requested_at DESC", GRANT_COLUMNS))
+            .bind(&id)
+            .bind(&auth.id)
+            .fetch_all(&state.pool)
+            .await?
+    };
+    Ok(Json(grants))
+}
+
+/// An admin grants a pending request; the access lasts the requested minutes from now
+#[utoipa::path(post, path = "/servers/elevation/{grant_id}/approve", tag = "servers", params(("grant_id" = String, Path)), responses((status = 200, body = ElevationGrant)))]
+pub async fn approve_elevation(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(grant_id): Path<String>,
+) -> Result<Json<ElevationGrant>, AppError> {
+    auth.require_admin()?;
+    let mut grant = grant_with_status(&state.pool, &grant_id, ELEVATION_PENDING).await?;
+
+    let now = Utc::now();
+    grant.status = ELEVATION_ACTIVE.to_string();
+    grant.decided_by = Some(auth.id.clone());
+    grant.expires_at = Some((now + chrono::Duration::minutes(grant.duration_minutes)).to_rfc3339());
+
+    sqlx::query("UPDATE elevation_grants SET status = ?, decided_by = ?, expires_at = ? WHERE id = ?")
+        .bind(&grant.status)
+        .bind(&grant.decided_by)
+        .bind(&grant.expires_at)
+        .bind(&grant.id)
+        .execute(&state.pool)
+        .await?;
+
+    let user = username(&state.pool, &grant.user_id).await?.unwrap_or_else(|| grant.user_id.clone());
+    history::record_event(
+        &state.pool,
+        &grant.server_id,
+        history::EVENT_ELEVATION_GRANTED,
+        Some(&format!("{} ({} min, {})", user, grant.duration_minutes, auth.username)),
+    ).await;
+
+    Ok(Json(grant))
+}
+
+#[utoipa::path(post, path = "/servers/elevation/{grant_id}/deny", tag = "servers", params(("grant_id" = String, Path)), responses((status = 200, body = serde_json::Value)))]
+pub async fn deny_elevation(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(grant_id): Path<String>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    auth.require_admin()?;
+    let grant = grant_with_status(&state.pool, &grant_id, ELEVATION_PENDING).await?;
+    resolve(&state.pool, &grant, ELEVATION_DENIED, &auth).await?;
+
+    let user = username(&state.pool, &grant.user_id).await?.unwrap_or_else(|| grant.user_id.clone());
+    history::record_event(
+        &state.pool,
+        &grant.server_id,
+        history::EVENT_ELEVATION_DENIED,
+        Some(&format!("{} ({})", user, auth.username)),
+    ).await;
+
+    Ok(Json(serde_json::json!({ "success": true })))
+}
+
+/// End an active grant early, by an admin or by the user once the incident is over
+#[utoipa::path(post, path = "/servers/elevation/{grant_id}/revoke", tag = "servers", params(("grant_id" = String, Path)), responses((status = 200, body = serde_json::Value)))]
+pub async fn revoke_elevation(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(grant_id): Path<String>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let grant = grant_with_status(&state.pool, &grant_id, ELEVATION_ACTIVE).await?;
+    if grant.user_id != auth.id {
+        auth.require_admin()?;
+    }
+    resolve(&state.pool, &grant, ELEVATION_REVOKED, &auth).await?;
+
+    let user = username(&state.pool, &grant.user_id).await?.unwrap_or_else(|| grant.user_id.clone());
+    history::record_event(
+        &state.pool,
+        &grant.server_id,
+        history::EVENT_ELEVATION_REVOKED,
+        Some(&format!("{} ({})", user, auth.username)),
+    ).await;
+
+    Ok(Json(serde_json::json!({ "success": true })))
+}
+
+
+/// A grant currently in `status` (an expired active grant doesn't count as active)
+async fn grant_with_status(pool: &DbPool, grant_id: &str, status: &str) -> Result<ElevationGrant, AppError> {
+    elevation::expire_due(pool).await?;
+    let grant: ElevationGrant = sqlx::query_as(&format!("SELECT {} FROM elevation_grants WHERE id = ?", GRANT_COLUMNS))
+        .bind(grant_id)
+        .fetch_optional(pool)
+        .await?
+        .ok_or_else(|| AppError::NotFound("elevation.not_found".into()))?;
+
+    if grant.status != status {
+        let key = if status == ELEVATION_PENDING { "elevation.not_pending" } else { "elevation.not_active" };
+        return Err(AppError::BadRequest(key.into()));
+    }
+    Ok(grant)
+}
+
+async fn resolve(pool: &DbPool, grant: &ElevationGrant, status: &str, auth: &AuthUser) -> Result<(), AppError> {
+    sqlx::query("UPDATE elevation_grants SET status = ?, decided_by = COALESCE(decided_by, ?), resolved_at = ? WHERE id = ?")
+        .bind(status)
+        .bind(&auth.id)
+        .bind(Utc::now().to_rfc3339())
+        .bind(&grant.id)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
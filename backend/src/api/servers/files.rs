@@ -1,15 +1,27 @@
 use axum::{
-    extract::{Path, Query, State},
+    extract::{ConnectInfo, Path, Query, State},
     Json,
 };
-use std::path::Path as StdPath;
+use std::collections::HashSet;
+use std::io::ErrorKind;
+use std::net::SocketAddr;
+use std::path::{Path as StdPath, PathBuf};
 use tracing::info;
 use crate::{AppState, error::AppError};
-use super::models::{FileEntry, FilesQuery, ReadFileQuery, WriteFileRequest, DeleteFileRequest};
+use crate::api::auth::AuthUser;
+use crate::services::ip_bans::{self, Offense};
+use crate::services::{audit, protected_paths, snapshots};
+use crate::utils::{atomic_file, platform, sandbox};
+use super::models::{BatchFilesRequest, FileEntry, FileOperation, FilesQuery, ReadFileQuery, WriteFileRequest, DeleteFileRequest};
 
+/// Operations accepted in one batch
+const MAX_BATCH_OPERATIONS: usize = 500;
+
+#[utoipa::path(get, path = "/servers/{id}/files", tag = "files", params(("id" = String, Path), FilesQuery), responses((status = 200, body = serde_json::Value, description = "`entries`: FileEntry list")))]
 pub async fn list_server_files(
     State(state): State<AppState>,
     Path(server_id): Path<String>,
+    connect_info: Option<ConnectInfo<SocketAddr>>,
     Query(query): Query<FilesQuery>,
 ) -> Result<Json<serde_json::Value>, AppError> {
     
@@ -24,25 +36,15 @@ pub async fn list_server_files(
         .0;
     
     // Build the path - relative to working_dir (includes server/ and manager/)
-    let base_path = StdPath::new(&working_dir);
     let relative_path = query.path.clone().unwrap_or_default();
-    let full_path = if relative_path.is_empty() {
-        base_path.to_path_buf()
-    } else {
-        base_path.join(&relative_path)
-    };
-    
-    // Security: ensure path is within server directory
-    if !full_path.starts_with(&base_path) {
-        return Err(AppError::BadRequest("Invalid path".into()));
-    }
+    let full_path = sandboxed(&state, connect_info, &working_dir, &relative_path).await?;
     
     if !full_path.exists() {
-        return Err(AppError::NotFound("Path not found".into()));
+        return Err(AppError::NotFound("files.not_found".into()));
     }
     
     if !full_path.is_dir() {
-        return Err(AppError::BadRequest("Path is not a directory".into()));
+        return Err(AppError::BadRequest("files.not_a_directory".into()));
     }
     
     let mut entries: Vec<FileEntry> = Vec::new();
@@ -117,9 +119,11 @@ pub async fn list_server_files(
     })))
 }
 
+#[utoipa::path(get, path = "/servers/{id}/files/read", tag = "files", params(("id" = String, Path), ReadFileQuery), responses((status = 200, body = serde_json::Value)))]
 pub async fn read_server_file(
     State(state): State<AppState>,
     Path(server_id): Path<String>,
+    connect_info: Option<ConnectInfo<SocketAddr>>,
     Query(query): Query<ReadFileQuery>,
 ) -> Result<Json<serde_json::Value>, AppError> {
     
@@ -133,20 +137,14 @@ pub async fn read_server_file(
         .ok_or_else(|| AppError::NotFound("servers.not_found".into()))?
         .0;
     
-    let base_path = StdPath::new(&working_dir);
-    let full_path = base_path.join(&query.path);
-    
-    // Security check
-    if !full_path.starts_with(&base_path) {
-        return Err(AppError::BadRequest("Invalid path".into()));
-    }
+    let full_path = sandboxed(&state, connect_info, &working_dir, &query.path).await?;
     
     if !full_path.exists() {
-        return Err(AppError::NotFound("File not found".into()));
+        return Err(AppError::NotFound("files.not_found".into()));
     }
     
     if full_path.is_dir() {
-        return Err(AppError::BadRequest("Cannot read a directory".into()));
+        return Err(AppError::BadRequest("files.is_a_directory".into()));
     }
     
     let content = std::fs::read_to_string(&full_path)
@@ -158,9 +156,12 @@ pub async fn read_server_file(
     })))
 }
 
+#[utoipa::path(post, path = "/servers/{id}/files/write", tag = "files", params(("id" = String, Path)), request_body = WriteFileRequest, responses((status = 200, body = serde_json::Value), (status = 403, description = "`files.protected_path`")))]
 pub async fn write_server_file(
     State(state): State<AppState>,
     Path(server_id): Path<String>,
+    auth: AuthUser,
+    connect_info: Option<ConnectInfo<SocketAddr>>,
     Json(body): Json<WriteFileRequest>,
 ) -> Result<Json<serde_json::Value>, AppError> {
     
@@ -174,28 +175,46 @@ pub async fn write_server_file(
         .ok_or_else(|| AppError::NotFound("servers.not_found".into()))?
         .0;
     
-    let base_path = StdPath::new(&working_dir);
-    let full_path = base_path.join(&body.path);
+    let full_path = sandboxed(&state, connect_info, &working_dir, &body.path).await?;
+    guard_protected(&auth, &working_dir, &full_path)?;
     
-    // Security check
-    if !full_path.starts_with(&base_path) {
-        return Err(AppError::BadRequest("Invalid path".into()));
-    }
-    
-    std::fs::write(&full_path, &body.content)
+    let snapshot_id = risky_change_snapshot(&state, &server_id, &working_dir, &body.path).await?;
+
+    let mode = match body.permissions.as_deref().map(str::trim).filter(|p| !p.is_empty()) {
+        Some(raw) => Some(
+            u32::from_str_radix(raw, 8)
+                .ok()
+                .filter(|m| *m <= 0o777)
+                .ok_or_else(|| AppError::BadRequest("files.invalid_permissions".into()))?,
+        ),
+        None => None,
+    };
+
+    atomic_file::write(&full_path, body.content.as_str())
+        .await
         .map_err(|e| AppError::Internal(format!("Failed to write file: {}", e)))?;
+
+    if let Some(mode) = mode {
+        platform::set_mode(&full_path, mode)
+            .map_err(|e| AppError::Internal(format!("Failed to set permissions: {}", e)))?;
+    }
     
     info!("File written: {:?}", full_path);
+    record_file_change(&state, &auth, audit::ACTION_FILE_WRITE, &server_id, &body.path).await;
     
     Ok(Json(serde_json::json!({
         "success": true,
-        "path": body.path
+        "path": body.path,
+        "snapshot_id": snapshot_id
     })))
 }
 
+#[utoipa::path(post, path = "/servers/{id}/files/delete", tag = "files", params(("id" = String, Path)), request_body = DeleteFileRequest, responses((status = 200, body = serde_json::Value), (status = 403, description = "`files.protected_path`")))]
 pub async fn delete_server_file(
     State(state): State<AppState>,
     Path(server_id): Path<String>,
+    auth: AuthUser,
+    connect_info: Option<ConnectInfo<SocketAddr>>,
     Json(body): Json<DeleteFileRequest>,
 ) -> Result<Json<serde_json::Value>, AppError> {
     
@@ -209,29 +228,236 @@ pub async fn delete_server_file(
         .ok_or_else(|| AppError::NotFound("servers.not_found".into()))?
         .0;
     
-    let base_path = StdPath::new(&working_dir);
-    let full_path = base_path.join(&body.path);
-    
-    // Security check
-    if !full_path.starts_with(&base_path) {
-        return Err(AppError::BadRequest("Invalid path".into()));
-    }
+    let full_path = sandboxed(&state, connect_info, &working_dir, &body.path).await?;
+    guard_protected(&auth, &working_dir, &full_path)?;
     
     if !full_path.exists() {
-        return Err(AppError::NotFound("File not found".into()));
+        return Err(AppError::NotFound("files.not_found".into()));
     }
     
     if full_path.is_dir() {
-         return Err(AppError::BadRequest("Cannot delete a directory with this endpoint".into()));
+         return Err(AppError::BadRequest("files.is_a_directory".into()));
     }
     
+    let snapshot_id = risky_change_snapshot(&state, &server_id, &working_dir, &body.path).await?;
+
     std::fs::remove_file(&full_path)
         .map_err(|e| AppError::Internal(format!("Failed to delete file: {}", e)))?;
     
     info!("File deleted: {:?}", full_path);
+    record_file_change(&state, &auth, audit::ACTION_FILE_DELETE, &server_id, &body.path).await;
     
     Ok(Json(serde_json::json!({
         "success": true,
-        "path": body.path
+        "path": body.path,
+        "snapshot_id": snapshot_id
+    })))
+}
+
+/// Resolved step of a batch
+enum Step {
+    Delete { path: PathBuf },
+    Move { from: PathBuf, to: PathBuf },
+}
+
+/// Run steps in order, deleted files parked in `staging` until all went through. On failure
+/// the steps done so far are undone in reverse.
+fn run_batch(steps: &[Step], staging: &StdPath) -> Result<(), (usize, std::io::Error)> {
+    std::fs::create_dir_all(staging).map_err(|e| (0, e))?;
+    let mut done = 0;
+    let result = steps.iter().enumerate().try_for_each(|(i, step)| {
+        let outcome = match step {
+            Step::Delete { path } => std::fs::rename(path, staging.join(i.to_string())),
+            Step::Move { from, to } => to.parent()
+                .map_or(Ok(()), std::fs::create_dir_all)
+                .and_then(|()| std::fs::rename(from, to)),
+        };
+        outcome.map_err(|e| (i, e))?;
+        done += 1;
+        Ok(())
+    });
+
+    if result.is_err() {
+        for (i, step) in steps.iter().enumerate().take(done).rev() {
+            let undo = match step {
+                Step::Delete { path } => std::fs::rename(staging.join(i.to_string()), path),
+                Step::Move { from, to } => std::fs::rename(to, from),
+            };
+            if let Err(e) = undo {
+                tracing::warn!("Failed to undo batch file operation {}: {}", i, e);
+            }
+        }
+    }
+    if let Err(e) = std::fs::remove_dir_all(staging) {
+        tracing::warn!("Failed to remove batch staging directory {:?}: {}", staging, e);
+    }
+    result
+}
+
+/// Delete and move many files in one request, all or nothing: every operation is checked
+/// before any runs, and those done are undone when a later one fails
+#[utoipa::path(post, path = "/servers/{id}/files/batch", tag = "files", params(("id" = String, Path)), request_body = BatchFilesRequest, responses((status = 200, body = serde_json::Value), (status = 403, description = "`files.protected_path`")))]
+pub async fn batch_server_files(
+    State(state): State<AppState>,
+    Path(server_id): Path<String>,
+    auth: AuthUser,
+    connect_info: Option<ConnectInfo<SocketAddr>>,
+    Json(body): Json<BatchFilesRequest>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    if body.operations.is_empty() {
+        return Err(AppError::BadRequest("files.batch_empty".into()));
+    }
+    if body.operations.len() > MAX_BATCH_OPERATIONS {
+        return Err(AppError::BadRequest("files.batch_too_large".into()));
+    }
+
+    let server: Option<(String,)> = sqlx::query_as("SELECT working_dir FROM servers WHERE id = ?")
+        .bind(&server_id)
+        .fetch_optional(&state.pool)
+        .await?;
+    let working_dir = server
+        .ok_or_else(|| AppError::NotFound("servers.not_found".into()))?
+        .0;
+
+    // Each path once, so no step depends on another having run
+    let mut seen = HashSet::new();
+    let mut steps = Vec::with_capacity(body.operations.len());
+    for (i, operation) in body.operations.iter().enumerate() {
+        let (source, target) = match operation {
+            FileOperation::Delete { path } => (path, None),
+            FileOperation::Move { from, to } => (from, Some(to)),
+        };
+        let source_path = sandboxed(&state, connect_info, &working_dir, source).await?;
+        guard_protected(&auth, &working_dir, &source_path)?;
+        if !source_path.exists() {
+            return Err(AppError::NotFound(format!("files.not_found: operation {}", i)));
+        }
+        if source_path.is_dir() {
+            return Err(AppError::BadRequest(format!("files.is_a_directory: operation {}", i)));
+        }
+        if !seen.insert(source_path.clone()) {
+            return Err(AppError::BadRequest(format!("files.batch_conflict: operation {}", i)));
+        }
+
+        let step = match target {
+            None => Step::Delete { path: source_path },
+            Some(target) => {
+                let target_path = sandboxed(&state, connect_info, &working_dir, target).await?;
+                guard_protected(&auth, &working_dir, &target_path)?;
+                if target_path.exists() {
+                    return Err(AppError::Conflict(format!("files.already_exists: operation {}", i)));
+                }
+                if !seen.insert(target_path.clone()) {
+                    return Err(AppError::BadRequest(format!("files.batch_conflict: operation {}", i)));
+                }
+                Step::Move { from: source_path, to: target_path }
+            }
+        };
+        steps.push(step);
+    }
+
+    // One snapshot covers the whole batch, mods taking precedence over config files
+    let risky = body.operations.iter()
+        .flat_map(|op| match op {
+            FileOperation::Delete { path } => vec![path.as_str()],
+            FileOperation::Move { from, to } => vec![from.as_str(), to.as_str()],
+        })
+        .min_by_key(|path| match (snapshots::is_mod_path(path), snapshots::is_config_path(path)) {
+            (true, _) => 0,
+            (false, true) => 1,
+            (false, false) => 2,
+        });
+    let snapshot_id = match risky {
+        Some(path) => risky_change_snapshot(&state, &server_id, &working_dir, path).await?,
+        None => None,
+    };
+
+    let staging = StdPath::new(&working_dir).join(format!(".batch-{}", uuid::Uuid::new_v4().simple()));
+    let count = steps.len();
+    tokio::task::spawn_blocking(move || run_batch(&steps, &staging))
+        .await
+        .map_err(|e| AppError::Internal(format!("Failed to run file operations: {}", e)))?
+        .map_err(|(i, e)| AppError::Conflict(format!("files.batch_failed: operation {}: {}", i, e)))?;
+
+    let (mut deleted, mut moved) = (0, 0);
+    for operation in &body.operations {
+        match operation {
+            FileOperation::Delete { path } => {
+                deleted += 1;
+                record_file_change(&state, &auth, audit::ACTION_FILE_DELETE, &server_id, path).await;
+            }
+            FileOperation::Move { from, to } => {
+                moved += 1;
+                record_file_change(&state, &auth, audit::ACTION_FILE_MOVE, &server_id, &format!("{} -> {}", from, to)).await;
+            }
+        }
+    }
+    info!("Batch of {} file operations on server {}", count, server_id);
+
+    Ok(Json(serde_json::json!({
+        "success": true,
+        "deleted": deleted,
+        "moved": moved,
+        "snapshot_id": snapshot_id
     })))
 }
+
+/// `relative` inside the server directory, symlinks and `..` resolved. Escapes count towards
+/// an automatic ban of the caller.
+pub(super) async fn sandboxed(
+    state: &AppState,
+    connect_info: Option<ConnectInfo<SocketAddr>>,
+    working_dir: &str,
+    relative: &str,
+) -> Result<PathBuf, AppError> {
+    match sandbox::resolve(StdPath::new(working_dir), relative) {
+        Ok(path) => Ok(path),
+        Err(e) if e.kind() == ErrorKind::PermissionDenied => {
+            let peer = connect_info.map(|ConnectInfo(addr)| addr.ip());
+            ip_bans::record_offense(&state.pool, peer, Offense::PathTraversal).await;
+            Err(AppError::BadRequest("files.invalid_path".into()))
+        }
+        Err(e) if e.kind() == ErrorKind::NotFound => Err(AppError::NotFound("files.not_found".into())),
+        Err(e) => Err(AppError::Internal(format!("Failed to resolve path: {}", e))),
+    }
+}
+
+/// Refuse changes to a protected file, unless made by an admin. `full_path` is the resolved
+/// path, so `./` prefixes, case or symlinks don't get around the list.
+fn guard_protected(auth: &AuthUser, working_dir: &str, full_path: &StdPath) -> Result<(), AppError> {
+    if auth.is_admin() {
+        return Ok(());
+    }
+    let root = std::fs::canonicalize(working_dir)
+        .map_err(|e| AppError::Internal(format!("Failed to resolve path: {}", e)))?;
+    let relative = full_path.strip_prefix(&root).unwrap_or(full_path);
+    if protected_paths::is_protected(relative) {
+        info!("{} was refused a change to protected file {:?}", auth.username, relative);
+        return Err(AppError::Forbidden("files.protected_path".into()));
+    }
+    Ok(())
+}
+
+/// Note a file change in the activity feed of its author
+async fn record_file_change(state: &AppState, auth: &AuthUser, action: &str, server_id: &str, path: &str) {
+    let name: Option<String> = sqlx::query_scalar("SELECT name FROM servers WHERE id = ?")
+        .bind(server_id)
+        .fetch_optional(&state.pool)
+        .await
+        .ok()
+        .flatten();
+    let detail = format!("{}: {}", name.as_deref().unwrap_or(server_id), path);
+    audit::record(&state.pool, Some(&auth.id), Some(&auth.username), action, Some(&detail), None).await;
+}
+
+/// Snapshot the world before a mod install/removal or a root config file change
+async fn risky_change_snapshot(state: &AppState, server_id: &str, working_dir: &str, path: &str) -> Result<Option<String>, AppError> {
+    let operation = if snapshots::is_mod_path(path) {
+        snapshots::OP_MOD_CHANGE
+    } else if snapshots::is_config_path(path) {
+        snapshots::OP_CONFIG_CHANGE
+    } else {
+        return Ok(None);
+    };
+    snapshots::snapshot_before(&state.pool, &state.settings.backups_dir, server_id, working_dir, operation).await
+}
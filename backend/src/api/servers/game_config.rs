@@ -0,0 +1,132 @@
+//! The game settings of config.json as a typed form, so the panel doesn't edit a raw JSON blob.
+//! They are saved both in the server's `config` column and in config.json, which keeps what
+//! users edit in it across starts apart from the keys the manager owns.
+
+use std::path::Path as StdPath;
+
+use axum::{
+    extract::{Path, State},
+    Json,
+};
+
+use crate::error::AppError;
+use crate::services::snapshots;
+use crate::templates::{self, GameConfig};
+use crate::AppState;
+use super::handlers::write_config_files;
+
+const MAX_PLAYERS: u32 = 1000;
+const MAX_VIEW_RADIUS: u32 = 64;
+const MAX_MOTD_LEN: usize = 256;
+const GAME_MODES: &[&str] = &["Adventure", "Creative"];
+/// java.util.logging levels, which the server's loggers use
+const LOG_LEVELS: &[&str] = &["OFF", "SEVERE", "WARNING", "INFO", "CONFIG", "FINE", "FINER", "FINEST", "ALL"];
+
+fn invalid(detail: &str) -> AppError {
+    AppError::BadRequest(format!("servers.invalid_game_config: {}", detail))
+}
+
+fn validate(config: &GameConfig) -> Result<(), AppError> {
+    if config.motd.chars().count() > MAX_MOTD_LEN {
+        return Err(invalid("MOTD"));
+    }
+    if !(1..=MAX_PLAYERS).contains(&config.max_players) {
+        return Err(invalid("MaxPlayers"));
+    }
+    if !(1..=MAX_VIEW_RADIUS).contains(&config.max_view_radius) {
+        return Err(invalid("MaxViewRadius"));
+    }
+    // A world folder name
+    let world = &config.defaults.world;
+    if world.is_empty() || world.starts_with('.') || world.contains(['/', '\\']) {
+        return Err(invalid("Defaults.World"));
+    }
+    if !GAME_MODES.contains(&config.defaults.game_mode.as_str()) {
+        return Err(invalid("Defaults.GameMode"));
+    }
+    for (logger, level) in &config.log_levels {
+        if logger.trim().is_empty() || !LOG_LEVELS.contains(&level.as_str()) {
+            return Err(invalid(&format!("LogLevels.{}", logger)));
+        }
+    }
+    Ok(())
+}
+
+struct ServerConfig {
+    name: String,
+    working_dir: String,
+    auth_mode: String,
+    config: serde_json::Value,
+}
+
+async fn server_config(state: &AppState, id: &str) -> Result<ServerConfig, AppError> {
+    let row: Option<(String, String, String, Option<String>)> =
+        sqlx::query_as("SELECT name, working_dir, auth_mode, config FROM servers WHERE id = ?")
+            .bind(id)
+            .fetch_optional(&state.pool)
+            .await?;
+    let (name, working_dir, auth_mode, config) = row.ok_or_else(|| AppError::NotFound("servers.not_found".into()))?;
+    let config = config
+        .and_then(|c| serde_json::from_str(&c).ok())
+        .filter(serde_json::Value::is_object)
+        .unwrap_or_else(|| serde_json::json!({}));
+    Ok(ServerConfig { name, working_dir, auth_mode, config })
+}
+
+/// config.json of the server, `None` when missing or unreadable
+async fn read_config_file(working_dir: &str) -> Option<serde_json::Value> {
+    let content = tokio::fs::read_to_string(StdPath::new(working_dir).join("config.json")).await.ok()?;
+    serde_json::from_str::<serde_json::Value>(&content).ok().filter(serde_json::Value::is_object)
+}
+
+/// Game settings of a server as found in config.json, which may have been edited by hand since
+/// they were saved. MaxPlayers is the saved one, the next start writes it back.
+#[utoipa::path(get, path = "/servers/{id}/game-config", tag = "servers", params(("id" = String, Path)), responses((status = 200, body = GameConfig)))]
+pub async fn get_game_config(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<Json<GameConfig>, AppError> {
+    let server = server_config(&state, &id).await?;
+    let mut merged = server.config.clone();
+    if let (Some(merged), Some(file)) = (merged.as_object_mut(), read_config_file(&server.working_dir).await) {
+        merged.extend(file.as_object().cloned().unwrap_or_default());
+        if let Some(max_players) = server.config.get("MaxPlayers") {
+            merged.insert("MaxPlayers".into(), max_players.clone());
+        }
+    }
+    Ok(Json(GameConfig::from_value(&merged)))
+}
+
+/// Replace the game settings, in the server's config and in config.json. The server picks
+/// them up at its next start.
+#[utoipa::path(
+    put, path = "/servers/{id}/game-config", tag = "servers", params(("id" = String, Path)), request_body = GameConfig,
+    responses((status = 200, body = serde_json::Value), (status = 400, description = "`servers.invalid_game_config` naming the field"))
+)]
+pub async fn update_game_config(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Json(body): Json<GameConfig>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    validate(&body)?;
+    let mut server = server_config(&state, &id).await?;
+
+    // Rewriting config.json: keep a rollback point of the current one
+    let snapshot_id = snapshots::snapshot_before(&state.pool, &state.settings.backups_dir, &id, &server.working_dir, snapshots::OP_CONFIG_CHANGE).await?;
+
+    body.apply(&mut server.config);
+    sqlx::query("UPDATE servers SET config = ?, updated_at = ? WHERE id = ?")
+        .bind(server.config.to_string())
+        .bind(chrono::Utc::now().to_rfc3339())
+        .bind(&id)
+        .execute(&state.pool)
+        .await?;
+
+    let mut file = read_config_file(&server.working_dir)
+        .await
+        .unwrap_or_else(|| templates::generate_config_json(&server.name, body.max_players, &server.auth_mode));
+    body.apply(&mut file);
+    write_config_files(&id, &server.working_dir, &file).await;
+
+    Ok(Json(serde_json::json!({ "success": true, "snapshot_id": snapshot_id })))
+}
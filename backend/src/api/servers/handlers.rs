@@ -1,9 +1,11 @@
 use axum::{
-    extract::{Path, State},
+    extract::{Multipart, Path, Query, State},
     Json,
-    http::StatusCode,
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
 };
 use tracing::{info, error};
+use std::collections::HashMap;
 use std::path::{Path as StdPath, PathBuf};
 use chrono::Utc;
 use walkdir::WalkDir;
@@ -13,35 +15,91 @@ use uuid::Uuid;
 
 use crate::{AppState, error::AppError};
 use crate::utils::memory::{parse_memory_to_bytes, calculate_total_memory};
+use crate::utils::platform;
+use crate::utils::interfaces;
+use crate::utils::atomic_file;
+use crate::utils::http_cache::ResponseCache;
 use crate::templates;
-use crate::services::ProcessManager;
+use crate::services::{audit, auth_detection, backup_service, broadcast_messages, console_log, discord_service, docker, health_probe, history, network, operation_lock, scheduled_restart, server_access, snapshots, ProcessManager};
+use crate::api::auth::AuthUser;
+use crate::api::{server_templates, settings};
+use crate::services::launch_config::{build_launch_config, prepare_config_json};
 use crate::db::DbPool;
 
-use super::models::{ServerRow, ServerResponse, CreateServerRequest, Player, PlayerRow, CommandRequest};
+use super::models::{
+    ServerRow, ServerResponse, CreateServerRequest, Player, PlayerRow, CommandRequest, ExecCommandRequest,
+    BulkSettingsRequest, BulkSettingsResult, ListServersQuery, StartupHistoryQuery,
+    INSTALL_COMPLETED, INSTALL_FAILED, INSTALL_INSTALLING, INSTALL_PENDING,
+};
+
+/// Official Hytale downloader, overridden by the `downloader_url` setting for mirrors
+pub const DEFAULT_DOWNLOADER_URL: &str = "https://downloader.hytale.com/hytale-downloader.zip";
+
+/// Limits on the labels a server can be tagged with
+const MAX_TAGS: usize = 20;
+const MAX_TAG_LEN: usize = 32;
 
+/// Name an uploaded server bundle is stored under until the install extracts it
+const INSTALL_BUNDLE_NAME: &str = "server-bundle.zip";
+
+/// Short cache so several open dashboards don't each rescan every server directory
+static SERVERS_CACHE: ResponseCache = ResponseCache::new(std::time::Duration::from_secs(3));
+
+#[utoipa::path(get, path = "/servers", tag = "servers", params(ListServersQuery), responses((status = 200, body = [ServerResponse])))]
 pub async fn list_servers(
     State(state): State<AppState>,
-) -> Result<Json<Vec<ServerResponse>>, AppError> {
-    let servers: Vec<ServerRow> = sqlx::query_as(
+    auth: AuthUser,
+    headers: HeaderMap,
+    Query(query): Query<ListServersQuery>,
+) -> Result<Response, AppError> {
+    let search = query.q.as_deref().map(|q| q.trim().to_lowercase()).filter(|q| !q.is_empty());
+    let wanted_tags: Vec<String> = query.tags.as_deref()
+        .map(|tags| tags.split(',').map(|t| t.trim().to_lowercase()).filter(|t| !t.is_empty()).collect())
+        .unwrap_or_default();
+    let filtered = search.is_some() || !wanted_tags.is_empty();
+
+    // The cache holds every server, only unfiltered callers managing all of them can use it
+    let allowed = server_access::allowed_servers(&state.pool, &auth, server_access::VIEW).await?;
+    let cacheable = allowed.is_none() && !filtered;
+    if cacheable {
+        if let Some(cached) = SERVERS_CACHE.get() {
+            return Ok(cached.respond(&headers));
+        }
+    }
+
+    let mut servers: Vec<ServerRow> = sqlx::query_as(
         "SELECT * FROM servers"
     )
     .fetch_all(&state.pool)
     .await?;
+    if let Some(ref allowed) = allowed {
+        servers.retain(|s| allowed.contains(&s.id));
+    }
+    if filtered {
+        servers.retain(|s| matches_search(s, search.as_deref(), &wanted_tags));
+    }
 
     let mut responses = Vec::new();
     let pm = &state.process_manager;
+    let startup_durations = history::last_startup_durations(&state.pool).await?;
     
     for s in servers {
         // Check if the working directory exists
         let dir_exists = StdPath::new(&s.working_dir).exists();
-        let is_running = pm.is_running(&s.id);
+        let is_running = pm.is_running(&s.id).await;
         
         let status = if !dir_exists { 
             "missing" 
-        } else if pm.is_installing(&s.id) {
-            if pm.is_auth_required(&s.id) { "auth_required" } else { "installing" }
+        } else if pm.is_installing(&s.id).await {
+            if pm.is_auth_required(&s.id).await { "auth_required" } else { "installing" }
         } else if is_running {
-             if pm.is_auth_required(&s.id) { "auth_required" } else { "running" }
+             if pm.is_suspended(&s.id).await {
+                 "suspended"
+             } else if pm.is_unresponsive(&s.id).await {
+                 "unresponsive"
+             } else if pm.is_auth_required(&s.id).await { "auth_required" } else { "running" }
+        } else if pm.is_queued(&s.id) {
+            "queued"
         } else {
             "stopped"
         };
@@ -83,6 +141,7 @@ pub async fn list_servers(
 
         let started_at = pm.get_server_started_at(&s.id).await;
         let (cpu, cpu_norm, mem, mut disk) = pm.get_metrics_data(&s.id).await;
+        let network = pm.get_network_data(&s.id).await.unwrap_or_default();
 
         // Fallback for offline disk usage
         if disk == 0 {
@@ -101,11 +160,13 @@ pub async fn list_servers(
         // Parse notifications JSON
         let notifications = s.discord_notifications.as_ref()
             .and_then(|n| serde_json::from_str(n).ok());
+        let startup_duration_ms = startup_durations.get(&s.id).copied();
 
         responses.push(ServerResponse {
             id: s.id,
             name: s.name,
             game_type: s.game_type,
+            tags: parse_tags(s.tags.as_deref()),
             status: status.to_string(),
             executable_path: s.executable_path,
             working_dir: s.working_dir,
@@ -131,9 +192,43 @@ pub async fn list_servers(
             discord_avatar: s.discord_avatar,
             discord_webhook_url: s.discord_webhook_url,
             discord_notifications: notifications,
+            discord_color: s.discord_color,
+            discord_emoji: s.discord_emoji,
             logs_retention_days: s.logs_retention_days as u32,
             watchdog_enabled: s.watchdog_enabled != 0,
             auth_mode: s.auth_mode,
+            env_vars: parse_env_vars(s.env_vars.as_deref()),
+            launch_mode: s.launch_mode,
+            command_template: s.command_template,
+            runtime: s.runtime,
+            docker_image: s.docker_image,
+            health_probe: s.health_probe,
+            health_probe_interval_secs: s.health_probe_interval_secs.max(0) as u32,
+            locale: s.locale,
+            stop_command: s.stop_command,
+            restart_schedule: s.restart_schedule,
+            install_state: s.install_state,
+            log_rotation_count: s.log_rotation_count as u32,
+            console_log_enabled: s.console_log_enabled != 0,
+            console_log_max_mb: s.console_log_max_mb.max(0) as u32,
+            console_log_dedup: s.console_log_dedup != 0,
+            network_role: s.network_role.filter(|r| !r.is_empty()),
+            parent_proxy_id: s.parent_proxy_id.filter(|p| !p.is_empty()),
+            backup_save_command: s.backup_save_command.filter(|v| !v.is_empty()),
+            backup_save_confirm: s.backup_save_confirm.filter(|v| !v.is_empty()),
+            backup_autosave_off_command: s.backup_autosave_off_command.filter(|v| !v.is_empty()),
+            backup_autosave_on_command: s.backup_autosave_on_command.filter(|v| !v.is_empty()),
+            backup_filename_template: s.backup_filename_template.filter(|v| !v.is_empty()),
+            alert_cpu_percent: s.alert_cpu_percent.map(|v| v as f32),
+            alert_ram_percent: s.alert_ram_percent.map(|v| v as f32),
+            alert_disk_mb: s.alert_disk_mb.map(|v| v.max(0) as u64),
+            owner_id: s.owner_id,
+            last_exit_code: s.last_exit_code,
+            last_exit_at: s.last_exit_at,
+            network_rx_bytes_per_sec: network.rx_bytes_per_sec,
+            network_tx_bytes_per_sec: network.tx_bytes_per_sec,
+            connections: network.connections,
+            startup_duration_ms,
 
             cpu_usage: cpu,
             cpu_usage_normalized: cpu_norm,
@@ -145,18 +240,71 @@ pub async fn list_servers(
         });
     }
 
-    Ok(Json(responses))
+    if !cacheable {
+        return Ok(Json(responses).into_response());
+    }
+    Ok(SERVERS_CACHE.store(&responses)?.respond(&headers))
 }
 
+/// With a `template_id`, the fields left out are taken from that server template, and
+/// `working_dir` defaults to the `servers_dir` setting
+#[utoipa::path(post, path = "/servers", tag = "servers", request_body = CreateServerRequest, responses((status = 201, body = serde_json::Value)))]
 pub async fn create_server(
     State(state): State<AppState>,
-    Json(body): Json<CreateServerRequest>,
+    auth: AuthUser,
+    Json(body): Json<serde_json::Value>,
+) -> Result<(StatusCode, Json<serde_json::Value>), AppError> {
+    // A server is a command run on the host, in a directory of its choosing
+    auth.require_admin()?;
+    let serde_json::Value::Object(mut request) = body else {
+        return Err(AppError::BadRequest("servers.invalid_request".into()));
+    };
+    server_templates::apply(&state.pool, &mut request).await?;
+    if request.get("working_dir").is_none_or(|dir| dir.is_null()) {
+        request.insert("working_dir".into(), settings::servers_dir(&state.pool).await.into());
+    }
+    let body: CreateServerRequest = serde_json::from_value(serde_json::Value::Object(request))
+        .map_err(|_| AppError::BadRequest("servers.invalid_request".into()))?;
+    create_server_from(state, body).await
+}
+
+/// Create a server from a complete request
+pub(super) async fn create_server_from(
+    state: AppState,
+    body: CreateServerRequest,
 ) -> Result<(StatusCode, Json<serde_json::Value>), AppError> {
     let id = Uuid::new_v4().to_string();
     let now = Utc::now().to_rfc3339();
     let auto_start = body.auto_start.unwrap_or(false) as i32;
 
+    let env_vars = body.env_vars.clone().unwrap_or_default();
+    validate_env_vars(&env_vars)?;
+    let tags = normalized_tags(body.tags.as_deref().unwrap_or_default())?;
+    let launch_mode = body.launch_mode.clone().unwrap_or_else(|| "java".to_string());
+    validate_launch_mode(&launch_mode, body.command_template.as_deref())?;
+    let runtime = body.runtime.clone().unwrap_or_else(|| docker::RUNTIME_NATIVE.to_string());
+    validate_runtime(&runtime, body.docker_image.as_deref(), &body.game_type)?;
+    let locale = body.locale.clone().unwrap_or_else(|| broadcast_messages::DEFAULT_LOCALE.to_string());
+    validate_locale(&locale)?;
+    if let Some(schedule) = &body.restart_schedule {
+        validate_restart_schedule(schedule)?;
+    }
+    validate_discord_identity(body.discord_color.as_deref(), body.discord_emoji.as_deref())?;
+    validate_alert_thresholds(&body)?;
+    validate_health_probe(&body)?;
+    validate_console_log_max(body.console_log_max_mb)?;
+    validate_save_confirm(body.backup_save_confirm.as_deref())?;
+    validate_filename_template(body.backup_filename_template.as_deref())?;
+    validate_network(&state.pool, None, body.network_role.as_deref(), body.parent_proxy_id.as_deref()).await?;
+    validate_game_type(&body.game_type, state.settings.dev_mode)?;
+    if let Some(address) = body.config.as_ref().and_then(|c| c.get("bind_address")).and_then(|v| v.as_str()) {
+        validate_bind_address(address)?;
+    }
+
     let server_base_path = StdPath::new(&body.working_dir).join(&id);
+    if !in_servers_dir(&state.pool, &server_base_path).await {
+        return Err(AppError::BadRequest("servers.working_dir_outside_servers_dir".into()));
+    }
     // Create base directories
     let directories = [
         &server_base_path,
@@ -180,25 +328,29 @@ pub async fn create_server(
     let auth_mode = config_value
         .and_then(|c| c.get("auth_mode"))
         .and_then(|v| v.as_str())
+        .or(body.auth_mode.as_deref())
         .unwrap_or("authenticated");
     let bind_address = config_value
         .and_then(|c| c.get("bind_address"))
         .and_then(|v| v.as_str())
+        .or(body.bind_address.as_deref())
         .unwrap_or("0.0.0.0");
     let port: u16 = config_value
         .and_then(|c| c.get("port"))
         .and_then(|v| v.as_u64())
-        .unwrap_or(5520) as u16;
+        .map(|p| p as u16)
+        .or(body.port)
+        .unwrap_or(5520);
 
     // Auto-download server jar if requested
     let mut final_executable = body.executable_path.clone();
     let install_path = server_base_path.clone();
 
-    if body.game_type == "hytale" {
-        spawn_hytale_installation(state.pool.clone(), state.process_manager.clone(), id.clone(), install_path.clone());
-        
+    let needs_install = body.game_type == "hytale";
+    if needs_install {
         final_executable = "Server/HytaleServer.jar".to_string(); 
     }
+    let install_state = if needs_install { INSTALL_PENDING } else { INSTALL_COMPLETED };
 
     let config_str = body.config.as_ref().map(|c| c.to_string());
 
@@ -212,10 +364,7 @@ pub async fn create_server(
         auth_mode
     );
     let config_json_path = server_base_path.join("config.json");
-    let mut config_file = fs::File::create(&config_json_path).await.map_err(|e| {
-        AppError::Internal(format!("Failed to create config.json: {}", e))
-    })?;
-    config_file.write_all(serde_json::to_string_pretty(&hytale_config).unwrap().as_bytes())
+    atomic_file::write(&config_json_path, serde_json::to_string_pretty(&hytale_config).unwrap())
         .await
         .map_err(|e| AppError::Internal(format!("Failed to write config.json: {}", e)))?;
 
@@ -227,13 +376,29 @@ pub async fn create_server(
             backup_enabled, backup_frequency, backup_max_backups, backup_prefix,
             discord_username, discord_avatar, discord_webhook_url, discord_notifications,
             logs_retention_days, watchdog_enabled,
-            auth_mode, bind_address, port
+            auth_mode, bind_address, port,
+            env_vars, launch_mode, command_template, locale, stop_command, restart_schedule, install_state,
+            log_rotation_count, discord_color, discord_emoji,
+            alert_cpu_percent, alert_ram_percent, alert_disk_mb,
+            runtime, docker_image, health_probe, health_probe_interval_secs,
+            console_log_enabled, console_log_max_mb, console_log_dedup,
+            network_role, parent_proxy_id,
+            backup_save_command, backup_save_confirm, backup_autosave_off_command, backup_autosave_on_command,
+            backup_filename_template, tags
         ) VALUES (
             ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?,
-            1, 30, 7, 'hytale_backup',
+            1, 30, 7, COALESCE(?, 'hytale_backup'),
             'Hytale Bot', '', '', '{}',
             7, 1,
-            ?, ?, ?
+            ?, ?, ?,
+            ?, ?, ?, ?, ?, ?, ?,
+            ?, ?, ?,
+            ?, ?, ?,
+            ?, ?, ?, ?,
+            ?, ?, ?,
+            ?, ?,
+            ?, ?, ?, ?,
+            ?, ?
         )",
     )
     .bind(&id)
@@ -249,12 +414,46 @@ pub async fn create_server(
     .bind(auto_start)
     .bind(&now)
     .bind(&now)
+    .bind(body.backup_prefix.as_deref().map(str::trim).filter(|v| !v.is_empty()))
     .bind(auth_mode)
     .bind(bind_address)
     .bind(port)
+    .bind(serde_json::to_string(&env_vars).unwrap_or_else(|_| "{}".to_string()))
+    .bind(&launch_mode)
+    .bind(&body.command_template)
+    .bind(&locale)
+    .bind(&body.stop_command)
+    .bind(&body.restart_schedule)
+    .bind(install_state)
+    .bind(body.log_rotation_count.unwrap_or(console_log::DEFAULT_ROTATION_COUNT))
+    .bind(&body.discord_color)
+    .bind(&body.discord_emoji)
+    .bind(body.alert_cpu_percent)
+    .bind(body.alert_ram_percent)
+    .bind(body.alert_disk_mb.map(|v| v as i64))
+    .bind(&runtime)
+    .bind(&body.docker_image)
+    .bind(&body.health_probe)
+    .bind(body.health_probe_interval_secs.unwrap_or(health_probe::DEFAULT_INTERVAL_SECS))
+    .bind(body.console_log_enabled.unwrap_or(true) as i32)
+    .bind(body.console_log_max_mb.unwrap_or(console_log::DEFAULT_MAX_SIZE_MB))
+    .bind(body.console_log_dedup.unwrap_or(false) as i32)
+    .bind(body.network_role.as_deref().map(str::trim).filter(|r| !r.is_empty()))
+    .bind(body.parent_proxy_id.as_deref().map(str::trim).filter(|p| !p.is_empty()))
+    .bind(body.backup_save_command.as_deref().map(str::trim).filter(|v| !v.is_empty()))
+    .bind(body.backup_save_confirm.as_deref().map(str::trim).filter(|v| !v.is_empty()))
+    .bind(body.backup_autosave_off_command.as_deref().map(str::trim).filter(|v| !v.is_empty()))
+    .bind(body.backup_autosave_on_command.as_deref().map(str::trim).filter(|v| !v.is_empty()))
+    .bind(body.backup_filename_template.as_deref().map(str::trim).filter(|v| !v.is_empty()))
+    .bind(serde_json::to_string(&tags).unwrap_or_else(|_| "[]".to_string()))
     .execute(&state.pool)
     .await?;
 
+    // Only once the row exists, so the task can track its install_state
+    if needs_install {
+        spawn_hytale_installation(state.pool.clone(), state.process_manager.clone(), id.clone(), install_path.clone(), InstallSource::Downloader);
+    }
+
     Ok((StatusCode::CREATED, Json(serde_json::json!({ 
         "id": id,
         "working_dir": actual_working_dir,
@@ -262,6 +461,7 @@ pub async fn create_server(
     }))))
 }
 
+#[utoipa::path(get, path = "/servers/{id}", tag = "servers", params(("id" = String, Path)), responses((status = 200, body = ServerResponse), (status = 404)))]
 pub async fn get_server(
     State(state): State<AppState>,
     Path(id): Path<String>,
@@ -277,13 +477,19 @@ pub async fn get_server(
 
     let pm = &state.process_manager;
     let dir_exists = StdPath::new(&server.working_dir).exists();
-    let is_running = pm.is_running(&server.id);
+    let is_running = pm.is_running(&server.id).await;
     let status = if !dir_exists {
         "missing"
-    } else if pm.is_installing(&server.id) {
-        if pm.is_auth_required(&server.id) { "auth_required" } else { "installing" }
+    } else if pm.is_installing(&server.id).await {
+        if pm.is_auth_required(&server.id).await { "auth_required" } else { "installing" }
     } else if is_running {
-        if pm.is_auth_required(&server.id) { "auth_required" } else { "running" }
+        if pm.is_suspended(&server.id).await {
+            "suspended"
+        } else if pm.is_unresponsive(&server.id).await {
+            "unresponsive"
+        } else if pm.is_auth_required(&server.id).await { "auth_required" } else { "running" }
+    } else if pm.is_queued(&server.id) {
+        "queued"
     } else {
         "stopped"
     };
@@ -374,6 +580,7 @@ pub async fn get_server(
 
     let started_at = pm.get_server_started_at(&server.id).await;
     let (cpu, cpu_norm, mem, mut disk) = pm.get_metrics_data(&server.id).await;
+    let network = pm.get_network_data(&server.id).await.unwrap_or_default();
 
     if disk == 0 {
         disk = WalkDir::new(&server.working_dir)
@@ -390,11 +597,15 @@ pub async fn get_server(
 
     let notifications = server.discord_notifications.as_ref()
         .and_then(|n| serde_json::from_str(n).ok());
+    let startup_duration_ms = history::startup_history(&state.pool, &server.id, 1).await?
+        .first()
+        .map(|s| s.duration_ms);
 
     Ok(Json(ServerResponse {
         id: server.id,
         name: server.name,
         game_type: server.game_type,
+        tags: parse_tags(server.tags.as_deref()),
         status: status.to_string(),
         executable_path: server.executable_path,
         working_dir: server.working_dir,
@@ -420,9 +631,43 @@ pub async fn get_server(
         discord_avatar: server.discord_avatar,
         discord_webhook_url: server.discord_webhook_url,
         discord_notifications: notifications,
+        discord_color: server.discord_color,
+        discord_emoji: server.discord_emoji,
         logs_retention_days: server.logs_retention_days as u32,
         watchdog_enabled: server.watchdog_enabled != 0,
         auth_mode: server.auth_mode,
+        env_vars: parse_env_vars(server.env_vars.as_deref()),
+        launch_mode: server.launch_mode,
+        command_template: server.command_template,
+        runtime: server.runtime,
+        docker_image: server.docker_image,
+        health_probe: server.health_probe,
+        health_probe_interval_secs: server.health_probe_interval_secs.max(0) as u32,
+        locale: server.locale,
+        stop_command: server.stop_command,
+        restart_schedule: server.restart_schedule,
+        install_state: server.install_state,
+        log_rotation_count: server.log_rotation_count as u32,
+        console_log_enabled: server.console_log_enabled != 0,
+        console_log_max_mb: server.console_log_max_mb.max(0) as u32,
+        console_log_dedup: server.console_log_dedup != 0,
+        network_role: server.network_role.filter(|r| !r.is_empty()),
+        parent_proxy_id: server.parent_proxy_id.filter(|p| !p.is_empty()),
+        backup_save_command: server.backup_save_command.filter(|v| !v.is_empty()),
+        backup_save_confirm: server.backup_save_confirm.filter(|v| !v.is_empty()),
+        backup_autosave_off_command: server.backup_autosave_off_command.filter(|v| !v.is_empty()),
+        backup_autosave_on_command: server.backup_autosave_on_command.filter(|v| !v.is_empty()),
+        backup_filename_template: server.backup_filename_template.filter(|v| !v.is_empty()),
+        alert_cpu_percent: server.alert_cpu_percent.map(|v| v as f32),
+        alert_ram_percent: server.alert_ram_percent.map(|v| v as f32),
+        alert_disk_mb: server.alert_disk_mb.map(|v| v.max(0) as u64),
+        owner_id: server.owner_id,
+        last_exit_code: server.last_exit_code,
+        last_exit_at: server.last_exit_at,
+        network_rx_bytes_per_sec: network.rx_bytes_per_sec,
+        network_tx_bytes_per_sec: network.tx_bytes_per_sec,
+        connections: network.connections,
+        startup_duration_ms,
 
         cpu_usage: cpu,
         cpu_usage_normalized: cpu_norm,
@@ -434,28 +679,116 @@ pub async fn get_server(
     }))
 }
 
+#[utoipa::path(post, path = "/servers/{id}/kill", tag = "servers", params(("id" = String, Path)), responses((status = 200, body = serde_json::Value)))]
 pub async fn kill_server(
     State(state): State<AppState>,
+    auth: Option<AuthUser>,
     Path(id): Path<String>,
 ) -> Result<Json<serde_json::Value>, AppError> {
     state.process_manager.kill(&id).await?;
+    record_power_action(&state.pool, auth.as_ref(), audit::ACTION_SERVER_KILL, &id).await;
     Ok(Json(serde_json::json!({ "success": true })))
 }
 
+/// Freeze the server process in place, e.g. to free the CPU for a while without a full stop
+#[utoipa::path(post, path = "/servers/{id}/suspend", tag = "servers", params(("id" = String, Path)), responses((status = 200, body = serde_json::Value)))]
+pub async fn suspend_server(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    state.process_manager.suspend(&id).await?;
+    history::record_event(&state.pool, &id, history::EVENT_SUSPEND, None).await;
+    Ok(Json(serde_json::json!({ "status": "suspended" })))
+}
+
+#[utoipa::path(post, path = "/servers/{id}/resume", tag = "servers", params(("id" = String, Path)), responses((status = 200, body = serde_json::Value)))]
+pub async fn resume_server(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    state.process_manager.resume(&id).await?;
+    history::record_event(&state.pool, &id, history::EVENT_RESUME, None).await;
+    Ok(Json(serde_json::json!({ "status": "running" })))
+}
+
+/// Replace the settings of a server. Optional fields left out keep their value, except
+/// `java_path`, the memory bounds and `extra_args`, which are cleared (see `patch_server`).
+#[utoipa::path(put, path = "/servers/{id}", tag = "servers", params(("id" = String, Path)), request_body = CreateServerRequest, responses((status = 200, body = serde_json::Value)))]
 pub async fn update_server(
     State(state): State<AppState>,
+    auth: AuthUser,
     Path(id): Path<String>,
     Json(body): Json<CreateServerRequest>,
 ) -> Result<Json<serde_json::Value>, AppError> {
+    let current: ServerRow = sqlx::query_as("SELECT * FROM servers WHERE id = ?")
+        .bind(&id)
+        .fetch_optional(&state.pool)
+        .await?
+        .ok_or_else(|| AppError::NotFound("servers.not_found".into()))?;
+    if !auth.is_admin() && changes_host_settings(&current, &body) {
+        return Err(AppError::Forbidden("servers.host_settings_admin_only".into()));
+    }
+    if body.working_dir != current.working_dir {
+        if !in_servers_dir(&state.pool, StdPath::new(&body.working_dir)).await {
+            return Err(AppError::BadRequest("servers.working_dir_outside_servers_dir".into()));
+        }
+        // The process keeps running from the old directory
+        if state.process_manager.is_running(&id).await {
+            return Err(AppError::Conflict("servers.working_dir_running".into()));
+        }
+    }
+
     let now = Utc::now().to_rfc3339();
     let auto_start = body.auto_start.unwrap_or(false) as i32;
 
     let config_str = body.config.as_ref().map(|c| c.to_string());
     let notifications_str = body.discord_notifications.as_ref().map(|c| c.to_string());
 
+    if let Some(env_vars) = &body.env_vars {
+        validate_env_vars(env_vars)?;
+    }
+    let env_vars_str = body.env_vars.as_ref().map(|v| serde_json::to_string(v).unwrap_or_else(|_| "{}".to_string()));
+    let tags_str = match &body.tags {
+        Some(tags) => Some(serde_json::to_string(&normalized_tags(tags)?).unwrap_or_else(|_| "[]".to_string())),
+        None => None,
+    };
+    // Checked on the row as it will be saved: either field may be left out of the patch
+    if body.launch_mode.is_some() || body.command_template.is_some() {
+        let launch_mode = body.launch_mode.as_deref().unwrap_or(&current.launch_mode);
+        validate_launch_mode(launch_mode, body.command_template.as_deref().or(current.command_template.as_deref()))?;
+    }
+    if let Some(runtime) = &body.runtime {
+        validate_runtime(runtime, body.docker_image.as_deref(), &body.game_type)?;
+    }
+    if let Some(locale) = &body.locale {
+        validate_locale(locale)?;
+    }
+    if let Some(schedule) = &body.restart_schedule {
+        validate_restart_schedule(schedule)?;
+    }
+    validate_discord_identity(body.discord_color.as_deref(), body.discord_emoji.as_deref())?;
+    validate_alert_thresholds(&body)?;
+    validate_health_probe(&body)?;
+    validate_console_log_max(body.console_log_max_mb)?;
+    validate_save_confirm(body.backup_save_confirm.as_deref())?;
+    validate_filename_template(body.backup_filename_template.as_deref())?;
+    if body.network_role.is_some() || body.parent_proxy_id.is_some() {
+        validate_network(&state.pool, Some(id.as_str()), body.network_role.as_deref(), body.parent_proxy_id.as_deref()).await?;
+    }
+    validate_game_type(&body.game_type, state.settings.dev_mode)?;
+    if let Some(address) = &body.bind_address {
+        validate_bind_address(address)?;
+    }
+
+    // Rewriting config.json: keep a rollback point of the current one
+    let snapshot_id = match &body.config {
+        Some(_) => snapshots::snapshot_before(&state.pool, &state.settings.backups_dir, &id, &body.working_dir, snapshots::OP_CONFIG_CHANGE).await?,
+        None => None,
+    };
+
     let result = sqlx::query(
         "UPDATE servers SET 
-        name = ?, game_type = ?, executable_path = ?, working_dir = ?, java_path = ?, min_memory = ?, max_memory = ?, extra_args = ?, config = ?, auto_start = ?, updated_at = ?,
+        name = ?, game_type = ?, executable_path = ?, working_dir = ?, java_path = ?, min_memory = ?, max_memory = ?, extra_args = ?, config = COALESCE(?, config), auto_start = ?, updated_at = ?,
         backup_enabled = COALESCE(?, backup_enabled),
         backup_frequency = COALESCE(?, backup_frequency),
         backup_max_backups = COALESCE(?, backup_max_backups),
@@ -468,7 +801,34 @@ pub async fn update_server(
         watchdog_enabled = COALESCE(?, watchdog_enabled),
         auth_mode = COALESCE(?, auth_mode),
         bind_address = COALESCE(?, bind_address),
-        port = COALESCE(?, port)
+        port = COALESCE(?, port),
+        env_vars = COALESCE(?, env_vars),
+        launch_mode = COALESCE(?, launch_mode),
+        command_template = COALESCE(?, command_template),
+        locale = COALESCE(?, locale),
+        stop_command = COALESCE(?, stop_command),
+        restart_schedule = COALESCE(?, restart_schedule),
+        log_rotation_count = COALESCE(?, log_rotation_count),
+        discord_color = COALESCE(?, discord_color),
+        discord_emoji = COALESCE(?, discord_emoji),
+        alert_cpu_percent = COALESCE(?, alert_cpu_percent),
+        alert_ram_percent = COALESCE(?, alert_ram_percent),
+        alert_disk_mb = COALESCE(?, alert_disk_mb),
+        runtime = COALESCE(?, runtime),
+        docker_image = COALESCE(?, docker_image),
+        health_probe = COALESCE(?, health_probe),
+        health_probe_interval_secs = COALESCE(?, health_probe_interval_secs),
+        console_log_enabled = COALESCE(?, console_log_enabled),
+        console_log_max_mb = COALESCE(?, console_log_max_mb),
+        console_log_dedup = COALESCE(?, console_log_dedup),
+        network_role = COALESCE(?, network_role),
+        parent_proxy_id = COALESCE(?, parent_proxy_id),
+        backup_save_command = COALESCE(?, backup_save_command),
+        backup_save_confirm = COALESCE(?, backup_save_confirm),
+        backup_autosave_off_command = COALESCE(?, backup_autosave_off_command),
+        backup_autosave_on_command = COALESCE(?, backup_autosave_on_command),
+        backup_filename_template = COALESCE(?, backup_filename_template),
+        tags = COALESCE(?, tags)
         WHERE id = ?",
     )
     .bind(&body.name)
@@ -495,6 +855,33 @@ pub async fn update_server(
     .bind(&body.auth_mode)
     .bind(&body.bind_address)
     .bind(body.port)
+    .bind(env_vars_str)
+    .bind(&body.launch_mode)
+    .bind(&body.command_template)
+    .bind(&body.locale)
+    .bind(&body.stop_command)
+    .bind(&body.restart_schedule)
+    .bind(body.log_rotation_count)
+    .bind(&body.discord_color)
+    .bind(&body.discord_emoji)
+    .bind(body.alert_cpu_percent)
+    .bind(body.alert_ram_percent)
+    .bind(body.alert_disk_mb.map(|v| v as i64))
+    .bind(&body.runtime)
+    .bind(&body.docker_image)
+    .bind(&body.health_probe)
+    .bind(body.health_probe_interval_secs)
+    .bind(body.console_log_enabled.map(|b| b as i32))
+    .bind(body.console_log_max_mb)
+    .bind(body.console_log_dedup.map(|b| b as i32))
+    .bind(body.network_role.as_deref().map(str::trim))
+    .bind(body.parent_proxy_id.as_deref().map(str::trim))
+    .bind(body.backup_save_command.as_deref().map(str::trim))
+    .bind(body.backup_save_confirm.as_deref().map(str::trim))
+    .bind(body.backup_autosave_off_command.as_deref().map(str::trim))
+    .bind(body.backup_autosave_on_command.as_deref().map(str::trim))
+    .bind(body.backup_filename_template.as_deref().map(str::trim))
+    .bind(tags_str)
     .bind(&id)
     .execute(&state.pool)
     .await?;
@@ -504,29 +891,164 @@ pub async fn update_server(
     }
 
     if let Some(config_json) = &body.config {
-        let root_config_path = StdPath::new(&body.working_dir).join("config.json");
-        let server_dir = StdPath::new(&body.working_dir).join("server");
-        let universe_dir = server_dir.join("universe");
-        let nested_config_path = universe_dir.join("config.json");
-        
-        if let Ok(json_str) = serde_json::to_string_pretty(config_json) {
-            if let Err(e) = tokio::fs::write(&root_config_path, &json_str).await {
-                error!("Failed to write root config.json for server {}: {}", id, e);
+        write_config_files(&id, &body.working_dir, config_json).await;
+    }
+
+    Ok(Json(serde_json::json!({ "success": true, "snapshot_id": snapshot_id })))
+}
+
+/// Write config.json at the root of the server and, once installed, in `server/universe/`
+pub(super) async fn write_config_files(id: &str, working_dir: &str, config_json: &serde_json::Value) {
+    let root_config_path = StdPath::new(working_dir).join("config.json");
+    let server_dir = StdPath::new(working_dir).join("server");
+    let universe_dir = server_dir.join("universe");
+    let nested_config_path = universe_dir.join("config.json");
+    
+    if let Ok(json_str) = serde_json::to_string_pretty(config_json) {
+        if let Err(e) = atomic_file::write(&root_config_path, json_str.as_str()).await {
+            error!("Failed to write root config.json for server {}: {}", id, e);
+        }
+        if server_dir.exists() {
+             if !universe_dir.exists() {
+                 let _ = tokio::fs::create_dir_all(&universe_dir).await;
+             }
+             if let Err(e) = atomic_file::write(&nested_config_path, json_str.as_str()).await {
+                error!("Failed to write nested server/universe/config.json for server {}: {}", id, e);
             }
-            if server_dir.exists() {
-                 if !universe_dir.exists() {
-                     let _ = tokio::fs::create_dir_all(&universe_dir).await;
-                 }
-                 if let Err(e) = tokio::fs::write(&nested_config_path, &json_str).await {
-                    error!("Failed to write nested server/universe/config.json for server {}: {}", id, e);
-                }
+        }
+    }
+}
+
+/// Change only the fields present in the body; `null` leaves a field as it is
+#[utoipa::path(
+    patch, path = "/servers/{id}", tag = "servers", params(("id" = String, Path)),
+    request_body(content = Object, description = "Any fields of a server creation request"),
+    responses(
+        (status = 200, body = serde_json::Value),
+        (status = 403, description = "`servers.host_settings_admin_only`"),
+        (status = 409, description = "`servers.working_dir_running`"),
+    )
+)]
+pub async fn patch_server(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(id): Path<String>,
+    Json(body): Json<serde_json::Value>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let serde_json::Value::Object(changes) = body else {
+        return Err(AppError::BadRequest("servers.invalid_request".into()));
+    };
+    let current: ServerRow = sqlx::query_as("SELECT * FROM servers WHERE id = ?")
+        .bind(&id)
+        .fetch_optional(&state.pool)
+        .await?
+        .ok_or_else(|| AppError::NotFound("servers.not_found".into()))?;
+
+    // The fields a full update overwrites start from their current value, the others are
+    // only changed when given
+    let mut request: serde_json::Map<String, serde_json::Value> = [
+        ("name", serde_json::Value::from(current.name)),
+        ("game_type", serde_json::Value::from(current.game_type)),
+        ("executable_path", serde_json::Value::from(current.executable_path)),
+        ("working_dir", serde_json::Value::from(current.working_dir)),
+        ("java_path", serde_json::Value::from(current.java_path)),
+        ("min_memory", serde_json::Value::from(current.min_memory)),
+        ("max_memory", serde_json::Value::from(current.max_memory)),
+        ("extra_args", serde_json::Value::from(current.extra_args)),
+        ("auto_start", serde_json::Value::from(current.auto_start != 0)),
+    ]
+    .into_iter()
+    .map(|(key, value)| (key.to_string(), value))
+    .collect();
+    request.extend(changes.into_iter().filter(|(_, value)| !value.is_null()));
+    let body: CreateServerRequest = serde_json::from_value(serde_json::Value::Object(request))
+        .map_err(|_| AppError::BadRequest("servers.invalid_request".into()))?;
+
+    update_server(State(state), auth, Path(id), Json(body)).await
+}
+
+/// Apply the same subset of settings to several servers, listed or selected by tag, in a single
+/// transaction
+#[utoipa::path(patch, path = "/servers/bulk-settings", tag = "servers", request_body = BulkSettingsRequest, responses((status = 200, body = serde_json::Value, description = "`results`: one BulkSettingsResult per server")))]
+pub async fn bulk_update_settings(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Json(body): Json<BulkSettingsRequest>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let allowed = server_access::allowed_servers(&state.pool, &auth, server_access::MANAGE).await?;
+
+    let mut server_ids = body.server_ids;
+    if let Some(tag) = body.tag.as_deref().map(|t| t.trim().to_lowercase()).filter(|t| !t.is_empty()) {
+        let tagged: Vec<(String, Option<String>)> = sqlx::query_as("SELECT id, tags FROM servers ORDER BY name")
+            .fetch_all(&state.pool)
+            .await?;
+        for (id, tags) in tagged {
+            // Servers the caller doesn't manage aren't part of its selection
+            let managed = allowed.as_ref().is_none_or(|allowed| allowed.contains(&id));
+            if managed && parse_tags(tags.as_deref()).contains(&tag) && !server_ids.contains(&id) {
+                server_ids.push(id);
             }
         }
     }
+    if server_ids.is_empty() {
+        return Err(AppError::BadRequest("servers.bulk_no_servers".into()));
+    }
 
-    Ok(Json(serde_json::json!({ "success": true })))
+    let settings = &body.settings;
+    let now = Utc::now().to_rfc3339();
+    let mut tx = state.pool.begin().await?;
+    let mut results = Vec::with_capacity(server_ids.len());
+
+    for server_id in &server_ids {
+        if allowed.as_ref().is_some_and(|allowed| !allowed.contains(server_id)) {
+            results.push(BulkSettingsResult {
+                server_id: server_id.clone(),
+                success: false,
+                error: Some("servers.access_denied".to_string()),
+            });
+            continue;
+        }
+        let result = sqlx::query(
+            "UPDATE servers SET updated_at = ?,
+            discord_webhook_url = COALESCE(?, discord_webhook_url),
+            backup_enabled = COALESCE(?, backup_enabled),
+            backup_frequency = COALESCE(?, backup_frequency),
+            backup_max_backups = COALESCE(?, backup_max_backups),
+            backup_prefix = COALESCE(?, backup_prefix),
+            logs_retention_days = COALESCE(?, logs_retention_days),
+            watchdog_enabled = COALESCE(?, watchdog_enabled)
+            WHERE id = ?",
+        )
+        .bind(&now)
+        .bind(&settings.discord_webhook_url)
+        .bind(settings.backup_enabled.map(|b| b as i32))
+        .bind(settings.backup_frequency)
+        .bind(settings.backup_max_backups)
+        .bind(&settings.backup_prefix)
+        .bind(settings.logs_retention_days)
+        .bind(settings.watchdog_enabled.map(|b| b as i32))
+        .bind(server_id)
+        .execute(&mut *tx)
+        .await?;
+
+        let found = result.rows_affected() > 0;
+        results.push(BulkSettingsResult {
+            server_id: server_id.clone(),
+            success: found,
+            error: (!found).then(|| "servers.not_found".to_string()),
+        });
+    }
+
+    tx.commit().await?;
+
+    let updated = results.iter().filter(|r| r.success).count();
+    Ok(Json(serde_json::json!({
+        "updated": updated,
+        "results": results,
+    })))
 }
 
+#[utoipa::path(delete, path = "/servers/{id}", tag = "servers", params(("id" = String, Path)), responses((status = 200, body = serde_json::Value)))]
 pub async fn delete_server(
     State(state): State<AppState>,
     Path(id): Path<String>,
@@ -537,7 +1059,7 @@ pub async fn delete_server(
         .await?;
 
     let pm = &state.process_manager;
-    if pm.is_running(&id) {
+    if pm.is_running(&id).await {
         pm.stop(&id).await?;
     }
 
@@ -550,9 +1072,16 @@ pub async fn delete_server(
         return Err(AppError::NotFound("servers.not_found".into()));
     }
 
+    // Members of a deleted proxy become standalone
+    sqlx::query("UPDATE servers SET parent_proxy_id = NULL WHERE parent_proxy_id = ?")
+        .bind(&id)
+        .execute(&state.pool)
+        .await?;
+
     if let Some((working_dir,)) = server {
         let path = StdPath::new(&working_dir);
-        if path.exists() {
+        // Only what the panel created: a row from before the directory was confined may point anywhere
+        if path.exists() && in_servers_dir(&state.pool, path).await {
              if let Err(e) = tokio::fs::remove_dir_all(path).await {
                  error!("Failed to remove server directory {}: {}", working_dir, e);
              } else {
@@ -564,8 +1093,10 @@ pub async fn delete_server(
     Ok(Json(serde_json::json!({ "success": true })))
 }
 
+#[utoipa::path(post, path = "/servers/{id}/start", tag = "servers", params(("id" = String, Path)), responses((status = 200, body = serde_json::Value)))]
 pub async fn start_server(
     State(state): State<AppState>,
+    auth: Option<AuthUser>,
     Path(id): Path<String>,
 ) -> Result<Json<serde_json::Value>, AppError> {
     let server: ServerRow = sqlx::query_as(
@@ -576,95 +1107,156 @@ pub async fn start_server(
     .await?
     .ok_or_else(|| AppError::NotFound("servers.not_found".into()))?;
 
-    let process_working_dir = StdPath::new(&server.working_dir).to_path_buf();
-    let process_working_dir_str = process_working_dir.to_str().unwrap_or(&server.working_dir);
+    if server.install_state != INSTALL_COMPLETED {
+        return Err(AppError::BadRequest("servers.install_incomplete".into()));
+    }
 
-    let config_json_path = process_working_dir.join("config.json");
-    let server_config: Option<serde_json::Value> = server.config.as_ref().and_then(|c| serde_json::from_str(c).ok());
-    
-    let port = server.port as u16;
-    let max_players = server_config.as_ref()
-        .and_then(|c| c.get("MaxPlayers"))
-        .and_then(|v| v.as_u64())
-        .map(|v| v as u32)
-        .unwrap_or(100);
-    let auth_mode = &server.auth_mode;
+    let queued = state.process_manager.enqueue_start(&server.id).await?;
+    record_power_action(&state.pool, auth.as_ref(), audit::ACTION_SERVER_START, &server.id).await;
+    if queued {
+        let pool = state.pool.clone();
+        let pm = state.process_manager.clone();
+        tokio::spawn(async move {
+            if let Err(e) = start_and_notify(&pool, &pm, &server).await {
+                error!("Queued start of server {} failed: {}", server.id, e);
+            }
+        });
+        return Ok(Json(serde_json::json!({ "status": "queued" })));
+    }
 
-    let hytale_config = templates::generate_config_json(
-        &server.name,
-        max_players, 
-        auth_mode 
-    );
-    
-    let mut hytale_config_obj = serde_json::to_value(hytale_config).unwrap();
-    if let Some(obj) = hytale_config_obj.as_object_mut() {
-        obj.insert("Port".to_string(), serde_json::json!(port));
-    }
-    if let Ok(mut config_file) = fs::File::create(&config_json_path).await {
-         let _ = config_file.write_all(serde_json::to_string_pretty(&hytale_config_obj).unwrap().as_bytes()).await;
-    }
-
-    let mut pm_config = server.config.as_ref()
-        .and_then(|c| serde_json::from_str::<serde_json::Value>(c).ok())
-        .unwrap_or(serde_json::json!({}));
-    if let Some(obj) = pm_config.as_object_mut() {
-        obj.insert("port".to_string(), serde_json::json!(server.port));
-        obj.insert("bind_address".to_string(), serde_json::json!(server.bind_address));
-    }
-
-    state.process_manager.start(
-        &server.id,
-        &server.executable_path,
-        process_working_dir_str,
-        server.java_path.as_deref(),
-        server.min_memory.as_deref(),
-        server.max_memory.as_deref(),
-        server.extra_args.as_deref(),
-        Some(&pm_config),
-    )
-    .await?;
+    start_and_notify(&state.pool, &state.process_manager, &server).await?;
+
+    Ok(Json(serde_json::json!({ "status": "starting" })))
+}
+
+/// Note a power action in the activity feed of the user who asked for it, none for hooks
+async fn record_power_action(pool: &DbPool, auth: Option<&AuthUser>, action: &str, server_id: &str) {
+    let Some(auth) = auth else {
+        return;
+    };
+    let name: Option<String> = sqlx::query_scalar("SELECT name FROM servers WHERE id = ?")
+        .bind(server_id)
+        .fetch_optional(pool)
+        .await
+        .ok()
+        .flatten();
+    let detail = name.as_deref().unwrap_or(server_id);
+    audit::record(pool, Some(&auth.id), Some(&auth.username), action, Some(detail), None).await;
+}
+
+/// Launch a server already passed to `enqueue_start` and record the lifecycle event. Manual,
+/// queued and boot-time starts all come through here, so they prepare config.json alike.
+async fn launch_enqueued(pool: &DbPool, pm: &ProcessManager, server: &ServerRow, event: &str) -> Result<(), AppError> {
+    prepare_config_json(server).await;
+    pm.start_queued(&server.id, &build_launch_config(server)).await?;
+    history::record_event(pool, &server.id, event, None).await;
+    Ok(())
+}
+
+/// Installs cut short by a panel restart can't resume on their own, flag them so they can be retried
+pub async fn mark_interrupted_installs(pool: &DbPool) {
+    let result = sqlx::query("UPDATE servers SET install_state = ? WHERE install_state IN (?, ?)")
+        .bind(INSTALL_FAILED)
+        .bind(INSTALL_PENDING)
+        .bind(INSTALL_INSTALLING)
+        .execute(pool)
+        .await;
+
+    match result {
+        Ok(r) if r.rows_affected() > 0 => info!("Marked {} interrupted installation(s) as failed", r.rows_affected()),
+        Ok(_) => {}
+        Err(e) => error!("Failed to check interrupted installations: {}", e),
+    }
+}
+
+/// Start every `auto_start` server through the start queue (called once at boot)
+pub async fn auto_start_servers(pool: &DbPool, pm: &ProcessManager) {
+    let servers: Vec<ServerRow> = match sqlx::query_as("SELECT * FROM servers WHERE auto_start = 1 AND install_state = ?")
+        .bind(INSTALL_COMPLETED)
+        .fetch_all(pool)
+        .await
+    {
+        Ok(servers) => servers,
+        Err(e) => {
+            error!("Failed to load auto-start servers: {}", e);
+            return;
+        }
+    };
 
-    let pool_clone = state.pool.clone();
+    for server in servers {
+        if let Err(e) = pm.enqueue_start(&server.id).await {
+            error!("Auto-start of server {} skipped: {}", server.name, e);
+            continue;
+        }
+        let pool = pool.clone();
+        let pm = pm.clone();
+        tokio::spawn(async move {
+            if let Err(e) = launch_enqueued(&pool, &pm, &server, history::EVENT_START).await {
+                error!("Auto-start of server {} failed: {}", server.name, e);
+            }
+        });
+    }
+}
+
+async fn start_and_notify(pool: &DbPool, pm: &ProcessManager, server: &ServerRow) -> Result<(), AppError> {
+    launch_enqueued(pool, pm, server, history::EVENT_START).await?;
+
+    let pool_clone = pool.clone();
     let server_name = server.name.clone();
     let webhook_url = server.discord_webhook_url.clone().filter(|u| !u.is_empty());
+    let title = format!("{} Serveur Démarré", discord_service::online_emoji(server.discord_emoji.as_deref()));
+    let color = discord_service::server_color(server.discord_color.as_deref(), discord_service::COLOR_SUCCESS);
         
     if let Some(url) = webhook_url {
         tokio::spawn(async move {
-            crate::services::discord_service::send_notification(
+            discord_service::send_notification(
                 &pool_clone,
-                "🟢 Serveur Démarré",
+                &title,
                 &format!("Le serveur **{}** a été démarré.", server_name),
-                crate::services::discord_service::COLOR_SUCCESS,
+                color,
                 Some(&server_name),
                 Some(&url),
             ).await;
         });
     }
 
-    Ok(Json(serde_json::json!({ "status": "starting" })))
+    Ok(())
 }
 
+#[utoipa::path(post, path = "/servers/{id}/stop", tag = "servers", params(("id" = String, Path)), responses((status = 200, body = serde_json::Value)))]
 pub async fn stop_server(
     State(state): State<AppState>,
+    auth: Option<AuthUser>,
     Path(id): Path<String>,
 ) -> Result<Json<serde_json::Value>, AppError> {
     let server: Option<ServerRow> = sqlx::query_as("SELECT * FROM servers WHERE id = ?")
         .bind(&id)
         .fetch_optional(&state.pool)
         .await?;
-    
+
+    if state.process_manager.cancel_queued_start(&id) {
+        record_power_action(&state.pool, auth.as_ref(), audit::ACTION_SERVER_STOP, &id).await;
+        return Ok(Json(serde_json::json!({ "status": "cancelled" })));
+    }
+
+    let was_installing = state.process_manager.is_installing(&id).await;
     state.process_manager.stop(&id).await?;
+    record_power_action(&state.pool, auth.as_ref(), audit::ACTION_SERVER_STOP, &id).await;
+    if was_installing {
+        set_install_state(&state.pool, &id, INSTALL_FAILED).await;
+    }
+    history::record_event(&state.pool, &id, history::EVENT_STOP, None).await;
     
     if let Some(s) = server {
         let pool_clone = state.pool.clone();
         if let Some(url) = s.discord_webhook_url {
             if !url.is_empty() {
                 tokio::spawn(async move {
-                    crate::services::discord_service::send_notification(
+                    discord_service::send_notification(
                         &pool_clone,
                         "🔴 Serveur Arrêté",
                         &format!("Le serveur **{}** a été arrêté.", s.name),
-                        crate::services::discord_service::COLOR_ERROR,
+                        discord_service::server_color(s.discord_color.as_deref(), discord_service::COLOR_ERROR),
                         Some(&s.name),
                         Some(&url),
                     ).await;
@@ -676,8 +1268,10 @@ pub async fn stop_server(
     Ok(Json(serde_json::json!({ "status": "stopping" })))
 }
 
+#[utoipa::path(post, path = "/servers/{id}/restart", tag = "servers", params(("id" = String, Path)), responses((status = 200, body = serde_json::Value)))]
 pub async fn restart_server(
     State(state): State<AppState>,
+    auth: Option<AuthUser>,
     Path(id): Path<String>,
 ) -> Result<Json<serde_json::Value>, AppError> {
     let server: ServerRow = sqlx::query_as(
@@ -688,24 +1282,31 @@ pub async fn restart_server(
     .await?
     .ok_or_else(|| AppError::NotFound("servers.not_found".into()))?;
 
-    let process_working_dir = StdPath::new(&server.working_dir).join("server");
-    let process_working_dir_str = process_working_dir.to_str().unwrap_or(&server.working_dir);
-
-    state.process_manager.restart(
-        &server.id,
-        &server.executable_path,
-        process_working_dir_str,
-        server.java_path.as_deref(),
-        server.min_memory.as_deref(),
-        server.max_memory.as_deref(),
-        server.extra_args.as_deref(),
-        server.config.as_ref().and_then(|c| serde_json::from_str(c).ok()).as_ref(),
-    )
-    .await?;
+    if state.process_manager.is_running(&server.id).await {
+        let _ = broadcast_messages::broadcast(&state.process_manager, &server.id, &server.locale, "restart_now", &[]).await;
+        state.process_manager.stop(&server.id).await?;
+    }
+
+    // Mass restarts go through the start queue instead of booting every JVM at once
+    let queued = state.process_manager.enqueue_start(&server.id).await?;
+    record_power_action(&state.pool, auth.as_ref(), audit::ACTION_SERVER_RESTART, &server.id).await;
+    if queued {
+        let pool = state.pool.clone();
+        let pm = state.process_manager.clone();
+        tokio::spawn(async move {
+            if let Err(e) = launch_enqueued(&pool, &pm, &server, history::EVENT_RESTART).await {
+                error!("Queued restart of server {} failed: {}", server.id, e);
+            }
+        });
+        return Ok(Json(serde_json::json!({ "status": "queued" })));
+    }
+
+    launch_enqueued(&state.pool, &state.process_manager, &server, history::EVENT_RESTART).await?;
 
     Ok(Json(serde_json::json!({ "status": "restarting" })))
 }
 
+#[utoipa::path(post, path = "/servers/{id}/command", tag = "servers", params(("id" = String, Path)), request_body = CommandRequest, responses((status = 200, body = serde_json::Value)))]
 pub async fn send_command(
     State(state): State<AppState>,
     Path(id): Path<String>,
@@ -715,6 +1316,23 @@ pub async fn send_command(
     Ok(Json(serde_json::json!({ "success": true })))
 }
 
+/// RCON-like command: send it and return the console output printed in response
+#[utoipa::path(post, path = "/servers/{id}/command/exec", tag = "servers", params(("id" = String, Path)), request_body = ExecCommandRequest, responses((status = 200, body = serde_json::Value)))]
+pub async fn exec_command(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Json(body): Json<ExecCommandRequest>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let window = std::time::Duration::from_millis(body.timeout_ms.unwrap_or(2000).clamp(100, 10_000));
+    let lines = state.process_manager.exec_command(&id, &body.command, window).await?;
+    Ok(Json(serde_json::json!({
+        "success": true,
+        "command": body.command,
+        "lines": lines
+    })))
+}
+
+#[utoipa::path(post, path = "/servers/{id}/reinstall", tag = "servers", params(("id" = String, Path)), responses((status = 200, body = serde_json::Value)))]
 pub async fn reinstall_server(
     State(state): State<AppState>,
     Path(id): Path<String>,
@@ -729,7 +1347,9 @@ pub async fn reinstall_server(
     .ok_or_else(|| AppError::NotFound("servers.not_found".into()))?;
 
     let pm = &state.process_manager;
-    if pm.is_running(&id) {
+    // Covers the cleanup, the running installer then keeps backups and restores away
+    let _guard = state.operations.try_acquire(pm, &id, operation_lock::OP_REINSTALL).await?;
+    if pm.is_running(&id).await {
         info!("Stopping server {} for reinstallation...", id);
         pm.stop(&id).await?;
         tokio::time::sleep(tokio::time::Duration::from_secs(2)).await; 
@@ -740,6 +1360,8 @@ pub async fn reinstall_server(
          let _ = fs::create_dir_all(base_path).await;
     }
 
+    let snapshot_id = snapshots::snapshot_before(&state.pool, &state.settings.backups_dir, &id, &server.working_dir, snapshots::OP_REINSTALL).await?;
+
     info!("Cleaning up server binaries in {:?} (preserving user data)...", base_path);
     
     let files_to_delete = vec![
@@ -748,6 +1370,7 @@ pub async fn reinstall_server(
         "lib", 
         "Assets.zip",
         "hytale-downloader.zip",
+        INSTALL_BUNDLE_NAME,
         "QUICKSTART.md",
         "hytale-downloader-linux-amd64",
         "hytale-downloader-windows-amd64.exe",
@@ -780,24 +1403,399 @@ pub async fn reinstall_server(
             100, 
             &auth_mode
         );
-        if let Ok(mut config_file) = fs::File::create(&config_json_path).await {
-            let _ = config_file.write_all(serde_json::to_string_pretty(&hytale_config).unwrap().as_bytes()).await;
+        if let Err(e) = atomic_file::write(&config_json_path, serde_json::to_string_pretty(&hytale_config).unwrap()).await {
+            error!("Failed to write config.json for server {}: {}", id, e);
         }
     }
 
-    spawn_hytale_installation(state.pool.clone(), pm.clone(), id.clone(), base_path.to_path_buf());
+    set_install_state(&state.pool, &id, INSTALL_PENDING).await;
+    spawn_hytale_installation(state.pool.clone(), pm.clone(), id.clone(), base_path.to_path_buf(), InstallSource::Downloader);
 
     Ok(Json(serde_json::json!({ 
         "success": true,
         "message": "Reinstallation started",
-        "working_dir": base_path.to_string_lossy()
+        "working_dir": base_path.to_string_lossy(),
+        "snapshot_id": snapshot_id
     })))
 }
 
+/// Resume an installation that failed or was interrupted, keeping already downloaded files
+#[utoipa::path(post, path = "/servers/{id}/install/retry", tag = "servers", params(("id" = String, Path)), responses((status = 200, body = serde_json::Value)))]
+pub async fn retry_install(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let server: ServerRow = sqlx::query_as(
+        "SELECT * FROM servers WHERE id = ?"
+    )
+    .bind(&id)
+    .fetch_optional(&state.pool)
+    .await?
+    .ok_or_else(|| AppError::NotFound("servers.not_found".into()))?;
+
+    if server.install_state == INSTALL_COMPLETED {
+        return Err(AppError::BadRequest("servers.install_already_completed".into()));
+    }
+    if state.process_manager.is_installing(&id).await {
+        return Err(AppError::BadRequest("servers.install_in_progress".into()));
+    }
+    if server.game_type != "hytale" {
+        // Nothing to download for other game types
+        set_install_state(&state.pool, &id, INSTALL_COMPLETED).await;
+        return Ok(Json(serde_json::json!({ "success": true, "install_state": INSTALL_COMPLETED })));
+    }
+
+    let base_path = StdPath::new(&server.working_dir);
+    if !base_path.exists() {
+        fs::create_dir_all(base_path).await
+            .map_err(|e| AppError::Internal(format!("Failed to create directory {:?}: {}", base_path, e)))?;
+    }
+
+    // A bundle left over from a failed offline install is extracted again instead of downloading
+    let source = if base_path.join(INSTALL_BUNDLE_NAME).exists() { InstallSource::Bundle } else { InstallSource::Downloader };
+    set_install_state(&state.pool, &id, INSTALL_PENDING).await;
+    spawn_hytale_installation(state.pool.clone(), state.process_manager.clone(), id.clone(), base_path.to_path_buf(), source);
+
+    Ok(Json(serde_json::json!({ "success": true, "install_state": INSTALL_PENDING })))
+}
+
+/// Offline install: store the uploaded server bundle zip, then extract and validate it locally
+#[utoipa::path(post, path = "/servers/{id}/install/bundle", tag = "servers", params(("id" = String, Path)), request_body(content_type = "multipart/form-data", description = "Server bundle archive"), responses((status = 200, body = serde_json::Value)))]
+pub async fn upload_install_bundle(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    mut multipart: Multipart,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let server: ServerRow = sqlx::query_as(
+        "SELECT * FROM servers WHERE id = ?"
+    )
+    .bind(&id)
+    .fetch_optional(&state.pool)
+    .await?
+    .ok_or_else(|| AppError::NotFound("servers.not_found".into()))?;
+
+    if server.game_type != "hytale" {
+        return Err(AppError::BadRequest("servers.bundle_unsupported".into()));
+    }
+    let pm = &state.process_manager;
+    if pm.is_installing(&id).await {
+        return Err(AppError::BadRequest("servers.install_in_progress".into()));
+    }
+    if pm.is_running(&id).await {
+        return Err(AppError::BadRequest("servers.already_running".into()));
+    }
+    let _guard = state.operations.try_acquire(pm, &id, operation_lock::OP_REINSTALL).await?;
+
+    let base_path = StdPath::new(&server.working_dir);
+    fs::create_dir_all(base_path).await
+        .map_err(|e| AppError::Internal(format!("Failed to create directory {:?}: {}", base_path, e)))?;
+    let bundle_path = base_path.join(INSTALL_BUNDLE_NAME);
+
+    let mut received = false;
+    while let Some(mut field) = multipart.next_field().await.map_err(|e| AppError::BadRequest(format!("upload.invalid_multipart: {}", e)))? {
+        if field.name() != Some("file") {
+            continue;
+        }
+
+        // Streamed to disk, server bundles are several GB
+        let mut file = fs::File::create(&bundle_path).await
+            .map_err(|e| AppError::Internal(format!("Failed to create file: {}", e)))?;
+        let mut magic: Vec<u8> = Vec::with_capacity(4);
+        while let Some(chunk) = field.chunk().await.map_err(|e| AppError::BadRequest(format!("upload.invalid_multipart: {}", e)))? {
+            if magic.len() < 4 {
+                magic.extend(chunk.iter().take(4 - magic.len()));
+            }
+            file.write_all(&chunk).await
+                .map_err(|e| AppError::Internal(format!("Failed to write file: {}", e)))?;
+        }
+        file.flush().await
+            .map_err(|e| AppError::Internal(format!("Failed to write file: {}", e)))?;
+
+        if magic != b"PK\x03\x04" {
+            let _ = fs::remove_file(&bundle_path).await;
+            return Err(AppError::BadRequest("servers.invalid_bundle".into()));
+        }
+        received = true;
+        break;
+    }
+
+    if !received {
+        return Err(AppError::BadRequest("servers.bundle_missing".into()));
+    }
+
+    let snapshot_id = snapshots::snapshot_before(&state.pool, &state.settings.backups_dir, &id, &server.working_dir, snapshots::OP_BUNDLE_INSTALL).await?;
+
+    info!("Installing server {} from uploaded bundle", id);
+    set_install_state(&state.pool, &id, INSTALL_PENDING).await;
+    spawn_hytale_installation(state.pool.clone(), pm.clone(), id.clone(), base_path.to_path_buf(), InstallSource::Bundle);
+
+    Ok(Json(serde_json::json!({ "success": true, "install_state": INSTALL_PENDING, "snapshot_id": snapshot_id })))
+}
+
+/// Boot times of the last startups, to spot a server getting slower to start
+#[utoipa::path(get, path = "/servers/{id}/startups", tag = "servers", params(("id" = String, Path), StartupHistoryQuery), responses((status = 200, body = [history::StartupSample])))]
+pub async fn get_startup_history(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Query(query): Query<StartupHistoryQuery>,
+) -> Result<Json<Vec<history::StartupSample>>, AppError> {
+    let limit = query.limit.unwrap_or(20).clamp(1, 200);
+    Ok(Json(history::startup_history(&state.pool, &id, limit).await?))
+}
+
 // Helpers
-fn spawn_hytale_installation(pool: DbPool, pm: ProcessManager, id: String, server_path: PathBuf) {
+
+/// Lowercase, trimmed and deduplicated, at most `MAX_TAGS` of `MAX_TAG_LEN` characters
+fn normalized_tags(tags: &[String]) -> Result<Vec<String>, AppError> {
+    let mut normalized: Vec<String> = Vec::new();
+    for tag in tags {
+        let tag = tag.trim().to_lowercase();
+        if tag.is_empty() || normalized.contains(&tag) {
+            continue;
+        }
+        if tag.chars().count() > MAX_TAG_LEN || tag.contains(',') {
+            return Err(AppError::BadRequest("servers.invalid_tag".into()));
+        }
+        normalized.push(tag);
+    }
+    if normalized.len() > MAX_TAGS {
+        return Err(AppError::BadRequest("servers.too_many_tags".into()));
+    }
+    Ok(normalized)
+}
+
+pub(super) fn parse_tags(raw: Option<&str>) -> Vec<String> {
+    raw.and_then(|r| serde_json::from_str(r).ok()).unwrap_or_default()
+}
+
+/// Whether a server has every wanted tag and, with a search, its name, a tag, its port or its
+/// game type contains it
+fn matches_search(server: &ServerRow, search: Option<&str>, wanted_tags: &[String]) -> bool {
+    let tags = parse_tags(server.tags.as_deref());
+    if !wanted_tags.iter().all(|wanted| tags.contains(wanted)) {
+        return false;
+    }
+    let Some(search) = search else {
+        return true;
+    };
+    server.name.to_lowercase().contains(search)
+        || server.game_type.to_lowercase().contains(search)
+        || server.port.to_string().contains(search)
+        || tags.iter().any(|tag| tag.contains(search))
+}
+
+pub(super) fn parse_env_vars(raw: Option<&str>) -> HashMap<String, String> {
+    raw.and_then(|r| serde_json::from_str(r).ok()).unwrap_or_default()
+}
+
+fn validate_env_vars(env_vars: &HashMap<String, String>) -> Result<(), AppError> {
+    for (key, value) in env_vars {
+        if key.is_empty() || key.contains('=') || key.contains('\0') || value.contains('\0') {
+            return Err(AppError::BadRequest("servers.invalid_env_var".into()));
+        }
+    }
+    Ok(())
+}
+
+fn validate_locale(locale: &str) -> Result<(), AppError> {
+    if broadcast_messages::is_supported_locale(locale) {
+        Ok(())
+    } else {
+        Err(AppError::BadRequest("servers.invalid_locale".into()))
+    }
+}
+
+fn validate_restart_schedule(schedule: &str) -> Result<(), AppError> {
+    if schedule.trim().is_empty() || scheduled_restart::parse_schedule(schedule).is_some() {
+        Ok(())
+    } else {
+        Err(AppError::BadRequest("servers.invalid_restart_schedule".into()))
+    }
+}
+
+fn validate_discord_identity(color: Option<&str>, emoji: Option<&str>) -> Result<(), AppError> {
+    if color.is_some_and(|c| !c.trim().is_empty() && discord_service::parse_color(c).is_none()) {
+        return Err(AppError::BadRequest("servers.invalid_discord_color".into()));
+    }
+    // Room for custom emojis such as <:name:123456789012345678>
+    if emoji.is_some_and(|e| e.chars().count() > 64) {
+        return Err(AppError::BadRequest("servers.invalid_discord_emoji".into()));
+    }
+    Ok(())
+}
+
+fn validate_alert_thresholds(body: &CreateServerRequest) -> Result<(), AppError> {
+    let invalid_percent = |v: Option<f32>| matches!(v, Some(v) if !(0.0..=100.0).contains(&v));
+    if invalid_percent(body.alert_cpu_percent) || invalid_percent(body.alert_ram_percent) {
+        return Err(AppError::BadRequest("servers.invalid_alert_threshold".into()));
+    }
+    Ok(())
+}
+
+fn validate_health_probe(body: &CreateServerRequest) -> Result<(), AppError> {
+    if body.health_probe.as_deref().is_some_and(|k| !health_probe::is_valid_kind(k.trim())) {
+        return Err(AppError::BadRequest("servers.invalid_health_probe".into()));
+    }
+    let range = health_probe::MIN_INTERVAL_SECS..=health_probe::MAX_INTERVAL_SECS;
+    if body.health_probe_interval_secs.is_some_and(|secs| !range.contains(&secs)) {
+        return Err(AppError::BadRequest("servers.invalid_health_probe_interval".into()));
+    }
+    Ok(())
+}
+
+fn validate_save_confirm(pattern: Option<&str>) -> Result<(), AppError> {
+    if pattern.map(str::trim).is_some_and(|p| !p.is_empty() && regex::Regex::new(p).is_err()) {
+        return Err(AppError::BadRequest("servers.invalid_backup_save_confirm".into()));
+    }
+    Ok(())
+}
+
+fn validate_filename_template(template: Option<&str>) -> Result<(), AppError> {
+    if template.map(str::trim).is_some_and(|t| !t.is_empty() && !backup_service::is_valid_filename_template(t)) {
+        return Err(AppError::BadRequest("servers.invalid_backup_filename_template".into()));
+    }
+    Ok(())
+}
+
+fn validate_console_log_max(max_mb: Option<u32>) -> Result<(), AppError> {
+    if max_mb.is_some_and(|mb| mb == 0 || mb > console_log::MAX_SIZE_MB_LIMIT) {
+        return Err(AppError::BadRequest("servers.invalid_console_log_max_size".into()));
+    }
+    Ok(())
+}
+
+/// A member's parent must be an existing proxy; a proxy can't sit behind another one, and
+/// stays a proxy while servers point to it
+async fn validate_network(pool: &DbPool, id: Option<&str>, role: Option<&str>, parent_proxy_id: Option<&str>) -> Result<(), AppError> {
+    let role = role.map(str::trim);
+    if role.is_some_and(|r| !network::is_valid_role(r)) {
+        return Err(AppError::BadRequest("servers.invalid_network_role".into()));
+    }
+
+    let current: Option<(Option<String>, Option<String>)> = match id {
+        Some(id) => sqlx::query_as("SELECT network_role, parent_proxy_id FROM servers WHERE id = ?")
+            .bind(id)
+            .fetch_optional(pool)
+            .await?,
+        None => None,
+    };
+    let (current_role, current_parent) = current.unwrap_or_default();
+    let role = role.map(str::to_string).or(current_role).unwrap_or_default();
+    let parent = parent_proxy_id.map(|p| p.trim().to_string()).or(current_parent).filter(|p| !p.is_empty());
+
+    if let Some(parent) = parent {
+        if role == network::ROLE_PROXY {
+            return Err(AppError::BadRequest("servers.proxy_cannot_have_parent".into()));
+        }
+        if id == Some(parent.as_str()) {
+            return Err(AppError::BadRequest("servers.invalid_parent_proxy".into()));
+        }
+        let parent_role: Option<Option<String>> = sqlx::query_scalar("SELECT network_role FROM servers WHERE id = ?")
+            .bind(&parent)
+            .fetch_optional(pool)
+            .await?;
+        match parent_role {
+            None => return Err(AppError::BadRequest("servers.invalid_parent_proxy".into())),
+            Some(parent_role) if parent_role.as_deref() != Some(network::ROLE_PROXY) => {
+                return Err(AppError::BadRequest("servers.parent_not_proxy".into()));
+            }
+            Some(_) => {}
+        }
+    }
+
+    if let Some(id) = id.filter(|_| role != network::ROLE_PROXY) {
+        let members: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM servers WHERE parent_proxy_id = ?")
+            .bind(id)
+            .fetch_one(pool)
+            .await?;
+        if members > 0 {
+            return Err(AppError::BadRequest("servers.proxy_has_members".into()));
+        }
+    }
+    Ok(())
+}
+
+/// Whether `dir` lies inside `servers_dir`, and isn't that directory itself. A server's directory
+/// is written by the panel and removed along with the server.
+async fn in_servers_dir(pool: &DbPool, dir: &StdPath) -> bool {
+    let (Ok(base), Ok(dir)) = (std::path::absolute(settings::servers_dir(pool).await), std::path::absolute(dir)) else {
+        return false;
+    };
+    !dir.components().any(|c| matches!(c, std::path::Component::ParentDir)) && dir != base && dir.starts_with(&base)
+}
+
+/// Whether an update changes what the server runs on the host, or where: admins only
+fn changes_host_settings(current: &ServerRow, body: &CreateServerRequest) -> bool {
+    // Empty and missing are the same value
+    fn set(value: Option<&str>) -> Option<&str> {
+        value.filter(|v| !v.is_empty())
+    }
+    // Left out of the request, these keep their value
+    let kept = |new: Option<&str>, old: Option<&str>| set(new).is_none() || set(new) == set(old);
+    body.working_dir != current.working_dir
+        || body.executable_path != current.executable_path
+        || set(body.java_path.as_deref()) != set(current.java_path.as_deref())
+        || set(body.extra_args.as_deref()) != set(current.extra_args.as_deref())
+        || body.env_vars.as_ref().is_some_and(|vars| *vars != parse_env_vars(current.env_vars.as_deref()))
+        || !kept(body.launch_mode.as_deref(), Some(&current.launch_mode))
+        || !kept(body.command_template.as_deref(), current.command_template.as_deref())
+        || !kept(body.runtime.as_deref(), Some(&current.runtime))
+        || !kept(body.docker_image.as_deref(), current.docker_image.as_deref())
+}
+
+fn validate_game_type(game_type: &str, dev_mode: bool) -> Result<(), AppError> {
+    if game_type == "dummy" && !dev_mode {
+        return Err(AppError::BadRequest("servers.dummy_requires_dev_mode".into()));
+    }
+    Ok(())
+}
+
+fn validate_launch_mode(launch_mode: &str, command_template: Option<&str>) -> Result<(), AppError> {
+    match launch_mode {
+        "java" => Ok(()),
+        "command" if command_template.is_some_and(|t| !t.trim().is_empty()) => Ok(()),
+        "command" => Err(AppError::BadRequest("servers.command_template_required".into())),
+        _ => Err(AppError::BadRequest("servers.invalid_launch_mode".into())),
+    }
+}
+
+fn validate_bind_address(address: &str) -> Result<(), AppError> {
+    interfaces::check_bind_address(address).map_err(|key| AppError::BadRequest(key.into()))
+}
+
+fn validate_runtime(runtime: &str, docker_image: Option<&str>, game_type: &str) -> Result<(), AppError> {
+    match runtime {
+        docker::RUNTIME_NATIVE => Ok(()),
+        // The simulator is this binary, it can't run in an arbitrary image
+        docker::RUNTIME_DOCKER if game_type == "dummy" => Err(AppError::BadRequest("servers.docker_unsupported_launch_mode".into())),
+        docker::RUNTIME_DOCKER if docker_image.is_some_and(|i| !i.trim().is_empty()) => Ok(()),
+        docker::RUNTIME_DOCKER => Err(AppError::BadRequest("servers.docker_image_required".into())),
+        _ => Err(AppError::BadRequest("servers.invalid_runtime".into())),
+    }
+}
+
+/// Where an installation gets the server files from
+#[derive(Clone, Copy, PartialEq)]
+enum InstallSource {
+    /// Run the Hytale downloader (skipped while the `offline_install` setting is on)
+    Downloader,
+    /// Extract the bundle uploaded to `INSTALL_BUNDLE_NAME`
+    Bundle,
+}
+
+async fn get_setting(pool: &DbPool, key: &str) -> Option<String> {
+    sqlx::query_scalar("SELECT value FROM settings WHERE key = ?")
+        .bind(key)
+        .fetch_optional(pool)
+        .await
+        .ok()
+        .flatten()
+}
+
+fn spawn_hytale_installation(pool: DbPool, pm: ProcessManager, id: String, server_path: PathBuf, source: InstallSource) {
     tokio::spawn(async move {
         let (tx_start, rx_start) = tokio::sync::oneshot::channel::<()>();
+        let pool_outer = pool.clone();
         
         let pm_inner = pm.clone();
         let id_inner = id.clone();
@@ -809,9 +1807,6 @@ fn spawn_hytale_installation(pool: DbPool, pm: ProcessManager, id: String, serve
                 return; // Aborted before start
             }
             
-            let working_dir_str = server_path_inner.to_string_lossy().to_string();
-            // Note: register_installing is done by parent
-            
             let logs_dir = server_path_inner.join("logs");
             if !logs_dir.exists() {
                  let _ = tokio::fs::create_dir_all(&logs_dir).await;
@@ -828,12 +1823,8 @@ fn spawn_hytale_installation(pool: DbPool, pm: ProcessManager, id: String, serve
                 let id = id_inner.clone();
                 let log_file = log_file.clone();
                 async move {
-                    if (msg.contains("IMPORTANT") && (msg.contains("authentifier") || msg.contains("authenticate"))) ||
-                       (msg.contains("[HytaleServer] No server tokens configured")) ||
-                       (msg.contains("/auth login to authenticate")) {
-                        pm.set_auth_required(&id, true);
-                    }
                     pm.broadcast_log(&id, msg.clone()).await;
+                    detect_install_auth(&pm, &id, &msg).await;
                     if let Some(f) = log_file {
                         let mut guard = f.lock().await;
                         let _ = guard.write_all(format!("{}\n", msg).as_bytes()).await;
@@ -841,77 +1832,119 @@ fn spawn_hytale_installation(pool: DbPool, pm: ProcessManager, id: String, serve
                 }
             };
 
+            set_install_state(&pool, &id_inner, INSTALL_INSTALLING).await;
             broadcast("🚀 Initialization de l'installation du serveur...".to_string()).await;
 
-            let zip_url = "https://downloader.hytale.com/hytale-downloader.zip";
-            let zip_name = "hytale-downloader.zip";
-            let dest_path = server_path_inner.join(zip_name);
+            if source == InstallSource::Downloader {
+                if get_setting(&pool, "offline_install").await.as_deref() == Some("true") {
+                    broadcast("📴 Mode hors ligne : envoyez l'archive du serveur pour terminer l'installation.".to_string()).await;
+                    broadcast(format!("[INSTALL_ERROR]: {}", serde_json::json!({
+                        "code": "bundle_required",
+                        "message": "Offline install is enabled: upload the server bundle zip",
+                        "setting": "offline_install"
+                    }))).await;
+                    finish_install(&pool, &pm_inner, &id_inner, INSTALL_PENDING).await;
+                    return;
+                }
+
+                // Fail fast on hosts the downloader can't run on, before fetching anything
+                let emulator = get_setting(&pool, "downloader_emulator").await;
+                if let Err(e) = platform::check_downloader_support(emulator.as_deref()) {
+                    broadcast(format!("❌ Architecture non supportée ({}) : {}", std::env::consts::ARCH, e)).await;
+                    broadcast(format!("[INSTALL_ERROR]: {}", serde_json::json!({
+                        "code": "unsupported_architecture",
+                        "arch": std::env::consts::ARCH,
+                        "message": e,
+                        "setting": "downloader_emulator"
+                    }))).await;
+                    finish_install(&pool, &pm_inner, &id_inner, INSTALL_FAILED).await;
+                    return;
+                }
+
+                let zip_url = get_setting(&pool, "downloader_url").await
+                    .filter(|url| !url.trim().is_empty())
+                    .unwrap_or_else(|| DEFAULT_DOWNLOADER_URL.to_string());
+                let zip_name = "hytale-downloader.zip";
+                let dest_path = server_path_inner.join(zip_name);
 
-            broadcast(format!("⬇️ Téléchargement de Hytale Downloader depuis {}...", zip_url)).await;
+                broadcast(format!("⬇️ Téléchargement de Hytale Downloader depuis {}...", zip_url)).await;
             
-            // 1. Download
-            if let Err(e) = run_with_logs(
-                &mut tokio::process::Command::new("curl")
-                    .arg("-L").arg("-o").arg(&dest_path).arg(zip_url),
-                pm_inner.clone(), id_inner.clone(), "", Some(install_log_path.clone())
-            ).await {
-                 broadcast(format!("❌ {}", e)).await;
-                 pm_inner.remove(&id_inner).await;
-                 return;
-            }
+                // 1. Download
+                if let Err(e) = run_with_logs(
+                    tokio::process::Command::new("curl")
+                        .arg("-L").arg("--fail").arg("-o").arg(&dest_path).arg(&zip_url),
+                    pm_inner.clone(), id_inner.clone(), "", Some(install_log_path.clone())
+                ).await {
+                     broadcast(format!("❌ {}", e)).await;
+                     finish_install(&pool, &pm_inner, &id_inner, INSTALL_FAILED).await;
+                     return;
+                }
             
-            broadcast("✅ Téléchargement terminé.".to_string()).await;
-            broadcast("📦 Extraction de l'archive...".to_string()).await;
+                broadcast("✅ Téléchargement terminé.".to_string()).await;
+                broadcast("📦 Extraction de l'archive...".to_string()).await;
             
-            // 2. Unzip
-            if let Err(e) = run_with_logs(
-                &mut tokio::process::Command::new("unzip")
-                    .arg("-o").arg(&dest_path).arg("-d").arg(&server_path_inner),
-                pm_inner.clone(), id_inner.clone(), "", Some(install_log_path.clone())
-            ).await {
-                broadcast(format!("❌ {}", e)).await;
-                pm_inner.remove(&id_inner).await;
-                return;
-            }
-            broadcast("✅ Extraction terminée.".to_string()).await;
-            broadcast("🧹 Nettoyage des fichiers temporaires...".to_string()).await;
+                // 2. Unzip
+                if let Err(e) = run_with_logs(
+                    &mut platform::unzip_command(&dest_path, &server_path_inner),
+                    pm_inner.clone(), id_inner.clone(), "", Some(install_log_path.clone())
+                ).await {
+                    broadcast(format!("❌ {}", e)).await;
+                    finish_install(&pool, &pm_inner, &id_inner, INSTALL_FAILED).await;
+                    return;
+                }
+                broadcast("✅ Extraction terminée.".to_string()).await;
+                broadcast("🧹 Nettoyage des fichiers temporaires...".to_string()).await;
             
-            let _ = tokio::fs::remove_file(&dest_path).await;
-            let _ = tokio::fs::remove_file(server_path_inner.join("QUICKSTART.md")).await;
-
-            let mut executable_name = "hytale-downloader-linux-amd64".to_string();
-            let windows_binary = "hytale-downloader-windows-amd64.exe";
-            let linux_binary = "hytale-downloader-linux-amd64";
-
-            if std::env::consts::OS == "linux" {
-                executable_name = linux_binary.to_string();
-                let _ = tokio::fs::remove_file(server_path_inner.join(windows_binary)).await;
-            } else if std::env::consts::OS == "windows" {
-                 executable_name = windows_binary.to_string();
-                 let _ = tokio::fs::remove_file(server_path_inner.join(linux_binary)).await;
-            } else {
-                 if cfg!(target_os = "macos") {
-                     broadcast("⚠️ Attention : macOS détecté. Le Hytale Downloader (Linux binary) peut ne pas fonctionner nativement.".to_string()).await;
-                     executable_name = linux_binary.to_string(); 
-                     let _ = tokio::fs::remove_file(server_path_inner.join(windows_binary)).await;
+                let _ = tokio::fs::remove_file(&dest_path).await;
+                let _ = tokio::fs::remove_file(server_path_inner.join("QUICKSTART.md")).await;
+
+                let mut executable_name = "hytale-downloader-linux-amd64".to_string();
+                let windows_binary = "hytale-downloader-windows-amd64.exe";
+                let linux_binary = "hytale-downloader-linux-amd64";
+
+                if std::env::consts::OS == "linux" {
+                    executable_name = linux_binary.to_string();
+                    let _ = tokio::fs::remove_file(server_path_inner.join(windows_binary)).await;
+                } else if std::env::consts::OS == "windows" {
+                     executable_name = windows_binary.to_string();
+                     let _ = tokio::fs::remove_file(server_path_inner.join(linux_binary)).await;
+                } else {
+                     if cfg!(target_os = "macos") {
+                         broadcast("⚠️ Attention : macOS détecté. Le Hytale Downloader (Linux binary) peut ne pas fonctionner nativement.".to_string()).await;
+                         executable_name = linux_binary.to_string(); 
+                         let _ = tokio::fs::remove_file(server_path_inner.join(windows_binary)).await;
+                    }
                 }
-            }
             
-            let executable_path = server_path_inner.join(&executable_name);
-            if std::env::consts::OS != "windows" {
-                let _ = tokio::process::Command::new("chmod").arg("+x").arg(&executable_path).status().await;
-            }
+                let executable_path = server_path_inner.join(&executable_name);
+                if let Err(e) = platform::make_executable(&executable_path) {
+                    broadcast(format!("⚠️ Impossible de rendre {} exécutable : {}", executable_name, e)).await;
+                }
 
-            broadcast(format!("⏳ Exécution du downloader ({}) pour récupérer le serveur...", executable_name)).await;
-            broadcast("⚠️ IMPORTANT : Le downloader va vous demander de vous authentifier via une URL.".to_string()).await;
+                broadcast(format!("⏳ Exécution du downloader ({}) pour récupérer le serveur...", executable_name)).await;
+                if platform::needs_downloader_emulation() {
+                    broadcast(format!("🧩 Hôte {} : exécution via l'émulateur \"{}\".", std::env::consts::ARCH, emulator.as_deref().unwrap_or_default())).await;
+                }
+                broadcast("⚠️ IMPORTANT : Le downloader va vous demander de vous authentifier via une URL.".to_string()).await;
             
-            if let Err(e) = run_with_logs(
-                &mut tokio::process::Command::new(&executable_path).current_dir(&server_path_inner),
-                pm_inner.clone(), id_inner.clone(), "", Some(install_log_path.clone())
-            ).await {
-                broadcast(format!("❌ {}", e)).await;
+                let mut downloader_cmd = match platform::downloader_command(&executable_path, emulator.as_deref()) {
+                    Ok(cmd) => cmd,
+                    Err(e) => {
+                        broadcast(format!("❌ {}", e)).await;
+                        finish_install(&pool, &pm_inner, &id_inner, INSTALL_FAILED).await;
+                        return;
+                    }
+                };
+                if let Err(e) = run_with_logs(
+                    downloader_cmd.current_dir(&server_path_inner),
+                    pm_inner.clone(), id_inner.clone(), "", Some(install_log_path.clone())
+                ).await {
+                    broadcast(format!("❌ {}", e)).await;
+                } else {
+                    broadcast("✅ Downloader terminé avec succès.".to_string()).await;
+                }
             } else {
-                broadcast("✅ Downloader terminé avec succès.".to_string()).await;
+                broadcast("📦 Installation hors ligne depuis l'archive envoyée...".to_string()).await;
             }
 
             if let Ok(mut entries) = tokio::fs::read_dir(&server_path_inner).await {
@@ -923,7 +1956,7 @@ fn spawn_hytale_installation(pool: DbPool, pm: ProcessManager, id: String, serve
                               if file_name != "hytale-downloader.zip" && file_name != "Assets.zip" {
                                   broadcast(format!("📦 Décompression du serveur : {}...", file_name)).await;
                                   if let Err(e) = run_with_logs(
-                                     &mut tokio::process::Command::new("unzip").arg("-o").arg(&path).arg("-d").arg(&server_path_inner),
+                                     &mut platform::unzip_command(&path, &server_path_inner),
                                      pm_inner.clone(), id_inner.clone(), "", Some(install_log_path.clone())
                                   ).await {
                                       broadcast(format!("❌ Erreur extraction: {}", e)).await;
@@ -946,17 +1979,21 @@ fn spawn_hytale_installation(pool: DbPool, pm: ProcessManager, id: String, serve
             }
 
             let nested_jar_path = nested_bundle_dir.join("HytaleServer.jar");
-            if nested_jar_path.exists() {
+            if nested_jar_path.exists() && !server_path_inner.join("Assets.zip").exists() {
+                 broadcast("⚠️ Attention: Assets.zip non trouvé à la racine du serveur.".to_string()).await;
+                 finish_install(&pool, &pm_inner, &id_inner, INSTALL_FAILED).await;
+            } else if nested_jar_path.exists() {
                  broadcast("✨ HytaleServer.jar présent. Installation terminée !".to_string()).await;
                  let _ = sqlx::query("UPDATE servers SET executable_path = ? WHERE id = ?")
                     .bind("Server/HytaleServer.jar")
                     .bind(&id_inner)
                     .execute(&pool)
                     .await;
+                 finish_install(&pool, &pm_inner, &id_inner, INSTALL_COMPLETED).await;
             } else {
                  broadcast("⚠️ Attention: HytaleServer.jar non trouvé après exécution.".to_string()).await;
+                 finish_install(&pool, &pm_inner, &id_inner, INSTALL_FAILED).await;
             }
-            pm_inner.remove(&id_inner).await;
         });
 
         // Register the task
@@ -964,6 +2001,7 @@ fn spawn_hytale_installation(pool: DbPool, pm: ProcessManager, id: String, serve
         if let Err(e) = pm.register_installing(&id, &working_dir_str, Some(handle.abort_handle())).await {
             error!("Failed to register installing process: {}", e);
             handle.abort(); // Cancel the task since we couldn't register it
+            set_install_state(&pool_outer, &id, INSTALL_FAILED).await;
         } else {
             // Signal the task to start
             let _ = tx_start.send(());
@@ -971,6 +2009,35 @@ fn spawn_hytale_installation(pool: DbPool, pm: ProcessManager, id: String, serve
     });
 }
 
+async fn set_install_state(pool: &DbPool, id: &str, install_state: &str) {
+    if let Err(e) = sqlx::query("UPDATE servers SET install_state = ? WHERE id = ?")
+        .bind(install_state)
+        .bind(id)
+        .execute(pool)
+        .await
+    {
+        error!("Failed to set install_state of server {}: {}", id, e);
+    }
+}
+
+async fn finish_install(pool: &DbPool, pm: &ProcessManager, id: &str, install_state: &str) {
+    set_install_state(pool, id, install_state).await;
+    pm.remove(id).await;
+}
+
+/// Installer counterpart of the console auth detection: flag the prompt, surface the link
+async fn detect_install_auth(pm: &ProcessManager, id: &str, line: &str) {
+    let detection = auth_detection::inspect("hytale", line);
+    if detection.prompt {
+        pm.set_auth_required(id, true).await;
+    }
+    if let Some(url) = detection.url {
+        if pm.is_auth_required(id).await {
+            pm.broadcast_log(id, format!("{} {}", auth_detection::AUTH_URL_PREFIX, url)).await;
+        }
+    }
+}
+
 async fn run_with_logs(
     cmd: &mut tokio::process::Command, 
     pm: ProcessManager, 
@@ -981,6 +2048,8 @@ async fn run_with_logs(
     use tokio::io::AsyncReadExt;
     cmd.stdout(std::process::Stdio::piped());
     cmd.stderr(std::process::Stdio::piped());
+    // Cancelling the installation drops this future: don't leave the downloader running
+    cmd.kill_on_drop(true);
     
     let mut child = cmd.spawn().map_err(|e| format!("Failed to spawn command: {}", e))?;
     let stdout = child.stdout.take().unwrap();
@@ -1001,6 +2070,7 @@ async fn run_with_logs(
                 if !buffer.is_empty() {
                     let line = String::from_utf8_lossy(&buffer).to_string();
                     pm1.broadcast_log(&id1, format!("{}{}", p1, line)).await;
+                    detect_install_auth(&pm1, &id1, &line).await;
                     if let Some(writer) = &fw1 {
                         let mut guard = writer.lock().await;
                         let _ = guard.write_all(line.as_bytes()).await;
@@ -1020,6 +2090,7 @@ async fn run_with_logs(
                 if !buffer.is_empty() {
                     let line = String::from_utf8_lossy(&buffer).to_string();
                     pm2.broadcast_log(&id2, format!("{}[ERR] {}", p2, line)).await;
+                    detect_install_auth(&pm2, &id2, &line).await;
                     if let Some(writer) = &fw2 {
                         let mut guard = writer.lock().await;
                         let _ = guard.write_all(line.as_bytes()).await;
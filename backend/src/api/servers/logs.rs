@@ -0,0 +1,285 @@
+//! A server's `logs/` directory: the live console log, its rotated archives and whatever the
+//! game writes there, listed, tailed, downloaded and compressed without the file editor.
+
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Read, Seek, SeekFrom};
+use std::net::SocketAddr;
+use std::path::{Path as StdPath, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use axum::{
+    extract::{ConnectInfo, Path, Query, State},
+    http::header,
+    response::{IntoResponse, Response},
+    Json,
+};
+use chrono::{DateTime, Utc};
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+use walkdir::WalkDir;
+
+use crate::error::AppError;
+use crate::services::log_cleanup::LIVE_LOG_NAME;
+use crate::AppState;
+use super::files::sandboxed;
+
+/// Lines of a tail unless asked otherwise, and at most
+const DEFAULT_TAIL_LINES: usize = 200;
+const MAX_TAIL_LINES: usize = 5000;
+
+/// Logs untouched for this long are no longer being written and may be compressed
+const COMPRESS_AFTER: Duration = Duration::from_secs(3600);
+
+/// Read backwards from the end of a file by this much at a time
+const TAIL_CHUNK: u64 = 64 * 1024;
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct LogFile {
+    /// Path inside `logs/`
+    pub name: String,
+    pub size: u64,
+    pub modified_at: Option<String>,
+    /// Gzipped, as the rotated console archives
+    pub compressed: bool,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct LogFileQuery {
+    /// Path inside `logs/`
+    pub file: String,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct TailLogQuery {
+    /// Path inside `logs/`, the live console log when absent
+    pub file: Option<String>,
+    /// 200 when absent, 5000 at most
+    pub lines: Option<usize>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct CompressLogsRequest {
+    /// Path inside `logs/`; when absent every uncompressed log untouched for an hour
+    pub file: Option<String>,
+}
+
+fn is_compressed(name: &str) -> bool {
+    name.ends_with(".gz")
+}
+
+async fn logs_dir(state: &AppState, server_id: &str) -> Result<PathBuf, AppError> {
+    let working_dir: Option<String> = sqlx::query_scalar("SELECT working_dir FROM servers WHERE id = ?")
+        .bind(server_id)
+        .fetch_optional(&state.pool)
+        .await?;
+    let working_dir = working_dir.ok_or_else(|| AppError::NotFound("servers.not_found".into()))?;
+    Ok(StdPath::new(&working_dir).join("logs"))
+}
+
+/// A file of the server's `logs/`, path escapes handled as in the file API
+async fn log_file(
+    state: &AppState,
+    connect_info: Option<ConnectInfo<SocketAddr>>,
+    server_id: &str,
+    file: &str,
+) -> Result<PathBuf, AppError> {
+    let dir = logs_dir(state, server_id).await?;
+    let path = sandboxed(state, connect_info, &dir.to_string_lossy(), file).await?;
+    if !path.is_file() {
+        return Err(AppError::NotFound("files.not_found".into()));
+    }
+    Ok(path)
+}
+
+/// Files of `dir` with their modification time, most recently written first
+fn scan(dir: &StdPath) -> Vec<(SystemTime, LogFile)> {
+    let mut files: Vec<(SystemTime, LogFile)> = WalkDir::new(dir)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file() && !e.file_name().to_string_lossy().ends_with(".lck"))
+        .filter_map(|entry| {
+            let metadata = entry.metadata().ok()?;
+            let modified = metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH);
+            let name = entry.path().strip_prefix(dir).ok()?.to_string_lossy().replace('\\', "/");
+            Some((modified, LogFile {
+                compressed: is_compressed(&name),
+                name,
+                size: metadata.len(),
+                modified_at: metadata.modified().ok().map(|m| DateTime::<Utc>::from(m).to_rfc3339()),
+            }))
+        })
+        .collect();
+    files.sort_by_key(|f| std::cmp::Reverse(f.0));
+    files
+}
+
+/// Last `count` lines of a plain file, read backwards from its end
+fn tail_plain(path: &StdPath, count: usize) -> io::Result<Vec<String>> {
+    let mut file = File::open(path)?;
+    let len = file.metadata()?.len();
+    let mut start = len;
+    let mut buffer = Vec::new();
+    // One more newline than lines wanted, the last line usually ends with one
+    while start > 0 && buffer.iter().filter(|b| **b == b'\n').count() <= count {
+        let read = TAIL_CHUNK.min(start);
+        start -= read;
+        let mut chunk = vec![0; read as usize];
+        file.seek(SeekFrom::Start(start))?;
+        file.read_exact(&mut chunk)?;
+        chunk.extend_from_slice(&buffer);
+        buffer = chunk;
+    }
+    Ok(last_lines(BufReader::new(buffer.as_slice()), count))
+}
+
+fn last_lines(reader: impl BufRead, count: usize) -> Vec<String> {
+    let mut lines = VecDeque::with_capacity(count);
+    for line in reader.split(b'\n').map_while(Result::ok) {
+        if lines.len() == count {
+            lines.pop_front();
+        }
+        lines.push_back(String::from_utf8_lossy(&line).trim_end_matches('\r').to_string());
+    }
+    lines.into()
+}
+
+/// Gzip `path` next to itself and remove it, returning the bytes saved
+fn compress_file(path: &StdPath) -> io::Result<u64> {
+    let original = path.metadata()?.len();
+    let mut target = path.as_os_str().to_owned();
+    target.push(".gz");
+    let target = PathBuf::from(target);
+
+    let mut encoder = GzEncoder::new(File::create(&target)?, Compression::default());
+    io::copy(&mut File::open(path)?, &mut encoder)?;
+    encoder.finish()?;
+    std::fs::remove_file(path)?;
+    Ok(original.saturating_sub(target.metadata()?.len()))
+}
+
+/// Files of the server's `logs/` directory, most recently written first
+#[utoipa::path(get, path = "/servers/{id}/logs", tag = "files", params(("id" = String, Path)), responses((status = 200, body = [LogFile])))]
+pub async fn list_logs(
+    State(state): State<AppState>,
+    Path(server_id): Path<String>,
+) -> Result<Json<Vec<LogFile>>, AppError> {
+    let dir = logs_dir(&state, &server_id).await?;
+    let files = tokio::task::spawn_blocking(move || scan(&dir))
+        .await
+        .map_err(|e| AppError::Internal(format!("Failed to list logs: {}", e)))?;
+    Ok(Json(files.into_iter().map(|(_, file)| file).collect()))
+}
+
+/// Last lines of a log, compressed archives included
+#[utoipa::path(get, path = "/servers/{id}/logs/tail", tag = "files", params(("id" = String, Path), TailLogQuery), responses((status = 200, body = serde_json::Value, description = "`file` and its last `lines`")))]
+pub async fn tail_log(
+    State(state): State<AppState>,
+    Path(server_id): Path<String>,
+    connect_info: Option<ConnectInfo<SocketAddr>>,
+    Query(query): Query<TailLogQuery>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let name = query.file.unwrap_or_else(|| LIVE_LOG_NAME.to_string());
+    let count = query.lines.unwrap_or(DEFAULT_TAIL_LINES).clamp(1, MAX_TAIL_LINES);
+    let path = log_file(&state, connect_info, &server_id, &name).await?;
+
+    let compressed = is_compressed(&name);
+    let lines = tokio::task::spawn_blocking(move || {
+        if compressed {
+            Ok(last_lines(BufReader::new(GzDecoder::new(File::open(&path)?)), count))
+        } else {
+            tail_plain(&path, count)
+        }
+    })
+    .await
+    .map_err(|e| AppError::Internal(format!("Failed to read log: {}", e)))?
+    .map_err(|e| AppError::Internal(format!("Failed to read log: {}", e)))?;
+
+    Ok(Json(serde_json::json!({ "file": name, "lines": lines })))
+}
+
+/// A log file as an attachment
+#[utoipa::path(get, path = "/servers/{id}/logs/download", tag = "files", params(("id" = String, Path), LogFileQuery), responses((status = 200, description = "The file, gzipped archives as they are")))]
+pub async fn download_log(
+    State(state): State<AppState>,
+    Path(server_id): Path<String>,
+    connect_info: Option<ConnectInfo<SocketAddr>>,
+    Query(query): Query<LogFileQuery>,
+) -> Result<Response, AppError> {
+    let path = log_file(&state, connect_info, &server_id, &query.file).await?;
+    let data = tokio::fs::read(&path).await
+        .map_err(|e| AppError::Internal(format!("Failed to read log: {}", e)))?;
+    let filename = path.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+    let content_type = if is_compressed(&filename) { "application/gzip" } else { "text/plain; charset=utf-8" };
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, content_type.to_string()),
+            (header::CONTENT_DISPOSITION, format!("attachment; filename=\"{}\"", filename)),
+        ],
+        data,
+    ).into_response())
+}
+
+/// Gzip a log, or every log no longer written to. The live console log is left alone: it is
+/// archived compressed when it rotates.
+#[utoipa::path(post, path = "/servers/{id}/logs/compress", tag = "files", params(("id" = String, Path)), request_body = CompressLogsRequest, responses((status = 200, body = serde_json::Value, description = "`compressed` files and `saved_bytes`")))]
+pub async fn compress_logs(
+    State(state): State<AppState>,
+    Path(server_id): Path<String>,
+    connect_info: Option<ConnectInfo<SocketAddr>>,
+    Json(body): Json<CompressLogsRequest>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let targets: Vec<(String, PathBuf)> = match body.file {
+        Some(file) => {
+            if is_compressed(&file) {
+                return Err(AppError::BadRequest("logs.already_compressed".into()));
+            }
+            if file == LIVE_LOG_NAME {
+                return Err(AppError::BadRequest("logs.live_file".into()));
+            }
+            let path = log_file(&state, connect_info, &server_id, &file).await?;
+            vec![(file, path)]
+        }
+        None => {
+            let dir = logs_dir(&state, &server_id).await?;
+            let cutoff = SystemTime::now() - COMPRESS_AFTER;
+            tokio::task::spawn_blocking(move || {
+                scan(&dir)
+                    .into_iter()
+                    .filter(|(modified, f)| !f.compressed && f.name != LIVE_LOG_NAME && *modified < cutoff)
+                    .map(|(_, f)| {
+                        let path = dir.join(&f.name);
+                        (f.name, path)
+                    })
+                    .collect()
+            })
+            .await
+            .map_err(|e| AppError::Internal(format!("Failed to list logs: {}", e)))?
+        }
+    };
+
+    let (compressed, saved_bytes) = tokio::task::spawn_blocking(move || {
+        let mut compressed = Vec::new();
+        let mut saved = 0;
+        for (name, path) in targets {
+            match compress_file(&path) {
+                Ok(bytes) => {
+                    saved += bytes;
+                    compressed.push(name);
+                }
+                Err(e) => tracing::warn!("Failed to compress log {}: {}", name, e),
+            }
+        }
+        (compressed, saved)
+    })
+    .await
+    .map_err(|e| AppError::Internal(format!("Failed to compress logs: {}", e)))?;
+
+    Ok(Json(serde_json::json!({ "success": true, "compressed": compressed, "saved_bytes": saved_bytes })))
+}
@@ -1,33 +1,97 @@
 use axum::{
-    routing::{get, post},
+    extract::DefaultBodyLimit,
+    routing::{get, patch, post, put},
     Router,
 };
+use utoipa::OpenApi;
 use crate::AppState;
 
 pub mod handlers;
 pub mod models;
 pub mod files;
+pub mod logs;
+pub mod transfers;
+pub mod elevation;
+pub mod network;
+pub mod permissions;
+pub mod portability;
+pub mod stats;
+pub mod game_config;
 
 use handlers::*;
 use files::*;
+use logs::*;
+use transfers::*;
+use elevation::*;
+use network::*;
+use permissions::*;
+use portability::*;
+use stats::*;
+use game_config::*;
 
 pub fn routes() -> Router<AppState> {
     Router::new()
         // Servers CRUD
         .route("/", get(list_servers).post(create_server))
-        .route("/:id", get(get_server).put(update_server).delete(delete_server))
+        .route("/bulk-settings", patch(bulk_update_settings))
+        .route("/networks", get(list_networks))
+        .route("/export", get(export_servers))
+        .route("/import", post(import_servers))
+        .route("/transfers", get(list_transfers))
+        .route("/transfers/:transfer_id/accept", post(accept_transfer))
+        .route("/transfers/:transfer_id/decline", post(decline_transfer))
+        .route("/elevation/:grant_id/approve", post(approve_elevation))
+        .route("/elevation/:grant_id/deny", post(deny_elevation))
+        .route("/elevation/:grant_id/revoke", post(revoke_elevation))
+        .route("/:id", get(get_server).put(update_server).patch(patch_server).delete(delete_server))
         
         // Actions
         .route("/:id/start", post(start_server))
         .route("/:id/stop", post(stop_server))
         .route("/:id/restart", post(restart_server))
         .route("/:id/kill", post(kill_server))
+        .route("/:id/suspend", post(suspend_server))
+        .route("/:id/resume", post(resume_server))
         .route("/:id/reinstall", post(reinstall_server))
+        .route("/:id/install/retry", post(retry_install))
+        .route("/:id/install/bundle", post(upload_install_bundle).layer(DefaultBodyLimit::disable()))
         .route("/:id/command", post(send_command))
+        .route("/:id/command/exec", post(exec_command))
+        .route("/:id/startups", get(get_startup_history))
+        .route("/:id/stats", get(get_server_stats))
+        .route("/:id/game-config", get(get_game_config).put(update_game_config))
+        .route("/:id/transfer", post(request_transfer))
+        .route("/:id/export", get(export_server))
+        .route("/:id/elevation", get(list_elevations).post(request_elevation))
+        .route("/:id/permissions", get(list_permissions))
+        .route("/:id/permissions/:user_id", put(set_permissions).delete(revoke_permissions))
         
         // Files API
         .route("/:id/files", get(list_server_files))
         .route("/:id/files/read", get(read_server_file))
         .route("/:id/files/write", post(write_server_file))
         .route("/:id/files/delete", post(delete_server_file))
+        .route("/:id/files/batch", post(batch_server_files))
+
+        // Logs API
+        .route("/:id/logs", get(list_logs))
+        .route("/:id/logs/tail", get(tail_log))
+        .route("/:id/logs/download", get(download_log))
+        .route("/:id/logs/compress", post(compress_logs))
 }
+
+#[derive(OpenApi)]
+#[openapi(paths(
+    list_servers, create_server, get_server, update_server, patch_server, delete_server, bulk_update_settings,
+    start_server, stop_server, restart_server, kill_server, suspend_server, resume_server,
+    reinstall_server, retry_install, upload_install_bundle, send_command, exec_command, get_startup_history, get_server_stats,
+    get_game_config, update_game_config,
+    list_networks,
+    export_servers, export_server, import_servers,
+    list_transfers, request_transfer, accept_transfer, decline_transfer,
+    list_elevations, request_elevation, approve_elevation, deny_elevation, revoke_elevation,
+    list_permissions, set_permissions, revoke_permissions,
+    list_server_files, read_server_file, write_server_file, delete_server_file, batch_server_files,
+    list_logs, tail_log, download_log, compress_logs,
+))]
+pub(super) struct ApiDoc;
@@ -1,7 +1,9 @@
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
+use std::collections::HashMap;
+use utoipa::{IntoParams, ToSchema};
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct CreateServerRequest {
     pub name: String,
     pub game_type: String,
@@ -23,6 +25,10 @@ pub struct CreateServerRequest {
     pub discord_avatar: Option<String>,
     pub discord_webhook_url: Option<String>,
     pub discord_notifications: Option<serde_json::Value>,
+    /// Embed color of this server's Discord messages (`#RRGGBB`), empty for the defaults
+    pub discord_color: Option<String>,
+    /// Emoji shown for this server when online, empty for 🟢
+    pub discord_emoji: Option<String>,
     pub logs_retention_days: Option<u32>,
     pub watchdog_enabled: Option<bool>,
     
@@ -30,9 +36,83 @@ pub struct CreateServerRequest {
     pub auth_mode: Option<String>,
     pub bind_address: Option<String>,
     pub port: Option<u16>,
+
+    // Process environment
+    pub env_vars: Option<HashMap<String, String>>,
+    pub launch_mode: Option<String>,
+    pub command_template: Option<String>,
+    /// "native" (default) or "docker"
+    pub runtime: Option<String>,
+    /// Image the server runs in with the docker runtime, which must provide Java for `java` launches
+    pub docker_image: Option<String>,
+    /// Language of in-game broadcast messages
+    pub locale: Option<String>,
+    /// Console command used for graceful stop, empty to rely on SIGTERM
+    pub stop_command: Option<String>,
+    /// Daily restart time (HH:MM, host local time), empty to disable
+    pub restart_schedule: Option<String>,
+    /// Number of rotated console logs kept
+    pub log_rotation_count: Option<u32>,
+    /// Write the console to `logs/console.log`
+    pub console_log_enabled: Option<bool>,
+    /// Size in MB at which `console.log` is rotated (1-10240)
+    pub console_log_max_mb: Option<u32>,
+    /// Collapse repeated lines into one line and a counter
+    pub console_log_dedup: Option<bool>,
+    /// "proxy", "lobby" or "game" in a multi-server network, empty when standalone
+    pub network_role: Option<String>,
+    /// Proxy server this lobby or game server sits behind, empty for none
+    pub parent_proxy_id: Option<String>,
+    /// Console command saving the world before a backup of the running server, empty for none
+    pub backup_save_command: Option<String>,
+    /// Regex of the console line confirming the save
+    pub backup_save_confirm: Option<String>,
+    /// Commands pausing and resuming autosave while the archive is written
+    pub backup_autosave_off_command: Option<String>,
+    pub backup_autosave_on_command: Option<String>,
+    /// Backup file name, e.g. `{prefix}_{server_name}_{timestamp}`, empty for the default
+    pub backup_filename_template: Option<String>,
+    /// Resource alert thresholds, 0 to disable
+    pub alert_cpu_percent: Option<f32>,
+    pub alert_ram_percent: Option<f32>,
+    pub alert_disk_mb: Option<u64>,
+    /// "tcp" or "quic" check of the game port while running, empty to disable
+    pub health_probe: Option<String>,
+    /// Seconds between two probes (5-3600)
+    pub health_probe_interval_secs: Option<u32>,
+    /// Free labels to group and find servers, e.g. "event"
+    pub tags: Option<Vec<String>>,
+}
+
+/// Subset of settings that can be applied to many servers at once
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct BulkSettings {
+    pub discord_webhook_url: Option<String>,
+    pub backup_enabled: Option<bool>,
+    pub backup_frequency: Option<u32>,
+    pub backup_max_backups: Option<u32>,
+    pub backup_prefix: Option<String>,
+    pub logs_retention_days: Option<u32>,
+    pub watchdog_enabled: Option<bool>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct BulkSettingsRequest {
+    #[serde(default)]
+    pub server_ids: Vec<String>,
+    /// Also every server carrying this tag that the caller manages
+    pub tag: Option<String>,
+    pub settings: BulkSettings,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct BulkSettingsResult {
+    pub server_id: String,
+    pub success: bool,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
 pub struct Player {
     pub name: String,
     pub is_online: bool,
@@ -42,11 +122,12 @@ pub struct Player {
     pub is_whitelisted: bool,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct ServerResponse {
     pub id: String,
     pub name: String,
     pub game_type: String,
+    pub tags: Vec<String>,
     pub status: String,
     pub executable_path: String,
     pub working_dir: String,
@@ -73,9 +154,48 @@ pub struct ServerResponse {
     pub discord_avatar: Option<String>,
     pub discord_webhook_url: Option<String>,
     pub discord_notifications: Option<serde_json::Value>,
+    pub discord_color: Option<String>,
+    pub discord_emoji: Option<String>,
     pub logs_retention_days: u32,
     pub watchdog_enabled: bool,
     pub auth_mode: String,
+    pub env_vars: HashMap<String, String>,
+    pub launch_mode: String,
+    pub command_template: Option<String>,
+    pub runtime: String,
+    pub docker_image: Option<String>,
+    pub health_probe: Option<String>,
+    pub health_probe_interval_secs: u32,
+    pub locale: String,
+    pub stop_command: Option<String>,
+    pub restart_schedule: Option<String>,
+    pub install_state: String,
+    pub log_rotation_count: u32,
+    pub console_log_enabled: bool,
+    pub console_log_max_mb: u32,
+    pub console_log_dedup: bool,
+    pub network_role: Option<String>,
+    pub parent_proxy_id: Option<String>,
+    pub backup_save_command: Option<String>,
+    pub backup_save_confirm: Option<String>,
+    pub backup_autosave_off_command: Option<String>,
+    pub backup_autosave_on_command: Option<String>,
+    pub backup_filename_template: Option<String>,
+    pub alert_cpu_percent: Option<f32>,
+    pub alert_ram_percent: Option<f32>,
+    pub alert_disk_mb: Option<u64>,
+    /// User the server belongs to, changed through transfers
+    pub owner_id: Option<String>,
+    /// Exit code of the last run (`None` when killed by a signal or never run)
+    pub last_exit_code: Option<i32>,
+    pub last_exit_at: Option<String>,
+    /// Network rates of the last metrics sample (see `utils::net_stats` for their scope)
+    pub network_rx_bytes_per_sec: u64,
+    pub network_tx_bytes_per_sec: u64,
+    /// Connections on the game port, when the platform can count them
+    pub connections: Option<u32>,
+    /// Spawn to "Universe ready!" of the last boot
+    pub startup_duration_ms: Option<u64>,
 
     pub cpu_usage: f32,
     pub cpu_usage_normalized: f32, // New field
@@ -93,11 +213,39 @@ pub struct PlayerRow {
     pub last_seen: String,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct CommandRequest {
     pub command: String,
 }
 
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct ExecCommandRequest {
+    pub command: String,
+    /// Longest time to wait for output (default 2000, max 10000)
+    pub timeout_ms: Option<u64>,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ListServersQuery {
+    /// Text found in the name, a tag, the port or the game type
+    pub q: Option<String>,
+    /// Comma-separated tags the servers must all have
+    pub tags: Option<String>,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct StartupHistoryQuery {
+    pub limit: Option<u32>,
+}
+
+// Installation lifecycle stored in `servers.install_state`
+pub const INSTALL_PENDING: &str = "pending";
+pub const INSTALL_INSTALLING: &str = "installing";
+pub const INSTALL_COMPLETED: &str = "completed";
+pub const INSTALL_FAILED: &str = "failed";
+
 #[derive(Debug, FromRow)]
 pub struct ServerRow {
     pub id: String,
@@ -141,11 +289,74 @@ pub struct ServerRow {
     pub bind_address: String,
     #[sqlx(default)]
     pub port: i32,
+    #[sqlx(default)]
+    pub env_vars: Option<String>,
+    #[sqlx(default)]
+    pub launch_mode: String,
+    #[sqlx(default)]
+    pub command_template: Option<String>,
+    #[sqlx(default)]
+    pub locale: String,
+    #[sqlx(default)]
+    pub stop_command: Option<String>,
+    #[sqlx(default)]
+    pub restart_schedule: Option<String>,
+    #[sqlx(default)]
+    pub install_state: String,
+    #[sqlx(default)]
+    pub log_rotation_count: i32,
+    #[sqlx(default)]
+    pub discord_color: Option<String>,
+    #[sqlx(default)]
+    pub discord_emoji: Option<String>,
+    #[sqlx(default)]
+    pub alert_cpu_percent: Option<f64>,
+    #[sqlx(default)]
+    pub alert_ram_percent: Option<f64>,
+    #[sqlx(default)]
+    pub alert_disk_mb: Option<i64>,
+    #[sqlx(default)]
+    pub owner_id: Option<String>,
+    #[sqlx(default)]
+    pub last_exit_code: Option<i32>,
+    #[sqlx(default)]
+    pub last_exit_at: Option<String>,
+    #[sqlx(default)]
+    pub runtime: String,
+    #[sqlx(default)]
+    pub docker_image: Option<String>,
+    #[sqlx(default)]
+    pub health_probe: Option<String>,
+    #[sqlx(default)]
+    pub health_probe_interval_secs: i32,
+    #[sqlx(default)]
+    pub console_log_enabled: i32,
+    #[sqlx(default)]
+    pub console_log_max_mb: i32,
+    #[sqlx(default)]
+    pub console_log_dedup: i32,
+    #[sqlx(default)]
+    pub network_role: Option<String>,
+    #[sqlx(default)]
+    pub parent_proxy_id: Option<String>,
+    #[sqlx(default)]
+    pub backup_save_command: Option<String>,
+    #[sqlx(default)]
+    pub backup_save_confirm: Option<String>,
+    #[sqlx(default)]
+    pub backup_autosave_off_command: Option<String>,
+    #[sqlx(default)]
+    pub backup_autosave_on_command: Option<String>,
+    #[sqlx(default)]
+    pub backup_filename_template: Option<String>,
+    /// JSON array of lowercase labels
+    #[sqlx(default)]
+    pub tags: Option<String>,
 }
 
 // ============= Server Files API Models =============
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct FileEntry {
     pub name: String,
     pub path: String,
@@ -153,23 +364,112 @@ pub struct FileEntry {
     pub size: Option<u64>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
 pub struct FilesQuery {
     pub path: Option<String>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
 pub struct ReadFileQuery {
     pub path: String,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct WriteFileRequest {
     pub path: String,
     pub content: String,
+    /// Octal Unix mode applied after writing, e.g. "755" for start scripts
+    pub permissions: Option<String>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct DeleteFileRequest {
     pub path: String,
 }
+
+/// One step of a batch, paths relative to the server directory
+#[derive(Debug, Deserialize, ToSchema)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum FileOperation {
+    Delete { path: String },
+    /// Missing parent directories of `to` are created, an existing `to` is refused
+    Move { from: String, to: String },
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct BatchFilesRequest {
+    /// Run in order, all undone when one fails
+    pub operations: Vec<FileOperation>,
+}
+
+// ============= Break-glass Access API Models =============
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct ElevationRequest {
+    /// Minutes of admin access once approved (1-240)
+    pub minutes: u32,
+    pub reason: String,
+}
+
+#[derive(Debug, Serialize, FromRow, ToSchema)]
+pub struct ElevationGrant {
+    pub id: String,
+    pub server_id: String,
+    pub user_id: String,
+    pub reason: String,
+    pub duration_minutes: i64,
+    pub status: String,
+    pub requested_at: String,
+    pub decided_by: Option<String>,
+    pub expires_at: Option<String>,
+    pub resolved_at: Option<String>,
+}
+
+// ============= Server Permission API Models =============
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct SetPermissionsRequest {
+    /// Names from `server_access::PERMISSIONS`, e.g. "console.view"
+    #[serde(default)]
+    pub permissions: Vec<String>,
+    /// Role template granting its permissions on top of those
+    pub template_id: Option<String>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct UserPermissions {
+    pub user_id: String,
+    pub username: Option<String>,
+    /// Granted one by one, the template's come on top
+    pub permissions: Vec<String>,
+    pub template_id: Option<String>,
+    pub template_name: Option<String>,
+}
+
+// ============= Server Transfer API Models =============
+
+pub const TRANSFER_PENDING: &str = "pending";
+pub const TRANSFER_ACCEPTED: &str = "accepted";
+pub const TRANSFER_DECLINED: &str = "declined";
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct TransferRequest {
+    pub to_user_id: String,
+    /// Also move the server from the old owner's allocation to the new one's (default true)
+    pub move_allocation: Option<bool>,
+}
+
+#[derive(Debug, Serialize, FromRow, ToSchema)]
+pub struct ServerTransfer {
+    pub id: String,
+    pub server_id: String,
+    pub from_user_id: Option<String>,
+    pub to_user_id: String,
+    pub move_allocation: bool,
+    pub status: String,
+    pub requested_by: String,
+    pub created_at: String,
+    pub resolved_at: Option<String>,
+}
@@ -0,0 +1,82 @@
+//! Network view: each proxy with its lobby and game servers and the players across them.
+
+use std::collections::HashMap;
+
+use axum::{extract::State, Json};
+use serde::Serialize;
+use utoipa::ToSchema;
+
+use crate::error::AppError;
+use crate::services::network::ROLE_PROXY;
+use crate::AppState;
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct NetworkMember {
+    pub id: String,
+    pub name: String,
+    pub network_role: Option<String>,
+    pub running: bool,
+    pub players: usize,
+    pub max_players: u32,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct NetworkResponse {
+    pub proxy: NetworkMember,
+    pub members: Vec<NetworkMember>,
+    /// Players on the lobby and game servers of the network
+    pub online_players: usize,
+    pub max_players: u32,
+}
+
+/// id, name, network_role, parent_proxy_id, config
+type NetworkRow = (String, String, Option<String>, Option<String>, Option<String>);
+
+/// Every proxy server with its members, in name order
+#[utoipa::path(get, path = "/servers/networks", tag = "servers", responses((status = 200, body = [NetworkResponse])))]
+pub async fn list_networks(State(state): State<AppState>) -> Result<Json<Vec<NetworkResponse>>, AppError> {
+    let servers: Vec<NetworkRow> = sqlx::query_as(
+        "SELECT id, name, network_role, parent_proxy_id, config FROM servers ORDER BY name"
+    )
+    .fetch_all(&state.pool)
+    .await?;
+
+    let pm = &state.process_manager;
+    let mut proxies = Vec::new();
+    let mut members: HashMap<String, Vec<NetworkMember>> = HashMap::new();
+    for (id, name, role, parent, config) in servers {
+        let running = pm.is_running(&id).await;
+        let max_players = config
+            .and_then(|c| serde_json::from_str::<serde_json::Value>(&c).ok())
+            .and_then(|c| c.get("MaxPlayers").and_then(|v| v.as_u64()))
+            .unwrap_or(100) as u32;
+        let member = NetworkMember {
+            players: if running { pm.get_online_players(&id).await.map(|p| p.len()).unwrap_or(0) } else { 0 },
+            id,
+            name,
+            network_role: role.filter(|r| !r.is_empty()),
+            running,
+            max_players,
+        };
+        match parent.filter(|p| !p.is_empty()) {
+            Some(parent) => members.entry(parent).or_default().push(member),
+            None if member.network_role.as_deref() == Some(ROLE_PROXY) => proxies.push(member),
+            None => {}
+        }
+    }
+
+    let networks = proxies
+        .into_iter()
+        .map(|proxy| {
+            let members = members.remove(&proxy.id).unwrap_or_default();
+            NetworkResponse {
+                online_players: members.iter().map(|m| m.players).sum(),
+                max_players: members.iter().map(|m| m.max_players).sum(),
+                proxy,
+                members,
+            }
+        })
+        .collect();
+
+    Ok(Json(networks))
+}
@@ -0,0 +1,168 @@
+//! Per-server permission grants: the actions a user may take on one server (see
+//! `services::server_access`), one by one or through a role template. The owner, admins and
+//! elevated users edit them.
+
+use std::collections::BTreeMap;
+
+use axum::{
+    extract::{Path, State},
+    Json,
+};
+use chrono::Utc;
+
+use crate::api::auth::AuthUser;
+use crate::error::AppError;
+use crate::services::{elevation, history, server_access};
+use crate::AppState;
+use super::models::{SetPermissionsRequest, UserPermissions};
+use super::transfers::username;
+
+async fn require_grant_admin(state: &AppState, auth: &AuthUser, server_id: &str) -> Result<(), AppError> {
+    let (owner_id,): (Option<String>,) = sqlx::query_as("SELECT owner_id FROM servers WHERE id = ?")
+        .bind(server_id)
+        .fetch_optional(&state.pool)
+        .await?
+        .ok_or_else(|| AppError::NotFound("servers.not_found".into()))?;
+
+    if owner_id.as_deref() != Some(auth.id.as_str()) && !elevation::has_server_admin(&state.pool, auth, server_id).await {
+        return Err(AppError::Forbidden("servers.permission_denied".into()));
+    }
+    Ok(())
+}
+
+#[utoipa::path(get, path = "/servers/{id}/permissions", tag = "servers", params(("id" = String, Path)), responses((status = 200, body = [UserPermissions])))]
+pub async fn list_permissions(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(id): Path<String>,
+) -> Result<Json<Vec<UserPermissions>>, AppError> {
+    require_grant_admin(&state, &auth, &id).await?;
+
+    let rows: Vec<(String, Option<String>, String)> = sqlx::query_as(
+        "SELECT p.user_id, u.username, p.permission FROM server_permissions p
+         LEFT JOIN users u ON u.id = p.user_id
+         WHERE p.server_id = ? ORDER BY u.username, p.permission"
+    )
+    .bind(&id)
+    .fetch_all(&state.pool)
+    .await?;
+
+    let templates: Vec<(String, Option<String>, String, String)> = sqlx::query_as(
+        "SELECT a.user_id, u.username, t.id, t.name FROM server_role_assignments a
+         JOIN role_templates t ON t.id = a.template_id
+         LEFT JOIN users u ON u.id = a.user_id
+         WHERE a.server_id = ?"
+    )
+    .bind(&id)
+    .fetch_all(&state.pool)
+    .await?;
+
+    let mut users: BTreeMap<String, UserPermissions> = BTreeMap::new();
+    let new_entry = |user_id: String, username: Option<String>| UserPermissions {
+        user_id,
+        username,
+        permissions: Vec::new(),
+        template_id: None,
+        template_name: None,
+    };
+    for (user_id, username, permission) in rows {
+        users
+            .entry(user_id.clone())
+            .or_insert_with(|| new_entry(user_id, username))
+            .permissions
+            .push(permission);
+    }
+    for (user_id, username, template_id, template_name) in templates {
+        let user = users.entry(user_id.clone()).or_insert_with(|| new_entry(user_id, username));
+        user.template_id = Some(template_id);
+        user.template_name = Some(template_name);
+    }
+    Ok(Json(users.into_values().collect()))
+}
+
+/// Replace what a user may do on the server; an empty list and no template remove its grants
+#[utoipa::path(put, path = "/servers/{id}/permissions/{user_id}", tag = "servers", params(("id" = String, Path), ("user_id" = String, Path)), request_body = SetPermissionsRequest, responses((status = 200, body = UserPermissions)))]
+pub async fn set_permissions(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path((id, user_id)): Path<(String, String)>,
+    Json(body): Json<SetPermissionsRequest>,
+) -> Result<Json<UserPermissions>, AppError> {
+    require_grant_admin(&state, &auth, &id).await?;
+    let name = username(&state.pool, &user_id)
+        .await?
+        .ok_or_else(|| AppError::NotFound("users.not_found".into()))?;
+
+    let permissions = server_access::normalize_permissions(body.permissions).map_err(|unknown| {
+        tracing::info!("Rejected unknown server permission {:?}", unknown);
+        AppError::BadRequest("servers.invalid_permission".into())
+    })?;
+    let template = match body.template_id.as_deref().filter(|t| !t.is_empty()) {
+        Some(template_id) => Some(
+            sqlx::query_as::<_, (String, String)>("SELECT id, name FROM role_templates WHERE id = ?")
+                .bind(template_id)
+                .fetch_optional(&state.pool)
+                .await?
+                .ok_or_else(|| AppError::NotFound("role_templates.not_found".into()))?,
+        ),
+        None => None,
+    };
+
+    let now = Utc::now().to_rfc3339();
+    let mut tx = state.pool.begin().await?;
+    sqlx::query("DELETE FROM server_permissions WHERE server_id = ? AND user_id = ?")
+        .bind(&id)
+        .bind(&user_id)
+        .execute(&mut *tx)
+        .await?;
+    for permission in &permissions {
+        sqlx::query("INSERT INTO server_permissions (server_id, user_id, permission, granted_by, granted_at) VALUES (?, ?, ?, ?, ?)")
+            .bind(&id)
+            .bind(&user_id)
+            .bind(permission)
+            .bind(&auth.id)
+            .bind(&now)
+            .execute(&mut *tx)
+            .await?;
+    }
+    sqlx::query("DELETE FROM server_role_assignments WHERE server_id = ? AND user_id = ?")
+        .bind(&id)
+        .bind(&user_id)
+        .execute(&mut *tx)
+        .await?;
+    if let Some((template_id, _)) = &template {
+        sqlx::query("INSERT INTO server_role_assignments (server_id, user_id, template_id, assigned_by, assigned_at) VALUES (?, ?, ?, ?, ?)")
+            .bind(&id)
+            .bind(&user_id)
+            .bind(template_id)
+            .bind(&auth.id)
+            .bind(&now)
+            .execute(&mut *tx)
+            .await?;
+    }
+    tx.commit().await?;
+
+    let granted: Vec<String> = template.iter()
+        .map(|(_, template_name)| format!("[{}]", template_name))
+        .chain(permissions.iter().cloned())
+        .collect();
+    let detail = if granted.is_empty() {
+        format!("{} (by {})", name, auth.username)
+    } else {
+        format!("{}: {} (by {})", name, granted.join(", "), auth.username)
+    };
+    history::record_event(&state.pool, &id, history::EVENT_PERMISSIONS_CHANGED, Some(&detail)).await;
+
+    let (template_id, template_name) = template.unzip();
+    Ok(Json(UserPermissions { user_id, username: Some(name), permissions, template_id, template_name }))
+}
+
+/// Remove every grant of a user on the server, returning what is left: nothing
+#[utoipa::path(delete, path = "/servers/{id}/permissions/{user_id}", tag = "servers", params(("id" = String, Path), ("user_id" = String, Path)), responses((status = 200, body = UserPermissions)))]
+pub async fn revoke_permissions(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path((id, user_id)): Path<(String, String)>,
+) -> Result<Json<UserPermissions>, AppError> {
+    set_permissions(State(state), auth, Path((id, user_id)), Json(SetPermissionsRequest { permissions: Vec::new(), template_id: None })).await
+}
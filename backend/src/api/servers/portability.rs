@@ -0,0 +1,221 @@
+//! Server definitions as a portable JSON document: the configuration of one or all servers,
+//! without their files, to recreate them on another panel. Imported servers are created in the
+//! local `servers_dir` and installed from scratch.
+
+use std::collections::HashMap;
+use std::path::Path as StdPath;
+
+use axum::{
+    extract::{Path, State},
+    http::header,
+    response::{IntoResponse, Response},
+    Json,
+};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::api::auth::AuthUser;
+use crate::api::settings;
+use crate::error::AppError;
+use crate::services::{network, server_access};
+use crate::AppState;
+use super::handlers::{create_server_from, parse_env_vars, parse_tags};
+use super::models::{CreateServerRequest, ServerRow};
+
+/// Bumped when a definition changes in a way older panels can't import
+pub const EXPORT_VERSION: u32 = 1;
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct ServerDefinition {
+    /// Id on the panel it was exported from, linking game servers to their proxy
+    pub source_id: String,
+    #[serde(flatten)]
+    pub server: CreateServerRequest,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct ServerExport {
+    pub version: u32,
+    pub exported_at: String,
+    pub servers: Vec<ServerDefinition>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ImportResult {
+    pub source_id: String,
+    pub name: String,
+    /// Id of the created server
+    pub id: Option<String>,
+    pub error: Option<String>,
+}
+
+/// Configuration of a server, its paths made relative to its directory
+fn definition(row: ServerRow) -> ServerDefinition {
+    let executable_path = StdPath::new(&row.executable_path)
+        .strip_prefix(&row.working_dir)
+        .map(|relative| relative.to_string_lossy().into_owned())
+        .unwrap_or(row.executable_path);
+    let non_empty = |value: Option<String>| value.filter(|v| !v.is_empty());
+
+    ServerDefinition {
+        source_id: row.id,
+        server: CreateServerRequest {
+            name: row.name,
+            game_type: row.game_type,
+            executable_path,
+            // Replaced by the importing panel's servers_dir
+            working_dir: String::new(),
+            java_path: row.java_path,
+            min_memory: row.min_memory,
+            max_memory: row.max_memory,
+            extra_args: row.extra_args,
+            config: row.config.as_deref().and_then(|c| serde_json::from_str(c).ok()),
+            auto_start: Some(row.auto_start != 0),
+            backup_enabled: Some(row.backup_enabled != 0),
+            backup_frequency: Some(row.backup_frequency.max(0) as u32),
+            backup_max_backups: Some(row.backup_max_backups.max(0) as u32),
+            backup_prefix: Some(row.backup_prefix),
+            discord_username: row.discord_username,
+            discord_avatar: row.discord_avatar,
+            discord_webhook_url: row.discord_webhook_url,
+            discord_notifications: row.discord_notifications.as_deref().and_then(|n| serde_json::from_str(n).ok()),
+            discord_color: row.discord_color,
+            discord_emoji: row.discord_emoji,
+            logs_retention_days: Some(row.logs_retention_days.max(0) as u32),
+            watchdog_enabled: Some(row.watchdog_enabled != 0),
+            auth_mode: Some(row.auth_mode),
+            bind_address: Some(row.bind_address),
+            port: u16::try_from(row.port).ok(),
+            env_vars: Some(parse_env_vars(row.env_vars.as_deref())),
+            launch_mode: Some(row.launch_mode),
+            command_template: row.command_template,
+            runtime: Some(row.runtime),
+            docker_image: row.docker_image,
+            locale: Some(row.locale),
+            stop_command: row.stop_command,
+            restart_schedule: row.restart_schedule,
+            log_rotation_count: Some(row.log_rotation_count.max(0) as u32),
+            console_log_enabled: Some(row.console_log_enabled != 0),
+            console_log_max_mb: Some(row.console_log_max_mb.max(0) as u32),
+            console_log_dedup: Some(row.console_log_dedup != 0),
+            network_role: non_empty(row.network_role),
+            parent_proxy_id: non_empty(row.parent_proxy_id),
+            backup_save_command: non_empty(row.backup_save_command),
+            backup_save_confirm: non_empty(row.backup_save_confirm),
+            backup_autosave_off_command: non_empty(row.backup_autosave_off_command),
+            backup_autosave_on_command: non_empty(row.backup_autosave_on_command),
+            backup_filename_template: non_empty(row.backup_filename_template),
+            alert_cpu_percent: row.alert_cpu_percent.map(|v| v as f32),
+            alert_ram_percent: row.alert_ram_percent.map(|v| v as f32),
+            alert_disk_mb: row.alert_disk_mb.map(|v| v.max(0) as u64),
+            health_probe: row.health_probe,
+            health_probe_interval_secs: Some(row.health_probe_interval_secs.max(0) as u32),
+            tags: Some(parse_tags(row.tags.as_deref())),
+        },
+    }
+}
+
+fn attachment(servers: Vec<ServerRow>, filename: &str) -> Response {
+    let export = ServerExport {
+        version: EXPORT_VERSION,
+        exported_at: Utc::now().to_rfc3339(),
+        servers: servers.into_iter().map(definition).collect(),
+    };
+    (
+        [(header::CONTENT_DISPOSITION, format!("attachment; filename=\"{}\"", filename))],
+        Json(export),
+    ).into_response()
+}
+
+/// Every server the caller manages. Definitions carry webhook URLs, so only managers get them.
+#[utoipa::path(get, path = "/servers/export", tag = "servers", responses((status = 200, body = ServerExport)))]
+pub async fn export_servers(State(state): State<AppState>, auth: AuthUser) -> Result<Response, AppError> {
+    let allowed = server_access::allowed_servers(&state.pool, &auth, server_access::MANAGE).await?;
+    let servers: Vec<ServerRow> = sqlx::query_as("SELECT * FROM servers ORDER BY name")
+        .fetch_all(&state.pool)
+        .await?;
+    let servers = servers.into_iter()
+        .filter(|s| allowed.as_ref().is_none_or(|allowed| allowed.contains(&s.id)))
+        .collect();
+    Ok(attachment(servers, "servers.json"))
+}
+
+#[utoipa::path(get, path = "/servers/{id}/export", tag = "servers", params(("id" = String, Path)), responses((status = 200, body = ServerExport)))]
+pub async fn export_server(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(id): Path<String>,
+) -> Result<Response, AppError> {
+    server_access::require_permission(&state.pool, &auth, &id, server_access::MANAGE).await?;
+    let server: ServerRow = sqlx::query_as("SELECT * FROM servers WHERE id = ?")
+        .bind(&id)
+        .fetch_optional(&state.pool)
+        .await?
+        .ok_or_else(|| AppError::NotFound("servers.not_found".into()))?;
+    let filename = format!("server-{}.json", server.id);
+    Ok(attachment(vec![server], &filename))
+}
+
+/// The translation key of a failed creation, internal details stay in the logs
+fn error_key(error: AppError) -> String {
+    match error {
+        AppError::NotFound(key) | AppError::BadRequest(key) | AppError::Unauthorized(key)
+        | AppError::Forbidden(key) | AppError::TooManyRequests(key) | AppError::Conflict(key) => key,
+        AppError::Internal(e) | AppError::Database(e) => {
+            tracing::error!("Server import failed: {}", e);
+            "errors.internal".to_string()
+        }
+    }
+}
+
+/// Create the servers of an export in the local `servers_dir`. Proxies come first so their
+/// lobby and game servers can be linked to the new ids; a server whose proxy isn't in the
+/// document keeps its role without a parent. Each server succeeds or fails on its own.
+#[utoipa::path(
+    post, path = "/servers/import", tag = "servers",
+    request_body = ServerExport,
+    responses((status = 200, body = serde_json::Value, description = "`imported` count and one ImportResult per server"))
+)]
+pub async fn import_servers(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Json(body): Json<ServerExport>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    // Definitions carry launch commands and paths, as a creation does
+    auth.require_admin()?;
+    if body.version > EXPORT_VERSION {
+        return Err(AppError::BadRequest("servers.import_unsupported_version".into()));
+    }
+    if body.servers.is_empty() {
+        return Err(AppError::BadRequest("servers.import_empty".into()));
+    }
+
+    let servers_dir = settings::servers_dir(&state.pool).await;
+    let mut definitions = body.servers;
+    definitions.sort_by_key(|d| d.server.network_role.as_deref() != Some(network::ROLE_PROXY));
+
+    let mut created: HashMap<String, String> = HashMap::new();
+    let mut results = Vec::with_capacity(definitions.len());
+    for ServerDefinition { source_id, mut server } in definitions {
+        server.working_dir = servers_dir.clone();
+        server.parent_proxy_id = server.parent_proxy_id.and_then(|parent| created.get(&parent).cloned());
+        let name = server.name.clone();
+
+        let result = match create_server_from(state.clone(), server).await {
+            Ok((_, Json(response))) => {
+                let id = response["id"].as_str().unwrap_or_default().to_string();
+                created.insert(source_id.clone(), id.clone());
+                ImportResult { source_id, name, id: Some(id), error: None }
+            }
+            Err(e) => ImportResult { source_id, name, id: None, error: Some(error_key(e)) },
+        };
+        results.push(result);
+    }
+
+    tracing::info!("Imported {} of {} servers", created.len(), results.len());
+    Ok(Json(serde_json::json!({
+        "imported": created.len(),
+        "results": results,
+    })))
+}
@@ -0,0 +1,91 @@
+//! Metrics history of a server for the dashboard graphs, from the samples `history` stores
+//! every minute.
+
+use axum::{
+    extract::{Path, Query, State},
+    Json,
+};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+use crate::api::auth::AuthUser;
+use crate::api::visibility;
+use crate::error::AppError;
+use crate::services::history::{self, MetricsPoint, SAMPLE_INTERVAL_SECS};
+use crate::AppState;
+
+/// Points returned at most, longer ranges average more samples per point
+const MAX_POINTS: i64 = 300;
+/// Longest range, the default retention of the samples
+const MAX_RANGE_SECS: i64 = 30 * 24 * 3600;
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct StatsQuery {
+    /// Period up to now, in minutes, hours or days (`30m`, `24h`, `7d`); 24 hours when absent
+    pub range: Option<String>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ServerStats {
+    pub range: String,
+    /// Period each point covers
+    pub interval_secs: i64,
+    /// Oldest first
+    pub points: Vec<MetricsPoint>,
+}
+
+/// Seconds of a `range` parameter
+fn parse_range(range: &str) -> Option<i64> {
+    if !range.is_ascii() || range.len() < 2 {
+        return None;
+    }
+    let (amount, unit) = range.split_at(range.len() - 1);
+    let unit_secs = match unit {
+        "m" => 60,
+        "h" => 3600,
+        "d" => 86400,
+        _ => return None,
+    };
+    let secs = amount.parse::<i64>().ok()?.checked_mul(unit_secs)?;
+    (secs > 0 && secs <= MAX_RANGE_SECS).then_some(secs)
+}
+
+/// CPU, memory, players and disk of a server over time
+#[utoipa::path(get, path = "/servers/{id}/stats", tag = "servers", params(("id" = String, Path), StatsQuery), responses((status = 200, body = ServerStats)))]
+pub async fn get_server_stats(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(id): Path<String>,
+    Query(query): Query<StatsQuery>,
+) -> Result<Json<ServerStats>, AppError> {
+    let range = query.range.unwrap_or_else(|| "24h".to_string());
+    let secs = parse_range(&range).ok_or_else(|| AppError::BadRequest("servers.invalid_stats_range".into()))?;
+
+    let exists: Option<(String,)> = sqlx::query_as("SELECT id FROM servers WHERE id = ?")
+        .bind(&id)
+        .fetch_optional(&state.pool)
+        .await?;
+    if exists.is_none() {
+        return Err(AppError::NotFound("servers.not_found".into()));
+    }
+
+    let interval_secs = (secs / MAX_POINTS).max(SAMPLE_INTERVAL_SECS as i64);
+    let since = Utc::now() - chrono::Duration::seconds(secs);
+    let mut points = history::metrics_series(&state.pool, &id, since, interval_secs).await?;
+
+    // Same field groups as the server responses
+    let hide_process = visibility::is_hidden("process_metrics", &auth.role);
+    let hide_disk = visibility::is_hidden("disk_usage", &auth.role);
+    for point in &mut points {
+        if hide_process {
+            point.cpu = None;
+            point.memory_bytes = None;
+        }
+        if hide_disk {
+            point.disk_bytes = None;
+        }
+    }
+    Ok(Json(ServerStats { range, interval_secs, points }))
+}
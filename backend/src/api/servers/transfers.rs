@@ -0,0 +1,236 @@
+//! Handing a server over to another panel user: the current owner (or an admin) proposes,
+//! the receiving user confirms, and every step lands in the server's event history.
+
+use axum::{
+    extract::{Path, State},
+    Json,
+};
+use chrono::Utc;
+use uuid::Uuid;
+
+use crate::api::auth::AuthUser;
+use crate::db::DbPool;
+use crate::error::AppError;
+use crate::services::{elevation, history};
+use crate::AppState;
+use super::models::{ServerTransfer, TransferRequest, TRANSFER_ACCEPTED, TRANSFER_DECLINED, TRANSFER_PENDING};
+
+const TRANSFER_COLUMNS: &str =
+    "id, server_id, from_user_id, to_user_id, move_allocation, status, requested_by, created_at, resolved_at";
+
+#[utoipa::path(post, path = "/servers/{id}/transfer", tag = "servers", params(("id" = String, Path)), request_body = TransferRequest, responses((status = 200, body = ServerTransfer)))]
+pub async fn request_transfer(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(id): Path<String>,
+    Json(body): Json<TransferRequest>,
+) -> Result<Json<ServerTransfer>, AppError> {
+    let (owner_id,): (Option<String>,) = sqlx::query_as("SELECT owner_id FROM servers WHERE id = ?")
+        .bind(&id)
+        .fetch_optional(&state.pool)
+        .await?
+        .ok_or_else(|| AppError::NotFound("servers.not_found".into()))?;
+
+    if owner_id.as_deref() != Some(auth.id.as_str()) && !elevation::has_server_admin(&state.pool, &auth, &id).await {
+        return Err(AppError::Unauthorized("servers.transfer_not_owner".into()));
+    }
+    if owner_id.as_deref() == Some(body.to_user_id.as_str()) {
+        return Err(AppError::BadRequest("servers.transfer_same_owner".into()));
+    }
+
+    let recipient = username(&state.pool, &body.to_user_id)
+        .await?
+        .ok_or_else(|| AppError::NotFound("users.not_found".into()))?;
+
+    let pending: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM server_transfers WHERE server_id = ? AND status = ?")
+        .bind(&id)
+        .bind(TRANSFER_PENDING)
+        .fetch_one(&state.pool)
+        .await?;
+    if pending > 0 {
+        return Err(AppError::BadRequest("servers.transfer_already_pending".into()));
+    }
+
+    let transfer = ServerTransfer {
+        id: Uuid::new_v4().to_string(),
+        server_id: id.clone(),
+        from_user_id: owner_id,
+        to_user_id: body.to_user_id.clone(),
+        move_allocation: body.move_allocation.unwrap_or(true),
+        status: TRANSFER_PENDING.to_string(),
+        requested_by: auth.id.clone(),
+        created_at: Utc::now().to_rfc3339(),
+        resolved_at: None,
+    };
+
+    sqlx::query(
+        "INSERT INTO server_transfers (id, server_id, from_user_id, to_user_id, move_allocation, status, requested_by, created_at)
+         VALUES (?, ?, ?, ?, ?, ?, ?, ?)"
+    )
+    .bind(&transfer.id)
+    .bind(&transfer.server_id)
+    .bind(&transfer.from_user_id)
+    .bind(&transfer.to_user_id)
+    .bind(transfer.move_allocation as i32)
+    .bind(&transfer.status)
+    .bind(&transfer.requested_by)
+    .bind(&transfer.created_at)
+    .execute(&state.pool)
+    .await?;
+
+    history::record_event(
+        &state.pool,
+        &id,
+        history::EVENT_TRANSFER_REQUESTED,
+        Some(&format!("{} -> {}", auth.username, recipient)),
+    ).await;
+
+    Ok(Json(transfer))
+}
+
+/// Pending transfers waiting for the caller (every pending transfer for admins)
+#[utoipa::path(get, path = "/servers/transfers", tag = "servers", responses((status = 200, body = [ServerTransfer])))]
+pub async fn list_transfers(
+    State(state): State<AppState>,
+    auth: AuthUser,
+) -> Result<Json<Vec<ServerTransfer>>, AppError> {
+    let transfers = if auth.is_admin() {
+        sqlx::query_as(&format!("SELECT {} FROM server_transfers WHERE status = ? ORDER BY created_at DESC", TRANSFER_COLUMNS))
+            .bind(TRANSFER_PENDING)
+            .fetch_all(&state.pool)
+            .await?
+    } else {
+        sqlx::query_as(&format!("SELECT {} FROM server_transfers WHERE status = ? AND to_user_id = ? ORDER BY created_at DESC", TRANSFER_COLUMNS))
+            .bind(TRANSFER_PENDING)
+            .bind(&auth.id)
+            .fetch_all(&state.pool)
+            .await?
+    };
+    Ok(Json(transfers))
+}
+
+/// Receiving user confirms: ownership (and allocation if asked) moves to them
+#[utoipa::path(post, path = "/servers/transfers/{transfer_id}/accept", tag = "servers", params(("transfer_id" = String, Path)), responses((status = 200, body = serde_json::Value)))]
+pub async fn accept_transfer(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(transfer_id): Path<String>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let transfer = pending_transfer_for(&state.pool, &transfer_id, &auth).await?;
+    let now = Utc::now().to_rfc3339();
+
+    let mut tx = state.pool.begin().await?;
+    sqlx::query("UPDATE servers SET owner_id = ?, updated_at = ? WHERE id = ?")
+        .bind(&transfer.to_user_id)
+        .bind(&now)
+        .bind(&transfer.server_id)
+        .execute(&mut *tx)
+        .await?;
+
+    if transfer.move_allocation {
+        if let Some(from) = &transfer.from_user_id {
+            update_allocation(&mut tx, from, |servers| servers.retain(|s| s != &transfer.server_id)).await?;
+        }
+        update_allocation(&mut tx, &transfer.to_user_id, |servers| {
+            if !servers.contains(&transfer.server_id) {
+                servers.push(transfer.server_id.clone());
+            }
+        }).await?;
+    }
+
+    sqlx::query("UPDATE server_transfers SET status = ?, resolved_at = ? WHERE id = ?")
+        .bind(TRANSFER_ACCEPTED)
+        .bind(&now)
+        .bind(&transfer.id)
+        .execute(&mut *tx)
+        .await?;
+    tx.commit().await?;
+
+    let from = match &transfer.from_user_id {
+        Some(from) => username(&state.pool, from).await?.unwrap_or_else(|| from.clone()),
+        None => "-".to_string(),
+    };
+    history::record_event(
+        &state.pool,
+        &transfer.server_id,
+        history::EVENT_TRANSFERRED,
+        Some(&format!("{} -> {}", from, auth.username)),
+    ).await;
+
+    Ok(Json(serde_json::json!({
+        "success": true,
+        "server_id": transfer.server_id,
+        "owner_id": transfer.to_user_id
+    })))
+}
+
+#[utoipa::path(post, path = "/servers/transfers/{transfer_id}/decline", tag = "servers", params(("transfer_id" = String, Path)), responses((status = 200, body = serde_json::Value)))]
+pub async fn decline_transfer(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(transfer_id): Path<String>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let transfer = pending_transfer_for(&state.pool, &transfer_id, &auth).await?;
+
+    sqlx::query("UPDATE server_transfers SET status = ?, resolved_at = ? WHERE id = ?")
+        .bind(TRANSFER_DECLINED)
+        .bind(Utc::now().to_rfc3339())
+        .bind(&transfer.id)
+        .execute(&state.pool)
+        .await?;
+
+    history::record_event(&state.pool, &transfer.server_id, history::EVENT_TRANSFER_DECLINED, Some(&auth.username)).await;
+
+    Ok(Json(serde_json::json!({ "success": true })))
+}
+
+/// A pending transfer addressed to the caller
+async fn pending_transfer_for(pool: &DbPool, transfer_id: &str, auth: &AuthUser) -> Result<ServerTransfer, AppError> {
+    let transfer: ServerTransfer = sqlx::query_as(&format!("SELECT {} FROM server_transfers WHERE id = ?", TRANSFER_COLUMNS))
+        .bind(transfer_id)
+        .fetch_optional(pool)
+        .await?
+        .ok_or_else(|| AppError::NotFound("servers.transfer_not_found".into()))?;
+
+    if transfer.status != TRANSFER_PENDING {
+        return Err(AppError::BadRequest("servers.transfer_not_pending".into()));
+    }
+    if transfer.to_user_id != auth.id {
+        return Err(AppError::Unauthorized("servers.transfer_not_recipient".into()));
+    }
+    Ok(transfer)
+}
+
+pub(super) async fn username(pool: &DbPool, user_id: &str) -> Result<Option<String>, AppError> {
+    Ok(sqlx::query_scalar("SELECT username FROM users WHERE id = ?")
+        .bind(user_id)
+        .fetch_optional(pool)
+        .await?)
+}
+
+/// Edit the `allocated_servers` JSON list of a user
+async fn update_allocation(
+    tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+    user_id: &str,
+    change: impl FnOnce(&mut Vec<String>),
+) -> Result<(), AppError> {
+    let current: Option<Option<String>> = sqlx::query_scalar("SELECT allocated_servers FROM users WHERE id = ?")
+        .bind(user_id)
+        .fetch_optional(&mut **tx)
+        .await?;
+    let Some(current) = current else {
+        return Ok(());
+    };
+
+    let mut servers: Vec<String> = current
+        .and_then(|s| serde_json::from_str(&s).ok())
+        .unwrap_or_default();
+    change(&mut servers);
+
+    sqlx::query("UPDATE users SET allocated_servers = ? WHERE id = ?")
+        .bind(serde_json::to_string(&servers).unwrap_or_else(|_| "[]".to_string()))
+        .bind(user_id)
+        .execute(&mut **tx)
+        .await?;
+    Ok(())
+}
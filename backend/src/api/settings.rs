@@ -4,16 +4,46 @@ use axum::{
     Json, Router,
 };
 use serde::{Deserialize, Serialize};
+use utoipa::{OpenApi, ToSchema};
 
 use crate::AppState;
+use crate::api::auth::AuthUser;
+use crate::api::visibility::{self, VisibilityMatrix};
+use crate::db::DbPool;
 use crate::error::AppError;
+use crate::services::auth_detection::{self, AuthPatterns};
+use crate::services::backup_crypto::EncryptionKey;
+use crate::services::{backup_quota, db_backup};
+use crate::services::backup_service::{self, Codec};
+use crate::services::backup_transfer;
+use crate::services::{cors, daily_summary, mailer, oidc, protected_paths, rate_limit, retention, server_access};
+use crate::services::rate_limit::RateLimits;
+use crate::utils::{atomic_file, platform};
+use crate::services::process_manager::{DEFAULT_MAX_CONCURRENT_STARTS, DEFAULT_METRICS_INTERVAL_SECS};
 
 pub fn routes() -> Router<AppState> {
     Router::new()
         .route("/", get(get_settings).put(update_settings))
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(OpenApi)]
+#[openapi(paths(get_settings, update_settings))]
+pub(super) struct ApiDoc;
+
+/// Directory new servers are created in: `SERVERS_DIR`, else the `servers_dir` setting
+pub async fn servers_dir(pool: &DbPool) -> String {
+    let saved: Option<String> = sqlx::query_scalar("SELECT value FROM settings WHERE key = 'servers_dir'")
+        .fetch_optional(pool)
+        .await
+        .ok()
+        .flatten();
+    std::env::var("SERVERS_DIR")
+        .ok()
+        .or(saved)
+        .unwrap_or_else(|| "./data/servers".into())
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct SettingsResponse {
     pub version: String,
     pub servers_dir: String,
@@ -21,11 +51,103 @@ pub struct SettingsResponse {
     pub database_path: String,
     pub webhook_url: Option<String>,
     pub is_docker: bool,
+    /// `dummy` servers (built-in simulator) can be created
+    pub dev_mode: bool,
     pub login_default_color: Option<String>,
     pub login_background_url: Option<String>,
+    pub downloader_emulator: Option<String>,
+    pub downloader_url: Option<String>,
+    pub offline_install: bool,
+    pub host_arch: String,
+    pub daily_summary_enabled: bool,
+    pub daily_summary_time: String,
+    pub daily_summary_scope: String,
+    pub daily_summary_webhook_url: Option<String>,
+    pub max_concurrent_starts: usize,
+    /// Seconds between two process metrics samples
+    pub metrics_interval_secs: u64,
+    pub metrics_retention_days: u32,
+    pub events_retention_days: u32,
+    pub audit_retention_days: u32,
+    /// World snapshot before reinstalls, bundle installs, mod and config changes
+    pub auto_snapshots: bool,
+    /// Backup and snapshot archives: "gzip", "zstd" or "none"
+    pub backup_compression: String,
+    pub backup_compression_level: i32,
+    /// S3-compatible bucket receiving a copy of every backup
+    pub s3_endpoint: Option<String>,
+    pub s3_region: Option<String>,
+    pub s3_bucket: Option<String>,
+    pub s3_access_key: Option<String>,
+    /// The secret itself is never sent back
+    pub s3_secret_key_set: bool,
+    pub s3_prefix: Option<String>,
+    /// Uploaded backups kept on disk per server (0 keeps all)
+    pub s3_local_keep: u32,
+    /// SFTP or FTP server receiving a copy of every backup ("sftp", "ftp" or empty)
+    pub backup_target_protocol: String,
+    pub backup_target_host: Option<String>,
+    pub backup_target_port: Option<u16>,
+    pub backup_target_username: Option<String>,
+    pub backup_target_password_set: bool,
+    pub backup_target_path: Option<String>,
+    pub backup_target_key_path: Option<String>,
+    pub backup_target_fingerprint: Option<String>,
+    /// Whether new backups are encrypted, the key itself is never sent back
+    pub backup_encryption_key_set: bool,
+    /// Disk budget of all local backups in MB (0 for none), the oldest are pruned beyond it
+    pub backup_quota_mb: u64,
+    /// Local backups each server keeps when pruning for the quota
+    pub backup_quota_min_per_server: u32,
+    /// Hours between two copies of the panel database (0 disables them)
+    pub db_backup_interval_hours: u64,
+    pub db_backup_keep: usize,
+    /// Launch native servers in systemd transient scopes
+    pub systemd_scopes: bool,
+    /// Whether the host runs systemd, required for `systemd_scopes`
+    pub systemd_available: bool,
+    /// Users whose break-glass requests are approved without an admin
+    pub break_glass_on_call: Vec<String>,
+    /// Admins must enable two-factor authentication before using the panel
+    pub require_2fa_admin: bool,
+    /// OpenID Connect provider for single sign-on, enabled once issuer, client id and redirect URL are set
+    pub oidc_issuer_url: Option<String>,
+    pub oidc_client_id: Option<String>,
+    pub oidc_client_secret_set: bool,
+    pub oidc_redirect_url: Option<String>,
+    pub oidc_scopes: Option<String>,
+    /// Role of accounts created on a first SSO login
+    pub oidc_default_role: String,
+    /// SSO logins take over local accounts with the same username or email
+    pub oidc_link_existing: bool,
+    /// SMTP server for password reset emails, enabled once host, sender and panel URL are set
+    pub smtp_host: Option<String>,
+    pub smtp_port: Option<u16>,
+    /// `starttls`, `tls` or `none`
+    pub smtp_security: String,
+    pub smtp_username: Option<String>,
+    pub smtp_password_set: bool,
+    pub smtp_from: Option<String>,
+    /// The panel's address as users reach it, reset links point there
+    pub smtp_panel_url: Option<String>,
+    /// Sites allowed to call the API besides those of `ALLOWED_ORIGINS`
+    pub allowed_origins: Vec<String>,
+    /// Server files only admins may write or delete through the file API
+    pub protected_paths: Vec<String>,
+    /// Bucket (`auth`, `files`, `commands`) -> requests per minute per client, 0 for unlimited
+    #[schema(value_type = Object)]
+    pub rate_limits: RateLimits,
+    /// Per-table row counts deleted by the last nightly cleanup
+    pub history_cleanup_report: Option<serde_json::Value>,
+    /// Field group -> non-admin roles it is hidden from
+    #[schema(value_type = Object)]
+    pub field_visibility: VisibilityMatrix,
+    /// Game type -> regexes spotting authentication prompts in downloader and server output
+    #[schema(value_type = Object)]
+    pub auth_patterns: AuthPatterns,
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, ToSchema)]
 struct UpdateSettingsRequest {
     webhook_url: Option<String>,
     servers_dir: Option<String>,
@@ -33,9 +155,71 @@ struct UpdateSettingsRequest {
     database_path: Option<String>,
     login_default_color: Option<String>,
     login_background_url: Option<String>,
+    downloader_emulator: Option<String>,
+    downloader_url: Option<String>,
+    offline_install: Option<bool>,
+    daily_summary_enabled: Option<bool>,
+    daily_summary_time: Option<String>,
+    daily_summary_scope: Option<String>,
+    daily_summary_webhook_url: Option<String>,
+    max_concurrent_starts: Option<usize>,
+    metrics_interval_secs: Option<u64>,
+    metrics_retention_days: Option<u32>,
+    events_retention_days: Option<u32>,
+    audit_retention_days: Option<u32>,
+    auto_snapshots: Option<bool>,
+    backup_compression: Option<String>,
+    backup_compression_level: Option<i32>,
+    s3_endpoint: Option<String>,
+    s3_region: Option<String>,
+    s3_bucket: Option<String>,
+    s3_access_key: Option<String>,
+    s3_secret_key: Option<String>,
+    s3_prefix: Option<String>,
+    s3_local_keep: Option<u32>,
+    backup_target_protocol: Option<String>,
+    backup_target_host: Option<String>,
+    backup_target_port: Option<u16>,
+    backup_target_username: Option<String>,
+    backup_target_password: Option<String>,
+    backup_target_path: Option<String>,
+    backup_target_key_path: Option<String>,
+    backup_target_fingerprint: Option<String>,
+    /// 64 hex characters, empty stops encrypting new backups
+    backup_encryption_key: Option<String>,
+    backup_quota_mb: Option<u64>,
+    backup_quota_min_per_server: Option<u32>,
+    db_backup_interval_hours: Option<u64>,
+    db_backup_keep: Option<usize>,
+    systemd_scopes: Option<bool>,
+    break_glass_on_call: Option<Vec<String>>,
+    require_2fa_admin: Option<bool>,
+    oidc_issuer_url: Option<String>,
+    oidc_client_id: Option<String>,
+    oidc_client_secret: Option<String>,
+    oidc_redirect_url: Option<String>,
+    oidc_scopes: Option<String>,
+    oidc_default_role: Option<String>,
+    oidc_link_existing: Option<bool>,
+    smtp_host: Option<String>,
+    smtp_port: Option<u16>,
+    smtp_security: Option<String>,
+    smtp_username: Option<String>,
+    smtp_password: Option<String>,
+    smtp_from: Option<String>,
+    smtp_panel_url: Option<String>,
+    allowed_origins: Option<Vec<String>>,
+    protected_paths: Option<Vec<String>>,
+    #[schema(value_type = Object)]
+    rate_limits: Option<RateLimits>,
+    #[schema(value_type = Object)]
+    field_visibility: Option<VisibilityMatrix>,
+    #[schema(value_type = Object)]
+    auth_patterns: Option<AuthPatterns>,
 }
 
-async fn get_settings(State(state): State<AppState>) -> Result<Json<SettingsResponse>, AppError> {
+#[utoipa::path(get, path = "/settings", tag = "settings", responses((status = 200, body = SettingsResponse)))]
+async fn get_settings(State(state): State<AppState>, auth: AuthUser) -> Result<Json<SettingsResponse>, AppError> {
     // Read from DB
     let settings_rows: Vec<(String, String)> = sqlx::query_as("SELECT key, value FROM settings")
         .fetch_all(&state.pool)
@@ -45,34 +229,122 @@ async fn get_settings(State(state): State<AppState>) -> Result<Json<SettingsResp
     let settings_map: std::collections::HashMap<String, String> = settings_rows.into_iter().collect();
 
     // Priority: Env > DB > Default
-    let servers_dir = std::env::var("SERVERS_DIR")
-        .ok()
-        .or_else(|| settings_map.get("servers_dir").cloned())
-        .unwrap_or_else(|| "./data/servers".into());
+    let servers_dir = servers_dir(&state.pool).await;
     
     let backups_dir = std::env::var("BACKUPS_DIR")
         .ok()
         .or_else(|| settings_map.get("backups_dir").cloned())
         .unwrap_or_else(|| "./data/backups".into());
 
-    let settings = SettingsResponse {
+    let compression = backup_service::configured_compression(&state.pool).await;
+
+    let mut settings = SettingsResponse {
         version: env!("CARGO_PKG_VERSION").to_string(),
         servers_dir,
         backups_dir,
         database_path: std::env::var("DATABASE_URL").unwrap_or_else(|_| "sqlite:data/database.db".into()),
         webhook_url: settings_map.get("webhook_url").cloned(),
         is_docker: std::env::var("IS_DOCKER").is_ok(),
+        dev_mode: state.settings.dev_mode,
         login_default_color: settings_map.get("login_default_color").cloned(),
         login_background_url: settings_map.get("login_background_url").cloned(),
+        downloader_emulator: settings_map.get("downloader_emulator").cloned(),
+        downloader_url: settings_map.get("downloader_url").cloned(),
+        offline_install: settings_map.get("offline_install").is_some_and(|v| v == "true"),
+        host_arch: std::env::consts::ARCH.to_string(),
+        daily_summary_enabled: settings_map.get("daily_summary_enabled").is_some_and(|v| v == "true"),
+        daily_summary_time: settings_map.get("daily_summary_time").cloned()
+            .unwrap_or_else(|| daily_summary::DEFAULT_TIME.to_string()),
+        daily_summary_scope: settings_map.get("daily_summary_scope").cloned()
+            .unwrap_or_else(|| "global".to_string()),
+        daily_summary_webhook_url: settings_map.get("daily_summary_webhook_url").cloned(),
+        max_concurrent_starts: settings_map.get("max_concurrent_starts")
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(DEFAULT_MAX_CONCURRENT_STARTS),
+        metrics_interval_secs: settings_map.get("metrics_interval_secs")
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(DEFAULT_METRICS_INTERVAL_SECS),
+        metrics_retention_days: retention_setting(&settings_map, "metrics_retention_days"),
+        events_retention_days: retention_setting(&settings_map, "events_retention_days"),
+        audit_retention_days: retention_setting(&settings_map, "audit_retention_days"),
+        auto_snapshots: settings_map.get("auto_snapshots").is_none_or(|v| v != "false"),
+        backup_compression: compression.codec.as_str().to_string(),
+        backup_compression_level: compression.level.unwrap_or_else(|| compression.codec.default_level()),
+        s3_endpoint: settings_map.get("s3_endpoint").cloned(),
+        s3_region: settings_map.get("s3_region").cloned(),
+        s3_bucket: settings_map.get("s3_bucket").cloned(),
+        s3_access_key: settings_map.get("s3_access_key").cloned(),
+        s3_secret_key_set: settings_map.get("s3_secret_key").is_some_and(|v| !v.is_empty()),
+        s3_prefix: settings_map.get("s3_prefix").cloned(),
+        s3_local_keep: settings_map.get("s3_local_keep").and_then(|v| v.parse().ok()).unwrap_or(0),
+        backup_target_protocol: settings_map.get("backup_target_protocol").cloned().unwrap_or_default(),
+        backup_target_host: settings_map.get("backup_target_host").cloned(),
+        backup_target_port: settings_map.get("backup_target_port").and_then(|v| v.parse().ok()),
+        backup_target_username: settings_map.get("backup_target_username").cloned(),
+        backup_target_password_set: settings_map.get("backup_target_password").is_some_and(|v| !v.is_empty()),
+        backup_target_path: settings_map.get("backup_target_path").cloned(),
+        backup_target_key_path: settings_map.get("backup_target_key_path").cloned(),
+        backup_target_fingerprint: settings_map.get("backup_target_fingerprint").cloned(),
+        backup_encryption_key_set: settings_map.get("backup_encryption_key").is_some_and(|v| !v.is_empty()),
+        backup_quota_mb: settings_map.get("backup_quota_mb").and_then(|v| v.parse().ok()).unwrap_or(0),
+        backup_quota_min_per_server: settings_map.get("backup_quota_min_per_server")
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(backup_quota::DEFAULT_MIN_PER_SERVER),
+        db_backup_interval_hours: settings_map.get("db_backup_interval_hours")
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(db_backup::DEFAULT_INTERVAL_HOURS),
+        db_backup_keep: settings_map.get("db_backup_keep").and_then(|v| v.parse().ok()).unwrap_or(db_backup::DEFAULT_KEEP),
+        systemd_scopes: settings_map.get("systemd_scopes").is_some_and(|v| v == "true"),
+        systemd_available: platform::systemd_available(),
+        break_glass_on_call: settings_map.get("break_glass_on_call")
+            .and_then(|v| serde_json::from_str(v).ok())
+            .unwrap_or_default(),
+        require_2fa_admin: settings_map.get("require_2fa_admin").is_some_and(|v| v == "true"),
+        oidc_issuer_url: settings_map.get("oidc_issuer_url").cloned(),
+        oidc_client_id: settings_map.get("oidc_client_id").cloned(),
+        oidc_client_secret_set: settings_map.get("oidc_client_secret").is_some_and(|v| !v.is_empty()),
+        oidc_redirect_url: settings_map.get("oidc_redirect_url").cloned(),
+        oidc_scopes: settings_map.get("oidc_scopes").cloned(),
+        oidc_default_role: settings_map.get("oidc_default_role").cloned().unwrap_or_else(|| oidc::DEFAULT_ROLE.to_string()),
+        oidc_link_existing: settings_map.get("oidc_link_existing").is_some_and(|v| v == "true"),
+        smtp_host: settings_map.get("smtp_host").cloned(),
+        smtp_port: settings_map.get("smtp_port").and_then(|v| v.parse().ok()),
+        smtp_security: settings_map.get("smtp_security").cloned().unwrap_or_else(|| "starttls".to_string()),
+        smtp_username: settings_map.get("smtp_username").cloned(),
+        smtp_password_set: settings_map.get("smtp_password").is_some_and(|v| !v.is_empty()),
+        smtp_from: settings_map.get("smtp_from").cloned(),
+        smtp_panel_url: settings_map.get("smtp_panel_url").cloned(),
+        allowed_origins: cors::get(),
+        protected_paths: protected_paths::get(),
+        rate_limits: state.rate_limiter.get(),
+        history_cleanup_report: settings_map.get("history_cleanup_report")
+            .and_then(|v| serde_json::from_str(v).ok()),
+        field_visibility: visibility::get(),
+        auth_patterns: auth_detection::get(),
     };
+    // Every user reads the version and the panel options, but webhook URLs carry their token and
+    // the offsite targets their login
+    if !auth.is_admin() {
+        settings.webhook_url = None;
+        settings.daily_summary_webhook_url = None;
+        settings.s3_access_key = None;
+        settings.backup_target_username = None;
+        settings.smtp_username = None;
+    }
 
     Ok(Json(settings))
 }
 
+/// Panel-wide settings reach every server, the host (installer, directories) and who may log
+/// in: admins only
+#[utoipa::path(put, path = "/settings", tag = "settings", request_body = UpdateSettingsRequest, responses((status = 200, body = serde_json::Value), (status = 403, description = "`auth.admin_required`")))]
 async fn update_settings(
     State(state): State<AppState>,
+    auth: AuthUser,
     Json(body): Json<UpdateSettingsRequest>,
 ) -> Result<Json<serde_json::Value>, AppError> {
+    auth.require_admin()?;
+
     // Helper function to upsert a setting
     async fn upsert_setting(pool: &crate::db::DbPool, key: &str, value: &str) -> Result<(), AppError> {
         sqlx::query(
@@ -86,6 +358,156 @@ async fn update_settings(
         Ok(())
     }
 
+    if let Some(required) = body.require_2fa_admin {
+        upsert_setting(&state.pool, "require_2fa_admin", if required { "true" } else { "false" }).await?;
+    }
+    // The identity provider decides who logs in and with which role
+    let oidc_fields = [
+        ("oidc_issuer_url", &body.oidc_issuer_url),
+        ("oidc_client_id", &body.oidc_client_id),
+        ("oidc_client_secret", &body.oidc_client_secret),
+        ("oidc_redirect_url", &body.oidc_redirect_url),
+        ("oidc_scopes", &body.oidc_scopes),
+        ("oidc_default_role", &body.oidc_default_role),
+    ];
+    for url in [&body.oidc_issuer_url, &body.oidc_redirect_url].into_iter().flatten().map(|u| u.trim()).filter(|u| !u.is_empty()) {
+        if !url.starts_with("http://") && !url.starts_with("https://") {
+            return Err(AppError::BadRequest("settings.invalid_oidc_url".into()));
+        }
+    }
+    if body.oidc_default_role.as_deref().is_some_and(|r| !server_access::ROLES.contains(&r)) {
+        return Err(AppError::BadRequest("settings.invalid_oidc_default_role".into()));
+    }
+    for (key, value) in oidc_fields {
+        if let Some(value) = value {
+            upsert_setting(&state.pool, key, value.trim()).await?;
+        }
+    }
+    if let Some(link) = body.oidc_link_existing {
+        upsert_setting(&state.pool, "oidc_link_existing", if link { "true" } else { "false" }).await?;
+    }
+    // Reset links are emailed from this server
+    let smtp_fields = [
+        ("smtp_host", &body.smtp_host),
+        ("smtp_security", &body.smtp_security),
+        ("smtp_username", &body.smtp_username),
+        ("smtp_password", &body.smtp_password),
+        ("smtp_from", &body.smtp_from),
+        ("smtp_panel_url", &body.smtp_panel_url),
+    ];
+    if body.smtp_security.as_deref().is_some_and(|s| !mailer::SECURITY_MODES.contains(&s)) {
+        return Err(AppError::BadRequest("settings.invalid_smtp_security".into()));
+    }
+    if let Some(url) = body.smtp_panel_url.as_deref().map(str::trim).filter(|u| !u.is_empty()) {
+        if !url.starts_with("http://") && !url.starts_with("https://") {
+            return Err(AppError::BadRequest("settings.invalid_smtp_panel_url".into()));
+        }
+    }
+    for (key, value) in smtp_fields {
+        if let Some(value) = value {
+            upsert_setting(&state.pool, key, value.trim()).await?;
+        }
+    }
+    if let Some(port) = body.smtp_port {
+        upsert_setting(&state.pool, "smtp_port", &port.to_string()).await?;
+    }
+    // Sites whose pages may call the API
+    if let Some(origins) = body.allowed_origins {
+        let origins = origins.iter()
+            .map(|o| o.trim())
+            .filter(|o| !o.is_empty())
+            .map(|o| cors::normalize(o).ok_or_else(|| AppError::BadRequest("settings.invalid_allowed_origin".into())))
+            .collect::<Result<Vec<_>, _>>()?;
+        let value = serde_json::to_string(&origins)
+            .map_err(|e| AppError::Internal(format!("Failed to serialize allowed origins: {}", e)))?;
+        upsert_setting(&state.pool, "allowed_origins", &value).await?;
+        cors::set(origins);
+    }
+    // The list is what keeps other users away from credentials and operators
+    if let Some(paths) = body.protected_paths {
+        let paths = paths.iter()
+            .filter(|p| !p.trim().is_empty())
+            .map(|p| protected_paths::normalize(p).ok_or_else(|| AppError::BadRequest("settings.invalid_protected_path".into())))
+            .collect::<Result<Vec<_>, _>>()?;
+        let value = serde_json::to_string(&paths)
+            .map_err(|e| AppError::Internal(format!("Failed to serialize protected paths: {}", e)))?;
+        upsert_setting(&state.pool, "protected_paths", &value).await?;
+        protected_paths::set(paths);
+    }
+    // Buckets left out keep their limit
+    if let Some(limits) = body.rate_limits {
+        if !limits.keys().all(|b| rate_limit::is_bucket(b)) {
+            return Err(AppError::BadRequest("settings.invalid_rate_limit".into()));
+        }
+        let mut merged = state.rate_limiter.get();
+        merged.extend(limits);
+        let value = serde_json::to_string(&merged)
+            .map_err(|e| AppError::Internal(format!("Failed to serialize rate limits: {}", e)))?;
+        upsert_setting(&state.pool, "rate_limits", &value).await?;
+        state.rate_limiter.set(merged);
+    }
+    // Offsite destinations, encryption and the storage quota reach every server's backups
+    let offsite_fields = [
+        ("s3_endpoint", &body.s3_endpoint),
+        ("s3_region", &body.s3_region),
+        ("s3_bucket", &body.s3_bucket),
+        ("s3_access_key", &body.s3_access_key),
+        ("s3_secret_key", &body.s3_secret_key),
+        ("s3_prefix", &body.s3_prefix),
+        ("backup_target_protocol", &body.backup_target_protocol),
+        ("backup_target_host", &body.backup_target_host),
+        ("backup_target_username", &body.backup_target_username),
+        ("backup_target_password", &body.backup_target_password),
+        ("backup_target_path", &body.backup_target_path),
+        ("backup_target_key_path", &body.backup_target_key_path),
+        ("backup_target_fingerprint", &body.backup_target_fingerprint),
+        ("backup_encryption_key", &body.backup_encryption_key),
+    ];
+    let offsite_numbers = [
+        ("s3_local_keep", body.s3_local_keep.map(|v| v.to_string())),
+        ("backup_target_port", body.backup_target_port.map(|v| v.to_string())),
+        ("backup_quota_mb", body.backup_quota_mb.map(|v| v.to_string())),
+        ("backup_quota_min_per_server", body.backup_quota_min_per_server.map(|v| v.to_string())),
+        ("db_backup_interval_hours", body.db_backup_interval_hours.map(|v| v.to_string())),
+        ("db_backup_keep", body.db_backup_keep.map(|v| v.to_string())),
+    ];
+    if let Some(endpoint) = body.s3_endpoint.as_deref().map(str::trim).filter(|e| !e.is_empty()) {
+        if !endpoint.starts_with("http://") && !endpoint.starts_with("https://") {
+            return Err(AppError::BadRequest("settings.invalid_s3_endpoint".into()));
+        }
+    }
+    if body.backup_target_protocol.as_deref().is_some_and(|p| !backup_transfer::is_valid_protocol(p.trim())) {
+        return Err(AppError::BadRequest("settings.invalid_backup_target_protocol".into()));
+    }
+    if let Some(key) = body.backup_encryption_key.as_deref().map(str::trim).filter(|k| !k.is_empty()) {
+        if EncryptionKey::parse(key).is_none() {
+            return Err(AppError::BadRequest("settings.invalid_backup_encryption_key".into()));
+        }
+    }
+    // A server's newest backup must survive the quota
+    if body.backup_quota_min_per_server == Some(0) {
+        return Err(AppError::BadRequest("settings.invalid_backup_quota_min_per_server".into()));
+    }
+    if body.db_backup_keep == Some(0) {
+        return Err(AppError::BadRequest("settings.invalid_db_backup_keep".into()));
+    }
+    for (key, value) in offsite_fields {
+        if let Some(value) = value {
+            upsert_setting(&state.pool, key, value.trim()).await?;
+        }
+    }
+    for (key, value) in offsite_numbers {
+        if let Some(value) = value {
+            upsert_setting(&state.pool, key, &value).await?;
+        }
+    }
+    if body.backup_quota_mb.is_some() || body.backup_quota_min_per_server.is_some() {
+        let (pool, backups_dir) = (state.pool.clone(), state.settings.backups_dir.clone());
+        tokio::spawn(async move {
+            backup_quota::enforce(&pool, &backups_dir).await;
+        });
+    }
+
     if let Some(ref webhook_url) = body.webhook_url {
         upsert_setting(&state.pool, "webhook_url", webhook_url).await?;
     }
@@ -116,27 +538,142 @@ async fn update_settings(
         upsert_setting(&state.pool, "login_background_url", url).await?;
     }
 
+    if let Some(ref emulator) = body.downloader_emulator {
+        upsert_setting(&state.pool, "downloader_emulator", emulator.trim()).await?;
+    }
+
+    if let Some(ref url) = body.downloader_url {
+        // Empty falls back to the official downloader
+        let url = url.trim();
+        if !url.is_empty() && !url.starts_with("http://") && !url.starts_with("https://") {
+            return Err(AppError::BadRequest("settings.invalid_downloader_url".into()));
+        }
+        upsert_setting(&state.pool, "downloader_url", url).await?;
+    }
+    if let Some(offline) = body.offline_install {
+        upsert_setting(&state.pool, "offline_install", if offline { "true" } else { "false" }).await?;
+    }
+
+    if let Some(ref time) = body.daily_summary_time {
+        if daily_summary::parse_time(time).is_none() {
+            return Err(AppError::BadRequest("settings.invalid_daily_summary_time".into()));
+        }
+        upsert_setting(&state.pool, "daily_summary_time", time).await?;
+    }
+    if let Some(ref scope) = body.daily_summary_scope {
+        if !daily_summary::SCOPES.contains(&scope.as_str()) {
+            return Err(AppError::BadRequest("settings.invalid_daily_summary_scope".into()));
+        }
+        upsert_setting(&state.pool, "daily_summary_scope", scope).await?;
+    }
+    if let Some(ref url) = body.daily_summary_webhook_url {
+        upsert_setting(&state.pool, "daily_summary_webhook_url", url.trim()).await?;
+    }
+    if let Some(enabled) = body.daily_summary_enabled {
+        upsert_setting(&state.pool, "daily_summary_enabled", if enabled { "true" } else { "false" }).await?;
+    }
+
+    if let Some(limit) = body.max_concurrent_starts {
+        if limit == 0 {
+            return Err(AppError::BadRequest("settings.invalid_max_concurrent_starts".into()));
+        }
+        upsert_setting(&state.pool, "max_concurrent_starts", &limit.to_string()).await?;
+        state.process_manager.set_max_concurrent_starts(limit);
+    }
+
+    if let Some(secs) = body.metrics_interval_secs {
+        if !(1..=60).contains(&secs) {
+            return Err(AppError::BadRequest("settings.invalid_metrics_interval".into()));
+        }
+        upsert_setting(&state.pool, "metrics_interval_secs", &secs.to_string()).await?;
+        state.process_manager.set_metrics_interval(secs);
+    }
+
+    for (key, days) in [
+        ("metrics_retention_days", body.metrics_retention_days),
+        ("events_retention_days", body.events_retention_days),
+        ("audit_retention_days", body.audit_retention_days),
+    ] {
+        if let Some(days) = days {
+            upsert_setting(&state.pool, key, &days.to_string()).await?;
+        }
+    }
+
+    if let Some(enabled) = body.auto_snapshots {
+        upsert_setting(&state.pool, "auto_snapshots", if enabled { "true" } else { "false" }).await?;
+    }
+
+    if body.backup_compression.is_some() || body.backup_compression_level.is_some() {
+        let codec = match body.backup_compression {
+            Some(ref value) => Codec::parse(value).ok_or_else(|| AppError::BadRequest("settings.invalid_backup_compression".into()))?,
+            None => backup_service::configured_compression(&state.pool).await.codec,
+        };
+        if let Some(level) = body.backup_compression_level {
+            if !codec.levels().contains(&level) {
+                return Err(AppError::BadRequest("settings.invalid_backup_compression_level".into()));
+            }
+            upsert_setting(&state.pool, "backup_compression_level", &level.to_string()).await?;
+        } else if body.backup_compression.is_some() {
+            // A level picked for the previous codec means something else for this one
+            sqlx::query("DELETE FROM settings WHERE key = 'backup_compression_level'").execute(&state.pool).await?;
+        }
+        upsert_setting(&state.pool, "backup_compression", codec.as_str()).await?;
+    }
+
+    if let Some(enabled) = body.systemd_scopes {
+        if enabled && !platform::systemd_available() {
+            return Err(AppError::BadRequest("settings.systemd_unavailable".into()));
+        }
+        upsert_setting(&state.pool, "systemd_scopes", if enabled { "true" } else { "false" }).await?;
+        state.process_manager.set_systemd_scopes(enabled);
+    }
+
+    if let Some(users) = body.break_glass_on_call {
+        let value = serde_json::to_string(&users)
+            .map_err(|e| AppError::Internal(format!("Failed to serialize on-call users: {}", e)))?;
+        upsert_setting(&state.pool, "break_glass_on_call", &value).await?;
+    }
+
+    if let Some(patterns) = body.auth_patterns {
+        if let Err(e) = auth_detection::validate(&patterns) {
+            tracing::warn!("Rejected auth patterns: {}", e);
+            return Err(AppError::BadRequest("settings.invalid_auth_patterns".into()));
+        }
+        let value = serde_json::to_string(&patterns)
+            .map_err(|e| AppError::Internal(format!("Failed to serialize auth patterns: {}", e)))?;
+        upsert_setting(&state.pool, "auth_patterns", &value).await?;
+        auth_detection::set(patterns);
+    }
+
+    if let Some(matrix) = body.field_visibility {
+        if !matrix.keys().all(|group| visibility::is_known_group(group)) {
+            return Err(AppError::BadRequest("settings.invalid_field_visibility".into()));
+        }
+        let value = serde_json::to_string(&matrix)
+            .map_err(|e| AppError::Internal(format!("Failed to serialize field visibility: {}", e)))?;
+        upsert_setting(&state.pool, "field_visibility", &value).await?;
+        visibility::set(matrix);
+    }
+
     Ok(Json(serde_json::json!({
         "success": true,
         "message": "Settings updated successfully"
     })))
 }
 
+/// Saved retention of a history table, or its default (0 keeps everything)
+fn retention_setting(settings_map: &std::collections::HashMap<String, String>, key: &str) -> u32 {
+    let default = retention::RULES.iter().find(|r| r.setting == key).map_or(0, |r| r.default_days);
+    settings_map.get(key).and_then(|v| v.parse().ok()).unwrap_or(default)
+}
+
 async fn update_env_file(key: &str, value: &str) -> std::io::Result<()> {
-    use tokio::io::{AsyncReadExt, AsyncWriteExt};
-    
     let env_path = ".env";
-    let mut content = String::new();
-    
-    // If file doesn't exist, create it
-    if tokio::fs::metadata(env_path).await.is_err() {
-         let mut file = tokio::fs::File::create(env_path).await?;
-         file.write_all(b"").await?;
-    }
-
-    if let Ok(mut file) = tokio::fs::File::open(env_path).await {
-        file.read_to_string(&mut content).await?;
-    }
+    let content = match tokio::fs::read_to_string(env_path).await {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+        Err(e) => return Err(e),
+    };
 
     let mut new_lines = Vec::new();
     let mut key_found = false;
@@ -155,9 +692,5 @@ async fn update_env_file(key: &str, value: &str) -> std::io::Result<()> {
         new_lines.push(new_line.as_str());
     }
 
-    let new_content = new_lines.join("\n");
-    let mut file = tokio::fs::File::create(env_path).await?;
-    file.write_all(new_content.as_bytes()).await?;
-    
-    Ok(())
+    atomic_file::write(env_path, new_lines.join("\n")).await
 }
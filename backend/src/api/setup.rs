@@ -1,19 +1,23 @@
+use std::net::SocketAddr;
 use axum::{
     routing::{get, post},
-    extract::State,
+    extract::{ConnectInfo, State},
+    http::HeaderMap,
     Json, Router,
 };
 use serde::{Deserialize, Serialize};
+use utoipa::{OpenApi, ToSchema};
 use crate::AppState;
 use crate::error::AppError;
-use crate::api::auth::AuthResponse;
+use crate::api::auth::{open_session, AuthResponse, UserRow};
+use crate::services::sessions::Client;
 
-#[derive(Serialize)]
+#[derive(Serialize, ToSchema)]
 struct SetupStatusResponse {
     is_setup: bool,
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, ToSchema)]
 struct SetupRequest {
     username: String,
     password: String,
@@ -28,6 +32,11 @@ pub fn routes() -> Router<AppState> {
         .route("/", post(perform_setup))
 }
 
+#[derive(OpenApi)]
+#[openapi(paths(get_setup_status, perform_setup))]
+pub(super) struct ApiDoc;
+
+#[utoipa::path(get, path = "/setup/status", tag = "setup", security(()), responses((status = 200, body = SetupStatusResponse)))]
 async fn get_setup_status(State(state): State<AppState>) -> Result<Json<SetupStatusResponse>, AppError> {
     let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM users")
         .fetch_one(&state.pool)
@@ -38,8 +47,11 @@ async fn get_setup_status(State(state): State<AppState>) -> Result<Json<SetupSta
     }))
 }
 
+#[utoipa::path(post, path = "/setup", tag = "setup", security(()), request_body = SetupRequest, responses((status = 200, body = AuthResponse)))]
 async fn perform_setup(
     State(state): State<AppState>,
+    connect_info: Option<ConnectInfo<SocketAddr>>,
+    headers: HeaderMap,
     Json(body): Json<SetupRequest>,
 ) -> Result<Json<AuthResponse>, AppError> {
     // 1. Check if setup is already done
@@ -48,7 +60,7 @@ async fn perform_setup(
         .await?;
 
     if count > 0 {
-        return Err(AppError::BadRequest("Setup already completed".into()));
+        return Err(AppError::BadRequest("setup.already_completed".into()));
     }
 
     // 2. Create Admin User
@@ -89,30 +101,17 @@ async fn perform_setup(
     upsert_setting(&state.pool, "login_default_color", &body.theme_color).await?;
 
     // 4. Return Login Token (Auto-login)
-    // Generate JWT
-    let secret = std::env::var("JWT_SECRET").unwrap_or_else(|_| "change-me-in-production".into());
-    let expiration = chrono::Utc::now()
-        .checked_add_signed(chrono::Duration::hours(24))
-        .expect("valid timestamp")
-        .timestamp();
-
-    let claims = crate::api::auth::Claims {
-        sub: user_id.clone(),
+    let token = open_session(&state.pool, &UserRow {
+        id: user_id.clone(),
         username: body.username.clone(),
+        password_hash,
         role: "admin".to_string(),
         accent_color: Some(body.theme_color.clone()),
-        exp: expiration,
-    };
-
-    let token = jsonwebtoken::encode(
-        &jsonwebtoken::Header::default(),
-        &claims,
-        &jsonwebtoken::EncodingKey::from_secret(secret.as_bytes()),
-    )
-    .map_err(|e| AppError::Internal(e.to_string()))?;
+    }, false, &Client::from_request(&headers, connect_info.as_ref())).await?;
 
     Ok(Json(AuthResponse {
         token,
+        two_factor_setup_required: false,
         user: crate::api::auth::UserInfo {
             id: user_id,
             username: body.username.clone(),
@@ -1,18 +1,33 @@
 use axum::{
-    routing::get,
-    extract::State,
+    body::Body,
+    routing::{delete, get, post},
+    extract::{Path, Query, State},
+    http::{header, HeaderMap, StatusCode},
+    response::Response,
     Json, Router,
 };
-use serde::Serialize;
-use std::process::Command;
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+use utoipa::{IntoParams, OpenApi, ToSchema};
 use std::sync::Mutex;
 use sysinfo::{Disks, System};
 use walkdir::WalkDir;
 
 use crate::AppState;
+use crate::api::auth::AuthUser;
 use crate::error::AppError;
+use crate::services::announcements::{self, Release};
+use crate::services::audit::{self, AuditEntry};
+use crate::services::db_backup::{self, DbBackup};
+use crate::services::support_bundle::{self, Bundle};
+use crate::utils::http_cache::ResponseCache;
+use crate::utils::interfaces::{self, InterfaceAddress};
+use crate::utils::java::check_java_version;
+use crate::utils::platform;
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct SystemStatsResponse {
     pub cpu: f32,
     pub ram: f32,
@@ -30,10 +45,30 @@ pub struct SystemStatsResponse {
     pub managed_disk: u64,
 }
 
-#[derive(Debug, Serialize, Clone, PartialEq)]
-pub struct JavaVersion {
-    pub path: String,
-    pub version: String,
+#[derive(Debug, Serialize, FromRow, ToSchema)]
+pub struct Announcement {
+    pub id: String,
+    pub title: String,
+    pub body: String,
+    pub level: String,
+    pub created_by: String,
+    pub created_at: String,
+    pub expires_at: Option<String>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct AnnouncementsResponse {
+    pub current_version: String,
+    pub releases: Vec<Release>,
+    pub announcements: Vec<Announcement>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct CreateAnnouncementRequest {
+    pub title: String,
+    pub body: String,
+    pub level: Option<String>,
+    pub expires_at: Option<String>,
 }
 
 // Keep a static System instance for accurate CPU readings
@@ -41,21 +76,274 @@ lazy_static::lazy_static! {
     static ref SYSTEM: Mutex<System> = Mutex::new(System::new_all());
 }
 
+static STATS_CACHE: ResponseCache = ResponseCache::new(std::time::Duration::from_secs(2));
+// Installed JDKs rarely change, probing them spawns one process per candidate
+static JAVA_VERSIONS_CACHE: ResponseCache = ResponseCache::new(std::time::Duration::from_secs(300));
+
 pub fn routes() -> Router<AppState> {
     Router::new()
         .route("/stats", get(get_system_stats))
         .route("/java-versions", get(get_java_versions))
+        .route("/interfaces", get(get_interfaces))
+        .route("/announcements", get(list_announcements).post(create_announcement))
+        .route("/announcements/:id", delete(delete_announcement))
+        .route("/support-bundle", post(create_support_bundle))
+        .route("/support-bundle/:id", get(get_support_bundle))
+        .route("/support-bundle/:id/download", get(download_support_bundle))
+        .route("/database-backups", get(list_database_backups).post(create_database_backup))
+        .route("/database-backups/:name/restore", post(restore_database_backup))
+        .route("/audit-log", get(list_audit_log))
+}
+
+#[derive(OpenApi)]
+#[openapi(paths(
+    list_announcements,
+    create_announcement,
+    delete_announcement,
+    create_support_bundle,
+    get_support_bundle,
+    download_support_bundle,
+    list_audit_log,
+    list_database_backups,
+    create_database_backup,
+    restore_database_backup,
+    get_interfaces,
+    get_java_versions,
+    get_system_stats,
+))]
+pub(super) struct ApiDoc;
+
+#[utoipa::path(get, path = "/system/announcements", tag = "system", responses((status = 200, body = AnnouncementsResponse)))]
+async fn list_announcements(State(state): State<AppState>) -> Result<Json<AnnouncementsResponse>, AppError> {
+    let now = Utc::now().to_rfc3339();
+    let local: Vec<Announcement> = sqlx::query_as(
+        "SELECT * FROM announcements WHERE expires_at IS NULL OR expires_at > ? ORDER BY created_at DESC"
+    )
+    .bind(&now)
+    .fetch_all(&state.pool)
+    .await?;
+
+    Ok(Json(AnnouncementsResponse {
+        current_version: env!("CARGO_PKG_VERSION").to_string(),
+        releases: announcements::releases().await,
+        announcements: local,
+    }))
+}
+
+#[utoipa::path(post, path = "/system/announcements", tag = "system", request_body = CreateAnnouncementRequest, responses((status = 201, body = Announcement)))]
+async fn create_announcement(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Json(body): Json<CreateAnnouncementRequest>,
+) -> Result<(StatusCode, Json<Announcement>), AppError> {
+    auth.require_admin()?;
+
+    if body.title.trim().is_empty() || body.body.trim().is_empty() {
+        return Err(AppError::BadRequest("announcements.title_and_body_required".into()));
+    }
+    let level = body.level.clone().unwrap_or_else(|| "info".to_string());
+    if !announcements::LEVELS.contains(&level.as_str()) {
+        return Err(AppError::BadRequest("announcements.invalid_level".into()));
+    }
+    let expires_at = match body.expires_at.as_deref().filter(|e| !e.is_empty()) {
+        Some(raw) => Some(
+            chrono::DateTime::parse_from_rfc3339(raw)
+                .map_err(|_| AppError::BadRequest("announcements.invalid_expires_at".into()))?
+                .with_timezone(&Utc)
+                .to_rfc3339(),
+        ),
+        None => None,
+    };
+
+    let announcement = Announcement {
+        id: Uuid::new_v4().to_string(),
+        title: body.title.trim().to_string(),
+        body: body.body,
+        level,
+        created_by: auth.username,
+        created_at: Utc::now().to_rfc3339(),
+        expires_at,
+    };
+
+    sqlx::query(
+        "INSERT INTO announcements (id, title, body, level, created_by, created_at, expires_at) VALUES (?, ?, ?, ?, ?, ?, ?)"
+    )
+    .bind(&announcement.id)
+    .bind(&announcement.title)
+    .bind(&announcement.body)
+    .bind(&announcement.level)
+    .bind(&announcement.created_by)
+    .bind(&announcement.created_at)
+    .bind(&announcement.expires_at)
+    .execute(&state.pool)
+    .await?;
+
+    Ok((StatusCode::CREATED, Json(announcement)))
+}
+
+#[utoipa::path(delete, path = "/system/announcements/{id}", tag = "system", params(("id" = String, Path)), responses((status = 200, body = serde_json::Value)))]
+async fn delete_announcement(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(id): Path<String>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    auth.require_admin()?;
+
+    let result = sqlx::query("DELETE FROM announcements WHERE id = ?")
+        .bind(&id)
+        .execute(&state.pool)
+        .await?;
+
+    if result.rows_affected() == 0 {
+        return Err(AppError::NotFound("announcements.not_found".into()));
+    }
+
+    Ok(Json(serde_json::json!({
+        "success": true,
+        "message": "announcements.delete_success"
+    })))
+}
+
+
+/// Start building a diagnostics archive for a bug report; poll the returned bundle until it is
+/// `ready`, then fetch `download_url`
+#[utoipa::path(post, path = "/system/support-bundle", tag = "system", responses((status = 202, body = serde_json::Value)))]
+async fn create_support_bundle(
+    State(state): State<AppState>,
+    auth: AuthUser,
+) -> Result<(StatusCode, Json<serde_json::Value>), AppError> {
+    auth.require_admin()?;
+    let bundle = support_bundle::start(state.pool.clone(), state.process_manager.clone(), (*state.settings).clone());
+    Ok((StatusCode::ACCEPTED, Json(bundle_json(&bundle))))
+}
+
+#[utoipa::path(get, path = "/system/support-bundle/{id}", tag = "system", params(("id" = String, Path)), responses((status = 200, body = serde_json::Value)))]
+async fn get_support_bundle(
+    auth: AuthUser,
+    Path(id): Path<String>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    auth.require_admin()?;
+    let bundle = support_bundle::get(&id).ok_or_else(|| AppError::NotFound("system.support_bundle_not_found".into()))?;
+    Ok(Json(bundle_json(&bundle)))
 }
 
-async fn get_java_versions() -> Result<Json<Vec<JavaVersion>>, AppError> {
+#[utoipa::path(get, path = "/system/support-bundle/{id}/download", tag = "system", params(("id" = String, Path)), responses((status = 200, content_type = "application/gzip", body = Vec<u8>)))]
+async fn download_support_bundle(
+    auth: AuthUser,
+    Path(id): Path<String>,
+) -> Result<Response, AppError> {
+    auth.require_admin()?;
+    let bundle = support_bundle::get(&id).ok_or_else(|| AppError::NotFound("system.support_bundle_not_found".into()))?;
+    if bundle.status != support_bundle::STATUS_READY {
+        return Err(AppError::BadRequest("system.support_bundle_not_ready".into()));
+    }
+    let data = tokio::fs::read(&bundle.path).await
+        .map_err(|_| AppError::NotFound("system.support_bundle_not_found".into()))?;
+    let filename = bundle.path.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+
+    Response::builder()
+        .header(header::CONTENT_TYPE, "application/gzip")
+        .header(header::CONTENT_DISPOSITION, format!("attachment; filename=\"{}\"", filename))
+        .body(Body::from(data))
+        .map_err(|e| AppError::Internal(e.to_string()))
+}
+
+fn bundle_json(bundle: &Bundle) -> serde_json::Value {
+    let mut value = serde_json::json!(bundle);
+    if bundle.status == support_bundle::STATUS_READY {
+        value["download_url"] = serde_json::json!(format!("/api/v1/system/support-bundle/{}/download", bundle.id));
+    }
+    value
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+struct AuditLogQuery {
+    limit: Option<u32>,
+}
+
+/// Latest panel-wide audit entries (login lockouts, ...), newest first
+#[utoipa::path(get, path = "/system/audit-log", tag = "system", params(AuditLogQuery), responses((status = 200, body = [AuditEntry])))]
+async fn list_audit_log(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Query(query): Query<AuditLogQuery>,
+) -> Result<Json<Vec<AuditEntry>>, AppError> {
+    auth.require_admin()?;
+    let limit = query.limit.unwrap_or(100).clamp(1, 1000);
+    Ok(Json(audit::recent(&state.pool, limit).await?))
+}
+
+/// Copies of the panel database, newest first
+#[utoipa::path(get, path = "/system/database-backups", tag = "system", responses((status = 200, body = [DbBackup])))]
+async fn list_database_backups(State(state): State<AppState>, auth: AuthUser) -> Result<Json<Vec<DbBackup>>, AppError> {
+    auth.require_admin()?;
+    let backups_dir = state.settings.backups_dir.clone();
+    let backups = tokio::task::spawn_blocking(move || db_backup::list(&backups_dir))
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))?;
+    Ok(Json(backups))
+}
+
+/// Copy the panel database into `panel/` of the backups directory now, besides the scheduled copies
+#[utoipa::path(post, path = "/system/database-backups", tag = "system", responses((status = 201, body = DbBackup)))]
+async fn create_database_backup(
+    State(state): State<AppState>,
+    auth: AuthUser,
+) -> Result<(StatusCode, Json<DbBackup>), AppError> {
+    auth.require_admin()?;
+    let keep: Option<String> = sqlx::query_scalar("SELECT value FROM settings WHERE key = 'db_backup_keep'")
+        .fetch_optional(&state.pool)
+        .await?;
+    let keep = keep.and_then(|k| k.parse().ok()).unwrap_or(db_backup::DEFAULT_KEEP);
+    let backup = db_backup::create(&state.pool, &state.settings.backups_dir, keep).await
+        .map_err(|e| AppError::Internal(format!("Database backup failed: {}", e)))?;
+    Ok((StatusCode::CREATED, Json(backup)))
+}
+
+/// Stage a database backup; it replaces the live database when the panel restarts
+#[utoipa::path(post, path = "/system/database-backups/{name}/restore", tag = "system", params(("name" = String, Path)), responses((status = 200, body = serde_json::Value)))]
+async fn restore_database_backup(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(name): Path<String>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    auth.require_admin()?;
+    if !db_backup::is_valid_name(&name) || !db_backup::list(&state.settings.backups_dir).iter().any(|b| b.name == name) {
+        return Err(AppError::NotFound("system.database_backup_not_found".into()));
+    }
+    db_backup::stage_restore(&state.settings.database_url, &state.settings.backups_dir, &name).await.map_err(|e| {
+        tracing::warn!("Failed to stage database backup {}: {}", name, e);
+        AppError::BadRequest("system.database_restore_failed".into())
+    })?;
+
+    Ok(Json(serde_json::json!({
+        "success": true,
+        "restart_required": true,
+        "message": "system.database_restore_staged",
+    })))
+}
+
+/// Addresses a server can bind to, for the bind address picker
+#[utoipa::path(get, path = "/system/interfaces", tag = "system", responses((status = 200, body = [InterfaceAddress])))]
+async fn get_interfaces() -> Json<Vec<InterfaceAddress>> {
+    Json(interfaces::list())
+}
+
+#[utoipa::path(get, path = "/system/java-versions", tag = "system", responses((status = 200, body = [serde_json::Value])))]
+async fn get_java_versions(headers: HeaderMap) -> Result<Response, AppError> {
+    if let Some(cached) = JAVA_VERSIONS_CACHE.get() {
+        return Ok(cached.respond(&headers));
+    }
+
     let mut versions = Vec::new();
     let mut checked_paths = std::collections::HashSet::new();
 
     // 1. Check JAVA_HOME
     if let Ok(java_home) = std::env::var("JAVA_HOME") {
-        let java_bin = std::path::Path::new(&java_home).join("bin").join("java");
+        let java_bin = std::path::Path::new(&java_home).join("bin").join(platform::JAVA_BINARY);
         if java_bin.exists() {
-            if let Some(v) = check_java_version(&java_bin) {
+            if let Some(v) = check_java_version(&java_bin).await {
                 if checked_paths.insert(java_bin.to_string_lossy().to_string()) {
                     versions.push(v);
                 }
@@ -66,13 +354,13 @@ async fn get_java_versions() -> Result<Json<Vec<JavaVersion>>, AppError> {
     // 2. Check PATH (via "java" command)
     if let Ok(path_var) = std::env::var("PATH") {
         for path in std::env::split_paths(&path_var) {
-            let java_bin = path.join("java");
+            let java_bin = path.join(platform::JAVA_BINARY);
             if java_bin.exists() {
                 // Resolve symlink to get real path
                 let real_path = std::fs::canonicalize(&java_bin).unwrap_or(java_bin.clone());
                 
                 if !checked_paths.contains(&real_path.to_string_lossy().to_string()) {
-                    if let Some(v) = check_java_version(&real_path) {
+                    if let Some(v) = check_java_version(&real_path).await {
                         checked_paths.insert(real_path.to_string_lossy().to_string());
                         versions.push(v);
                     }
@@ -103,7 +391,7 @@ async fn get_java_versions() -> Result<Json<Vec<JavaVersion>>, AppError> {
                         let real_path = std::fs::canonicalize(java_path).unwrap_or(java_path.to_path_buf());
                          
                         if !checked_paths.contains(&real_path.to_string_lossy().to_string()) {
-                            if let Some(v) = check_java_version(&real_path) {
+                            if let Some(v) = check_java_version(&real_path).await {
                                 checked_paths.insert(real_path.to_string_lossy().to_string());
                                 versions.push(v);
                             }
@@ -114,36 +402,15 @@ async fn get_java_versions() -> Result<Json<Vec<JavaVersion>>, AppError> {
         }
     }
 
-    Ok(Json(versions))
-}
-
-fn check_java_version(path: &std::path::Path) -> Option<JavaVersion> {
-    let output = Command::new(path)
-        .arg("-version")
-        .output()
-        .ok()?;
-    
-    // Java version info is often in stderr
-    let output_str = String::from_utf8_lossy(&output.stderr);
-    
-    // Parse version from string like: "openjdk version \"17.0.8\" 2023-07-18"
-    // or "java version \"1.8.0_381\""
-    for line in output_str.lines() {
-        if line.contains("version") {
-            let parts: Vec<&str> = line.split('"').collect();
-            if parts.len() >= 2 {
-                return Some(JavaVersion {
-                    path: path.to_string_lossy().to_string(),
-                    version: parts[1].to_string(),
-                });
-            }
-        }
-    }
-    
-    None
+    Ok(JAVA_VERSIONS_CACHE.store(&versions)?.respond(&headers))
 }
 
-async fn get_system_stats(State(state): State<AppState>) -> Result<Json<SystemStatsResponse>, AppError> {
+#[utoipa::path(get, path = "/system/stats", tag = "system", responses((status = 200, body = SystemStatsResponse)))]
+async fn get_system_stats(State(state): State<AppState>, headers: HeaderMap) -> Result<Response, AppError> {
+    if let Some(cached) = STATS_CACHE.get() {
+        return Ok(cached.respond(&headers));
+    }
+
     let pm = &state.process_manager;
     let (cpu_usage, ram_percent, ram_used, ram_total) = {
         let mut sys = SYSTEM.lock().unwrap();
@@ -209,12 +476,12 @@ async fn get_system_stats(State(state): State<AppState>) -> Result<Json<SystemSt
 
     let procs = pm.get_processes_read_guard().await;
     for proc in procs.values() {
-        managed_cpu += *proc.last_cpu.read().unwrap();
-        managed_ram += *proc.last_memory.read().unwrap();
-        managed_disk += *proc.last_disk.read().unwrap();
+        managed_cpu += *proc.last_cpu.read().await;
+        managed_ram += *proc.last_memory.read().await;
+        managed_disk += *proc.last_disk.read().await;
     }
 
-    Ok(Json(SystemStatsResponse {
+    let stats = SystemStatsResponse {
         cpu: cpu_usage,
         ram: ram_percent,
         ram_used,
@@ -229,5 +496,7 @@ async fn get_system_stats(State(state): State<AppState>) -> Result<Json<SystemSt
         managed_cpu_normalized: if cpu_cores > 0 { managed_cpu / cpu_cores as f32 } else { 0.0 },
         managed_ram,
         managed_disk,
-    }))
+    };
+
+    Ok(STATS_CACHE.store(&stats)?.respond(&headers))
 }
@@ -0,0 +1,436 @@
+//! Personal API tokens for scripts and bots: scoped to read-only or read/write access, rate
+//! limited per token, with a per-day report of the endpoints they hit.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use axum::{
+    extract::{MatchedPath, Path, Query, Request, State},
+    http::{header, Method, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    routing::{get, patch},
+    Json, Router,
+};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use sqlx::FromRow;
+use uuid::Uuid;
+use utoipa::{IntoParams, OpenApi, ToSchema};
+
+use crate::api::auth::AuthUser;
+use crate::db::DbPool;
+use crate::error::AppError;
+use crate::AppState;
+
+/// Distinguishes API tokens from session JWTs in the `Authorization` header
+pub const TOKEN_PREFIX: &str = "dmk_";
+
+pub const SCOPE_READ: &str = "read";
+pub const SCOPE_WRITE: &str = "write";
+
+const DEFAULT_RATE_LIMIT: u32 = 60;
+const MAX_RATE_LIMIT: u32 = 6000;
+const RATE_WINDOW: Duration = Duration::from_secs(60);
+
+/// Days covered by the usage report unless `days` is given
+const DEFAULT_REPORT_DAYS: u32 = 30;
+
+lazy_static::lazy_static! {
+    /// Token id -> start of its current window and requests counted in it
+    static ref WINDOWS: Mutex<HashMap<String, (Instant, u32)>> = Mutex::new(HashMap::new());
+}
+
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/", get(list_tokens).post(create_token))
+        .route("/:id", patch(update_token).delete(revoke_token))
+        .route("/:id/usage", get(token_usage))
+}
+
+#[derive(OpenApi)]
+#[openapi(paths(list_tokens, create_token, update_token, revoke_token, token_usage))]
+pub(super) struct ApiDoc;
+
+#[derive(Debug, FromRow)]
+struct TokenRow {
+    id: String,
+    user_id: String,
+    name: String,
+    scopes: String,
+    rate_limit_per_minute: i64,
+    created_at: String,
+    last_used_at: Option<String>,
+    revoked_at: Option<String>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct TokenResponse {
+    pub id: String,
+    pub name: String,
+    pub scopes: Vec<String>,
+    pub rate_limit_per_minute: u32,
+    pub created_at: String,
+    pub last_used_at: Option<String>,
+    pub revoked_at: Option<String>,
+    /// Only returned once, when the token is created
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub token: Option<String>,
+}
+
+impl From<TokenRow> for TokenResponse {
+    fn from(row: TokenRow) -> Self {
+        Self {
+            id: row.id,
+            name: row.name,
+            scopes: row.scopes.split(',').map(str::to_string).collect(),
+            rate_limit_per_minute: row.rate_limit_per_minute.max(0) as u32,
+            created_at: row.created_at,
+            last_used_at: row.last_used_at,
+            revoked_at: row.revoked_at,
+            token: None,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct CreateTokenRequest {
+    pub name: String,
+    /// `["read"]` (default) or `["read", "write"]`
+    pub scopes: Option<Vec<String>>,
+    pub rate_limit_per_minute: Option<u32>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct UpdateTokenRequest {
+    pub name: Option<String>,
+    pub scopes: Option<Vec<String>>,
+    pub rate_limit_per_minute: Option<u32>,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct UsageQuery {
+    pub days: Option<u32>,
+}
+
+#[derive(Debug, Serialize, FromRow, ToSchema)]
+pub struct DailyUsage {
+    pub day: String,
+    pub requests: i64,
+    pub rejected: i64,
+}
+
+#[derive(Debug, Serialize, FromRow, ToSchema)]
+pub struct EndpointUsage {
+    pub method: String,
+    pub endpoint: String,
+    pub requests: i64,
+    pub rejected: i64,
+    pub last_day: String,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct UsageReport {
+    pub token_id: String,
+    pub last_used_at: Option<String>,
+    pub days: u32,
+    pub requests_per_day: Vec<DailyUsage>,
+    pub endpoints: Vec<EndpointUsage>,
+}
+
+fn hash_token(token: &str) -> String {
+    hex::encode(Sha256::digest(token.as_bytes()))
+}
+
+/// Normalize requested scopes to the stored form ("read" or "read,write")
+fn parse_scopes(scopes: Option<&[String]>) -> Result<String, AppError> {
+    let Some(scopes) = scopes else {
+        return Ok(SCOPE_READ.to_string());
+    };
+    if scopes.iter().any(|s| s != SCOPE_READ && s != SCOPE_WRITE) {
+        return Err(AppError::BadRequest("tokens.invalid_scope".into()));
+    }
+    Ok(if scopes.iter().any(|s| s == SCOPE_WRITE) {
+        format!("{},{}", SCOPE_READ, SCOPE_WRITE)
+    } else {
+        SCOPE_READ.to_string()
+    })
+}
+
+fn validate_rate_limit(limit: u32) -> Result<(), AppError> {
+    if limit == 0 || limit > MAX_RATE_LIMIT {
+        return Err(AppError::BadRequest("tokens.invalid_rate_limit".into()));
+    }
+    Ok(())
+}
+
+/// Tokens are managed from a panel session only, a token can't mint or widen tokens
+fn require_session(auth: &AuthUser) -> Result<(), AppError> {
+    if auth.api_token_id.is_some() {
+        return Err(AppError::Unauthorized("tokens.session_required".into()));
+    }
+    Ok(())
+}
+
+async fn owned_token(pool: &DbPool, id: &str, auth: &AuthUser) -> Result<TokenRow, AppError> {
+    let token: TokenRow = sqlx::query_as("SELECT * FROM api_tokens WHERE id = ?")
+        .bind(id)
+        .fetch_optional(pool)
+        .await?
+        .ok_or_else(|| AppError::NotFound("tokens.not_found".into()))?;
+    if token.user_id != auth.id && !auth.is_admin() {
+        return Err(AppError::NotFound("tokens.not_found".into()));
+    }
+    Ok(token)
+}
+
+#[utoipa::path(get, path = "/tokens", tag = "tokens", responses((status = 200, body = [TokenResponse])))]
+async fn list_tokens(
+    State(state): State<AppState>,
+    auth: AuthUser,
+) -> Result<Json<Vec<TokenResponse>>, AppError> {
+    require_session(&auth)?;
+    let tokens: Vec<TokenRow> = sqlx::query_as("SELECT * FROM api_tokens WHERE user_id = ? ORDER BY created_at DESC")
+        .bind(&auth.id)
+        .fetch_all(&state.pool)
+        .await?;
+    Ok(Json(tokens.into_iter().map(TokenResponse::from).collect()))
+}
+
+#[utoipa::path(post, path = "/tokens", tag = "tokens", request_body = CreateTokenRequest, responses((status = 201, body = TokenResponse)))]
+async fn create_token(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Json(body): Json<CreateTokenRequest>,
+) -> Result<(StatusCode, Json<TokenResponse>), AppError> {
+    require_session(&auth)?;
+    let name = body.name.trim();
+    if name.is_empty() {
+        return Err(AppError::BadRequest("tokens.name_required".into()));
+    }
+    let scopes = parse_scopes(body.scopes.as_deref())?;
+    let rate_limit = body.rate_limit_per_minute.unwrap_or(DEFAULT_RATE_LIMIT);
+    validate_rate_limit(rate_limit)?;
+
+    let id = Uuid::new_v4().to_string();
+    let secret = format!("{}{}{}", TOKEN_PREFIX, Uuid::new_v4().simple(), Uuid::new_v4().simple());
+    let now = Utc::now().to_rfc3339();
+
+    sqlx::query(
+        "INSERT INTO api_tokens (id, user_id, name, token_hash, scopes, rate_limit_per_minute, created_at) VALUES (?, ?, ?, ?, ?, ?, ?)",
+    )
+    .bind(&id)
+    .bind(&auth.id)
+    .bind(name)
+    .bind(hash_token(&secret))
+    .bind(&scopes)
+    .bind(rate_limit as i64)
+    .bind(&now)
+    .execute(&state.pool)
+    .await?;
+
+    let mut response = TokenResponse::from(owned_token(&state.pool, &id, &auth).await?);
+    response.token = Some(secret);
+    Ok((StatusCode::CREATED, Json(response)))
+}
+
+#[utoipa::path(patch, path = "/tokens/{id}", tag = "tokens", params(("id" = String, Path)), request_body = UpdateTokenRequest, responses((status = 200, body = TokenResponse)))]
+async fn update_token(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(id): Path<String>,
+    Json(body): Json<UpdateTokenRequest>,
+) -> Result<Json<TokenResponse>, AppError> {
+    require_session(&auth)?;
+    owned_token(&state.pool, &id, &auth).await?;
+
+    let scopes = match body.scopes.as_deref() {
+        Some(scopes) => Some(parse_scopes(Some(scopes))?),
+        None => None,
+    };
+    if let Some(limit) = body.rate_limit_per_minute {
+        validate_rate_limit(limit)?;
+    }
+    let name = body.name.as_deref().map(str::trim).filter(|n| !n.is_empty());
+
+    sqlx::query(
+        "UPDATE api_tokens SET name = COALESCE(?, name), scopes = COALESCE(?, scopes),
+         rate_limit_per_minute = COALESCE(?, rate_limit_per_minute) WHERE id = ?",
+    )
+    .bind(name)
+    .bind(scopes)
+    .bind(body.rate_limit_per_minute.map(|l| l as i64))
+    .bind(&id)
+    .execute(&state.pool)
+    .await?;
+
+    Ok(Json(owned_token(&state.pool, &id, &auth).await?.into()))
+}
+
+/// Revoked tokens are kept so their usage report stays available
+#[utoipa::path(delete, path = "/tokens/{id}", tag = "tokens", params(("id" = String, Path)), responses((status = 200, body = serde_json::Value)))]
+async fn revoke_token(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(id): Path<String>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    require_session(&auth)?;
+    owned_token(&state.pool, &id, &auth).await?;
+
+    sqlx::query("UPDATE api_tokens SET revoked_at = COALESCE(revoked_at, ?) WHERE id = ?")
+        .bind(Utc::now().to_rfc3339())
+        .bind(&id)
+        .execute(&state.pool)
+        .await?;
+    if let Ok(mut windows) = WINDOWS.lock() {
+        windows.remove(&id);
+    }
+
+    Ok(Json(serde_json::json!({ "success": true })))
+}
+
+#[utoipa::path(get, path = "/tokens/{id}/usage", tag = "tokens", params(("id" = String, Path), UsageQuery), responses((status = 200, body = UsageReport)))]
+async fn token_usage(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(id): Path<String>,
+    Query(query): Query<UsageQuery>,
+) -> Result<Json<UsageReport>, AppError> {
+    require_session(&auth)?;
+    let token = owned_token(&state.pool, &id, &auth).await?;
+
+    let days = query.days.unwrap_or(DEFAULT_REPORT_DAYS).clamp(1, 365);
+    let since = (Utc::now() - chrono::Duration::days(days as i64 - 1)).format("%Y-%m-%d").to_string();
+
+    let requests_per_day: Vec<DailyUsage> = sqlx::query_as(
+        "SELECT day, SUM(requests) AS requests, SUM(rejected) AS rejected FROM api_token_usage
+         WHERE token_id = ? AND day >= ? GROUP BY day ORDER BY day",
+    )
+    .bind(&id)
+    .bind(&since)
+    .fetch_all(&state.pool)
+    .await?;
+
+    let endpoints: Vec<EndpointUsage> = sqlx::query_as(
+        "SELECT method, endpoint, SUM(requests) AS requests, SUM(rejected) AS rejected, MAX(day) AS last_day
+         FROM api_token_usage WHERE token_id = ? AND day >= ?
+         GROUP BY method, endpoint ORDER BY requests DESC",
+    )
+    .bind(&id)
+    .bind(&since)
+    .fetch_all(&state.pool)
+    .await?;
+
+    Ok(Json(UsageReport {
+        token_id: token.id,
+        last_used_at: token.last_used_at,
+        days,
+        requests_per_day,
+        endpoints,
+    }))
+}
+
+/// Count a request against the token's per-minute budget
+fn within_rate_limit(token_id: &str, limit: u32) -> bool {
+    let Ok(mut windows) = WINDOWS.lock() else {
+        return true;
+    };
+    let window = windows.entry(token_id.to_string()).or_insert((Instant::now(), 0));
+    if window.0.elapsed() >= RATE_WINDOW {
+        *window = (Instant::now(), 0);
+    }
+    window.1 += 1;
+    window.1 <= limit
+}
+
+async fn record_usage(pool: &DbPool, token_id: &str, method: &str, endpoint: &str, rejected: bool) {
+    let now = Utc::now();
+    let result = sqlx::query(
+        "INSERT INTO api_token_usage (token_id, day, method, endpoint, requests, rejected) VALUES (?, ?, ?, ?, 1, ?)
+         ON CONFLICT(token_id, day, method, endpoint) DO UPDATE SET
+         requests = requests + 1, rejected = rejected + excluded.rejected",
+    )
+    .bind(token_id)
+    .bind(now.format("%Y-%m-%d").to_string())
+    .bind(method)
+    .bind(endpoint)
+    .bind(rejected as i64)
+    .execute(pool)
+    .await;
+    if let Err(e) = result {
+        tracing::warn!("Failed to record usage of API token {}: {}", token_id, e);
+    }
+
+    let _ = sqlx::query("UPDATE api_tokens SET last_used_at = ? WHERE id = ?")
+        .bind(now.to_rfc3339())
+        .bind(token_id)
+        .execute(pool)
+        .await;
+}
+
+/// Token id, scopes and rate limit, then its owner's id, username, role and accent color
+type BearerRow = (String, String, i64, String, String, String, Option<String>);
+
+/// Middleware resolving `Authorization: Bearer dmk_...` headers: checks the token, its scope
+/// against the method and its rate limit, then hands the owner to `AuthUser` and records the hit
+pub async fn authenticate(State(state): State<AppState>, mut req: Request, next: Next) -> Response {
+    let Some(secret) = req.headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|h| h.to_str().ok())
+        .and_then(|h| h.strip_prefix("Bearer "))
+        .filter(|t| t.starts_with(TOKEN_PREFIX))
+        .map(str::to_string)
+    else {
+        return next.run(req).await;
+    };
+
+    let row: Option<BearerRow> = match sqlx::query_as(
+        "SELECT t.id, t.scopes, t.rate_limit_per_minute, u.id, u.username, u.role, u.accent_color
+         FROM api_tokens t JOIN users u ON u.id = t.user_id
+         WHERE t.token_hash = ? AND t.revoked_at IS NULL AND u.is_active = 1",
+    )
+    .bind(hash_token(&secret))
+    .fetch_optional(&state.pool)
+    .await
+    {
+        Ok(row) => row,
+        Err(e) => return AppError::from(e).into_response(),
+    };
+    let Some((token_id, scopes, rate_limit, user_id, username, role, accent_color)) = row else {
+        return AppError::Unauthorized("auth.invalid_api_token".into()).into_response();
+    };
+
+    let method = req.method().clone();
+    let endpoint = req.extensions()
+        .get::<MatchedPath>()
+        .map(|p| p.as_str().to_string())
+        .unwrap_or_else(|| req.uri().path().to_string());
+
+    let read_only = matches!(method, Method::GET | Method::HEAD | Method::OPTIONS);
+    let rejection = if !read_only && !scopes.split(',').any(|s| s == SCOPE_WRITE) {
+        Some(AppError::Unauthorized("tokens.write_scope_required".into()))
+    } else if !within_rate_limit(&token_id, rate_limit.max(0) as u32) {
+        Some(AppError::TooManyRequests("tokens.rate_limited".into()))
+    } else {
+        None
+    };
+    record_usage(&state.pool, &token_id, method.as_str(), &endpoint, rejection.is_some()).await;
+    if let Some(rejection) = rejection {
+        return rejection.into_response();
+    }
+
+    // Token requests act with the owner's identity
+    req.extensions_mut().insert(AuthUser {
+        id: user_id,
+        username,
+        role,
+        accent_color,
+        api_token_id: Some(token_id),
+        two_factor_setup_required: false,
+        session_id: None,
+    });
+    next.run(req).await
+}
@@ -6,12 +6,22 @@ use axum::{
 use uuid::Uuid;
 use std::io::Write;
 use crate::{AppState, error::AppError};
+use utoipa::OpenApi;
 
 pub fn routes() -> Router<AppState> {
     Router::new()
         .route("/image", post(upload_image))
 }
 
+#[derive(OpenApi)]
+#[openapi(paths(upload_image))]
+pub(super) struct ApiDoc;
+
+#[utoipa::path(
+    post, path = "/upload/image", tag = "system",
+    request_body(content = Vec<u8>, content_type = "multipart/form-data", description = "The image in a `file` field"),
+    responses((status = 200, body = serde_json::Value))
+)]
 async fn upload_image(mut multipart: Multipart) -> Result<Json<serde_json::Value>, AppError> {
     // Create uploads directory if it doesn't exist
     let upload_dir_str = std::env::var("UPLOADS_DIR").unwrap_or_else(|_| "./data/uploads".into());
@@ -22,7 +32,7 @@ async fn upload_image(mut multipart: Multipart) -> Result<Json<serde_json::Value
     }
 
     // Process the multipart form
-    while let Some(field) = multipart.next_field().await.map_err(|e| AppError::Internal(format!("Multipart error: {}", e)))? {
+    while let Some(field) = multipart.next_field().await.map_err(|e| AppError::BadRequest(format!("upload.invalid_multipart: {}", e)))? {
         
         // Get content type and validate it's an image
         let content_type = field.content_type().map(|ct| ct.to_string());
@@ -36,7 +46,7 @@ async fn upload_image(mut multipart: Multipart) -> Result<Json<serde_json::Value
             // Let's assume we want to stop if it's not an image, or just find the one that is.
             // Original code: check item, if not image return error.
             if field.name() == Some("file") || field.name().is_some() {
-                 return Err(AppError::BadRequest("Only image files are allowed".into()));
+                 return Err(AppError::BadRequest("upload.image_required".into()));
             }
             continue; 
         }
@@ -54,7 +64,7 @@ async fn upload_image(mut multipart: Multipart) -> Result<Json<serde_json::Value
         let filepath = upload_dir.join(&filename);
 
         // Read data
-        let data = field.bytes().await.map_err(|e| AppError::Internal(format!("Failed to read chunk: {}", e)))?;
+        let data = field.bytes().await.map_err(|e| AppError::BadRequest(format!("upload.invalid_multipart: {}", e)))?;
 
         // Write file
         let mut file = std::fs::File::create(&filepath)
@@ -72,5 +82,5 @@ async fn upload_image(mut multipart: Multipart) -> Result<Json<serde_json::Value
         })));
     }
 
-    Err(AppError::BadRequest("No file uploaded".into()))
+    Err(AppError::BadRequest("upload.file_required".into()))
 }
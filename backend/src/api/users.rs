@@ -1,6 +1,6 @@
 use axum::{
-    routing::get,
-    extract::{Path, State},
+    routing::{get, post},
+    extract::{Path, Query, State},
     Json, Router,
     http::StatusCode,
 };
@@ -8,20 +8,38 @@ use chrono::Utc;
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
 use uuid::Uuid;
+use utoipa::{IntoParams, OpenApi, ToSchema};
 
 use crate::AppState;
+use crate::api::auth::AuthUser;
 use crate::error::AppError;
+use crate::services::{audit, password_reset, server_access, sessions};
 
 pub fn routes() -> Router<AppState> {
     Router::new()
         .route("/", get(list_users).post(create_user))
         .route("/:id", get(get_user).put(update_user).delete(delete_user))
+        .route("/:id/password-reset", post(create_password_reset))
+        .route("/:id/activity", get(get_activity))
 }
 
+#[derive(OpenApi)]
+#[openapi(paths(
+    list_users,
+    get_user,
+    create_user,
+    update_user,
+    delete_user,
+    create_password_reset,
+    get_activity,
+))]
+pub(super) struct ApiDoc;
+
 #[derive(Debug, Serialize, FromRow)]
 pub struct UserResponse {
     pub id: String,
     pub username: String,
+    pub email: Option<String>,
     pub role: String,
     pub is_active: bool,
     pub language: String,
@@ -33,10 +51,12 @@ pub struct UserResponse {
     pub allocated_servers: Option<String>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct CreateUserRequest {
     pub username: String,
     pub password: String,
+    /// Where password reset links are emailed
+    pub email: Option<String>,
     pub role: Option<String>,
     pub is_active: Option<bool>,
     pub language: Option<String>,
@@ -44,10 +64,12 @@ pub struct CreateUserRequest {
     pub allocated_servers: Option<Vec<String>>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct UpdateUserRequest {
     pub username: Option<String>,
     pub password: Option<String>,
+    /// Empty to remove it
+    pub email: Option<String>,
     pub role: Option<String>,
     pub is_active: Option<bool>,
     pub language: Option<String>,
@@ -55,9 +77,10 @@ pub struct UpdateUserRequest {
     pub allocated_servers: Option<Vec<String>>,
 }
 
+#[utoipa::path(get, path = "/users", tag = "users", responses((status = 200, body = [serde_json::Value])))]
 async fn list_users(State(state): State<AppState>) -> Result<Json<Vec<serde_json::Value>>, AppError> {
     let users: Vec<UserResponse> = sqlx::query_as(
-        r#"SELECT id, username, role, 
+        r#"SELECT id, username, email, role, 
            COALESCE(is_active, 1) as is_active,
            COALESCE(language, 'fr') as language,
            COALESCE(accent_color, '#3A82F6') as accent_color,
@@ -80,6 +103,7 @@ async fn list_users(State(state): State<AppState>) -> Result<Json<Vec<serde_json
             serde_json::json!({
                 "id": user.id,
                 "username": user.username,
+                "email": user.email,
                 "role": user.role,
                 "is_active": user.is_active,
                 "language": user.language,
@@ -96,12 +120,13 @@ async fn list_users(State(state): State<AppState>) -> Result<Json<Vec<serde_json
     Ok(Json(users_with_servers))
 }
 
+#[utoipa::path(get, path = "/users/{id}", tag = "users", params(("id" = String, Path)), responses((status = 200, body = serde_json::Value), (status = 404)))]
 async fn get_user(
     State(state): State<AppState>,
     Path(user_id): Path<String>,
 ) -> Result<Json<serde_json::Value>, AppError> {
     let user: UserResponse = sqlx::query_as(
-        r#"SELECT id, username, role,
+        r#"SELECT id, username, email, role,
            COALESCE(is_active, 1) as is_active,
            COALESCE(language, 'fr') as language,
            COALESCE(accent_color, '#3A82F6') as accent_color,
@@ -122,6 +147,7 @@ async fn get_user(
     Ok(Json(serde_json::json!({
         "id": user.id,
         "username": user.username,
+        "email": user.email,
         "role": user.role,
         "is_active": user.is_active,
         "language": user.language,
@@ -134,10 +160,34 @@ async fn get_user(
     })))
 }
 
+fn validate_role(role: Option<&str>) -> Result<(), AppError> {
+    if role.is_some_and(|r| !server_access::ROLES.contains(&r)) {
+        return Err(AppError::BadRequest("users.invalid_role".into()));
+    }
+    Ok(())
+}
+
+/// An email address, `None` for an empty one
+fn parse_email(email: &str) -> Result<Option<String>, AppError> {
+    let email = email.trim();
+    if email.is_empty() {
+        return Ok(None);
+    }
+    match email.split_once('@') {
+        Some((local, domain)) if !local.is_empty() && domain.contains('.') && !email.contains(char::is_whitespace) => Ok(Some(email.to_string())),
+        _ => Err(AppError::BadRequest("users.invalid_email".into())),
+    }
+}
+
+#[utoipa::path(post, path = "/users", tag = "users", request_body = CreateUserRequest, responses((status = 201, body = serde_json::Value)))]
 async fn create_user(
     State(state): State<AppState>,
+    auth: AuthUser,
     Json(body): Json<CreateUserRequest>,
 ) -> Result<(StatusCode, Json<serde_json::Value>), AppError> {
+    auth.require_admin()?;
+    validate_role(body.role.as_deref())?;
+    let email = body.email.as_deref().map(parse_email).transpose()?.flatten();
     // Check if username already exists
     let exists: Option<(i32,)> =
         sqlx::query_as("SELECT 1 FROM users WHERE username = ?")
@@ -177,12 +227,13 @@ async fn create_user(
         .map(|s| serde_json::to_string(s).unwrap_or_else(|_| "[]".to_string()));
 
     sqlx::query(
-        r#"INSERT INTO users (id, username, password_hash, role, is_active, language, accent_color, allocated_servers, created_at, updated_at)
-           VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)"#,
+        r#"INSERT INTO users (id, username, password_hash, email, role, is_active, language, accent_color, allocated_servers, created_at, updated_at)
+           VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)"#,
     )
     .bind(&id)
     .bind(&body.username)
     .bind(&password_hash)
+    .bind(&email)
     .bind(&role)
     .bind(is_active)
     .bind(&language)
@@ -202,22 +253,23 @@ async fn create_user(
     }))))
 }
 
+#[utoipa::path(put, path = "/users/{id}", tag = "users", params(("id" = String, Path)), request_body = UpdateUserRequest, responses((status = 200, body = serde_json::Value)))]
 async fn update_user(
     State(state): State<AppState>,
+    auth: AuthUser,
     Path(user_id): Path<String>,
     Json(body): Json<UpdateUserRequest>,
 ) -> Result<Json<serde_json::Value>, AppError> {
+    auth.require_admin()?;
+    validate_role(body.role.as_deref())?;
+    let email = body.email.as_deref().map(parse_email).transpose()?;
     let now = Utc::now().to_rfc3339();
 
-    // Check if user exists
-    let exists: Option<(i32,)> = sqlx::query_as("SELECT 1 FROM users WHERE id = ?")
+    let current_role: String = sqlx::query_scalar("SELECT role FROM users WHERE id = ?")
         .bind(&user_id)
         .fetch_optional(&state.pool)
-        .await?;
-
-    if exists.is_none() {
-        return Err(AppError::NotFound("users.not_found".into()));
-    }
+        .await?
+        .ok_or_else(|| AppError::NotFound("users.not_found".into()))?;
 
     // Build dynamic update query
     let mut updates = vec!["updated_at = ?"];
@@ -230,6 +282,9 @@ async fn update_user(
         updates.push("password_hash = ?");
         has_password = true;
     }
+    if email.is_some() {
+        updates.push("email = ?");
+    }
     if body.role.is_some() {
         updates.push("role = ?");
     }
@@ -261,6 +316,9 @@ async fn update_user(
             .map_err(|_| AppError::Internal("Password hashing failed".into()))?;
         sql_query = sql_query.bind(password_hash);
     }
+    if let Some(email) = email {
+        sql_query = sql_query.bind(email);
+    }
     if let Some(ref role) = body.role {
         sql_query = sql_query.bind(role);
     }
@@ -285,16 +343,27 @@ async fn update_user(
         .await
         .map_err(|e| AppError::Internal(format!("Failed to update user: {}", e)))?;
 
+    let role_changed = body.role.as_deref().is_some_and(|role| role != current_role);
+    // Tokens carry the role they were issued with, the user logs in again
+    if role_changed || body.is_active == Some(false) || has_password {
+        sessions::revoke_all(&state.pool, &user_id).await?;
+    }
+
     Ok(Json(serde_json::json!({
         "success": true,
         "message": "users.update_success"
     })))
 }
 
+#[utoipa::path(delete, path = "/users/{id}", tag = "users", params(("id" = String, Path)), responses((status = 200, body = serde_json::Value), (status = 404)))]
 async fn delete_user(
     State(state): State<AppState>,
+    auth: AuthUser,
     Path(user_id): Path<String>,
 ) -> Result<Json<serde_json::Value>, AppError> {
+    auth.require_admin()?;
+    // The rows go with the user, their ids must be revoked in the cache first
+    sessions::revoke_all(&state.pool, &user_id).await?;
     let result = sqlx::query("DELETE FROM users WHERE id = ?")
         .bind(&user_id)
         .execute(&state.pool)
@@ -309,3 +378,60 @@ async fn delete_user(
         "message": "users.delete_success"
     })))
 }
+
+/// One-time link letting a user choose a new password, to hand over out of band. Any previous
+/// link of the user stops working.
+#[utoipa::path(post, path = "/users/{id}/password-reset", tag = "users", params(("id" = String, Path)), responses((status = 201, body = serde_json::Value)))]
+async fn create_password_reset(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(user_id): Path<String>,
+) -> Result<(StatusCode, Json<serde_json::Value>), AppError> {
+    auth.require_admin()?;
+    let username: String = sqlx::query_scalar("SELECT username FROM users WHERE id = ?")
+        .bind(&user_id)
+        .fetch_optional(&state.pool)
+        .await?
+        .ok_or_else(|| AppError::NotFound("users.not_found".into()))?;
+
+    let (token, expires_at) = password_reset::issue(&state.pool, &user_id, &auth.id).await?;
+    let detail = format!("by {}", auth.username);
+    audit::record(&state.pool, Some(&user_id), Some(&username), audit::ACTION_PASSWORD_RESET_ISSUED, Some(&detail), None).await;
+
+    Ok((StatusCode::CREATED, Json(serde_json::json!({
+        "token": token,
+        "reset_path": format!("/reset-password?token={}", token),
+        "expires_at": expires_at
+    }))))
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ActivityQuery {
+    pub limit: Option<u32>,
+    /// Id of the oldest entry already shown, to load older ones
+    pub before: Option<i64>,
+}
+
+/// Recent actions of a user (logins, server power actions, file changes), newest first. Users
+/// may see their own, admins anyone's.
+#[utoipa::path(get, path = "/users/{id}/activity", tag = "users", params(("id" = String, Path), ActivityQuery), responses((status = 200, body = [audit::AuditEntry])))]
+async fn get_activity(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(user_id): Path<String>,
+    Query(query): Query<ActivityQuery>,
+) -> Result<Json<Vec<audit::AuditEntry>>, AppError> {
+    if user_id != auth.id {
+        auth.require_admin()?;
+    }
+    let exists: Option<String> = sqlx::query_scalar("SELECT id FROM users WHERE id = ?")
+        .bind(&user_id)
+        .fetch_optional(&state.pool)
+        .await?;
+    if exists.is_none() {
+        return Err(AppError::NotFound("users.not_found".into()));
+    }
+    let limit = query.limit.unwrap_or(50).clamp(1, 500);
+    Ok(Json(audit::for_user(&state.pool, &user_id, limit, query.before).await?))
+}
@@ -0,0 +1,156 @@
+//! Role-based response shaping: fields of sensitive groups are stripped from JSON responses
+//! for the roles the `field_visibility` setting hides them from. Admins always see everything.
+
+use std::collections::HashMap;
+use std::sync::RwLock;
+
+use axum::{
+    body::Body,
+    extract::{FromRequestParts, Request},
+    http::header,
+    middleware::Next,
+    response::Response,
+};
+
+use crate::api::auth::AuthUser;
+use crate::db::DbPool;
+
+pub struct FieldGroup {
+    pub name: &'static str,
+    /// Routes relative to the API root, `*` matching one path segment
+    pub routes: &'static [&'static str],
+    /// Keys removed from the response object (or from each object of a list)
+    pub fields: &'static [&'static str],
+}
+
+pub const GROUPS: &[FieldGroup] = &[
+    FieldGroup {
+        name: "host_stats",
+        routes: &["/system/stats"],
+        fields: &["cpu", "ram", "ram_used", "ram_total", "disk", "disk_used", "disk_total", "cpu_cores"],
+    },
+    FieldGroup {
+        name: "disk_usage",
+        routes: &["/servers", "/servers/*", "/system/stats"],
+        fields: &["disk_usage_bytes", "managed_disk"],
+    },
+    FieldGroup {
+        name: "process_metrics",
+        routes: &["/servers", "/servers/*", "/system/stats"],
+        fields: &[
+            "cpu_usage", "cpu_usage_normalized", "memory_usage_bytes", "managed_cpu", "managed_cpu_normalized", "managed_ram",
+            "network_rx_bytes_per_sec", "network_tx_bytes_per_sec", "connections",
+        ],
+    },
+    FieldGroup {
+        name: "user_names",
+        routes: &["/system/announcements"],
+        fields: &["created_by"],
+    },
+];
+
+/// Group name -> roles it is hidden from
+pub type VisibilityMatrix = HashMap<String, Vec<String>>;
+
+lazy_static::lazy_static! {
+    static ref MATRIX: RwLock<VisibilityMatrix> = RwLock::new(HashMap::new());
+}
+
+/// Load the saved matrix, called once at startup
+pub async fn load(pool: &DbPool) {
+    let saved: Option<String> = sqlx::query_scalar("SELECT value FROM settings WHERE key = 'field_visibility'")
+        .fetch_optional(pool)
+        .await
+        .ok()
+        .flatten();
+    if let Some(matrix) = saved.and_then(|v| serde_json::from_str(&v).ok()) {
+        set(matrix);
+    }
+}
+
+pub fn get() -> VisibilityMatrix {
+    MATRIX.read().map(|m| m.clone()).unwrap_or_default()
+}
+
+pub fn set(matrix: VisibilityMatrix) {
+    if let Ok(mut current) = MATRIX.write() {
+        *current = matrix;
+    }
+}
+
+pub fn is_known_group(name: &str) -> bool {
+    GROUPS.iter().any(|g| g.name == name)
+}
+
+/// Whether a group is hidden from a role, for handlers returning its fields nested deeper
+/// than the middleware strips
+pub fn is_hidden(group: &str, role: &str) -> bool {
+    role != "admin" && get().get(group).is_some_and(|roles| roles.iter().any(|r| r == role))
+}
+
+/// Middleware removing the fields hidden from the caller's role
+pub async fn shape_response(req: Request, next: Next) -> Response {
+    let path = req.uri().path().to_string();
+    let (mut parts, body) = req.into_parts();
+    let role = AuthUser::from_request_parts(&mut parts, &()).await.ok().map(|u| u.role);
+    let req = Request::from_parts(parts, body);
+
+    let hidden: Vec<&str> = match role.as_deref() {
+        None | Some("admin") => Vec::new(),
+        Some(role) => {
+            let matrix = get();
+            GROUPS
+                .iter()
+                .filter(|g| matrix.get(g.name).is_some_and(|roles| roles.iter().any(|r| r == role)))
+                .filter(|g| g.routes.iter().any(|route| route_matches(route, &path)))
+                .flat_map(|g| g.fields.iter().copied())
+                .collect()
+        }
+    };
+
+    let response = next.run(req).await;
+    if hidden.is_empty() {
+        return response;
+    }
+
+    let is_json = response
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.starts_with("application/json"));
+    if !is_json {
+        return response;
+    }
+
+    let (mut parts, body) = response.into_parts();
+    let Ok(bytes) = axum::body::to_bytes(body, usize::MAX).await else {
+        return Response::from_parts(parts, Body::empty());
+    };
+    let Ok(mut value) = serde_json::from_slice::<serde_json::Value>(&bytes) else {
+        return Response::from_parts(parts, Body::from(bytes));
+    };
+
+    match &mut value {
+        serde_json::Value::Array(items) => items.iter_mut().for_each(|item| strip_fields(item, &hidden)),
+        item => strip_fields(item, &hidden),
+    }
+
+    // The cached ETag describes the unshaped body
+    parts.headers.remove(header::ETAG);
+    parts.headers.remove(header::CONTENT_LENGTH);
+    Response::from_parts(parts, Body::from(value.to_string()))
+}
+
+fn strip_fields(value: &mut serde_json::Value, hidden: &[&str]) {
+    if let Some(obj) = value.as_object_mut() {
+        for field in hidden {
+            obj.remove(*field);
+        }
+    }
+}
+
+fn route_matches(route: &str, path: &str) -> bool {
+    let route: Vec<&str> = route.trim_matches('/').split('/').collect();
+    let path: Vec<&str> = path.trim_matches('/').split('/').collect();
+    route.len() == path.len() && route.iter().zip(&path).all(|(r, p)| *r == "*" || r == p)
+}
@@ -4,15 +4,22 @@ use axum::{
     Json, Router,
 };
 use serde::{Deserialize, Serialize};
+use utoipa::{OpenApi, ToSchema};
 
 use crate::{AppState, error::AppError};
+use crate::api::auth::AuthUser;
+use crate::services::{discord_service, network};
 
 pub fn routes() -> Router<AppState> {
     Router::new()
         .route("/test", post(test_webhook))
 }
 
-#[derive(Debug, Serialize)]
+#[derive(OpenApi)]
+#[openapi(paths(test_webhook))]
+pub(super) struct ApiDoc;
+
+#[derive(Debug, Serialize, ToSchema)]
 pub struct WebhookTestResponse {
     pub success: bool,
     pub message: String,
@@ -25,15 +32,22 @@ struct ServerInfo {
     game_type: String,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct TestWebhookRequest {
     pub webhook_url: Option<String>,
 }
 
+/// name, game_type, id, discord_emoji, parent_proxy_id
+type StatusRow = (String, String, String, Option<String>, Option<String>);
+
+#[utoipa::path(post, path = "/webhook/test", tag = "settings", request_body = TestWebhookRequest, responses((status = 200, body = WebhookTestResponse)))]
 async fn test_webhook(
     State(state): State<AppState>,
+    auth: AuthUser,
     Json(body): Json<TestWebhookRequest>,
 ) -> Result<Json<WebhookTestResponse>, AppError> {
+    // Posts to any URL from the host, with the status of every server
+    auth.require_admin()?;
     let pool = &state.pool;
     let pm = &state.process_manager;
 
@@ -48,32 +62,35 @@ async fn test_webhook(
         
         settings
             .map(|s| s.0)
-            .ok_or_else(|| AppError::BadRequest("No webhook URL configured".into()))?
+            .ok_or_else(|| AppError::BadRequest("webhook.url_missing".into()))?
     };
 
     if webhook_url.is_empty() {
-        return Err(AppError::BadRequest("Webhook URL is empty".into()));
+        return Err(AppError::BadRequest("webhook.url_missing".into()));
     }
 
     // Fetch all servers with their status
-    let servers: Vec<(String, String, String)> = sqlx::query_as(
-        "SELECT name, game_type, id FROM servers ORDER BY name"
+    let servers: Vec<StatusRow> = sqlx::query_as(
+        "SELECT name, game_type, id, discord_emoji, parent_proxy_id FROM servers ORDER BY name"
     )
     .fetch_all(pool)
     .await?;
+    let servers = network::group_by_proxy(servers, |s| (s.2.as_str(), s.4.as_deref()));
 
     let mut server_list: Vec<ServerInfo> = Vec::new();
     let mut online_count = 0;
     let mut offline_count = 0;
 
-    for (name, game_type, id) in servers {
-        let status = if pm.is_running(&id) {
+    for ((name, game_type, id, emoji, _), is_member) in servers {
+        let status = if pm.is_running(&id).await {
             online_count += 1;
-            "🟢 En ligne".to_string()
+            format!("{} En ligne", discord_service::online_emoji(emoji.as_deref()))
         } else {
             offline_count += 1;
             "🔴 Arrêté".to_string()
         };
+        // Members are listed under their proxy
+        let status = if is_member { format!("  ↳ {}", status) } else { status };
 
         server_list.push(ServerInfo {
             name,
@@ -148,7 +165,7 @@ async fn test_webhook(
     });
 
     // Use update_status_message to edit existing message or create new one
-    crate::services::discord_service::update_status_message(pool, embed).await
+    discord_service::update_status_message(pool, embed).await
         .map_err(|e| AppError::Internal(format!("Failed to update status message: {}", e)))?;
 
     Ok(Json(WebhookTestResponse {
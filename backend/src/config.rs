@@ -1,13 +1,38 @@
+use std::path::PathBuf;
+
 #[derive(Clone, Debug)]
 pub struct Settings {
     pub host: String,
     pub port: u16,
     pub database_url: String,
     pub uploads_dir: String,
+    /// Backup archives and automatic snapshots, panel database copies in `panel/` (`BACKUPS_DIR`)
+    pub backups_dir: PathBuf,
+    /// Development mode: allows `dummy` servers backed by the built-in simulator
+    pub dev_mode: bool,
+    /// Other sites whose pages may call the API (`ALLOWED_ORIGINS`, comma-separated)
+    pub allowed_origins: Vec<String>,
+    /// Any site may call the API (`CORS_PERMISSIVE`, defaults to `DEV_MODE`)
+    pub cors_permissive: bool,
+    /// HTTPS on `port`, None serves plain HTTP (`TLS_ENABLED`, defaults to off in `DEV_MODE`)
+    pub tls: Option<TlsSettings>,
+}
+
+#[derive(Clone, Debug)]
+pub struct TlsSettings {
+    /// PEM certificate chain and private key (`TLS_CERT_PATH`, `TLS_KEY_PATH`)
+    pub cert_path: PathBuf,
+    pub key_path: PathBuf,
+    /// Generate a self-signed pair at those paths when they don't exist (`TLS_SELF_SIGNED`)
+    pub self_signed: bool,
+    /// Port answering plain HTTP with a redirect to HTTPS (`HTTP_REDIRECT_PORT`)
+    pub http_redirect_port: Option<u16>,
 }
 
 impl Settings {
     pub fn from_env() -> Self {
+        let flag = |name: &str| std::env::var(name).ok().map(|v| v == "1" || v.eq_ignore_ascii_case("true"));
+        let dev_mode = flag("DEV_MODE").unwrap_or(false);
         Self {
             host: std::env::var("HOST").unwrap_or_else(|_| "0.0.0.0".into()),
             port: std::env::var("PORT")
@@ -17,6 +42,16 @@ impl Settings {
             database_url: std::env::var("DATABASE_URL")
                 .unwrap_or_else(|_| "sqlite:data/database.db?mode=rwc".into()),
             uploads_dir: std::env::var("UPLOADS_DIR").unwrap_or_else(|_| "./data/uploads".into()),
+            backups_dir: std::env::var("BACKUPS_DIR").unwrap_or_else(|_| "backups".into()).into(),
+            dev_mode,
+            allowed_origins: crate::services::cors::parse_list(&std::env::var("ALLOWED_ORIGINS").unwrap_or_default()),
+            cors_permissive: flag("CORS_PERMISSIVE").unwrap_or(dev_mode),
+            tls: flag("TLS_ENABLED").unwrap_or(!dev_mode).then(|| TlsSettings {
+                cert_path: std::env::var("TLS_CERT_PATH").unwrap_or_else(|_| "data/tls/cert.pem".into()).into(),
+                key_path: std::env::var("TLS_KEY_PATH").unwrap_or_else(|_| "data/tls/key.pem".into()).into(),
+                self_signed: flag("TLS_SELF_SIGNED").unwrap_or(true),
+                http_redirect_port: std::env::var("HTTP_REDIRECT_PORT").ok().and_then(|p| p.parse().ok()),
+            }),
         }
     }
 }
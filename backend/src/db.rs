@@ -1,5 +1,5 @@
 use sqlx::{sqlite::SqlitePoolOptions, Pool, Sqlite};
-use std::io::{Error, ErrorKind};
+use std::io::Error;
 use tracing::info;
 
 pub type DbPool = Pool<Sqlite>;
@@ -18,7 +18,7 @@ pub async fn init_pool(database_url: &str) -> std::io::Result<DbPool> {
         .max_connections(5)
         .connect(database_url)
         .await
-        .map_err(|e| Error::new(ErrorKind::Other, e.to_string()))
+        .map_err(|e| Error::other(e.to_string()))
 }
 
 pub async fn run_migrations(pool: &DbPool) -> std::io::Result<()> {
@@ -31,6 +31,7 @@ pub async fn run_migrations(pool: &DbPool) -> std::io::Result<()> {
             id TEXT PRIMARY KEY,
             username TEXT NOT NULL UNIQUE,
             password_hash TEXT NOT NULL,
+            email TEXT,
             role TEXT NOT NULL DEFAULT 'user',
             is_active INTEGER NOT NULL DEFAULT 1,
             language TEXT NOT NULL DEFAULT 'fr',
@@ -38,6 +39,11 @@ pub async fn run_migrations(pool: &DbPool) -> std::io::Result<()> {
             last_login TEXT,
             last_ip TEXT,
             allocated_servers TEXT,
+            totp_secret TEXT,
+            totp_enabled INTEGER NOT NULL DEFAULT 0,
+            totp_backup_codes TEXT,
+            totp_last_step INTEGER,
+            oidc_subject TEXT,
             created_at TEXT NOT NULL,
             updated_at TEXT NOT NULL
         );
@@ -73,7 +79,38 @@ pub async fn run_migrations(pool: &DbPool) -> std::io::Result<()> {
             
             auth_mode TEXT NOT NULL DEFAULT 'authenticated',
             bind_address TEXT NOT NULL DEFAULT '0.0.0.0',
-            port INTEGER NOT NULL DEFAULT 5520
+            port INTEGER NOT NULL DEFAULT 5520,
+
+            env_vars TEXT DEFAULT '{}',
+            launch_mode TEXT NOT NULL DEFAULT 'java',
+            command_template TEXT,
+            locale TEXT NOT NULL DEFAULT 'fr',
+            stop_command TEXT,
+            restart_schedule TEXT,
+            install_state TEXT NOT NULL DEFAULT 'completed',
+            log_rotation_count INTEGER NOT NULL DEFAULT 5,
+            discord_color TEXT,
+            discord_emoji TEXT,
+            alert_cpu_percent REAL,
+            alert_ram_percent REAL,
+            alert_disk_mb INTEGER,
+            owner_id TEXT,
+            last_exit_code INTEGER,
+            last_exit_at TEXT,
+            runtime TEXT NOT NULL DEFAULT 'native',
+            docker_image TEXT,
+            health_probe TEXT,
+            health_probe_interval_secs INTEGER NOT NULL DEFAULT 30,
+            console_log_enabled INTEGER NOT NULL DEFAULT 1,
+            console_log_max_mb INTEGER NOT NULL DEFAULT 10,
+            console_log_dedup INTEGER NOT NULL DEFAULT 0,
+            network_role TEXT,
+            parent_proxy_id TEXT,
+            backup_save_command TEXT,
+            backup_save_confirm TEXT,
+            backup_autosave_off_command TEXT,
+            backup_autosave_on_command TEXT,
+            backup_filename_template TEXT
         );
 
         CREATE TABLE IF NOT EXISTS backups (
@@ -82,6 +119,25 @@ pub async fn run_migrations(pool: &DbPool) -> std::io::Result<()> {
             filename TEXT NOT NULL,
             size_bytes INTEGER NOT NULL,
             created_at TEXT NOT NULL,
+            kind TEXT NOT NULL DEFAULT 'manual',
+            label TEXT,
+            compression TEXT NOT NULL DEFAULT 'gzip',
+            location TEXT NOT NULL DEFAULT 'local',
+            sha256 TEXT,
+            integrity TEXT,
+            verified_at TEXT,
+            encrypted INTEGER NOT NULL DEFAULT 0,
+            FOREIGN KEY (server_id) REFERENCES servers(id) ON DELETE CASCADE
+        );
+
+        CREATE TABLE IF NOT EXISTS backup_jobs (
+            id TEXT PRIMARY KEY,
+            server_id TEXT NOT NULL,
+            backup_id TEXT NOT NULL,
+            status TEXT NOT NULL DEFAULT 'pending',
+            error TEXT,
+            created_at TEXT NOT NULL,
+            finished_at TEXT,
             FOREIGN KEY (server_id) REFERENCES servers(id) ON DELETE CASCADE
         );
 
@@ -110,17 +166,222 @@ pub async fn run_migrations(pool: &DbPool) -> std::io::Result<()> {
             value TEXT NOT NULL,
             updated_at TEXT NOT NULL
         );
+
+        CREATE TABLE IF NOT EXISTS metrics_history (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            server_id TEXT NOT NULL,
+            cpu REAL NOT NULL,
+            memory_bytes INTEGER NOT NULL,
+            players INTEGER NOT NULL,
+            recorded_at TEXT NOT NULL,
+            disk_bytes INTEGER,
+            FOREIGN KEY (server_id) REFERENCES servers(id) ON DELETE CASCADE
+        );
+        CREATE INDEX IF NOT EXISTS idx_metrics_history_server ON metrics_history(server_id, recorded_at);
+
+        CREATE TABLE IF NOT EXISTS server_events (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            server_id TEXT NOT NULL,
+            event_type TEXT NOT NULL,
+            detail TEXT,
+            created_at TEXT NOT NULL,
+            FOREIGN KEY (server_id) REFERENCES servers(id) ON DELETE CASCADE
+        );
+        CREATE INDEX IF NOT EXISTS idx_server_events_server ON server_events(server_id, created_at);
+
+        CREATE TABLE IF NOT EXISTS announcements (
+            id TEXT PRIMARY KEY,
+            title TEXT NOT NULL,
+            body TEXT NOT NULL,
+            level TEXT NOT NULL DEFAULT 'info',
+            created_by TEXT NOT NULL,
+            created_at TEXT NOT NULL,
+            expires_at TEXT
+        );
+
+        CREATE TABLE IF NOT EXISTS server_transfers (
+            id TEXT PRIMARY KEY,
+            server_id TEXT NOT NULL,
+            from_user_id TEXT,
+            to_user_id TEXT NOT NULL,
+            move_allocation INTEGER NOT NULL DEFAULT 1,
+            status TEXT NOT NULL DEFAULT 'pending',
+            requested_by TEXT NOT NULL,
+            created_at TEXT NOT NULL,
+            resolved_at TEXT,
+            FOREIGN KEY (server_id) REFERENCES servers(id) ON DELETE CASCADE
+        );
+
+        CREATE TABLE IF NOT EXISTS elevation_grants (
+            id TEXT PRIMARY KEY,
+            server_id TEXT NOT NULL,
+            user_id TEXT NOT NULL,
+            reason TEXT NOT NULL,
+            duration_minutes INTEGER NOT NULL,
+            status TEXT NOT NULL DEFAULT 'pending',
+            requested_at TEXT NOT NULL,
+            decided_by TEXT,
+            expires_at TEXT,
+            resolved_at TEXT,
+            FOREIGN KEY (server_id) REFERENCES servers(id) ON DELETE CASCADE
+        );
+
+        CREATE TABLE IF NOT EXISTS api_tokens (
+            id TEXT PRIMARY KEY,
+            user_id TEXT NOT NULL,
+            name TEXT NOT NULL,
+            token_hash TEXT NOT NULL UNIQUE,
+            scopes TEXT NOT NULL DEFAULT 'read',
+            rate_limit_per_minute INTEGER NOT NULL DEFAULT 60,
+            created_at TEXT NOT NULL,
+            last_used_at TEXT,
+            revoked_at TEXT,
+            FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+        );
+
+        CREATE TABLE IF NOT EXISTS api_token_usage (
+            token_id TEXT NOT NULL,
+            day TEXT NOT NULL,
+            method TEXT NOT NULL,
+            endpoint TEXT NOT NULL,
+            requests INTEGER NOT NULL DEFAULT 0,
+            rejected INTEGER NOT NULL DEFAULT 0,
+            PRIMARY KEY (token_id, day, method, endpoint),
+            FOREIGN KEY (token_id) REFERENCES api_tokens(id) ON DELETE CASCADE
+        );
+
+        CREATE TABLE IF NOT EXISTS inbound_hooks (
+            id TEXT PRIMARY KEY,
+            name TEXT NOT NULL,
+            server_id TEXT NOT NULL,
+            action TEXT NOT NULL,
+            command TEXT,
+            secret TEXT NOT NULL,
+            allowed_ips TEXT NOT NULL DEFAULT '[]',
+            created_by TEXT NOT NULL,
+            created_at TEXT NOT NULL,
+            last_triggered_at TEXT,
+            FOREIGN KEY (server_id) REFERENCES servers(id) ON DELETE CASCADE
+        );
+
+        CREATE TABLE IF NOT EXISTS inbound_nonces (
+            key_id TEXT NOT NULL,
+            nonce TEXT NOT NULL,
+            seen_at TEXT NOT NULL,
+            PRIMARY KEY (key_id, nonce)
+        );
+
+        CREATE TABLE IF NOT EXISTS outgoing_webhooks (
+            id TEXT PRIMARY KEY,
+            name TEXT NOT NULL,
+            url TEXT NOT NULL,
+            secret TEXT NOT NULL,
+            events TEXT NOT NULL DEFAULT '[]',
+            server_id TEXT,
+            enabled INTEGER NOT NULL DEFAULT 1,
+            created_by TEXT NOT NULL,
+            created_at TEXT NOT NULL,
+            last_delivery_at TEXT,
+            last_delivery_status TEXT,
+            FOREIGN KEY (server_id) REFERENCES servers(id) ON DELETE CASCADE
+        );
+
+        CREATE TABLE IF NOT EXISTS server_permissions (
+            server_id TEXT NOT NULL,
+            user_id TEXT NOT NULL,
+            permission TEXT NOT NULL,
+            granted_by TEXT NOT NULL,
+            granted_at TEXT NOT NULL,
+            PRIMARY KEY (server_id, user_id, permission),
+            FOREIGN KEY (server_id) REFERENCES servers(id) ON DELETE CASCADE,
+            FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+        );
+
+        CREATE TABLE IF NOT EXISTS role_templates (
+            id TEXT PRIMARY KEY,
+            name TEXT UNIQUE NOT NULL COLLATE NOCASE,
+            description TEXT,
+            permissions TEXT NOT NULL,
+            created_at TEXT NOT NULL,
+            updated_at TEXT NOT NULL
+        );
+
+        CREATE TABLE IF NOT EXISTS server_role_assignments (
+            server_id TEXT NOT NULL,
+            user_id TEXT NOT NULL,
+            template_id TEXT NOT NULL,
+            assigned_by TEXT NOT NULL,
+            assigned_at TEXT NOT NULL,
+            PRIMARY KEY (server_id, user_id),
+            FOREIGN KEY (server_id) REFERENCES servers(id) ON DELETE CASCADE,
+            FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE,
+            FOREIGN KEY (template_id) REFERENCES role_templates(id) ON DELETE CASCADE
+        );
+
+        CREATE TABLE IF NOT EXISTS audit_log (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            user_id TEXT,
+            username TEXT,
+            action TEXT NOT NULL,
+            detail TEXT,
+            ip TEXT,
+            created_at TEXT NOT NULL,
+            request_id TEXT
+        );
+        CREATE INDEX IF NOT EXISTS idx_audit_log_created ON audit_log(created_at);
+        CREATE INDEX IF NOT EXISTS idx_audit_log_user ON audit_log(user_id, id);
+
+        CREATE TABLE IF NOT EXISTS password_resets (
+            token_hash TEXT PRIMARY KEY,
+            user_id TEXT NOT NULL,
+            created_by TEXT NOT NULL,
+            created_at TEXT NOT NULL,
+            expires_at TEXT NOT NULL,
+            FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+        );
+
+        CREATE TABLE IF NOT EXISTS ip_bans (
+            id TEXT PRIMARY KEY,
+            address TEXT NOT NULL,
+            reason TEXT,
+            automatic INTEGER NOT NULL DEFAULT 0,
+            created_by TEXT,
+            created_at TEXT NOT NULL,
+            expires_at TEXT
+        );
+
+        CREATE TABLE IF NOT EXISTS sessions (
+            id TEXT PRIMARY KEY,
+            user_id TEXT NOT NULL,
+            ip TEXT,
+            user_agent TEXT,
+            created_at TEXT NOT NULL,
+            last_seen_at TEXT NOT NULL,
+            expires_at TEXT NOT NULL,
+            revoked_at TEXT,
+            FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+        );
+        CREATE INDEX IF NOT EXISTS idx_sessions_user ON sessions(user_id);
+
+        CREATE TABLE IF NOT EXISTS server_templates (
+            id TEXT PRIMARY KEY,
+            name TEXT UNIQUE NOT NULL COLLATE NOCASE,
+            description TEXT,
+            settings TEXT NOT NULL,
+            created_at TEXT NOT NULL,
+            updated_at TEXT NOT NULL
+        );
         "#,
     )
     .execute(pool)
     .await
-    .map_err(|e| Error::new(ErrorKind::Other, e.to_string()))?;
+    .map_err(|e| Error::other(e.to_string()))?;
 
     // Run migrations for existing databases
     let columns: Vec<(i64, String, String, i64, Option<String>, i64)> = sqlx::query_as("PRAGMA table_info(users)")
         .fetch_all(pool)
         .await
-        .map_err(|e| Error::new(ErrorKind::Other, e.to_string()))?;
+        .map_err(|e| Error::other(e.to_string()))?;
 
     let column_names: Vec<&str> = columns.iter().map(|c| c.1.as_str()).collect();
 
@@ -143,12 +404,35 @@ pub async fn run_migrations(pool: &DbPool) -> std::io::Result<()> {
     if !column_names.contains(&"allocated_servers") {
         sqlx::query("ALTER TABLE users ADD COLUMN allocated_servers TEXT").execute(pool).await.ok();
     }
+    if !column_names.contains(&"totp_secret") {
+        sqlx::query("ALTER TABLE users ADD COLUMN totp_secret TEXT").execute(pool).await.ok();
+    }
+    if !column_names.contains(&"totp_enabled") {
+        sqlx::query("ALTER TABLE users ADD COLUMN totp_enabled INTEGER NOT NULL DEFAULT 0").execute(pool).await.ok();
+    }
+    if !column_names.contains(&"totp_backup_codes") {
+        sqlx::query("ALTER TABLE users ADD COLUMN totp_backup_codes TEXT").execute(pool).await.ok();
+    }
+    if !column_names.contains(&"totp_last_step") {
+        sqlx::query("ALTER TABLE users ADD COLUMN totp_last_step INTEGER").execute(pool).await.ok();
+    }
+    if !column_names.contains(&"oidc_subject") {
+        sqlx::query("ALTER TABLE users ADD COLUMN oidc_subject TEXT").execute(pool).await.ok();
+    }
+    if !column_names.contains(&"email") {
+        sqlx::query("ALTER TABLE users ADD COLUMN email TEXT").execute(pool).await.ok();
+    }
+    // One panel account per identity provider subject
+    sqlx::query("CREATE UNIQUE INDEX IF NOT EXISTS idx_users_oidc_subject ON users(oidc_subject)")
+        .execute(pool)
+        .await
+        .map_err(|e| Error::other(e.to_string()))?;
 
     // Server table migrations
     let server_columns: Vec<(i64, String, String, i64, Option<String>, i64)> = sqlx::query_as("PRAGMA table_info(servers)")
         .fetch_all(pool)
         .await
-        .map_err(|e| Error::new(ErrorKind::Other, e.to_string()))?;
+        .map_err(|e| Error::other(e.to_string()))?;
 
     let server_column_names: Vec<&str> = server_columns.iter().map(|c| c.1.as_str()).collect();
 
@@ -196,6 +480,158 @@ pub async fn run_migrations(pool: &DbPool) -> std::io::Result<()> {
     if !server_column_names.contains(&"port") {
         sqlx::query("ALTER TABLE servers ADD COLUMN port INTEGER NOT NULL DEFAULT 5520").execute(pool).await.ok();
     }
+    if !server_column_names.contains(&"env_vars") {
+        sqlx::query("ALTER TABLE servers ADD COLUMN env_vars TEXT DEFAULT '{}'").execute(pool).await.ok();
+    }
+    if !server_column_names.contains(&"launch_mode") {
+        sqlx::query("ALTER TABLE servers ADD COLUMN launch_mode TEXT NOT NULL DEFAULT 'java'").execute(pool).await.ok();
+    }
+    if !server_column_names.contains(&"command_template") {
+        sqlx::query("ALTER TABLE servers ADD COLUMN command_template TEXT").execute(pool).await.ok();
+    }
+    if !server_column_names.contains(&"locale") {
+        sqlx::query("ALTER TABLE servers ADD COLUMN locale TEXT NOT NULL DEFAULT 'fr'").execute(pool).await.ok();
+    }
+    if !server_column_names.contains(&"stop_command") {
+        sqlx::query("ALTER TABLE servers ADD COLUMN stop_command TEXT").execute(pool).await.ok();
+    }
+    if !server_column_names.contains(&"restart_schedule") {
+        sqlx::query("ALTER TABLE servers ADD COLUMN restart_schedule TEXT").execute(pool).await.ok();
+    }
+    if !server_column_names.contains(&"install_state") {
+        sqlx::query("ALTER TABLE servers ADD COLUMN install_state TEXT NOT NULL DEFAULT 'completed'").execute(pool).await.ok();
+    }
+    if !server_column_names.contains(&"log_rotation_count") {
+        sqlx::query("ALTER TABLE servers ADD COLUMN log_rotation_count INTEGER NOT NULL DEFAULT 5").execute(pool).await.ok();
+    }
+    if !server_column_names.contains(&"discord_color") {
+        sqlx::query("ALTER TABLE servers ADD COLUMN discord_color TEXT").execute(pool).await.ok();
+    }
+    if !server_column_names.contains(&"discord_emoji") {
+        sqlx::query("ALTER TABLE servers ADD COLUMN discord_emoji TEXT").execute(pool).await.ok();
+    }
+    if !server_column_names.contains(&"alert_cpu_percent") {
+        sqlx::query("ALTER TABLE servers ADD COLUMN alert_cpu_percent REAL").execute(pool).await.ok();
+    }
+    if !server_column_names.contains(&"alert_ram_percent") {
+        sqlx::query("ALTER TABLE servers ADD COLUMN alert_ram_percent REAL").execute(pool).await.ok();
+    }
+    if !server_column_names.contains(&"alert_disk_mb") {
+        sqlx::query("ALTER TABLE servers ADD COLUMN alert_disk_mb INTEGER").execute(pool).await.ok();
+    }
+    if !server_column_names.contains(&"owner_id") {
+        sqlx::query("ALTER TABLE servers ADD COLUMN owner_id TEXT").execute(pool).await.ok();
+    }
+    if !server_column_names.contains(&"last_exit_code") {
+        sqlx::query("ALTER TABLE servers ADD COLUMN last_exit_code INTEGER").execute(pool).await.ok();
+    }
+    if !server_column_names.contains(&"last_exit_at") {
+        sqlx::query("ALTER TABLE servers ADD COLUMN last_exit_at TEXT").execute(pool).await.ok();
+    }
+    if !server_column_names.contains(&"runtime") {
+        sqlx::query("ALTER TABLE servers ADD COLUMN runtime TEXT NOT NULL DEFAULT 'native'").execute(pool).await.ok();
+    }
+    if !server_column_names.contains(&"docker_image") {
+        sqlx::query("ALTER TABLE servers ADD COLUMN docker_image TEXT").execute(pool).await.ok();
+    }
+    if !server_column_names.contains(&"health_probe") {
+        sqlx::query("ALTER TABLE servers ADD COLUMN health_probe TEXT").execute(pool).await.ok();
+    }
+    if !server_column_names.contains(&"health_probe_interval_secs") {
+        sqlx::query("ALTER TABLE servers ADD COLUMN health_probe_interval_secs INTEGER NOT NULL DEFAULT 30").execute(pool).await.ok();
+    }
+    if !server_column_names.contains(&"console_log_enabled") {
+        sqlx::query("ALTER TABLE servers ADD COLUMN console_log_enabled INTEGER NOT NULL DEFAULT 1").execute(pool).await.ok();
+    }
+    if !server_column_names.contains(&"console_log_max_mb") {
+        sqlx::query("ALTER TABLE servers ADD COLUMN console_log_max_mb INTEGER NOT NULL DEFAULT 10").execute(pool).await.ok();
+    }
+    if !server_column_names.contains(&"console_log_dedup") {
+        sqlx::query("ALTER TABLE servers ADD COLUMN console_log_dedup INTEGER NOT NULL DEFAULT 0").execute(pool).await.ok();
+    }
+    if !server_column_names.contains(&"network_role") {
+        sqlx::query("ALTER TABLE servers ADD COLUMN network_role TEXT").execute(pool).await.ok();
+    }
+    if !server_column_names.contains(&"parent_proxy_id") {
+        sqlx::query("ALTER TABLE servers ADD COLUMN parent_proxy_id TEXT").execute(pool).await.ok();
+    }
+    if !server_column_names.contains(&"backup_save_command") {
+        sqlx::query("ALTER TABLE servers ADD COLUMN backup_save_command TEXT").execute(pool).await.ok();
+    }
+    if !server_column_names.contains(&"backup_save_confirm") {
+        sqlx::query("ALTER TABLE servers ADD COLUMN backup_save_confirm TEXT").execute(pool).await.ok();
+    }
+    if !server_column_names.contains(&"backup_autosave_off_command") {
+        sqlx::query("ALTER TABLE servers ADD COLUMN backup_autosave_off_command TEXT").execute(pool).await.ok();
+    }
+    if !server_column_names.contains(&"backup_autosave_on_command") {
+        sqlx::query("ALTER TABLE servers ADD COLUMN backup_autosave_on_command TEXT").execute(pool).await.ok();
+    }
+    if !server_column_names.contains(&"backup_filename_template") {
+        sqlx::query("ALTER TABLE servers ADD COLUMN backup_filename_template TEXT").execute(pool).await.ok();
+    }
+    if !server_column_names.contains(&"tags") {
+        sqlx::query("ALTER TABLE servers ADD COLUMN tags TEXT").execute(pool).await.ok();
+    }
+
+    // Backup table migrations
+    let backup_columns: Vec<(i64, String, String, i64, Option<String>, i64)> = sqlx::query_as("PRAGMA table_info(backups)")
+        .fetch_all(pool)
+        .await
+        .map_err(|e| Error::other(e.to_string()))?;
+
+    let backup_column_names: Vec<&str> = backup_columns.iter().map(|c| c.1.as_str()).collect();
+
+    if !backup_column_names.contains(&"kind") {
+        sqlx::query("ALTER TABLE backups ADD COLUMN kind TEXT NOT NULL DEFAULT 'manual'").execute(pool).await.ok();
+    }
+
+    if !backup_column_names.contains(&"compression") {
+        sqlx::query("ALTER TABLE backups ADD COLUMN compression TEXT NOT NULL DEFAULT 'gzip'").execute(pool).await.ok();
+    }
+
+    if !backup_column_names.contains(&"location") {
+        sqlx::query("ALTER TABLE backups ADD COLUMN location TEXT NOT NULL DEFAULT 'local'").execute(pool).await.ok();
+    }
+
+    if !backup_column_names.contains(&"sha256") {
+        sqlx::query("ALTER TABLE backups ADD COLUMN sha256 TEXT").execute(pool).await.ok();
+    }
+
+    if !backup_column_names.contains(&"integrity") {
+        sqlx::query("ALTER TABLE backups ADD COLUMN integrity TEXT").execute(pool).await.ok();
+    }
+
+    if !backup_column_names.contains(&"verified_at") {
+        sqlx::query("ALTER TABLE backups ADD COLUMN verified_at TEXT").execute(pool).await.ok();
+    }
+
+    if !backup_column_names.contains(&"encrypted") {
+        sqlx::query("ALTER TABLE backups ADD COLUMN encrypted INTEGER NOT NULL DEFAULT 0").execute(pool).await.ok();
+    }
+    if !backup_column_names.contains(&"label") {
+        sqlx::query("ALTER TABLE backups ADD COLUMN label TEXT").execute(pool).await.ok();
+    }
+
+    // Metrics history migrations
+    let metrics_columns: Vec<(i64, String, String, i64, Option<String>, i64)> = sqlx::query_as("PRAGMA table_info(metrics_history)")
+        .fetch_all(pool)
+        .await
+        .map_err(|e| Error::other(e.to_string()))?;
+
+    if !metrics_columns.iter().any(|c| c.1 == "disk_bytes") {
+        sqlx::query("ALTER TABLE metrics_history ADD COLUMN disk_bytes INTEGER").execute(pool).await.ok();
+    }
+
+    // Audit log migrations
+    let audit_columns: Vec<(i64, String, String, i64, Option<String>, i64)> = sqlx::query_as("PRAGMA table_info(audit_log)")
+        .fetch_all(pool)
+        .await
+        .map_err(|e| Error::other(e.to_string()))?;
+
+    if !audit_columns.iter().any(|c| c.1 == "request_id") {
+        sqlx::query("ALTER TABLE audit_log ADD COLUMN request_id TEXT").execute(pool).await.ok();
+    }
 
     info!("✅ Migrations completed");
     Ok(())
@@ -3,13 +3,291 @@ use axum::{
     response::{IntoResponse, Response},
     Json,
 };
+use serde::Serialize;
 use std::fmt;
+use utoipa::ToSchema;
+
+use crate::services::request_id;
+
+/// Body of every failed request
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ErrorBody {
+    /// Stable code to branch on
+    pub code: ErrorCode,
+    /// Translation key of the message, e.g. `servers.not_found`
+    pub key: String,
+    /// Specifics the key doesn't carry, such as the parser error of a malformed upload
+    pub detail: Option<String>,
+    /// Same as `key`, kept for clients written before `code`
+    pub error: String,
+    /// Quoted in a bug report, the ID finds the matching log lines
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub request_id: Option<String>,
+}
+
+macro_rules! error_codes {
+    ($($code:ident => $key:literal,)*) => {
+        /// Stable identifier of an error, one per translation key, e.g. `SERVER_NOT_FOUND` for
+        /// `servers.not_found`. Keys without a code of their own get the generic code of their
+        /// status (`NOT_FOUND`, `BAD_REQUEST`...).
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+        #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+        pub enum ErrorCode {
+            NotFound,
+            BadRequest,
+            Unauthorized,
+            Forbidden,
+            TooManyRequests,
+            Conflict,
+            Internal,
+            Database,
+            $($code,)*
+        }
+
+        impl ErrorCode {
+            pub fn from_key(key: &str) -> Option<Self> {
+                match key {
+                    $($key => Some(Self::$code),)*
+                    _ => None,
+                }
+            }
+        }
+    };
+}
+
+// New error keys get their code here
+error_codes! {
+    AnnouncementInvalidExpiresAt => "announcements.invalid_expires_at",
+    AnnouncementInvalidLevel => "announcements.invalid_level",
+    AnnouncementNotFound => "announcements.not_found",
+    AnnouncementTitleAndBodyRequired => "announcements.title_and_body_required",
+
+    AuthAdminRequired => "auth.admin_required",
+    AuthInvalidApiToken => "auth.invalid_api_token",
+    AuthInvalidAuthHeader => "auth.invalid_auth_header",
+    AuthInvalidCredentials => "auth.invalid_credentials",
+    AuthInvalidResetToken => "auth.invalid_reset_token",
+    AuthInvalidToken => "auth.invalid_token",
+    AuthInvalidTotp => "auth.invalid_totp",
+    AuthIpBanned => "auth.ip_banned",
+    AuthMissingAuthHeader => "auth.missing_auth_header",
+    AuthOidcAccountExists => "auth.oidc_account_exists",
+    AuthOidcDisabled => "auth.oidc_disabled",
+    AuthOidcFailed => "auth.oidc_failed",
+    AuthPasswordLength => "auth.password_length",
+    AuthReadOnly => "auth.read_only",
+    AuthRegistrationClosed => "auth.registration_closed",
+    AuthResetEmailDisabled => "auth.reset_email_disabled",
+    AuthSessionNotFound => "auth.session_not_found",
+    AuthSessionRevoked => "auth.session_revoked",
+    AuthTooManyAttempts => "auth.too_many_attempts",
+    AuthTotpAlreadyEnabled => "auth.totp_already_enabled",
+    AuthTotpNotEnabled => "auth.totp_not_enabled",
+    AuthTotpNotSetUp => "auth.totp_not_set_up",
+    AuthTotpRequired => "auth.totp_required",
+    AuthTotpRequiredForRole => "auth.totp_required_for_role",
+    AuthTwoFactorSetupRequired => "auth.two_factor_setup_required",
+    AuthUserNotFound => "auth.user_not_found",
+
+    BackupBulkFilterRequired => "backups.bulk_filter_required",
+    BackupBulkNoServers => "backups.bulk_no_servers",
+    BackupDecryptionFailed => "backups.decryption_failed",
+    BackupEncryptionKeyRequired => "backups.encryption_key_required",
+    BackupFileMissing => "backups.file_missing",
+    BackupImportMissingFile => "backups.import_missing_file",
+    BackupInvalidArchive => "backups.invalid_archive",
+    BackupInvalidEncryptionKey => "backups.invalid_encryption_key",
+    BackupInvalidKind => "backups.invalid_kind",
+    BackupInvalidRestorePath => "backups.invalid_restore_path",
+    BackupJobNotFound => "backups.job_not_found",
+    BackupNotFound => "backups.not_found",
+    BackupPathsNotFound => "backups.paths_not_found",
+    BackupRemoteNotConfigured => "backups.remote_not_configured",
+    BackupUnreadableArchive => "backups.unreadable_archive",
+
+    ElevationAlreadyAdmin => "elevation.already_admin",
+    ElevationAlreadyRequested => "elevation.already_requested",
+    ElevationInvalidDuration => "elevation.invalid_duration",
+    ElevationNotActive => "elevation.not_active",
+    ElevationNotFound => "elevation.not_found",
+    ElevationNotPending => "elevation.not_pending",
+    ElevationReasonRequired => "elevation.reason_required",
+
+    RateLimited => "errors.rate_limited",
+
+    FileAlreadyExists => "files.already_exists",
+    FileBatchConflict => "files.batch_conflict",
+    FileBatchEmpty => "files.batch_empty",
+    FileBatchFailed => "files.batch_failed",
+    FileBatchTooLarge => "files.batch_too_large",
+    FileInvalidPath => "files.invalid_path",
+    FileInvalidPermissions => "files.invalid_permissions",
+    FileIsADirectory => "files.is_a_directory",
+    FileNotADirectory => "files.not_a_directory",
+    FileNotFound => "files.not_found",
+    FileProtectedPath => "files.protected_path",
+
+    HookCommandNotApplicable => "hooks.command_not_applicable",
+    HookCommandRequired => "hooks.command_required",
+    HookInvalidAction => "hooks.invalid_action",
+    HookInvalidIpRule => "hooks.invalid_ip_rule",
+    HookInvalidNonce => "hooks.invalid_nonce",
+    HookInvalidSignature => "hooks.invalid_signature",
+    HookInvalidTimestamp => "hooks.invalid_timestamp",
+    HookIpNotAllowed => "hooks.ip_not_allowed",
+    HookNameRequired => "hooks.name_required",
+    HookNotFound => "hooks.not_found",
+    HookReplayed => "hooks.replayed",
+    HookSignatureMissing => "hooks.signature_missing",
+    HookTimestampExpired => "hooks.timestamp_expired",
+
+    IpBanInvalidAddress => "ip_bans.invalid_address",
+    IpBanInvalidDuration => "ip_bans.invalid_duration",
+    IpBanNotFound => "ip_bans.not_found",
+    IpBanOwnAddress => "ip_bans.own_address",
+
+    LogAlreadyCompressed => "logs.already_compressed",
+    LogLiveFile => "logs.live_file",
+
+    OutgoingWebhookEventsRequired => "outgoing_webhooks.events_required",
+    OutgoingWebhookInvalidEvent => "outgoing_webhooks.invalid_event",
+    OutgoingWebhookInvalidUrl => "outgoing_webhooks.invalid_url",
+    OutgoingWebhookNameRequired => "outgoing_webhooks.name_required",
+    OutgoingWebhookNotFound => "outgoing_webhooks.not_found",
+
+    RoleTemplateNameRequired => "role_templates.name_required",
+    RoleTemplateNameTaken => "role_templates.name_taken",
+    RoleTemplateNotFound => "role_templates.not_found",
+
+    ServerTemplateInvalidSettings => "server_templates.invalid_settings",
+    ServerTemplateNameRequired => "server_templates.name_required",
+    ServerTemplateNameTaken => "server_templates.name_taken",
+    ServerTemplateNotFound => "server_templates.not_found",
+
+    ServerAccessDenied => "servers.access_denied",
+    ServerAlreadyActive => "servers.already_active",
+    ServerAlreadyQueued => "servers.already_queued",
+    ServerAlreadyRunning => "servers.already_running",
+    ServerAlreadySuspended => "servers.already_suspended",
+    ServerBindAddressUnavailable => "servers.bind_address_unavailable",
+    ServerBulkNoServers => "servers.bulk_no_servers",
+    ServerBundleMissing => "servers.bundle_missing",
+    ServerBundleUnsupported => "servers.bundle_unsupported",
+    ServerCommandTemplateRequired => "servers.command_template_required",
+    ServerDockerImageRequired => "servers.docker_image_required",
+    ServerDockerUnsupportedLaunchMode => "servers.docker_unsupported_launch_mode",
+    ServerDummyRequiresDevMode => "servers.dummy_requires_dev_mode",
+    ServerHostSettingsAdminOnly => "servers.host_settings_admin_only",
+    ServerImportEmpty => "servers.import_empty",
+    ServerImportUnsupportedVersion => "servers.import_unsupported_version",
+    ServerInstallAlreadyCompleted => "servers.install_already_completed",
+    ServerInstallInProgress => "servers.install_in_progress",
+    ServerInstallIncomplete => "servers.install_incomplete",
+    ServerInstalling => "servers.installing",
+    ServerInvalidAlertThreshold => "servers.invalid_alert_threshold",
+    ServerInvalidBackupFilenameTemplate => "servers.invalid_backup_filename_template",
+    ServerInvalidBackupSaveConfirm => "servers.invalid_backup_save_confirm",
+    ServerInvalidBindAddress => "servers.invalid_bind_address",
+    ServerInvalidBundle => "servers.invalid_bundle",
+    ServerInvalidConsoleLogMaxSize => "servers.invalid_console_log_max_size",
+    ServerInvalidDiscordColor => "servers.invalid_discord_color",
+    ServerInvalidDiscordEmoji => "servers.invalid_discord_emoji",
+    ServerInvalidEnvVar => "servers.invalid_env_var",
+    ServerInvalidGameConfig => "servers.invalid_game_config",
+    ServerInvalidHealthProbe => "servers.invalid_health_probe",
+    ServerInvalidHealthProbeInterval => "servers.invalid_health_probe_interval",
+    ServerInvalidLaunchMode => "servers.invalid_launch_mode",
+    ServerInvalidLocale => "servers.invalid_locale",
+    ServerInvalidNetworkRole => "servers.invalid_network_role",
+    ServerInvalidParentProxy => "servers.invalid_parent_proxy",
+    ServerInvalidPermission => "servers.invalid_permission",
+    ServerInvalidRequest => "servers.invalid_request",
+    ServerInvalidRestartSchedule => "servers.invalid_restart_schedule",
+    ServerInvalidRuntime => "servers.invalid_runtime",
+    ServerInvalidStatsRange => "servers.invalid_stats_range",
+    ServerInvalidTag => "servers.invalid_tag",
+    ServerNotFound => "servers.not_found",
+    ServerNotRunning => "servers.not_running",
+    ServerNotSuspended => "servers.not_suspended",
+    ServerOperationInProgress => "servers.operation_in_progress",
+    ServerParentNotProxy => "servers.parent_not_proxy",
+    ServerPermissionDenied => "servers.permission_denied",
+    ServerProxyCannotHaveParent => "servers.proxy_cannot_have_parent",
+    ServerProxyHasMembers => "servers.proxy_has_members",
+    ServerStartCancelled => "servers.start_cancelled",
+    ServerSuspendUnsupported => "servers.suspend_unsupported",
+    ServerTooManyTags => "servers.too_many_tags",
+    ServerTransferAlreadyPending => "servers.transfer_already_pending",
+    ServerTransferNotFound => "servers.transfer_not_found",
+    ServerTransferNotOwner => "servers.transfer_not_owner",
+    ServerTransferNotPending => "servers.transfer_not_pending",
+    ServerTransferNotRecipient => "servers.transfer_not_recipient",
+    ServerTransferSameOwner => "servers.transfer_same_owner",
+    ServerWorkingDirOutsideServersDir => "servers.working_dir_outside_servers_dir",
+    ServerWorkingDirRunning => "servers.working_dir_running",
+
+    SettingsInvalidAllowedOrigin => "settings.invalid_allowed_origin",
+    SettingsInvalidAuthPatterns => "settings.invalid_auth_patterns",
+    SettingsInvalidBackupCompression => "settings.invalid_backup_compression",
+    SettingsInvalidBackupCompressionLevel => "settings.invalid_backup_compression_level",
+    SettingsInvalidBackupEncryptionKey => "settings.invalid_backup_encryption_key",
+    SettingsInvalidBackupQuotaMinPerServer => "settings.invalid_backup_quota_min_per_server",
+    SettingsInvalidBackupTargetProtocol => "settings.invalid_backup_target_protocol",
+    SettingsInvalidDailySummaryScope => "settings.invalid_daily_summary_scope",
+    SettingsInvalidDailySummaryTime => "settings.invalid_daily_summary_time",
+    SettingsInvalidDbBackupKeep => "settings.invalid_db_backup_keep",
+    SettingsInvalidDownloaderUrl => "settings.invalid_downloader_url",
+    SettingsInvalidFieldVisibility => "settings.invalid_field_visibility",
+    SettingsInvalidMaxConcurrentStarts => "settings.invalid_max_concurrent_starts",
+    SettingsInvalidMetricsInterval => "settings.invalid_metrics_interval",
+    SettingsInvalidOidcDefaultRole => "settings.invalid_oidc_default_role",
+    SettingsInvalidOidcUrl => "settings.invalid_oidc_url",
+    SettingsInvalidProtectedPath => "settings.invalid_protected_path",
+    SettingsInvalidRateLimit => "settings.invalid_rate_limit",
+    SettingsInvalidS3Endpoint => "settings.invalid_s3_endpoint",
+    SettingsInvalidSmtpPanelUrl => "settings.invalid_smtp_panel_url",
+    SettingsInvalidSmtpSecurity => "settings.invalid_smtp_security",
+    SettingsSystemdUnavailable => "settings.systemd_unavailable",
+
+    SetupAlreadyCompleted => "setup.already_completed",
+
+    SystemDatabaseBackupNotFound => "system.database_backup_not_found",
+    SystemDatabaseRestoreFailed => "system.database_restore_failed",
+    SystemSupportBundleNotFound => "system.support_bundle_not_found",
+    SystemSupportBundleNotReady => "system.support_bundle_not_ready",
+
+    TokenInvalidRateLimit => "tokens.invalid_rate_limit",
+    TokenInvalidScope => "tokens.invalid_scope",
+    TokenNameRequired => "tokens.name_required",
+    TokenNotFound => "tokens.not_found",
+    TokenRateLimited => "tokens.rate_limited",
+    TokenSessionRequired => "tokens.session_required",
+    TokenWriteScopeRequired => "tokens.write_scope_required",
+
+    UploadFileRequired => "upload.file_required",
+    UploadImageRequired => "upload.image_required",
+    UploadInvalidMultipart => "upload.invalid_multipart",
+
+    UserDeleteConfirmMismatch => "users.delete_confirm_mismatch",
+    UserExists => "users.exists",
+    UserInvalidEmail => "users.invalid_email",
+    UserInvalidRole => "users.invalid_role",
+    UserLastAdmin => "users.last_admin",
+    UserNotFound => "users.not_found",
+
+    WebhookUrlMissing => "webhook.url_missing",
+}
 
 #[derive(Debug)]
 pub enum AppError {
     NotFound(String),
     BadRequest(String),
     Unauthorized(String),
+    /// Authenticated, but the role doesn't allow it
+    Forbidden(String),
+    TooManyRequests(String),
+    /// The resource is busy with another operation
+    Conflict(String),
     Internal(String),
     Database(String),
 }
@@ -20,33 +298,84 @@ impl fmt::Display for AppError {
             AppError::NotFound(msg) => write!(f, "Not found: {}", msg),
             AppError::BadRequest(msg) => write!(f, "Bad request: {}", msg),
             AppError::Unauthorized(msg) => write!(f, "Unauthorized: {}", msg),
+            AppError::Forbidden(msg) => write!(f, "Forbidden: {}", msg),
+            AppError::TooManyRequests(msg) => write!(f, "Too many requests: {}", msg),
+            AppError::Conflict(msg) => write!(f, "Conflict: {}", msg),
             AppError::Internal(msg) => write!(f, "Internal error: {}", msg),
             AppError::Database(msg) => write!(f, "Database error: {}", msg),
         }
     }
 }
 
-impl IntoResponse for AppError {
-    fn into_response(self) -> Response {
-        let (status, error_message) = match self {
-            AppError::NotFound(msg) => (StatusCode::NOT_FOUND, msg),
-            AppError::BadRequest(msg) => (StatusCode::BAD_REQUEST, msg),
-            AppError::Unauthorized(msg) => (StatusCode::UNAUTHORIZED, msg),
-            AppError::Internal(msg) => {
-                eprintln!("Internal Server Error: {}", msg);
-                (StatusCode::INTERNAL_SERVER_ERROR, "errors.internal".to_string())
-            }
-            AppError::Database(msg) => {
-                eprintln!("Database Error: {}", msg);
-                (StatusCode::INTERNAL_SERVER_ERROR, "errors.database".to_string())
+impl AppError {
+    pub fn status(&self) -> StatusCode {
+        match self {
+            AppError::NotFound(_) => StatusCode::NOT_FOUND,
+            AppError::BadRequest(_) => StatusCode::BAD_REQUEST,
+            AppError::Unauthorized(_) => StatusCode::UNAUTHORIZED,
+            AppError::Forbidden(_) => StatusCode::FORBIDDEN,
+            AppError::TooManyRequests(_) => StatusCode::TOO_MANY_REQUESTS,
+            AppError::Conflict(_) => StatusCode::CONFLICT,
+            AppError::Internal(_) | AppError::Database(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    /// Code of errors whose key has none, and key of those carrying plain text
+    fn fallback(&self) -> (ErrorCode, &'static str) {
+        match self {
+            AppError::NotFound(_) => (ErrorCode::NotFound, "errors.not_found"),
+            AppError::BadRequest(_) => (ErrorCode::BadRequest, "errors.bad_request"),
+            AppError::Unauthorized(_) => (ErrorCode::Unauthorized, "errors.unauthorized"),
+            AppError::Forbidden(_) => (ErrorCode::Forbidden, "errors.forbidden"),
+            AppError::TooManyRequests(_) => (ErrorCode::TooManyRequests, "errors.too_many_requests"),
+            AppError::Conflict(_) => (ErrorCode::Conflict, "errors.conflict"),
+            AppError::Internal(_) => (ErrorCode::Internal, "errors.internal"),
+            AppError::Database(_) => (ErrorCode::Database, "errors.database"),
+        }
+    }
+
+    /// What the client is told: messages are a key, optionally followed by `": "` and a
+    /// detail (`"upload.invalid_multipart: <cause>"`). Internal and database errors only
+    /// give their generic key, their message stays in the logs.
+    pub fn body(&self) -> ErrorBody {
+        let (fallback_code, fallback_key) = self.fallback();
+        let (key, detail) = match self {
+            AppError::Internal(_) | AppError::Database(_) => (fallback_key.to_string(), None),
+            AppError::NotFound(msg) | AppError::BadRequest(msg) | AppError::Unauthorized(msg)
+            | AppError::Forbidden(msg) | AppError::TooManyRequests(msg) | AppError::Conflict(msg) => {
+                match msg.split_once(": ") {
+                    Some((key, detail)) if is_key(key) => (key.to_string(), Some(detail.to_string())),
+                    _ if is_key(msg) => (msg.clone(), None),
+                    _ => (fallback_key.to_string(), Some(msg.clone())),
+                }
             }
         };
+        ErrorBody {
+            code: ErrorCode::from_key(&key).unwrap_or(fallback_code),
+            error: key.clone(),
+            key,
+            detail,
+            request_id: request_id::current(),
+        }
+    }
+}
 
-        let body = Json(serde_json::json!({
-            "error": error_message
-        }));
+/// Translation keys look like `servers.not_found`
+fn is_key(s: &str) -> bool {
+    s.contains('.')
+        && !s.starts_with('.')
+        && !s.ends_with('.')
+        && s.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_' || c == '.')
+}
 
-        (status, body).into_response()
+impl IntoResponse for AppError {
+    fn into_response(self) -> Response {
+        match &self {
+            AppError::Internal(msg) => tracing::error!("Internal Server Error: {}", msg),
+            AppError::Database(msg) => tracing::error!("Database Error: {}", msg),
+            _ => {}
+        }
+        (self.status(), Json(self.body())).into_response()
     }
 }
 
@@ -58,6 +387,6 @@ impl From<sqlx::Error> for AppError {
 
 impl From<jsonwebtoken::errors::Error> for AppError {
     fn from(err: jsonwebtoken::errors::Error) -> Self {
-        AppError::Unauthorized(err.to_string())
+        AppError::Unauthorized(format!("auth.invalid_token: {}", err))
     }
 }
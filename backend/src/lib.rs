@@ -0,0 +1,76 @@
+//! Panel library: the binary in `main.rs` and the integration tests both build on it
+
+use axum::{middleware, routing::{get, get_service}, Router};
+use std::sync::Arc;
+use tower_http::{
+    services::ServeDir,
+    trace::{DefaultOnResponse, TraceLayer},
+};
+use tracing::Level;
+
+pub mod api;
+pub mod config;
+pub mod db;
+pub mod error;
+pub mod models;
+pub mod services;
+pub mod templates;
+pub mod utils;
+
+use config::Settings;
+use db::DbPool;
+use services::events::EventBus;
+use services::rate_limit::RateLimiter;
+use services::{OperationLocks, ProcessManager};
+
+#[derive(Clone)]
+pub struct AppState {
+    pub pool: DbPool,
+    pub process_manager: ProcessManager,
+    pub settings: Arc<Settings>,
+    /// Backups, restores and reinstalls in progress, one per server
+    pub operations: OperationLocks,
+    /// What happens to servers, for notifications and the dashboard stream
+    pub events: EventBus,
+    /// Request budgets of the login, file write and command endpoints
+    pub rate_limiter: RateLimiter,
+}
+
+/// HTTP application: the API, uploaded files and the frontend
+pub fn app(state: AppState) -> Router {
+    let uploads_dir = state.settings.uploads_dir.clone();
+
+    let cors = services::cors::layer(&state.settings);
+
+    Router::new()
+        .nest("/api/v1", api::routes(state.clone())
+            .layer(middleware::from_fn_with_state(state.clone(), api::rate_limit::limit))
+            .layer(middleware::from_fn_with_state(state.clone(), api::tokens::authenticate))
+            .layer(middleware::from_fn(services::panel_metrics::track_requests)))
+
+        // Prometheus scrape endpoint, API tokens resolved as on the API
+        .route("/metrics", get(api::metrics::metrics).layer(middleware::from_fn_with_state(state.clone(), api::tokens::authenticate)))
+        
+        // Serve uploaded files
+        .nest_service("/uploads", get_service(ServeDir::new(&uploads_dir)))
+
+        // Interactive API documentation
+        .merge(api::openapi::swagger_ui())
+        
+        // Serve frontend in production (static files)
+        // With fallback to index.html for SPA routing
+        .nest_service("/", get_service(
+            ServeDir::new("./static")
+                .fallback(tower_http::services::ServeFile::new("./static/index.html"))
+        ))
+        
+        // Banned addresses get nothing, not even the frontend
+        .layer(middleware::from_fn(api::ip_bans::block_banned))
+        .layer(TraceLayer::new_for_http()
+            .make_span_with(services::request_id::span)
+            .on_response(DefaultOnResponse::new().level(Level::INFO)))
+        // Around the logs and bans so every response, error bodies included, carries its ID
+        .layer(middleware::from_fn(services::request_id::assign))
+        .layer(cors)
+        .with_state(state)
+}
@@ -1,50 +1,51 @@
-use axum::{
-    routing::{get_service},
-    Router,
-};
-use tower_http::{
-    cors::{CorsLayer, Any},
-    services::ServeDir,
-    trace::TraceLayer,
-};
+use std::future::Future;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
 use tracing::info;
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
-// Modules will be uncommented as they are migrated
-mod api;
-mod config;
-mod db;
-mod error;
-mod models;
-mod services;
-mod templates;
-mod utils;
-
-use config::Settings;
-use services::ProcessManager;
-use db::DbPool;
-use std::sync::Arc;
+use draveur_manager::{api, app, db, services, AppState};
+use draveur_manager::config::Settings;
+use draveur_manager::services::events::EventBus;
+use draveur_manager::services::rate_limit::RateLimiter;
+use draveur_manager::services::{simulator, OperationLocks, ProcessManager};
+use draveur_manager::utils::{log_buffer, tls};
+
+#[cfg(windows)]
+mod windows_service;
 
-#[derive(Clone)]
-pub struct AppState {
-    pub pool: DbPool,
-    pub process_manager: ProcessManager,
-    pub settings: Arc<Settings>,
+fn main() -> anyhow::Result<()> {
+    // Dummy servers run this same binary as their game process
+    let args: Vec<String> = std::env::args().collect();
+    if let Some(pos) = args.iter().position(|arg| arg == simulator::SIMULATE_FLAG) {
+        return simulator::run(simulator::SimulatorOptions::from_args(&args[pos + 1..]));
+    }
+
+    // On Windows the Service Control Manager launches us with --service
+    #[cfg(windows)]
+    if std::env::args().any(|arg| arg == "--service") {
+        return windows_service::run();
+    }
+
+    tokio::runtime::Runtime::new()?.block_on(run(shutdown_signal()))
 }
 
-#[tokio::main]
-async fn main() -> anyhow::Result<()> {
+/// Run the panel until `shutdown` resolves, then stop managed servers cleanly
+pub async fn run(shutdown: impl Future<Output = ()> + Send + 'static) -> anyhow::Result<()> {
     // Initialize tracing
     tracing_subscriber::registry()
         .with(tracing_subscriber::EnvFilter::new(
             std::env::var("RUST_LOG").unwrap_or_else(|_| "info".into()),
         ))
         .with(tracing_subscriber::fmt::layer())
+        .with(log_buffer::RecentLogs)
         .init();
 
     // Load configuration
     dotenvy::dotenv().ok();
     let settings = Settings::from_env();
+    api::auth::check_jwt_secret(settings.dev_mode)?;
 
     // Ensure data directory exists
     std::fs::create_dir_all("data").ok();
@@ -53,51 +54,84 @@ async fn main() -> anyhow::Result<()> {
     info!("🚀 Draveur Manager v{}", env!("CARGO_PKG_VERSION"));
     info!("📡 Starting server on {}:{}", settings.host, settings.port);
 
-    // Initialize database
+    // Initialize database, swapping in a restore staged before the last shutdown
+    if services::db_backup::apply_staged_restore(&settings.database_url)? {
+        info!("♻️ Panel database restored from backup");
+    }
     let pool = db::init_pool(&settings.database_url).await?;
     db::run_migrations(&pool).await?;
+    api::visibility::load(&pool).await;
+    services::auth_detection::load(&pool).await;
+    services::cors::load(&pool).await;
+    services::ip_bans::load(&pool).await?;
+    services::sessions::load(&pool).await?;
+    services::protected_paths::load(&pool).await;
 
     // Initialize services
-    let process_manager = ProcessManager::new(Some(pool.clone()));
+    let events = EventBus::default();
+    services::events::start_consumers(&pool, &events);
+    let process_manager = ProcessManager::new(Some(pool.clone()), events.clone());
+    let rate_limiter = RateLimiter::default();
+    rate_limiter.load(&pool).await;
 
     // Start background services
-    services::scheduler::start(pool.clone(), process_manager.clone());
+    services::scheduler::start(pool.clone(), process_manager.clone(), settings.backups_dir.clone());
+    api::servers::handlers::mark_interrupted_installs(&pool).await;
+    api::backups::mark_interrupted_jobs(&pool).await;
+    api::servers::handlers::auto_start_servers(&pool, &process_manager).await;
 
     let state = AppState {
         pool,
-        process_manager,
+        process_manager: process_manager.clone(),
         settings: Arc::new(settings.clone()),
+        operations: OperationLocks::default(),
+        events,
+        rate_limiter,
     };
-    
-    let uploads_dir = settings.uploads_dir.clone();
-
-    // CORS configuration
-    let cors = CorsLayer::new()
-        .allow_origin(Any)
-        .allow_methods(Any)
-        .allow_headers(Any);
-
-    let app = Router::new()
-        .nest("/api/v1", api::routes())
-        
-        // Serve uploaded files
-        .nest_service("/uploads", get_service(ServeDir::new(&uploads_dir)))
-        
-        // Serve frontend in production (static files)
-        // With fallback to index.html for SPA routing
-        .nest_service("/", get_service(
-            ServeDir::new("./static")
-                .fallback(tower_http::services::ServeFile::new("./static/index.html"))
-        ))
-        
-        .layer(TraceLayer::new_for_http())
-        .layer(cors)
-        .with_state(state);
 
     let addr = format!("{}:{}", settings.host, settings.port);
     let listener = tokio::net::TcpListener::bind(&addr).await?;
     
-    axum::serve(listener, app).await?;
+    // Peer addresses are needed by the inbound hooks IP allowlists
+    let service = app(state).into_make_service_with_connect_info::<SocketAddr>();
+    match settings.tls {
+        Some(ref tls) => {
+            let config = tls::rustls_config(tls).await?;
+            if let Some(port) = tls.http_redirect_port {
+                let redirect_addr = SocketAddr::new(listener.local_addr()?.ip(), port);
+                let https_port = settings.port;
+                tokio::spawn(async move {
+                    if let Err(e) = tls::redirect_http(redirect_addr, https_port).await {
+                        tracing::error!("HTTP redirect stopped: {:#}", e);
+                    }
+                });
+            }
+
+            let handle = axum_server::Handle::new();
+            let stopping = handle.clone();
+            tokio::spawn(async move {
+                shutdown.await;
+                stopping.graceful_shutdown(Some(Duration::from_secs(10)));
+            });
+            info!("🔒 HTTPS enabled");
+            axum_server::from_tcp_rustls(listener.into_std()?, config)
+                .handle(handle)
+                .serve(service)
+                .await?;
+        }
+        None => {
+            axum::serve(listener, service)
+                .with_graceful_shutdown(shutdown)
+                .await?;
+        }
+    }
+
+    info!("🛑 Shutting down, stopping managed servers...");
+    process_manager.stop_all().await;
 
     Ok(())
 }
+
+async fn shutdown_signal() {
+    let _ = tokio::signal::ctrl_c().await;
+}
@@ -0,0 +1,68 @@
+//! Panel announcement feed: GitHub releases (cached) and admin-authored notices
+
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+pub const RELEASES_URL: &str = "https://api.github.com/repos/thefrcrazy/Draveur-Manager/releases?per_page=10";
+pub const LEVELS: &[&str] = &["info", "maintenance", "warning"];
+
+/// How long the GitHub releases are kept before being fetched again
+const RELEASES_TTL: Duration = Duration::from_secs(3600);
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct Release {
+    pub tag_name: String,
+    pub name: Option<String>,
+    pub body: Option<String>,
+    pub html_url: String,
+    pub published_at: Option<String>,
+    #[serde(default)]
+    pub prerelease: bool,
+}
+
+lazy_static::lazy_static! {
+    static ref RELEASES_CACHE: Mutex<Option<(Instant, Vec<Release>)>> = Mutex::new(None);
+}
+
+/// Latest releases, served from cache when fresh. On fetch failure the stale cache
+/// (or an empty list) is returned so the feed keeps working offline.
+pub async fn releases() -> Vec<Release> {
+    let cached = RELEASES_CACHE.lock().unwrap().clone();
+    if let Some((fetched_at, list)) = &cached {
+        if fetched_at.elapsed() < RELEASES_TTL {
+            return list.clone();
+        }
+    }
+
+    match fetch_releases().await {
+        Ok(list) => {
+            *RELEASES_CACHE.lock().unwrap() = Some((Instant::now(), list.clone()));
+            list
+        }
+        Err(e) => {
+            tracing::warn!("Failed to fetch GitHub releases: {}", e);
+            cached.map(|(_, list)| list).unwrap_or_default()
+        }
+    }
+}
+
+async fn fetch_releases() -> anyhow::Result<Vec<Release>> {
+    let client = reqwest::Client::builder()
+        .timeout(Duration::from_secs(10))
+        .build()?;
+
+    let list = client
+        .get(RELEASES_URL)
+        .header("User-Agent", concat!("Draveur-Manager/", env!("CARGO_PKG_VERSION")))
+        .header("Accept", "application/vnd.github+json")
+        .send()
+        .await?
+        .error_for_status()?
+        .json::<Vec<Release>>()
+        .await?;
+
+    Ok(list)
+}
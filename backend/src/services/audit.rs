@@ -0,0 +1,88 @@
+//! Panel-wide audit trail: security-relevant actions, and what each user did to servers (the
+//! server's own lifecycle goes to `server_events`, see `history`). Entries of a user make up
+//! their activity feed.
+
+use serde::Serialize;
+use sqlx::FromRow;
+use utoipa::ToSchema;
+
+use crate::db::DbPool;
+use crate::services::request_id;
+
+/// Logins blocked after too many failures, detail is the locked key and its duration
+pub const ACTION_LOGIN_LOCKOUT: &str = "login_lockout";
+/// Reset link generated by an admin for a user / used to set a new password
+pub const ACTION_PASSWORD_RESET_ISSUED: &str = "password_reset_issued";
+pub const ACTION_PASSWORD_RESET: &str = "password_reset";
+/// Single sign-on identity attached to an existing account / account created for it
+pub const ACTION_SSO_LINKED: &str = "sso_linked";
+pub const ACTION_SSO_PROVISIONED: &str = "sso_provisioned";
+/// Address banned by an admin or automatically, detail is the address and duration / ban lifted
+pub const ACTION_IP_BANNED: &str = "ip_banned";
+pub const ACTION_IP_UNBANNED: &str = "ip_unbanned";
+/// Successful password or SSO login
+pub const ACTION_LOGIN: &str = "login";
+/// Server power actions, detail is the server name
+pub const ACTION_SERVER_START: &str = "server_start";
+pub const ACTION_SERVER_STOP: &str = "server_stop";
+pub const ACTION_SERVER_RESTART: &str = "server_restart";
+pub const ACTION_SERVER_KILL: &str = "server_kill";
+/// Account deleted by its own user
+pub const ACTION_ACCOUNT_DELETED: &str = "account_deleted";
+/// File API changes, detail is `server name: path`
+pub const ACTION_FILE_WRITE: &str = "file_write";
+pub const ACTION_FILE_DELETE: &str = "file_delete";
+/// Detail is `server name: from -> to`
+pub const ACTION_FILE_MOVE: &str = "file_move";
+
+#[derive(Debug, Serialize, FromRow, ToSchema)]
+pub struct AuditEntry {
+    pub id: i64,
+    pub user_id: Option<String>,
+    pub username: Option<String>,
+    pub action: String,
+    pub detail: Option<String>,
+    pub ip: Option<String>,
+    pub created_at: String,
+    /// Request that did it, matching the `X-Request-Id` response header and the log lines
+    pub request_id: Option<String>,
+}
+
+/// Append an entry, tagged with the request being handled; failures are only logged so the
+/// audited action still goes through
+pub async fn record(pool: &DbPool, user_id: Option<&str>, username: Option<&str>, action: &str, detail: Option<&str>, ip: Option<&str>) {
+    let result = sqlx::query(
+        "INSERT INTO audit_log (user_id, username, action, detail, ip, created_at, request_id) VALUES (?, ?, ?, ?, ?, ?, ?)"
+    )
+    .bind(user_id)
+    .bind(username)
+    .bind(action)
+    .bind(detail)
+    .bind(ip)
+    .bind(chrono::Utc::now().to_rfc3339())
+    .bind(request_id::current())
+    .execute(pool)
+    .await;
+
+    if let Err(e) = result {
+        tracing::warn!("Failed to record {} audit entry: {}", action, e);
+    }
+}
+
+/// Most recent entries, newest first
+pub async fn recent(pool: &DbPool, limit: u32) -> Result<Vec<AuditEntry>, sqlx::Error> {
+    sqlx::query_as("SELECT * FROM audit_log ORDER BY id DESC LIMIT ?")
+        .bind(limit)
+        .fetch_all(pool)
+        .await
+}
+
+/// Entries of one user, newest first; `before` pages back from an entry id
+pub async fn for_user(pool: &DbPool, user_id: &str, limit: u32, before: Option<i64>) -> Result<Vec<AuditEntry>, sqlx::Error> {
+    sqlx::query_as("SELECT * FROM audit_log WHERE user_id = ? AND id < ? ORDER BY id DESC LIMIT ?")
+        .bind(user_id)
+        .bind(before.unwrap_or(i64::MAX))
+        .bind(limit)
+        .fetch_all(pool)
+        .await
+}
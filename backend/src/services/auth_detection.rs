@@ -0,0 +1,141 @@
+//! Detection of authentication prompts in downloader and server output. Prompts are matched
+//! with per-game-type regexes from the `auth_patterns` setting, while links are picked up by a
+//! generic `https://` matcher so a reworded or translated output still yields the URL.
+
+use std::collections::HashMap;
+use std::sync::RwLock;
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use crate::db::DbPool;
+
+/// Game type whose patterns apply when a type has none of its own
+pub const FALLBACK_GAME_TYPE: &str = "hytale";
+
+/// Console line carrying the authentication link, for the frontend
+pub const AUTH_URL_PREFIX: &str = "[AUTH_URL]:";
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PatternSet {
+    /// A line matching one of these means the user has to authenticate
+    #[serde(default)]
+    pub prompts: Vec<String>,
+    /// Lines worth a diagnostic log when no prompt matched them
+    #[serde(default)]
+    pub important: Vec<String>,
+}
+
+/// Game type -> patterns
+pub type AuthPatterns = HashMap<String, PatternSet>;
+
+struct CompiledSet {
+    prompts: Vec<Regex>,
+    important: Vec<Regex>,
+}
+
+lazy_static::lazy_static! {
+    static ref PATTERNS: RwLock<AuthPatterns> = RwLock::new(default_patterns());
+    static ref COMPILED: RwLock<HashMap<String, CompiledSet>> = RwLock::new(compile(&default_patterns()).unwrap_or_default());
+    static ref URL_RE: Regex = Regex::new(r#"https://[^\s"'<>\x1b]+"#).unwrap();
+    static ref ANSI_RE: Regex = Regex::new(r"\x1b\[[0-9;?]*[A-Za-z]").unwrap();
+}
+
+pub fn default_patterns() -> AuthPatterns {
+    let hytale = PatternSet {
+        prompts: vec![
+            r"(?i)IMPORTANT.*(authentifier|authenticate)".to_string(),
+            r"No server tokens configured".to_string(),
+            r"/auth login to authenticate".to_string(),
+            r"(?i)user_code=".to_string(),
+        ],
+        important: vec![
+            r"(?i)\b(important|auth|oauth|tokens?|login|authenticate|authentifier)\b".to_string(),
+        ],
+    };
+    HashMap::from([(FALLBACK_GAME_TYPE.to_string(), hytale)])
+}
+
+fn compile(patterns: &AuthPatterns) -> Result<HashMap<String, CompiledSet>, String> {
+    let compile_all = |sources: &[String]| {
+        sources.iter()
+            .map(|p| Regex::new(p).map_err(|e| format!("{}: {}", p, e)))
+            .collect::<Result<Vec<_>, _>>()
+    };
+    patterns.iter()
+        .map(|(game_type, set)| {
+            Ok((game_type.clone(), CompiledSet {
+                prompts: compile_all(&set.prompts)?,
+                important: compile_all(&set.important)?,
+            }))
+        })
+        .collect()
+}
+
+/// Check that every pattern compiles, returning the first invalid one
+pub fn validate(patterns: &AuthPatterns) -> Result<(), String> {
+    compile(patterns).map(|_| ())
+}
+
+/// Load the saved patterns, called once at startup
+pub async fn load(pool: &DbPool) {
+    let saved: Option<String> = sqlx::query_scalar("SELECT value FROM settings WHERE key = 'auth_patterns'")
+        .fetch_optional(pool)
+        .await
+        .ok()
+        .flatten();
+    if let Some(patterns) = saved.and_then(|v| serde_json::from_str(&v).ok()) {
+        set(patterns);
+    }
+}
+
+pub fn get() -> AuthPatterns {
+    PATTERNS.read().map(|p| p.clone()).unwrap_or_default()
+}
+
+/// Replace the patterns; invalid ones are refused by `validate` before reaching here
+pub fn set(patterns: AuthPatterns) {
+    let Ok(compiled) = compile(&patterns) else {
+        return;
+    };
+    if let (Ok(mut current), Ok(mut current_compiled)) = (PATTERNS.write(), COMPILED.write()) {
+        *current = patterns;
+        *current_compiled = compiled;
+    }
+}
+
+/// What a line of output tells about authentication
+#[derive(Debug, Default)]
+pub struct Detection {
+    pub prompt: bool,
+    pub url: Option<String>,
+}
+
+/// Inspect one output line of a `game_type` server or its downloader
+pub fn inspect(game_type: &str, line: &str) -> Detection {
+    let line = ANSI_RE.replace_all(line, "");
+    let detection = Detection {
+        prompt: false,
+        url: extract_url(&line),
+    };
+
+    let Ok(compiled) = COMPILED.read() else {
+        return detection;
+    };
+    let Some(set) = compiled.get(game_type).or_else(|| compiled.get(FALLBACK_GAME_TYPE)) else {
+        return detection;
+    };
+
+    let prompt = set.prompts.iter().any(|re| re.is_match(&line));
+    if !prompt && set.important.iter().any(|re| re.is_match(&line)) {
+        tracing::info!("Unmatched {} output line (no auth prompt pattern): {}", game_type, line);
+    }
+    Detection { prompt, ..detection }
+}
+
+/// First `https://` link of a line, without trailing punctuation
+pub fn extract_url(line: &str) -> Option<String> {
+    URL_RE.find(line)
+        .map(|m| m.as_str().trim_end_matches(['.', ',', ';', ':', ')', ']', '!']).to_string())
+        .filter(|url| url.len() > "https://".len())
+}
@@ -0,0 +1,190 @@
+//! At-rest encryption of backup archives with AES-256-GCM, so copies sent to a bucket or an
+//! SFTP server don't expose player data. The archive is sealed in 64 KiB chunks (the STREAM
+//! construction): multi-GB worlds stay streamed and a truncated file fails to decrypt.
+//!
+//! Layout: `MAGIC`, the 7-byte nonce prefix, then the sealed chunks, the last one flagged.
+
+use std::io::{self, Read, Write};
+use std::path::Path;
+
+use aes_gcm::aead::generic_array::GenericArray;
+use aes_gcm::aead::rand_core::RngCore;
+use aes_gcm::aead::stream::{DecryptorBE32, EncryptorBE32};
+use aes_gcm::aead::{KeyInit, OsRng};
+use aes_gcm::Aes256Gcm;
+
+use crate::db::DbPool;
+
+const MAGIC: &[u8; 8] = b"KWENC\x00\x01\x00";
+const NONCE_PREFIX_LEN: usize = 7;
+const CHUNK_LEN: usize = 64 * 1024;
+const TAG_LEN: usize = 16;
+
+/// Appended to the archive extension, e.g. `.tar.zst.enc`
+pub const EXTENSION: &str = "enc";
+
+#[derive(Clone, Copy)]
+pub struct EncryptionKey([u8; 32]);
+
+impl std::fmt::Debug for EncryptionKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("EncryptionKey(..)")
+    }
+}
+
+impl EncryptionKey {
+    /// 64 hex characters, e.g. from `openssl rand -hex 32`
+    pub fn parse(value: &str) -> Option<Self> {
+        let bytes = hex::decode(value.trim()).ok()?;
+        Some(Self(bytes.try_into().ok()?))
+    }
+
+    fn cipher(&self) -> Aes256Gcm {
+        Aes256Gcm::new(GenericArray::from_slice(&self.0))
+    }
+}
+
+/// Key from the `backup_encryption_key` setting, `None` when backups are stored in clear
+pub async fn configured_key(pool: &DbPool) -> Option<EncryptionKey> {
+    let value: Option<String> = sqlx::query_scalar("SELECT value FROM settings WHERE key = 'backup_encryption_key'")
+        .fetch_optional(pool)
+        .await
+        .ok()
+        .flatten();
+    value.as_deref().and_then(EncryptionKey::parse)
+}
+
+fn decryption_failed() -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, "backup decryption failed (wrong key or corrupted archive)")
+}
+
+/// Seals everything written through it; `finish` must be called to write the last chunk
+pub struct EncryptWriter<W: Write> {
+    inner: W,
+    encryptor: Option<EncryptorBE32<Aes256Gcm>>,
+    buffer: Vec<u8>,
+}
+
+impl<W: Write> EncryptWriter<W> {
+    pub fn new(mut inner: W, key: &EncryptionKey) -> io::Result<Self> {
+        let mut prefix = [0u8; NONCE_PREFIX_LEN];
+        OsRng.fill_bytes(&mut prefix);
+        inner.write_all(MAGIC)?;
+        inner.write_all(&prefix)?;
+        Ok(Self {
+            inner,
+            encryptor: Some(EncryptorBE32::from_aead(key.cipher(), GenericArray::from_slice(&prefix))),
+            buffer: Vec::with_capacity(CHUNK_LEN),
+        })
+    }
+
+    /// Seal the last chunk and hand back the inner writer
+    pub fn finish(mut self) -> io::Result<W> {
+        let encryptor = self.encryptor.take().ok_or_else(|| io::Error::other("encryption already finished"))?;
+        let sealed = encryptor.encrypt_last(self.buffer.as_slice()).map_err(|_| io::Error::other("encryption failed"))?;
+        self.inner.write_all(&sealed)?;
+        Ok(self.inner)
+    }
+}
+
+impl<W: Write> Write for EncryptWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let n = buf.len().min(CHUNK_LEN - self.buffer.len());
+        self.buffer.extend_from_slice(&buf[..n]);
+        if self.buffer.len() == CHUNK_LEN {
+            let encryptor = self.encryptor.as_mut().ok_or_else(|| io::Error::other("encryption already finished"))?;
+            let sealed = encryptor.encrypt_next(self.buffer.as_slice()).map_err(|_| io::Error::other("encryption failed"))?;
+            self.inner.write_all(&sealed)?;
+            self.buffer.clear();
+        }
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// Opens what `EncryptWriter` sealed, failing on a wrong key, a tampered chunk or a missing end
+pub struct DecryptReader<R: Read> {
+    inner: R,
+    decryptor: Option<DecryptorBE32<Aes256Gcm>>,
+    plain: Vec<u8>,
+    pos: usize,
+    /// First byte of the next chunk, read to tell whether the current one is the last
+    lookahead: Option<u8>,
+}
+
+impl<R: Read> DecryptReader<R> {
+    pub fn new(mut inner: R, key: &EncryptionKey) -> io::Result<Self> {
+        let mut header = [0u8; MAGIC.len() + NONCE_PREFIX_LEN];
+        inner.read_exact(&mut header)?;
+        if !is_encrypted(&header) {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "not an encrypted backup"));
+        }
+        let prefix = &header[MAGIC.len()..];
+        Ok(Self {
+            inner,
+            decryptor: Some(DecryptorBE32::from_aead(key.cipher(), GenericArray::from_slice(prefix))),
+            plain: Vec::new(),
+            pos: 0,
+            lookahead: None,
+        })
+    }
+
+    fn next_byte(&mut self) -> io::Result<Option<u8>> {
+        let mut byte = [0u8; 1];
+        loop {
+            match self.inner.read(&mut byte) {
+                Ok(0) => return Ok(None),
+                Ok(_) => return Ok(Some(byte[0])),
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    fn open_next_chunk(&mut self) -> io::Result<()> {
+        let mut sealed = Vec::with_capacity(CHUNK_LEN + TAG_LEN);
+        sealed.extend(self.lookahead.take());
+        let missing = (CHUNK_LEN + TAG_LEN - sealed.len()) as u64;
+        (&mut self.inner).take(missing).read_to_end(&mut sealed)?;
+
+        self.lookahead = self.next_byte()?;
+        self.plain = if self.lookahead.is_some() {
+            let decryptor = self.decryptor.as_mut().ok_or_else(decryption_failed)?;
+            decryptor.decrypt_next(sealed.as_slice()).map_err(|_| decryption_failed())?
+        } else {
+            let decryptor = self.decryptor.take().ok_or_else(decryption_failed)?;
+            decryptor.decrypt_last(sealed.as_slice()).map_err(|_| decryption_failed())?
+        };
+        self.pos = 0;
+        Ok(())
+    }
+}
+
+impl<R: Read> Read for DecryptReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        while self.pos == self.plain.len() {
+            if self.decryptor.is_none() {
+                return Ok(0);
+            }
+            self.open_next_chunk()?;
+        }
+        let n = buf.len().min(self.plain.len() - self.pos);
+        buf[..n].copy_from_slice(&self.plain[self.pos..self.pos + n]);
+        self.pos += n;
+        Ok(n)
+    }
+}
+
+pub fn is_encrypted(magic: &[u8]) -> bool {
+    magic.starts_with(MAGIC)
+}
+
+/// Open the first chunk of an encrypted archive, to reject a wrong key before a restore
+/// starts overwriting files
+pub fn check_key(path: &Path, key: &EncryptionKey) -> io::Result<()> {
+    let mut reader = DecryptReader::new(std::fs::File::open(path)?, key)?;
+    reader.read(&mut [0u8; 1]).map(|_| ())
+}
@@ -0,0 +1,125 @@
+//! Disk budget of the backups directory across all servers. Once it is exceeded the oldest
+//! local archives go first, every server keeping its minimum, and Discord is warned. Backups
+//! with an offsite copy only lose their local file.
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use crate::db::DbPool;
+use crate::services::discord_service;
+use crate::services::remote_storage::{LOCATION_BOTH, LOCATION_REMOTE};
+
+/// Local backups each server keeps whatever the quota
+pub const DEFAULT_MIN_PER_SERVER: u32 = 1;
+
+#[derive(Debug, Clone, Copy)]
+pub struct QuotaConfig {
+    pub max_bytes: u64,
+    pub min_per_server: u32,
+}
+
+#[derive(Debug, Default)]
+pub struct QuotaReport {
+    pub used_bytes: u64,
+    pub pruned: usize,
+    pub reclaimed_bytes: u64,
+}
+
+/// The quota from the `backup_quota_mb` and `backup_quota_min_per_server` settings, `None`
+/// when no budget is set
+pub async fn load(pool: &DbPool) -> Option<QuotaConfig> {
+    let rows: Vec<(String, String)> = sqlx::query_as("SELECT key, value FROM settings WHERE key LIKE 'backup_quota_%'")
+        .fetch_all(pool)
+        .await
+        .ok()?;
+    let get = |key: &str| rows.iter().find(|(k, _)| k == key).and_then(|(_, v)| v.trim().parse::<u64>().ok());
+
+    let max_mb = get("backup_quota_mb").filter(|mb| *mb > 0)?;
+    Some(QuotaConfig {
+        max_bytes: max_mb * 1024 * 1024,
+        min_per_server: get("backup_quota_min_per_server").map_or(DEFAULT_MIN_PER_SERVER, |n| n as u32),
+    })
+}
+
+/// Prune the oldest local backups until the directory fits its budget
+pub async fn enforce(pool: &DbPool, backups_dir: &Path) -> Option<QuotaReport> {
+    let config = load(pool).await?;
+    let backups: Vec<(String, String, String, i64, String)> = match sqlx::query_as(
+        "SELECT id, server_id, filename, size_bytes, location FROM backups WHERE location != ? ORDER BY created_at ASC"
+    )
+    .bind(LOCATION_REMOTE)
+    .fetch_all(pool)
+    .await
+    {
+        Ok(backups) => backups,
+        Err(e) => {
+            tracing::warn!("Failed to list backups for the storage quota: {}", e);
+            return None;
+        }
+    };
+
+    let mut report = QuotaReport {
+        used_bytes: backups.iter().map(|b| b.3.max(0) as u64).sum(),
+        ..QuotaReport::default()
+    };
+    if report.used_bytes <= config.max_bytes {
+        return Some(report);
+    }
+
+    let mut kept: HashMap<String, u32> = HashMap::new();
+    for (_, server_id, ..) in &backups {
+        *kept.entry(server_id.clone()).or_default() += 1;
+    }
+
+    for (id, server_id, filename, size_bytes, location) in backups {
+        if report.used_bytes <= config.max_bytes {
+            break;
+        }
+        let count = kept.entry(server_id.clone()).or_default();
+        if *count <= config.min_per_server {
+            continue;
+        }
+
+        let path = backups_dir.join(&filename);
+        if let Err(e) = tokio::fs::remove_file(&path).await {
+            if e.kind() != std::io::ErrorKind::NotFound {
+                tracing::warn!("Failed to delete backup {:?} over quota: {}", path, e);
+                continue;
+            }
+        }
+        let result = if location == LOCATION_BOTH {
+            sqlx::query("UPDATE backups SET location = ? WHERE id = ?").bind(LOCATION_REMOTE).bind(&id).execute(pool).await
+        } else {
+            sqlx::query("DELETE FROM backups WHERE id = ?").bind(&id).execute(pool).await
+        };
+        if let Err(e) = result {
+            tracing::warn!("Failed to record pruning of backup {}: {}", filename, e);
+        }
+
+        *count -= 1;
+        let size = size_bytes.max(0) as u64;
+        report.used_bytes -= size;
+        report.reclaimed_bytes += size;
+        report.pruned += 1;
+    }
+
+    let still_over = report.used_bytes > config.max_bytes;
+    tracing::warn!(
+        "Backup quota exceeded: pruned {} backups ({} bytes), {} of {} bytes used",
+        report.pruned, report.reclaimed_bytes, report.used_bytes, config.max_bytes
+    );
+    let mb = |bytes: u64| bytes / (1024 * 1024);
+    let mut description = format!(
+        "Le quota de stockage des sauvegardes a été dépassé.\n**{}** sauvegarde(s) supprimée(s), {} Mo libérés.\nUtilisation : {} / {} Mo.",
+        report.pruned, mb(report.reclaimed_bytes), mb(report.used_bytes), mb(config.max_bytes)
+    );
+    if still_over {
+        description.push_str(&format!(
+            "\n⚠️ Le quota reste dépassé : chaque serveur conserve au moins {} sauvegarde(s).",
+            config.min_per_server
+        ));
+    }
+    discord_service::send_notification(pool, "💾 Quota de Sauvegardes Dépassé", &description, discord_service::COLOR_WARNING, None, None).await;
+
+    Some(report)
+}
@@ -1,10 +1,293 @@
 use std::fs::File;
+use std::io::{Read, Write};
 use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
 use flate2::write::GzEncoder;
 use flate2::read::GzDecoder;
-use flate2::Compression;
 use tar::Archive;
 
+use crate::db::DbPool;
+use crate::services::backup_crypto::{DecryptReader, EncryptWriter, EncryptionKey};
+
+/// Bytes needed to recognise any codec, up to the end of the tar `ustar` magic
+pub const SNIFF_LEN: usize = 262;
+
+/// How an archive is compressed, stored in `backups.compression`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Codec {
+    Gzip,
+    Zstd,
+    None,
+}
+
+impl Codec {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "gzip" => Some(Self::Gzip),
+            "zstd" => Some(Self::Zstd),
+            "none" => Some(Self::None),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Gzip => "gzip",
+            Self::Zstd => "zstd",
+            Self::None => "none",
+        }
+    }
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::Gzip => "tar.gz",
+            Self::Zstd => "tar.zst",
+            Self::None => "tar",
+        }
+    }
+
+    /// Accepted compression levels
+    pub fn levels(self) -> std::ops::RangeInclusive<i32> {
+        match self {
+            Self::Gzip => 0..=9,
+            Self::Zstd => 1..=22,
+            Self::None => 0..=0,
+        }
+    }
+
+    pub fn default_level(self) -> i32 {
+        match self {
+            Self::Gzip => 6,
+            Self::Zstd => 3,
+            Self::None => 0,
+        }
+    }
+
+    /// Codec of an archive from its first [`SNIFF_LEN`] bytes, for archives made elsewhere
+    pub fn sniff(magic: &[u8]) -> Option<Self> {
+        if magic.starts_with(&[0x1f, 0x8b]) {
+            Some(Self::Gzip)
+        } else if magic.starts_with(&[0x28, 0xb5, 0x2f, 0xfd]) {
+            Some(Self::Zstd)
+        } else if magic.len() >= SNIFF_LEN && &magic[257..SNIFF_LEN] == b"ustar" {
+            Some(Self::None)
+        } else {
+            None
+        }
+    }
+}
+
+/// Codec and level used to write an archive, `level` falling back to the codec's default
+#[derive(Debug, Clone, Copy)]
+pub struct Compression {
+    pub codec: Codec,
+    pub level: Option<i32>,
+}
+
+impl Default for Compression {
+    fn default() -> Self {
+        Self { codec: Codec::Gzip, level: None }
+    }
+}
+
+/// Compression chosen in the panel settings (`backup_compression`, `backup_compression_level`)
+pub async fn configured_compression(pool: &DbPool) -> Compression {
+    let rows: Vec<(String, String)> = sqlx::query_as(
+        "SELECT key, value FROM settings WHERE key IN ('backup_compression', 'backup_compression_level')"
+    )
+    .fetch_all(pool)
+    .await
+    .unwrap_or_default();
+
+    let mut compression = Compression::default();
+    for (key, value) in rows {
+        match key.as_str() {
+            "backup_compression" => compression.codec = Codec::parse(&value).unwrap_or(Codec::Gzip),
+            _ => compression.level = value.parse().ok(),
+        }
+    }
+    // A level saved for another codec doesn't apply
+    compression.level = compression.level.filter(|level| compression.codec.levels().contains(level));
+    compression
+}
+
+/// File under the compressor, sealing the compressed bytes when a key is given
+enum Sink {
+    Clear(File),
+    Encrypted(Box<EncryptWriter<File>>),
+}
+
+impl Sink {
+    fn finish(self) -> std::io::Result<()> {
+        match self {
+            Self::Clear(file) => file.sync_all(),
+            Self::Encrypted(writer) => writer.finish()?.sync_all(),
+        }
+    }
+}
+
+impl Write for Sink {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        match self {
+            Self::Clear(file) => file.write(buf),
+            Self::Encrypted(writer) => writer.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        match self {
+            Self::Clear(file) => file.flush(),
+            Self::Encrypted(writer) => writer.flush(),
+        }
+    }
+}
+
+/// Used when a server has no `backup_filename_template`
+pub const DEFAULT_FILENAME_TEMPLATE: &str = "{prefix}_{server_name}_{timestamp}";
+const FILENAME_VARIABLES: [&str; 6] = ["prefix", "server_name", "server_id", "date", "time", "timestamp"];
+/// Longest file name stem kept, the extension comes on top
+const MAX_FILENAME_LEN: usize = 120;
+
+lazy_static::lazy_static! {
+    static ref TEMPLATE_VARIABLE: regex::Regex = regex::Regex::new(r"\{([a-z_]*)\}").unwrap();
+}
+
+/// Whether a template only uses known `{variables}` and has no stray braces
+pub fn is_valid_filename_template(template: &str) -> bool {
+    let known = TEMPLATE_VARIABLE
+        .captures_iter(template)
+        .all(|c| FILENAME_VARIABLES.contains(&&c[1]));
+    let rest = TEMPLATE_VARIABLE.replace_all(template, "");
+    known && !rest.contains(['{', '}'])
+}
+
+/// Archive file name without extension from a template, reduced to characters safe on every
+/// filesystem. Names aren't parsed back: the `backups` table maps ids to files.
+pub fn render_filename(template: &str, prefix: &str, server_name: &str, server_id: &str, now: chrono::DateTime<chrono::Utc>) -> String {
+    let rendered = TEMPLATE_VARIABLE.replace_all(template, |c: &regex::Captures| match &c[1] {
+        "prefix" => prefix.to_string(),
+        "server_name" => server_name.to_string(),
+        "server_id" => server_id.to_string(),
+        "date" => now.format("%Y%m%d").to_string(),
+        "time" => now.format("%H%M%S").to_string(),
+        "timestamp" => now.format("%Y%m%d_%H%M%S_%3f").to_string(),
+        _ => String::new(),
+    });
+
+    let mut name = String::with_capacity(rendered.len());
+    for c in rendered.chars() {
+        let c = if c.is_ascii_alphanumeric() || c == '-' || c == '.' { c } else { '_' };
+        // No runs of separators, as left by spaces or an empty variable
+        if c == '_' && name.ends_with('_') {
+            continue;
+        }
+        name.push(c);
+    }
+    let name: String = name.trim_matches(['_', '.', '-']).chars().take(MAX_FILENAME_LEN).collect();
+    if name.is_empty() { format!("backup_{}", now.format("%Y%m%d_%H%M%S_%3f")) } else { name }
+}
+
+enum ArchiveWriter {
+    Gzip(GzEncoder<Sink>),
+    Zstd(zstd::stream::write::Encoder<'static, Sink>),
+    Plain(Sink),
+}
+
+impl ArchiveWriter {
+    fn create(path: &Path, compression: Compression, key: Option<&EncryptionKey>) -> std::io::Result<Self> {
+        let file = File::create(path)?;
+        let file = match key {
+            Some(key) => Sink::Encrypted(Box::new(EncryptWriter::new(file, key)?)),
+            None => Sink::Clear(file),
+        };
+        let level = compression.level.unwrap_or(compression.codec.default_level());
+        Ok(match compression.codec {
+            Codec::Gzip => Self::Gzip(GzEncoder::new(file, flate2::Compression::new(level.clamp(0, 9) as u32))),
+            Codec::Zstd => Self::Zstd(zstd::stream::write::Encoder::new(file, level)?),
+            Codec::None => Self::Plain(file),
+        })
+    }
+
+    fn finish(self) -> std::io::Result<()> {
+        match self {
+            Self::Gzip(encoder) => encoder.finish()?.finish(),
+            Self::Zstd(encoder) => encoder.finish()?.finish(),
+            Self::Plain(sink) => sink.finish(),
+        }
+    }
+}
+
+impl Write for ArchiveWriter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        match self {
+            Self::Gzip(encoder) => encoder.write(buf),
+            Self::Zstd(encoder) => encoder.write(buf),
+            Self::Plain(file) => file.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        match self {
+            Self::Gzip(encoder) => encoder.flush(),
+            Self::Zstd(encoder) => encoder.flush(),
+            Self::Plain(file) => file.flush(),
+        }
+    }
+}
+
+fn open_archive(path: &Path, codec: Codec, key: Option<&EncryptionKey>) -> std::io::Result<Archive<Box<dyn Read>>> {
+    decode(Box::new(File::open(path)?), codec, key)
+}
+
+/// Decrypt when a key is given, then decompress
+fn decode(input: Box<dyn Read>, codec: Codec, key: Option<&EncryptionKey>) -> std::io::Result<Archive<Box<dyn Read>>> {
+    let input: Box<dyn Read> = match key {
+        Some(key) => Box::new(DecryptReader::new(input, key)?),
+        None => input,
+    };
+    let reader: Box<dyn Read> = match codec {
+        Codec::Gzip => Box::new(GzDecoder::new(input)),
+        Codec::Zstd => Box::new(zstd::stream::read::Decoder::new(input)?),
+        Codec::None => input,
+    };
+    Ok(Archive::new(reader))
+}
+
+/// Where a backup or restore stands, reported after each file. Restores count archive bytes
+/// read, backups the file bytes archived.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Progress {
+    pub files: u64,
+    pub bytes: u64,
+    pub total_bytes: u64,
+}
+
+impl Progress {
+    pub fn percent(&self) -> u8 {
+        if self.total_bytes == 0 {
+            return 100;
+        }
+        (self.bytes.min(self.total_bytes) * 100 / self.total_bytes) as u8
+    }
+}
+
+/// Counts the bytes read through it
+struct CountingReader<R> {
+    inner: R,
+    count: Arc<AtomicU64>,
+}
+
+impl<R: Read> Read for CountingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.count.fetch_add(n as u64, Ordering::Relaxed);
+        Ok(n)
+    }
+}
+
 #[derive(Debug)]
 #[allow(dead_code)]
 pub enum BackupError {
@@ -18,7 +301,14 @@ impl From<std::io::Error> for BackupError {
     }
 }
 
-pub fn create_archive(source_dir: &str, backup_file_path: &str) -> Result<u64, BackupError> {
+/// Archive `source_dir`, encrypted when `key` is given, returning the archive size
+pub fn create_archive(
+    source_dir: &str,
+    backup_file_path: &str,
+    compression: Compression,
+    key: Option<&EncryptionKey>,
+    mut on_progress: impl FnMut(Progress),
+) -> Result<u64, BackupError> {
     let source_path = Path::new(source_dir);
     let backup_path = Path::new(backup_file_path);
 
@@ -31,24 +321,90 @@ pub fn create_archive(source_dir: &str, backup_file_path: &str) -> Result<u64, B
         std::fs::create_dir_all(parent)?;
     }
 
-    let tar_gz = File::create(backup_path)?;
-    let enc = GzEncoder::new(tar_gz, Compression::default());
-    let mut tar = tar::Builder::new(enc);
+    // Entries are listed first so the total is known before archiving starts
+    let entries: Vec<walkdir::DirEntry> = walkdir::WalkDir::new(source_path)
+        .follow_links(true)
+        .min_depth(1)
+        .into_iter()
+        .collect::<Result<_, _>>()
+        .map_err(|e| BackupError::IoError(e.into()))?;
+    let mut progress = Progress {
+        total_bytes: entries.iter().filter(|e| e.file_type().is_file()).filter_map(|e| e.metadata().ok()).map(|m| m.len()).sum(),
+        ..Progress::default()
+    };
+    on_progress(progress);
+
+    let mut tar = tar::Builder::new(ArchiveWriter::create(backup_path, compression, key)?);
+
+    // Archive the content OF the directory, relative to source_dir
+    for entry in &entries {
+        let name = Path::new(".").join(entry.path().strip_prefix(source_path).unwrap_or(entry.path()));
+        if entry.file_type().is_dir() {
+            tar.append_dir(&name, entry.path())?;
+        } else {
+            tar.append_path_with_name(entry.path(), &name)?;
+            progress.files += 1;
+            progress.bytes += entry.metadata().map(|m| m.len()).unwrap_or(0);
+            on_progress(progress);
+        }
+    }
 
-    // Add directory content recursivly
-    // We add the content OF the directory, not the directory itself as top level if possible, 
-    // or we add "."? 
-    // Usually standard is to archive the content relative to source_dir.
-    tar.append_dir_all(".", source_path)?;
-    
-    tar.finish()?;
+    tar.into_inner()?.finish()?;
 
     // Get size
     let metadata = std::fs::metadata(backup_path)?;
     Ok(metadata.len())
 }
 
-pub fn extract_archive(backup_file_path: &str, dest_dir: &str) -> Result<(), BackupError> {
+/// Archive only the listed entries of `source_dir` (those that exist), returning the archive size
+pub fn create_partial_archive(source_dir: &str, entries: &[&str], backup_file_path: &str, compression: Compression) -> Result<u64, BackupError> {
+    let source_path = Path::new(source_dir);
+    let backup_path = Path::new(backup_file_path);
+
+    if !source_path.exists() {
+        return Err(BackupError::PathError(format!("Source directory not found: {}", source_dir)));
+    }
+    if let Some(parent) = backup_path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+
+    let mut tar = tar::Builder::new(ArchiveWriter::create(backup_path, compression, None)?);
+
+    for entry in entries {
+        let path = source_path.join(entry);
+        if path.is_dir() {
+            tar.append_dir_all(entry, &path)?;
+        } else if path.is_file() {
+            tar.append_path_with_name(&path, entry)?;
+        }
+    }
+
+    tar.into_inner()?.finish()?;
+
+    let metadata = std::fs::metadata(backup_path)?;
+    Ok(metadata.len())
+}
+
+/// Path of an entry relative to the archive root, without `./` or a trailing `/`
+fn relative_path(path: &Path) -> String {
+    path.to_string_lossy().trim_start_matches("./").trim_end_matches('/').to_string()
+}
+
+/// Whether `path` is one of `selected` or lies under one of them
+fn is_selected(path: &str, selected: &[String]) -> bool {
+    selected.iter().any(|s| path == s || path.strip_prefix(s.as_str()).is_some_and(|rest| rest.starts_with('/')))
+}
+
+/// Unpack an archive into `dest_dir`, only the `selected` paths (files or directories,
+/// relative to the archive root) when the list isn't empty. Returns the entries restored.
+pub fn extract_archive(
+    backup_file_path: &str,
+    dest_dir: &str,
+    codec: Codec,
+    key: Option<&EncryptionKey>,
+    selected: &[String],
+    mut on_progress: impl FnMut(Progress),
+) -> Result<u64, BackupError> {
     let backup_path = Path::new(backup_file_path);
     let dest_path = Path::new(dest_dir);
 
@@ -60,12 +416,100 @@ pub fn extract_archive(backup_file_path: &str, dest_dir: &str) -> Result<(), Bac
         std::fs::create_dir_all(dest_path)?;
     }
 
-    let tar_gz = File::open(backup_path)?;
-    let tar = GzDecoder::new(tar_gz);
-    let mut archive = Archive::new(tar);
+    let file = File::open(backup_path)?;
+    let mut progress = Progress { total_bytes: file.metadata()?.len(), ..Progress::default() };
+    let read = Arc::new(AtomicU64::new(0));
+    let mut archive = decode(Box::new(CountingReader { inner: file, count: read.clone() }), codec, key)?;
+    on_progress(progress);
+
+    // Unpack entry by entry to report progress; `unpack_in` refuses paths leaving `dest_path`
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        if !selected.is_empty() && !is_selected(&relative_path(&entry.path()?), selected) {
+            continue;
+        }
+        entry.unpack_in(dest_path)?;
+        progress.files += 1;
+        progress.bytes = read.load(Ordering::Relaxed);
+        on_progress(progress);
+    }
+
+    Ok(progress.files)
+}
+
+/// A file or directory stored in an archive
+#[derive(Debug, Clone, Serialize)]
+pub struct ArchiveEntry {
+    /// Relative to the server directory, without the leading `./`
+    pub path: String,
+    pub size: u64,
+    /// Unix timestamp of the last modification
+    pub mtime: u64,
+    pub is_dir: bool,
+}
+
+/// What an archive holds, read without extracting. Entries under `prefix` are listed up to
+/// `limit`; the totals cover the whole archive.
+pub struct ArchiveListing {
+    pub entries: Vec<ArchiveEntry>,
+    pub total_files: u64,
+    pub total_bytes: u64,
+    pub truncated: bool,
+}
+
+pub fn list_entries(
+    backup_file_path: &str,
+    codec: Codec,
+    key: Option<&EncryptionKey>,
+    prefix: &str,
+    limit: usize,
+) -> Result<ArchiveListing, BackupError> {
+    let mut archive = open_archive(Path::new(backup_file_path), codec, key)?;
+    let prefix = prefix.trim_start_matches("./").trim_start_matches('/');
+    let mut listing = ArchiveListing { entries: Vec::new(), total_files: 0, total_bytes: 0, truncated: false };
+
+    for entry in archive.entries()? {
+        let entry = entry?;
+        let header = entry.header();
+        let is_dir = header.entry_type().is_dir();
+        let size = header.size()?;
+        if !is_dir {
+            listing.total_files += 1;
+            listing.total_bytes += size;
+        }
+
+        let path = relative_path(&entry.path()?);
+        if path.is_empty() || !path.starts_with(prefix) {
+            continue;
+        }
+        if listing.entries.len() == limit {
+            listing.truncated = true;
+            continue;
+        }
+        listing.entries.push(ArchiveEntry { path, size, mtime: header.mtime()?, is_dir });
+    }
+    Ok(listing)
+}
+
+/// Hex SHA-256 of an archive file, stored with the backup to detect later corruption
+pub fn checksum(path: &str) -> std::io::Result<String> {
+    let mut hasher = Sha256::new();
+    std::io::copy(&mut File::open(path)?, &mut hasher)?;
+    Ok(hex::encode(hasher.finalize()))
+}
 
-    // Unpack
-    archive.unpack(dest_path)?;
+/// Read an archive through to the end, so a truncated or corrupt upload is rejected before it
+/// is registered, and refuse entries that would land outside the restore directory
+pub fn verify_archive(backup_file_path: &str, codec: Codec, key: Option<&EncryptionKey>) -> Result<(), BackupError> {
+    let mut archive = open_archive(Path::new(backup_file_path), codec, key)?;
 
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        let path = entry.path()?.into_owned();
+        if path.components().any(|c| matches!(c, std::path::Component::ParentDir | std::path::Component::RootDir | std::path::Component::Prefix(_))) {
+            return Err(BackupError::PathError(format!("Entry outside the archive root: {}", path.display())));
+        }
+        std::io::copy(&mut entry, &mut std::io::sink())?;
+    }
     Ok(())
 }
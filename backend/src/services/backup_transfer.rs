@@ -0,0 +1,163 @@
+//! Push finished backup archives to an SFTP or FTP server, for setups without an S3 bucket.
+//! The destination only receives copies: backups are still restored from the local file.
+//! Failed transfers are retried, then reported on the server's Discord webhook.
+
+use std::fs::File;
+use std::net::TcpStream;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::db::DbPool;
+use crate::services::discord_service;
+
+pub const PROTOCOL_SFTP: &str = "sftp";
+pub const PROTOCOL_FTP: &str = "ftp";
+
+/// Waits before the 2nd and 3rd attempts
+const RETRY_DELAYS: [Duration; 2] = [Duration::from_secs(30), Duration::from_secs(120)];
+const IO_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Empty disables the transfer
+pub fn is_valid_protocol(protocol: &str) -> bool {
+    protocol.is_empty() || protocol == PROTOCOL_SFTP || protocol == PROTOCOL_FTP
+}
+
+#[derive(Debug, Clone)]
+pub struct TransferTarget {
+    pub protocol: String,
+    pub host: String,
+    pub port: u16,
+    pub username: String,
+    pub password: String,
+    /// Remote directory receiving the archives
+    pub path: String,
+    /// SFTP private key, the password then unlocks it
+    pub key_path: Option<String>,
+    /// Expected SHA-256 of the SFTP host key (hex), checked when set
+    pub fingerprint: Option<String>,
+}
+
+/// The destination from the `backup_target_*` settings, `None` when disabled
+pub async fn load(pool: &DbPool) -> Option<TransferTarget> {
+    let rows: Vec<(String, String)> = sqlx::query_as("SELECT key, value FROM settings WHERE key LIKE 'backup_target_%'")
+        .fetch_all(pool)
+        .await
+        .ok()?;
+    let get = |key: &str| rows.iter().find(|(k, _)| k == key).map(|(_, v)| v.trim().to_string()).unwrap_or_default();
+    let optional = |key: &str| Some(get(key)).filter(|v| !v.is_empty());
+
+    let protocol = get("backup_target_protocol");
+    let host = get("backup_target_host");
+    if protocol.is_empty() || host.is_empty() {
+        return None;
+    }
+    let default_port = if protocol == PROTOCOL_SFTP { 22 } else { 21 };
+    Some(TransferTarget {
+        port: get("backup_target_port").parse().unwrap_or(default_port),
+        protocol,
+        host,
+        username: get("backup_target_username"),
+        password: get("backup_target_password"),
+        path: get("backup_target_path"),
+        key_path: optional("backup_target_key_path"),
+        fingerprint: optional("backup_target_fingerprint"),
+    })
+}
+
+impl TransferTarget {
+    fn remote_path(&self, filename: &str) -> String {
+        let dir = self.path.trim_end_matches('/');
+        if dir.is_empty() { filename.to_string() } else { format!("{}/{}", dir, filename) }
+    }
+
+    /// Upload under a `.part` name then rename, so the destination never shows a truncated archive
+    fn push(&self, local: &Path, filename: &str) -> anyhow::Result<()> {
+        let remote = self.remote_path(filename);
+        let partial = format!("{}.part", remote);
+        match self.protocol.as_str() {
+            PROTOCOL_SFTP => self.push_sftp(local, &partial, &remote),
+            PROTOCOL_FTP => self.push_ftp(local, &partial, &remote),
+            other => anyhow::bail!("unknown transfer protocol {}", other),
+        }
+    }
+
+    fn push_sftp(&self, local: &Path, partial: &str, remote: &str) -> anyhow::Result<()> {
+        let tcp = TcpStream::connect((self.host.as_str(), self.port))?;
+        tcp.set_read_timeout(Some(IO_TIMEOUT))?;
+        tcp.set_write_timeout(Some(IO_TIMEOUT))?;
+
+        let mut session = ssh2::Session::new()?;
+        session.set_tcp_stream(tcp);
+        session.handshake()?;
+        if let Some(ref expected) = self.fingerprint {
+            let actual = session.host_key_hash(ssh2::HashType::Sha256).map(hex::encode).unwrap_or_default();
+            if !actual.eq_ignore_ascii_case(expected.replace(':', "").as_str()) {
+                anyhow::bail!("host key fingerprint mismatch (got {})", actual);
+            }
+        }
+        match self.key_path {
+            Some(ref key) => {
+                let passphrase = Some(self.password.as_str()).filter(|p| !p.is_empty());
+                session.userauth_pubkey_file(&self.username, None, Path::new(key), passphrase)?;
+            }
+            None => session.userauth_password(&self.username, &self.password)?,
+        }
+
+        let sftp = session.sftp()?;
+        let mut out = sftp.create(Path::new(partial))?;
+        std::io::copy(&mut File::open(local)?, &mut out)?;
+        drop(out);
+        sftp.rename(Path::new(partial), Path::new(remote), None)?;
+        Ok(())
+    }
+
+    fn push_ftp(&self, local: &Path, partial: &str, remote: &str) -> anyhow::Result<()> {
+        let mut ftp = suppaftp::FtpStream::connect((self.host.as_str(), self.port))?;
+        ftp.get_ref().set_read_timeout(Some(IO_TIMEOUT))?;
+        ftp.login(&self.username, &self.password)?;
+        ftp.transfer_type(suppaftp::types::FileType::Binary)?;
+        ftp.put_file(partial, &mut File::open(local)?)?;
+        ftp.rename(partial, remote)?;
+        let _ = ftp.quit();
+        Ok(())
+    }
+}
+
+/// Send a backup to the configured destination in the background, retrying before giving up
+pub fn spawn_push(pool: DbPool, backups_dir: PathBuf, server_id: String, filename: String) {
+    tokio::spawn(async move {
+        let Some(target) = load(&pool).await else {
+            return;
+        };
+        let local = backups_dir.join(&filename);
+
+        let mut last_error = String::new();
+        for attempt in 0..=RETRY_DELAYS.len() {
+            if attempt > 0 {
+                tokio::time::sleep(RETRY_DELAYS[attempt - 1]).await;
+            }
+            let (pushed, local, name) = (target.clone(), local.clone(), filename.clone());
+            let result = tokio::task::spawn_blocking(move || pushed.push(&local, &name))
+                .await
+                .map_err(anyhow::Error::from)
+                .and_then(|r| r);
+            match result {
+                Ok(()) => {
+                    tracing::info!("Backup {} sent to {}://{}", filename, target.protocol, target.host);
+                    return;
+                }
+                Err(e) => {
+                    tracing::warn!("Transfer of backup {} failed (attempt {}): {}", filename, attempt + 1, e);
+                    last_error = e.to_string();
+                }
+            }
+        }
+
+        tracing::error!("Giving up on sending backup {} to {}", filename, target.host);
+        let description = format!(
+            "La sauvegarde **{}** n'a pas pu être envoyée vers {}://{} après {} tentatives.\n```{}```",
+            filename, target.protocol, target.host, RETRY_DELAYS.len() + 1, last_error
+        );
+        discord_service::notify_server(&pool, &server_id, "📤 Envoi de Sauvegarde Échoué", &description, discord_service::COLOR_ERROR).await;
+    });
+}
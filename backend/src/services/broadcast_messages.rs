@@ -0,0 +1,69 @@
+//! Translatable in-game broadcast messages (restart warnings, watchdog)
+
+use crate::error::AppError;
+use crate::services::ProcessManager;
+
+pub const DEFAULT_LOCALE: &str = "fr";
+pub const SUPPORTED_LOCALES: &[&str] = &["fr", "en", "de", "es"];
+
+/// Raw template for `key` in `locale`, falling back to English then French
+fn template(locale: &str, key: &str) -> Option<&'static str> {
+    let found = match (locale, key) {
+        ("fr", "restart_warning") => Some("Redémarrage du serveur dans {time}."),
+        ("fr", "restart_now") => Some("Redémarrage du serveur maintenant !"),
+        ("fr", "watchdog_restart") => Some("Le serveur a planté et redémarre automatiquement."),
+        ("fr", "time_seconds") => Some("{count} seconde(s)"),
+        ("fr", "time_minutes") => Some("{count} minute(s)"),
+
+        ("en", "restart_warning") => Some("Server restarting in {time}."),
+        ("en", "restart_now") => Some("Server restarting now!"),
+        ("en", "watchdog_restart") => Some("The server crashed and is restarting automatically."),
+        ("en", "time_seconds") => Some("{count} second(s)"),
+        ("en", "time_minutes") => Some("{count} minute(s)"),
+
+        ("de", "restart_warning") => Some("Server-Neustart in {time}."),
+        ("de", "restart_now") => Some("Server wird jetzt neu gestartet!"),
+        ("de", "watchdog_restart") => Some("Der Server ist abgestürzt und startet automatisch neu."),
+        ("de", "time_seconds") => Some("{count} Sekunde(n)"),
+        ("de", "time_minutes") => Some("{count} Minute(n)"),
+
+        ("es", "restart_warning") => Some("Reinicio del servidor en {time}."),
+        ("es", "restart_now") => Some("¡Reiniciando el servidor ahora!"),
+        ("es", "watchdog_restart") => Some("El servidor se ha caído y se reinicia automáticamente."),
+        ("es", "time_seconds") => Some("{count} segundo(s)"),
+        ("es", "time_minutes") => Some("{count} minuto(s)"),
+
+        _ => None,
+    };
+
+    found.or_else(|| match locale {
+        "en" => None,
+        "fr" => template("en", key),
+        _ => template("en", key).or_else(|| template("fr", key)),
+    })
+}
+
+pub fn is_supported_locale(locale: &str) -> bool {
+    SUPPORTED_LOCALES.contains(&locale)
+}
+
+/// Render `key` in `locale`, replacing `{name}` placeholders with `vars`
+pub fn render(locale: &str, key: &str, vars: &[(&str, &str)]) -> String {
+    let mut text = template(locale, key).unwrap_or(key).to_string();
+    for (name, value) in vars {
+        text = text.replace(&format!("{{{}}}", name), value);
+    }
+    text
+}
+
+/// Broadcast a translated message to the players of a running server
+pub async fn broadcast(
+    pm: &ProcessManager,
+    server_id: &str,
+    locale: &str,
+    key: &str,
+    vars: &[(&str, &str)],
+) -> Result<(), AppError> {
+    let text = render(locale, key, vars);
+    pm.send_command(server_id, &format!("/say {}", text)).await
+}
@@ -0,0 +1,159 @@
+//! Rotating `logs/console.log`: rolled over by size or age into `console.log.1.gz`, `console.log.2.gz`, ...
+//! Runs of a repeated line can be collapsed into one line and a counter before reaching the disk.
+
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use tokio::fs::File;
+use tokio::io::AsyncWriteExt;
+
+/// Archives kept per server unless its `log_rotation_count` says otherwise
+pub const DEFAULT_ROTATION_COUNT: u32 = 5;
+
+/// Size at which the live file is rolled over unless the server's `console_log_max_mb` says otherwise
+pub const DEFAULT_MAX_SIZE_MB: u32 = 10;
+pub const MAX_SIZE_MB_LIMIT: u32 = 10 * 1024;
+
+/// Files are also rolled over once written to for this long
+const MAX_AGE: Duration = Duration::from_secs(24 * 3600);
+
+/// How a server's console output is written to disk
+#[derive(Debug, Clone)]
+pub struct ConsoleLogOptions {
+    /// Rotated archives to keep
+    pub keep: u32,
+    pub max_size_bytes: u64,
+    /// Collapse runs of the same line into the first one and a repeat counter
+    pub dedup: bool,
+}
+
+pub struct ConsoleLog {
+    path: PathBuf,
+    file: File,
+    size: u64,
+    opened_at: Instant,
+    options: ConsoleLogOptions,
+    /// Last line written and how many times it was repeated since
+    last_line: Option<String>,
+    repeated: u64,
+}
+
+impl ConsoleLog {
+    /// Start a fresh log for a new run, archiving the previous run's output first
+    pub async fn create(path: PathBuf, options: ConsoleLogOptions) -> io::Result<Self> {
+        let previous_len = tokio::fs::metadata(&path).await.map(|m| m.len()).unwrap_or(0);
+        if previous_len > 0 {
+            run_rotation(&path, options.keep).await?;
+        }
+
+        Ok(Self {
+            file: File::create(&path).await?,
+            path,
+            size: 0,
+            opened_at: Instant::now(),
+            options,
+            last_line: None,
+            repeated: 0,
+        })
+    }
+
+    pub async fn write_line(&mut self, line: &str) -> io::Result<()> {
+        if self.options.dedup {
+            if self.last_line.as_deref().is_some_and(|last| dedup_key(last) == dedup_key(line)) {
+                self.repeated += 1;
+                return Ok(());
+            }
+            self.flush_repeats().await?;
+            self.last_line = Some(line.to_string());
+        }
+        self.append(line).await
+    }
+
+    /// Write the counter of the run of repeats in progress, if any
+    async fn flush_repeats(&mut self) -> io::Result<()> {
+        if self.repeated > 0 {
+            let note = format!("[Previous line repeated {} more times]", self.repeated);
+            self.repeated = 0;
+            self.append(&note).await?;
+        }
+        Ok(())
+    }
+
+    async fn append(&mut self, line: &str) -> io::Result<()> {
+        if self.size >= self.options.max_size_bytes || self.opened_at.elapsed() >= MAX_AGE {
+            if let Err(e) = self.rotate().await {
+                tracing::warn!("Failed to rotate {:?}: {}", self.path, e);
+            }
+        }
+
+        let data = format!("{}\n", line);
+        self.file.write_all(data.as_bytes()).await?;
+        self.size += data.len() as u64;
+        Ok(())
+    }
+
+    async fn rotate(&mut self) -> io::Result<()> {
+        self.file.flush().await?;
+        run_rotation(&self.path, self.options.keep).await?;
+
+        self.file = File::create(&self.path).await?;
+        self.size = 0;
+        self.opened_at = Instant::now();
+        Ok(())
+    }
+}
+
+/// A line without its leading `[timestamp ...]` prefix, so the same message logged in a tight
+/// loop counts as a repeat even though its timestamp changes
+fn dedup_key(line: &str) -> &str {
+    if let Some(rest) = line.strip_prefix('[') {
+        if let Some((prefix, message)) = rest.split_once(']') {
+            if prefix.bytes().any(|b| b.is_ascii_digit()) {
+                return message;
+            }
+        }
+    }
+    line
+}
+
+/// Compression of a full log takes a moment, keep it off the async workers
+async fn run_rotation(path: &Path, keep: u32) -> io::Result<()> {
+    let path = path.to_path_buf();
+    tokio::task::spawn_blocking(move || rotate_files(&path, keep))
+        .await
+        .map_err(io::Error::other)?
+}
+
+fn archive_path(path: &Path, index: u32) -> PathBuf {
+    let file_name = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+    path.with_file_name(format!("{}.{}.gz", file_name, index))
+}
+
+/// Shift `.N.gz` archives up by one, dropping those past `keep`, then gzip the live file into `.1.gz`.
+/// With `keep == 0` nothing is archived and the caller simply truncates the live file.
+fn rotate_files(path: &Path, keep: u32) -> io::Result<()> {
+    // Also clears archives left over from a higher retention count
+    let mut index = keep.max(1);
+    while archive_path(path, index).exists() {
+        std::fs::remove_file(archive_path(path, index))?;
+        index += 1;
+    }
+    if keep == 0 {
+        return Ok(());
+    }
+
+    for index in (1..keep).rev() {
+        let from = archive_path(path, index);
+        if from.exists() {
+            std::fs::rename(&from, archive_path(path, index + 1))?;
+        }
+    }
+
+    let mut input = std::fs::File::open(path)?;
+    let mut encoder = GzEncoder::new(std::fs::File::create(archive_path(path, 1))?, Compression::default());
+    io::copy(&mut input, &mut encoder)?;
+    encoder.finish()?.sync_all()
+}
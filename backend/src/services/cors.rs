@@ -0,0 +1,90 @@
+//! Origins allowed to call the API from another site. The panel serves its own frontend, whose
+//! requests are same-origin and need no CORS headers, so no other origin is allowed unless
+//! listed in `ALLOWED_ORIGINS` or the `allowed_origins` setting. `CORS_PERMISSIVE` (on by
+//! default in `DEV_MODE`, for the Vite dev server) allows any origin.
+
+use std::sync::RwLock;
+
+use axum::http::{header, HeaderValue, Method};
+use tower_http::cors::{AllowOrigin, Any, CorsLayer};
+
+use crate::config::Settings;
+use crate::db::DbPool;
+
+lazy_static::lazy_static! {
+    /// Origins of the `allowed_origins` setting, on top of those of the environment
+    static ref SAVED: RwLock<Vec<String>> = RwLock::new(Vec::new());
+}
+
+/// `scheme://host[:port]` the way browsers send it in `Origin`, None for anything else
+pub fn normalize(origin: &str) -> Option<String> {
+    let url = reqwest::Url::parse(origin.trim()).ok()?;
+    let bare = url.path() == "/" && url.query().is_none() && url.fragment().is_none() && url.username().is_empty();
+    if !matches!(url.scheme(), "http" | "https") || url.host_str().is_none() || !bare {
+        return None;
+    }
+    Some(url.origin().ascii_serialization())
+}
+
+/// Origins of a comma-separated list such as `ALLOWED_ORIGINS`, invalid ones dropped
+pub fn parse_list(raw: &str) -> Vec<String> {
+    raw.split(',')
+        .map(str::trim)
+        .filter(|o| !o.is_empty())
+        .filter_map(|o| {
+            let origin = normalize(o);
+            if origin.is_none() {
+                tracing::warn!("Ignoring invalid CORS origin: {}", o);
+            }
+            origin
+        })
+        .collect()
+}
+
+/// Load the saved origins, called once at startup
+pub async fn load(pool: &DbPool) {
+    let saved: Option<String> = sqlx::query_scalar("SELECT value FROM settings WHERE key = 'allowed_origins'")
+        .fetch_optional(pool)
+        .await
+        .ok()
+        .flatten();
+    if let Some(origins) = saved.and_then(|v| serde_json::from_str(&v).ok()) {
+        set(origins);
+    }
+}
+
+pub fn get() -> Vec<String> {
+    SAVED.read().map(|o| o.clone()).unwrap_or_default()
+}
+
+/// Replace the saved origins, already normalized
+pub fn set(origins: Vec<String>) {
+    if let Ok(mut saved) = SAVED.write() {
+        *saved = origins;
+    }
+}
+
+fn is_allowed(origin: &HeaderValue, env_origins: &[String]) -> bool {
+    let Ok(origin) = origin.to_str() else {
+        return false;
+    };
+    env_origins.iter().any(|o| o == origin)
+        || SAVED.read().is_ok_and(|saved| saved.iter().any(|o| o == origin))
+}
+
+/// CORS layer of the whole application
+pub fn layer(settings: &Settings) -> CorsLayer {
+    if settings.cors_permissive {
+        return CorsLayer::new()
+            .allow_origin(Any)
+            .allow_methods(Any)
+            .allow_headers(Any);
+    }
+
+    let env_origins = settings.allowed_origins.clone();
+    CorsLayer::new()
+        .allow_origin(AllowOrigin::predicate(move |origin, _| is_allowed(origin, &env_origins)))
+        .allow_methods([Method::GET, Method::POST, Method::PUT, Method::PATCH, Method::DELETE])
+        .allow_headers([header::AUTHORIZATION, header::CONTENT_TYPE, header::IF_NONE_MATCH])
+        .expose_headers([header::ETAG, header::RETRY_AFTER])
+}
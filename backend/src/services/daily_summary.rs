@@ -0,0 +1,179 @@
+//! Daily Discord summary built from the metrics history and server events
+
+use std::collections::HashMap;
+
+use chrono::{Duration, Local, NaiveTime, Utc};
+
+use crate::db::DbPool;
+use crate::services::discord_service;
+use crate::services::history::{self, SAMPLE_INTERVAL_SECS};
+
+pub const DEFAULT_TIME: &str = "09:00";
+pub const SCOPES: &[&str] = &["global", "per_server"];
+
+const COLOR_SUMMARY: u32 = 0x3A82F6;
+
+struct ServerStats {
+    name: String,
+    webhook_url: Option<String>,
+    color: u32,
+    peak_players: i64,
+    unique_players: i64,
+    uptime_percent: f64,
+    restarts: i64,
+    backups: i64,
+    avg_cpu: f64,
+    avg_memory_bytes: f64,
+}
+
+pub fn parse_time(value: &str) -> Option<NaiveTime> {
+    NaiveTime::parse_from_str(value, "%H:%M").ok()
+}
+
+/// Send the summary once per day, as soon as the configured local time has passed
+pub async fn send_if_due(pool: &DbPool) -> anyhow::Result<()> {
+    let rows: Vec<(String, String)> = sqlx::query_as(
+        "SELECT key, value FROM settings WHERE key LIKE 'daily_summary_%'"
+    )
+    .fetch_all(pool)
+    .await?;
+    let settings: HashMap<String, String> = rows.into_iter().collect();
+
+    if settings.get("daily_summary_enabled").map(String::as_str) != Some("true") {
+        return Ok(());
+    }
+
+    let now = Local::now();
+    let send_at = settings.get("daily_summary_time")
+        .and_then(|t| parse_time(t))
+        .or_else(|| parse_time(DEFAULT_TIME))
+        .unwrap_or_default();
+    let today = now.format("%Y-%m-%d").to_string();
+
+    if now.time() < send_at || settings.get("daily_summary_last_sent") == Some(&today) {
+        return Ok(());
+    }
+
+    // Mark first so a Discord failure doesn't retry every minute
+    sqlx::query(
+        "INSERT INTO settings (key, value, updated_at) VALUES ('daily_summary_last_sent', ?, datetime('now'))
+         ON CONFLICT(key) DO UPDATE SET value = excluded.value, updated_at = excluded.updated_at"
+    )
+    .bind(&today)
+    .execute(pool)
+    .await?;
+
+    let stats = collect_stats(pool).await?;
+    let channel = settings.get("daily_summary_webhook_url").filter(|u| !u.is_empty()).cloned();
+    let scope = settings.get("daily_summary_scope").map(String::as_str).unwrap_or("global");
+
+    if scope == "per_server" {
+        for server in &stats {
+            let url = server.webhook_url.clone().or_else(|| channel.clone());
+            discord_service::send_notification(
+                pool,
+                "📈 Résumé quotidien",
+                &format_stats(server),
+                server.color,
+                Some(&server.name),
+                url.as_deref(),
+            ).await;
+        }
+    } else {
+        let description = if stats.is_empty() {
+            "Aucun serveur configuré.".to_string()
+        } else {
+            stats.iter()
+                .map(|s| format!("**{}**\n{}", s.name, format_stats(s)))
+                .collect::<Vec<_>>()
+                .join("\n\n")
+        };
+        discord_service::send_notification(
+            pool,
+            "📈 Résumé quotidien",
+            &description,
+            COLOR_SUMMARY,
+            None,
+            channel.as_deref(),
+        ).await;
+    }
+
+    Ok(())
+}
+
+async fn collect_stats(pool: &DbPool) -> anyhow::Result<Vec<ServerStats>> {
+    let since = (Utc::now() - Duration::hours(24)).to_rfc3339();
+    let servers: Vec<(String, String, Option<String>, Option<String>)> = sqlx::query_as(
+        "SELECT id, name, discord_webhook_url, discord_color FROM servers ORDER BY name"
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let mut stats = Vec::with_capacity(servers.len());
+    for (id, name, webhook_url, color) in servers {
+        let (samples, peak_players, avg_cpu, avg_memory_bytes): (i64, Option<i64>, Option<f64>, Option<f64>) = sqlx::query_as(
+            "SELECT COUNT(*), MAX(players), AVG(cpu), AVG(memory_bytes)
+             FROM metrics_history WHERE server_id = ? AND recorded_at >= ?"
+        )
+        .bind(&id)
+        .bind(&since)
+        .fetch_one(pool)
+        .await?;
+
+        let (unique_players,): (i64,) = sqlx::query_as(
+            "SELECT COUNT(DISTINCT detail) FROM server_events WHERE server_id = ? AND event_type = ? AND created_at >= ?"
+        )
+        .bind(&id)
+        .bind(history::EVENT_PLAYER_JOIN)
+        .bind(&since)
+        .fetch_one(pool)
+        .await?;
+
+        let (restarts,): (i64,) = sqlx::query_as(
+            "SELECT COUNT(*) FROM server_events WHERE server_id = ? AND event_type = ? AND created_at >= ?"
+        )
+        .bind(&id)
+        .bind(history::EVENT_RESTART)
+        .bind(&since)
+        .fetch_one(pool)
+        .await?;
+
+        let (backups,): (i64,) = sqlx::query_as(
+            "SELECT COUNT(*) FROM backups WHERE server_id = ? AND created_at >= ?"
+        )
+        .bind(&id)
+        .bind(&since)
+        .fetch_one(pool)
+        .await?;
+
+        let uptime_percent = (samples as f64 * SAMPLE_INTERVAL_SECS as f64 / 86_400.0 * 100.0).min(100.0);
+
+        stats.push(ServerStats {
+            name,
+            webhook_url: webhook_url.filter(|u| !u.is_empty()),
+            color: discord_service::server_color(color.as_deref(), COLOR_SUMMARY),
+            peak_players: peak_players.unwrap_or(0),
+            unique_players,
+            uptime_percent,
+            restarts,
+            backups,
+            avg_cpu: avg_cpu.unwrap_or(0.0),
+            avg_memory_bytes: avg_memory_bytes.unwrap_or(0.0),
+        });
+    }
+
+    Ok(stats)
+}
+
+fn format_stats(s: &ServerStats) -> String {
+    format!(
+        "👥 Pic: **{}** • Uniques: **{}**\n⏱️ Disponibilité: **{:.1}%** • 🔄 Redémarrages: **{}** • 💾 Sauvegardes: **{}**\n📊 CPU moyen: **{:.1}%** • RAM moyenne: **{:.0} MB**",
+        s.peak_players,
+        s.unique_players,
+        s.uptime_percent,
+        s.restarts,
+        s.backups,
+        s.avg_cpu,
+        s.avg_memory_bytes / 1024.0 / 1024.0,
+    )
+}
@@ -0,0 +1,161 @@
+//! Copies of the panel's own SQLite database (servers, users, settings), taken with
+//! `VACUUM INTO` while the panel runs. They live in `panel/` of the backups directory, outside the database
+//! they protect, so they are listed from the directory. A restore is staged next to the
+//! database and swapped in at the next start, before any connection is open.
+
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use utoipa::ToSchema;
+
+use crate::db::DbPool;
+
+/// Subdirectory of the backups directory holding the copies
+const DIR: &str = "panel";
+/// Appended to the database path of a copy waiting to be restored
+const STAGED_SUFFIX: &str = ".restore";
+const SQLITE_HEADER: &[u8; 16] = b"SQLite format 3\0";
+
+pub const DEFAULT_INTERVAL_HOURS: u64 = 24;
+pub const DEFAULT_KEEP: usize = 7;
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct DbBackup {
+    pub name: String,
+    pub size_bytes: u64,
+    pub created_at: String,
+}
+
+/// File behind a `sqlite:` URL, `None` for an in-memory database
+pub fn database_path(database_url: &str) -> Option<PathBuf> {
+    let path = database_url.strip_prefix("sqlite://").or_else(|| database_url.strip_prefix("sqlite:"))?;
+    let path = path.split('?').next().unwrap_or_default();
+    (!path.is_empty() && path != ":memory:").then(|| PathBuf::from(path))
+}
+
+fn staged_path(database: &Path) -> PathBuf {
+    let mut staged = database.as_os_str().to_owned();
+    staged.push(STAGED_SUFFIX);
+    PathBuf::from(staged)
+}
+
+/// Names as written by `create`, nothing that could leave the directory
+pub fn is_valid_name(name: &str) -> bool {
+    name.starts_with("panel_")
+        && name.ends_with(".db")
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '.')
+        && !name.contains("..")
+}
+
+fn entry(path: &Path) -> Option<DbBackup> {
+    let name = path.file_name()?.to_str()?.to_string();
+    if !is_valid_name(&name) {
+        return None;
+    }
+    let metadata = std::fs::metadata(path).ok()?;
+    let created_at: DateTime<Utc> = metadata.modified().ok()?.into();
+    Some(DbBackup { name, size_bytes: metadata.len(), created_at: created_at.to_rfc3339() })
+}
+
+/// Copies on disk, newest first
+pub fn list(backups_dir: &Path) -> Vec<DbBackup> {
+    let Ok(dir) = std::fs::read_dir(backups_dir.join(DIR)) else {
+        return Vec::new();
+    };
+    let mut backups: Vec<DbBackup> = dir.filter_map(|e| e.ok()).filter_map(|e| entry(&e.path())).collect();
+    // Names embed the creation time
+    backups.sort_unstable_by(|a, b| b.name.cmp(&a.name));
+    backups
+}
+
+/// Write a consistent copy of the live database, then drop copies beyond `keep`
+pub async fn create(pool: &DbPool, backups_dir: &Path, keep: usize) -> anyhow::Result<DbBackup> {
+    let dir = backups_dir.join(DIR);
+    tokio::fs::create_dir_all(&dir).await?;
+    let path = dir.join(format!("panel_{}.db", Utc::now().format("%Y%m%d_%H%M%S_%3f")));
+    sqlx::query("VACUUM INTO ?")
+        .bind(path.to_string_lossy().as_ref())
+        .execute(pool)
+        .await?;
+
+    for old in list(backups_dir).into_iter().skip(keep.max(1)) {
+        if let Err(e) = tokio::fs::remove_file(dir.join(&old.name)).await {
+            tracing::warn!("Failed to delete old database backup {}: {}", old.name, e);
+        }
+    }
+
+    let backup = entry(&path).ok_or_else(|| anyhow::anyhow!("database backup {:?} missing after VACUUM INTO", path))?;
+    tracing::info!("Panel database backed up to {} ({} bytes)", backup.name, backup.size_bytes);
+    Ok(backup)
+}
+
+/// Take a copy when the last one is older than `db_backup_interval_hours` (0 disables)
+pub async fn backup_if_due(pool: &DbPool, backups_dir: &Path) -> anyhow::Result<()> {
+    let rows: Vec<(String, String)> = sqlx::query_as(
+        "SELECT key, value FROM settings WHERE key IN ('db_backup_interval_hours', 'db_backup_keep')"
+    )
+    .fetch_all(pool)
+    .await?;
+    let get = |key: &str| rows.iter().find(|(k, _)| k == key).and_then(|(_, v)| v.parse::<u64>().ok());
+    let interval_hours = get("db_backup_interval_hours").unwrap_or(DEFAULT_INTERVAL_HOURS);
+    if interval_hours == 0 {
+        return Ok(());
+    }
+
+    let latest = list(backups_dir).into_iter().next().and_then(|b| DateTime::parse_from_rfc3339(&b.created_at).ok());
+    let due = latest.is_none_or(|at| Utc::now() - at.with_timezone(&Utc) >= chrono::Duration::hours(interval_hours as i64));
+    if due {
+        create(pool, backups_dir, get("db_backup_keep").map_or(DEFAULT_KEEP, |k| k as usize)).await?;
+    }
+    Ok(())
+}
+
+/// Copy a backup next to the database, to replace it at the next start
+pub async fn stage_restore(database_url: &str, backups_dir: &Path, name: &str) -> anyhow::Result<()> {
+    let database = database_path(database_url).ok_or_else(|| anyhow::anyhow!("the database isn't a file"))?;
+    let source = backups_dir.join(DIR).join(name);
+
+    let mut header = [0u8; 16];
+    {
+        use tokio::io::AsyncReadExt;
+        tokio::fs::File::open(&source).await?.read_exact(&mut header).await?;
+    }
+    if &header != SQLITE_HEADER {
+        anyhow::bail!("{} isn't an SQLite database", name);
+    }
+
+    let staged = staged_path(&database);
+    let mut partial = staged.as_os_str().to_owned();
+    partial.push(".part");
+    tokio::fs::copy(&source, &partial).await?;
+    tokio::fs::rename(&partial, &staged).await?;
+    tracing::warn!("Database backup {} staged, it replaces the database at the next start", name);
+    Ok(())
+}
+
+/// Swap in a staged restore before the pool opens. The replaced database is kept beside it,
+/// and its WAL files removed so they aren't replayed onto the restored one.
+pub fn apply_staged_restore(database_url: &str) -> std::io::Result<bool> {
+    let Some(database) = database_path(database_url) else {
+        return Ok(false);
+    };
+    let staged = staged_path(&database);
+    if !staged.exists() {
+        return Ok(false);
+    }
+
+    if database.exists() {
+        let mut previous = database.as_os_str().to_owned();
+        previous.push(format!(".before-restore-{}", Utc::now().format("%Y%m%d_%H%M%S")));
+        std::fs::rename(&database, PathBuf::from(previous))?;
+    }
+    for suffix in ["-wal", "-shm"] {
+        let mut sidecar = database.as_os_str().to_owned();
+        sidecar.push(suffix);
+        let _ = std::fs::remove_file(PathBuf::from(sidecar));
+    }
+    std::fs::rename(&staged, &database)?;
+    Ok(true)
+}
+
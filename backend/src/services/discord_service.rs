@@ -1,4 +1,6 @@
 use crate::db::DbPool;
+use crate::services::events::{EventBus, PanelEvent};
+use crate::services::process_manager::{WATCHDOG_DELAY, WATCHDOG_MAX_RESTARTS, WATCHDOG_WINDOW};
 use anyhow::Result;
 use serde_json::Value;
 
@@ -62,6 +64,101 @@ pub async fn send_notification(
     let _ = client.post(&url).json(&payload).send().await;
 }
 
+/// Send a notification for one server if its `discord_notifications` map enables `event`
+/// (e.g. `playerJoin`), using the server's webhook and color when set
+pub async fn notify_server_event(
+    pool: &DbPool,
+    server_id: &str,
+    event: &str,
+    title: &str,
+    description: &str,
+    default_color: u32,
+) {
+    let notifications: Option<Option<String>> = sqlx::query_scalar("SELECT discord_notifications FROM servers WHERE id = ?")
+        .bind(server_id)
+        .fetch_optional(pool)
+        .await
+        .ok()
+        .flatten();
+
+    let enabled = notifications
+        .flatten()
+        .and_then(|n| serde_json::from_str::<Value>(&n).ok())
+        .and_then(|n| n.get(event).and_then(Value::as_bool))
+        .unwrap_or(false);
+    if enabled {
+        notify_server(pool, server_id, title, description, default_color).await;
+    }
+}
+
+/// Send a notification for one server, using its webhook (or the global one) and its color when set
+pub async fn notify_server(pool: &DbPool, server_id: &str, title: &str, description: &str, default_color: u32) {
+    let row: Option<(String, Option<String>, Option<String>)> = sqlx::query_as(
+        "SELECT name, discord_webhook_url, discord_color FROM servers WHERE id = ?"
+    )
+    .bind(server_id)
+    .fetch_optional(pool)
+    .await
+    .ok()
+    .flatten();
+    let Some((name, webhook_url, color)) = row else {
+        return;
+    };
+
+    let webhook_url = webhook_url.filter(|u| !u.is_empty());
+    send_notification(
+        pool,
+        title,
+        description,
+        server_color(color.as_deref(), default_color),
+        Some(&name),
+        webhook_url.as_deref(),
+    ).await;
+}
+
+/// Notify crashes and, when the server enables them, player joins and leaves
+pub fn consume_events(pool: &DbPool, bus: &EventBus) {
+    let pool = pool.clone();
+    bus.consume("discord", move |event| {
+        let pool = pool.clone();
+        async move {
+            match event {
+                PanelEvent::ServerCrashed { server_id, exit_code, watchdog_enabled, auto_restart } => {
+                    let mut description = match exit_code {
+                        Some(code) => format!("Le processus s'est arrêté avec le code {}.", code),
+                        None => "Le processus s'est arrêté de façon inattendue.".to_string(),
+                    };
+                    if auto_restart {
+                        description.push_str(&format!(" Redémarrage automatique dans {} s.", WATCHDOG_DELAY.as_secs()));
+                    } else if watchdog_enabled {
+                        description.push_str(&format!(
+                            " Redémarrage automatique suspendu après {} plantages en {} minutes.",
+                            WATCHDOG_MAX_RESTARTS,
+                            WATCHDOG_WINDOW.as_secs() / 60
+                        ));
+                    }
+                    tokio::spawn(async move {
+                        notify_server(&pool, &server_id, "💥 Serveur Planté", &description, COLOR_ERROR).await;
+                    });
+                }
+                PanelEvent::PlayerJoined { server_id, player } => {
+                    tokio::spawn(async move {
+                        let description = format!("**{}** a rejoint le serveur.", player);
+                        notify_server_event(&pool, &server_id, "playerJoin", "👋 Joueur Connecté", &description, COLOR_SUCCESS).await;
+                    });
+                }
+                PanelEvent::PlayerLeft { server_id, player } => {
+                    tokio::spawn(async move {
+                        let description = format!("**{}** a quitté le serveur.", player);
+                        notify_server_event(&pool, &server_id, "playerLeave", "🚪 Joueur Déconnecté", &description, COLOR_ERROR).await;
+                    });
+                }
+                _ => {}
+            }
+        }
+    });
+}
+
 /// Update or Create the persistent Status Message
 pub async fn update_status_message(
     pool: &DbPool,
@@ -149,3 +246,26 @@ pub async fn update_status_message(
 // Webhook colors
 pub const COLOR_SUCCESS: u32 = 0x10B981; // Green
 pub const COLOR_ERROR: u32 = 0xEF4444;   // Red
+pub const COLOR_WARNING: u32 = 0xF59E0B; // Amber
+
+/// Emoji shown in front of an online server unless it defines its own
+pub const DEFAULT_ONLINE_EMOJI: &str = "🟢";
+
+/// Parse a per-server embed color written as `#RRGGBB`
+pub fn parse_color(value: &str) -> Option<u32> {
+    let hex = value.trim().strip_prefix('#')?;
+    if hex.len() != 6 {
+        return None;
+    }
+    u32::from_str_radix(hex, 16).ok()
+}
+
+/// Server color if set and valid, otherwise `default`
+pub fn server_color(custom: Option<&str>, default: u32) -> u32 {
+    custom.and_then(parse_color).unwrap_or(default)
+}
+
+/// Server emoji if set, otherwise the default online emoji
+pub fn online_emoji(custom: Option<&str>) -> &str {
+    custom.map(str::trim).filter(|e| !e.is_empty()).unwrap_or(DEFAULT_ONLINE_EMOJI)
+}
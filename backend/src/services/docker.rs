@@ -0,0 +1,100 @@
+//! Docker runtime: a server can run inside a container instead of directly on the host. The
+//! panel still spawns an attached `docker run -i`, so the console, the stop command and exit
+//! handling work unchanged; only kill and suspend have to go through the Docker CLI.
+
+use crate::services::LaunchConfig;
+
+/// Runtime of a server, stored in `servers.runtime`
+pub const RUNTIME_NATIVE: &str = "native";
+pub const RUNTIME_DOCKER: &str = "docker";
+
+/// Where the server directory is mounted inside the container
+pub const CONTAINER_DATA_DIR: &str = "/data";
+
+const DOCKER_BIN: &str = "docker";
+
+pub fn container_name(server_id: &str) -> String {
+    format!("draveur-{}", server_id)
+}
+
+/// The launch as seen from inside the container: server directory at `/data`, the image's
+/// `java`, and listening on every interface (the host address is chosen when publishing)
+pub fn containerized(launch: &LaunchConfig) -> LaunchConfig {
+    let host_dir = std::path::Path::new(&launch.working_dir);
+    let executable_path = match std::path::Path::new(&launch.executable_path).strip_prefix(host_dir) {
+        Ok(relative) => std::path::Path::new(CONTAINER_DATA_DIR).join(relative).to_string_lossy().into_owned(),
+        Err(_) => launch.executable_path.clone(),
+    };
+
+    let mut config = launch.config.clone();
+    if let Some(obj) = config.as_mut().and_then(|c| c.as_object_mut()) {
+        obj.insert("bind_address".to_string(), serde_json::json!("0.0.0.0"));
+    }
+
+    LaunchConfig {
+        executable_path,
+        working_dir: CONTAINER_DATA_DIR.to_string(),
+        java_path: None,
+        config,
+        ..launch.clone()
+    }
+}
+
+/// Wrap `inner`, built from `containerized(launch)`, into an attached `docker run` publishing
+/// `port` on `bind_ip`. Environment variables are forwarded by name from the docker process.
+pub fn run_command(server_id: &str, launch: &LaunchConfig, image: &str, inner: &std::process::Command, bind_ip: &str, port: u64) -> std::process::Command {
+    let host_dir = std::fs::canonicalize(&launch.working_dir)
+        .map(|p| p.to_string_lossy().into_owned())
+        .unwrap_or_else(|_| launch.working_dir.clone());
+
+    let mut cmd = std::process::Command::new(DOCKER_BIN);
+    cmd.arg("run")
+        .arg("--rm")
+        .arg("-i")
+        .arg("--init")
+        .arg("--name").arg(container_name(server_id))
+        .arg("-v").arg(format!("{}:{}", host_dir, CONTAINER_DATA_DIR))
+        .arg("-w").arg(CONTAINER_DATA_DIR)
+        .arg("-p").arg(format!("{}:{}:{}/udp", bind_ip, port, port))
+        .arg("-p").arg(format!("{}:{}:{}/tcp", bind_ip, port, port));
+
+    // Files written in the mounted directory stay owned by the panel user
+    #[cfg(unix)]
+    {
+        let (uid, gid) = unsafe { (libc::getuid(), libc::getgid()) };
+        cmd.arg("--user").arg(format!("{}:{}", uid, gid));
+    }
+
+    for key in launch.env_vars.keys() {
+        cmd.arg("-e").arg(key);
+    }
+
+    cmd.arg(image).arg(inner.get_program()).args(inner.get_args());
+    cmd
+}
+
+async fn docker(args: &[&str]) -> bool {
+    tokio::process::Command::new(DOCKER_BIN)
+        .args(args)
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .status()
+        .await
+        .is_ok_and(|s| s.success())
+}
+
+/// Remove a container left behind by a previous panel run, which would block the name
+pub async fn remove_stale(server_id: &str) {
+    docker(&["rm", "-f", &container_name(server_id)]).await;
+}
+
+/// Kill the container; killing the attached `docker run` alone would leave it running
+pub async fn kill(server_id: &str) {
+    docker(&["kill", &container_name(server_id)]).await;
+}
+
+/// Freeze or thaw the container (`docker pause`/`unpause`), returning whether it worked
+pub async fn set_paused(server_id: &str, paused: bool) -> bool {
+    let action = if paused { "pause" } else { "unpause" };
+    docker(&[action, &container_name(server_id)]).await
+}
@@ -0,0 +1,77 @@
+//! Break-glass access: a non-admin user asks for admin rights on one server for a few minutes,
+//! an admin approves (users on the `break_glass_on_call` list are approved at once), and the
+//! grant lapses on its own. Every step is recorded in the server's event history.
+
+use chrono::Utc;
+
+use crate::api::auth::AuthUser;
+use crate::db::DbPool;
+use crate::services::history;
+
+pub const ELEVATION_PENDING: &str = "pending";
+pub const ELEVATION_ACTIVE: &str = "active";
+pub const ELEVATION_DENIED: &str = "denied";
+pub const ELEVATION_REVOKED: &str = "revoked";
+pub const ELEVATION_EXPIRED: &str = "expired";
+
+/// Longest grant that can be requested
+pub const MAX_MINUTES: u32 = 240;
+
+/// Users whose requests need no approval
+pub async fn on_call_users(pool: &DbPool) -> Vec<String> {
+    let value: Option<String> = sqlx::query_scalar("SELECT value FROM settings WHERE key = 'break_glass_on_call'")
+        .fetch_optional(pool)
+        .await
+        .ok()
+        .flatten();
+    value.and_then(|v| serde_json::from_str(&v).ok()).unwrap_or_default()
+}
+
+/// Whether the caller may act as an admin on `server_id`: admins always, other users while a
+/// grant is active. Grants are bound to the login session, API tokens never benefit from them.
+pub async fn has_server_admin(pool: &DbPool, auth: &AuthUser, server_id: &str) -> bool {
+    if auth.is_admin() {
+        return true;
+    }
+    if auth.api_token_id.is_some() {
+        return false;
+    }
+    let active: Option<i64> = sqlx::query_scalar(
+        "SELECT COUNT(*) FROM elevation_grants WHERE server_id = ? AND user_id = ? AND status = ? AND expires_at > ?"
+    )
+    .bind(server_id)
+    .bind(&auth.id)
+    .bind(ELEVATION_ACTIVE)
+    .bind(Utc::now().to_rfc3339())
+    .fetch_optional(pool)
+    .await
+    .ok()
+    .flatten();
+    active.is_some_and(|count| count > 0)
+}
+
+/// Mark lapsed grants as expired so the history shows when access ended
+pub async fn expire_due(pool: &DbPool) -> Result<(), sqlx::Error> {
+    let now = Utc::now().to_rfc3339();
+    let lapsed: Vec<(String, String, String)> = sqlx::query_as(
+        "SELECT g.id, g.server_id, COALESCE(u.username, g.user_id) FROM elevation_grants g
+         LEFT JOIN users u ON u.id = g.user_id
+         WHERE g.status = ? AND g.expires_at <= ?"
+    )
+    .bind(ELEVATION_ACTIVE)
+    .bind(&now)
+    .fetch_all(pool)
+    .await?;
+
+    for (id, server_id, username) in lapsed {
+        sqlx::query("UPDATE elevation_grants SET status = ?, resolved_at = ? WHERE id = ? AND status = ?")
+            .bind(ELEVATION_EXPIRED)
+            .bind(&now)
+            .bind(&id)
+            .bind(ELEVATION_ACTIVE)
+            .execute(pool)
+            .await?;
+        history::record_event(pool, &server_id, history::EVENT_ELEVATION_EXPIRED, Some(&username)).await;
+    }
+    Ok(())
+}
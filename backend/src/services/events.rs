@@ -0,0 +1,147 @@
+//! Panel-wide event bus, held in `AppState`: the process manager, the scheduler and backup jobs
+//! publish what happens to servers, and each notification channel (server history, Discord,
+//! outgoing webhooks, the `/api/v1/events` stream) subscribes instead of being called from
+//! every place an event can come from.
+
+use std::future::Future;
+
+use serde::Serialize;
+use tokio::sync::broadcast::{self, error::RecvError};
+use utoipa::ToSchema;
+
+use crate::db::DbPool;
+use crate::services::{discord_service, history, outgoing_webhooks};
+
+/// Events kept for a slow subscriber before it skips ahead
+const CAPACITY: usize = 1024;
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum PanelEvent {
+    /// Same values as the console `[STATUS]` lines: `running`, `stopped`, `unresponsive`,
+    /// `suspended`
+    #[serde(rename = "status")]
+    StatusChanged { server_id: String, status: String },
+    /// Same fields as the console `[METRICS]` lines, `disk_bytes` always set
+    Metrics {
+        server_id: String,
+        #[schema(value_type = Object)]
+        metrics: serde_json::Value,
+    },
+    Players { server_id: String, count: usize },
+    /// The game reported it finished booting
+    ServerReady { server_id: String, boot_ms: u64 },
+    /// The process exited on request
+    ServerStopped { server_id: String, exit_code: Option<i32> },
+    /// The process exited with a failure nobody asked for
+    ServerCrashed {
+        server_id: String,
+        exit_code: Option<i32>,
+        watchdog_enabled: bool,
+        /// The watchdog relaunches it
+        auto_restart: bool,
+    },
+    PlayerJoined { server_id: String, player: String },
+    PlayerLeft { server_id: String, player: String },
+    /// A scheduled restart countdown began
+    RestartScheduled { server_id: String, in_secs: u64 },
+    /// A backup job ended, `error` set when it failed
+    BackupFinished {
+        server_id: String,
+        backup_id: String,
+        filename: Option<String>,
+        size_bytes: Option<i64>,
+        label: Option<String>,
+        error: Option<String>,
+    },
+}
+
+impl PanelEvent {
+    pub fn status(server_id: &str, status: &str) -> Self {
+        Self::StatusChanged { server_id: server_id.to_string(), status: status.to_string() }
+    }
+
+    pub fn server_id(&self) -> &str {
+        match self {
+            Self::StatusChanged { server_id, .. }
+            | Self::Metrics { server_id, .. }
+            | Self::Players { server_id, .. }
+            | Self::ServerReady { server_id, .. }
+            | Self::ServerStopped { server_id, .. }
+            | Self::ServerCrashed { server_id, .. }
+            | Self::PlayerJoined { server_id, .. }
+            | Self::PlayerLeft { server_id, .. }
+            | Self::RestartScheduled { server_id, .. }
+            | Self::BackupFinished { server_id, .. } => server_id,
+        }
+    }
+
+    /// SSE event name, the same as the `type` field
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::StatusChanged { .. } => "status",
+            Self::Metrics { .. } => "metrics",
+            Self::Players { .. } => "players",
+            Self::ServerReady { .. } => "server_ready",
+            Self::ServerStopped { .. } => "server_stopped",
+            Self::ServerCrashed { .. } => "server_crashed",
+            Self::PlayerJoined { .. } => "player_joined",
+            Self::PlayerLeft { .. } => "player_left",
+            Self::RestartScheduled { .. } => "restart_scheduled",
+            Self::BackupFinished { .. } => "backup_finished",
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct EventBus {
+    tx: broadcast::Sender<PanelEvent>,
+}
+
+impl Default for EventBus {
+    fn default() -> Self {
+        Self { tx: broadcast::channel(CAPACITY).0 }
+    }
+}
+
+impl EventBus {
+    /// Dropped when nobody listens
+    pub fn publish(&self, event: PanelEvent) {
+        let _ = self.tx.send(event);
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<PanelEvent> {
+        self.tx.subscribe()
+    }
+
+    /// Whether building an event is worth it
+    pub fn has_subscribers(&self) -> bool {
+        self.tx.receiver_count() > 0
+    }
+
+    /// Run `handle` on every event from now on, in order. Slow work (HTTP calls) belongs in a
+    /// spawned task so the subscriber keeps up; events it still misses are logged and skipped.
+    pub fn consume<F, Fut>(&self, name: &'static str, mut handle: F)
+    where
+        F: FnMut(PanelEvent) -> Fut + Send + 'static,
+        Fut: Future<Output = ()> + Send,
+    {
+        let mut rx = self.subscribe();
+        tokio::spawn(async move {
+            loop {
+                match rx.recv().await {
+                    Ok(event) => handle(event).await,
+                    Err(RecvError::Lagged(skipped)) => tracing::warn!("Event subscriber {} skipped {} events", name, skipped),
+                    Err(RecvError::Closed) => break,
+                }
+            }
+        });
+    }
+}
+
+/// Subscribe the notification channels; the SSE stream subscribes per connection
+pub fn start_consumers(pool: &DbPool, bus: &EventBus) {
+    history::consume_events(pool, bus);
+    discord_service::consume_events(pool, bus);
+    outgoing_webhooks::consume_events(pool, bus);
+}
@@ -0,0 +1,95 @@
+//! External health probe of a running server: a live process can still be a hung JVM, so the
+//! game port is checked from the outside every few seconds.
+
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::time::Duration;
+
+/// Connect to the port, for servers speaking TCP
+pub const KIND_TCP: &str = "tcp";
+/// QUIC version negotiation on the UDP port, which any QUIC server (Hytale) answers
+pub const KIND_QUIC: &str = "quic";
+
+pub const DEFAULT_INTERVAL_SECS: u32 = 30;
+pub const MIN_INTERVAL_SECS: u32 = 5;
+pub const MAX_INTERVAL_SECS: u32 = 3600;
+
+/// Failed probes in a row before the server is reported unresponsive
+pub const FAILURES_BEFORE_UNRESPONSIVE: u32 = 3;
+
+const PROBE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// QUIC servers only answer Initial-sized datagrams
+const QUIC_MIN_DATAGRAM: usize = 1200;
+
+#[derive(Debug, Clone)]
+pub struct HealthProbe {
+    pub kind: String,
+    pub interval: Duration,
+}
+
+impl HealthProbe {
+    /// Probe configured on a server, `None` when disabled (no or empty kind)
+    pub fn from_settings(kind: Option<&str>, interval_secs: i32) -> Option<Self> {
+        let kind = kind.map(str::trim).filter(|k| !k.is_empty())?;
+        let interval_secs = (interval_secs.max(0) as u32).clamp(MIN_INTERVAL_SECS, MAX_INTERVAL_SECS);
+        Some(Self {
+            kind: kind.to_string(),
+            interval: Duration::from_secs(interval_secs as u64),
+        })
+    }
+
+    /// Check the server answers on `bind_ip:port`
+    pub async fn check(&self, bind_ip: &str, port: u16) -> bool {
+        let target = SocketAddr::new(probe_ip(bind_ip), port);
+        let probe = async {
+            match self.kind.as_str() {
+                KIND_QUIC => quic_ping(target).await,
+                _ => tokio::net::TcpStream::connect(target).await.is_ok(),
+            }
+        };
+        tokio::time::timeout(PROBE_TIMEOUT, probe).await.unwrap_or(false)
+    }
+}
+
+pub fn is_valid_kind(kind: &str) -> bool {
+    kind.is_empty() || kind == KIND_TCP || kind == KIND_QUIC
+}
+
+/// A server bound to every interface is probed over loopback
+fn probe_ip(bind_ip: &str) -> IpAddr {
+    match bind_ip.parse::<IpAddr>() {
+        Ok(IpAddr::V4(ip)) if ip.is_unspecified() => IpAddr::V4(Ipv4Addr::LOCALHOST),
+        Ok(IpAddr::V6(ip)) if ip.is_unspecified() => IpAddr::V6(Ipv6Addr::LOCALHOST),
+        Ok(ip) => ip,
+        Err(_) => IpAddr::V4(Ipv4Addr::LOCALHOST),
+    }
+}
+
+/// Send a long-header packet with a reserved version: RFC 9000 requires the server to answer
+/// with a Version Negotiation packet (version 0), without opening a connection
+async fn quic_ping(target: SocketAddr) -> bool {
+    let local: SocketAddr = if target.is_ipv4() { "0.0.0.0:0" } else { "[::]:0" }.parse().unwrap();
+    let Ok(socket) = tokio::net::UdpSocket::bind(local).await else {
+        return false;
+    };
+    if socket.connect(target).await.is_err() {
+        return false;
+    }
+
+    let mut packet = vec![0u8; QUIC_MIN_DATAGRAM];
+    packet[0] = 0xC0;
+    packet[1..5].copy_from_slice(&[0x1a, 0x2a, 0x3a, 0x4a]);
+    packet[5] = 8;
+    packet[6..14].copy_from_slice(b"draveur!");
+    packet[14] = 8;
+    packet[15..23].copy_from_slice(b"healthck");
+    if socket.send(&packet).await.is_err() {
+        return false;
+    }
+
+    let mut response = [0u8; 1500];
+    match socket.recv(&mut response).await {
+        Ok(len) => len >= 5 && response[0] & 0x80 != 0 && response[1..5] == [0, 0, 0, 0],
+        Err(_) => false,
+    }
+}
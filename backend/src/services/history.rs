@@ -0,0 +1,211 @@
+//! Historical metrics samples and server lifecycle events
+
+use std::collections::{BTreeMap, HashMap};
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use utoipa::ToSchema;
+
+use crate::db::DbPool;
+use crate::services::events::{EventBus, PanelEvent};
+use crate::services::ProcessManager;
+
+pub const EVENT_START: &str = "start";
+pub const EVENT_STOP: &str = "stop";
+pub const EVENT_RESTART: &str = "restart";
+pub const EVENT_PLAYER_JOIN: &str = "player_join";
+/// Process exited with an error without a stop or kill request
+pub const EVENT_CRASH: &str = "crash";
+pub const EVENT_LOGS_PRUNED: &str = "logs_pruned";
+/// Server finished booting, detail is the startup duration in milliseconds
+pub const EVENT_READY: &str = "ready";
+/// Ownership handed to another user, detail names both users
+pub const EVENT_TRANSFER_REQUESTED: &str = "transfer_requested";
+pub const EVENT_TRANSFERRED: &str = "transferred";
+pub const EVENT_TRANSFER_DECLINED: &str = "transfer_declined";
+/// Process frozen / thawed in place
+pub const EVENT_SUSPEND: &str = "suspend";
+pub const EVENT_RESUME: &str = "resume";
+/// Alive but failing its health probe, detail is the probe and port
+pub const EVENT_UNRESPONSIVE: &str = "unresponsive";
+/// Break-glass access lifecycle, detail names the user (and who decided)
+pub const EVENT_ELEVATION_REQUESTED: &str = "elevation_requested";
+pub const EVENT_ELEVATION_GRANTED: &str = "elevation_granted";
+pub const EVENT_ELEVATION_DENIED: &str = "elevation_denied";
+pub const EVENT_ELEVATION_REVOKED: &str = "elevation_revoked";
+pub const EVENT_ELEVATION_EXPIRED: &str = "elevation_expired";
+/// Per-server permissions of a user replaced, detail names the user and the new list
+pub const EVENT_PERMISSIONS_CHANGED: &str = "permissions_changed";
+
+/// Seconds between two metrics samples
+pub const SAMPLE_INTERVAL_SECS: u64 = 60;
+
+/// Record a lifecycle event (start, stop, restart, player join, ...)
+pub async fn record_event(pool: &DbPool, server_id: &str, event_type: &str, detail: Option<&str>) {
+    let result = sqlx::query(
+        "INSERT INTO server_events (server_id, event_type, detail, created_at) VALUES (?, ?, ?, ?)"
+    )
+    .bind(server_id)
+    .bind(event_type)
+    .bind(detail)
+    .bind(chrono::Utc::now().to_rfc3339())
+    .execute(pool)
+    .await;
+
+    if let Err(e) = result {
+        tracing::warn!("Failed to record {} event for server {}: {}", event_type, server_id, e);
+    }
+}
+
+/// Record the lifecycle events published by the process manager
+pub fn consume_events(pool: &DbPool, bus: &EventBus) {
+    let pool = pool.clone();
+    bus.consume("history", move |event| {
+        let pool = pool.clone();
+        async move {
+            match event {
+                PanelEvent::ServerReady { server_id, boot_ms } => {
+                    record_event(&pool, &server_id, EVENT_READY, Some(&boot_ms.to_string())).await
+                }
+                PanelEvent::ServerCrashed { server_id, exit_code, .. } => {
+                    let detail = exit_code.map(|code| format!("exit code {}", code));
+                    record_event(&pool, &server_id, EVENT_CRASH, detail.as_deref()).await
+                }
+                PanelEvent::PlayerJoined { server_id, player } => {
+                    record_event(&pool, &server_id, EVENT_PLAYER_JOIN, Some(&player)).await
+                }
+                _ => {}
+            }
+        }
+    });
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct StartupSample {
+    pub duration_ms: u64,
+    pub recorded_at: String,
+}
+
+/// Most recent startups of a server, newest first
+pub async fn startup_history(pool: &DbPool, server_id: &str, limit: u32) -> Result<Vec<StartupSample>, sqlx::Error> {
+    let rows: Vec<(Option<String>, String)> = sqlx::query_as(
+        "SELECT detail, created_at FROM server_events WHERE server_id = ? AND event_type = ? ORDER BY id DESC LIMIT ?"
+    )
+    .bind(server_id)
+    .bind(EVENT_READY)
+    .bind(limit)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .filter_map(|(detail, recorded_at)| {
+            let duration_ms = detail?.parse().ok()?;
+            Some(StartupSample { duration_ms, recorded_at })
+        })
+        .collect())
+}
+
+/// Duration of the last startup of every server that booted at least once
+pub async fn last_startup_durations(pool: &DbPool) -> Result<HashMap<String, u64>, sqlx::Error> {
+    let rows: Vec<(String, Option<String>)> = sqlx::query_as(
+        "SELECT server_id, detail FROM server_events
+         WHERE id IN (SELECT MAX(id) FROM server_events WHERE event_type = ? GROUP BY server_id)"
+    )
+    .bind(EVENT_READY)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .filter_map(|(server_id, detail)| Some((server_id, detail?.parse().ok()?)))
+        .collect())
+}
+
+/// Store one metrics sample for every running server
+pub async fn sample_metrics(pool: &DbPool, pm: &ProcessManager) -> anyhow::Result<()> {
+    let now = chrono::Utc::now().to_rfc3339();
+    let mut samples: Vec<(String, f32, u64, usize, u64)> = Vec::new();
+    {
+        let processes = pm.get_processes_read_guard().await;
+        for (id, proc) in processes.iter().filter(|(_, proc)| proc.has_game_process()) {
+            let cpu = *proc.last_cpu_normalized.read().await;
+            let memory = *proc.last_memory.read().await;
+            let disk = *proc.last_disk.read().await;
+            samples.push((id.clone(), cpu, memory, proc.player_count().await, disk));
+        }
+    }
+
+    for (server_id, cpu, memory, players, disk) in samples {
+        sqlx::query(
+            "INSERT INTO metrics_history (server_id, cpu, memory_bytes, players, recorded_at, disk_bytes) VALUES (?, ?, ?, ?, ?, ?)"
+        )
+        .bind(&server_id)
+        .bind(cpu)
+        .bind(memory as i64)
+        .bind(players as i64)
+        .bind(&now)
+        // Not measured yet right after a start
+        .bind((disk > 0).then_some(disk as i64))
+        .execute(pool)
+        .await?;
+    }
+
+    Ok(())
+}
+
+/// Metrics of a server over a period of time, averaged per `interval_secs`
+#[derive(Debug, Serialize, ToSchema)]
+pub struct MetricsPoint {
+    /// Start of the interval
+    pub recorded_at: String,
+    /// Percent of the whole host, averaged; absent when hidden from the caller's role
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cpu: Option<f32>,
+    /// Averaged, absent when hidden from the caller's role
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub memory_bytes: Option<i64>,
+    /// Most players online at once
+    pub players: i64,
+    /// Last measured size of the server directory, absent before the first measurement
+    pub disk_bytes: Option<i64>,
+}
+
+/// Samples of a server since `since`, oldest first, grouped into `interval_secs` intervals.
+/// Intervals without samples (the server was stopped) are left out.
+pub async fn metrics_series(pool: &DbPool, server_id: &str, since: DateTime<Utc>, interval_secs: i64) -> Result<Vec<MetricsPoint>, sqlx::Error> {
+    let rows: Vec<(f32, i64, i64, String, Option<i64>)> = sqlx::query_as(
+        "SELECT cpu, memory_bytes, players, recorded_at, disk_bytes FROM metrics_history
+         WHERE server_id = ? AND recorded_at >= ? ORDER BY recorded_at"
+    )
+    .bind(server_id)
+    .bind(since.to_rfc3339())
+    .fetch_all(pool)
+    .await?;
+
+    // Interval index -> (cpu sum, memory sum, samples, players max, last disk)
+    let mut intervals: BTreeMap<i64, (f64, i64, i64, i64, Option<i64>)> = BTreeMap::new();
+    for (cpu, memory, players, recorded_at, disk) in rows {
+        let Ok(at) = DateTime::parse_from_rfc3339(&recorded_at) else {
+            continue;
+        };
+        let index = (at.timestamp() - since.timestamp()) / interval_secs.max(1);
+        let entry = intervals.entry(index).or_insert((0.0, 0, 0, 0, None));
+        entry.0 += f64::from(cpu);
+        entry.1 += memory;
+        entry.2 += 1;
+        entry.3 = entry.3.max(players);
+        entry.4 = disk.or(entry.4);
+    }
+
+    Ok(intervals
+        .into_iter()
+        .map(|(index, (cpu, memory, samples, players, disk))| MetricsPoint {
+            recorded_at: (since + chrono::Duration::seconds(index * interval_secs)).to_rfc3339(),
+            cpu: Some((cpu / samples as f64) as f32),
+            memory_bytes: Some(memory / samples),
+            players,
+            disk_bytes: disk,
+        })
+        .collect())
+}
@@ -0,0 +1,120 @@
+//! Authentication of inbound calls from outside the panel (hooks today, remote agents later):
+//! the caller signs `timestamp.nonce.body` with a shared secret (HMAC-SHA256), each nonce is
+//! accepted once within the timestamp window, and the source address can be restricted.
+
+use std::net::IpAddr;
+
+use axum::http::HeaderMap;
+use chrono::Utc;
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+use crate::db::DbPool;
+use crate::error::AppError;
+
+pub const HEADER_TIMESTAMP: &str = "x-draveur-timestamp";
+pub const HEADER_NONCE: &str = "x-draveur-nonce";
+/// `sha256=<hex>`
+pub const HEADER_SIGNATURE: &str = "x-draveur-signature";
+
+/// Accepted difference between the caller's timestamp and the panel clock
+pub const MAX_CLOCK_SKEW_SECS: i64 = 300;
+
+const NONCE_MIN_LEN: usize = 8;
+const NONCE_MAX_LEN: usize = 128;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Hex signature of a call, as the caller computes it
+pub fn sign(secret: &str, timestamp: i64, nonce: &str, body: &[u8]) -> String {
+    hex::encode(mac(secret, timestamp, nonce, body).finalize().into_bytes())
+}
+
+fn mac(secret: &str, timestamp: i64, nonce: &str, body: &[u8]) -> HmacSha256 {
+    let mut mac = HmacSha256::new_from_slice(secret.as_bytes()).expect("HMAC accepts any key length");
+    mac.update(format!("{}.{}.", timestamp, nonce).as_bytes());
+    mac.update(body);
+    mac
+}
+
+/// An allowlist entry: a single address or a CIDR range
+pub fn is_valid_ip_rule(rule: &str) -> bool {
+    let rule = rule.trim();
+    rule.parse::<IpAddr>().is_ok() || rule.parse::<ipnet::IpNet>().is_ok()
+}
+
+/// Whether `ip` matches the allowlist; an empty list allows every address
+pub fn ip_allowed(rules: &[String], ip: IpAddr) -> bool {
+    // IPv4 clients of a dual-stack listener show up as ::ffff:a.b.c.d
+    let ip = match ip {
+        IpAddr::V6(v6) => v6.to_ipv4_mapped().map(IpAddr::V4).unwrap_or(ip),
+        v4 => v4,
+    };
+    rules.is_empty()
+        || rules.iter().map(|r| r.trim()).any(|rule| match rule.parse::<IpAddr>() {
+            Ok(allowed) => allowed == ip,
+            Err(_) => rule.parse::<ipnet::IpNet>().is_ok_and(|net| net.contains(&ip)),
+        })
+}
+
+/// Check a call to the inbound endpoint `key_id`: source address, signature, timestamp window
+/// and nonce reuse. `peer` is the TCP peer, `None` when it couldn't be determined.
+pub async fn verify(
+    pool: &DbPool,
+    key_id: &str,
+    secret: &str,
+    allowed_ips: &[String],
+    peer: Option<IpAddr>,
+    headers: &HeaderMap,
+    body: &[u8],
+) -> Result<(), AppError> {
+    if !allowed_ips.is_empty() && !peer.is_some_and(|ip| ip_allowed(allowed_ips, ip)) {
+        return Err(AppError::Unauthorized("hooks.ip_not_allowed".into()));
+    }
+
+    let header = |name: &str| headers.get(name).and_then(|v| v.to_str().ok()).map(str::trim);
+    let (Some(timestamp), Some(nonce), Some(signature)) =
+        (header(HEADER_TIMESTAMP), header(HEADER_NONCE), header(HEADER_SIGNATURE))
+    else {
+        return Err(AppError::Unauthorized("hooks.signature_missing".into()));
+    };
+
+    let timestamp: i64 = timestamp.parse().map_err(|_| AppError::Unauthorized("hooks.invalid_timestamp".into()))?;
+    if (Utc::now().timestamp() - timestamp).abs() > MAX_CLOCK_SKEW_SECS {
+        return Err(AppError::Unauthorized("hooks.timestamp_expired".into()));
+    }
+    if !(NONCE_MIN_LEN..=NONCE_MAX_LEN).contains(&nonce.len()) {
+        return Err(AppError::Unauthorized("hooks.invalid_nonce".into()));
+    }
+
+    let signature = signature.strip_prefix("sha256=").unwrap_or(signature);
+    let signature = hex::decode(signature).map_err(|_| AppError::Unauthorized("hooks.invalid_signature".into()))?;
+    mac(secret, timestamp, nonce, body)
+        .verify_slice(&signature)
+        .map_err(|_| AppError::Unauthorized("hooks.invalid_signature".into()))?;
+
+    // Only signed calls reach the nonce table, so it can't be filled by anonymous requests
+    remember_nonce(pool, key_id, nonce).await
+}
+
+/// Record the nonce, failing when it was already used; nonces older than the timestamp window
+/// are forgotten since a replay of them fails the timestamp check anyway
+async fn remember_nonce(pool: &DbPool, key_id: &str, nonce: &str) -> Result<(), AppError> {
+    let now = Utc::now();
+    sqlx::query("DELETE FROM inbound_nonces WHERE seen_at < ?")
+        .bind((now - chrono::Duration::seconds(2 * MAX_CLOCK_SKEW_SECS)).to_rfc3339())
+        .execute(pool)
+        .await?;
+
+    let inserted = sqlx::query("INSERT OR IGNORE INTO inbound_nonces (key_id, nonce, seen_at) VALUES (?, ?, ?)")
+        .bind(key_id)
+        .bind(nonce)
+        .bind(now.to_rfc3339())
+        .execute(pool)
+        .await?
+        .rows_affected();
+    if inserted == 0 {
+        return Err(AppError::Unauthorized("hooks.replayed".into()));
+    }
+    Ok(())
+}
@@ -0,0 +1,215 @@
+//! IP denylist checked before routing: bans placed by admins, and temporary ones placed
+//! automatically on addresses that keep failing logins or probing paths outside server
+//! directories. Bans are cached in memory so the check costs no query per request.
+
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::sync::{Mutex, RwLock};
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Utc};
+use ipnet::IpNet;
+use serde::Serialize;
+use sqlx::FromRow;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::db::DbPool;
+use crate::services::audit;
+
+/// Offense weights of one address are summed over this window
+const STRIKE_WINDOW: Duration = Duration::from_secs(15 * 60);
+const STRIKE_THRESHOLD: u32 = 30;
+/// Length of an automatic ban
+const AUTO_BAN_MINUTES: i64 = 60;
+
+/// Suspicious requests counting towards an automatic ban
+#[derive(Debug, Clone, Copy)]
+pub enum Offense {
+    /// Wrong password or second factor
+    AuthFailure,
+    /// A file path resolving outside its server directory
+    PathTraversal,
+}
+
+impl Offense {
+    fn weight(self) -> u32 {
+        match self {
+            Offense::AuthFailure => 1,
+            // Nobody types these by mistake
+            Offense::PathTraversal => 5,
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            Offense::AuthFailure => "repeated authentication failures",
+            Offense::PathTraversal => "path traversal attempts",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, FromRow, ToSchema)]
+pub struct IpBan {
+    pub id: String,
+    /// Single address or CIDR network
+    pub address: String,
+    pub reason: Option<String>,
+    /// Placed by the suspicious-activity detection rather than an admin
+    pub automatic: bool,
+    pub created_by: Option<String>,
+    pub created_at: String,
+    /// None for a permanent ban
+    pub expires_at: Option<String>,
+}
+
+struct CachedBan {
+    network: IpNet,
+    expires_at: Option<DateTime<Utc>>,
+}
+
+lazy_static::lazy_static! {
+    static ref BANS: RwLock<Vec<CachedBan>> = RwLock::new(Vec::new());
+    /// Address -> start of its strike window and the weight counted in it
+    static ref STRIKES: Mutex<HashMap<IpAddr, (Instant, u32)>> = Mutex::new(HashMap::new());
+}
+
+/// `1.2.3.4` or `1.2.3.0/24` in canonical form, None when invalid
+pub fn parse_address(raw: &str) -> Option<IpNet> {
+    let raw = raw.trim();
+    match raw.parse::<IpAddr>() {
+        Ok(ip) => IpNet::new(ip, if ip.is_ipv4() { 32 } else { 128 }).ok(),
+        Err(_) => raw.parse::<IpNet>().ok().map(|net| net.trunc()),
+    }
+}
+
+/// Stored form of a network: the bare address for a single host
+pub fn display_address(network: &IpNet) -> String {
+    if network.prefix_len() == network.max_prefix_len() {
+        network.addr().to_string()
+    } else {
+        network.to_string()
+    }
+}
+
+pub fn is_banned(ip: IpAddr) -> bool {
+    let now = Utc::now();
+    BANS.read().is_ok_and(|bans| {
+        bans.iter().any(|ban| ban.network.contains(&ip) && ban.expires_at.is_none_or(|e| e > now))
+    })
+}
+
+/// Drop expired bans and reload the cache, at startup and after every change
+pub async fn load(pool: &DbPool) -> Result<(), sqlx::Error> {
+    let now = Utc::now().to_rfc3339();
+    sqlx::query("DELETE FROM ip_bans WHERE expires_at IS NOT NULL AND expires_at <= ?")
+        .bind(&now)
+        .execute(pool)
+        .await?;
+    let rows: Vec<(String, Option<String>)> = sqlx::query_as("SELECT address, expires_at FROM ip_bans")
+        .fetch_all(pool)
+        .await?;
+
+    let bans = rows.into_iter()
+        .filter_map(|(address, expires_at)| {
+            Some(CachedBan {
+                network: parse_address(&address)?,
+                expires_at: expires_at.and_then(|e| DateTime::parse_from_rfc3339(&e).ok()).map(|e| e.with_timezone(&Utc)),
+            })
+        })
+        .collect();
+    if let Ok(mut cached) = BANS.write() {
+        *cached = bans;
+    }
+    Ok(())
+}
+
+/// Bans in force, newest first
+pub async fn list(pool: &DbPool) -> Result<Vec<IpBan>, sqlx::Error> {
+    sqlx::query_as("SELECT * FROM ip_bans WHERE expires_at IS NULL OR expires_at > ? ORDER BY created_at DESC")
+        .bind(Utc::now().to_rfc3339())
+        .fetch_all(pool)
+        .await
+}
+
+pub async fn add(
+    pool: &DbPool,
+    network: &IpNet,
+    reason: Option<&str>,
+    expires_at: Option<DateTime<Utc>>,
+    automatic: bool,
+    created_by: Option<&str>,
+) -> Result<IpBan, sqlx::Error> {
+    let ban = IpBan {
+        id: Uuid::new_v4().to_string(),
+        address: display_address(network),
+        reason: reason.map(str::to_string),
+        automatic,
+        created_by: created_by.map(str::to_string),
+        created_at: Utc::now().to_rfc3339(),
+        expires_at: expires_at.map(|e| e.to_rfc3339()),
+    };
+    sqlx::query(
+        "INSERT INTO ip_bans (id, address, reason, automatic, created_by, created_at, expires_at) VALUES (?, ?, ?, ?, ?, ?, ?)"
+    )
+    .bind(&ban.id)
+    .bind(&ban.address)
+    .bind(&ban.reason)
+    .bind(ban.automatic)
+    .bind(&ban.created_by)
+    .bind(&ban.created_at)
+    .bind(&ban.expires_at)
+    .execute(pool)
+    .await?;
+    load(pool).await?;
+    Ok(ban)
+}
+
+/// Lift a ban, false when there was none with this id
+pub async fn remove(pool: &DbPool, id: &str) -> Result<bool, sqlx::Error> {
+    let removed = sqlx::query("DELETE FROM ip_bans WHERE id = ?")
+        .bind(id)
+        .execute(pool)
+        .await?
+        .rows_affected() > 0;
+    load(pool).await?;
+    Ok(removed)
+}
+
+/// Count an offense from `ip`, banning it for a while past the threshold. Loopback is never
+/// banned automatically: behind a local reverse proxy every request comes from it.
+pub async fn record_offense(pool: &DbPool, ip: Option<IpAddr>, offense: Offense) {
+    let Some(ip) = ip.filter(|ip| !ip.is_loopback()) else {
+        return;
+    };
+    let now = Instant::now();
+    let reached = {
+        let Ok(mut strikes) = STRIKES.lock() else {
+            return;
+        };
+        strikes.retain(|_, (start, _)| now.duration_since(*start) < STRIKE_WINDOW);
+        let (_, weight) = strikes.entry(ip).or_insert((now, 0));
+        *weight += offense.weight();
+        let reached = *weight >= STRIKE_THRESHOLD;
+        if reached {
+            strikes.remove(&ip);
+        }
+        reached
+    };
+    if !reached || is_banned(ip) {
+        return;
+    }
+
+    let Some(network) = parse_address(&ip.to_string()) else {
+        return;
+    };
+    let expires_at = Utc::now() + chrono::Duration::minutes(AUTO_BAN_MINUTES);
+    match add(pool, &network, Some(offense.label()), Some(expires_at), true, None).await {
+        Ok(_) => {
+            let detail = format!("{} banned for {} minutes: {}", ip, AUTO_BAN_MINUTES, offense.label());
+            tracing::warn!("IP ban: {}", detail);
+            audit::record(pool, None, None, audit::ACTION_IP_BANNED, Some(&detail), Some(&ip.to_string())).await;
+        }
+        Err(e) => tracing::error!("Failed to ban {}: {}", ip, e),
+    }
+}
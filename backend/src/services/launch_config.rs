@@ -0,0 +1,78 @@
+//! What a server is launched with, from its DB row. Every start path (API, auto-start, queue,
+//! scheduled restart) builds its launch parameters and writes config.json here.
+
+use crate::api::servers::models::ServerRow;
+use crate::services::console_log::ConsoleLogOptions;
+use crate::services::health_probe::HealthProbe;
+use crate::services::resource_alerts::AlertThresholds;
+use crate::services::LaunchConfig;
+use crate::templates;
+use crate::utils::atomic_file;
+use crate::utils::memory::{calculate_total_memory, parse_memory_to_bytes};
+
+/// Build the process launch parameters for a server from its DB row
+pub fn build_launch_config(server: &ServerRow) -> LaunchConfig {
+    let mut config = server.config.as_ref()
+        .and_then(|c| serde_json::from_str::<serde_json::Value>(c).ok())
+        .unwrap_or(serde_json::json!({}));
+    if let Some(obj) = config.as_object_mut() {
+        obj.insert("port".to_string(), serde_json::json!(server.port));
+        obj.insert("bind_address".to_string(), serde_json::json!(server.bind_address));
+    }
+
+    LaunchConfig {
+        game_type: server.game_type.clone(),
+        executable_path: server.executable_path.clone(),
+        working_dir: server.working_dir.clone(),
+        java_path: server.java_path.clone(),
+        max_memory: server.max_memory.clone(),
+        extra_args: server.extra_args.clone(),
+        config: Some(config),
+        env_vars: server.env_vars.as_deref().and_then(|r| serde_json::from_str(r).ok()).unwrap_or_default(),
+        // Dummy servers always run the built-in simulator
+        launch_mode: if server.game_type == "dummy" { "simulator".to_string() } else { server.launch_mode.clone() },
+        command_template: server.command_template.clone(),
+        runtime: server.runtime.clone(),
+        docker_image: server.docker_image.clone(),
+        health_probe: HealthProbe::from_settings(server.health_probe.as_deref(), server.health_probe_interval_secs),
+        stop_command: server.stop_command.clone(),
+        console_log: (server.console_log_enabled != 0).then(|| ConsoleLogOptions {
+            keep: server.log_rotation_count.max(0) as u32,
+            max_size_bytes: server.console_log_max_mb.max(1) as u64 * 1024 * 1024,
+            dedup: server.console_log_dedup != 0,
+        }),
+        alert_thresholds: AlertThresholds {
+            cpu_percent: server.alert_cpu_percent.filter(|v| *v > 0.0).map(|v| v as f32),
+            ram_percent: server.alert_ram_percent.filter(|v| *v > 0.0).map(|v| v as f32),
+            disk_bytes: server.alert_disk_mb.filter(|v| *v > 0).map(|v| v as u64 * 1024 * 1024),
+            max_memory_bytes: calculate_total_memory(parse_memory_to_bytes(server.max_memory.as_deref().unwrap_or("4G"))),
+        },
+    }
+}
+
+/// Bring config.json in line with the server's settings before a launch
+pub async fn prepare_config_json(server: &ServerRow) {
+    let config_json_path = std::path::Path::new(&server.working_dir).join("config.json");
+    let server_config: Option<serde_json::Value> = server.config.as_ref().and_then(|c| serde_json::from_str(c).ok());
+
+    let port = server.port as u16;
+    let max_players = server_config.as_ref()
+        .and_then(|c| c.get("MaxPlayers"))
+        .and_then(|v| v.as_u64())
+        .map(|v| v as u32);
+    let auth_mode = &server.auth_mode;
+
+    // Edits made in the file manager (MOTD, rate limits, modules...) survive the start
+    let existing = tokio::fs::read_to_string(&config_json_path).await.ok()
+        .and_then(|content| serde_json::from_str::<serde_json::Value>(&content).ok())
+        .filter(serde_json::Value::is_object);
+    let mut hytale_config_obj = existing.unwrap_or_else(|| templates::generate_config_json(
+        &server.name,
+        max_players.unwrap_or(100),
+        auth_mode
+    ));
+    templates::apply_managed_keys(&mut hytale_config_obj, port, max_players, auth_mode);
+    if let Err(e) = atomic_file::write(&config_json_path, serde_json::to_string_pretty(&hytale_config_obj).unwrap()).await {
+        tracing::error!("Failed to write config.json for server {}: {}", server.id, e);
+    }
+}
@@ -0,0 +1,77 @@
+//! Periodic pruning of each server's `logs/` directory according to `logs_retention_days`
+
+use std::path::Path;
+use std::time::{Duration, SystemTime};
+
+use walkdir::WalkDir;
+
+use crate::db::DbPool;
+use crate::services::history;
+
+/// Seconds between two cleanup passes
+pub const CHECK_INTERVAL_SECS: u64 = 3600;
+
+/// Live file written by the process manager, rotated rather than pruned
+pub const LIVE_LOG_NAME: &str = "console.log";
+
+/// Delete log files older than the retention of every server and record what was reclaimed
+pub async fn prune_all(pool: &DbPool) -> anyhow::Result<()> {
+    let servers: Vec<(String, String, String, i32)> = sqlx::query_as(
+        "SELECT id, name, working_dir, logs_retention_days FROM servers WHERE logs_retention_days > 0"
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let mut total_files = 0;
+    let mut total_bytes = 0;
+
+    for (id, name, working_dir, retention_days) in servers {
+        let logs_dir = Path::new(&working_dir).join("logs");
+        let max_age = Duration::from_secs(retention_days as u64 * 24 * 3600);
+
+        let (files, bytes) = tokio::task::spawn_blocking(move || prune_dir(&logs_dir, max_age)).await?;
+        if files == 0 {
+            continue;
+        }
+
+        tracing::info!("Pruned {} log files ({} bytes) of server {}", files, bytes, name);
+        history::record_event(pool, &id, history::EVENT_LOGS_PRUNED, Some(&format!("{} files, {} bytes", files, bytes))).await;
+        total_files += files;
+        total_bytes += bytes;
+    }
+
+    if total_files > 0 {
+        tracing::info!("Log cleanup reclaimed {} bytes across {} files", total_bytes, total_files);
+    }
+    Ok(())
+}
+
+/// Remove files last modified more than `max_age` ago, returning how many and their total size
+fn prune_dir(logs_dir: &Path, max_age: Duration) -> (u64, u64) {
+    let Some(cutoff) = SystemTime::now().checked_sub(max_age) else {
+        return (0, 0);
+    };
+
+    let mut files = 0;
+    let mut bytes = 0;
+    for entry in WalkDir::new(logs_dir).into_iter().filter_map(|e| e.ok()) {
+        if !entry.file_type().is_file() || (entry.path().parent() == Some(logs_dir) && entry.file_name() == LIVE_LOG_NAME) {
+            continue;
+        }
+        let Ok(metadata) = entry.metadata() else {
+            continue;
+        };
+        if !metadata.modified().is_ok_and(|modified| modified < cutoff) {
+            continue;
+        }
+
+        match std::fs::remove_file(entry.path()) {
+            Ok(()) => {
+                files += 1;
+                bytes += metadata.len();
+            }
+            Err(e) => tracing::warn!("Failed to delete old log {:?}: {}", entry.path(), e),
+        }
+    }
+    (files, bytes)
+}
@@ -0,0 +1,91 @@
+//! Brute-force protection for `/auth/login`: failed attempts are counted per client IP and per
+//! username, and past a few free failures each new one locks the key out for twice as long as
+//! the previous lockout.
+
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Failures allowed on a username before it gets locked
+const USERNAME_FREE_FAILURES: u32 = 5;
+/// Failures allowed from one IP, higher since several users may share it
+const IP_FREE_FAILURES: u32 = 20;
+/// First lockout, doubled by every failure past the free ones
+const BASE_LOCKOUT: Duration = Duration::from_secs(30);
+const MAX_LOCKOUT: Duration = Duration::from_secs(60 * 60);
+/// Failures are forgotten after this long without a new one
+const FORGET_AFTER: Duration = Duration::from_secs(24 * 60 * 60);
+
+lazy_static::lazy_static! {
+    /// `ip:<addr>` / `user:<name>` -> failures and lockout
+    static ref ATTEMPTS: Mutex<HashMap<String, Attempts>> = Mutex::new(HashMap::new());
+}
+
+struct Attempts {
+    failures: u32,
+    last_failure: Instant,
+    locked_until: Option<Instant>,
+}
+
+/// A key that just got locked out, for the audit trail
+pub struct Lockout {
+    pub key: String,
+    pub duration: Duration,
+}
+
+fn keys(ip: Option<IpAddr>, username: &str) -> Vec<(String, u32)> {
+    let mut keys = vec![(format!("user:{}", username.trim().to_lowercase()), USERNAME_FREE_FAILURES)];
+    if let Some(ip) = ip {
+        keys.push((format!("ip:{}", ip), IP_FREE_FAILURES));
+    }
+    keys
+}
+
+fn lockout_after(failures: u32, free: u32) -> Option<Duration> {
+    let extra = failures.checked_sub(free)?;
+    let lockout = BASE_LOCKOUT.saturating_mul(2u32.saturating_pow(extra.min(16)));
+    Some(lockout.min(MAX_LOCKOUT))
+}
+
+/// Time left before the IP or the username may try again, `None` when neither is locked
+pub fn retry_after(ip: Option<IpAddr>, username: &str) -> Option<Duration> {
+    let Ok(mut attempts) = ATTEMPTS.lock() else {
+        return None;
+    };
+    let now = Instant::now();
+    attempts.retain(|_, a| now.duration_since(a.last_failure) < FORGET_AFTER);
+    keys(ip, username)
+        .iter()
+        .filter_map(|(key, _)| attempts.get(key)?.locked_until)
+        .filter(|until| *until > now)
+        .map(|until| until - now)
+        .max()
+}
+
+/// Count a failed login, returning the lockouts it started
+pub fn record_failure(ip: Option<IpAddr>, username: &str) -> Vec<Lockout> {
+    let Ok(mut attempts) = ATTEMPTS.lock() else {
+        return Vec::new();
+    };
+    let now = Instant::now();
+    let mut lockouts = Vec::new();
+    for (key, free) in keys(ip, username) {
+        let entry = attempts.entry(key.clone()).or_insert(Attempts { failures: 0, last_failure: now, locked_until: None });
+        entry.failures += 1;
+        entry.last_failure = now;
+        if let Some(duration) = lockout_after(entry.failures, free) {
+            entry.locked_until = Some(now + duration);
+            lockouts.push(Lockout { key, duration });
+        }
+    }
+    lockouts
+}
+
+/// Successful login: the username starts over. The IP keeps its count so one valid account
+/// can't be used to keep guessing others.
+pub fn record_success(username: &str) {
+    if let Ok(mut attempts) = ATTEMPTS.lock() {
+        attempts.remove(&format!("user:{}", username.trim().to_lowercase()));
+    }
+}
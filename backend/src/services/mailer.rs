@@ -0,0 +1,76 @@
+//! Outgoing email through the SMTP server of the `smtp_*` settings, for password reset links
+
+use std::collections::HashMap;
+
+use anyhow::Context;
+use lettre::message::{header::ContentType, Mailbox};
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor};
+
+use crate::db::DbPool;
+
+/// How the connection to the SMTP server is secured
+pub const SECURITY_MODES: &[&str] = &["starttls", "tls", "none"];
+const DEFAULT_SECURITY: &str = "starttls";
+
+/// SMTP settings, from the `smtp_*` keys of the settings table
+pub struct SmtpConfig {
+    pub host: String,
+    /// The mode's usual port when unset
+    pub port: Option<u16>,
+    pub security: String,
+    pub username: Option<String>,
+    pub password: Option<String>,
+    pub from: String,
+    /// The panel's address as users reach it, links in emails point there
+    pub panel_url: String,
+}
+
+/// Email settings, `None` until a host, a sender and the panel URL are configured
+pub async fn config(pool: &DbPool) -> Option<SmtpConfig> {
+    let rows: Vec<(String, String)> = sqlx::query_as("SELECT key, value FROM settings WHERE key LIKE 'smtp_%'")
+        .fetch_all(pool)
+        .await
+        .ok()?;
+    let settings: HashMap<String, String> = rows.into_iter().collect();
+    let get = |key: &str| settings.get(key).map(|v| v.trim().to_string()).filter(|v| !v.is_empty());
+
+    Some(SmtpConfig {
+        host: get("smtp_host")?,
+        port: get("smtp_port").and_then(|p| p.parse().ok()),
+        security: get("smtp_security").unwrap_or_else(|| DEFAULT_SECURITY.to_string()),
+        username: get("smtp_username"),
+        password: get("smtp_password"),
+        from: get("smtp_from")?,
+        panel_url: get("smtp_panel_url")?.trim_end_matches('/').to_string(),
+    })
+}
+
+fn transport(config: &SmtpConfig) -> anyhow::Result<AsyncSmtpTransport<Tokio1Executor>> {
+    let mut builder = match config.security.as_str() {
+        "tls" => AsyncSmtpTransport::<Tokio1Executor>::relay(&config.host)?,
+        "none" => AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(&config.host),
+        _ => AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(&config.host)?,
+    };
+    if let Some(port) = config.port {
+        builder = builder.port(port);
+    }
+    if let (Some(username), Some(password)) = (&config.username, &config.password) {
+        builder = builder.credentials(Credentials::new(username.clone(), password.clone()));
+    }
+    Ok(builder.build())
+}
+
+/// Send a plain text email
+pub async fn send(config: &SmtpConfig, to: &str, subject: &str, body: String) -> anyhow::Result<()> {
+    let from: Mailbox = config.from.parse().with_context(|| format!("invalid sender {}", config.from))?;
+    let to: Mailbox = to.parse().with_context(|| format!("invalid recipient {}", to))?;
+    let message = Message::builder()
+        .from(from)
+        .to(to)
+        .subject(subject)
+        .header(ContentType::TEXT_PLAIN)
+        .body(body)?;
+    transport(config)?.send(message).await.context("SMTP delivery failed")?;
+    Ok(())
+}
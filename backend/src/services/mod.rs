@@ -1,6 +1,49 @@
 pub mod process_manager;
 pub mod backup_service;
+pub mod backup_crypto;
+pub mod backup_quota;
+pub mod db_backup;
 pub mod discord_service;
 pub mod scheduler;
+pub mod server_access;
+pub mod broadcast_messages;
+pub mod history;
+pub mod daily_summary;
+pub mod scheduled_restart;
+pub mod launch_config;
+pub mod announcements;
+pub mod console_log;
+pub mod log_cleanup;
+pub mod retention;
+pub mod resource_alerts;
+pub mod simulator;
+pub mod snapshots;
+pub mod auth_detection;
+pub mod docker;
+pub mod health_probe;
+pub mod elevation;
+pub mod events;
+pub mod inbound_auth;
+pub mod network;
+pub mod support_bundle;
+pub mod remote_storage;
+pub mod backup_transfer;
+pub mod world_flush;
+pub mod operation_lock;
+pub mod outgoing_webhooks;
+pub mod totp;
+pub mod audit;
+pub mod login_throttle;
+pub mod password_reset;
+pub mod mailer;
+pub mod oidc;
+pub mod cors;
+pub mod ip_bans;
+pub mod sessions;
+pub mod protected_paths;
+pub mod rate_limit;
+pub mod request_id;
+pub mod panel_metrics;
 
-pub use process_manager::ProcessManager;
+pub use operation_lock::OperationLocks;
+pub use process_manager::{LaunchConfig, ProcessManager};
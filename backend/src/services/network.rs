@@ -0,0 +1,35 @@
+//! Multi-server networks: a proxy in front of lobby and game servers. Servers point to their
+//! proxy through `parent_proxy_id`, so listings can show each network as a group.
+
+pub const ROLE_PROXY: &str = "proxy";
+pub const ROLE_LOBBY: &str = "lobby";
+pub const ROLE_GAME: &str = "game";
+
+/// Empty means standalone
+pub fn is_valid_role(role: &str) -> bool {
+    role.is_empty() || role == ROLE_PROXY || role == ROLE_LOBBY || role == ROLE_GAME
+}
+
+/// Reorder servers so every proxy is directly followed by its members, flagged `true`. Servers
+/// whose proxy is not in the list stay at the top level. `key` gives a server's id and proxy id.
+pub fn group_by_proxy<T>(servers: Vec<T>, key: impl Fn(&T) -> (&str, Option<&str>)) -> Vec<(T, bool)> {
+    let ids: Vec<String> = servers.iter().map(|s| key(s).0.to_string()).collect();
+    let (members, top): (Vec<T>, Vec<T>) = servers.into_iter().partition(|s| {
+        key(s).1.filter(|p| !p.is_empty()).is_some_and(|parent| parent != key(s).0 && ids.iter().any(|id| id == parent))
+    });
+
+    let mut members: Vec<Option<T>> = members.into_iter().map(Some).collect();
+    let mut grouped = Vec::with_capacity(ids.len());
+    for server in top {
+        let id = key(&server).0.to_string();
+        grouped.push((server, false));
+        for slot in members.iter_mut() {
+            if slot.as_ref().is_some_and(|m| key(m).1 == Some(id.as_str())) {
+                grouped.extend(slot.take().map(|m| (m, true)));
+            }
+        }
+    }
+    // Members of a proxy that is itself a member elsewhere (not allowed, but keep them visible)
+    grouped.extend(members.into_iter().flatten().map(|m| (m, false)));
+    grouped
+}
@@ -0,0 +1,236 @@
+//! OpenID Connect single sign-on (Authelia, Keycloak, Google...): provider discovery, the
+//! authorization code flow and ID token validation against the provider's keys. Which panel
+//! account an identity maps to is decided by `api::oidc`.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use anyhow::{anyhow, bail, Context};
+use jsonwebtoken::jwk::JwkSet;
+use jsonwebtoken::{Algorithm, DecodingKey, Validation};
+use serde::Deserialize;
+use uuid::Uuid;
+
+use crate::db::DbPool;
+
+const DEFAULT_SCOPES: &str = "openid profile email";
+pub const DEFAULT_ROLE: &str = "user";
+/// Time a user has to come back from the provider
+pub const LOGIN_TTL: Duration = Duration::from_secs(10 * 60);
+/// Discovery documents are refetched after this long
+const DISCOVERY_TTL: Duration = Duration::from_secs(60 * 60);
+/// Codes tried on one SSO login before it has to start over
+const SECOND_FACTOR_ATTEMPTS: u32 = 5;
+
+lazy_static::lazy_static! {
+    /// `state` parameter -> nonce of logins waiting for the provider's redirect
+    static ref PENDING: Mutex<HashMap<String, (String, Instant)>> = Mutex::new(HashMap::new());
+    /// Issuer -> provider metadata
+    static ref DISCOVERY: Mutex<HashMap<String, (Discovery, Instant)>> = Mutex::new(HashMap::new());
+    /// Ticket -> user id and codes tried, of SSO logins waiting for the user's authenticator code
+    static ref SECOND_FACTOR: Mutex<HashMap<String, (String, u32, Instant)>> = Mutex::new(HashMap::new());
+}
+
+/// Provider settings, from the `oidc_*` keys of the settings table
+pub struct OidcConfig {
+    pub issuer_url: String,
+    pub client_id: String,
+    pub client_secret: String,
+    /// The panel's `/api/v1/auth/oidc/callback` as the provider reaches it
+    pub redirect_url: String,
+    pub scopes: String,
+    /// Role of accounts created on first login
+    pub default_role: String,
+    /// Attach identities to local accounts with the same username or email
+    pub link_existing: bool,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct Discovery {
+    issuer: String,
+    authorization_endpoint: String,
+    token_endpoint: String,
+    jwks_uri: String,
+}
+
+/// What the provider vouched for
+pub struct Identity {
+    pub subject: String,
+    pub username: Option<String>,
+    pub email: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct IdClaims {
+    sub: String,
+    nonce: Option<String>,
+    preferred_username: Option<String>,
+    email: Option<String>,
+    email_verified: Option<bool>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TokenResponse {
+    id_token: String,
+}
+
+/// SSO settings, `None` until an issuer, a client id and a redirect URL are configured
+pub async fn config(pool: &DbPool) -> Option<OidcConfig> {
+    let rows: Vec<(String, String)> = sqlx::query_as("SELECT key, value FROM settings WHERE key LIKE 'oidc_%'")
+        .fetch_all(pool)
+        .await
+        .ok()?;
+    let settings: HashMap<String, String> = rows.into_iter().collect();
+    let get = |key: &str| settings.get(key).map(|v| v.trim().to_string()).filter(|v| !v.is_empty());
+
+    Some(OidcConfig {
+        issuer_url: get("oidc_issuer_url")?.trim_end_matches('/').to_string(),
+        client_id: get("oidc_client_id")?,
+        client_secret: get("oidc_client_secret").unwrap_or_default(),
+        redirect_url: get("oidc_redirect_url")?,
+        scopes: get("oidc_scopes").unwrap_or_else(|| DEFAULT_SCOPES.to_string()),
+        default_role: get("oidc_default_role").unwrap_or_else(|| DEFAULT_ROLE.to_string()),
+        link_existing: get("oidc_link_existing").is_some_and(|v| v == "true"),
+    })
+}
+
+async fn discover(issuer_url: &str) -> anyhow::Result<Discovery> {
+    if let Ok(cache) = DISCOVERY.lock() {
+        if let Some((discovery, fetched)) = cache.get(issuer_url) {
+            if fetched.elapsed() < DISCOVERY_TTL {
+                return Ok(discovery.clone());
+            }
+        }
+    }
+
+    let url = format!("{}/.well-known/openid-configuration", issuer_url);
+    let discovery: Discovery = reqwest::get(&url)
+        .await?
+        .error_for_status()?
+        .json()
+        .await
+        .with_context(|| format!("invalid discovery document at {}", url))?;
+    if discovery.issuer.trim_end_matches('/') != issuer_url {
+        bail!("discovery document is for issuer {}", discovery.issuer);
+    }
+
+    if let Ok(mut cache) = DISCOVERY.lock() {
+        cache.insert(issuer_url.to_string(), (discovery.clone(), Instant::now()));
+    }
+    Ok(discovery)
+}
+
+/// Provider URL to send the browser to, remembering the login's state and nonce. The state is
+/// returned too, for the browser to prove at the callback that it started the login.
+pub async fn authorization_url(config: &OidcConfig) -> anyhow::Result<(String, String)> {
+    let discovery = discover(&config.issuer_url).await?;
+    let state = Uuid::new_v4().simple().to_string();
+    let nonce = Uuid::new_v4().simple().to_string();
+
+    let url = reqwest::Url::parse_with_params(&discovery.authorization_endpoint, &[
+        ("response_type", "code"),
+        ("client_id", config.client_id.as_str()),
+        ("redirect_uri", config.redirect_url.as_str()),
+        ("scope", config.scopes.as_str()),
+        ("state", state.as_str()),
+        ("nonce", nonce.as_str()),
+    ])?;
+
+    let mut pending = PENDING.lock().map_err(|_| anyhow!("pending logins lock poisoned"))?;
+    pending.retain(|_, (_, created)| created.elapsed() < LOGIN_TTL);
+    pending.insert(state.clone(), (nonce, Instant::now()));
+    Ok((url.into(), state))
+}
+
+/// Finish a login the provider redirected back: trade the code for an ID token and check it
+pub async fn complete(config: &OidcConfig, code: &str, state: &str) -> anyhow::Result<Identity> {
+    let nonce = PENDING
+        .lock()
+        .map_err(|_| anyhow!("pending logins lock poisoned"))?
+        .remove(state)
+        .filter(|(_, created)| created.elapsed() < LOGIN_TTL)
+        .map(|(nonce, _)| nonce)
+        .ok_or_else(|| anyhow!("unknown or expired login state"))?;
+
+    let discovery = discover(&config.issuer_url).await?;
+    let tokens: TokenResponse = reqwest::Client::new()
+        .post(&discovery.token_endpoint)
+        .form(&[
+            ("grant_type", "authorization_code"),
+            ("code", code),
+            ("redirect_uri", config.redirect_url.as_str()),
+            ("client_id", config.client_id.as_str()),
+            ("client_secret", config.client_secret.as_str()),
+        ])
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await
+        .context("token endpoint answered without an ID token")?;
+
+    let claims = validate_id_token(&discovery, &config.client_id, &tokens.id_token).await?;
+    if claims.nonce.as_deref() != Some(nonce.as_str()) {
+        bail!("ID token nonce doesn't match the login");
+    }
+
+    Ok(Identity {
+        subject: claims.sub,
+        username: claims.preferred_username.filter(|u| !u.trim().is_empty()),
+        // An address the provider didn't check can't identify anyone
+        email: claims.email.filter(|_| claims.email_verified != Some(false)),
+    })
+}
+
+async fn validate_id_token(discovery: &Discovery, client_id: &str, id_token: &str) -> anyhow::Result<IdClaims> {
+    let header = jsonwebtoken::decode_header(id_token)?;
+    // Symmetric algorithms would take the client secret as key, only provider keys are trusted
+    if matches!(header.alg, Algorithm::HS256 | Algorithm::HS384 | Algorithm::HS512) {
+        bail!("ID token signed with {:?}", header.alg);
+    }
+
+    let keys: JwkSet = reqwest::get(&discovery.jwks_uri).await?.error_for_status()?.json().await?;
+    let jwk = match header.kid.as_deref() {
+        Some(kid) => keys.find(kid),
+        None => keys.keys.first(),
+    }
+    .ok_or_else(|| anyhow!("no provider key matches the ID token"))?;
+
+    let mut validation = Validation::new(header.alg);
+    validation.set_audience(&[client_id]);
+    validation.set_issuer(&[discovery.issuer.as_str()]);
+    let data = jsonwebtoken::decode::<IdClaims>(id_token, &DecodingKey::from_jwk(jwk)?, &validation)?;
+    Ok(data.claims)
+}
+
+/// Park an SSO login of a user with two-factor authentication until the code comes in. Returns
+/// the ticket to send it with.
+pub fn hold_for_second_factor(user_id: &str) -> String {
+    let ticket = Uuid::new_v4().simple().to_string();
+    if let Ok(mut held) = SECOND_FACTOR.lock() {
+        held.retain(|_, (_, _, created)| created.elapsed() < LOGIN_TTL);
+        held.insert(ticket.clone(), (user_id.to_string(), 0, Instant::now()));
+    }
+    ticket
+}
+
+/// User of a held login, counting the attempt. `None` once the ticket is unknown, expired or
+/// out of attempts.
+pub fn second_factor_attempt(ticket: &str) -> Option<String> {
+    let mut held = SECOND_FACTOR.lock().ok()?;
+    let (user_id, attempts, created) = held.get_mut(ticket)?;
+    if created.elapsed() >= LOGIN_TTL || *attempts >= SECOND_FACTOR_ATTEMPTS {
+        held.remove(ticket);
+        return None;
+    }
+    *attempts += 1;
+    Some(user_id.clone())
+}
+
+/// The code was right, the ticket is used up
+pub fn release_second_factor(ticket: &str) {
+    if let Ok(mut held) = SECOND_FACTOR.lock() {
+        held.remove(ticket);
+    }
+}
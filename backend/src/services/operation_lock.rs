@@ -0,0 +1,73 @@
+//! One long-running filesystem operation per server at a time. A backup archiving a world that
+//! a restore is overwriting, or a reinstall deleting binaries under either, leaves a corrupt
+//! archive or a half-restored server, so they exclude each other.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use crate::error::AppError;
+use crate::services::ProcessManager;
+
+pub const OP_BACKUP: &str = "backup";
+pub const OP_RESTORE: &str = "restore";
+pub const OP_REINSTALL: &str = "reinstall";
+
+/// Polling interval of queued operations
+const QUEUE_POLL: Duration = Duration::from_millis(500);
+
+#[derive(Clone, Default)]
+pub struct OperationLocks {
+    running: Arc<Mutex<HashMap<String, &'static str>>>,
+}
+
+/// Held for the whole operation, the server is released when it is dropped
+pub struct OperationGuard {
+    locks: OperationLocks,
+    server_id: String,
+}
+
+impl Drop for OperationGuard {
+    fn drop(&mut self) {
+        if let Ok(mut running) = self.locks.running.lock() {
+            running.remove(&self.server_id);
+        }
+    }
+}
+
+impl OperationLocks {
+    /// The guard, or the operation already holding the server
+    fn lock(&self, server_id: &str, operation: &'static str) -> Result<OperationGuard, &'static str> {
+        let mut running = self.running.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(current) = running.get(server_id) {
+            return Err(current);
+        }
+        running.insert(server_id.to_string(), operation);
+        Ok(OperationGuard { locks: self.clone(), server_id: server_id.to_string() })
+    }
+
+    /// Take a server for `operation`, failing right away when another one runs on it or the
+    /// server is being installed
+    pub async fn try_acquire(&self, pm: &ProcessManager, server_id: &str, operation: &'static str) -> Result<OperationGuard, AppError> {
+        if pm.is_installing(server_id).await {
+            tracing::info!("Refusing {} of server {}: it is being installed", operation, server_id);
+            return Err(AppError::Conflict("servers.operation_in_progress".into()));
+        }
+        self.lock(server_id, operation).map_err(|current| {
+            tracing::info!("Refusing {} of server {}: {} in progress", operation, server_id, current);
+            AppError::Conflict("servers.operation_in_progress".into())
+        })
+    }
+
+    /// Wait for the server to be free, for background work that can queue
+    pub async fn acquire(&self, pm: &ProcessManager, server_id: &str, operation: &'static str) -> OperationGuard {
+        loop {
+            if !pm.is_installing(server_id).await {
+                if let Ok(guard) = self.lock(server_id, operation) {
+                    return guard;
+                }
+            }
+            tokio::time::sleep(QUEUE_POLL).await;
+        }
+    }
+}
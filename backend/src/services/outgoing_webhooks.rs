@@ -0,0 +1,202 @@
+//! Outgoing webhooks: lifecycle events POSTed as JSON to URLs configured by admins, for
+//! automation that would rather not parse Discord embeds. Deliveries are signed the way inbound
+//! hook calls are (`services::inbound_auth`), with the webhook's secret, so receivers can reuse
+//! the same check.
+
+use std::time::Duration;
+
+use chrono::Utc;
+use serde_json::Value;
+use sqlx::FromRow;
+use uuid::Uuid;
+
+use crate::db::DbPool;
+use crate::services::events::{EventBus, PanelEvent};
+use crate::services::inbound_auth;
+
+pub const EVENT_SERVER_STARTED: &str = "server.started";
+pub const EVENT_SERVER_STOPPED: &str = "server.stopped";
+pub const EVENT_SERVER_CRASHED: &str = "server.crashed";
+pub const EVENT_BACKUP_COMPLETED: &str = "backup.completed";
+pub const EVENT_BACKUP_FAILED: &str = "backup.failed";
+pub const EVENT_PLAYER_JOINED: &str = "player.joined";
+pub const EVENT_PLAYER_LEFT: &str = "player.left";
+/// Sent on demand from the API, never subscribed to
+pub const EVENT_TEST: &str = "test";
+
+pub const EVENTS: &[&str] = &[
+    EVENT_SERVER_STARTED,
+    EVENT_SERVER_STOPPED,
+    EVENT_SERVER_CRASHED,
+    EVENT_BACKUP_COMPLETED,
+    EVENT_BACKUP_FAILED,
+    EVENT_PLAYER_JOINED,
+    EVENT_PLAYER_LEFT,
+];
+
+/// Name of the delivered event
+pub const HEADER_EVENT: &str = "x-draveur-event";
+
+const TIMEOUT: Duration = Duration::from_secs(10);
+/// Attempts of a delivery failing with a network error or a 5xx, 2 s then 4 s apart
+const MAX_ATTEMPTS: u32 = 3;
+
+#[derive(Debug, Clone, FromRow)]
+pub struct WebhookRow {
+    pub id: String,
+    pub name: String,
+    pub url: String,
+    pub secret: String,
+    pub events: String,
+    pub server_id: Option<String>,
+    pub enabled: i32,
+    pub created_by: String,
+    pub created_at: String,
+    pub last_delivery_at: Option<String>,
+    pub last_delivery_status: Option<String>,
+}
+
+impl WebhookRow {
+    pub fn events(&self) -> Vec<String> {
+        serde_json::from_str(&self.events).unwrap_or_default()
+    }
+}
+
+/// Forward the subscribable events of the bus
+pub fn consume_events(pool: &DbPool, bus: &EventBus) {
+    let pool = pool.clone();
+    bus.consume("outgoing_webhooks", move |event| {
+        let (name, data) = match &event {
+            PanelEvent::ServerReady { boot_ms, .. } => (EVENT_SERVER_STARTED, serde_json::json!({ "boot_ms": boot_ms })),
+            PanelEvent::ServerStopped { exit_code, .. } => (EVENT_SERVER_STOPPED, serde_json::json!({ "exit_code": exit_code })),
+            PanelEvent::ServerCrashed { exit_code, auto_restart, .. } => {
+                (EVENT_SERVER_CRASHED, serde_json::json!({ "exit_code": exit_code, "auto_restart": auto_restart }))
+            }
+            PanelEvent::PlayerJoined { player, .. } => (EVENT_PLAYER_JOINED, serde_json::json!({ "player": player })),
+            PanelEvent::PlayerLeft { player, .. } => (EVENT_PLAYER_LEFT, serde_json::json!({ "player": player })),
+            PanelEvent::BackupFinished { backup_id, filename, size_bytes, label, error: None, .. } => (
+                EVENT_BACKUP_COMPLETED,
+                serde_json::json!({ "backup_id": backup_id, "filename": filename, "size_bytes": size_bytes, "label": label }),
+            ),
+            PanelEvent::BackupFinished { backup_id, error: Some(error), .. } => {
+                (EVENT_BACKUP_FAILED, serde_json::json!({ "backup_id": backup_id, "error": error }))
+            }
+            _ => return std::future::ready(()),
+        };
+        dispatch(&pool, name, Some(event.server_id()), data);
+        std::future::ready(())
+    });
+}
+
+/// Send `event` to every enabled webhook subscribed to it, limited to no server or to
+/// `server_id`, without waiting for the deliveries
+pub fn dispatch(pool: &DbPool, event: &'static str, server_id: Option<&str>, data: Value) {
+    let pool = pool.clone();
+    let server_id = server_id.map(str::to_string);
+    tokio::spawn(async move {
+        let webhooks: Vec<WebhookRow> = match sqlx::query_as(
+            "SELECT * FROM outgoing_webhooks WHERE enabled = 1 AND (server_id IS NULL OR server_id = ?)"
+        )
+        .bind(&server_id)
+        .fetch_all(&pool)
+        .await
+        {
+            Ok(webhooks) => webhooks,
+            Err(e) => {
+                tracing::error!("Failed to load outgoing webhooks: {}", e);
+                return;
+            }
+        };
+        let webhooks: Vec<WebhookRow> = webhooks.into_iter().filter(|w| w.events().iter().any(|e| e == event)).collect();
+        if webhooks.is_empty() {
+            return;
+        }
+
+        let payload = payload(&pool, event, server_id.as_deref(), data).await;
+        for webhook in webhooks {
+            let pool = pool.clone();
+            let payload = payload.clone();
+            tokio::spawn(async move {
+                let _ = deliver(&pool, &webhook, &payload).await;
+            });
+        }
+    });
+}
+
+/// `{"event", "timestamp", "server": {"id", "name"} | null, "data"}`
+pub async fn payload(pool: &DbPool, event: &str, server_id: Option<&str>, data: Value) -> Value {
+    let server = match server_id {
+        Some(id) => {
+            let name: Option<String> = sqlx::query_scalar("SELECT name FROM servers WHERE id = ?")
+                .bind(id)
+                .fetch_optional(pool)
+                .await
+                .ok()
+                .flatten();
+            serde_json::json!({ "id": id, "name": name })
+        }
+        None => Value::Null,
+    };
+    serde_json::json!({
+        "event": event,
+        "timestamp": Utc::now().to_rfc3339(),
+        "server": server,
+        "data": data,
+    })
+}
+
+/// POST a payload to one webhook, retrying transient failures, and record the outcome on it.
+/// Returns the final HTTP status, or the error of the last attempt.
+pub async fn deliver(pool: &DbPool, webhook: &WebhookRow, payload: &Value) -> Result<u16, String> {
+    let body = serde_json::to_vec(payload).unwrap_or_default();
+    let event = payload["event"].as_str().unwrap_or_default();
+    let client = reqwest::Client::builder().timeout(TIMEOUT).build().map_err(|e| e.to_string())?;
+
+    let mut outcome = Err(String::new());
+    for attempt in 1..=MAX_ATTEMPTS {
+        if attempt > 1 {
+            tokio::time::sleep(Duration::from_secs(1 << (attempt - 1))).await;
+        }
+        // Fresh timestamp and nonce per attempt, a retry is not a replay
+        let timestamp = Utc::now().timestamp();
+        let nonce = Uuid::new_v4().simple().to_string();
+        let signature = inbound_auth::sign(&webhook.secret, timestamp, &nonce, &body);
+        let response = client
+            .post(&webhook.url)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .header(HEADER_EVENT, event)
+            .header(inbound_auth::HEADER_TIMESTAMP, timestamp.to_string())
+            .header(inbound_auth::HEADER_NONCE, &nonce)
+            .header(inbound_auth::HEADER_SIGNATURE, format!("sha256={}", signature))
+            .body(body.clone())
+            .send()
+            .await;
+
+        outcome = match response {
+            Ok(response) => Ok(response.status().as_u16()),
+            Err(e) => Err(e.to_string()),
+        };
+        let transient = match &outcome {
+            Ok(status) => *status >= 500,
+            Err(_) => true,
+        };
+        if !transient {
+            break;
+        }
+    }
+
+    let status = match &outcome {
+        Ok(status) => status.to_string(),
+        Err(e) => {
+            tracing::warn!("Webhook {} ({}) delivery of {} failed: {}", webhook.name, webhook.id, event, e);
+            format!("error: {}", e)
+        }
+    };
+    let _ = sqlx::query("UPDATE outgoing_webhooks SET last_delivery_at = ?, last_delivery_status = ? WHERE id = ?")
+        .bind(Utc::now().to_rfc3339())
+        .bind(&status)
+        .bind(&webhook.id)
+        .execute(pool)
+        .await;
+    outcome
+}
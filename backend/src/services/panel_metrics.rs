@@ -0,0 +1,112 @@
+//! Prometheus exporter: counters of the panel itself (API requests, console WebSockets) and
+//! gauges of each server from the process manager, rendered in the text exposition format.
+
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::sync::Mutex;
+
+use axum::{extract::Request, middleware::Next, response::Response};
+
+use crate::db::DbPool;
+use crate::services::ProcessManager;
+
+lazy_static::lazy_static! {
+    /// (method, status) -> requests answered
+    static ref HTTP_REQUESTS: Mutex<BTreeMap<(String, u16), u64>> = Mutex::new(BTreeMap::new());
+}
+
+static WS_CONNECTIONS: AtomicI64 = AtomicI64::new(0);
+
+/// Middleware counting the API requests by method and status
+pub async fn track_requests(req: Request, next: Next) -> Response {
+    let method = req.method().to_string();
+    let response = next.run(req).await;
+    if let Ok(mut requests) = HTTP_REQUESTS.lock() {
+        *requests.entry((method, response.status().as_u16())).or_default() += 1;
+    }
+    response
+}
+
+/// An open console WebSocket, counted until dropped
+pub struct WsConnection;
+
+impl WsConnection {
+    pub fn open() -> Self {
+        WS_CONNECTIONS.fetch_add(1, Ordering::Relaxed);
+        Self
+    }
+}
+
+impl Drop for WsConnection {
+    fn drop(&mut self) {
+        WS_CONNECTIONS.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+struct ServerSample {
+    labels: String,
+    up: bool,
+    cpu_percent: f32,
+    memory_bytes: u64,
+    disk_bytes: u64,
+    players: usize,
+}
+
+/// Label values are quoted, backslashes, quotes and newlines escaped
+fn label(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
+/// Metric name, help text and the value it takes from a server sample
+type Gauge = (&'static str, &'static str, fn(&ServerSample) -> String);
+
+fn header(out: &mut String, name: &str, kind: &str, help: &str) {
+    let _ = writeln!(out, "# HELP {} {}", name, help);
+    let _ = writeln!(out, "# TYPE {} {}", name, kind);
+}
+
+/// Every metric, in the text format scraped by Prometheus
+pub async fn render(pool: &DbPool, pm: &ProcessManager) -> Result<String, sqlx::Error> {
+    let mut out = String::new();
+
+    header(&mut out, "draveur_http_requests_total", "counter", "API requests answered, by method and status.");
+    if let Ok(requests) = HTTP_REQUESTS.lock() {
+        for ((method, status), count) in requests.iter() {
+            let _ = writeln!(out, "draveur_http_requests_total{{method=\"{}\",status=\"{}\"}} {}", method, status, count);
+        }
+    }
+    header(&mut out, "draveur_websocket_connections", "gauge", "Open console WebSockets.");
+    let _ = writeln!(out, "draveur_websocket_connections {}", WS_CONNECTIONS.load(Ordering::Relaxed).max(0));
+
+    let servers: Vec<(String, String, String)> = sqlx::query_as("SELECT id, name, game_type FROM servers ORDER BY name")
+        .fetch_all(pool)
+        .await?;
+    let mut samples = Vec::with_capacity(servers.len());
+    for (id, name, game_type) in &servers {
+        let (cpu_percent, _, memory_bytes, disk_bytes) = pm.get_metrics_data(id).await;
+        samples.push(ServerSample {
+            labels: format!("server_id=\"{}\",server=\"{}\",game_type=\"{}\"", label(id), label(name), label(game_type)),
+            up: pm.is_running(id).await,
+            cpu_percent,
+            memory_bytes,
+            disk_bytes,
+            players: pm.get_online_players(id).await.map_or(0, |p| p.len()),
+        });
+    }
+
+    let gauges: [Gauge; 5] = [
+        ("draveur_server_up", "1 when the server process is running.", |s| u8::from(s.up).to_string()),
+        ("draveur_server_cpu_percent", "CPU usage of the server process, 100 per core.", |s| s.cpu_percent.to_string()),
+        ("draveur_server_memory_bytes", "Resident memory of the server process.", |s| s.memory_bytes.to_string()),
+        ("draveur_server_disk_bytes", "Size of the server directory, as of the last scan.", |s| s.disk_bytes.to_string()),
+        ("draveur_server_players", "Players online.", |s| s.players.to_string()),
+    ];
+    for (name, help, value) in gauges {
+        header(&mut out, name, "gauge", help);
+        for sample in &samples {
+            let _ = writeln!(out, "{}{{{}}} {}", name, sample.labels, value(sample));
+        }
+    }
+    Ok(out)
+}
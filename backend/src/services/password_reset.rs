@@ -0,0 +1,100 @@
+//! One-time password reset links, handed over by an admin or emailed to users who ask for one
+//! when SMTP is configured. Only the SHA-256 of a token is stored; a token expires after
+//! `TOKEN_TTL_HOURS` and is gone once used.
+
+use chrono::{Duration, Utc};
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+
+use crate::db::DbPool;
+use crate::services::{mailer, sessions};
+
+pub const TOKEN_TTL_HOURS: i64 = 24;
+
+fn hash_token(token: &str) -> String {
+    hex::encode(Sha256::digest(token.trim().as_bytes()))
+}
+
+/// New reset token for a user, replacing any pending one. Returns the token and its expiry.
+pub async fn issue(pool: &DbPool, user_id: &str, created_by: &str) -> Result<(String, String), sqlx::Error> {
+    let token = format!("{}{}", Uuid::new_v4().simple(), Uuid::new_v4().simple());
+    let now = Utc::now();
+    let expires_at = (now + Duration::hours(TOKEN_TTL_HOURS)).to_rfc3339();
+
+    let mut tx = pool.begin().await?;
+    sqlx::query("DELETE FROM password_resets WHERE user_id = ?")
+        .bind(user_id)
+        .execute(&mut *tx)
+        .await?;
+    sqlx::query("INSERT INTO password_resets (token_hash, user_id, created_by, created_at, expires_at) VALUES (?, ?, ?, ?, ?)")
+        .bind(hash_token(&token))
+        .bind(user_id)
+        .bind(created_by)
+        .bind(now.to_rfc3339())
+        .bind(&expires_at)
+        .execute(&mut *tx)
+        .await?;
+    tx.commit().await?;
+
+    Ok((token, expires_at))
+}
+
+/// Email a reset link to the active user with this username or email address, if they have an
+/// address. Returns the user's id and username when a link went out.
+pub async fn email_link(pool: &DbPool, config: &mailer::SmtpConfig, login: &str) -> anyhow::Result<Option<(String, String)>> {
+    let user: Option<(String, String, String)> = sqlx::query_as(
+        "SELECT id, username, email FROM users
+         WHERE (username = ? OR email = ?) AND email IS NOT NULL AND email != '' AND COALESCE(is_active, 1) = 1"
+    )
+    .bind(login)
+    .bind(login)
+    .fetch_optional(pool)
+    .await?;
+    let Some((user_id, username, email)) = user else {
+        return Ok(None);
+    };
+
+    let (token, _) = issue(pool, &user_id, &user_id).await?;
+    let body = format!(
+        "Hello {},\n\nSomeone asked to reset the password of your panel account. Choose a new one here:\n\n{}/reset-password?token={}\n\nThe link works once and expires in {} hours. If you didn't ask for it, ignore this email.\n",
+        username, config.panel_url, token, TOKEN_TTL_HOURS
+    );
+    mailer::send(config, &email, "Password reset", body).await?;
+    Ok(Some((user_id, username)))
+}
+
+/// Set the password of the user a valid token belongs to, burn the token and log the user out
+/// everywhere. `None` when the token is unknown, used or expired.
+pub async fn redeem(pool: &DbPool, token: &str, password_hash: &str) -> Result<Option<(String, String)>, sqlx::Error> {
+    // Writing first takes the write lock up front: a read upgraded to a write fails with
+    // "database is locked" when another connection writes in between
+    let mut tx = pool.begin().await?;
+    let user_id: Option<String> = sqlx::query_scalar(
+        "DELETE FROM password_resets WHERE token_hash = ? AND expires_at > ? RETURNING user_id"
+    )
+    .bind(hash_token(token))
+    .bind(Utc::now().to_rfc3339())
+    .fetch_optional(&mut *tx)
+    .await?;
+    let Some(user_id) = user_id else {
+        return Ok(None);
+    };
+
+    let username: Option<String> = sqlx::query_scalar("UPDATE users SET password_hash = ?, updated_at = ? WHERE id = ? RETURNING username")
+        .bind(password_hash)
+        .bind(Utc::now().to_rfc3339())
+        .bind(&user_id)
+        .fetch_optional(&mut *tx)
+        .await?;
+    let Some(username) = username else {
+        return Ok(None);
+    };
+    sqlx::query("DELETE FROM password_resets WHERE user_id = ?")
+        .bind(&user_id)
+        .execute(&mut *tx)
+        .await?;
+    tx.commit().await?;
+    sessions::revoke_all(pool, &user_id).await?;
+
+    Ok(Some((user_id, username)))
+}
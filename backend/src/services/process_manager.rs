@@ -1,14 +1,22 @@
 use std::collections::{HashMap, HashSet};
-use std::io::{BufRead, BufReader, Write};
-use std::process::{Child, Command, Stdio};
+use std::process::Stdio;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
 use std::sync::Arc;
-use tokio::sync::{broadcast, RwLock};
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::process::{Child, ChildStdin, Command};
+use tokio::sync::{broadcast, mpsc, watch, Mutex, Notify, RwLock};
 
 use tracing::info;
 
 use regex::Regex;
 
 use crate::error::AppError;
+use crate::services::console_log::{ConsoleLog, ConsoleLogOptions};
+use crate::services::events::{EventBus, PanelEvent};
+use crate::services::{auth_detection, broadcast_messages, discord_service, docker, health_probe, history, simulator};
+use crate::services::health_probe::HealthProbe;
+use crate::services::resource_alerts::{AlertState, AlertThresholds};
 use walkdir::WalkDir;
 
 
@@ -16,217 +24,593 @@ use walkdir::WalkDir;
 /// Manages game server processes
 use crate::db::DbPool;
 
+pub const DEFAULT_STOP_COMMAND: &str = "/shutdown";
+
+/// Servers allowed to boot at the same time unless the `max_concurrent_starts` setting overrides it
+pub const DEFAULT_MAX_CONCURRENT_STARTS: usize = 2;
+
+/// Seconds between two metrics samples unless the `metrics_interval_secs` setting overrides it
+pub const DEFAULT_METRICS_INTERVAL_SECS: u64 = 2;
+
+/// How often server directories are walked to measure their disk usage
+const DISK_WALK_INTERVAL: Duration = Duration::from_secs(30);
+
+/// `exec_command` returns once the console stays quiet this long after answering
+const EXEC_IDLE_GAP: Duration = Duration::from_millis(300);
+
+/// A launch stops counting against the limit once the server is ready, or after this delay
+const START_SLOT_TIMEOUT_SECS: u64 = 180;
+
+/// How often the reaper looks for map entries whose process already exited
+const REAP_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Delay before the watchdog relaunches a crashed server
+pub const WATCHDOG_DELAY: Duration = Duration::from_secs(5);
+
+/// The watchdog gives up after this many relaunches of a server within `WATCHDOG_WINDOW`
+pub const WATCHDOG_MAX_RESTARTS: usize = 3;
+pub const WATCHDOG_WINDOW: Duration = Duration::from_secs(10 * 60);
+
 #[derive(Clone)]
 pub struct ProcessManager {
     processes: Arc<RwLock<HashMap<String, ServerProcess>>>,
     pool: Option<DbPool>,
+    start_queue: Arc<StartQueue>,
+    metrics_interval: Arc<AtomicU64>,
+    /// Launch native servers in `systemd-run --scope` units (`systemd_scopes` setting)
+    systemd_scopes: Arc<AtomicBool>,
+    /// Crashed runs to relaunch, consumed by the watchdog task
+    watchdog_tx: mpsc::UnboundedSender<(String, Arc<LaunchConfig>)>,
+    /// Recent watchdog relaunches per server
+    watchdog_restarts: Arc<std::sync::Mutex<HashMap<String, Vec<std::time::Instant>>>>,
+    /// `(server id, line)` of panel tasks (backups, restores) shown in the console even while the
+    /// server is stopped
+    task_tx: broadcast::Sender<(String, String)>,
+    /// Status, metrics and player counts of every server, for the dashboard stream
+    events: EventBus,
+}
+
+/// Limits how many JVMs are booting at once; other starts wait in `queued`
+struct StartQueue {
+    limit: AtomicUsize,
+    booting: std::sync::Mutex<usize>,
+    released: Notify,
+    queued: std::sync::RwLock<HashSet<String>>,
+}
+
+/// Everything needed to launch a server process
+#[derive(Debug, Clone, Default)]
+pub struct LaunchConfig {
+    /// Selects the auth prompt patterns applied to the console
+    pub game_type: String,
+    pub executable_path: String,
+    pub working_dir: String,
+    pub java_path: Option<String>,
+    pub max_memory: Option<String>,
+    pub extra_args: Option<String>,
+    pub config: Option<serde_json::Value>,
+    pub env_vars: HashMap<String, String>,
+    /// "java" (default) or "command" to run `command_template` instead
+    pub launch_mode: String,
+    pub command_template: Option<String>,
+    /// "native" (default) or "docker" to run the command inside a `docker_image` container
+    pub runtime: String,
+    pub docker_image: Option<String>,
+    /// Console command sent on stop (`/shutdown` when unset, empty to skip straight to SIGTERM)
+    pub stop_command: Option<String>,
+    /// How `logs/console.log` is written, `None` to keep the console off the disk
+    pub console_log: Option<ConsoleLogOptions>,
+    /// Checks the game port answers while running, `None` to rely on the process alone
+    pub health_probe: Option<HealthProbe>,
+    pub alert_thresholds: AlertThresholds,
 }
 
 pub struct ServerProcess {
-    child: Option<Child>,
+    /// None while the entry only tracks an installation
+    game: Option<GameProcess>,
     install_task: Option<tokio::task::AbortHandle>,
     log_tx: broadcast::Sender<String>,
-    players: Arc<std::sync::RwLock<HashSet<String>>>,
-    pub last_metrics: Arc<std::sync::RwLock<Option<String>>>,
-    pub last_cpu: Arc<std::sync::RwLock<f32>>,
-    pub last_cpu_normalized: Arc<std::sync::RwLock<f32>>,
-    pub last_memory: Arc<std::sync::RwLock<u64>>,
-    pub last_disk: Arc<std::sync::RwLock<u64>>,
+    players: Arc<RwLock<HashSet<String>>>,
+    pub last_metrics: Arc<RwLock<Option<String>>>,
+    pub last_cpu: Arc<RwLock<f32>>,
+    pub last_cpu_normalized: Arc<RwLock<f32>>,
+    pub last_memory: Arc<RwLock<u64>>,
+    pub last_disk: Arc<RwLock<u64>>,
+    pub last_network: Arc<RwLock<NetworkStats>>,
     pub working_dir: String,
     pub started_at: Option<chrono::DateTime<chrono::Utc>>,
-    pub auth_required: Arc<std::sync::RwLock<bool>>,
+    pub auth_required: Arc<RwLock<bool>>,
+    stop_command: String,
+    alert_thresholds: AlertThresholds,
+    /// Game port, used to count connections
+    port: u16,
+    /// Task owning the child, finished once the exit is handled
+    exit_watcher: Option<tokio::task::AbortHandle>,
+}
+
+/// Handles on a spawned server. The `Child` itself is owned by the task waiting for its exit,
+/// so nothing has to poll `try_wait` and no lock is held while the process shuts down.
+#[derive(Clone)]
+struct GameProcess {
+    pid: Option<u32>,
+    stdin: Option<Arc<Mutex<ChildStdin>>>,
+    kill_tx: mpsc::Sender<()>,
+    exited: watch::Receiver<bool>,
+    /// Set by stop/kill so an exit afterwards isn't reported as a crash
+    stop_requested: Arc<AtomicBool>,
+    /// Filled by the watcher before it publishes the exit (`None` if the status couldn't be read)
+    exit_status: Arc<std::sync::OnceLock<Option<std::process::ExitStatus>>>,
+    /// Set by whoever handles the exit first, the watcher or the reaper
+    exit_handled: Arc<AtomicBool>,
+    /// Relaunched as-is by the watchdog
+    launch: Arc<LaunchConfig>,
+    /// Frozen with `suspend` until `resume`
+    suspended: Arc<AtomicBool>,
+    /// Alive but failing its health probe
+    unresponsive: Arc<AtomicBool>,
+}
+
+impl ServerProcess {
+    fn new(game: Option<GameProcess>, log_tx: broadcast::Sender<String>, working_dir: &str) -> Self {
+        Self {
+            game,
+            install_task: None,
+            log_tx,
+            players: Arc::new(RwLock::new(HashSet::new())),
+            last_metrics: Arc::new(RwLock::new(None)),
+            last_cpu: Arc::new(RwLock::new(0.0)),
+            last_cpu_normalized: Arc::new(RwLock::new(0.0)),
+            last_memory: Arc::new(RwLock::new(0)),
+            last_disk: Arc::new(RwLock::new(0)),
+            last_network: Arc::new(RwLock::new(NetworkStats::default())),
+            working_dir: working_dir.to_string(),
+            started_at: Some(chrono::Utc::now()),
+            auth_required: Arc::new(RwLock::new(false)),
+            stop_command: String::new(),
+            alert_thresholds: AlertThresholds::default(),
+            port: 0,
+            exit_watcher: None,
+        }
+    }
+
+    /// True once the game process is spawned (false while installing)
+    pub fn has_game_process(&self) -> bool {
+        self.game.is_some()
+    }
+
+    pub async fn player_count(&self) -> usize {
+        self.players.read().await.len()
+    }
+
+    /// Installing entries count as running, game processes until they exit
+    fn is_alive(&self) -> bool {
+        self.game.as_ref().is_none_or(|g| !g.has_exited())
+    }
+}
+
+impl GameProcess {
+    fn has_exited(&self) -> bool {
+        *self.exited.borrow()
+    }
+
+    /// Wait up to `timeout` for the process to exit, returning whether it did
+    async fn wait_exit(&self, timeout: Duration) -> bool {
+        let mut exited = self.exited.clone();
+        // An error means the watcher is gone, which only happens after exit
+        let exited_in_time = tokio::time::timeout(timeout, exited.wait_for(|e| *e)).await.is_ok();
+        exited_in_time
+    }
+
+    async fn write_line(&self, line: &str) -> std::io::Result<()> {
+        let Some(stdin) = &self.stdin else {
+            return Ok(());
+        };
+        let mut stdin = stdin.lock().await;
+        stdin.write_all(format!("{}\n", line).as_bytes()).await?;
+        stdin.flush().await
+    }
+
+    async fn kill(&self) {
+        self.stop_requested.store(true, Ordering::SeqCst);
+        let _ = self.kill_tx.send(()).await;
+        self.wait_exit(Duration::from_secs(5)).await;
+    }
+
+    fn same_run(&self, other: &GameProcess) -> bool {
+        self.exited.same_channel(&other.exited)
+    }
+}
+
+impl StartQueue {
+    fn release(&self) {
+        if let Ok(mut booting) = self.booting.lock() {
+            *booting = booting.saturating_sub(1);
+        }
+        self.released.notify_waiters();
+    }
+}
+
+/// Resolve once the server reports ready ("Universe ready!") or its output ends, bounded by a timeout
+async fn wait_until_booted(mut rx: broadcast::Receiver<String>) {
+    let booted = async {
+        loop {
+            match rx.recv().await {
+                Ok(line) if line.starts_with("[STATUS]:") => break,
+                Ok(_) | Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                Err(broadcast::error::RecvError::Closed) => break,
+            }
+        }
+    };
+    let _ = tokio::time::timeout(std::time::Duration::from_secs(START_SLOT_TIMEOUT_SECS), booted).await;
 }
 
 impl ProcessManager {
-    pub fn new(pool: Option<DbPool>) -> Self {
+    pub fn new(pool: Option<DbPool>, events: EventBus) -> Self {
         let processes = Arc::new(RwLock::new(HashMap::<String, ServerProcess>::new()));
         
         // Spawn metrics loop
         let processes_clone = processes.clone();
+        let alerts_pool = pool.clone();
+        let metrics_interval = Arc::new(AtomicU64::new(DEFAULT_METRICS_INTERVAL_SECS));
+        let interval = metrics_interval.clone();
+        let metrics_events = events.clone();
         tokio::spawn(async move {
             let mut system = sysinfo::System::new_all();
-            let mut tick_count = 0;
+            let mut last_disk_walk: Option<std::time::Instant> = None;
+            // Keyed by server, reset when the PID changes (new run)
+            let mut alert_states: HashMap<String, (u32, AlertState)> = HashMap::new();
+            let mut network_counters: HashMap<String, (u32, (u64, u64), std::time::Instant)> = HashMap::new();
             loop {
                 // Refresh first so we have accurate CPU readings even on first iteration
                 system.refresh_processes(sysinfo::ProcessesToUpdate::All, true);
-                
-                {
+
+                // Snapshot the handles so the map isn't locked while walking server directories
+                let targets: Vec<_> = {
                     let procs = processes_clone.read().await;
-                    for (_id, server_proc) in procs.iter() {
-                        if let Some(child) = &server_proc.child {
-                            let pid = sysinfo::Pid::from_u32(child.id());
-                            if let Some(process) = system.process(pid) {
-                                let cpu = process.cpu_usage();
-                                let cores = system.cpus().len() as f32;
-                                let cpu_normalized = if cores > 0.0 { cpu / cores } else { 0.0 };
-                                let memory = process.memory(); // in bytes
-                                
-                                let mut metrics_json = serde_json::json!({
-                                    "cpu": cpu,
-                                    "cpu_normalized": cpu_normalized,
-                                    "memory": memory
-                                });
+                    procs.iter()
+                        .filter_map(|(id, p)| {
+                            let pid = p.game.as_ref()?.pid?;
+                            Some((id.clone(), pid, p.working_dir.clone(), p.log_tx.clone(), p.last_metrics.clone(),
+                                p.last_cpu.clone(), p.last_cpu_normalized.clone(), p.last_memory.clone(), p.last_disk.clone(),
+                                p.alert_thresholds.clone(), p.port, p.last_network.clone()))
+                        })
+                        .collect()
+                };
+                alert_states.retain(|id, _| targets.iter().any(|t| &t.0 == id));
+                network_counters.retain(|id, _| targets.iter().any(|t| &t.0 == id));
+                let disk_due = last_disk_walk.is_none_or(|at| at.elapsed() >= DISK_WALK_INTERVAL);
+
+                for (server_id, pid, working_dir, log_tx, last_metrics, last_cpu, last_cpu_normalized, last_memory, last_disk, thresholds, port, last_network) in targets {
+                    let Some(process) = system.process(sysinfo::Pid::from_u32(pid)) else {
+                        continue;
+                    };
+                    let cpu = process.cpu_usage();
+                    let cores = system.cpus().len() as f32;
+                    let cpu_normalized = if cores > 0.0 { cpu / cores } else { 0.0 };
+                    let memory = process.memory(); // in bytes
+
+                    // Byte rates from the counter deltas since the previous sample of this run
+                    let mut network = NetworkStats {
+                        connections: net_stats::connections_on_port(pid, port),
+                        ..Default::default()
+                    };
+                    if let Some(totals) = net_stats::namespace_io(pid) {
+                        let now = std::time::Instant::now();
+                        if let Some((prev_pid, prev, at)) = network_counters.get(&server_id) {
+                            let secs = at.elapsed().as_secs_f64();
+                            if *prev_pid == pid && secs > 0.0 {
+                                network.rx_bytes_per_sec = (totals.0.saturating_sub(prev.0) as f64 / secs) as u64;
+                                network.tx_bytes_per_sec = (totals.1.saturating_sub(prev.1) as f64 / secs) as u64;
+                            }
+                        }
+                        network_counters.insert(server_id.clone(), (pid, totals, now));
+                    }
+
+                    let mut metrics_json = serde_json::json!({
+                        "cpu": cpu,
+                        "cpu_normalized": cpu_normalized,
+                        "memory": memory,
+                        "net_rx_bytes_per_sec": network.rx_bytes_per_sec,
+                        "net_tx_bytes_per_sec": network.tx_bytes_per_sec,
+                        "connections": network.connections
+                    });
+
+                    // Nobody has the console open: skip the broadcast, and the disk walk unless an alert needs it
+                    let watched = log_tx.receiver_count() > 0;
+
+                    // Calculate disk size every ~30 seconds
+                    let mut disk_sample = None;
+                    if disk_due && (watched || thresholds.disk_bytes.is_some()) {
+                        let size: u64 = tokio::task::spawn_blocking(move || {
+                            WalkDir::new(working_dir)
+                                .into_iter()
+                                .filter_map(|entry| entry.ok())
+                                .filter_map(|entry| entry.metadata().ok())
+                                .filter(|metadata| metadata.is_file())
+                                .map(|metadata| metadata.len())
+                                .sum()
+                        })
+                        .await
+                        .unwrap_or(0);
+
+                        if let Some(obj) = metrics_json.as_object_mut() {
+                            obj.insert("disk_bytes".to_string(), serde_json::Value::Number(serde_json::Number::from(size)));
+                        }
+                        *last_disk.write().await = size;
+                        disk_sample = Some(size);
+                    }
 
-                                // Calculate disk size every ~30 seconds (15 ticks) OR at tick 0
-                                if tick_count % 15 == 0 {
-                                    let server_path = &server_proc.working_dir;
-                                    let size: u64 = WalkDir::new(server_path)
-                                        .into_iter()
-                                        .filter_map(|entry| entry.ok())
-                                        .filter_map(|entry| entry.metadata().ok())
-                                        .filter(|metadata| metadata.is_file())
-                                        .map(|metadata| metadata.len())
-                                        .sum();
-                                    
-                                    if let Some(obj) = metrics_json.as_object_mut() {
-                                        obj.insert("disk_bytes".to_string(), serde_json::Value::Number(serde_json::Number::from(size)));
-                                    }
-                                    if let Ok(mut disk_cache) = server_proc.last_disk.write() {
-                                        *disk_cache = size;
-                                    }
-                                }
-
-                                let metrics_msg = format!("[METRICS]: {}", metrics_json);
-                                let _ = server_proc.log_tx.send(metrics_msg.clone());
-                                if let Ok(mut cache) = server_proc.last_metrics.write() {
-                                    *cache = Some(metrics_msg);
-                                }
-                                if let Ok(mut cpu_cache) = server_proc.last_cpu.write() {
-                                    *cpu_cache = cpu;
-                                }
-                                if let Ok(mut cpu_norm_cache) = server_proc.last_cpu_normalized.write() {
-                                    *cpu_norm_cache = cpu_normalized;
-                                }
-                                if let Ok(mut mem_cache) = server_proc.last_memory.write() {
-                                    *mem_cache = memory;
-                                }
+                    if !thresholds.is_empty() {
+                        let entry = alert_states.entry(server_id.clone()).or_default();
+                        if entry.0 != pid {
+                            *entry = (pid, AlertState::default());
+                        }
+                        for alert in entry.1.observe(&thresholds, cpu_normalized, memory, disk_sample) {
+                            tracing::warn!("Resource alert on server {}: {}", server_id, alert);
+                            let _ = log_tx.send(format!("[WARN] ⚠️ Seuil de ressources dépassé : {}", alert));
+                            if let Some(pool) = alerts_pool.clone() {
+                                let server_id = server_id.clone();
+                                tokio::spawn(async move {
+                                    discord_service::notify_server(
+                                        &pool,
+                                        &server_id,
+                                        "⚠️ Alerte Ressources",
+                                        &alert,
+                                        discord_service::COLOR_WARNING,
+                                    ).await;
+                                });
                             }
                         }
                     }
+
+                    let metrics_msg = format!("[METRICS]: {}", metrics_json);
+                    if watched {
+                        let _ = log_tx.send(metrics_msg.clone());
+                    }
+                    *last_metrics.write().await = Some(metrics_msg);
+                    if metrics_events.has_subscribers() {
+                        let disk_bytes = *last_disk.read().await;
+                        if let Some(obj) = metrics_json.as_object_mut() {
+                            obj.entry("disk_bytes").or_insert_with(|| disk_bytes.into());
+                        }
+                        metrics_events.publish(PanelEvent::Metrics { server_id: server_id.clone(), metrics: metrics_json });
+                    }
+                    *last_cpu.write().await = cpu;
+                    *last_cpu_normalized.write().await = cpu_normalized;
+                    *last_memory.write().await = memory;
+                    *last_network.write().await = network;
                 }
 
-                tokio::time::sleep(tokio::time::Duration::from_secs(2)).await;
-                tick_count += 1;
+                if disk_due {
+                    last_disk_walk = Some(std::time::Instant::now());
+                }
+                let secs = interval.load(Ordering::SeqCst);
+                tokio::time::sleep(Duration::from_secs(secs)).await;
             }
         });
 
-        Self {
+        let start_queue = Arc::new(StartQueue {
+            limit: AtomicUsize::new(DEFAULT_MAX_CONCURRENT_STARTS),
+            booting: std::sync::Mutex::new(0),
+            released: Notify::new(),
+            queued: std::sync::RwLock::new(HashSet::new()),
+        });
+
+        let systemd_scopes = Arc::new(AtomicBool::new(false));
+
+        // Apply the saved concurrency limit, metrics interval and systemd scopes
+        if let Some(pool) = pool.clone() {
+            let start_queue = start_queue.clone();
+            let metrics_interval = metrics_interval.clone();
+            let systemd_scopes = systemd_scopes.clone();
+            tokio::spawn(async move {
+                let saved: Option<String> = sqlx::query_scalar("SELECT value FROM settings WHERE key = 'max_concurrent_starts'")
+                    .fetch_optional(&pool)
+                    .await
+                    .ok()
+                    .flatten();
+                if let Some(limit) = saved.and_then(|v| v.parse::<usize>().ok()) {
+                    start_queue.limit.store(limit.max(1), Ordering::SeqCst);
+                    start_queue.released.notify_waiters();
+                }
+
+                let saved: Option<String> = sqlx::query_scalar("SELECT value FROM settings WHERE key = 'metrics_interval_secs'")
+                    .fetch_optional(&pool)
+                    .await
+                    .ok()
+                    .flatten();
+                if let Some(secs) = saved.and_then(|v| v.parse::<u64>().ok()) {
+                    metrics_interval.store(secs.max(1), Ordering::SeqCst);
+                }
+
+                let saved: Option<String> = sqlx::query_scalar("SELECT value FROM settings WHERE key = 'systemd_scopes'")
+                    .fetch_optional(&pool)
+                    .await
+                    .ok()
+                    .flatten();
+                systemd_scopes.store(saved.is_some_and(|v| v == "true"), Ordering::SeqCst);
+            });
+        }
+
+        let (watchdog_tx, mut watchdog_rx) = mpsc::unbounded_channel();
+        let manager = Self {
             processes,
             pool,
+            start_queue,
+            metrics_interval,
+            systemd_scopes,
+            watchdog_tx,
+            watchdog_restarts: Arc::new(std::sync::Mutex::new(HashMap::new())),
+            task_tx: broadcast::channel(256).0,
+            events,
+        };
+
+        // Reaper: safety net for runs whose exit wasn't cleaned up by their watcher
+        let reaper = manager.clone();
+        tokio::spawn(async move {
+            loop {
+                tokio::time::sleep(REAP_INTERVAL).await;
+                reaper.reap().await;
+            }
+        });
+
+        // Watchdog: relaunch crashed servers that have it enabled
+        let watchdog = manager.clone();
+        tokio::spawn(async move {
+            while let Some((server_id, launch)) = watchdog_rx.recv().await {
+                let watchdog = watchdog.clone();
+                tokio::spawn(async move {
+                    watchdog.watchdog_restart(&server_id, &launch).await;
+                });
+            }
+        });
+
+        manager
+    }
+
+    /// Change the delay between metrics samples (applies after the current one)
+    pub fn set_metrics_interval(&self, secs: u64) {
+        self.metrics_interval.store(secs.max(1), Ordering::SeqCst);
+    }
+
+    /// Change how many servers may boot at once (takes effect for queued starts immediately)
+    pub fn set_max_concurrent_starts(&self, limit: usize) {
+        self.start_queue.limit.store(limit.max(1), Ordering::SeqCst);
+        self.start_queue.released.notify_waiters();
+    }
+
+    /// Run the next native launches in systemd scopes or not
+    pub fn set_systemd_scopes(&self, enabled: bool) {
+        self.systemd_scopes.store(enabled, Ordering::SeqCst);
+    }
+
+    pub fn is_queued(&self, server_id: &str) -> bool {
+        self.start_queue.queued.read().map(|q| q.contains(server_id)).unwrap_or(false)
+    }
+
+    /// Put a server in the start queue. Returns true when it will have to wait for a free slot.
+    pub async fn enqueue_start(&self, server_id: &str) -> Result<bool, AppError> {
+        if self.processes.read().await.contains_key(server_id) {
+            return Err(AppError::BadRequest("servers.already_running".into()));
+        }
+
+        let waiting = {
+            let mut queued = self.start_queue.queued.write().unwrap();
+            if !queued.insert(server_id.to_string()) {
+                return Err(AppError::BadRequest("servers.already_queued".into()));
+            }
+            queued.len()
+        };
+
+        let booting = *self.start_queue.booting.lock().unwrap();
+        Ok(waiting + booting > self.start_queue.limit.load(Ordering::SeqCst))
+    }
+
+    /// Drop a server from the start queue; its pending `start_queued` returns an error
+    pub fn cancel_queued_start(&self, server_id: &str) -> bool {
+        let removed = self.start_queue.queued.write().unwrap().remove(server_id);
+        if removed {
+            self.start_queue.released.notify_waiters();
         }
+        removed
+    }
+
+    /// Start a server through the launch queue, waiting for a free slot if needed
+    pub async fn start(&self, server_id: &str, launch: &LaunchConfig) -> Result<(), AppError> {
+        self.enqueue_start(server_id).await?;
+        self.start_queued(server_id, launch).await
     }
 
-    pub fn is_running(&self, server_id: &str) -> bool {
-        // We need to check if the process is actually alive, not just in the map
-        if let Ok(mut processes) = self.processes.try_write() {
-            if let Some(proc) = processes.get_mut(server_id) {
-                // If child is None, it means it's a virtual process (installing/updating)
-                // So it is technically "running" if install_task is active
-                if proc.child.is_none() {
-                    return true;
+    /// Wait for a launch slot for a server previously passed to `enqueue_start`, then spawn it
+    pub async fn start_queued(&self, server_id: &str, launch: &LaunchConfig) -> Result<(), AppError> {
+        let queue = &self.start_queue;
+        loop {
+            // Register before checking so a release between the check and the await isn't missed
+            let released = queue.released.notified();
+            {
+                if !self.is_queued(server_id) {
+                    return Err(AppError::BadRequest("servers.start_cancelled".into()));
                 }
-                
-                if let Some(child) = &mut proc.child {
-                    // Check if process is still running
-                    match child.try_wait() {
-                        Ok(None) => {
-                            // Process is still running
-                            return true;
-                        }
-                        Ok(Some(_status)) => {
-                            // Process has exited, remove from map
-                            info!("Server {} process has exited, cleaning up", server_id);
-                            processes.remove(server_id);
-                            return false;
-                        }
-                        Err(_) => {
-                            // Error checking status, assume not running
-                            return false;
-                        }
-                    }
+                let mut booting = queue.booting.lock().unwrap();
+                if *booting < queue.limit.load(Ordering::SeqCst) {
+                    *booting += 1;
+                    queue.queued.write().unwrap().remove(server_id);
+                    break;
                 }
             }
+            released.await;
         }
-        false
-    }
-    
-    pub fn is_installing(&self, server_id: &str) -> bool {
-        if let Ok(processes) = self.processes.try_read() {
-            if let Some(proc) = processes.get(server_id) {
-                return proc.child.is_none();
+
+        match self.launch(server_id, launch).await {
+            Ok(ready_rx) => {
+                let queue = self.start_queue.clone();
+                tokio::spawn(async move {
+                    wait_until_booted(ready_rx).await;
+                    queue.release();
+                });
+                Ok(())
             }
-        }
-        false
-    }
-
-    pub fn is_auth_required(&self, server_id: &str) -> bool {
-        if let Ok(processes) = self.processes.try_read() {
-            if let Some(proc) = processes.get(server_id) {
-                if let Ok(mut auth) = proc.auth_required.write() {
-                    if *auth {
-                        // Check if auth.enc exists in working dir
-                        // If it exists, it means we are authenticated
-                        let auth_file = std::path::Path::new(&proc.working_dir).join("auth.enc");
-                        if auth_file.exists() {
-                             // Update state to false since we found the file
-                            *auth = false;
-                            return false;
-                        }
-                    }
-                    return *auth;
-                }
+            Err(e) => {
+                queue.release();
+                Err(e)
             }
         }
-        false
     }
 
-    pub fn set_auth_required(&self, server_id: &str, required: bool) {
-        if let Ok(processes) = self.processes.try_read() {
-            if let Some(proc) = processes.get(server_id) {
-                if let Ok(mut auth) = proc.auth_required.write() {
-                    *auth = required;
-                }
+    pub async fn is_running(&self, server_id: &str) -> bool {
+        // Exited processes are dropped from the map by their watcher task (or the reaper)
+        self.processes.read().await.get(server_id).is_some_and(ServerProcess::is_alive)
+    }
+    
+    pub async fn is_installing(&self, server_id: &str) -> bool {
+        self.processes.read().await.get(server_id).is_some_and(|p| p.game.is_none())
+    }
+
+    pub async fn is_auth_required(&self, server_id: &str) -> bool {
+        let processes = self.processes.read().await;
+        let Some(proc) = processes.get(server_id) else {
+            return false;
+        };
+
+        let mut auth = proc.auth_required.write().await;
+        if *auth {
+            // Check if auth.enc exists in working dir
+            // If it exists, it means we are authenticated
+            let auth_file = std::path::Path::new(&proc.working_dir).join("auth.enc");
+            if tokio::fs::try_exists(&auth_file).await.unwrap_or(false) {
+                // Update state to false since we found the file
+                *auth = false;
             }
         }
+        *auth
     }
 
-    pub fn subscribe_logs(&self, server_id: &str) -> broadcast::Receiver<String> {
-        let (_tx, rx) = broadcast::channel(1000);
-        if let Ok(processes) = self.processes.try_read() {
-            if let Some(proc) = processes.get(server_id) {
-                return proc.log_tx.subscribe();
-            }
+    pub async fn set_auth_required(&self, server_id: &str, required: bool) {
+        let processes = self.processes.read().await;
+        if let Some(proc) = processes.get(server_id) {
+            *proc.auth_required.write().await = required;
+        }
+    }
+
+    pub async fn subscribe_logs(&self, server_id: &str) -> broadcast::Receiver<String> {
+        let processes = self.processes.read().await;
+        match processes.get(server_id) {
+            Some(proc) => proc.log_tx.subscribe(),
+            None => broadcast::channel(1000).1,
         }
-        rx
     }
 
     pub async fn register_installing(&self, server_id: &str, working_dir: &str, abort_handle: Option<tokio::task::AbortHandle>) -> Result<(), AppError> {
         let mut processes = self.processes.write().await;
-         if processes.contains_key(server_id) {
-             return Err(AppError::BadRequest("Server already active".into()));
-         }
-
-         let (log_tx, _) = broadcast::channel::<String>(1000);
-         let players = Arc::new(std::sync::RwLock::new(HashSet::new()));
-
-         processes.insert(
-             server_id.to_string(),
-             ServerProcess { 
-                 child: None,
-                 install_task: abort_handle,
-                 log_tx, 
-                 players,
-                 last_metrics: Arc::new(std::sync::RwLock::new(None)),
-                 last_cpu: Arc::new(std::sync::RwLock::new(0.0)),
-                 last_cpu_normalized: Arc::new(std::sync::RwLock::new(0.0)),
-                 last_memory: Arc::new(std::sync::RwLock::new(0)),
-                 last_disk: Arc::new(std::sync::RwLock::new(0)),
-                 working_dir: working_dir.to_string(),
-                 started_at: Some(chrono::Utc::now()),
-                 auth_required: Arc::new(std::sync::RwLock::new(false)),
-             },
-         );
-         Ok(())
+        if processes.contains_key(server_id) {
+            return Err(AppError::BadRequest("servers.already_active".into()));
+        }
+
+        let (log_tx, _) = broadcast::channel::<String>(1000);
+        let mut proc = ServerProcess::new(None, log_tx, working_dir);
+        proc.install_task = abort_handle;
+        processes.insert(server_id.to_string(), proc);
+        Ok(())
     }
 
     pub async fn broadcast_log(&self, server_id: &str, message: String) {
@@ -236,42 +620,199 @@ impl ProcessManager {
         }
     }
 
+    /// Sender for task lines, usable from blocking threads
+    pub fn task_sender(&self) -> broadcast::Sender<(String, String)> {
+        self.task_tx.clone()
+    }
+
+    pub fn subscribe_tasks(&self) -> broadcast::Receiver<(String, String)> {
+        self.task_tx.subscribe()
+    }
+
+    pub fn events(&self) -> &EventBus {
+        &self.events
+    }
+
     /// Remove a process from manager (used when installation finishes)
     pub async fn remove(&self, server_id: &str) {
         let mut processes = self.processes.write().await;
         processes.remove(server_id);
     }
 
-    pub async fn start(
-        &self,
-        server_id: &str,
-        executable_path: &str,
-        working_dir: &str,
-        java_path: Option<&str>,
-        _min_memory: Option<&str>,
-        max_memory: Option<&str>,
-        extra_args: Option<&str>,
-        config: Option<&serde_json::Value>,
-    ) -> Result<(), AppError> {
+    /// Spawn the server process. The returned receiver sees the status lines used to detect boot completion.
+    async fn launch(&self, server_id: &str, launch: &LaunchConfig) -> Result<broadcast::Receiver<String>, AppError> {
+        // Catch a stale address here rather than as a bind failure deep in the console
+        let (bind_ip, _) = Self::bind_target(launch);
+        interfaces::check_bind_address(&bind_ip).map_err(|key| AppError::BadRequest(key.into()))?;
+
+        let in_docker = launch.runtime == docker::RUNTIME_DOCKER;
+        if launch.launch_mode == "java" && !in_docker {
+            Self::check_java(launch).await?;
+        }
+
+        // The image provides the runtime; the same command runs inside the container
+        let mut std_cmd = if in_docker {
+            if self.processes.read().await.contains_key(server_id) {
+                return Err(AppError::BadRequest("servers.already_running".into()));
+            }
+            let image = launch.docker_image.as_deref()
+                .filter(|i| !i.trim().is_empty())
+                .ok_or_else(|| AppError::BadRequest("servers.docker_image_required".into()))?;
+            if launch.launch_mode == "simulator" {
+                return Err(AppError::BadRequest("servers.docker_unsupported_launch_mode".into()));
+            }
+            docker::remove_stale(server_id).await;
+
+            let inner = Self::command(&docker::containerized(launch))?;
+            let (_, port) = Self::bind_target(launch);
+            docker::run_command(server_id, launch, image, &inner, &bind_ip, port)
+        } else if self.systemd_scopes.load(Ordering::SeqCst) && platform::systemd_available() {
+            let memory_max = launch.alert_thresholds.max_memory_bytes;
+            platform::systemd_scope_command(&format!("draveur-{}", server_id), &Self::command(launch)?, memory_max)
+        } else {
+            Self::command(launch)?
+        };
+
         let mut processes = self.processes.write().await;
 
         if processes.contains_key(server_id) {
-            return Err(AppError::BadRequest("Server already running".into()));
+            return Err(AppError::BadRequest("servers.already_running".into()));
+        }
+
+        let working_dir = launch.working_dir.as_str();
+        std_cmd.current_dir(working_dir);
+        std_cmd.envs(&launch.env_vars);
+        platform::configure_command(&mut std_cmd);
+
+        let mut cmd = Command::from(std_cmd);
+        cmd.stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+
+        let mut child = cmd
+            .spawn()
+            .map_err(|e| AppError::Internal(format!("Failed to start server: {}", e)))?;
+        let spawned_at = std::time::Instant::now();
+
+        info!("Started server {} with PID {:?}", server_id, child.id());
+
+        // Create log file
+        let logs_dir = std::path::Path::new(working_dir).join("logs");
+        if tokio::fs::create_dir_all(&logs_dir).await.is_err() {
+            tracing::warn!("Could not create logs directory for server {}", server_id);
+        }
+        let log_file = match launch.console_log.clone() {
+            Some(options) => match ConsoleLog::create(logs_dir.join("console.log"), options).await {
+                Ok(log) => Some(Arc::new(Mutex::new(log))),
+                Err(e) => {
+                    tracing::warn!("Could not open console log for server {}: {}", server_id, e);
+                    None
+                }
+            },
+            None => None,
+        };
+
+        // Create log broadcaster
+        let (log_tx, _) = broadcast::channel::<String>(1000);
+        send_status(&log_tx, &self.events, server_id, "running");
+        let ready_rx = log_tx.subscribe();
+        let probe_rx = log_tx.subscribe();
+
+        let (kill_tx, kill_rx) = mpsc::channel(1);
+        let (exit_tx, exit_rx) = watch::channel(false);
+        let game = GameProcess {
+            pid: child.id(),
+            stdin: child.stdin.take().map(|s| Arc::new(Mutex::new(s))),
+            kill_tx,
+            exited: exit_rx,
+            stop_requested: Arc::new(AtomicBool::new(false)),
+            exit_status: Arc::new(std::sync::OnceLock::new()),
+            exit_handled: Arc::new(AtomicBool::new(false)),
+            launch: Arc::new(launch.clone()),
+            suspended: Arc::new(AtomicBool::new(false)),
+            unresponsive: Arc::new(AtomicBool::new(false)),
+        };
+
+        let mut proc = ServerProcess::new(Some(game.clone()), log_tx.clone(), working_dir);
+        proc.stop_command = launch.stop_command.clone().unwrap_or_else(|| DEFAULT_STOP_COMMAND.to_string());
+        proc.alert_thresholds = launch.alert_thresholds.clone();
+        proc.port = Self::bind_target(launch).1 as u16;
+
+        let output = OutputContext {
+            server_id: server_id.to_string(),
+            tx: log_tx.clone(),
+            log_file,
+            auth_required: proc.auth_required.clone(),
+            game_type: launch.game_type.clone(),
+        };
+        if let Some(stdout) = child.stdout.take() {
+            tokio::spawn(read_stdout(
+                stdout,
+                output.clone(),
+                proc.players.clone(),
+                self.pool.clone(),
+                spawned_at,
+                self.events.clone(),
+            ));
+        }
+        if let Some(stderr) = child.stderr.take() {
+            tokio::spawn(read_stderr(stderr, output));
         }
 
-        // Build command based on game type (Hytale uses Java)
-        let java = java_path.unwrap_or("java");
-        let max_mem = max_memory.unwrap_or("8G");
+        if let Some(probe) = launch.health_probe.clone() {
+            tokio::spawn(self.clone().run_health_probe(server_id.to_string(), game.clone(), probe, probe_rx, log_tx));
+        }
+
+        let tree = platform::ProcessTree::attach(&child);
+        let watcher = tokio::spawn(watch_exit(child, tree, kill_rx, exit_tx, self.clone(), server_id.to_string(), game));
+        proc.exit_watcher = Some(watcher.abort_handle());
+
+        processes.insert(server_id.to_string(), proc);
+
+        Ok(ready_rx)
+    }
+
+    /// Build command based on launch mode (Hytale uses Java)
+    fn command(launch: &LaunchConfig) -> Result<std::process::Command, AppError> {
+        Ok(match launch.launch_mode.as_str() {
+            "command" => Self::template_command(launch)?,
+            "simulator" => Self::simulator_command(launch)?,
+            _ => Self::java_command(launch),
+        })
+    }
+
+    /// Refuse to launch with a Java runtime that is missing or too old for the server
+    async fn check_java(launch: &LaunchConfig) -> Result<(), AppError> {
+        let java = launch.java_path.as_deref().filter(|p| !p.trim().is_empty()).unwrap_or("java");
+
+        let found = java::check_java_version(std::path::Path::new(java)).await.ok_or_else(|| {
+            AppError::BadRequest(format!(
+                "servers.java_not_found: Java not found at \"{}\": install Java {}+ or set the server's Java path",
+                java, MIN_JAVA_VERSION
+            ))
+        })?;
+
+        match found.major() {
+            Some(major) if major < MIN_JAVA_VERSION => Err(AppError::BadRequest(format!(
+                "servers.java_too_old: Java {} found at \"{}\" but Java {}+ is required: install a newer JDK or pick it as the server's Java path",
+                found.version, java, MIN_JAVA_VERSION
+            ))),
+            _ => Ok(()),
+        }
+    }
+
+    /// Default Hytale launch: `java [jvm flags] -jar <executable> --assets ... --bind ...`
+    fn java_command(launch: &LaunchConfig) -> std::process::Command {
+        let java = launch.java_path.as_deref().unwrap_or("java");
+        let max_mem = launch.max_memory.as_deref().unwrap_or("8G");
 
         // Config is generated by servers.rs (Hytale config.json)
         // Legacy server.properties/world-config.json generation removed.
 
 
-        let final_working_dir = std::path::PathBuf::from(working_dir);
         let assets_path = "Assets.zip".to_string();
 
-        let mut cmd = Command::new(java);
-        cmd.current_dir(&final_working_dir);
+        let mut cmd = std::process::Command::new(java);
 
         // Smart Memory Adjustment: User provided max_mem is now the HEAP SIZE (-Xmx)
         // We calculate Xms based on this.
@@ -290,410 +831,572 @@ impl ProcessManager {
         // Actually, JVM flags MUST be before -jar. Program args MUST be after.
         // Hytale's --bind and --assets are program args.
         
-        if let Some(args) = extra_args {
+        if let Some(args) = &launch.extra_args {
             for arg in args.split_whitespace() {
                 cmd.arg(arg);
             }
         }
 
         cmd.arg("-jar")
-            .arg(executable_path)
+            .arg(&launch.executable_path)
             .arg("--assets")
             .arg(assets_path);
 
-        if let Some(cfg) = config {
-            let port = cfg.get("port")
-                .or(cfg.get("Port"))
-                .and_then(|v| v.as_u64())
-                .unwrap_or(5520);
-             
-            let bind_ip = cfg.get("bind_address")
-                .and_then(|v| v.as_str())
-                .unwrap_or("0.0.0.0");
-
-            cmd.arg("--bind");
-            cmd.arg(format!("{}:{}", bind_ip, port));
+        let (bind_ip, port) = Self::bind_target(launch);
+        cmd.arg("--bind");
+        cmd.arg(format!("{}:{}", bind_ip, port));
+
+        cmd
+    }
+
+    /// Dummy servers: this binary in simulator mode, tuned by the `simulator` object of the server config
+    fn simulator_command(launch: &LaunchConfig) -> Result<std::process::Command, AppError> {
+        let exe = std::env::current_exe()
+            .map_err(|e| AppError::Internal(format!("Failed to locate the panel binary: {}", e)))?;
+        let (_, port) = Self::bind_target(launch);
+
+        let mut cmd = std::process::Command::new(exe);
+        cmd.arg(simulator::SIMULATE_FLAG).arg("--port").arg(port.to_string());
+
+        let options = launch.config.as_ref().and_then(|c| c.get("simulator"));
+        if let Some(secs) = options.and_then(|o| o.get("player_interval_secs")).and_then(|v| v.as_u64()) {
+            cmd.arg("--player-interval").arg(secs.to_string());
+        }
+        if let Some(secs) = options.and_then(|o| o.get("crash_after_secs")).and_then(|v| v.as_u64()) {
+            cmd.arg("--crash-after").arg(secs.to_string());
+        }
+        if options.and_then(|o| o.get("crash_on_start")).and_then(|v| v.as_bool()).unwrap_or(false) {
+            cmd.arg("--crash-on-start");
+        }
+        Ok(cmd)
+    }
+
+    /// Custom launch: the template is split on whitespace, then placeholders are
+    /// substituted per argument so values containing spaces stay a single argument.
+    fn template_command(launch: &LaunchConfig) -> Result<std::process::Command, AppError> {
+        let template = launch.command_template.as_deref().unwrap_or_default();
+        let (bind_ip, port) = Self::bind_target(launch);
+        let max_memory = launch.max_memory.as_deref().unwrap_or("8G");
+
+        let mut args = template.split_whitespace().map(|token| {
+            token
+                .replace("{executable}", &launch.executable_path)
+                .replace("{port}", &port.to_string())
+                .replace("{bind_address}", &bind_ip)
+                .replace("{max_memory}", max_memory)
+                .replace("{working_dir}", &launch.working_dir)
+        });
+
+        let program = args
+            .next()
+            .ok_or_else(|| AppError::BadRequest("servers.command_template_required".into()))?;
+
+        // Relative program paths (./bin/server) are resolved against the server directory
+        let program_path = std::path::Path::new(&program);
+        let mut cmd = if program_path.is_relative() && program_path.components().count() > 1 {
+            std::process::Command::new(std::path::Path::new(&launch.working_dir).join(program_path))
         } else {
+            std::process::Command::new(&program)
+        };
+        cmd.args(args);
+        Ok(cmd)
+    }
+
+    /// Bind address and port taken from the launch config
+    fn bind_target(launch: &LaunchConfig) -> (String, u64) {
+        let Some(cfg) = &launch.config else {
             // Default to standard port if no config
-            cmd.arg("--bind");
-            cmd.arg("0.0.0.0:5520");
+            return ("0.0.0.0".to_string(), 5520);
+        };
+
+        let port = cfg.get("port")
+            .or(cfg.get("Port"))
+            .and_then(|v| v.as_u64())
+            .unwrap_or(5520);
+
+        let bind_ip = cfg.get("bind_address")
+            .and_then(|v| v.as_str())
+            .unwrap_or("0.0.0.0");
+
+        (bind_ip.to_string(), port)
+    }
+
+    pub async fn stop(&self, server_id: &str) -> Result<(), AppError> {
+        if self.cancel_queued_start(server_id) {
+            info!("Cancelled queued start of server {}", server_id);
+            return Ok(());
         }
 
-        cmd.stdin(Stdio::piped())
-            .stdout(Stdio::piped())
-            .stderr(Stdio::piped());
+        // Only hold the lock to grab the handles, shutting down can take several seconds
+        let (game, stop_command) = {
+            let processes = self.processes.read().await;
+            let proc = processes
+                .get(server_id)
+                .ok_or_else(|| AppError::NotFound("servers.not_running".into()))?;
+
+            // If it's an installation task, abort it
+            if let Some(task) = &proc.install_task {
+                task.abort();
+                info!("Aborted installation task for server {}", server_id);
+            }
+            (proc.game.clone(), proc.stop_command.trim().to_string())
+        };
 
-        let mut child = cmd
-            .spawn()
-            .map_err(|e| AppError::Internal(format!("Failed to start server: {}", e)))?;
+        if let Some(game) = &game {
+            game.stop_requested.store(true, Ordering::SeqCst);
 
-        info!("Started server {} with PID {:?}", server_id, child.id());
+            // A frozen process can't read the stop command nor handle SIGTERM
+            if game.suspended.swap(false, Ordering::SeqCst) {
+                if let Some(pid) = game.pid {
+                    Self::freeze(server_id, game, pid, false).await;
+                }
+            }
 
-        // Create log file
-        let logs_dir = std::path::Path::new(working_dir).join("logs");
-        if !logs_dir.exists() {
-             let _ = std::fs::create_dir_all(&logs_dir);
-        }
-        let log_path = logs_dir.join("console.log");
-        let log_file = std::fs::OpenOptions::new()
-            .create(true)
-            .write(true)
-            .truncate(true)
-            .open(log_path)
-            .ok()
-            .map(|f| Arc::new(std::sync::Mutex::new(f)));
+            // Try graceful shutdown first (send the configured stop command)
+            let mut exited = game.has_exited();
+            if !exited && !stop_command.is_empty() && game.write_line(&stop_command).await.is_ok() {
+                exited = game.wait_exit(Duration::from_secs(5)).await;
+            }
 
-        // Create log broadcaster
-        let (log_tx, _) = broadcast::channel::<String>(1000);
-        let _ = log_tx.send(format!("[STATUS]: running"));
+            // Still running: ask the OS to stop it (SIGTERM / console CTRL_C)
+            if !exited && game.pid.is_some_and(platform::request_graceful_stop) {
+                exited = game.wait_exit(Duration::from_secs(5)).await;
+            }
 
-        // Create players tracker
-        let players = Arc::new(std::sync::RwLock::new(HashSet::new()));
+            // Force kill if still running
+            if !exited {
+                game.kill().await;
+            }
+        }
 
-        let auth_required = Arc::new(std::sync::RwLock::new(false));
+        self.remove_run(server_id, game.as_ref()).await;
+        info!("Stopped server {}", server_id);
 
-        // Spawn task to read stdout
-        if let Some(stdout) = child.stdout.take() {
-            let tx = log_tx.clone();
-            let players_clone = players.clone();
-            let server_id_clone = server_id.to_string();
-            let log_file_clone = log_file.clone();
-            let pool_clone_opt = self.pool.clone();
-            let auth_required_clone = auth_required.clone();
-            
-            std::thread::spawn(move || {
-                let reader = BufReader::new(stdout);
-                let join_re = Regex::new(r"\[.*\] \[.*\]: (.*) joined the game").unwrap();
-                let leave_re = Regex::new(r"\[.*\] \[.*\]: (.*) left the game").unwrap();
-                // Also match "Authentication successful! Welcome, [Player]!" logic if needed?
-                // Actually server emits "Player joined" essentially.
-
-                // Regex for "Server started" or similar if we want to change status?
-                // Hytale: "[HytaleServer] Universe ready!"
-                let server_started_re = Regex::new(r"Universe ready!").unwrap();
-
-                let pool_clone = pool_clone_opt; // Capture optional pool
-
-                for line in reader.lines().map_while(Result::ok) {
-                    // tracing::debug!("Server {} log line: {}", server_id_clone, line);
-                    
-                    // Write to file
-                    if let Some(f) = &log_file_clone {
-                        if let Ok(mut guard) = f.lock() {
-                            let _ = writeln!(guard, "{}", line);
-                        }
-                    }
+        Ok(())
+    }
 
-                    // Try to match player events
-                    if let Some(caps) = join_re.captures(&line) {
-                        if let Some(name) = caps.get(1) {
-                            let player_name = name.as_str().to_string();
-                            info!("Player joined server {}: {}", server_id_clone, player_name);
-                            if let Ok(mut p) = players_clone.write() {
-                                p.insert(player_name.clone());
-                            }
-                            
-                            // DB Update: Connect
-                            if let Some(pool) = &pool_clone {
-                                let pool = pool.clone();
-                                let s_id = server_id_clone.clone();
-                                let p_name = player_name.clone();
-                                tokio::spawn(async move {
-                                    let now = chrono::Utc::now().to_rfc3339();
-                                    let _ = sqlx::query(
-                                        "INSERT INTO server_players (server_id, player_name, first_seen, last_seen, is_online) 
-                                         VALUES (?, ?, ?, ?, 1)
-                                         ON CONFLICT(server_id, player_name) DO UPDATE SET 
-                                         last_seen = excluded.last_seen, 
-                                         is_online = 1"
-                                    )
-                                    .bind(s_id)
-                                    .bind(p_name)
-                                    .bind(&now) // first_seen
-                                    .bind(&now) // last_seen
-                                    .execute(&pool)
-                                    .await;
-                                });
-                            }
-                        }
-                    } else if let Some(caps) = leave_re.captures(&line) {
-                        if let Some(name) = caps.get(1) {
-                            let player_name = name.as_str().to_string();
-                            if let Ok(mut p) = players_clone.write() {
-                                p.remove(&player_name);
-                            }
+    /// Drop the entry unless a new run already replaced it
+    async fn remove_run(&self, server_id: &str, game: Option<&GameProcess>) {
+        let mut processes = self.processes.write().await;
+        let current = processes.get(server_id).map(|p| p.game.as_ref());
+        let same = match (current, game) {
+            (Some(Some(current)), Some(game)) => current.same_run(game),
+            (Some(None), None) => true,
+            _ => false,
+        };
+        if same {
+            processes.remove(server_id);
+        }
+    }
 
-                            // DB Update: Disconnect
-                            if let Some(pool) = &pool_clone {
-                                let pool = pool.clone();
-                                let s_id = server_id_clone.clone();
-                                let p_name = player_name.clone();
-                                tokio::spawn(async move {
-                                    let now = chrono::Utc::now().to_rfc3339();
-                                    let _ = sqlx::query(
-                                        "UPDATE server_players SET is_online = 0, last_seen = ? WHERE server_id = ? AND player_name = ?"
-                                    )
-                                    .bind(now)
-                                    .bind(s_id)
-                                    .bind(p_name)
-                                    .execute(&pool)
-                                    .await;
-                                });
-                            }
-                        }
-                    } else if server_started_re.is_match(&line) {
-                         let _ = tx.send(format!("[STATUS]: running"));
-                    }
+    /// Drop the entry of an exited run (unless a new run replaced it) and handle the exit once
+    async fn finish_run(&self, server_id: &str, game: &GameProcess) {
+        {
+            let mut processes = self.processes.write().await;
+            if processes.get(server_id).and_then(|p| p.game.as_ref()).is_some_and(|g| g.same_run(game)) {
+                processes.remove(server_id);
+            }
+        }
 
-                    // Runtime Auth Detection
-                    if (line.contains("IMPORTANT") && (line.contains("authentifier") || line.contains("authenticate"))) ||
-                       (line.contains("[HytaleServer] No server tokens configured")) ||
-                       (line.contains("/auth login to authenticate")) {
-                         if let Ok(mut auth) = auth_required_clone.write() {
-                             *auth = true;
-                         }
-                    }
+        if !game.exit_handled.swap(true, Ordering::SeqCst) {
+            self.handle_exit(server_id, game).await;
+        }
+    }
 
-                    let _ = tx.send(line);
-                }
-                
-                info!("Server {} stdout stream ended", server_id_clone);
-                let _ = tx.send(format!("[STATUS]: stopped"));
-                
-                 // Write stop marker to file
-                if let Some(f) = &log_file_clone {
-                    if let Ok(mut guard) = f.lock() {
-                        let _ = writeln!(guard, "[Server Stopped]");
-                    }
+    /// Clean up entries whose process exited but are still in the map, e.g. because their
+    /// watcher task died before it could remove them
+    async fn reap(&self) {
+        let ghosts: Vec<(String, GameProcess, broadcast::Sender<String>, bool)> = {
+            let processes = self.processes.read().await;
+            processes.iter()
+                .filter_map(|(id, p)| {
+                    let game = p.game.as_ref()?;
+                    let watcher_gone = p.exit_watcher.as_ref().is_some_and(|w| w.is_finished());
+                    (game.has_exited() || watcher_gone).then(|| (id.clone(), game.clone(), p.log_tx.clone(), watcher_gone))
+                })
+                .collect()
+        };
+
+        for (server_id, game, log_tx, watcher_gone) in ghosts {
+            tracing::warn!("Reaping exited process of server {}", server_id);
+            // Nothing owns the child anymore: make sure it doesn't keep running unmanaged
+            if watcher_gone && !game.has_exited() {
+                if let Some(pid) = game.pid {
+                    platform::request_graceful_stop(pid);
                 }
-            });
+            }
+            send_status(&log_tx, &self.events, &server_id, "stopped");
+            self.finish_run(&server_id, &game).await;
         }
+    }
 
-        // Spawn task to read stderr
-        if let Some(stderr) = child.stderr.take() {
-            let tx = log_tx.clone();
-            let server_id_clone = server_id.to_string();
-            let log_file_clone = log_file.clone();
-            let auth_required_clone = auth_required.clone();
-
-            std::thread::spawn(move || {
-                let reader = BufReader::new(stderr);
-                for line in reader.lines().map_while(Result::ok) {
-                    let log_line = format!("[STDERR] {}", line);
-                     // Write to file
-                    if let Some(f) = &log_file_clone {
-                        if let Ok(mut guard) = f.lock() {
-                            let _ = writeln!(guard, "{}", log_line);
-                        }
-                    }
-                    let _ = tx.send(log_line);
-                    
-                    // Runtime Auth Detection (stderr)
-                     if (line.contains("IMPORTANT") && (line.contains("authentifier") || line.contains("authenticate"))) ||
-                       (line.contains("[HytaleServer] No server tokens configured")) ||
-                       (line.contains("/auth login to authenticate")) {
-                            if let Ok(mut auth) = auth_required_clone.write() {
-                             *auth = true;
-                         }
-                    }
+    /// Record the exit code and publish the stop or crash. A crash is handed to the watchdog
+    /// when the server has it enabled.
+    async fn handle_exit(&self, server_id: &str, game: &GameProcess) {
+        let status = game.exit_status.get().copied().flatten();
+        let exit_code = status.and_then(|s| s.code());
+        let Some(pool) = &self.pool else {
+            return;
+        };
+
+        let _ = sqlx::query("UPDATE servers SET last_exit_code = ?, last_exit_at = ? WHERE id = ?")
+            .bind(exit_code)
+            .bind(chrono::Utc::now().to_rfc3339())
+            .bind(server_id)
+            .execute(pool)
+            .await;
+
+        // Failed exit nobody asked for: the server crashed
+        let failed = !status.is_some_and(|s| s.success());
+        if !failed || game.stop_requested.load(Ordering::SeqCst) {
+            self.events.publish(PanelEvent::ServerStopped { server_id: server_id.to_string(), exit_code });
+            return;
+        }
+
+        tracing::warn!("Server {} crashed", server_id);
+        let watchdog_enabled = Self::watchdog_enabled(pool, server_id).await;
+        let restart = watchdog_enabled && self.watchdog_allows(server_id);
+        self.events.publish(PanelEvent::ServerCrashed {
+            server_id: server_id.to_string(),
+            exit_code,
+            watchdog_enabled,
+            auto_restart: restart,
+        });
+
+        if restart {
+            let _ = self.watchdog_tx.send((server_id.to_string(), game.launch.clone()));
+        }
+    }
+
+    async fn watchdog_enabled(pool: &DbPool, server_id: &str) -> bool {
+        let enabled: Option<i32> = sqlx::query_scalar("SELECT watchdog_enabled FROM servers WHERE id = ?")
+            .bind(server_id)
+            .fetch_optional(pool)
+            .await
+            .ok()
+            .flatten();
+        enabled.is_some_and(|enabled| enabled != 0)
+    }
+
+    /// Probe the game port of a run from its boot until it exits. After
+    /// `FAILURES_BEFORE_UNRESPONSIVE` failures in a row the server is reported unresponsive.
+    async fn run_health_probe(
+        self,
+        server_id: String,
+        game: GameProcess,
+        probe: HealthProbe,
+        booted: broadcast::Receiver<String>,
+        log_tx: broadcast::Sender<String>,
+    ) {
+        wait_until_booted(booted).await;
+        let (bind_ip, port) = Self::bind_target(&game.launch);
+        let mut failures = 0;
+
+        loop {
+            tokio::time::sleep(probe.interval).await;
+            if game.has_exited() {
+                return;
+            }
+            // A frozen server can't answer, on purpose
+            if game.suspended.load(Ordering::SeqCst) {
+                failures = 0;
+                continue;
+            }
+
+            if probe.check(&bind_ip, port as u16).await {
+                failures = 0;
+                if game.unresponsive.swap(false, Ordering::SeqCst) {
+                    info!("Server {} responds again", server_id);
+                    send_status(&log_tx, &self.events, &server_id, "running");
                 }
-                info!("Server {} stderr stream ended", server_id_clone);
-            });
+                continue;
+            }
+
+            failures += 1;
+            if failures < health_probe::FAILURES_BEFORE_UNRESPONSIVE || game.unresponsive.swap(true, Ordering::SeqCst) {
+                continue;
+            }
+            tracing::warn!("Server {} is unresponsive ({} probe on port {})", server_id, probe.kind, port);
+            send_status(&log_tx, &self.events, &server_id, "unresponsive");
+            if self.handle_unresponsive(&server_id, &game, &probe, port).await {
+                return;
+            }
         }
+    }
 
-        processes.insert(
-            server_id.to_string(),
-            ServerProcess { 
-                child: Some(child), 
-                install_task: None,
-                log_tx, 
-                players,
-                last_metrics: Arc::new(std::sync::RwLock::new(None)),
-                last_cpu: Arc::new(std::sync::RwLock::new(0.0)),
-                last_cpu_normalized: Arc::new(std::sync::RwLock::new(0.0)),
-                last_memory: Arc::new(std::sync::RwLock::new(0)),
-                last_disk: Arc::new(std::sync::RwLock::new(0)),
-                working_dir: working_dir.to_string(),
-                started_at: Some(chrono::Utc::now()),
-                auth_required: auth_required,
-            },
+    /// Log and alert about a hung server, then have the watchdog kill and relaunch it when
+    /// enabled. Returns whether the run was killed.
+    async fn handle_unresponsive(&self, server_id: &str, game: &GameProcess, probe: &HealthProbe, port: u64) -> bool {
+        let Some(pool) = &self.pool else {
+            return false;
+        };
+        let detail = format!("{} probe on port {}", probe.kind, port);
+        history::record_event(pool, server_id, history::EVENT_UNRESPONSIVE, Some(&detail)).await;
+
+        let watchdog_enabled = Self::watchdog_enabled(pool, server_id).await;
+        let restart = watchdog_enabled && self.watchdog_allows(server_id);
+
+        let mut description = format!(
+            "Le processus tourne mais ne répond plus sur le port {} ({} sondes {} échouées).",
+            port,
+            health_probe::FAILURES_BEFORE_UNRESPONSIVE,
+            probe.kind.to_uppercase()
         );
+        if restart {
+            description.push_str(&format!(" Redémarrage forcé dans {} s.", WATCHDOG_DELAY.as_secs()));
+        }
+        discord_service::notify_server(pool, server_id, "⚠️ Serveur Ne Répond Plus", &description, discord_service::COLOR_WARNING).await;
 
-        Ok(())
+        if !restart {
+            return false;
+        }
+        game.kill().await;
+        self.remove_run(server_id, Some(game)).await;
+        let _ = self.watchdog_tx.send((server_id.to_string(), game.launch.clone()));
+        true
     }
 
-    pub async fn stop(&self, server_id: &str) -> Result<(), AppError> {
-        let mut processes = self.processes.write().await;
+    pub async fn is_unresponsive(&self, server_id: &str) -> bool {
+        self.processes.read().await
+            .get(server_id)
+            .and_then(|p| p.game.as_ref())
+            .is_some_and(|g| g.unresponsive.load(Ordering::SeqCst))
+    }
 
-        let proc = processes
-            .get_mut(server_id)
-            .ok_or_else(|| AppError::NotFound("Server not running".into()))?;
+    /// Count a watchdog relaunch, refusing it when the server keeps crashing
+    fn watchdog_allows(&self, server_id: &str) -> bool {
+        let Ok(mut restarts) = self.watchdog_restarts.lock() else {
+            return false;
+        };
+        let recent = restarts.entry(server_id.to_string()).or_default();
+        recent.retain(|at| at.elapsed() < WATCHDOG_WINDOW);
+        if recent.len() >= WATCHDOG_MAX_RESTARTS {
+            return false;
+        }
+        recent.push(std::time::Instant::now());
+        true
+    }
+
+    /// Relaunch a crashed server, then tell its players once it is back
+    async fn watchdog_restart(&self, server_id: &str, launch: &LaunchConfig) {
+        tokio::time::sleep(WATCHDOG_DELAY).await;
 
-        // If it's an installation task, abort it
-        if let Some(task) = &proc.install_task {
-            task.abort();
-            info!("Aborted installation task for server {}", server_id);
-            // We can return early or continue to cleanup
+        // Started (or queued) by someone else in the meantime
+        if self.is_running(server_id).await || self.is_queued(server_id) {
+            return;
         }
 
-        if let Some(child) = &mut proc.child {
-             // Try graceful shutdown first (send quit command)
-            if let Some(stdin) = child.stdin.as_mut() {
-                let _ = writeln!(stdin, "/shutdown");
-            }
+        info!("Watchdog restarting server {}", server_id);
+        if let Err(e) = self.start(server_id, launch).await {
+            tracing::error!("Watchdog could not restart server {}: {}", server_id, e);
+            return;
+        }
 
-            // Wait a bit for graceful shutdown
-            tokio::time::sleep(tokio::time::Duration::from_secs(5)).await;
+        let Some(pool) = &self.pool else {
+            return;
+        };
+        history::record_event(pool, server_id, history::EVENT_RESTART, Some("watchdog")).await;
 
-            // Force kill if still running
-            if child.try_wait().map_err(|e| AppError::Internal(e.to_string()))?.is_none() {
-                child
-                    .kill()
-                    .map_err(|e| AppError::Internal(format!("Failed to kill server: {}", e)))?;
-            }
+        let locale: Option<String> = sqlx::query_scalar("SELECT locale FROM servers WHERE id = ?")
+            .bind(server_id)
+            .fetch_optional(pool)
+            .await
+            .ok()
+            .flatten();
+        wait_until_booted(self.subscribe_logs(server_id).await).await;
+        let locale = locale.unwrap_or_else(|| broadcast_messages::DEFAULT_LOCALE.to_string());
+        let _ = broadcast_messages::broadcast(self, server_id, &locale, "watchdog_restart", &[]).await;
+    }
+
+    /// Gracefully stop every managed server (used when the panel shuts down)
+    pub async fn stop_all(&self) {
+        if let Ok(mut queued) = self.start_queue.queued.write() {
+            queued.clear();
         }
-        
-        processes.remove(server_id);
-        info!("Stopped server {}", server_id);
+        self.start_queue.released.notify_waiters();
 
-        Ok(())
+        let ids: Vec<String> = self.processes.read().await.keys().cloned().collect();
+        for id in ids {
+            if let Err(e) = self.stop(&id).await {
+                tracing::error!("Failed to stop server {} during shutdown: {}", id, e);
+            }
+        }
     }
 
     /// Force kill a server immediately without graceful shutdown
     pub async fn kill(&self, server_id: &str) -> Result<(), AppError> {
-        let mut processes = self.processes.write().await;
-
-        let proc = processes
-            .get_mut(server_id)
-            .ok_or_else(|| AppError::NotFound("Server not running".into()))?;
-
-        if let Some(child) = &mut proc.child {
-            // Force kill immediately
-            child
-                .kill()
-                .map_err(|e| AppError::Internal(format!("Failed to kill server: {}", e)))?;
+        let game = self.processes.read().await
+            .get(server_id)
+            .ok_or_else(|| AppError::NotFound("servers.not_running".into()))?
+            .game
+            .clone();
+
+        if let Some(game) = &game {
+            game.kill().await;
         }
 
-        processes.remove(server_id);
+        self.remove_run(server_id, game.as_ref()).await;
         info!("Killed server {}", server_id);
 
         Ok(())
     }
 
-    pub async fn restart(
-        &self,
-        server_id: &str,
-        executable_path: &str,
-        working_dir: &str,
-        java_path: Option<&str>,
-        min_memory: Option<&str>,
-        max_memory: Option<&str>,
-        extra_args: Option<&str>,
-        config: Option<&serde_json::Value>,
-    ) -> Result<(), AppError> {
+    /// Freeze the server process (SIGSTOP) without losing its state, freeing its CPU
+    pub async fn suspend(&self, server_id: &str) -> Result<(), AppError> {
+        self.set_suspended(server_id, true).await
+    }
+
+    /// Thaw a process frozen by `suspend`
+    pub async fn resume(&self, server_id: &str) -> Result<(), AppError> {
+        self.set_suspended(server_id, false).await
+    }
+
+    async fn set_suspended(&self, server_id: &str, suspended: bool) -> Result<(), AppError> {
+        let (game, log_tx) = {
+            let processes = self.processes.read().await;
+            let proc = processes
+                .get(server_id)
+                .filter(|p| p.is_alive())
+                .ok_or_else(|| AppError::NotFound("servers.not_running".into()))?;
+            (proc.game.clone(), proc.log_tx.clone())
+        };
+        let (Some(game), Some(pid)) = (game.as_ref(), game.as_ref().and_then(|g| g.pid)) else {
+            return Err(AppError::BadRequest("servers.installing".into()));
+        };
+
+        if game.suspended.load(Ordering::SeqCst) == suspended {
+            let key = if suspended { "servers.already_suspended" } else { "servers.not_suspended" };
+            return Err(AppError::BadRequest(key.into()));
+        }
+        if !Self::freeze(server_id, game, pid, suspended).await {
+            return Err(AppError::BadRequest("servers.suspend_unsupported".into()));
+        }
+        game.suspended.store(suspended, Ordering::SeqCst);
+
+        let status = if suspended { "suspended" } else { "running" };
+        send_status(&log_tx, &self.events, server_id, status);
+        info!("Server {} {}", server_id, if suspended { "suspended" } else { "resumed" });
+        Ok(())
+    }
+
+    /// Freeze or thaw a run, through Docker for containerized servers
+    async fn freeze(server_id: &str, game: &GameProcess, pid: u32, suspended: bool) -> bool {
+        if game.launch.runtime == docker::RUNTIME_DOCKER {
+            docker::set_paused(server_id, suspended).await
+        } else {
+            platform::set_suspended(pid, suspended)
+        }
+    }
+
+    pub async fn is_suspended(&self, server_id: &str) -> bool {
+        self.processes.read().await
+            .get(server_id)
+            .and_then(|p| p.game.as_ref())
+            .is_some_and(|g| g.suspended.load(Ordering::SeqCst))
+    }
+
+    pub async fn restart(&self, server_id: &str, launch: &LaunchConfig) -> Result<(), AppError> {
         // Stop if running
-        if self.is_running(server_id) {
+        if self.is_running(server_id).await {
             self.stop(server_id).await?;
         }
 
         // Start again
-        self.start(
-            server_id,
-            executable_path,
-            working_dir,
-            java_path,
-            min_memory,
-            max_memory,
-            extra_args,
-            config,
-        )
-        .await
+        self.start(server_id, launch).await
     }
 
     pub async fn send_command(&self, server_id: &str, command: &str) -> Result<(), AppError> {
-        let mut processes = self.processes.write().await;
-
-        let proc = processes
-            .get_mut(server_id)
-            .ok_or_else(|| AppError::NotFound("Server not running".into()))?;
-
-        if let Some(child) = &mut proc.child {
-            if let Some(stdin) = child.stdin.as_mut() {
-                writeln!(stdin, "{}", command)
-                    .map_err(|e| AppError::Internal(format!("Failed to send command: {}", e)))?;
-            }
+        let game = self.processes.read().await
+            .get(server_id)
+            .ok_or_else(|| AppError::NotFound("servers.not_running".into()))?
+            .game
+            .clone();
+
+        if let Some(game) = game {
+            game.write_line(command)
+                .await
+                .map_err(|e| AppError::Internal(format!("Failed to send command: {}", e)))?;
         }
 
         Ok(())
     }
 
-
-
-
+    /// Send a command and return the console lines printed in response. Collection stops after
+    /// `window`, or once the output has been quiet for `EXEC_IDLE_GAP` after the first line.
+    pub async fn exec_command(&self, server_id: &str, command: &str, window: Duration) -> Result<Vec<String>, AppError> {
+        // Subscribe before writing so the first response line can't be missed
+        let mut rx = self.subscribe_logs(server_id).await;
+        self.send_command(server_id, command).await?;
+
+        let deadline = tokio::time::Instant::now() + window;
+        let mut lines = Vec::new();
+        loop {
+            let wait_until = if lines.is_empty() {
+                deadline
+            } else {
+                deadline.min(tokio::time::Instant::now() + EXEC_IDLE_GAP)
+            };
+            match tokio::time::timeout_at(wait_until, rx.recv()).await {
+                Ok(Ok(line)) if line.starts_with("[METRICS]:") || line.starts_with("[STATUS]:") => continue,
+                Ok(Ok(line)) => lines.push(line),
+                Ok(Err(broadcast::error::RecvError::Lagged(_))) => continue,
+                Ok(Err(broadcast::error::RecvError::Closed)) | Err(_) => break,
+            }
+        }
+        Ok(lines)
+    }
 
     pub async fn get_online_players(&self, server_id: &str) -> Option<Vec<String>> {
         let processes = self.processes.read().await;
-        if let Some(proc) = processes.get(server_id) {
-            if let Ok(players) = proc.players.read() {
-                return Some(players.iter().cloned().collect());
-            }
-        }
-        None
+        let proc = processes.get(server_id)?;
+        let players = proc.players.read().await;
+        Some(players.iter().cloned().collect())
     }
 
     pub async fn get_server_started_at(&self, server_id: &str) -> Option<chrono::DateTime<chrono::Utc>> {
-        if let Ok(processes) = self.processes.try_read() {
-            if let Some(proc) = processes.get(server_id) {
-                return proc.started_at;
-            }
-        }
-        None
+        self.processes.read().await.get(server_id)?.started_at
     }
 
     pub async fn get_total_online_players(&self) -> u32 {
         let mut total = 0;
         let processes = self.processes.read().await;
         for proc in processes.values() {
-             if let Ok(players) = proc.players.read() {
-                 total += players.len() as u32;
-             }
+            total += proc.player_count().await as u32;
         }
         total
     }
 
     pub async fn get_server_pid(&self, server_id: &str) -> Option<u32> {
-        if let Ok(processes) = self.processes.try_read() {
-            if let Some(proc) = processes.get(server_id) {
-                if let Some(child) = &proc.child {
-                    return Some(child.id());
-                }
-            }
-        }
-        None
+        self.processes.read().await.get(server_id)?.game.as_ref()?.pid
     }
 
     pub async fn get_last_metrics(&self, server_id: &str) -> Option<String> {
         let processes = self.processes.read().await;
-        if let Some(proc) = processes.get(server_id) {
-            if let Ok(cache) = proc.last_metrics.read() {
-                return cache.clone();
-            }
-        }
-        None
+        let proc = processes.get(server_id)?;
+        let cache = proc.last_metrics.read().await;
+        cache.clone()
     }
 
     pub async fn get_metrics_data(&self, server_id: &str) -> (f32, f32, u64, u64) {
         let processes = self.processes.read().await;
         if let Some(proc) = processes.get(server_id) {
-            let cpu = proc.last_cpu.read().map(|g| *g).unwrap_or(0.0);
-            let cpu_norm = proc.last_cpu_normalized.read().map(|g| *g).unwrap_or(0.0);
-            let mem = proc.last_memory.read().map(|g| *g).unwrap_or(0);
-            let disk = proc.last_disk.read().map(|g| *g).unwrap_or(0);
+            let cpu = *proc.last_cpu.read().await;
+            let cpu_norm = *proc.last_cpu_normalized.read().await;
+            let mem = *proc.last_memory.read().await;
+            let disk = *proc.last_disk.read().await;
             return (cpu, cpu_norm, mem, disk);
         }
         (0.0, 0.0, 0, 0)
     }
 
+    /// Network rates and connections of the last metrics sample, `None` when not running
+    pub async fn get_network_data(&self, server_id: &str) -> Option<NetworkStats> {
+        let processes = self.processes.read().await;
+        let proc = processes.get(server_id)?;
+        let stats = *proc.last_network.read().await;
+        Some(stats)
+    }
+
     pub async fn get_processes_read_guard(&self) -> tokio::sync::RwLockReadGuard<'_, HashMap<String, ServerProcess>> {
         self.processes.read().await
     }
@@ -701,10 +1404,187 @@ impl ProcessManager {
 
 impl Default for ProcessManager {
     fn default() -> Self {
-        Self::new(None)
+        Self::new(None, EventBus::default())
+    }
+}
+
+/// Line matching "the server needs `/auth login`" on stdout or stderr
+/// Flag the server as waiting for authentication on a prompt, and surface links seen while it is
+async fn detect_auth(game_type: &str, line: &str, auth_required: &RwLock<bool>, tx: &broadcast::Sender<String>) {
+    let detection = auth_detection::inspect(game_type, line);
+    if detection.prompt {
+        *auth_required.write().await = true;
+    }
+    if let Some(url) = detection.url {
+        if *auth_required.read().await {
+            let _ = tx.send(format!("{} {}", auth_detection::AUTH_URL_PREFIX, url));
+        }
+    }
+}
+
+async fn append_log(log_file: &Option<Arc<Mutex<ConsoleLog>>>, line: &str) {
+    if let Some(f) = log_file {
+        let _ = f.lock().await.write_line(line).await;
+    }
+}
+
+/// Console `[STATUS]` line, mirrored on the event bus
+fn send_status(log_tx: &broadcast::Sender<String>, events: &EventBus, server_id: &str, status: &str) {
+    let _ = log_tx.send(format!("[STATUS]: {}", status));
+    events.publish(PanelEvent::status(server_id, status));
+}
+
+/// What the stdout and stderr readers of a process share
+#[derive(Clone)]
+struct OutputContext {
+    server_id: String,
+    tx: broadcast::Sender<String>,
+    log_file: Option<Arc<Mutex<ConsoleLog>>>,
+    auth_required: Arc<RwLock<bool>>,
+    game_type: String,
+}
+
+/// Forward stdout to the console, tracking players, readiness and auth prompts
+async fn read_stdout(
+    stdout: tokio::process::ChildStdout,
+    output: OutputContext,
+    players: Arc<RwLock<HashSet<String>>>,
+    pool: Option<DbPool>,
+    spawned_at: std::time::Instant,
+    events: EventBus,
+) {
+    let OutputContext { server_id, tx, log_file, auth_required, game_type } = output;
+    let join_re = Regex::new(r"\[.*\] \[.*\]: (.*) joined the game").unwrap();
+    let leave_re = Regex::new(r"\[.*\] \[.*\]: (.*) left the game").unwrap();
+    // Hytale: "[HytaleServer] Universe ready!"
+    let server_started_re = Regex::new(r"Universe ready!").unwrap();
+
+    let mut ready = false;
+
+    let mut lines = BufReader::new(stdout).lines();
+    while let Ok(Some(line)) = lines.next_line().await {
+        append_log(&log_file, &line).await;
+
+        // Try to match player events
+        if let Some(name) = join_re.captures(&line).and_then(|c| c.get(1)) {
+            let player_name = name.as_str().to_string();
+            info!("Player joined server {}: {}", server_id, player_name);
+            players.write().await.insert(player_name.clone());
+            events.publish(PanelEvent::Players { server_id: server_id.clone(), count: players.read().await.len() });
+            events.publish(PanelEvent::PlayerJoined { server_id: server_id.clone(), player: player_name.clone() });
+
+            // DB Update: Connect
+            if let Some(pool) = pool.clone() {
+                let s_id = server_id.clone();
+                tokio::spawn(async move {
+                    let now = chrono::Utc::now().to_rfc3339();
+                    let _ = sqlx::query(
+                        "INSERT INTO server_players (server_id, player_name, first_seen, last_seen, is_online) 
+                         VALUES (?, ?, ?, ?, 1)
+                         ON CONFLICT(server_id, player_name) DO UPDATE SET 
+                         last_seen = excluded.last_seen, 
+                         is_online = 1"
+                    )
+                    .bind(&s_id)
+                    .bind(&player_name)
+                    .bind(&now) // first_seen
+                    .bind(&now) // last_seen
+                    .execute(&pool)
+                    .await;
+                });
+            }
+        } else if let Some(name) = leave_re.captures(&line).and_then(|c| c.get(1)) {
+            let player_name = name.as_str().to_string();
+            players.write().await.remove(&player_name);
+            events.publish(PanelEvent::Players { server_id: server_id.clone(), count: players.read().await.len() });
+            events.publish(PanelEvent::PlayerLeft { server_id: server_id.clone(), player: player_name.clone() });
+
+            // DB Update: Disconnect
+            if let Some(pool) = pool.clone() {
+                let s_id = server_id.clone();
+                tokio::spawn(async move {
+                    let now = chrono::Utc::now().to_rfc3339();
+                    let _ = sqlx::query(
+                        "UPDATE server_players SET is_online = 0, last_seen = ? WHERE server_id = ? AND player_name = ?"
+                    )
+                    .bind(now)
+                    .bind(&s_id)
+                    .bind(&player_name)
+                    .execute(&pool)
+                    .await;
+                });
+            }
+        } else if server_started_re.is_match(&line) {
+            send_status(&tx, &events, &server_id, "running");
+
+            // Boot time: spawn to the first "Universe ready!"
+            if !ready {
+                ready = true;
+                let duration_ms = spawned_at.elapsed().as_millis() as u64;
+                info!("Server {} ready after {} ms", server_id, duration_ms);
+                events.publish(PanelEvent::ServerReady { server_id: server_id.clone(), boot_ms: duration_ms });
+            }
+        }
+
+        // Runtime Auth Detection
+        detect_auth(&game_type, &line, &auth_required, &tx).await;
+
+        let _ = tx.send(line);
     }
+
+    info!("Server {} stdout stream ended", server_id);
+    send_status(&tx, &events, &server_id, "stopped");
+
+    // Write stop marker to file
+    append_log(&log_file, "[Server Stopped]").await;
 }
 
+async fn read_stderr(stderr: tokio::process::ChildStderr, output: OutputContext) {
+    let OutputContext { server_id, tx, log_file, auth_required, game_type } = output;
+    let mut lines = BufReader::new(stderr).lines();
+    while let Ok(Some(line)) = lines.next_line().await {
+        let log_line = format!("[STDERR] {}", line);
+        append_log(&log_file, &log_line).await;
+        let _ = tx.send(log_line);
+
+        // Runtime Auth Detection (stderr)
+        detect_auth(&game_type, &line, &auth_required, &tx).await;
+    }
+    info!("Server {} stderr stream ended", server_id);
+}
+
+/// Own the child until it exits (or is killed), then publish the exit and hand it to the manager
+async fn watch_exit(
+    mut child: Child,
+    tree: platform::ProcessTree,
+    mut kill_rx: mpsc::Receiver<()>,
+    exit_tx: watch::Sender<bool>,
+    manager: ProcessManager,
+    server_id: String,
+    game: GameProcess,
+) {
+    let status = tokio::select! {
+        status = child.wait() => status,
+        Some(()) = kill_rx.recv() => {
+            if game.launch.runtime == docker::RUNTIME_DOCKER {
+                docker::kill(&server_id).await;
+            }
+            tree.kill();
+            let _ = child.start_kill();
+            child.wait().await
+        }
+    };
+    // Sweep what the server left running (launcher children, helper processes)
+    drop(tree);
+    info!("Server {} process exited ({:?})", server_id, status);
+    let _ = game.exit_status.set(status.ok());
+    let _ = exit_tx.send(true);
+
+    manager.finish_run(&server_id, &game).await;
+}
 
 use crate::utils::memory::{parse_memory_to_bytes, calculate_jvm_tokens};
+use crate::utils::java::{self, MIN_JAVA_VERSION};
+use crate::utils::{interfaces, platform};
+use crate::utils::net_stats::{self, NetworkStats};
 
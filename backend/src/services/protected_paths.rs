@@ -0,0 +1,73 @@
+//! Server files only admins may change through the file API: credentials (`auth.enc`) and the
+//! operator list (`permissions.json`) by default, so a user granted `files.edit` to tune configs
+//! can't de-op everyone or wipe the server's login. Listed in the `protected_paths` setting.
+
+use std::path::{Component, Path};
+use std::sync::RwLock;
+
+use crate::db::DbPool;
+
+pub const DEFAULT: &[&str] = &["auth.enc", "permissions.json"];
+
+lazy_static::lazy_static! {
+    static ref PATTERNS: RwLock<Vec<String>> = RwLock::new(DEFAULT.iter().map(|p| p.to_string()).collect());
+}
+
+/// `dir/file.json` with forward slashes and no leading `./` or `/`, None when nothing is left
+/// or it would climb out with `..`
+pub fn normalize(pattern: &str) -> Option<String> {
+    let parts: Vec<&str> = pattern.trim()
+        .split(['/', '\\'])
+        .filter(|p| !p.is_empty() && *p != ".")
+        .collect();
+    if parts.is_empty() || parts.contains(&"..") {
+        return None;
+    }
+    Some(parts.join("/"))
+}
+
+/// Load the saved list, called once at startup
+pub async fn load(pool: &DbPool) {
+    let saved: Option<String> = sqlx::query_scalar("SELECT value FROM settings WHERE key = 'protected_paths'")
+        .fetch_optional(pool)
+        .await
+        .ok()
+        .flatten();
+    if let Some(patterns) = saved.and_then(|v| serde_json::from_str(&v).ok()) {
+        set(patterns);
+    }
+}
+
+pub fn get() -> Vec<String> {
+    PATTERNS.read().map(|p| p.clone()).unwrap_or_default()
+}
+
+/// Replace the list, already normalized
+pub fn set(patterns: Vec<String>) {
+    if let Ok(mut cached) = PATTERNS.write() {
+        *cached = patterns;
+    }
+}
+
+/// Whether `relative`, a resolved path inside a server directory, is protected. A bare name
+/// matches a file or directory of that name at any depth, a path with a `/` matches from the
+/// server root; everything inside a matching directory is protected too. Case is ignored, as
+/// on Windows hosts.
+pub fn is_protected(relative: &Path) -> bool {
+    let components: Vec<String> = relative.components()
+        .filter_map(|c| match c {
+            Component::Normal(name) => Some(name.to_string_lossy().to_lowercase()),
+            _ => None,
+        })
+        .collect();
+    let Ok(patterns) = PATTERNS.read() else {
+        return true;
+    };
+    patterns.iter().any(|pattern| {
+        let parts: Vec<String> = pattern.split('/').map(str::to_lowercase).collect();
+        match parts.as_slice() {
+            [name] => components.contains(name),
+            _ => components.starts_with(&parts),
+        }
+    })
+}
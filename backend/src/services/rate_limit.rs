@@ -0,0 +1,112 @@
+//! Request budgets of the endpoints abusive clients hammer: logins, file writes and console
+//! commands. Each bucket is a token bucket per client (API token, or address for everything
+//! else) refilled continuously to its per-minute limit, set in the `rate_limits` setting.
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, Instant};
+
+use crate::db::DbPool;
+
+pub const BUCKET_AUTH: &str = "auth";
+pub const BUCKET_FILES: &str = "files";
+pub const BUCKET_COMMANDS: &str = "commands";
+
+/// Requests per minute of each bucket unless the setting overrides it, 0 meaning unlimited
+pub const DEFAULTS: &[(&str, u32)] = &[(BUCKET_AUTH, 20), (BUCKET_FILES, 120), (BUCKET_COMMANDS, 60)];
+
+/// Past this many clients tracked, full buckets are forgotten
+const PRUNE_ABOVE: usize = 10_000;
+
+/// Bucket -> requests per minute
+pub type RateLimits = BTreeMap<String, u32>;
+
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+/// The outcome of a request, for the response headers
+pub struct Decision {
+    pub allowed: bool,
+    pub limit: u32,
+    pub remaining: u32,
+    /// Until the next request is allowed, when rejected
+    pub retry_after: Duration,
+}
+
+#[derive(Clone)]
+pub struct RateLimiter {
+    limits: Arc<RwLock<RateLimits>>,
+    /// (bucket, client) -> tokens left
+    buckets: Arc<Mutex<HashMap<(&'static str, String), Bucket>>>,
+}
+
+impl Default for RateLimiter {
+    fn default() -> Self {
+        Self {
+            limits: Arc::new(RwLock::new(DEFAULTS.iter().map(|(b, l)| (b.to_string(), *l)).collect())),
+            buckets: Arc::default(),
+        }
+    }
+}
+
+impl RateLimiter {
+    /// Apply the saved limits, called once at startup
+    pub async fn load(&self, pool: &DbPool) {
+        let saved: Option<String> = sqlx::query_scalar("SELECT value FROM settings WHERE key = 'rate_limits'")
+            .fetch_optional(pool)
+            .await
+            .ok()
+            .flatten();
+        if let Some(limits) = saved.and_then(|v| serde_json::from_str(&v).ok()) {
+            self.set(limits);
+        }
+    }
+
+    /// Every bucket's limit, defaults included
+    pub fn get(&self) -> RateLimits {
+        self.limits.read().map(|l| l.clone()).unwrap_or_default()
+    }
+
+    /// Override some buckets, the others keep their limit
+    pub fn set(&self, limits: RateLimits) {
+        if let Ok(mut current) = self.limits.write() {
+            current.extend(limits.into_iter().filter(|(b, _)| is_bucket(b)));
+        }
+    }
+
+    /// Count a request of `client` against `bucket`
+    pub fn check(&self, bucket: &'static str, client: &str) -> Decision {
+        let limit = self.limits.read().ok().and_then(|l| l.get(bucket).copied()).unwrap_or(0);
+        let unlimited = Decision { allowed: true, limit, remaining: limit, retry_after: Duration::ZERO };
+        if limit == 0 {
+            return unlimited;
+        }
+        let Ok(mut buckets) = self.buckets.lock() else {
+            return unlimited;
+        };
+
+        let capacity = f64::from(limit);
+        let per_sec = capacity / 60.0;
+        let now = Instant::now();
+        if buckets.len() > PRUNE_ABOVE {
+            buckets.retain(|_, b| now.duration_since(b.updated).as_secs_f64() * per_sec < capacity);
+        }
+        let entry = buckets.entry((bucket, client.to_string())).or_insert(Bucket { tokens: capacity, updated: now });
+        entry.tokens = (entry.tokens + now.duration_since(entry.updated).as_secs_f64() * per_sec).min(capacity);
+        entry.updated = now;
+
+        if entry.tokens >= 1.0 {
+            entry.tokens -= 1.0;
+            Decision { allowed: true, limit, remaining: entry.tokens as u32, retry_after: Duration::ZERO }
+        } else {
+            let retry_after = Duration::from_secs_f64((1.0 - entry.tokens) / per_sec);
+            Decision { allowed: false, limit, remaining: 0, retry_after }
+        }
+    }
+}
+
+pub fn is_bucket(name: &str) -> bool {
+    DEFAULTS.iter().any(|(b, _)| *b == name)
+}
@@ -0,0 +1,242 @@
+//! Offsite copies of backups on an S3-compatible bucket (AWS S3, MinIO, Backblaze B2...).
+//! Requests are signed with AWS Signature V4 and address objects path-style
+//! (`endpoint/bucket/key`), which every S3-compatible service accepts.
+
+use std::path::{Path, PathBuf};
+
+use chrono::Utc;
+use hmac::{Hmac, Mac};
+use sha2::{Digest, Sha256};
+
+use crate::db::DbPool;
+
+pub const LOCATION_LOCAL: &str = "local";
+pub const LOCATION_REMOTE: &str = "remote";
+/// Uploaded with the local copy still on disk
+pub const LOCATION_BOTH: &str = "both";
+
+const UNSIGNED_PAYLOAD: &str = "UNSIGNED-PAYLOAD";
+
+type HmacSha256 = Hmac<Sha256>;
+
+#[derive(Debug, Clone)]
+pub struct RemoteConfig {
+    pub endpoint: String,
+    pub region: String,
+    pub bucket: String,
+    pub access_key: String,
+    pub secret_key: String,
+    pub prefix: String,
+    /// Uploaded backups kept on disk per server, older ones only live in the bucket (0 keeps all)
+    pub local_keep: u32,
+}
+
+/// The configured bucket from the `s3_*` settings, `None` when offsite storage is off
+pub async fn load(pool: &DbPool) -> Option<RemoteConfig> {
+    let rows: Vec<(String, String)> = sqlx::query_as("SELECT key, value FROM settings WHERE key LIKE 's3_%'")
+        .fetch_all(pool)
+        .await
+        .ok()?;
+    let get = |key: &str| rows.iter().find(|(k, _)| k == key).map(|(_, v)| v.trim().to_string()).unwrap_or_default();
+
+    let config = RemoteConfig {
+        endpoint: get("s3_endpoint").trim_end_matches('/').to_string(),
+        region: get("s3_region"),
+        bucket: get("s3_bucket"),
+        access_key: get("s3_access_key"),
+        secret_key: get("s3_secret_key"),
+        prefix: get("s3_prefix").trim_matches('/').to_string(),
+        local_keep: get("s3_local_keep").parse().unwrap_or(0),
+    };
+    let complete = [&config.endpoint, &config.region, &config.bucket, &config.access_key, &config.secret_key]
+        .iter()
+        .all(|v| !v.is_empty());
+    complete.then_some(config)
+}
+
+impl RemoteConfig {
+    fn object_url(&self, filename: &str) -> String {
+        let key = if self.prefix.is_empty() { filename.to_string() } else { format!("{}/{}", self.prefix, filename) };
+        format!("{}/{}/{}", self.endpoint, uri_encode(&self.bucket, false), uri_encode(&key, true))
+    }
+
+    /// Request with the SigV4 `Authorization` header; the payload is left unsigned so large
+    /// archives can be streamed
+    fn request(&self, client: &reqwest::Client, method: reqwest::Method, url: &str) -> anyhow::Result<reqwest::RequestBuilder> {
+        let parsed = reqwest::Url::parse(url)?;
+        let host = match parsed.port() {
+            Some(port) => format!("{}:{}", parsed.host_str().unwrap_or_default(), port),
+            None => parsed.host_str().unwrap_or_default().to_string(),
+        };
+        let now = Utc::now();
+        let amz_date = now.format("%Y%m%dT%H%M%SZ").to_string();
+        let date = now.format("%Y%m%d").to_string();
+        let scope = format!("{}/{}/s3/aws4_request", date, self.region);
+        let signed_headers = "host;x-amz-content-sha256;x-amz-date";
+
+        let canonical_request = format!(
+            "{}\n{}\n\nhost:{}\nx-amz-content-sha256:{}\nx-amz-date:{}\n\n{}\n{}",
+            method.as_str(), parsed.path(), host, UNSIGNED_PAYLOAD, amz_date, signed_headers, UNSIGNED_PAYLOAD
+        );
+        let string_to_sign = format!(
+            "AWS4-HMAC-SHA256\n{}\n{}\n{}",
+            amz_date, scope, hex::encode(Sha256::digest(canonical_request.as_bytes()))
+        );
+
+        let mut key = hmac(format!("AWS4{}", self.secret_key).as_bytes(), date.as_bytes());
+        for part in [self.region.as_str(), "s3", "aws4_request"] {
+            key = hmac(&key, part.as_bytes());
+        }
+        let signature = hex::encode(hmac(&key, string_to_sign.as_bytes()));
+
+        Ok(client
+            .request(method, parsed)
+            .header("x-amz-date", amz_date)
+            .header("x-amz-content-sha256", UNSIGNED_PAYLOAD)
+            .header("authorization", format!(
+                "AWS4-HMAC-SHA256 Credential={}/{}, SignedHeaders={}, Signature={}",
+                self.access_key, scope, signed_headers, signature
+            )))
+    }
+
+    /// Send a file in a single PUT, which S3 caps at 5 GiB per object
+    pub async fn upload(&self, filename: &str, path: &Path) -> anyhow::Result<()> {
+        let file = tokio::fs::File::open(path).await?;
+        let size = file.metadata().await?.len();
+        let client = reqwest::Client::new();
+        let response = self
+            .request(&client, reqwest::Method::PUT, &self.object_url(filename))?
+            .header(reqwest::header::CONTENT_LENGTH, size)
+            .body(reqwest::Body::from(file))
+            .send()
+            .await?;
+        check(response).await
+    }
+
+    /// Fetch an object into `path`, chunk by chunk
+    pub async fn download(&self, filename: &str, path: &Path) -> anyhow::Result<()> {
+        use tokio::io::AsyncWriteExt;
+
+        let client = reqwest::Client::new();
+        let mut response = self.request(&client, reqwest::Method::GET, &self.object_url(filename))?.send().await?;
+        if !response.status().is_success() {
+            return check(response).await;
+        }
+        let mut file = tokio::fs::File::create(path).await?;
+        while let Some(chunk) = response.chunk().await? {
+            file.write_all(&chunk).await?;
+        }
+        file.flush().await?;
+        Ok(())
+    }
+
+    pub async fn delete(&self, filename: &str) -> anyhow::Result<()> {
+        let client = reqwest::Client::new();
+        let response = self.request(&client, reqwest::Method::DELETE, &self.object_url(filename))?.send().await?;
+        check(response).await
+    }
+}
+
+async fn check(response: reqwest::Response) -> anyhow::Result<()> {
+    let status = response.status();
+    if status.is_success() {
+        return Ok(());
+    }
+    let body = response.text().await.unwrap_or_default();
+    anyhow::bail!("bucket answered {}: {}", status, body.chars().take(300).collect::<String>())
+}
+
+fn hmac(key: &[u8], data: &[u8]) -> Vec<u8> {
+    let mut mac = HmacSha256::new_from_slice(key).expect("HMAC accepts any key length");
+    mac.update(data);
+    mac.finalize().into_bytes().to_vec()
+}
+
+/// Percent-encoding of SigV4 canonical URIs, `/` kept between key segments
+fn uri_encode(value: &str, keep_slash: bool) -> String {
+    let mut encoded = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => encoded.push(byte as char),
+            b'/' if keep_slash => encoded.push('/'),
+            _ => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    encoded
+}
+
+/// Upload a backup in the background, then drop local copies beyond `local_keep`
+pub fn spawn_upload(pool: DbPool, backups_dir: PathBuf, backup_id: String, server_id: String, filename: String) {
+    tokio::spawn(async move {
+        let Some(config) = load(&pool).await else {
+            return;
+        };
+        let path = backups_dir.join(&filename);
+        if let Err(e) = config.upload(&filename, &path).await {
+            tracing::error!("Offsite upload of backup {} failed: {}", filename, e);
+            return;
+        }
+        if let Err(e) = sqlx::query("UPDATE backups SET location = ? WHERE id = ?")
+            .bind(LOCATION_BOTH)
+            .bind(&backup_id)
+            .execute(&pool)
+            .await
+        {
+            tracing::error!("Failed to record offsite copy of backup {}: {}", filename, e);
+            return;
+        }
+        tracing::info!("Backup {} uploaded to bucket {}", filename, config.bucket);
+        prune_local(&pool, &backups_dir, &config, &server_id).await;
+    });
+}
+
+/// Delete the local files of uploaded backups beyond the newest `local_keep` of a server
+async fn prune_local(pool: &DbPool, backups_dir: &Path, config: &RemoteConfig, server_id: &str) {
+    if config.local_keep == 0 {
+        return;
+    }
+    let old: Vec<(String, String)> = match sqlx::query_as(
+        "SELECT id, filename FROM backups WHERE server_id = ? AND location = ? ORDER BY created_at DESC LIMIT -1 OFFSET ?"
+    )
+    .bind(server_id)
+    .bind(LOCATION_BOTH)
+    .bind(config.local_keep as i64)
+    .fetch_all(pool)
+    .await
+    {
+        Ok(old) => old,
+        Err(e) => {
+            tracing::warn!("Failed to list local backups of server {}: {}", server_id, e);
+            return;
+        }
+    };
+
+    for (id, filename) in old {
+        let path = backups_dir.join(&filename);
+        if let Err(e) = tokio::fs::remove_file(&path).await {
+            if e.kind() != std::io::ErrorKind::NotFound {
+                tracing::warn!("Failed to delete local copy {:?}: {}", path, e);
+                continue;
+            }
+        }
+        let _ = sqlx::query("UPDATE backups SET location = ? WHERE id = ?")
+            .bind(LOCATION_REMOTE)
+            .bind(&id)
+            .execute(pool)
+            .await;
+    }
+}
+
+/// Delete the bucket copy of a removed backup, if it has one
+pub async fn delete_remote_copy(pool: &DbPool, filename: &str, location: &str) {
+    if location == LOCATION_LOCAL {
+        return;
+    }
+    let Some(config) = load(pool).await else {
+        tracing::warn!("Backup {} has an offsite copy but offsite storage is no longer configured", filename);
+        return;
+    };
+    if let Err(e) = config.delete(filename).await {
+        tracing::warn!("Failed to delete offsite copy of backup {}: {}", filename, e);
+    }
+}
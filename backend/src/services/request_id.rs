@@ -0,0 +1,68 @@
+//! Request IDs tying together the log lines, the error response and the audit entries of one
+//! HTTP request or WebSocket session. The ID comes from the client's `X-Request-Id` when it
+//! looks sane (a reverse proxy may already have set one), is generated otherwise, and is
+//! echoed back in the same header.
+
+use std::future::Future;
+
+use axum::{
+    body::Body,
+    extract::Request,
+    http::{HeaderValue, Request as HttpRequest},
+    middleware::Next,
+    response::Response,
+};
+use tracing::Span;
+use uuid::Uuid;
+
+pub const HEADER: &str = "x-request-id";
+
+/// Longest ID accepted from a client
+const MAX_LEN: usize = 64;
+
+/// Request extension holding the ID
+#[derive(Debug, Clone)]
+pub struct RequestId(pub String);
+
+tokio::task_local! {
+    static CURRENT: String;
+}
+
+/// ID of the request being handled, `None` outside one (background jobs, tasks spawned by a
+/// handler)
+pub fn current() -> Option<String> {
+    CURRENT.try_with(Clone::clone).ok()
+}
+
+/// Run `future` as part of request `id`, for work moved to another task
+pub async fn scope<F: Future>(id: String, future: F) -> F::Output {
+    CURRENT.scope(id, future).await
+}
+
+fn from_client(value: &HeaderValue) -> Option<String> {
+    let id = value.to_str().ok()?;
+    let sane = !id.is_empty() && id.len() <= MAX_LEN && id.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'));
+    sane.then(|| id.to_string())
+}
+
+/// Middleware giving every request its ID, before anything else sees it
+pub async fn assign(mut req: Request, next: Next) -> Response {
+    let id = req.headers()
+        .get(HEADER)
+        .and_then(from_client)
+        .unwrap_or_else(|| Uuid::new_v4().simple().to_string());
+    req.extensions_mut().insert(RequestId(id.clone()));
+
+    let mut response = scope(id.clone(), next.run(req)).await;
+    if let Ok(value) = HeaderValue::from_str(&id) {
+        response.headers_mut().insert(HEADER, value);
+    }
+    response
+}
+
+/// Tracing span of a request, the ID shown on every line logged while handling it. The path
+/// leaves out the query, which carries the token of WebSocket and event stream requests.
+pub fn span(req: &HttpRequest<Body>) -> Span {
+    let id = req.extensions().get::<RequestId>().map(|r| r.0.as_str()).unwrap_or("-");
+    tracing::info_span!("request", id = %id, method = %req.method(), path = %req.uri().path())
+}
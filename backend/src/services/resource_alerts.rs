@@ -0,0 +1,90 @@
+//! Per-server resource alerts raised by the metrics loop when a threshold stays breached
+
+use std::time::{Duration, Instant};
+
+/// Consecutive metrics samples above a threshold before alerting (~30 s at the default 2 s interval)
+pub const SUSTAINED_SAMPLES: u32 = 15;
+
+/// Minimum delay between two alerts for the same resource of a server
+pub const COOLDOWN: Duration = Duration::from_secs(15 * 60);
+
+/// Thresholds taken from the server settings at launch; `None` disables the check
+#[derive(Debug, Clone, Default)]
+pub struct AlertThresholds {
+    /// Share of the host CPU (0-100)
+    pub cpu_percent: Option<f32>,
+    /// Share of the memory allotted to the server (heap plus JVM overhead)
+    pub ram_percent: Option<f32>,
+    pub disk_bytes: Option<u64>,
+    /// Reference for `ram_percent`
+    pub max_memory_bytes: u64,
+}
+
+impl AlertThresholds {
+    pub fn is_empty(&self) -> bool {
+        self.cpu_percent.is_none() && self.ram_percent.is_none() && self.disk_bytes.is_none()
+    }
+}
+
+#[derive(Default)]
+struct Tracker {
+    breaches: u32,
+    last_alert: Option<Instant>,
+}
+
+impl Tracker {
+    /// Count a sample and report whether an alert is due
+    fn observe(&mut self, breached: bool, required: u32) -> bool {
+        if !breached {
+            self.breaches = 0;
+            return false;
+        }
+        self.breaches += 1;
+        if self.breaches < required || self.last_alert.is_some_and(|at| at.elapsed() < COOLDOWN) {
+            return false;
+        }
+        self.last_alert = Some(Instant::now());
+        true
+    }
+}
+
+/// Breach counters and cooldowns of one server run
+#[derive(Default)]
+pub struct AlertState {
+    cpu: Tracker,
+    ram: Tracker,
+    disk: Tracker,
+}
+
+impl AlertState {
+    /// Feed one metrics sample (`disk` only when it was measured this tick) and get the alert messages to raise
+    pub fn observe(&mut self, thresholds: &AlertThresholds, cpu: f32, memory: u64, disk: Option<u64>) -> Vec<String> {
+        let mut alerts = Vec::new();
+
+        if let Some(limit) = thresholds.cpu_percent {
+            if self.cpu.observe(cpu > limit, SUSTAINED_SAMPLES) {
+                alerts.push(format!("CPU à {:.1}% (seuil {:.0}%)", cpu, limit));
+            }
+        }
+
+        if let Some(limit) = thresholds.ram_percent.filter(|_| thresholds.max_memory_bytes > 0) {
+            let percent = memory as f32 / thresholds.max_memory_bytes as f32 * 100.0;
+            if self.ram.observe(percent > limit, SUSTAINED_SAMPLES) {
+                alerts.push(format!("RAM à {:.1}% (seuil {:.0}%)", percent, limit));
+            }
+        }
+
+        // Disk size is only sampled every ~30 s and hardly fluctuates, one reading is enough
+        if let (Some(limit), Some(disk)) = (thresholds.disk_bytes, disk) {
+            if self.disk.observe(disk > limit, 1) {
+                alerts.push(format!(
+                    "Disque à {:.1} GB (seuil {:.1} GB)",
+                    disk as f64 / 1024.0 / 1024.0 / 1024.0,
+                    limit as f64 / 1024.0 / 1024.0 / 1024.0
+                ));
+            }
+        }
+
+        alerts
+    }
+}
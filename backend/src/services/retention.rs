@@ -0,0 +1,88 @@
+//! Nightly cleanup of the history tables so the SQLite file doesn't grow forever
+
+use chrono::{Duration, Local, NaiveTime, Utc};
+
+use crate::db::DbPool;
+
+/// Local time after which the nightly cleanup runs
+const CLEANUP_TIME: (u32, u32) = (3, 30);
+
+pub struct RetentionRule {
+    pub table: &'static str,
+    /// RFC 3339 timestamp column compared against the cutoff
+    pub column: &'static str,
+    /// Setting holding the retention in days (0 keeps everything)
+    pub setting: &'static str,
+    pub default_days: u32,
+}
+
+pub const RULES: &[RetentionRule] = &[
+    RetentionRule { table: "metrics_history", column: "recorded_at", setting: "metrics_retention_days", default_days: 30 },
+    RetentionRule { table: "server_events", column: "created_at", setting: "events_retention_days", default_days: 90 },
+    RetentionRule { table: "audit_log", column: "created_at", setting: "audit_retention_days", default_days: 365 },
+];
+
+/// Retention of a rule from the settings table, falling back to its default
+pub async fn retention_days(pool: &DbPool, rule: &RetentionRule) -> u32 {
+    sqlx::query_scalar::<_, String>("SELECT value FROM settings WHERE key = ?")
+        .bind(rule.setting)
+        .fetch_optional(pool)
+        .await
+        .ok()
+        .flatten()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(rule.default_days)
+}
+
+/// Run the cleanup once per day after `CLEANUP_TIME`, storing the per-table counts in `history_cleanup_report`
+pub async fn cleanup_if_due(pool: &DbPool) -> anyhow::Result<()> {
+    let now = Local::now();
+    let run_at = NaiveTime::from_hms_opt(CLEANUP_TIME.0, CLEANUP_TIME.1, 0).unwrap_or_default();
+    let today = now.format("%Y-%m-%d").to_string();
+
+    let last_run: Option<String> = sqlx::query_scalar("SELECT value FROM settings WHERE key = 'history_cleanup_last_run'")
+        .fetch_optional(pool)
+        .await?;
+    if now.time() < run_at || last_run.as_deref() == Some(today.as_str()) {
+        return Ok(());
+    }
+
+    // Mark first so a failing table doesn't retry every minute
+    upsert(pool, "history_cleanup_last_run", &today).await?;
+
+    let mut deleted = serde_json::Map::new();
+    for rule in RULES {
+        let days = retention_days(pool, rule).await;
+        if days == 0 {
+            continue;
+        }
+
+        let cutoff = (Utc::now() - Duration::days(days as i64)).to_rfc3339();
+        let result = sqlx::query(&format!("DELETE FROM {} WHERE {} < ?", rule.table, rule.column))
+            .bind(&cutoff)
+            .execute(pool)
+            .await?;
+        deleted.insert(rule.table.to_string(), result.rows_affected().into());
+    }
+
+    tracing::info!("History cleanup done: {}", serde_json::Value::Object(deleted.clone()));
+    let report = serde_json::json!({
+        "ran_at": Utc::now().to_rfc3339(),
+        "deleted": deleted,
+    });
+    upsert(pool, "history_cleanup_report", &report.to_string()).await?;
+
+    Ok(())
+}
+
+async fn upsert(pool: &DbPool, key: &str, value: &str) -> anyhow::Result<()> {
+    sqlx::query(
+        "INSERT INTO settings (key, value, updated_at) VALUES (?, ?, datetime('now'))
+         ON CONFLICT(key) DO UPDATE SET value = excluded.value, updated_at = excluded.updated_at"
+    )
+    .bind(key)
+    .bind(value)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
@@ -0,0 +1,120 @@
+//! Daily scheduled restarts with an in-game warning countdown
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use chrono::{Local, NaiveTime};
+use tokio::time;
+
+use crate::api::servers::models::ServerRow;
+use crate::db::DbPool;
+use crate::services::launch_config::{build_launch_config, prepare_config_json};
+use crate::services::events::PanelEvent;
+use crate::services::{broadcast_messages, history, ProcessManager};
+
+/// Seconds before the restart at which players are warned (descending)
+pub const WARNING_SECONDS: &[u64] = &[300, 60, 30, 10];
+
+/// Seconds between two schedule checks, must stay well below the first warning
+pub const CHECK_INTERVAL_SECS: u64 = 15;
+
+pub fn parse_schedule(value: &str) -> Option<NaiveTime> {
+    NaiveTime::parse_from_str(value.trim(), "%H:%M").ok()
+}
+
+/// Launch the countdown for every running server whose restart falls inside the warning window.
+/// `started` keeps the date of the last countdown launched per server so it only runs once a day.
+pub async fn check_due(
+    pool: &DbPool,
+    pm: &ProcessManager,
+    started: &mut HashMap<String, String>,
+) -> anyhow::Result<()> {
+    let servers: Vec<ServerRow> = sqlx::query_as(
+        "SELECT * FROM servers WHERE restart_schedule IS NOT NULL AND restart_schedule != ''"
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let now = Local::now().naive_local();
+    let lead = chrono::Duration::seconds(WARNING_SECONDS[0] as i64);
+
+    for server in servers {
+        let Some(at) = server.restart_schedule.as_deref().and_then(parse_schedule) else {
+            continue;
+        };
+
+        // Next occurrence, so a 00:02 restart is announced from 23:57 the day before
+        let mut restart_at = now.date().and_time(at);
+        if restart_at < now {
+            restart_at += chrono::Duration::days(1);
+        }
+        if restart_at - now > lead || !pm.is_running(&server.id).await {
+            continue;
+        }
+
+        let day = restart_at.date().to_string();
+        if started.get(&server.id) == Some(&day) {
+            continue;
+        }
+        started.insert(server.id.clone(), day);
+
+        let remaining = (restart_at - now).num_seconds().max(0) as u64;
+        let pool = pool.clone();
+        let pm = pm.clone();
+        tokio::spawn(async move {
+            run_countdown(&pool, &pm, server, remaining).await;
+        });
+    }
+
+    Ok(())
+}
+
+async fn run_countdown(pool: &DbPool, pm: &ProcessManager, server: ServerRow, mut remaining: u64) {
+    tracing::info!("Scheduled restart of server {} in {}s", server.name, remaining);
+    pm.events().publish(PanelEvent::RestartScheduled { server_id: server.id.clone(), in_secs: remaining });
+
+    let thresholds: Vec<u64> = WARNING_SECONDS.iter().copied().filter(|&s| s <= remaining).collect();
+    for warn_at in thresholds {
+        time::sleep(Duration::from_secs(remaining - warn_at)).await;
+        remaining = warn_at;
+
+        let delay = format_delay(&server.locale, warn_at);
+        let _ = broadcast_messages::broadcast(pm, &server.id, &server.locale, "restart_warning", &[("time", &delay)]).await;
+    }
+    time::sleep(Duration::from_secs(remaining)).await;
+
+    // Stopped by hand during the countdown: nothing to restart
+    if !pm.is_running(&server.id).await {
+        return;
+    }
+
+    // Reload the row so settings edited during the countdown are applied
+    let server: ServerRow = match sqlx::query_as("SELECT * FROM servers WHERE id = ?")
+        .bind(&server.id)
+        .fetch_optional(pool)
+        .await
+    {
+        Ok(Some(s)) => s,
+        Ok(None) => return,
+        Err(e) => {
+            tracing::warn!("Scheduled restart of server {} aborted: {}", server.name, e);
+            return;
+        }
+    };
+
+    let _ = broadcast_messages::broadcast(pm, &server.id, &server.locale, "restart_now", &[]).await;
+
+    prepare_config_json(&server).await;
+    match pm.restart(&server.id, &build_launch_config(&server)).await {
+        Ok(()) => history::record_event(pool, &server.id, history::EVENT_RESTART, Some("scheduled")).await,
+        Err(e) => tracing::warn!("Scheduled restart of server {} failed: {}", server.name, e),
+    }
+}
+
+fn format_delay(locale: &str, seconds: u64) -> String {
+    if seconds >= 60 && seconds.is_multiple_of(60) {
+        broadcast_messages::render(locale, "time_minutes", &[("count", &(seconds / 60).to_string())])
+    } else {
+        broadcast_messages::render(locale, "time_seconds", &[("count", &seconds.to_string())])
+    }
+}
@@ -1,11 +1,70 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
 use std::time::Duration;
 use tokio::time;
 use sysinfo::{System, RefreshKind, CpuRefreshKind, MemoryRefreshKind};
 use crate::db::DbPool;
 use crate::services::process_manager::ProcessManager;
-use crate::services::discord_service;
+use crate::services::{daily_summary, db_backup, discord_service, elevation, history, log_cleanup, network, retention, scheduled_restart};
+
+pub fn start(pool: DbPool, process_manager: ProcessManager, backups_dir: PathBuf) {
+    // History sampling + daily summary + nightly history cleanup
+    let history_pool = pool.clone();
+    let history_pm = process_manager.clone();
+    tokio::spawn(async move {
+        let mut interval = time::interval(Duration::from_secs(history::SAMPLE_INTERVAL_SECS));
+
+        loop {
+            interval.tick().await;
+
+            if let Err(e) = history::sample_metrics(&history_pool, &history_pm).await {
+                eprintln!("Error while sampling metrics history: {}", e);
+            }
+            if let Err(e) = daily_summary::send_if_due(&history_pool).await {
+                eprintln!("Error in daily summary: {}", e);
+            }
+            if let Err(e) = retention::cleanup_if_due(&history_pool).await {
+                eprintln!("Error in history cleanup: {}", e);
+            }
+            if let Err(e) = elevation::expire_due(&history_pool).await {
+                eprintln!("Error while expiring elevated access: {}", e);
+            }
+            if let Err(e) = db_backup::backup_if_due(&history_pool, &backups_dir).await {
+                eprintln!("Error in database backup: {}", e);
+            }
+        }
+    });
+
+    // Scheduled restarts
+    let restart_pool = pool.clone();
+    let restart_pm = process_manager.clone();
+    tokio::spawn(async move {
+        let mut interval = time::interval(Duration::from_secs(scheduled_restart::CHECK_INTERVAL_SECS));
+        let mut started = HashMap::new();
+
+        loop {
+            interval.tick().await;
+
+            if let Err(e) = scheduled_restart::check_due(&restart_pool, &restart_pm, &mut started).await {
+                eprintln!("Error in restart scheduler: {}", e);
+            }
+        }
+    });
+
+    // Old log files
+    let cleanup_pool = pool.clone();
+    tokio::spawn(async move {
+        let mut interval = time::interval(Duration::from_secs(log_cleanup::CHECK_INTERVAL_SECS));
+
+        loop {
+            interval.tick().await;
+
+            if let Err(e) = log_cleanup::prune_all(&cleanup_pool).await {
+                eprintln!("Error in log cleanup: {}", e);
+            }
+        }
+    });
 
-pub fn start(pool: DbPool, process_manager: ProcessManager) {
     tokio::spawn(async move {
         // Wait a bit for server start
         time::sleep(Duration::from_secs(5)).await;
@@ -30,6 +89,9 @@ pub fn start(pool: DbPool, process_manager: ProcessManager) {
     });
 }
 
+/// name, id, config, discord_emoji, network_role, parent_proxy_id
+type StatusRow = (String, String, Option<String>, Option<String>, Option<String>, Option<String>);
+
 async fn run_status_update(pool: &DbPool, sys: &mut System, pm: &ProcessManager) -> anyhow::Result<()> {
     // 1. Refresh System Stats
     sys.refresh_cpu_all();
@@ -87,19 +149,35 @@ async fn run_status_update(pool: &DbPool, sys: &mut System, pm: &ProcessManager)
     
     // 2. Get Servers Info
     // Fetch config as well to get MaxPlayers
-    let servers: Vec<(String, String, Option<String>)> = sqlx::query_as(
-        "SELECT name, id, config FROM servers ORDER BY name"
+    let servers: Vec<StatusRow> = sqlx::query_as(
+        "SELECT name, id, config, discord_emoji, network_role, parent_proxy_id FROM servers ORDER BY name"
     )
     .fetch_all(pool)
     .await?;
 
+    // Players across the lobby and game servers behind each proxy
+    let mut network_players: HashMap<String, usize> = HashMap::new();
+    for (_, id, _, _, _, parent) in &servers {
+        if let Some(parent) = parent.as_deref().filter(|p| !p.is_empty()) {
+            let players = pm.get_online_players(id).await.map(|p| p.len()).unwrap_or(0);
+            *network_players.entry(parent.to_string()).or_default() += players;
+        }
+    }
+    let servers = network::group_by_proxy(servers, |s| (s.1.as_str(), s.5.as_deref()));
+
     let mut total_servers = 0;
     let mut online_servers = 0;
     let mut server_lines = Vec::new();
 
-    for (name, id, config_str) in servers {
+    for ((name, id, config_str, emoji, role, _), is_member) in servers {
         total_servers += 1;
-        let is_running = pm.is_running(&id);
+        // Members are listed under their proxy
+        let indent = if is_member { "  ↳ " } else { "" };
+        let network_line = match network_players.get(&id) {
+            Some(players) if role.as_deref() == Some(network::ROLE_PROXY) => format!("\n╰ 🌐 Réseau : {} joueur(s)", players),
+            _ => String::new(),
+        };
+        let is_running = pm.is_running(&id).await;
         
         if is_running {
             online_servers += 1;
@@ -150,9 +228,9 @@ async fn run_status_update(pool: &DbPool, sys: &mut System, pm: &ProcessManager)
                  }
             }
 
-            server_lines.push(format!("🟢 **{}**\n╰ {}", name, details));
+            server_lines.push(format!("{}{} **{}**\n╰ {}{}", indent, discord_service::online_emoji(emoji.as_deref()), name, details, network_line));
         } else {
-            server_lines.push(format!("🔴 **{}**", name));
+            server_lines.push(format!("{}🔴 **{}**{}", indent, name, network_line));
         }
     }
     
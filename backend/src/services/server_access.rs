@@ -0,0 +1,173 @@
+//! Which servers a user may manage, and how. Admins have full access to every server and
+//! viewers read-only access to all of them; other users reach the ones they own, hold an active
+//! break-glass grant on, or have in their `allocated_servers` list. Permissions granted on a server, one by one or through a role
+//! template, narrow that user to them: the server shows up, but only the granted actions are open.
+
+use std::collections::{HashMap, HashSet};
+
+use chrono::Utc;
+
+use crate::api::auth::AuthUser;
+use crate::db::DbPool;
+use crate::error::AppError;
+use crate::services::elevation;
+
+pub const ROLE_ADMIN: &str = "admin";
+pub const ROLE_USER: &str = "user";
+/// Sees every server's status, metrics, console and backups but changes nothing, see
+/// `access::block_read_only_writes`
+pub const ROLE_VIEWER: &str = "viewer";
+pub const ROLES: &[&str] = &[ROLE_ADMIN, ROLE_USER, ROLE_VIEWER];
+
+/// Any access: the server's overview, status and history
+pub const VIEW: &str = "view";
+/// Settings, reinstall, deletion, grants: never granted, full access only
+pub const MANAGE: &str = "manage";
+
+pub const CONSOLE_VIEW: &str = "console.view";
+pub const CONSOLE_COMMAND: &str = "console.command";
+pub const FILES_EDIT: &str = "files.edit";
+pub const BACKUPS_MANAGE: &str = "backups.manage";
+/// Listing backups and reading their metadata, not the archives. Implied by `backups.manage`
+/// and held by viewers, never granted on its own.
+pub const BACKUPS_VIEW: &str = "backups.view";
+/// Start, stop, restart, kill, suspend and resume
+pub const SERVER_CONTROL: &str = "server.control";
+
+/// Permissions that can be granted per server and user
+pub const PERMISSIONS: &[&str] = &[CONSOLE_VIEW, CONSOLE_COMMAND, FILES_EDIT, BACKUPS_MANAGE, SERVER_CONTROL];
+
+/// Sorted, deduplicated `permissions`, or the first unknown one
+pub fn normalize_permissions(mut permissions: Vec<String>) -> Result<Vec<String>, String> {
+    permissions.sort();
+    permissions.dedup();
+    match permissions.iter().find(|p| !PERMISSIONS.contains(&p.as_str())) {
+        Some(unknown) => Err(unknown.clone()),
+        None => Ok(permissions),
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum Access {
+    Full,
+    Granted(Vec<String>),
+}
+
+impl Access {
+    pub fn allows(&self, permission: &str) -> bool {
+        match self {
+            Access::Full => true,
+            Access::Granted(granted) => {
+                let implied = |p: &String| permission == BACKUPS_VIEW && p == BACKUPS_MANAGE;
+                permission == VIEW || granted.iter().any(|p| p == permission || implied(p))
+            }
+        }
+    }
+}
+
+/// The caller's access to each server it reaches, `None` for admins who reach all of them
+pub async fn accesses(pool: &DbPool, auth: &AuthUser) -> Result<Option<HashMap<String, Access>>, sqlx::Error> {
+    if auth.role == ROLE_ADMIN {
+        return Ok(None);
+    }
+    if auth.role == ROLE_VIEWER {
+        // Backups are listed, not opened: archives hold the server's config and password
+        let servers: Vec<String> = sqlx::query_scalar("SELECT id FROM servers").fetch_all(pool).await?;
+        let read_only = Access::Granted(vec![CONSOLE_VIEW.to_string(), BACKUPS_VIEW.to_string()]);
+        return Ok(Some(servers.into_iter().map(|id| (id, read_only.clone())).collect()));
+    }
+    let mut accesses = HashMap::new();
+
+    let allocated: Option<Option<String>> = sqlx::query_scalar("SELECT allocated_servers FROM users WHERE id = ?")
+        .bind(&auth.id)
+        .fetch_optional(pool)
+        .await?;
+    let allocated: Vec<String> = allocated
+        .flatten()
+        .and_then(|s| serde_json::from_str(&s).ok())
+        .unwrap_or_default();
+    for server_id in allocated {
+        accesses.insert(server_id, Access::Full);
+    }
+
+    // Grants override the allocation of their server
+    let grants: Vec<(String, String)> = sqlx::query_as("SELECT server_id, permission FROM server_permissions WHERE user_id = ?")
+        .bind(&auth.id)
+        .fetch_all(pool)
+        .await?;
+    let mut granted: HashMap<String, Vec<String>> = HashMap::new();
+    for (server_id, permission) in grants {
+        granted.entry(server_id).or_default().push(permission);
+    }
+    // A template assigned on a server grants what it currently lists
+    let templated: Vec<(String, String)> = sqlx::query_as(
+        "SELECT a.server_id, t.permissions FROM server_role_assignments a
+         JOIN role_templates t ON t.id = a.template_id
+         WHERE a.user_id = ?"
+    )
+    .bind(&auth.id)
+    .fetch_all(pool)
+    .await?;
+    for (server_id, permissions) in templated {
+        let permissions: Vec<String> = serde_json::from_str(&permissions).unwrap_or_default();
+        granted.entry(server_id).or_default().extend(permissions);
+    }
+    for (server_id, permissions) in granted {
+        accesses.insert(server_id, Access::Granted(permissions));
+    }
+
+    // Owners and elevated users have full access whatever was granted
+    let owned: Vec<String> = sqlx::query_scalar("SELECT id FROM servers WHERE owner_id = ?")
+        .bind(&auth.id)
+        .fetch_all(pool)
+        .await?;
+    let mut full: HashSet<String> = owned.into_iter().collect();
+
+    // Break-glass grants are bound to the login session, as in `elevation::has_server_admin`
+    if auth.api_token_id.is_none() {
+        let elevated: Vec<String> = sqlx::query_scalar(
+            "SELECT server_id FROM elevation_grants WHERE user_id = ? AND status = ? AND expires_at > ?"
+        )
+        .bind(&auth.id)
+        .bind(elevation::ELEVATION_ACTIVE)
+        .bind(Utc::now().to_rfc3339())
+        .fetch_all(pool)
+        .await?;
+        full.extend(elevated);
+    }
+    for server_id in full {
+        accesses.insert(server_id, Access::Full);
+    }
+    Ok(Some(accesses))
+}
+
+/// Servers on which the caller holds `permission`, `None` when it holds it everywhere
+pub async fn allowed_servers(pool: &DbPool, auth: &AuthUser, permission: &str) -> Result<Option<HashSet<String>>, sqlx::Error> {
+    Ok(accesses(pool, auth).await?.map(|accesses| {
+        accesses
+            .into_iter()
+            .filter(|(_, access)| access.allows(permission))
+            .map(|(server_id, _)| server_id)
+            .collect()
+    }))
+}
+
+pub async fn has_permission(pool: &DbPool, auth: &AuthUser, server_id: &str, permission: &str) -> Result<bool, sqlx::Error> {
+    let Some(mut accesses) = accesses(pool, auth).await? else {
+        return Ok(true);
+    };
+    Ok(accesses.remove(server_id).is_some_and(|access| access.allows(permission)))
+}
+
+/// Reject the caller unless it holds `permission` on `server_id`
+pub async fn require_permission(pool: &DbPool, auth: &AuthUser, server_id: &str, permission: &str) -> Result<(), AppError> {
+    let access = match accesses(pool, auth).await? {
+        None => return Ok(()),
+        Some(mut accesses) => accesses.remove(server_id),
+    };
+    match access {
+        Some(access) if access.allows(permission) => Ok(()),
+        Some(_) => Err(AppError::Forbidden("servers.permission_denied".into())),
+        None => Err(AppError::Forbidden("servers.access_denied".into())),
+    }
+}
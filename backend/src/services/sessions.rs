@@ -0,0 +1,163 @@
+//! Login sessions behind the session JWTs: each token carries the id (`sid`) of a row recording
+//! the device and address it was issued to, so its user or an admin can list them and log one
+//! out remotely. Revoked ids are kept in memory so checking a token needs no query.
+
+use std::collections::{HashMap, HashSet};
+use std::net::SocketAddr;
+use std::sync::{Mutex, RwLock};
+use std::time::{Duration, Instant};
+
+use axum::extract::ConnectInfo;
+use axum::http::{header, HeaderMap};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sqlx::FromRow;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::db::DbPool;
+
+/// `last_seen_at` is written at most this often per session
+const TOUCH_INTERVAL: Duration = Duration::from_secs(60);
+
+lazy_static::lazy_static! {
+    static ref REVOKED: RwLock<HashSet<String>> = RwLock::new(HashSet::new());
+    /// Session id -> last `last_seen_at` write
+    static ref TOUCHED: Mutex<HashMap<String, Instant>> = Mutex::new(HashMap::new());
+}
+
+#[derive(Debug, Serialize, FromRow, ToSchema)]
+pub struct Session {
+    pub id: String,
+    pub user_id: String,
+    pub ip: Option<String>,
+    pub user_agent: Option<String>,
+    pub created_at: String,
+    pub last_seen_at: String,
+    pub expires_at: String,
+}
+
+/// Where a login comes from
+#[derive(Debug, Default, Clone)]
+pub struct Client {
+    pub ip: Option<String>,
+    pub user_agent: Option<String>,
+}
+
+impl Client {
+    pub fn from_request(headers: &HeaderMap, connect_info: Option<&ConnectInfo<SocketAddr>>) -> Self {
+        Self {
+            ip: connect_info.map(|ConnectInfo(addr)| addr.ip().to_string()),
+            user_agent: headers.get(header::USER_AGENT)
+                .and_then(|v| v.to_str().ok())
+                .map(|ua| ua.chars().take(300).collect()),
+        }
+    }
+}
+
+/// Record a new session, returning its id
+pub async fn open(pool: &DbPool, user_id: &str, client: &Client, expires_at: DateTime<Utc>) -> Result<String, sqlx::Error> {
+    let id = Uuid::new_v4().to_string();
+    let now = Utc::now().to_rfc3339();
+    sqlx::query(
+        "INSERT INTO sessions (id, user_id, ip, user_agent, created_at, last_seen_at, expires_at) VALUES (?, ?, ?, ?, ?, ?, ?)"
+    )
+    .bind(&id)
+    .bind(user_id)
+    .bind(&client.ip)
+    .bind(&client.user_agent)
+    .bind(&now)
+    .bind(&now)
+    .bind(expires_at.to_rfc3339())
+    .execute(pool)
+    .await?;
+    Ok(id)
+}
+
+pub fn is_revoked(id: &str) -> bool {
+    REVOKED.read().is_ok_and(|revoked| revoked.contains(id))
+}
+
+/// Forget expired sessions and cache the revoked ones, called once at startup
+pub async fn load(pool: &DbPool) -> Result<(), sqlx::Error> {
+    sqlx::query("DELETE FROM sessions WHERE expires_at <= ?")
+        .bind(Utc::now().to_rfc3339())
+        .execute(pool)
+        .await?;
+    let revoked: Vec<String> = sqlx::query_scalar("SELECT id FROM sessions WHERE revoked_at IS NOT NULL")
+        .fetch_all(pool)
+        .await?;
+    if let Ok(mut cached) = REVOKED.write() {
+        cached.extend(revoked);
+    }
+    Ok(())
+}
+
+/// Note that the session was just used
+pub async fn touch(pool: &DbPool, id: &str) {
+    let now = Instant::now();
+    {
+        let Ok(mut touched) = TOUCHED.lock() else {
+            return;
+        };
+        if touched.get(id).is_some_and(|last| now.duration_since(*last) < TOUCH_INTERVAL) {
+            return;
+        }
+        touched.retain(|_, last| now.duration_since(*last) < TOUCH_INTERVAL);
+        touched.insert(id.to_string(), now);
+    }
+    let result = sqlx::query("UPDATE sessions SET last_seen_at = ? WHERE id = ?")
+        .bind(Utc::now().to_rfc3339())
+        .bind(id)
+        .execute(pool)
+        .await;
+    if let Err(e) = result {
+        tracing::warn!("Failed to update session {}: {}", id, e);
+    }
+}
+
+pub async fn get(pool: &DbPool, id: &str) -> Result<Option<Session>, sqlx::Error> {
+    sqlx::query_as(
+        "SELECT id, user_id, ip, user_agent, created_at, last_seen_at, expires_at FROM sessions
+         WHERE id = ? AND revoked_at IS NULL AND expires_at > ?"
+    )
+    .bind(id)
+    .bind(Utc::now().to_rfc3339())
+    .fetch_optional(pool)
+    .await
+}
+
+/// Sessions of a user that can still be used, most recently seen first
+pub async fn active(pool: &DbPool, user_id: &str) -> Result<Vec<Session>, sqlx::Error> {
+    sqlx::query_as(
+        "SELECT id, user_id, ip, user_agent, created_at, last_seen_at, expires_at FROM sessions
+         WHERE user_id = ? AND revoked_at IS NULL AND expires_at > ?
+         ORDER BY last_seen_at DESC"
+    )
+    .bind(user_id)
+    .bind(Utc::now().to_rfc3339())
+    .fetch_all(pool)
+    .await
+}
+
+/// Log out the given sessions
+pub async fn revoke(pool: &DbPool, ids: &[String]) -> Result<(), sqlx::Error> {
+    let now = Utc::now().to_rfc3339();
+    for id in ids {
+        sqlx::query("UPDATE sessions SET revoked_at = ? WHERE id = ? AND revoked_at IS NULL")
+            .bind(&now)
+            .bind(id)
+            .execute(pool)
+            .await?;
+    }
+    if let Ok(mut revoked) = REVOKED.write() {
+        revoked.extend(ids.iter().cloned());
+    }
+    Ok(())
+}
+
+/// Log a user out everywhere
+pub async fn revoke_all(pool: &DbPool, user_id: &str) -> Result<(), sqlx::Error> {
+    let open: Vec<String> = active(pool, user_id).await?.into_iter().map(|s| s.id).collect();
+    revoke(pool, &open).await
+}
@@ -0,0 +1,129 @@
+//! Built-in stand-in for the Hytale server, run by `draveur --simulate` for `dummy` servers.
+//! Prints Hytale-like logs, makes fake players come and go, and can crash on demand,
+//! so the panel can be developed without downloading or authenticating a real server.
+
+use std::io::{BufRead, Write};
+use std::time::Duration;
+
+/// Command-line flag switching the binary into simulator mode
+pub const SIMULATE_FLAG: &str = "--simulate";
+
+const FAKE_PLAYERS: &[&str] = &["Kweebec", "Trork", "Feran", "Outlander", "Scarak", "Klops"];
+
+#[derive(Debug)]
+pub struct SimulatorOptions {
+    pub port: u16,
+    /// Seconds between two fake player joins/leaves, 0 to disable
+    pub player_interval_secs: u64,
+    /// Exit with an error after this many seconds
+    pub crash_after_secs: Option<u64>,
+    /// Exit with an error before reporting ready
+    pub crash_on_start: bool,
+}
+
+impl SimulatorOptions {
+    /// Parse the flags passed after `--simulate` (see `ProcessManager::simulator_command`)
+    pub fn from_args(args: &[String]) -> Self {
+        let mut options = Self {
+            port: 5520,
+            player_interval_secs: 20,
+            crash_after_secs: None,
+            crash_on_start: false,
+        };
+
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--port" => options.port = args.next().and_then(|v| v.parse().ok()).unwrap_or(options.port),
+                "--player-interval" => {
+                    options.player_interval_secs = args.next().and_then(|v| v.parse().ok()).unwrap_or(options.player_interval_secs)
+                }
+                "--crash-after" => options.crash_after_secs = args.next().and_then(|v| v.parse().ok()),
+                "--crash-on-start" => options.crash_on_start = true,
+                _ => {}
+            }
+        }
+        options
+    }
+}
+
+fn log(source: &str, message: &str) {
+    let now = chrono::Local::now().format("%Y/%m/%d %H:%M:%S");
+    let mut stdout = std::io::stdout().lock();
+    let _ = writeln!(stdout, "[{}   INFO] [{}]: {}", now, source, message);
+    let _ = stdout.flush();
+}
+
+/// Cheap xorshift so the simulator doesn't need a RNG dependency
+fn next_random(state: &mut u64) -> u64 {
+    *state ^= *state << 13;
+    *state ^= *state >> 7;
+    *state ^= *state << 17;
+    *state
+}
+
+/// Run the simulator until `/shutdown`, `/crash` or the configured crash
+pub fn run(options: SimulatorOptions) -> anyhow::Result<()> {
+    log("HytaleServer", "Starting simulated Hytale server (dummy)");
+    log("HytaleServer", &format!("Loading universe on port {}", options.port));
+    std::thread::sleep(Duration::from_millis(500));
+
+    if options.crash_on_start {
+        eprintln!("Exception in thread \"main\" java.lang.IllegalStateException: simulated startup crash");
+        std::process::exit(1);
+    }
+
+    log("World|default", "Generating spawn chunks");
+    std::thread::sleep(Duration::from_millis(500));
+    println!("[HytaleServer] Universe ready!");
+    let _ = std::io::stdout().flush();
+
+    if let Some(secs) = options.crash_after_secs {
+        std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_secs(secs));
+            eprintln!("Exception in thread \"WorldThread\" java.lang.OutOfMemoryError: simulated crash");
+            std::process::exit(1);
+        });
+    }
+
+    if options.player_interval_secs > 0 {
+        let interval = Duration::from_secs(options.player_interval_secs);
+        std::thread::spawn(move || {
+            let mut seed = std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .map(|d| d.as_nanos() as u64)
+                .unwrap_or(0x2545F4914F6CDD1D)
+                | 1;
+            let mut online = vec![false; FAKE_PLAYERS.len()];
+            loop {
+                std::thread::sleep(interval);
+                let index = (next_random(&mut seed) % FAKE_PLAYERS.len() as u64) as usize;
+                let action = if online[index] { "left" } else { "joined" };
+                online[index] = !online[index];
+                log("World|default", &format!("{} {} the game", FAKE_PLAYERS[index], action));
+            }
+        });
+    }
+
+    for line in std::io::stdin().lock().lines() {
+        let line = line?;
+        let line = line.trim();
+        match line.split_once(' ').unwrap_or((line, "")) {
+            ("/shutdown" | "/stop", _) => {
+                log("HytaleServer", "Saving universe and shutting down");
+                return Ok(());
+            }
+            ("/crash", _) => {
+                eprintln!("Exception in thread \"main\" java.lang.RuntimeException: crash requested from console");
+                std::process::exit(1);
+            }
+            ("/join", name) if !name.is_empty() => log("World|default", &format!("{} joined the game", name)),
+            ("/leave", name) if !name.is_empty() => log("World|default", &format!("{} left the game", name)),
+            ("/say", message) => log("Chat", message),
+            ("", _) => {}
+            (command, _) => log("CommandManager", &format!("Unknown command: {}", command)),
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,122 @@
+//! Automatic world snapshots taken before risky operations (reinstall, install from a bundle,
+//! mod changes, config rewrites). They are regular backups of kind `snapshot`, so the usual
+//! restore endpoint rolls them back.
+
+use std::path::Path;
+
+use chrono::Utc;
+use uuid::Uuid;
+
+use crate::db::DbPool;
+use crate::error::AppError;
+use crate::services::backup_service;
+
+/// Kind of the backups taken by hand or by the schedule
+pub const KIND_MANUAL: &str = "manual";
+pub const KIND_SNAPSHOT: &str = "snapshot";
+
+/// World data and configuration, without binaries, assets or logs
+const SNAPSHOT_ENTRIES: &[&str] = &[
+    "universe",
+    "mods",
+    "config.json",
+    "permissions.json",
+    "whitelist.json",
+    "bans.json",
+];
+
+/// Snapshots kept per server, the oldest are deleted beyond that
+const SNAPSHOTS_KEPT: i64 = 5;
+
+pub const OP_REINSTALL: &str = "reinstall";
+pub const OP_BUNDLE_INSTALL: &str = "bundle_install";
+pub const OP_CONFIG_CHANGE: &str = "config_change";
+pub const OP_MOD_CHANGE: &str = "mod_change";
+
+/// Whether `auto_snapshots` is on (default)
+pub async fn enabled(pool: &DbPool) -> bool {
+    let value: Option<String> = sqlx::query_scalar("SELECT value FROM settings WHERE key = 'auto_snapshots'")
+        .fetch_optional(pool)
+        .await
+        .ok()
+        .flatten();
+    value.as_deref() != Some("false")
+}
+
+/// Snapshot the world of a server before `operation`. Returns the backup id, or `None` when
+/// automatic snapshots are disabled. A failure aborts the operation: it would have no rollback point.
+pub async fn snapshot_before(pool: &DbPool, backups_dir: &Path, server_id: &str, working_dir: &str, operation: &str) -> Result<Option<String>, AppError> {
+    // Nothing installed yet, nothing to roll back to
+    if !enabled(pool).await || !Path::new(working_dir).exists() {
+        return Ok(None);
+    }
+
+    // The backup id doubles as the id of the operation, returned to the caller
+    let id = Uuid::new_v4().to_string();
+    let now = Utc::now();
+    let compression = backup_service::configured_compression(pool).await;
+    let filename = format!("snapshot_{}_{}_{}.{}", server_id, operation, now.format("%Y%m%d_%H%M%S"), compression.codec.extension());
+    tokio::fs::create_dir_all(backups_dir)
+        .await
+        .map_err(|e| AppError::Internal(format!("Failed to create backups dir: {}", e)))?;
+    let backup_path = backups_dir.join(&filename);
+
+    let source = working_dir.to_string();
+    let (size_bytes, sha256) = tokio::task::spawn_blocking(move || {
+        let path = backup_path.to_string_lossy();
+        let size = backup_service::create_partial_archive(&source, SNAPSHOT_ENTRIES, &path, compression)?;
+        Ok::<_, backup_service::BackupError>((size, backup_service::checksum(&path)?))
+    })
+    .await
+    .map_err(|e| AppError::Internal(format!("Snapshot task failed: {}", e)))?
+    .map_err(|e| AppError::Internal(format!("Snapshot before {} failed: {:?}", operation, e)))?;
+
+    sqlx::query(
+        "INSERT INTO backups (id, server_id, filename, size_bytes, created_at, kind, label, compression, sha256) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)",
+    )
+    .bind(&id)
+    .bind(server_id)
+    .bind(&filename)
+    .bind(size_bytes as i64)
+    .bind(now.to_rfc3339())
+    .bind(KIND_SNAPSHOT)
+    .bind(operation)
+    .bind(compression.codec.as_str())
+    .bind(&sha256)
+    .execute(pool)
+    .await?;
+
+    tracing::info!("Snapshot {} of server {} taken before {}", id, server_id, operation);
+    prune(pool, backups_dir, server_id).await;
+    Ok(Some(id))
+}
+
+/// Delete the snapshots of a server beyond `SNAPSHOTS_KEPT`
+async fn prune(pool: &DbPool, backups_dir: &Path, server_id: &str) {
+    let old: Vec<(String, String)> = sqlx::query_as(
+        "SELECT id, filename FROM backups WHERE server_id = ? AND kind = ? ORDER BY created_at DESC LIMIT -1 OFFSET ?"
+    )
+    .bind(server_id)
+    .bind(KIND_SNAPSHOT)
+    .bind(SNAPSHOTS_KEPT)
+    .fetch_all(pool)
+    .await
+    .unwrap_or_default();
+
+    for (id, filename) in old {
+        let _ = tokio::fs::remove_file(backups_dir.join(&filename)).await;
+        let _ = sqlx::query("DELETE FROM backups WHERE id = ?").bind(&id).execute(pool).await;
+    }
+}
+
+/// File API paths whose change counts as a mod install/removal
+pub fn is_mod_path(path: &str) -> bool {
+    let path = path.trim_start_matches("./");
+    path == "mods" || path.starts_with("mods/")
+}
+
+/// File API paths holding structured server configuration
+pub fn is_config_path(path: &str) -> bool {
+    let path = path.trim_start_matches("./");
+    !path.contains('/') && path.ends_with(".json")
+}
@@ -0,0 +1,223 @@
+//! Support bundle: a `.tar.gz` of diagnostics to attach to bug reports. It holds the panel's
+//! recent logs, its configuration with secrets redacted, the database schema, server metadata
+//! and recent failures. Nothing that identifies players or grants access goes in.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use chrono::Utc;
+use serde::Serialize;
+
+use crate::config::Settings;
+use crate::db::DbPool;
+use crate::services::{history, ProcessManager};
+use crate::utils::log_buffer;
+
+pub const STATUS_PENDING: &str = "pending";
+pub const STATUS_READY: &str = "ready";
+pub const STATUS_FAILED: &str = "failed";
+
+const BUNDLES_DIR: &str = "data/support";
+/// Bundles kept on disk, the oldest are deleted first
+const KEEP_BUNDLES: usize = 5;
+/// Failures of the last days included
+const FAILURE_DAYS: i64 = 7;
+
+const REDACTED: &str = "[redacted]";
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Bundle {
+    pub id: String,
+    pub status: String,
+    pub created_at: String,
+    pub size_bytes: Option<u64>,
+    pub error: Option<String>,
+    #[serde(skip)]
+    pub path: PathBuf,
+}
+
+lazy_static::lazy_static! {
+    /// Bundles generated since the panel started
+    static ref BUNDLES: Mutex<HashMap<String, Bundle>> = Mutex::new(HashMap::new());
+}
+
+pub fn get(id: &str) -> Option<Bundle> {
+    BUNDLES.lock().ok()?.get(id).cloned()
+}
+
+fn set(bundle: Bundle) {
+    if let Ok(mut bundles) = BUNDLES.lock() {
+        bundles.insert(bundle.id.clone(), bundle);
+    }
+}
+
+/// Register a bundle and build it in the background
+pub fn start(pool: DbPool, pm: ProcessManager, settings: Settings) -> Bundle {
+    let id = uuid::Uuid::new_v4().to_string();
+    let now = Utc::now();
+    let bundle = Bundle {
+        path: Path::new(BUNDLES_DIR).join(format!("support_{}_{}.tar.gz", now.format("%Y%m%d_%H%M%S"), &id[..8])),
+        id,
+        status: STATUS_PENDING.to_string(),
+        created_at: now.to_rfc3339(),
+        size_bytes: None,
+        error: None,
+    };
+    set(bundle.clone());
+
+    let mut result = bundle.clone();
+    tokio::spawn(async move {
+        match build(&pool, &pm, &settings, &result.path).await {
+            Ok(size) => {
+                result.status = STATUS_READY.to_string();
+                result.size_bytes = Some(size);
+                tracing::info!("Support bundle {} ready ({} bytes)", result.id, size);
+            }
+            Err(e) => {
+                result.status = STATUS_FAILED.to_string();
+                result.error = Some(e.to_string());
+                tracing::error!("Support bundle {} failed: {}", result.id, e);
+            }
+        }
+        set(result);
+        prune_old();
+    });
+
+    bundle
+}
+
+/// Whether a settings key holds a credential (webhook URLs embed their token)
+fn is_secret_key(key: &str) -> bool {
+    let key = key.to_ascii_lowercase();
+    ["secret", "token", "password", "webhook", "key", "on_call"].iter().any(|s| key.contains(s))
+}
+
+/// The server fields of `servers.json`, in query order
+type ServerCsvRow = (String, String, String, String, String, String, i64, String, i64, Option<i64>, Option<String>, Option<String>);
+
+async fn build(pool: &DbPool, pm: &ProcessManager, settings: &Settings, path: &Path) -> anyhow::Result<u64> {
+    let mut files: Vec<(&str, Vec<u8>)> = Vec::new();
+
+    files.push(("panel.log", log_buffer::recent().join("\n").into_bytes()));
+
+    let stored: Vec<(String, String)> = sqlx::query_as("SELECT key, value FROM settings ORDER BY key")
+        .fetch_all(pool)
+        .await?;
+    let stored: serde_json::Map<String, serde_json::Value> = stored
+        .into_iter()
+        .map(|(key, value)| {
+            let value = if is_secret_key(&key) && !value.is_empty() { REDACTED.to_string() } else { value };
+            (key, serde_json::Value::String(value))
+        })
+        .collect();
+    files.push(("config.json", serde_json::to_vec_pretty(&serde_json::json!({
+        "version": env!("CARGO_PKG_VERSION"),
+        "os": std::env::consts::OS,
+        "arch": std::env::consts::ARCH,
+        "generated_at": Utc::now().to_rfc3339(),
+        "environment": {
+            "host": settings.host,
+            "port": settings.port,
+            "database_url": settings.database_url,
+            "uploads_dir": settings.uploads_dir,
+            "dev_mode": settings.dev_mode,
+            "jwt_secret_set": std::env::var("JWT_SECRET").is_ok(),
+        },
+        "settings": stored,
+    }))?));
+
+    // No migration counter: the version above plus the live schema identify the database
+    let schema: Vec<(String,)> = sqlx::query_as("SELECT sql FROM sqlite_master WHERE sql IS NOT NULL ORDER BY type, name")
+        .fetch_all(pool)
+        .await?;
+    files.push(("schema.sql", schema.into_iter().map(|(sql,)| format!("{};\n", sql)).collect::<String>().into_bytes()));
+
+    let servers: Vec<ServerCsvRow> = sqlx::query_as(
+        "SELECT id, name, game_type, launch_mode, runtime, install_state, port, bind_address, auto_start,
+         last_exit_code, last_exit_at, network_role FROM servers ORDER BY name"
+    )
+    .fetch_all(pool)
+    .await?;
+    let mut server_list = Vec::with_capacity(servers.len());
+    for (id, name, game_type, launch_mode, runtime, install_state, port, bind_address, auto_start, last_exit_code, last_exit_at, network_role) in servers {
+        server_list.push(serde_json::json!({
+            "running": pm.is_running(&id).await,
+            "id": id,
+            "name": name,
+            "game_type": game_type,
+            "launch_mode": launch_mode,
+            "runtime": runtime,
+            "install_state": install_state,
+            "port": port,
+            "bind_address": bind_address,
+            "auto_start": auto_start != 0,
+            "last_exit_code": last_exit_code,
+            "last_exit_at": last_exit_at,
+            "network_role": network_role,
+        }));
+    }
+    files.push(("servers.json", serde_json::to_vec_pretty(&server_list)?));
+
+    // There's no job queue: failures are the crashes and hangs recorded in server history
+    let since = (Utc::now() - chrono::Duration::days(FAILURE_DAYS)).to_rfc3339();
+    let failures: Vec<(String, String, Option<String>, String)> = sqlx::query_as(
+        "SELECT server_id, event_type, detail, created_at FROM server_events
+         WHERE event_type IN (?, ?) AND created_at >= ? ORDER BY created_at DESC LIMIT 200"
+    )
+    .bind(history::EVENT_CRASH)
+    .bind(history::EVENT_UNRESPONSIVE)
+    .bind(&since)
+    .fetch_all(pool)
+    .await?;
+    let failures: Vec<serde_json::Value> = failures
+        .into_iter()
+        .map(|(server_id, event_type, detail, created_at)| serde_json::json!({
+            "server_id": server_id,
+            "event": event_type,
+            "detail": detail,
+            "at": created_at,
+        }))
+        .collect();
+    files.push(("failures.json", serde_json::to_vec_pretty(&failures)?));
+
+    let path = path.to_path_buf();
+    let size = tokio::task::spawn_blocking(move || write_archive(&path, &files)).await??;
+    Ok(size)
+}
+
+fn write_archive(path: &Path, files: &[(&str, Vec<u8>)]) -> std::io::Result<u64> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let encoder = flate2::write::GzEncoder::new(std::fs::File::create(path)?, flate2::Compression::default());
+    let mut tar = tar::Builder::new(encoder);
+    for (name, data) in files {
+        let mut header = tar::Header::new_gnu();
+        header.set_size(data.len() as u64);
+        header.set_mode(0o644);
+        header.set_mtime(Utc::now().timestamp().max(0) as u64);
+        header.set_cksum();
+        tar.append_data(&mut header, name, data.as_slice())?;
+    }
+    tar.into_inner()?.finish()?;
+    Ok(std::fs::metadata(path)?.len())
+}
+
+/// Delete all but the newest bundles, on disk and in the registry
+fn prune_old() {
+    let Ok(mut bundles) = BUNDLES.lock() else {
+        return;
+    };
+    let mut finished: Vec<(String, String)> = bundles
+        .values()
+        .filter(|b| b.status != STATUS_PENDING)
+        .map(|b| (b.created_at.clone(), b.id.clone()))
+        .collect();
+    finished.sort_unstable_by(|a, b| b.cmp(a));
+    for (_, id) in finished.into_iter().skip(KEEP_BUNDLES) {
+        if let Some(bundle) = bundles.remove(&id) {
+            let _ = std::fs::remove_file(&bundle.path);
+        }
+    }
+}
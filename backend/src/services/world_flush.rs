@@ -0,0 +1,108 @@
+//! Getting a running server's world onto disk before it is archived: the server's save command
+//! is sent and its confirmation awaited in the console, and autosave can be paused while the
+//! archive is written so files don't change under it. Commands are per server, none by default.
+
+use std::time::Duration;
+
+use regex::Regex;
+use tokio::sync::broadcast::error::RecvError;
+
+use crate::db::DbPool;
+use crate::services::ProcessManager;
+
+/// Longest wait for the save confirmation before archiving anyway
+const SAVE_TIMEOUT: Duration = Duration::from_secs(60);
+/// Wait after the save command when no confirmation pattern is set
+const UNCONFIRMED_SAVE_WAIT: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Default)]
+pub struct FlushCommands {
+    pub save_command: Option<String>,
+    /// Regex matched against console lines, e.g. `Saved the world`
+    pub save_confirm: Option<Regex>,
+    pub autosave_off_command: Option<String>,
+    pub autosave_on_command: Option<String>,
+}
+
+/// save command, save confirmation, autosave off and on commands
+type FlushRow = (Option<String>, Option<String>, Option<String>, Option<String>);
+
+impl FlushCommands {
+    pub async fn load(pool: &DbPool, server_id: &str) -> Self {
+        let row: Option<FlushRow> = sqlx::query_as(
+            "SELECT backup_save_command, backup_save_confirm, backup_autosave_off_command, backup_autosave_on_command
+             FROM servers WHERE id = ?"
+        )
+        .bind(server_id)
+        .fetch_optional(pool)
+        .await
+        .ok()
+        .flatten();
+        let Some((save, confirm, off, on)) = row else {
+            return Self::default();
+        };
+
+        let set = |v: Option<String>| v.map(|v| v.trim().to_string()).filter(|v| !v.is_empty());
+        Self {
+            save_command: set(save),
+            save_confirm: set(confirm).and_then(|p| Regex::new(&p).ok()),
+            autosave_off_command: set(off),
+            autosave_on_command: set(on),
+        }
+    }
+}
+
+/// Pause autosave and save the world of a running server. Returns whether the save was
+/// confirmed in the console; archiving goes ahead either way.
+pub async fn prepare(pm: &ProcessManager, server_id: &str, commands: &FlushCommands) -> bool {
+    if !pm.is_running(server_id).await {
+        return true;
+    }
+    if let Some(ref off) = commands.autosave_off_command {
+        if let Err(e) = pm.send_command(server_id, off).await {
+            tracing::warn!("Failed to pause autosave of server {}: {}", server_id, e);
+        }
+    }
+    let Some(ref save) = commands.save_command else {
+        return true;
+    };
+
+    // Subscribe before writing so the confirmation can't be missed
+    let mut rx = pm.subscribe_logs(server_id).await;
+    if let Err(e) = pm.send_command(server_id, save).await {
+        tracing::warn!("Failed to send save command to server {}: {}", server_id, e);
+        return false;
+    }
+    let Some(ref confirm) = commands.save_confirm else {
+        tokio::time::sleep(UNCONFIRMED_SAVE_WAIT).await;
+        return true;
+    };
+
+    let confirmed = tokio::time::timeout(SAVE_TIMEOUT, async {
+        loop {
+            match rx.recv().await {
+                Ok(line) if confirm.is_match(&line) => return true,
+                Ok(_) | Err(RecvError::Lagged(_)) => continue,
+                Err(RecvError::Closed) => return false,
+            }
+        }
+    })
+    .await
+    .unwrap_or(false);
+    if !confirmed {
+        tracing::warn!("Server {} didn't confirm its save within {:?}, archiving anyway", server_id, SAVE_TIMEOUT);
+    }
+    confirmed
+}
+
+/// Turn autosave back on once the archive is written (or failed)
+pub async fn resume(pm: &ProcessManager, server_id: &str, commands: &FlushCommands) {
+    let Some(ref on) = commands.autosave_on_command else {
+        return;
+    };
+    if pm.is_running(server_id).await {
+        if let Err(e) = pm.send_command(server_id, on).await {
+            tracing::warn!("Failed to resume autosave of server {}: {}", server_id, e);
+        }
+    }
+}
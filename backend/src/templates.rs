@@ -1,14 +1,73 @@
 //! Templates for Hytale server configuration files
 
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
+use utoipa::ToSchema;
 
-/// Generate the Hytale server config.json
-pub fn generate_config_json(
-    server_name: &str,
-    max_players: u32,
-    auth_mode: &str,
-) -> Value {
-    let auth_store = if auth_mode == "authenticated" {
+/// The settings of config.json the panel edits, the rest of the file is left as generated
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "PascalCase")]
+pub struct GameConfig {
+    #[serde(rename = "MOTD")]
+    pub motd: String,
+    pub password: String,
+    pub max_players: u32,
+    pub max_view_radius: u32,
+    pub defaults: GameDefaults,
+    /// Logger name to level, `INFO`, `FINE`...
+    pub log_levels: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "PascalCase")]
+pub struct GameDefaults {
+    pub world: String,
+    pub game_mode: String,
+}
+
+impl Default for GameConfig {
+    fn default() -> Self {
+        Self {
+            motd: String::new(),
+            password: String::new(),
+            max_players: 100,
+            max_view_radius: 12,
+            defaults: GameDefaults { world: "default".into(), game_mode: "Adventure".into() },
+            log_levels: BTreeMap::new(),
+        }
+    }
+}
+
+impl GameConfig {
+    /// The settings found in a config object, each missing or malformed one at its default
+    pub fn from_value(config: &Value) -> Self {
+        let default = Self::default();
+        let field = |key: &str| config.get(key).cloned();
+        Self {
+            motd: field("MOTD").and_then(|v| serde_json::from_value(v).ok()).unwrap_or(default.motd),
+            password: field("Password").and_then(|v| serde_json::from_value(v).ok()).unwrap_or(default.password),
+            max_players: field("MaxPlayers").and_then(|v| serde_json::from_value(v).ok()).unwrap_or(default.max_players),
+            max_view_radius: field("MaxViewRadius").and_then(|v| serde_json::from_value(v).ok()).unwrap_or(default.max_view_radius),
+            defaults: field("Defaults").and_then(|v| serde_json::from_value(v).ok()).unwrap_or(default.defaults),
+            log_levels: field("LogLevels").and_then(|v| serde_json::from_value(v).ok()).unwrap_or(default.log_levels),
+        }
+    }
+
+    /// Overwrite these settings in a config object, keeping its other keys
+    pub fn apply(&self, config: &mut Value) {
+        if !config.is_object() {
+            *config = json!({});
+        }
+        if let (Some(obj), Ok(Value::Object(fields))) = (config.as_object_mut(), serde_json::to_value(self)) {
+            obj.extend(fields);
+        }
+    }
+}
+
+fn auth_credential_store(auth_mode: &str) -> Value {
+    if auth_mode == "authenticated" {
         json!({
             "Type": "Encrypted",
             "Path": "auth.enc"
@@ -17,19 +76,35 @@ pub fn generate_config_json(
         json!({
             "Type": "None"
         })
-    };
+    }
+}
+
+/// Overwrite the keys of config.json the manager owns, leaving whatever users edited. An unset
+/// `max_players` keeps the file's value.
+pub fn apply_managed_keys(config: &mut Value, port: u16, max_players: Option<u32>, auth_mode: &str) {
+    if !config.is_object() {
+        *config = json!({});
+    }
+    if let Some(obj) = config.as_object_mut() {
+        obj.insert("Port".into(), json!(port));
+        if let Some(max_players) = max_players {
+            obj.insert("MaxPlayers".into(), json!(max_players));
+        }
+        obj.insert("AuthCredentialStore".into(), auth_credential_store(auth_mode));
+    }
+}
+
+/// Generate the Hytale server config.json
+pub fn generate_config_json(
+    server_name: &str,
+    max_players: u32,
+    auth_mode: &str,
+) -> Value {
+    let auth_store = auth_credential_store(auth_mode);
 
-    json!({
+    let mut config = json!({
         "Version": 3,
         "ServerName": server_name,
-        "MOTD": "",
-        "Password": "",
-        "MaxPlayers": max_players,
-        "MaxViewRadius": 12,
-        "Defaults": {
-            "World": "default",
-            "GameMode": "Adventure"
-        },
         "ConnectionTimeouts": {
             "JoinTimeouts": {}
         },
@@ -39,12 +114,13 @@ pub fn generate_config_json(
                 "Modules": {}
             }
         },
-        "LogLevels": {},
         "Mods": {},
         "DisplayTmpTagsInStrings": false,
         "PlayerStorage": {
             "Type": "Hytale"
         },
         "AuthCredentialStore": auth_store
-    })
-}
\ No newline at end of file
+    });
+    GameConfig { max_players, ..GameConfig::default() }.apply(&mut config);
+    config
+}
@@ -0,0 +1,50 @@
+//! Crash-safe file writes: write a sibling temp file, fsync it, then rename over the target
+
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// Replace `path` with `contents` so readers only ever see the old or the new file
+pub fn write_sync(path: &Path, contents: &[u8]) -> std::io::Result<()> {
+    let tmp_path = temp_path(path);
+
+    let result = (|| {
+        let mut file = std::fs::File::create(&tmp_path)?;
+        file.write_all(contents)?;
+        file.sync_all()?;
+        // Keep the mode of the file being replaced (e.g. executable start scripts)
+        if let Ok(existing) = std::fs::metadata(path) {
+            std::fs::set_permissions(&tmp_path, existing.permissions())?;
+        }
+        std::fs::rename(&tmp_path, path)
+    })();
+
+    if result.is_err() {
+        let _ = std::fs::remove_file(&tmp_path);
+        return result;
+    }
+
+    // Persist the rename itself (directories can't be opened for syncing on Windows)
+    #[cfg(unix)]
+    if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) {
+        if let Ok(dir) = std::fs::File::open(dir) {
+            let _ = dir.sync_all();
+        }
+    }
+
+    Ok(())
+}
+
+/// Async variant of [`write_sync`], run on the blocking pool
+pub async fn write(path: impl AsRef<Path>, contents: impl Into<Vec<u8>>) -> std::io::Result<()> {
+    let path = path.as_ref().to_path_buf();
+    let contents = contents.into();
+    tokio::task::spawn_blocking(move || write_sync(&path, &contents))
+        .await
+        .map_err(std::io::Error::other)?
+}
+
+/// Temp file next to the target so the rename stays on the same filesystem
+fn temp_path(path: &Path) -> PathBuf {
+    let file_name = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+    path.with_file_name(format!(".{}.{}.tmp", file_name, uuid::Uuid::new_v4().simple()))
+}
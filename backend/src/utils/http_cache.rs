@@ -0,0 +1,111 @@
+//! ETag / If-None-Match support and short-lived server-side caches for read endpoints
+
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use axum::{
+    body::Bytes,
+    extract::Request,
+    http::{header, HeaderMap, Method, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use serde::Serialize;
+
+use crate::error::AppError;
+
+/// Bumped on every state change; cached entries from an older generation are stale
+static GENERATION: AtomicU64 = AtomicU64::new(0);
+
+pub fn invalidate() {
+    GENERATION.fetch_add(1, Ordering::Relaxed);
+}
+
+/// Middleware invalidating every cache once a mutating request has been handled
+pub async fn invalidate_on_write(req: Request, next: Next) -> Response {
+    let is_write = !matches!(*req.method(), Method::GET | Method::HEAD | Method::OPTIONS);
+    let response = next.run(req).await;
+    if is_write {
+        invalidate();
+    }
+    response
+}
+
+#[derive(Clone)]
+pub struct CachedBody {
+    body: Bytes,
+    etag: String,
+}
+
+struct Entry {
+    stored_at: Instant,
+    generation: u64,
+    cached: CachedBody,
+}
+
+/// Single-entry cache holding the serialized JSON body of one endpoint
+pub struct ResponseCache {
+    ttl: Duration,
+    entry: Mutex<Option<Entry>>,
+}
+
+impl ResponseCache {
+    pub const fn new(ttl: Duration) -> Self {
+        Self { ttl, entry: Mutex::new(None) }
+    }
+
+    pub fn get(&self) -> Option<CachedBody> {
+        let entry = self.entry.lock().unwrap();
+        entry.as_ref()
+            .filter(|e| e.stored_at.elapsed() < self.ttl && e.generation == GENERATION.load(Ordering::Relaxed))
+            .map(|e| e.cached.clone())
+    }
+
+    pub fn store<T: Serialize>(&self, value: &T) -> Result<CachedBody, AppError> {
+        // Read the generation before serializing so a concurrent write marks this entry stale
+        let generation = GENERATION.load(Ordering::Relaxed);
+        let body = serde_json::to_vec(value)
+            .map_err(|e| AppError::Internal(format!("Failed to serialize response: {}", e)))?;
+
+        let mut hasher = DefaultHasher::new();
+        body.hash(&mut hasher);
+        let cached = CachedBody {
+            body: Bytes::from(body),
+            etag: format!("\"{:016x}\"", hasher.finish()),
+        };
+
+        *self.entry.lock().unwrap() = Some(Entry {
+            stored_at: Instant::now(),
+            generation,
+            cached: cached.clone(),
+        });
+        Ok(cached)
+    }
+}
+
+impl CachedBody {
+    /// JSON response, or 304 when the client already holds this ETag
+    pub fn respond(self, headers: &HeaderMap) -> Response {
+        let not_modified = headers
+            .get(header::IF_NONE_MATCH)
+            .and_then(|v| v.to_str().ok())
+            .is_some_and(|v| v.split(',').any(|tag| tag.trim() == self.etag || tag.trim() == "*"));
+
+        if not_modified {
+            return (StatusCode::NOT_MODIFIED, [(header::ETAG, self.etag)]).into_response();
+        }
+
+        (
+            [
+                (header::CONTENT_TYPE, "application/json".to_string()),
+                (header::CACHE_CONTROL, "private, no-cache".to_string()),
+                (header::ETAG, self.etag),
+            ],
+            self.body,
+        )
+            .into_response()
+    }
+}
@@ -0,0 +1,48 @@
+//! Addresses of the host's network interfaces, to check a server's `bind_address` before the
+//! JVM fails on it with a cryptic bind error.
+
+use std::net::IpAddr;
+
+use serde::Serialize;
+use utoipa::ToSchema;
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct InterfaceAddress {
+    pub interface: String,
+    pub address: String,
+    /// Netmask length
+    pub prefix: u8,
+    pub loopback: bool,
+}
+
+/// Every address of every interface, IPv4 first
+pub fn list() -> Vec<InterfaceAddress> {
+    let networks = sysinfo::Networks::new_with_refreshed_list();
+    let mut addresses: Vec<InterfaceAddress> = networks
+        .iter()
+        .flat_map(|(name, data)| {
+            data.ip_networks().iter().map(move |ip| InterfaceAddress {
+                interface: name.clone(),
+                address: ip.addr.to_string(),
+                prefix: ip.prefix,
+                loopback: ip.addr.is_loopback(),
+            })
+        })
+        .collect();
+    addresses.sort_by_key(|a| (a.address.contains(':'), a.interface.clone(), a.address.clone()));
+    addresses
+}
+
+/// Why a bind address can't be used, as an i18n key
+pub fn check_bind_address(address: &str) -> Result<(), &'static str> {
+    let ip: IpAddr = address.trim().parse().map_err(|_| "servers.invalid_bind_address")?;
+    // All interfaces and loopback exist everywhere, even when interfaces can't be listed
+    if ip.is_unspecified() || ip.is_loopback() {
+        return Ok(());
+    }
+    if list().iter().any(|a| a.address.parse::<IpAddr>().is_ok_and(|a| a == ip)) {
+        Ok(())
+    } else {
+        Err("servers.bind_address_unavailable")
+    }
+}
@@ -0,0 +1,53 @@
+//! Java runtime detection shared by the Java picker and the launch checks
+
+use std::path::Path;
+
+use serde::Serialize;
+use tokio::process::Command;
+
+/// Oldest Java release able to run the Hytale server
+pub const MIN_JAVA_VERSION: u32 = 25;
+
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct JavaVersion {
+    pub path: String,
+    pub version: String,
+}
+
+impl JavaVersion {
+    /// Feature release number: "1.8.0_381" -> 8, "25.0.1" -> 25, "25-ea" -> 25
+    pub fn major(&self) -> Option<u32> {
+        let mut parts = self.version.split(|c: char| !c.is_ascii_digit()).filter(|p| !p.is_empty());
+        match parts.next()?.parse().ok()? {
+            1 => parts.next()?.parse().ok(),
+            major => Some(major),
+        }
+    }
+}
+
+pub async fn check_java_version(path: &Path) -> Option<JavaVersion> {
+    let output = Command::new(path)
+        .arg("-version")
+        .output()
+        .await
+        .ok()?;
+    
+    // Java version info is often in stderr
+    let output_str = String::from_utf8_lossy(&output.stderr);
+    
+    // Parse version from string like: "openjdk version \"17.0.8\" 2023-07-18"
+    // or "java version \"1.8.0_381\""
+    for line in output_str.lines() {
+        if line.contains("version") {
+            let parts: Vec<&str> = line.split('"').collect();
+            if parts.len() >= 2 {
+                return Some(JavaVersion {
+                    path: path.to_string_lossy().to_string(),
+                    version: parts[1].to_string(),
+                });
+            }
+        }
+    }
+    
+    None
+}
@@ -0,0 +1,58 @@
+//! The panel's own recent log lines, kept in memory so they can be attached to a support bundle
+//! even when stdout isn't captured anywhere.
+
+use std::collections::VecDeque;
+use std::fmt::Write;
+use std::sync::Mutex;
+
+use tracing::field::{Field, Visit};
+use tracing::{Event, Subscriber};
+use tracing_subscriber::layer::Context;
+use tracing_subscriber::Layer;
+
+/// Lines kept, oldest dropped first
+const CAPACITY: usize = 2000;
+
+lazy_static::lazy_static! {
+    static ref LINES: Mutex<VecDeque<String>> = Mutex::new(VecDeque::with_capacity(CAPACITY));
+}
+
+/// Tracing layer feeding the buffer
+pub struct RecentLogs;
+
+impl<S: Subscriber> Layer<S> for RecentLogs {
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        let metadata = event.metadata();
+        let mut line = format!(
+            "{} {:>5} {}:",
+            chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Millis, true),
+            metadata.level(),
+            metadata.target()
+        );
+        event.record(&mut LineVisitor(&mut line));
+
+        if let Ok(mut lines) = LINES.lock() {
+            if lines.len() == CAPACITY {
+                lines.pop_front();
+            }
+            lines.push_back(line);
+        }
+    }
+}
+
+struct LineVisitor<'a>(&'a mut String);
+
+impl Visit for LineVisitor<'_> {
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        if field.name() == "message" {
+            let _ = write!(self.0, " {:?}", value);
+        } else {
+            let _ = write!(self.0, " {}={:?}", field.name(), value);
+        }
+    }
+}
+
+/// Buffered lines, oldest first
+pub fn recent() -> Vec<String> {
+    LINES.lock().map(|lines| lines.iter().cloned().collect()).unwrap_or_default()
+}
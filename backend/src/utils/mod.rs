@@ -1 +1,10 @@
 pub mod memory;
+pub mod platform;
+pub mod http_cache;
+pub mod atomic_file;
+pub mod java;
+pub mod net_stats;
+pub mod interfaces;
+pub mod log_buffer;
+pub mod sandbox;
+pub mod tls;
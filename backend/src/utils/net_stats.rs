@@ -0,0 +1,78 @@
+//! Network counters for server processes, read from procfs (Linux only, `None` elsewhere).
+//!
+//! The kernel doesn't account traffic per process, so byte counters come from the process's
+//! network namespace: exact for containerized servers, host-wide for the others.
+
+use serde::Serialize;
+
+#[derive(Debug, Clone, Copy, Default, Serialize)]
+pub struct NetworkStats {
+    /// Bytes per second over the last metrics interval
+    pub rx_bytes_per_sec: u64,
+    pub tx_bytes_per_sec: u64,
+    /// Established TCP connections and connected UDP sockets on the server port
+    pub connections: Option<u32>,
+}
+
+/// Total received and sent bytes of the network namespace of `pid`, loopback excluded
+#[cfg(target_os = "linux")]
+pub fn namespace_io(pid: u32) -> Option<(u64, u64)> {
+    let content = std::fs::read_to_string(format!("/proc/{}/net/dev", pid)).ok()?;
+    Some(parse_net_dev(&content))
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn namespace_io(_pid: u32) -> Option<(u64, u64)> {
+    None
+}
+
+/// Connections on local `port` as seen from the network namespace of `pid`
+#[cfg(target_os = "linux")]
+pub fn connections_on_port(pid: u32, port: u16) -> Option<u32> {
+    let mut total = 0;
+    let mut found = false;
+    for table in ["tcp", "tcp6", "udp", "udp6"] {
+        if let Ok(content) = std::fs::read_to_string(format!("/proc/{}/net/{}", pid, table)) {
+            found = true;
+            total += count_established(&content, port);
+        }
+    }
+    found.then_some(total)
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn connections_on_port(_pid: u32, _port: u16) -> Option<u32> {
+    None
+}
+
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn parse_net_dev(content: &str) -> (u64, u64) {
+    // "  eth0: rx_bytes rx_packets ... (8 rx fields) tx_bytes ..."
+    content
+        .lines()
+        .skip(2)
+        .filter_map(|line| line.split_once(':'))
+        .filter(|(iface, _)| iface.trim() != "lo")
+        .fold((0, 0), |(rx, tx), (_, counters)| {
+            let fields: Vec<u64> = counters.split_whitespace().filter_map(|v| v.parse().ok()).collect();
+            (rx + fields.first().copied().unwrap_or(0), tx + fields.get(8).copied().unwrap_or(0))
+        })
+}
+
+/// Rows of /proc/net/{tcp,udp}[6] bound to `port` in state 01 (ESTABLISHED, or connected for UDP)
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn count_established(content: &str, port: u16) -> u32 {
+    content
+        .lines()
+        .skip(1)
+        .filter(|line| {
+            let mut fields = line.split_whitespace().skip(1);
+            let local_port = fields
+                .next()
+                .and_then(|local| local.rsplit_once(':'))
+                .and_then(|(_, port)| u16::from_str_radix(port, 16).ok());
+            let state = fields.nth(1);
+            local_port == Some(port) && state == Some("01")
+        })
+        .count() as u32
+}
@@ -0,0 +1,258 @@
+//! Platform-specific helpers for spawning and stopping game server processes
+
+use std::path::Path;
+
+/// File name of the Java launcher on this platform
+pub const JAVA_BINARY: &str = if cfg!(windows) { "java.exe" } else { "java" };
+
+/// Prepare a server command so the process can be signalled later on
+pub fn configure_command(cmd: &mut std::process::Command) {
+    #[cfg(windows)]
+    {
+        use std::os::windows::process::CommandExt;
+        // The child gets its own hidden console: no window pops up on the host,
+        // and we can attach to that console to deliver CTRL_C on stop.
+        const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+        cmd.creation_flags(CREATE_NO_WINDOW);
+    }
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::CommandExt;
+        // Lead a new process group, so signals reach wrappers' children too
+        cmd.process_group(0);
+    }
+}
+
+/// Send `signal` to the process group led by `pid`, or to the process alone if it has none
+#[cfg(unix)]
+fn signal_group(pid: u32, signal: libc::c_int) -> bool {
+    let pid = pid as libc::pid_t;
+    unsafe { libc::kill(-pid, signal) == 0 || libc::kill(pid, signal) == 0 }
+}
+
+/// Ask a process to shut down cleanly (SIGTERM on Unix, CTRL_C on Windows).
+/// Returns false if the signal could not be delivered.
+#[cfg(unix)]
+pub fn request_graceful_stop(pid: u32) -> bool {
+    signal_group(pid, libc::SIGTERM)
+}
+
+#[cfg(windows)]
+pub fn request_graceful_stop(pid: u32) -> bool {
+    use windows_sys::Win32::System::Console::{
+        AttachConsole, FreeConsole, GenerateConsoleCtrlEvent, SetConsoleCtrlHandler,
+        ATTACH_PARENT_PROCESS, CTRL_C_EVENT,
+    };
+
+    unsafe {
+        // A process can only be attached to one console, so leave ours first
+        FreeConsole();
+        if AttachConsole(pid) == 0 {
+            AttachConsole(ATTACH_PARENT_PROCESS);
+            return false;
+        }
+        // Ignore the event ourselves while it is broadcast to the child's console
+        SetConsoleCtrlHandler(None, 1);
+        let sent = GenerateConsoleCtrlEvent(CTRL_C_EVENT, 0) != 0;
+        FreeConsole();
+        // Let the event drain before restoring our handler, or we may receive it too
+        std::thread::sleep(std::time::Duration::from_millis(100));
+        SetConsoleCtrlHandler(None, 0);
+        AttachConsole(ATTACH_PARENT_PROCESS);
+        sent
+    }
+}
+
+/// Freeze (SIGSTOP) or thaw (SIGCONT) a process, keeping its memory as is.
+/// Returns false if the signal could not be delivered.
+#[cfg(unix)]
+pub fn set_suspended(pid: u32, suspended: bool) -> bool {
+    signal_group(pid, if suspended { libc::SIGSTOP } else { libc::SIGCONT })
+}
+
+/// Not supported on Windows, which has no job-control signals
+#[cfg(windows)]
+pub fn set_suspended(_pid: u32, _suspended: bool) -> bool {
+    false
+}
+
+/// A server's whole process tree: the process group it leads on Unix (see `configure_command`),
+/// a Job Object on Windows. Killing it also takes down wrappers' and launchers' children, and
+/// dropping it (once the server exited) sweeps the descendants it left behind.
+pub struct ProcessTree {
+    #[cfg(unix)]
+    pgid: Option<u32>,
+    /// Job handle, kept as an integer so the tree can be held across awaits
+    #[cfg(windows)]
+    job: Option<usize>,
+}
+
+impl ProcessTree {
+    #[cfg(unix)]
+    pub fn attach(child: &tokio::process::Child) -> Self {
+        Self { pgid: child.id() }
+    }
+
+    /// Put the child in a new job. Processes it spawned before this call aren't covered.
+    #[cfg(windows)]
+    pub fn attach(child: &tokio::process::Child) -> Self {
+        use windows_sys::Win32::Foundation::CloseHandle;
+        use windows_sys::Win32::System::JobObjects::{
+            AssignProcessToJobObject, CreateJobObjectW, JobObjectExtendedLimitInformation,
+            SetInformationJobObject, JOBOBJECT_EXTENDED_LIMIT_INFORMATION, JOB_OBJECT_LIMIT_KILL_ON_JOB_CLOSE,
+        };
+
+        let Some(process) = child.raw_handle() else {
+            return Self { job: None };
+        };
+        unsafe {
+            let job = CreateJobObjectW(std::ptr::null(), std::ptr::null());
+            if job.is_null() {
+                return Self { job: None };
+            }
+            let mut limits: JOBOBJECT_EXTENDED_LIMIT_INFORMATION = std::mem::zeroed();
+            limits.BasicLimitInformation.LimitFlags = JOB_OBJECT_LIMIT_KILL_ON_JOB_CLOSE;
+            SetInformationJobObject(
+                job,
+                JobObjectExtendedLimitInformation,
+                &limits as *const _ as *const std::ffi::c_void,
+                std::mem::size_of::<JOBOBJECT_EXTENDED_LIMIT_INFORMATION>() as u32,
+            );
+            if AssignProcessToJobObject(job, process as _) == 0 {
+                CloseHandle(job);
+                return Self { job: None };
+            }
+            Self { job: Some(job as usize) }
+        }
+    }
+
+    /// Forcefully terminate every process of the tree
+    pub fn kill(&self) {
+        #[cfg(unix)]
+        if let Some(pgid) = self.pgid {
+            unsafe {
+                libc::killpg(pgid as libc::pid_t, libc::SIGKILL);
+            }
+        }
+        #[cfg(windows)]
+        if let Some(job) = self.job {
+            unsafe {
+                windows_sys::Win32::System::JobObjects::TerminateJobObject(job as _, 1);
+            }
+        }
+    }
+}
+
+impl Drop for ProcessTree {
+    fn drop(&mut self) {
+        #[cfg(unix)]
+        self.kill();
+        // The job kills its remaining processes when its last handle closes
+        #[cfg(windows)]
+        if let Some(job) = self.job {
+            unsafe {
+                windows_sys::Win32::Foundation::CloseHandle(job as _);
+            }
+        }
+    }
+}
+
+/// Mark a file as executable (`chmod +x`). Windows has no execute bit, so this is a no-op there.
+pub fn make_executable(path: &Path) -> std::io::Result<()> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = std::fs::metadata(path)?.permissions().mode();
+        set_mode(path, mode | 0o111)?;
+    }
+    #[cfg(not(unix))]
+    let _ = path;
+    Ok(())
+}
+
+/// Apply Unix permission bits (e.g. `0o755`); ignored on Windows
+pub fn set_mode(path: &Path, mode: u32) -> std::io::Result<()> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(path, std::fs::Permissions::from_mode(mode))?;
+    }
+    #[cfg(not(unix))]
+    let _ = (path, mode);
+    Ok(())
+}
+
+/// Whether the host was booted with systemd (same check as `sd_booted`)
+pub fn systemd_available() -> bool {
+    cfg!(target_os = "linux") && Path::new("/run/systemd/system").is_dir()
+}
+
+/// Wrap `inner` into a transient `<unit>.scope`: systemd-run registers the scope then execs the
+/// command in place, so the PID, stdio and signals are unchanged while systemd accounts
+/// resources, applies `MemoryMax` (0 for none) and shows the server in `systemctl status`.
+pub fn systemd_scope_command(unit: &str, inner: &std::process::Command, memory_max: u64) -> std::process::Command {
+    let mut cmd = std::process::Command::new("systemd-run");
+    cmd.arg("--scope")
+        .arg("--quiet")
+        .arg("--collect")
+        .arg(format!("--unit={}", unit))
+        .arg("-p").arg("OOMPolicy=kill");
+    if memory_max > 0 {
+        cmd.arg("-p").arg(format!("MemoryMax={}", memory_max));
+    }
+    // Without root, scopes live in the user's own manager
+    #[cfg(unix)]
+    if unsafe { libc::geteuid() } != 0 {
+        cmd.arg("--user");
+    }
+    cmd.arg("--").arg(inner.get_program()).args(inner.get_args());
+    cmd
+}
+
+/// Build the command used to extract a zip archive.
+/// Windows has no `unzip`, but ships bsdtar which reads zip files.
+pub fn unzip_command(archive: &Path, dest: &Path) -> tokio::process::Command {
+    if cfg!(windows) {
+        let mut cmd = tokio::process::Command::new("tar");
+        cmd.arg("-xf").arg(archive).arg("-C").arg(dest);
+        cmd
+    } else {
+        let mut cmd = tokio::process::Command::new("unzip");
+        cmd.arg("-o").arg(archive).arg("-d").arg(dest);
+        cmd
+    }
+}
+
+/// Check the Hytale downloader can run on this host.
+/// Only x86_64 builds are published, so other Linux/macOS hosts need an
+/// emulator prefix (e.g. `box64` or `qemu-x86_64`); Windows on ARM emulates x64 itself.
+pub fn check_downloader_support(emulator: Option<&str>) -> Result<(), String> {
+    if needs_downloader_emulation() && emulator.is_none_or(|e| e.trim().is_empty()) {
+        return Err(format!(
+            "The Hytale downloader only ships x86_64 binaries and this host is {}. \
+             Install box64 or qemu-user and set the downloader emulator in the panel settings.",
+            std::env::consts::ARCH
+        ));
+    }
+    Ok(())
+}
+
+/// Build the command that runs the downloader, through the emulator when needed
+pub fn downloader_command(binary: &Path, emulator: Option<&str>) -> Result<tokio::process::Command, String> {
+    check_downloader_support(emulator)?;
+
+    if !needs_downloader_emulation() {
+        return Ok(tokio::process::Command::new(binary));
+    }
+
+    let mut prefix = emulator.unwrap_or_default().split_whitespace();
+    let program = prefix.next().unwrap_or_default();
+    let mut cmd = tokio::process::Command::new(program);
+    cmd.args(prefix).arg(binary);
+    Ok(cmd)
+}
+
+/// Whether the downloader has to go through an x86_64 emulator on this host
+pub fn needs_downloader_emulation() -> bool {
+    !cfg!(windows) && std::env::consts::ARCH != "x86_64"
+}
@@ -0,0 +1,62 @@
+//! Keeping user-supplied paths inside a directory. A lexical `starts_with` check is fooled by
+//! `..` components and by symlinks, so containment is checked on real (canonical) paths; a
+//! target that doesn't exist yet is checked through its nearest existing ancestor.
+
+use std::io::{Error, ErrorKind};
+use std::path::{Component, Path, PathBuf};
+
+fn escape() -> Error {
+    Error::new(ErrorKind::PermissionDenied, "path escapes its sandbox")
+}
+
+/// Real path of the existing part of `path`, followed by the components that don't exist yet.
+/// Those can't be resolved, so they may only be plain names.
+fn canonicalize_partial(path: &Path) -> std::io::Result<PathBuf> {
+    let mut existing = path;
+    let mut missing = Vec::new();
+    loop {
+        match existing.symlink_metadata() {
+            Ok(_) => break,
+            Err(e) if e.kind() == ErrorKind::NotFound => {
+                missing.push(existing.file_name().ok_or_else(escape)?);
+                existing = existing.parent().ok_or_else(escape)?;
+            }
+            Err(e) => return Err(e),
+        }
+    }
+
+    // A dangling symlink would let a later write create its target anywhere
+    let mut real = std::fs::canonicalize(existing).map_err(|e| match e.kind() {
+        ErrorKind::NotFound => escape(),
+        _ => e,
+    })?;
+    for name in missing.into_iter().rev() {
+        if matches!(Path::new(name).components().next(), Some(Component::Normal(_))) {
+            real.push(name);
+        } else {
+            return Err(escape());
+        }
+    }
+    Ok(real)
+}
+
+/// Resolve `relative` inside `base`, following symlinks. Fails with `PermissionDenied` when the
+/// result would be outside `base`, including absolute paths and links pointing out of it.
+pub fn resolve(base: &Path, relative: &str) -> std::io::Result<PathBuf> {
+    let relative = Path::new(relative);
+    if relative.has_root() || relative.components().any(|c| matches!(c, Component::Prefix(_))) {
+        return Err(escape());
+    }
+    contain(base, &base.join(relative))
+}
+
+/// Real path of `path` when it lies inside `base`, `PermissionDenied` otherwise
+pub fn contain(base: &Path, path: &Path) -> std::io::Result<PathBuf> {
+    let root = std::fs::canonicalize(base)?;
+    let real = canonicalize_partial(path)?;
+    if real.starts_with(&root) {
+        Ok(real)
+    } else {
+        Err(escape())
+    }
+}
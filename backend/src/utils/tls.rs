@@ -0,0 +1,78 @@
+//! HTTPS termination for panels exposed without a reverse proxy: the certificate comes from PEM
+//! files, generated self-signed on first run when none were provided, and a plain HTTP port can
+//! redirect browsers to the HTTPS one.
+
+use std::net::SocketAddr;
+
+use anyhow::Context;
+use axum::{
+    extract::Host,
+    http::Uri,
+    response::Redirect,
+    Router,
+};
+use axum_server::tls_rustls::RustlsConfig;
+
+use crate::config::TlsSettings;
+use crate::utils::{interfaces, platform};
+
+/// Certificate and key of `tls`, created first when missing and allowed to
+pub async fn rustls_config(tls: &TlsSettings) -> anyhow::Result<RustlsConfig> {
+    if !tls.cert_path.exists() || !tls.key_path.exists() {
+        if !tls.self_signed {
+            anyhow::bail!(
+                "TLS certificate {} or key {} not found (set TLS_SELF_SIGNED=true to generate them)",
+                tls.cert_path.display(),
+                tls.key_path.display()
+            );
+        }
+        generate_self_signed(tls)?;
+    }
+    RustlsConfig::from_pem_file(&tls.cert_path, &tls.key_path)
+        .await
+        .with_context(|| format!("Failed to load TLS certificate {}", tls.cert_path.display()))
+}
+
+/// Self-signed certificate valid for localhost, the host name and every interface address
+fn generate_self_signed(tls: &TlsSettings) -> anyhow::Result<()> {
+    let mut names = vec!["localhost".to_string()];
+    names.extend(sysinfo::System::host_name());
+    names.extend(interfaces::list().into_iter().map(|a| a.address));
+
+    let rcgen::CertifiedKey { cert, key_pair } = rcgen::generate_simple_self_signed(names)
+        .context("Failed to generate a self-signed certificate")?;
+    for path in [&tls.cert_path, &tls.key_path] {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+    }
+    std::fs::write(&tls.cert_path, cert.pem())?;
+    std::fs::write(&tls.key_path, key_pair.serialize_pem())?;
+    platform::set_mode(&tls.key_path, 0o600)?;
+
+    tracing::warn!(
+        "🔒 Generated a self-signed TLS certificate in {}: browsers will warn until it is replaced by a trusted one",
+        tls.cert_path.display()
+    );
+    Ok(())
+}
+
+/// Answer plain HTTP on `addr` with a permanent redirect to the same URL on `https_port`
+pub async fn redirect_http(addr: SocketAddr, https_port: u16) -> anyhow::Result<()> {
+    let redirect = move |Host(host): Host, uri: Uri| async move {
+        // The host may carry the plain HTTP port
+        let name = match host.rsplit_once(':') {
+            Some((name, port)) if !name.is_empty() && port.chars().all(|c| c.is_ascii_digit()) => name.to_string(),
+            _ => host,
+        };
+        let path = uri.path_and_query().map_or("/", |p| p.as_str());
+        Redirect::permanent(&format!("https://{}:{}{}", name, https_port, path))
+    };
+
+    let listener = tokio::net::TcpListener::bind(addr)
+        .await
+        .with_context(|| format!("Failed to bind the HTTP redirect on {}", addr))?;
+    tracing::info!("↪️ Redirecting HTTP on {} to HTTPS", addr);
+    axum::serve(listener, Router::new().fallback(redirect)).await?;
+    Ok(())
+}
@@ -0,0 +1,90 @@
+//! Native Windows service wrapper, used when the binary is started with `--service`
+
+use std::ffi::OsString;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use windows_service::{
+    define_windows_service,
+    service::{
+        ServiceControl, ServiceControlAccept, ServiceExitCode, ServiceState, ServiceStatus,
+        ServiceType,
+    },
+    service_control_handler::{self, ServiceControlHandlerResult, ServiceStatusHandle},
+    service_dispatcher,
+};
+
+const SERVICE_NAME: &str = "DraveurManager";
+
+define_windows_service!(ffi_service_main, service_main);
+
+/// Hand control to the Service Control Manager (blocks until the service stops)
+pub fn run() -> anyhow::Result<()> {
+    service_dispatcher::start(SERVICE_NAME, ffi_service_main)?;
+    Ok(())
+}
+
+fn service_main(_arguments: Vec<OsString>) {
+    if let Err(e) = run_service() {
+        eprintln!("Windows service failed: {}", e);
+    }
+}
+
+fn run_service() -> anyhow::Result<()> {
+    // Services start in System32: move to the install directory so relative
+    // paths (.env, ./static, data/) resolve like an interactive launch.
+    if let Some(dir) = workdir_arg() {
+        std::env::set_current_dir(dir)?;
+    }
+
+    let (shutdown_tx, shutdown_rx) = tokio::sync::oneshot::channel::<()>();
+    let shutdown_tx = Mutex::new(Some(shutdown_tx));
+
+    let event_handler = move |control| match control {
+        ServiceControl::Stop | ServiceControl::Shutdown => {
+            if let Some(tx) = shutdown_tx.lock().ok().and_then(|mut tx| tx.take()) {
+                let _ = tx.send(());
+            }
+            ServiceControlHandlerResult::NoError
+        }
+        ServiceControl::Interrogate => ServiceControlHandlerResult::NoError,
+        _ => ServiceControlHandlerResult::NotImplemented,
+    };
+
+    let status_handle = service_control_handler::register(SERVICE_NAME, event_handler)?;
+    set_state(&status_handle, ServiceState::Running, 0)?;
+
+    let runtime = tokio::runtime::Runtime::new()?;
+    let result = runtime.block_on(crate::run(async {
+        let _ = shutdown_rx.await;
+    }));
+
+    set_state(&status_handle, ServiceState::Stopped, if result.is_ok() { 0 } else { 1 })?;
+    result
+}
+
+fn set_state(handle: &ServiceStatusHandle, state: ServiceState, exit_code: u32) -> anyhow::Result<()> {
+    let controls_accepted = if state == ServiceState::Running {
+        ServiceControlAccept::STOP | ServiceControlAccept::SHUTDOWN
+    } else {
+        ServiceControlAccept::empty()
+    };
+
+    handle.set_service_status(ServiceStatus {
+        service_type: ServiceType::OWN_PROCESS,
+        current_state: state,
+        controls_accepted,
+        exit_code: ServiceExitCode::Win32(exit_code),
+        checkpoint: 0,
+        wait_hint: Duration::default(),
+        process_id: None,
+    })?;
+    Ok(())
+}
+
+/// Value of `--workdir <path>` from the service command line
+fn workdir_arg() -> Option<String> {
+    let mut args = std::env::args().skip_while(|arg| arg != "--workdir");
+    args.next();
+    args.next()
+}
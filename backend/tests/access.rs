@@ -0,0 +1,253 @@
+mod common;
+
+use common::TestApp;
+use serde_json::json;
+
+#[tokio::test]
+async fn users_only_manage_their_allocated_servers() {
+    let app = TestApp::spawn().await;
+    let mine = app.create_fake_server("Allocated").await;
+    let other = app.create_fake_server("Someone else's").await;
+
+    let (operator, operator_id) = app.create_user("operator", "user").await;
+
+    // Only admins hand out servers
+    let allocation = json!({ "allocated_servers": [mine] });
+    let (status, _) = app.call(reqwest::Method::PUT, &format!("/users/{}", operator_id), &operator, Some(allocation.clone())).await;
+    assert_eq!(status, 403);
+    let (status, body) = app.call(reqwest::Method::PUT, &format!("/users/{}", operator_id), &app.admin_token, Some(allocation)).await;
+    assert_eq!(status, 200, "allocation failed: {}", body);
+
+    let (status, servers) = app.call(reqwest::Method::GET, "/servers", &operator, None).await;
+    assert_eq!(status, 200);
+    let ids: Vec<&str> = servers.as_array().expect("server list").iter().filter_map(|s| s["id"].as_str()).collect();
+    assert_eq!(ids, vec![mine.as_str()]);
+
+    let (status, _) = app.call(reqwest::Method::GET, &format!("/servers/{}", mine), &operator, None).await;
+    assert_eq!(status, 200);
+    let (status, _) = app.call(reqwest::Method::GET, &format!("/servers/{}", other), &operator, None).await;
+    assert_eq!(status, 403);
+    let (status, _) = app.call(reqwest::Method::POST, &format!("/servers/{}/start", other), &operator, Some(json!({}))).await;
+    assert_eq!(status, 403);
+    let (status, _) = app.call(reqwest::Method::GET, &format!("/servers/{}/files?path=", other), &operator, None).await;
+    assert_eq!(status, 403);
+
+    // Backups of other servers are neither listed nor reachable
+    let backup = app.create_backup(&other).await;
+    let backup_id = backup["id"].as_str().expect("backup id").to_string();
+    let (status, _) = app.call(reqwest::Method::POST, "/backups", &operator, Some(json!({ "server_id": other }))).await;
+    assert_eq!(status, 403);
+    let (_, backups) = app.call(reqwest::Method::GET, "/backups", &operator, None).await;
+    assert_eq!(backups.as_array().map(Vec::len), Some(0));
+    let (status, _) = app.call(reqwest::Method::POST, &format!("/backups/{}/restore", backup_id), &operator, Some(json!({}))).await;
+    assert_eq!(status, 403);
+
+    // Admins still reach everything
+    let (_, servers) = app.get("/servers").await;
+    assert_eq!(servers.as_array().map(Vec::len), Some(2));
+
+    // Nor does the inbox show them, or their directories
+    let (_, server) = app.get(&format!("/servers/{}", other)).await;
+    std::fs::remove_dir_all(server["working_dir"].as_str().expect("working dir")).unwrap();
+    let (_, inbox) = app.get("/inbox").await;
+    assert!(inbox.as_array().unwrap().iter().any(|item| item["server_id"] == other.as_str()));
+    let (status, inbox) = app.call(reqwest::Method::GET, "/inbox", &operator, None).await;
+    assert_eq!(status, 200);
+    assert!(inbox.as_array().unwrap().iter().all(|item| item["server_id"] != other.as_str()));
+}
+
+#[tokio::test]
+async fn granted_permissions_open_only_their_actions() {
+    let app = TestApp::spawn().await;
+    let id = app.create_fake_server("Shared").await;
+
+    let (moderator, moderator_id) = app.create_user("moderator", "user").await;
+    let grants = format!("/servers/{}/permissions/{}", id, moderator_id);
+
+    let (status, _) = app.call(reqwest::Method::PUT, &grants, &app.admin_token, Some(json!({ "permissions": ["console.sudo"] }))).await;
+    assert_eq!(status, 400);
+    let (status, body) = app.call(reqwest::Method::PUT, &grants, &app.admin_token, Some(json!({
+        "permissions": ["console.view", "console.command"],
+    }))).await;
+    assert_eq!(status, 200, "grant failed: {}", body);
+
+    // The server shows up, only the granted actions are open
+    let (_, servers) = app.call(reqwest::Method::GET, "/servers", &moderator, None).await;
+    assert_eq!(servers.as_array().map(Vec::len), Some(1));
+    let (status, _) = app.call(reqwest::Method::GET, &format!("/servers/{}", id), &moderator, None).await;
+    assert_eq!(status, 200);
+    let (status, _) = app.call(reqwest::Method::POST, &format!("/servers/{}/start", id), &moderator, Some(json!({}))).await;
+    assert_eq!(status, 403);
+    let (status, _) = app.call(reqwest::Method::GET, &format!("/servers/{}/files?path=", id), &moderator, None).await;
+    assert_eq!(status, 403);
+    let (status, _) = app.call(reqwest::Method::PUT, &grants, &moderator, Some(json!({ "permissions": ["files.edit"] }))).await;
+    assert_eq!(status, 403);
+    // Reading other users' grants, the export bundle or the server password isn't viewing
+    for path in ["permissions", "export", "game-config"] {
+        let (status, body) = app.call(reqwest::Method::GET, &format!("/servers/{}/{}", id, path), &moderator, None).await;
+        assert_eq!(status, 403, "{} allowed: {}", path, body);
+    }
+
+    let (status, _) = app.call(reqwest::Method::PUT, &grants, &app.admin_token, Some(json!({ "permissions": ["files.edit"] }))).await;
+    assert_eq!(status, 200);
+    let (status, _) = app.call(reqwest::Method::GET, &format!("/servers/{}/files?path=", id), &moderator, None).await;
+    assert_eq!(status, 200);
+
+    let (_, listed) = app.call(reqwest::Method::GET, &format!("/servers/{}/permissions", id), &app.admin_token, None).await;
+    assert_eq!(listed[0]["permissions"], json!(["files.edit"]));
+
+    let (status, _) = app.call(reqwest::Method::DELETE, &grants, &app.admin_token, None).await;
+    assert_eq!(status, 200);
+    let (status, _) = app.call(reqwest::Method::GET, &format!("/servers/{}", id), &moderator, None).await;
+    assert_eq!(status, 403);
+}
+
+#[tokio::test]
+async fn role_templates_grant_their_current_permissions() {
+    let app = TestApp::spawn().await;
+    let id = app.create_fake_server("Templated").await;
+
+    let (helper, helper_id) = app.create_user("helper", "user").await;
+
+    let (status, _) = app.call(reqwest::Method::POST, "/role-templates", &helper, Some(json!({ "name": "Moderator" }))).await;
+    assert_eq!(status, 403);
+    let (status, _) = app.post("/role-templates", json!({ "name": "Broken", "permissions": ["console.sudo"] })).await;
+    assert_eq!(status, 400);
+    let (status, template) = app.post("/role-templates", json!({
+        "name": "Moderator",
+        "permissions": ["console.command", "console.view"],
+    })).await;
+    assert_eq!(status, 201, "create template failed: {}", template);
+    let template_id = template["id"].as_str().expect("template id").to_string();
+    let (status, _) = app.post("/role-templates", json!({ "name": "moderator" })).await;
+    assert_eq!(status, 409);
+
+    let grants = format!("/servers/{}/permissions/{}", id, helper_id);
+    let (status, body) = app.call(reqwest::Method::PUT, &grants, &app.admin_token, Some(json!({ "template_id": template_id }))).await;
+    assert_eq!(status, 200, "assign failed: {}", body);
+    let (status, _) = app.call(reqwest::Method::GET, &format!("/servers/{}", id), &helper, None).await;
+    assert_eq!(status, 200);
+    let (status, _) = app.call(reqwest::Method::GET, &format!("/servers/{}/files?path=", id), &helper, None).await;
+    assert_eq!(status, 403);
+
+    // Editing the template changes the access of everyone holding it
+    let (status, _) = app.call(reqwest::Method::PUT, &format!("/role-templates/{}", template_id), &app.admin_token, Some(json!({
+        "permissions": ["console.view", "console.command", "files.edit"],
+    }))).await;
+    assert_eq!(status, 200);
+    let (status, _) = app.call(reqwest::Method::GET, &format!("/servers/{}/files?path=", id), &helper, None).await;
+    assert_eq!(status, 200);
+
+    let (_, listed) = app.call(reqwest::Method::GET, &format!("/servers/{}/permissions", id), &app.admin_token, None).await;
+    assert_eq!(listed[0]["template_name"], "Moderator");
+    assert_eq!(listed[0]["permissions"], json!([]));
+
+    let (status, _) = app.delete(&format!("/role-templates/{}", template_id)).await;
+    assert_eq!(status, 200);
+    let (status, _) = app.call(reqwest::Method::GET, &format!("/servers/{}", id), &helper, None).await;
+    assert_eq!(status, 403);
+}
+
+#[tokio::test]
+async fn viewers_see_every_server_but_change_nothing() {
+    let app = TestApp::spawn().await;
+    let id = app.create_fake_server("Watched").await;
+
+    let (status, _) = app.post("/users", json!({ "username": "boss", "password": "password123", "role": "owner" })).await;
+    assert_eq!(status, 400);
+    let (status, body) = app.post("/users", json!({ "username": "boss", "password": "password123", "role": "viewer" })).await;
+    assert_eq!(status, 201, "create viewer failed: {}", body);
+    let (_, body) = app.post("/auth/login", json!({ "username": "boss", "password": "password123" })).await;
+    let viewer = body["token"].as_str().expect("token").to_string();
+
+    let (_, servers) = app.call(reqwest::Method::GET, "/servers", &viewer, None).await;
+    assert_eq!(servers.as_array().map(Vec::len), Some(1));
+    let (status, _) = app.call(reqwest::Method::GET, &format!("/servers/{}", id), &viewer, None).await;
+    assert_eq!(status, 200);
+    let backup = app.create_backup(&id).await;
+    let backup_id = backup["id"].as_str().expect("backup id");
+    let (status, listed) = app.call(reqwest::Method::GET, &format!("/backups?server_id={}", id), &viewer, None).await;
+    assert_eq!(status, 200);
+    assert_eq!(listed.as_array().map(Vec::len), Some(1));
+    let (status, _) = app.call(reqwest::Method::GET, &format!("/backups/{}", backup_id), &viewer, None).await;
+    assert_eq!(status, 200);
+    // The archive holds config.json and its password
+    let (status, _) = app.call(reqwest::Method::GET, &format!("/backups/{}/entries", backup_id), &viewer, None).await;
+    assert_eq!(status, 403);
+
+    for (method, path) in [
+        (reqwest::Method::POST, format!("/servers/{}/start", id)),
+        (reqwest::Method::POST, format!("/servers/{}/command", id)),
+        (reqwest::Method::POST, format!("/servers/{}/files/write", id)),
+        (reqwest::Method::DELETE, format!("/servers/{}", id)),
+        (reqwest::Method::POST, "/backups".to_string()),
+    ] {
+        let (status, body) = app.call(method, &path, &viewer, Some(json!({}))).await;
+        assert_eq!(status, 403, "{} allowed: {}", path, body);
+        assert_eq!(body["error"], "auth.read_only");
+    }
+    // Files may hold secrets, they aren't part of the status view
+    let (status, _) = app.call(reqwest::Method::GET, &format!("/servers/{}/files?path=", id), &viewer, None).await;
+    assert_eq!(status, 403);
+}
+
+#[tokio::test]
+async fn host_settings_stay_with_admins() {
+    let app = TestApp::spawn().await;
+    let id = app.create_fake_server("Hosted").await;
+
+    let (status, body) = app.post("/users", json!({ "username": "operator", "password": "password123", "role": "user", "allocated_servers": [id] })).await;
+    assert_eq!(status, 201, "create user failed: {}", body);
+    let (_, body) = app.post("/auth/login", json!({ "username": "operator", "password": "password123" })).await;
+    let operator = body["token"].as_str().expect("token").to_string();
+
+    let (status, _) = app.call(reqwest::Method::POST, "/servers", &operator, Some(json!({ "name": "Mine", "game_type": "fake" }))).await;
+    assert_eq!(status, 403);
+    let (status, _) = app.call(reqwest::Method::POST, "/servers/import", &operator, Some(json!({ "version": 1, "exported_at": "", "servers": [] }))).await;
+    assert_eq!(status, 403);
+
+    let path = format!("/servers/{}", id);
+    let (status, body) = app.call(reqwest::Method::PATCH, &path, &operator, Some(json!({ "max_memory": "6G" }))).await;
+    assert_eq!(status, 200, "{}", body);
+    // Sending back the saved launch settings changes nothing
+    let (_, server) = app.get(&path).await;
+    let unchanged = json!({ "launch_mode": server["launch_mode"], "command_template": server["command_template"] });
+    let (status, body) = app.call(reqwest::Method::PATCH, &path, &operator, Some(unchanged)).await;
+    assert_eq!(status, 200, "{}", body);
+    for change in [
+        json!({ "java_path": "/bin/sh" }),
+        json!({ "executable_path": "/bin/sh" }),
+        json!({ "extra_args": "-jar /tmp/other.jar" }),
+        json!({ "env_vars": { "LD_PRELOAD": "/tmp/hook.so" } }),
+        json!({ "working_dir": app.servers_dir().join("moved").to_string_lossy() }),
+        json!({ "runtime": "docker", "docker_image": "alpine" }),
+        json!({ "launch_mode": "java" }),
+        json!({ "command_template": "/bin/sh -c id" }),
+    ] {
+        let (status, body) = app.call(reqwest::Method::PATCH, &path, &operator, Some(change.clone())).await;
+        assert_eq!(status, 403, "{} allowed: {}", change, body);
+        assert_eq!(body["error"], "servers.host_settings_admin_only");
+    }
+
+    // Even for admins, a server's directory stays in servers_dir, since deleting the server removes it
+    let outside = app.dir.path().join("outside");
+    for dir in [outside.clone(), app.servers_dir(), app.servers_dir().join("../outside")] {
+        let (status, body) = app.call(reqwest::Method::PATCH, &path, &app.admin_token, Some(json!({ "working_dir": dir.to_string_lossy() }))).await;
+        assert_eq!(status, 400, "{}", body);
+        assert_eq!(body["error"], "servers.working_dir_outside_servers_dir");
+    }
+    let (status, _) = app.post("/servers", json!({ "name": "Elsewhere", "game_type": "fake", "working_dir": outside.to_string_lossy() })).await;
+    assert_eq!(status, 400);
+
+    // A row saved before the rule keeps its directory on deletion
+    std::fs::create_dir_all(&outside).unwrap();
+    sqlx::query("UPDATE servers SET working_dir = ? WHERE id = ?")
+        .bind(outside.to_string_lossy().as_ref())
+        .bind(&id)
+        .execute(&app.state.pool)
+        .await
+        .unwrap();
+    let (status, _) = app.delete(&path).await;
+    assert_eq!(status, 200);
+    assert!(outside.exists());
+}
@@ -0,0 +1,51 @@
+mod common;
+
+use common::TestApp;
+use serde_json::{json, Value};
+
+#[tokio::test]
+async fn users_export_and_delete_their_own_account() {
+    let app = TestApp::spawn().await;
+    let (token, _) = app.create_user("leaving", "user").await;
+
+    let response = app.client.get(app.url("/auth/me/export")).bearer_auth(&token).send().await.expect("export");
+    assert_eq!(response.status().as_u16(), 200);
+    assert!(response.headers()["content-disposition"].to_str().unwrap().starts_with("attachment"));
+    let export: Value = response.json().await.expect("export body");
+    assert_eq!(export["profile"]["username"], "leaving");
+    assert!(export["profile"].get("password_hash").is_none());
+    assert_eq!(export["sessions"].as_array().map(Vec::len), Some(1));
+
+    let delete = |token: String, confirm: &'static str| {
+        let request = app.client.delete(app.url("/auth/me")).bearer_auth(token).json(&json!({ "confirm": confirm }));
+        async move {
+            let response = request.send().await.expect("delete");
+            (response.status().as_u16(), response.json::<Value>().await.unwrap_or(Value::Null))
+        }
+    };
+    // The only admin stays
+    let (status, body) = delete(app.admin_token.clone(), "admin").await;
+    assert_eq!(status, 409);
+    assert_eq!(body["error"], "users.last_admin");
+
+    let (status, _) = delete(token.clone(), "someone-else").await;
+    assert_eq!(status, 400);
+    let (status, _) = delete(token.clone(), "leaving").await;
+    assert_eq!(status, 200);
+
+    let response = app.client.get(app.url("/auth/me")).bearer_auth(&token).send().await.expect("me");
+    assert_eq!(response.status().as_u16(), 401);
+    let (status, _) = app.post("/auth/login", json!({ "username": "leaving", "password": "password123" })).await;
+    assert_eq!(status, 401);
+}
+
+#[tokio::test]
+async fn registration_closes_after_the_first_user() {
+    let app = TestApp::spawn().await;
+
+    let (status, body) = app.post("/auth/register", json!({ "username": "intruder", "password": "password123" })).await;
+    assert_eq!(status, 403);
+    assert_eq!(body["error"], "auth.registration_closed");
+    let (status, _) = app.post("/auth/login", json!({ "username": "intruder", "password": "password123" })).await;
+    assert_eq!(status, 401);
+}
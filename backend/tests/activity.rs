@@ -0,0 +1,38 @@
+mod common;
+
+use common::TestApp;
+use serde_json::json;
+
+#[tokio::test]
+async fn users_actions_show_up_in_their_activity() {
+    let app = TestApp::spawn().await;
+    let id = app.create_fake_server("Audited").await;
+
+    let (status, _) = app.post("/auth/login", json!({ "username": "admin", "password": "password123" })).await;
+    assert_eq!(status, 200);
+    let (status, _) = app.post(&format!("/servers/{}/files/write", id), json!({ "path": "notes.txt", "content": "hi" })).await;
+    assert_eq!(status, 200);
+
+    let (status, body) = app.get(&format!("/users/{}/activity", app.admin_id)).await;
+    assert_eq!(status, 200, "{}", body);
+    let entries = body.as_array().expect("activity entries");
+    assert_eq!(entries[0]["action"], "file_write");
+    assert_eq!(entries[0]["detail"], "Audited: notes.txt");
+    assert!(entries.iter().any(|e| e["action"] == "login" && e["ip"] == "127.0.0.1"), "{}", body);
+
+    // Paging back from the newest entry skips it
+    let newest = entries[0]["id"].as_i64().unwrap();
+    let (_, older) = app.get(&format!("/users/{}/activity?before={}", app.admin_id, newest)).await;
+    assert!(older.as_array().unwrap().iter().all(|e| e["id"].as_i64().unwrap() < newest));
+
+    // Other users only see their own
+    let (teammate, teammate_id) = app.create_user("teammate", "user").await;
+    let status = |path: String, token: String| {
+        let request = app.client.get(app.url(&path)).bearer_auth(token);
+        async move { request.send().await.expect("request").status().as_u16() }
+    };
+    assert_eq!(status(format!("/users/{}/activity", app.admin_id), teammate.clone()).await, 403);
+    assert_eq!(status(format!("/users/{}/activity", teammate_id), teammate).await, 200);
+    let (status, _) = app.get("/users/unknown/activity").await;
+    assert_eq!(status, 404);
+}
@@ -0,0 +1,382 @@
+mod common;
+
+use common::TestApp;
+use serde_json::json;
+
+#[tokio::test]
+async fn backup_and_restore_server_files() {
+    let app = TestApp::spawn().await;
+    let id = app.create_fake_server("Backups").await;
+
+    let write = |content: &'static str| {
+        let app = &app;
+        let id = &id;
+        async move {
+            let (status, body) = app.post(&format!("/servers/{}/files/write", id), json!({
+                "path": "data.txt",
+                "content": content,
+            })).await;
+            assert_eq!(status, 200, "write failed: {}", body);
+        }
+    };
+
+    write("before backup").await;
+
+    let backup = app.create_backup(&id).await;
+    let backup_id = backup["id"].as_str().expect("backup id").to_string();
+    assert!(backup["size_bytes"].as_i64().is_some_and(|size| size > 0));
+
+    let (_, list) = app.get(&format!("/backups?server_id={}", id)).await;
+    assert_eq!(list.as_array().map(Vec::len), Some(1));
+
+    write("after backup").await;
+
+    let (status, body) = app.post(&format!("/backups/{}/restore", backup_id), json!({})).await;
+    assert_eq!(status, 200, "restore failed: {}", body);
+
+    let (_, file) = app.get(&format!("/servers/{}/files/read?path=data.txt", id)).await;
+    assert_eq!(file["content"], "before backup");
+
+    // Deleting removes the backup
+    let (status, _) = app.delete(&format!("/backups/{}", backup_id)).await;
+    assert_eq!(status, 200);
+    let (status, _) = app.get(&format!("/backups/{}", backup_id)).await;
+    assert_eq!(status, 404);
+}
+
+#[tokio::test]
+async fn zstd_backup_restores_with_its_own_decoder() {
+    let app = TestApp::spawn().await;
+    let id = app.create_fake_server("Zstd").await;
+
+    let response = app.client
+        .put(app.url("/settings"))
+        .json(&json!({ "backup_compression": "zstd", "backup_compression_level": 30 }))
+        .send()
+        .await
+        .expect("update settings");
+    assert_eq!(response.status().as_u16(), 400);
+    let response = app.client
+        .put(app.url("/settings"))
+        .json(&json!({ "backup_compression": "zstd", "backup_compression_level": 19 }))
+        .send()
+        .await
+        .expect("update settings");
+    assert_eq!(response.status().as_u16(), 200);
+
+    let (status, _) = app.post(&format!("/servers/{}/files/write", id), json!({ "path": "data.txt", "content": "zstd" })).await;
+    assert_eq!(status, 200);
+    let backup = app.create_backup(&id).await;
+    assert_eq!(backup["compression"], "zstd");
+    assert!(backup["filename"].as_str().is_some_and(|f| f.ends_with(".tar.zst")));
+    let backup_id = backup["id"].as_str().expect("backup id").to_string();
+
+    app.post(&format!("/servers/{}/files/write", id), json!({ "path": "data.txt", "content": "changed" })).await;
+    let (status, body) = app.post(&format!("/backups/{}/restore", backup_id), json!({})).await;
+    assert_eq!(status, 200, "restore failed: {}", body);
+    let (_, file) = app.get(&format!("/servers/{}/files/read?path=data.txt", id)).await;
+    assert_eq!(file["content"], "zstd");
+
+    app.delete(&format!("/backups/{}", backup_id)).await;
+}
+
+#[tokio::test]
+async fn backup_of_unknown_server_is_not_found() {
+    let app = TestApp::spawn().await;
+    let (status, _) = app.post("/backups", json!({ "server_id": "missing" })).await;
+    assert_eq!(status, 404);
+}
+
+#[tokio::test]
+async fn backups_beyond_max_backups_are_pruned() {
+    let app = TestApp::spawn().await;
+    let id = app.create_fake_server("Retention").await;
+    sqlx::query("UPDATE servers SET backup_max_backups = 2 WHERE id = ?")
+        .bind(&id)
+        .execute(&app.state.pool)
+        .await
+        .expect("set backup_max_backups");
+
+    let mut ids = Vec::new();
+    for _ in 0..3 {
+        let backup = app.create_backup(&id).await;
+        ids.push(backup["id"].as_str().expect("backup id").to_string());
+    }
+
+    let (_, list) = app.get(&format!("/backups?server_id={}", id)).await;
+    let kept: Vec<&str> = list.as_array().expect("backup list").iter().filter_map(|b| b["id"].as_str()).collect();
+    assert_eq!(kept, vec![ids[2].as_str(), ids[1].as_str()]);
+
+    for backup_id in &ids[1..] {
+        app.delete(&format!("/backups/{}", backup_id)).await;
+    }
+}
+
+/// A `.tar.gz` holding `data.txt`, as made by `tar czf` on another machine
+fn external_archive(content: &str) -> Vec<u8> {
+    let encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+    let mut tar = tar::Builder::new(encoder);
+    let mut header = tar::Header::new_gnu();
+    header.set_size(content.len() as u64);
+    header.set_mode(0o644);
+    header.set_cksum();
+    tar.append_data(&mut header, "data.txt", content.as_bytes()).expect("append entry");
+    tar.into_inner().expect("finish tar").finish().expect("finish gzip")
+}
+
+#[tokio::test]
+async fn imported_archive_can_be_restored() {
+    let app = TestApp::spawn().await;
+    let id = app.create_fake_server("Imported").await;
+
+    let response = app.client
+        .put(app.url(&format!("/backups/import?server_id={}&label=migration", id)))
+        .body(b"not an archive".to_vec())
+        .send()
+        .await
+        .expect("import request");
+    assert_eq!(response.status().as_u16(), 400);
+
+    let response = app.client
+        .put(app.url(&format!("/backups/import?server_id={}&label=migration", id)))
+        .body(external_archive("from the old machine"))
+        .send()
+        .await
+        .expect("import request");
+    assert_eq!(response.status().as_u16(), 201);
+    let backup: serde_json::Value = response.json().await.expect("backup json");
+    assert_eq!(backup["label"], "migration");
+    let backup_id = backup["id"].as_str().expect("backup id").to_string();
+
+    let (status, body) = app.post(&format!("/backups/{}/restore", backup_id), json!({})).await;
+    assert_eq!(status, 200, "restore failed: {}", body);
+    let (_, file) = app.get(&format!("/servers/{}/files/read?path=data.txt", id)).await;
+    assert_eq!(file["content"], "from the old machine");
+
+    let (status, _) = app.delete(&format!("/backups/{}", backup_id)).await;
+    assert_eq!(status, 200);
+}
+
+#[tokio::test]
+async fn verify_flags_a_backup_changed_on_disk() {
+    let app = TestApp::spawn().await;
+    let id = app.create_fake_server("Integrity").await;
+    app.post(&format!("/servers/{}/files/write", id), json!({ "path": "data.txt", "content": "intact" })).await;
+
+    let backup = app.create_backup(&id).await;
+    assert!(backup["sha256"].as_str().is_some_and(|hash| hash.len() == 64));
+    let backup_id = backup["id"].as_str().expect("backup id").to_string();
+
+    let (status, report) = app.post(&format!("/backups/{}/verify", backup_id), json!({})).await;
+    assert_eq!(status, 200, "verify failed: {}", report);
+    assert_eq!(report["integrity"], "ok");
+    assert_eq!(report["checksum_matches"], true);
+
+    let path = app.backups_dir().join(backup["filename"].as_str().expect("filename"));
+    let mut bytes = std::fs::read(&path).expect("read archive");
+    let middle = bytes.len() / 2;
+    bytes[middle] ^= 0xff;
+    std::fs::write(&path, bytes).expect("corrupt archive");
+
+    let (_, report) = app.post(&format!("/backups/{}/verify", backup_id), json!({})).await;
+    assert_eq!(report["integrity"], "corrupted");
+    assert_eq!(report["checksum_matches"], false);
+    let (_, listed) = app.get(&format!("/backups/{}", backup_id)).await;
+    assert_eq!(listed["integrity"], "corrupted");
+
+    app.delete(&format!("/backups/{}", backup_id)).await;
+}
+
+#[tokio::test]
+async fn backup_and_restore_fail_fast_while_the_server_is_busy() {
+    let app = TestApp::spawn().await;
+    let id = app.create_fake_server("Busy").await;
+    let backup = app.create_backup(&id).await;
+    let backup_id = backup["id"].as_str().expect("backup id").to_string();
+
+    let guard = app.state.operations
+        .try_acquire(&app.state.process_manager, &id, draveur_manager::services::operation_lock::OP_REINSTALL)
+        .await
+        .expect("server is free");
+
+    let (status, body) = app.post("/backups", json!({ "server_id": id })).await;
+    assert_eq!(status, 409, "backup ran during a reinstall: {}", body);
+    assert_eq!(body["error"], "servers.operation_in_progress");
+    let (status, _) = app.post(&format!("/backups/{}/restore", backup_id), json!({})).await;
+    assert_eq!(status, 409);
+
+    drop(guard);
+    let (status, body) = app.post(&format!("/backups/{}/restore", backup_id), json!({})).await;
+    assert_eq!(status, 200, "restore failed once the server was free: {}", body);
+
+    let (status, _) = app.delete(&format!("/backups/{}", backup_id)).await;
+    assert_eq!(status, 200);
+}
+
+#[tokio::test]
+async fn encrypted_backup_restores_only_with_its_key() {
+    let app = TestApp::spawn().await;
+    let id = app.create_fake_server("Encrypted").await;
+    let key = "7f".repeat(32);
+
+    let (status, body) = app.post("/backups", json!({ "server_id": id, "encryption_key": "not-hex" })).await;
+    assert_eq!(status, 400);
+    assert_eq!(body["error"], "backups.invalid_encryption_key");
+
+    let (status, _) = app.post(&format!("/servers/{}/files/write", id), json!({ "path": "data.txt", "content": "secret world" })).await;
+    assert_eq!(status, 200);
+    let backup = app.create_backup_with(json!({ "server_id": id, "encryption_key": key })).await;
+    assert_eq!(backup["encrypted"], true);
+    let filename = backup["filename"].as_str().expect("filename").to_string();
+    assert!(filename.ends_with(".enc"));
+    let backup_id = backup["id"].as_str().expect("backup id").to_string();
+
+    // Neither the tar headers nor the file name show through
+    let raw = std::fs::read(app.backups_dir().join(&filename)).expect("read archive");
+    assert!(!raw.windows(8).any(|w| w == b"data.txt"));
+
+    app.post(&format!("/servers/{}/files/write", id), json!({ "path": "data.txt", "content": "changed" })).await;
+    let (status, body) = app.post(&format!("/backups/{}/restore", backup_id), json!({})).await;
+    assert_eq!(status, 400);
+    assert_eq!(body["error"], "backups.encryption_key_required");
+    let (status, body) = app.post(&format!("/backups/{}/restore", backup_id), json!({ "encryption_key": "00".repeat(32) })).await;
+    assert_eq!(status, 400);
+    assert_eq!(body["error"], "backups.decryption_failed");
+    let (_, file) = app.get(&format!("/servers/{}/files/read?path=data.txt", id)).await;
+    assert_eq!(file["content"], "changed");
+
+    let (status, body) = app.post(&format!("/backups/{}/restore", backup_id), json!({ "encryption_key": key })).await;
+    assert_eq!(status, 200, "restore failed: {}", body);
+    let (_, file) = app.get(&format!("/servers/{}/files/read?path=data.txt", id)).await;
+    assert_eq!(file["content"], "secret world");
+
+    app.delete(&format!("/backups/{}", backup_id)).await;
+}
+
+#[tokio::test]
+async fn backup_entries_are_listed_without_restoring() {
+    let app = TestApp::spawn().await;
+    let id = app.create_fake_server("Entries").await;
+    // The write endpoint doesn't create parent directories
+    let (_, server) = app.get(&format!("/servers/{}", id)).await;
+    let working_dir = std::path::PathBuf::from(server["working_dir"].as_str().expect("working dir"));
+    std::fs::create_dir_all(working_dir.join("universe/worlds/default")).unwrap();
+    let (status, _) = app.post(&format!("/servers/{}/files/write", id), json!({
+        "path": "universe/worlds/default/chunk.dat",
+        "content": "0123456789",
+    })).await;
+    assert_eq!(status, 200);
+    let backup = app.create_backup(&id).await;
+    let backup_id = backup["id"].as_str().expect("backup id").to_string();
+
+    let (status, body) = app.get(&format!("/backups/{}/entries?prefix=universe", backup_id)).await;
+    assert_eq!(status, 200, "listing failed: {}", body);
+    let entries = body["entries"].as_array().expect("entries");
+    let chunk = entries.iter().find(|e| e["path"] == "universe/worlds/default/chunk.dat").expect("world file listed");
+    assert_eq!(chunk["size"], 10);
+    assert_eq!(chunk["is_dir"], false);
+    assert!(entries.iter().all(|e| e["path"].as_str().is_some_and(|p| p.starts_with("universe"))));
+    assert!(body["total_files"].as_u64().is_some_and(|n| n >= 1));
+
+    let (status, body) = app.get(&format!("/backups/{}/entries?limit=1", backup_id)).await;
+    assert_eq!(status, 200);
+    assert_eq!(body["entries"].as_array().map(Vec::len), Some(1));
+    assert_eq!(body["truncated"], true);
+
+    app.delete(&format!("/backups/{}", backup_id)).await;
+}
+
+#[tokio::test]
+async fn selective_restore_only_touches_the_given_paths() {
+    let app = TestApp::spawn().await;
+    let id = app.create_fake_server("Selective").await;
+    let (_, server) = app.get(&format!("/servers/{}", id)).await;
+    std::fs::create_dir_all(std::path::Path::new(server["working_dir"].as_str().expect("working dir")).join("universe")).unwrap();
+    let write = |path: &'static str, content: &'static str| {
+        let app = &app;
+        let id = &id;
+        async move {
+            let (status, body) = app.post(&format!("/servers/{}/files/write", id), json!({ "path": path, "content": content })).await;
+            assert_eq!(status, 200, "write failed: {}", body);
+        }
+    };
+
+    write("universe/players.json", "old players").await;
+    write("settings.json", "old settings").await;
+    let backup = app.create_backup(&id).await;
+    let backup_id = backup["id"].as_str().expect("backup id").to_string();
+    write("universe/players.json", "new players").await;
+    write("settings.json", "new settings").await;
+
+    let (status, body) = app.post(&format!("/backups/{}/restore", backup_id), json!({ "paths": ["../etc"] })).await;
+    assert_eq!(status, 400);
+    assert_eq!(body["error"], "backups.invalid_restore_path");
+    let (status, _) = app.post(&format!("/backups/{}/restore", backup_id), json!({ "paths": ["missing"] })).await;
+    assert_eq!(status, 404);
+
+    let (status, body) = app.post(&format!("/backups/{}/restore", backup_id), json!({ "paths": ["universe/"] })).await;
+    assert_eq!(status, 200, "restore failed: {}", body);
+    let (_, file) = app.get(&format!("/servers/{}/files/read?path=universe/players.json", id)).await;
+    assert_eq!(file["content"], "old players");
+    let (_, file) = app.get(&format!("/servers/{}/files/read?path=settings.json", id)).await;
+    assert_eq!(file["content"], "new settings");
+
+    app.delete(&format!("/backups/{}", backup_id)).await;
+}
+
+#[tokio::test]
+async fn backup_files_are_named_from_the_server_template() {
+    let app = TestApp::spawn().await;
+    let id = app.create_fake_server("My World!").await;
+    sqlx::query("UPDATE servers SET backup_prefix = 'nightly', backup_filename_template = '{prefix}-{server_name}_{date}' WHERE id = ?")
+        .bind(&id)
+        .execute(&app.state.pool)
+        .await
+        .expect("set filename template");
+
+    let first = app.create_backup(&id).await;
+    let second = app.create_backup(&id).await;
+    let date = chrono::Utc::now().format("%Y%m%d").to_string();
+    assert_eq!(first["filename"], format!("nightly-My_World_{}.tar.gz", date));
+    assert_eq!(second["filename"], format!("nightly-My_World_{}_2.tar.gz", date));
+
+    for backup in [first, second] {
+        app.delete(&format!("/backups/{}", backup["id"].as_str().expect("backup id"))).await;
+    }
+}
+
+#[tokio::test]
+async fn oldest_backups_are_pruned_beyond_the_storage_quota() {
+    let app = TestApp::spawn().await;
+    let first_server = app.create_fake_server("Quota A").await;
+    let second_server = app.create_fake_server("Quota B").await;
+    for (key, value) in [("backup_compression", "none"), ("backup_quota_mb", "1"), ("backup_quota_min_per_server", "1")] {
+        sqlx::query("INSERT INTO settings (key, value, updated_at) VALUES (?, ?, datetime('now'))")
+            .bind(key)
+            .bind(value)
+            .execute(&app.state.pool)
+            .await
+            .expect("set quota");
+    }
+    // Uncompressed, each backup takes ~300 KB: three fit in 1 MB, four don't
+    let content = "x".repeat(300 * 1024);
+    for id in [&first_server, &second_server] {
+        let (status, _) = app.post(&format!("/servers/{}/files/write", id), json!({ "path": "world.dat", "content": content })).await;
+        assert_eq!(status, 200);
+    }
+
+    let oldest = app.create_backup(&first_server).await;
+    let only_b = app.create_backup(&second_server).await;
+    app.create_backup(&first_server).await;
+    app.create_backup(&first_server).await;
+
+    let (status, _) = app.get(&format!("/backups/{}", oldest["id"].as_str().expect("backup id"))).await;
+    assert_eq!(status, 404, "oldest backup survived the quota");
+    let (status, _) = app.get(&format!("/backups/{}", only_b["id"].as_str().expect("backup id"))).await;
+    assert_eq!(status, 200, "a server's only backup was pruned");
+
+    let (_, list) = app.get("/backups").await;
+    for backup in list.as_array().expect("backups") {
+        app.delete(&format!("/backups/{}", backup["id"].as_str().expect("backup id"))).await;
+    }
+}
@@ -0,0 +1,40 @@
+mod common;
+
+use common::TestApp;
+use reqwest::Method;
+use serde_json::json;
+
+#[tokio::test]
+async fn bulk_settings_apply_to_listed_and_tagged_servers() {
+    let app = TestApp::spawn().await;
+    let admin = app.admin_token.clone();
+    let listed = app.create_fake_server("Listed").await;
+    let tagged = app.create_fake_server("Tagged").await;
+    let untouched = app.create_fake_server("Untouched").await;
+
+    let (status, _) = app.call(Method::PATCH, &format!("/servers/{}", tagged), &admin, Some(json!({ "tags": ["Event"] }))).await;
+    assert_eq!(status, 200);
+    let (_, before) = app.get(&format!("/servers/{}", untouched)).await;
+
+    let (status, body) = app.call(Method::PATCH, "/servers/bulk-settings", &admin, Some(json!({
+        "server_ids": [listed],
+        "tag": "event",
+        "settings": { "backup_max_backups": 3 },
+    }))).await;
+    assert_eq!(status, 200, "bulk update failed: {}", body);
+    assert_eq!(body["updated"], 2);
+    for id in [&listed, &tagged] {
+        let (_, server) = app.get(&format!("/servers/{}", id)).await;
+        assert_eq!(server["backup_max_backups"], 3, "server {}", id);
+    }
+    let (_, after) = app.get(&format!("/servers/{}", untouched)).await;
+    assert_eq!(after["backup_max_backups"], before["backup_max_backups"]);
+
+    // A tag nobody carries selects nothing
+    let (status, body) = app.call(Method::PATCH, "/servers/bulk-settings", &admin, Some(json!({
+        "tag": "missing",
+        "settings": { "watchdog_enabled": false },
+    }))).await;
+    assert_eq!(status, 400);
+    assert_eq!(body["error"], "servers.bulk_no_servers");
+}
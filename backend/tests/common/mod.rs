@@ -0,0 +1,233 @@
+//! Shared harness: the panel served on a random local port, backed by a temporary SQLite
+//! database, with servers running the bundled `fake-game-server` binary.
+
+#![allow(dead_code)]
+
+use std::future::Future;
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+use serde_json::{json, Value};
+use tempfile::TempDir;
+
+use draveur_manager::config::Settings;
+use draveur_manager::services::events::{self, EventBus};
+use draveur_manager::services::{OperationLocks, ProcessManager};
+use draveur_manager::{app, db, AppState};
+
+pub const FAKE_SERVER_BIN: &str = env!("CARGO_BIN_EXE_fake-game-server");
+
+pub struct TestApp {
+    pub base_url: String,
+    pub client: reqwest::Client,
+    pub state: AppState,
+    /// Session of the first registered user, the admin; `client` sends it by default
+    pub admin_token: String,
+    pub admin_id: String,
+    /// Holds the database and the server directories, removed on drop
+    pub dir: TempDir,
+}
+
+impl TestApp {
+    pub async fn spawn() -> Self {
+        let dir = tempfile::tempdir().expect("create temp dir");
+        let settings = Settings {
+            host: "127.0.0.1".into(),
+            port: 0,
+            database_url: format!("sqlite:{}?mode=rwc", dir.path().join("database.db").display()),
+            uploads_dir: dir.path().join("uploads").to_string_lossy().into_owned(),
+            backups_dir: dir.path().join("backups"),
+            dev_mode: true,
+            allowed_origins: vec!["https://status.example.com".into()],
+            cors_permissive: false,
+            tls: None,
+        };
+
+        let pool = db::init_pool(&settings.database_url).await.expect("open database");
+        db::run_migrations(&pool).await.expect("run migrations");
+        // Server directories must lie under it
+        sqlx::query("INSERT INTO settings (key, value, updated_at) VALUES ('servers_dir', ?, datetime('now'))")
+            .bind(dir.path().join("servers").to_string_lossy().as_ref())
+            .execute(&pool)
+            .await
+            .expect("set servers_dir");
+
+        let bus = EventBus::default();
+        events::start_consumers(&pool, &bus);
+        let state = AppState {
+            pool: pool.clone(),
+            process_manager: ProcessManager::new(Some(pool), bus.clone()),
+            settings: Arc::new(settings),
+            operations: OperationLocks::default(),
+            events: bus,
+            rate_limiter: Default::default(),
+        };
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.expect("bind test listener");
+        let addr = listener.local_addr().expect("listener address");
+        let router = app(state.clone());
+        tokio::spawn(async move {
+            axum::serve(listener, router.into_make_service_with_connect_info::<SocketAddr>()).await.expect("serve test app");
+        });
+
+        let base_url = format!("http://{}/api/v1", addr);
+        let registered: Value = reqwest::Client::new()
+            .post(format!("{}/auth/register", base_url))
+            .json(&json!({ "username": "admin", "password": "password123" }))
+            .send()
+            .await
+            .expect("register admin")
+            .json()
+            .await
+            .expect("register response");
+        let admin_token = registered["token"].as_str().expect("admin token").to_string();
+        let admin_id = registered["user"]["id"].as_str().expect("admin id").to_string();
+
+        let mut headers = reqwest::header::HeaderMap::new();
+        let bearer = format!("Bearer {}", admin_token).parse().expect("authorization header");
+        headers.insert(reqwest::header::AUTHORIZATION, bearer);
+        let client = reqwest::Client::builder().default_headers(headers).build().expect("build client");
+
+        Self {
+            base_url,
+            client,
+            state,
+            admin_token,
+            admin_id,
+            dir,
+        }
+    }
+
+    pub fn url(&self, path: &str) -> String {
+        format!("{}{}", self.base_url, path)
+    }
+
+    /// WebSockets can't carry the header, the session goes in the query
+    pub fn ws_url(&self, path: &str) -> String {
+        format!("{}?token={}", self.url(path).replacen("http://", "ws://", 1), self.admin_token)
+    }
+
+    pub fn servers_dir(&self) -> PathBuf {
+        self.dir.path().join("servers")
+    }
+
+    pub fn backups_dir(&self) -> PathBuf {
+        self.dir.path().join("backups")
+    }
+
+    pub async fn get(&self, path: &str) -> (u16, Value) {
+        let response = self.client.get(self.url(path)).send().await.expect("GET request");
+        Self::decode(response).await
+    }
+
+    pub async fn post(&self, path: &str, body: Value) -> (u16, Value) {
+        let response = self.client.post(self.url(path)).json(&body).send().await.expect("POST request");
+        Self::decode(response).await
+    }
+
+    pub async fn put(&self, path: &str, body: Value) -> (u16, Value) {
+        let response = self.client.put(self.url(path)).json(&body).send().await.expect("PUT request");
+        Self::decode(response).await
+    }
+
+    pub async fn delete(&self, path: &str) -> (u16, Value) {
+        let response = self.client.delete(self.url(path)).send().await.expect("DELETE request");
+        Self::decode(response).await
+    }
+
+    /// A request as another user, `bearer` being their session or API token
+    pub async fn call(&self, method: reqwest::Method, path: &str, bearer: &str, body: Option<Value>) -> (u16, Value) {
+        let mut request = self.client.request(method, self.url(path)).bearer_auth(bearer);
+        if let Some(body) = body {
+            request = request.json(&body);
+        }
+        Self::decode(request.send().await.expect("request")).await
+    }
+
+    async fn decode(response: reqwest::Response) -> (u16, Value) {
+        let status = response.status().as_u16();
+        let body = response.json().await.unwrap_or(Value::Null);
+        (status, body)
+    }
+
+    /// Create a user as the admin and log it in, returning its session and id
+    pub async fn create_user(&self, username: &str, role: &str) -> (String, String) {
+        let (status, body) = self.post("/users", json!({ "username": username, "password": "password123", "role": role })).await;
+        assert_eq!(status, 201, "create user failed: {}", body);
+        let id = body["id"].as_str().expect("user id").to_string();
+        let (status, body) = self.post("/auth/login", json!({ "username": username, "password": "password123" })).await;
+        assert_eq!(status, 200, "login failed: {}", body);
+        (body["token"].as_str().expect("token").to_string(), id)
+    }
+
+    /// Create a server running the fake game server, returning its id
+    pub async fn create_fake_server(&self, name: &str) -> String {
+        let (status, body) = self.post("/servers", json!({
+            "name": name,
+            "game_type": "fake",
+            "executable_path": FAKE_SERVER_BIN,
+            "working_dir": self.servers_dir().to_string_lossy(),
+            "launch_mode": "command",
+            "command_template": format!("{} {{port}}", FAKE_SERVER_BIN),
+        })).await;
+        assert_eq!(status, 201, "create server failed: {}", body);
+        body["id"].as_str().expect("server id in response").to_string()
+    }
+
+    /// Start a server and wait until its console reported ready
+    pub async fn start_server(&self, id: &str) {
+        let (status, body) = self.post(&format!("/servers/{}/start", id), json!({})).await;
+        assert_eq!(status, 200, "start failed: {}", body);
+        wait_for("server running", || async { self.server_status(id).await == "running" }).await;
+    }
+
+    /// Back up a server and wait for its job, returning the backup
+    pub async fn create_backup(&self, server_id: &str) -> Value {
+        self.create_backup_with(json!({ "server_id": server_id })).await
+    }
+
+    /// `create_backup` with a custom request body
+    pub async fn create_backup_with(&self, request: Value) -> Value {
+        let (status, job) = self.post("/backups", request).await;
+        assert_eq!(status, 202, "backup not started: {}", job);
+        let job_id = job["job_id"].as_str().expect("job id").to_string();
+
+        wait_for("backup job", || async {
+            let (_, job) = self.get(&format!("/backups/jobs/{}", job_id)).await;
+            assert_ne!(job["status"], "failed", "backup failed: {}", job);
+            job["status"] == "done"
+        }).await;
+
+        let (status, backup) = self.get(&format!("/backups/{}", job["backup_id"].as_str().expect("backup id"))).await;
+        assert_eq!(status, 200, "backup missing: {}", backup);
+        backup
+    }
+
+    pub async fn server_status(&self, id: &str) -> String {
+        let (_, body) = self.get(&format!("/servers/{}", id)).await;
+        body["status"].as_str().unwrap_or_default().to_string()
+    }
+
+    pub async fn send_command(&self, id: &str, command: &str) {
+        let (status, body) = self.post(&format!("/servers/{}/command", id), json!({ "command": command })).await;
+        assert_eq!(status, 200, "command failed: {}", body);
+    }
+}
+
+/// Poll `check` until it holds, failing the test after 10 seconds
+pub async fn wait_for<F, Fut>(what: &str, mut check: F)
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = bool>,
+{
+    let deadline = tokio::time::Instant::now() + Duration::from_secs(10);
+    while tokio::time::Instant::now() < deadline {
+        if check().await {
+            return;
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+    panic!("timed out waiting for {}", what);
+}
@@ -0,0 +1,142 @@
+mod common;
+
+use std::time::Duration;
+
+use common::TestApp;
+use futures::{SinkExt, StreamExt};
+use serde_json::json;
+use tokio_tungstenite::tungstenite::Message;
+
+#[tokio::test]
+async fn console_streams_output_and_accepts_commands() {
+    let app = TestApp::spawn().await;
+    let id = app.create_fake_server("Console").await;
+    app.start_server(&id).await;
+
+    let (mut socket, _) = tokio_tungstenite::connect_async(app.ws_url(&format!("/ws/console/{}", id)))
+        .await
+        .expect("connect console websocket");
+
+    // Commands typed in the console go to the server's stdin
+    socket.send(Message::Text("ping from ws".into())).await.expect("send command");
+
+    let echoed = tokio::time::timeout(Duration::from_secs(10), async {
+        while let Some(Ok(message)) = socket.next().await {
+            if let Message::Text(text) = message {
+                if text.contains("Received command: ping from ws") {
+                    return true;
+                }
+            }
+        }
+        false
+    })
+    .await
+    .unwrap_or(false);
+    assert!(echoed, "command output never reached the websocket");
+
+    app.post(&format!("/servers/{}/stop", id), json!({})).await;
+}
+
+#[tokio::test]
+async fn console_sees_lines_sent_through_the_api() {
+    let app = TestApp::spawn().await;
+    let id = app.create_fake_server("Console API").await;
+    app.start_server(&id).await;
+
+    let (mut socket, _) = tokio_tungstenite::connect_async(app.ws_url(&format!("/ws/console/{}", id)))
+        .await
+        .expect("connect console websocket");
+
+    app.send_command(&id, "/join Bob").await;
+
+    let joined = tokio::time::timeout(Duration::from_secs(10), async {
+        while let Some(Ok(message)) = socket.next().await {
+            if let Message::Text(text) = message {
+                if text.contains("Bob joined the game") {
+                    return true;
+                }
+            }
+        }
+        false
+    })
+    .await
+    .unwrap_or(false);
+    assert!(joined, "join line never reached the websocket");
+
+    app.post(&format!("/servers/{}/stop", id), json!({})).await;
+}
+
+#[tokio::test]
+async fn console_of_stopped_server_shows_backup_progress() {
+    let app = TestApp::spawn().await;
+    let id = app.create_fake_server("Console Backup").await;
+
+    let (mut socket, _) = tokio_tungstenite::connect_async(app.ws_url(&format!("/ws/console/{}", id)))
+        .await
+        .expect("connect console websocket");
+
+    let (status, backup) = app.post("/backups", json!({ "server_id": id })).await;
+    assert_eq!(status, 202, "backup not started: {}", backup);
+
+    let finished = tokio::time::timeout(Duration::from_secs(10), async {
+        while let Some(Ok(message)) = socket.next().await {
+            if let Message::Text(text) = message {
+                if let Some(progress) = text.strip_prefix("[BACKUP_PROGRESS]: ") {
+                    let progress: serde_json::Value = serde_json::from_str(progress).expect("progress json");
+                    if progress["status"] == "done" {
+                        return progress["backup_id"] == backup["backup_id"] && progress["percent"] == 100;
+                    }
+                }
+            }
+        }
+        false
+    })
+    .await
+    .unwrap_or(false);
+    assert!(finished, "backup progress never reached the websocket");
+
+    app.delete(&format!("/backups/{}", backup["backup_id"].as_str().expect("backup id"))).await;
+}
+
+#[tokio::test]
+async fn running_server_saves_its_world_before_backup() {
+    let app = TestApp::spawn().await;
+    let id = app.create_fake_server("Flush").await;
+    sqlx::query(
+        "UPDATE servers SET backup_save_command = '/save', backup_save_confirm = 'Received command: /save$',
+         backup_autosave_off_command = '/autosave off', backup_autosave_on_command = '/autosave on' WHERE id = ?"
+    )
+    .bind(&id)
+    .execute(&app.state.pool)
+    .await
+    .expect("set flush commands");
+    app.start_server(&id).await;
+
+    let (mut socket, _) = tokio_tungstenite::connect_async(app.ws_url(&format!("/ws/console/{}", id)))
+        .await
+        .expect("connect console websocket");
+    let backup = app.create_backup(&id).await;
+
+    // Order seen on the console: pause, save, archive, resume
+    let expected = ["Received command: /autosave off", "Received command: /save", "\"status\":\"done\"", "Received command: /autosave on"];
+    let seen = tokio::time::timeout(Duration::from_secs(10), async {
+        let mut next = 0;
+        while let Some(Ok(message)) = socket.next().await {
+            if let Message::Text(text) = message {
+                if text.contains(expected[next]) {
+                    next += 1;
+                    if next == expected.len() {
+                        break;
+                    }
+                }
+            }
+        }
+        next
+    })
+    .await
+    .unwrap_or(0);
+    assert_eq!(seen, expected.len(), "flush sequence incomplete");
+
+    app.delete(&format!("/backups/{}", backup["id"].as_str().expect("backup id"))).await;
+    app.post(&format!("/servers/{}/stop", id), json!({})).await;
+}
@@ -0,0 +1,45 @@
+mod common;
+
+use common::TestApp;
+use serde_json::json;
+
+async fn preflight(app: &TestApp, origin: &str) -> Option<String> {
+    let response = reqwest::Client::new()
+        .request(reqwest::Method::OPTIONS, app.url("/servers"))
+        .header("Origin", origin)
+        .header("Access-Control-Request-Method", "GET")
+        .header("Access-Control-Request-Headers", "authorization")
+        .send()
+        .await
+        .expect("preflight request");
+    response.headers()
+        .get("access-control-allow-origin")
+        .map(|v| v.to_str().unwrap().to_string())
+}
+
+#[tokio::test]
+async fn only_listed_origins_are_allowed() {
+    let app = TestApp::spawn().await;
+
+    assert_eq!(preflight(&app, "https://status.example.com").await.as_deref(), Some("https://status.example.com"));
+    assert_eq!(preflight(&app, "https://evil.example").await, None);
+
+    let response = app.client.put(app.url("/settings"))
+        .json(&json!({ "allowed_origins": ["https://evil.example/"] }))
+        .send()
+        .await
+        .expect("request");
+    assert_eq!(response.status().as_u16(), 200);
+    assert_eq!(preflight(&app, "https://evil.example").await.as_deref(), Some("https://evil.example"));
+
+    let (status, body) = app.get("/settings").await;
+    assert_eq!(status, 200);
+    assert_eq!(body["allowed_origins"], json!(["https://evil.example"]));
+
+    let response = app.client.put(app.url("/settings"))
+        .json(&json!({ "allowed_origins": ["evil.example/path"] }))
+        .send()
+        .await
+        .expect("request");
+    assert_eq!(response.status().as_u16(), 400);
+}
@@ -0,0 +1,48 @@
+mod common;
+
+use common::TestApp;
+use serde_json::json;
+
+#[tokio::test]
+async fn approved_elevation_grants_admin_rights_until_revoked() {
+    let app = TestApp::spawn().await;
+    let (admin, admin_id) = (app.admin_token.clone(), app.admin_id.clone());
+    let (operator, _) = app.create_user("operator", "user").await;
+    let id = app.create_fake_server("Break glass").await;
+
+    let transfer = json!({ "to_user_id": admin_id });
+    let (status, _) = app.call(reqwest::Method::POST, &format!("/servers/{}/transfer", id), &operator, Some(transfer.clone())).await;
+    assert_eq!(status, 403);
+
+    let (status, grant) = app.call(reqwest::Method::POST, &format!("/servers/{}/elevation", id), &operator, Some(json!({
+        "minutes": 30,
+        "reason": "server hung at 3am",
+    }))).await;
+    assert_eq!(status, 200, "request failed: {}", grant);
+    assert_eq!(grant["status"], "pending");
+    let grant_id = grant["id"].as_str().expect("grant id").to_string();
+
+    // Only admins approve
+    let (status, _) = app.call(reqwest::Method::POST, &format!("/servers/elevation/{}/approve", grant_id), &operator, None).await;
+    assert_eq!(status, 403);
+    let (status, grant) = app.call(reqwest::Method::POST, &format!("/servers/elevation/{}/approve", grant_id), &admin, None).await;
+    assert_eq!(status, 200, "approve failed: {}", grant);
+    assert_eq!(grant["status"], "active");
+
+    let (status, body) = app.call(reqwest::Method::POST, &format!("/servers/{}/transfer", id), &operator, Some(transfer.clone())).await;
+    assert_eq!(status, 200, "elevated transfer failed: {}", body);
+
+    let (status, _) = app.call(reqwest::Method::POST, &format!("/servers/elevation/{}/revoke", grant_id), &operator, None).await;
+    assert_eq!(status, 200);
+    let (status, _) = app.call(reqwest::Method::POST, &format!("/servers/{}/transfer", id), &operator, Some(transfer)).await;
+    assert_eq!(status, 403);
+
+    let events: Vec<String> = sqlx::query_scalar(
+        "SELECT event_type FROM server_events WHERE server_id = ? AND event_type LIKE 'elevation_%' ORDER BY id"
+    )
+    .bind(&id)
+    .fetch_all(&app.state.pool)
+    .await
+    .expect("read events");
+    assert_eq!(events, vec!["elevation_requested", "elevation_granted", "elevation_revoked"]);
+}
@@ -0,0 +1,44 @@
+mod common;
+
+use common::TestApp;
+use serde_json::{json, Value};
+
+#[tokio::test]
+async fn errors_carry_a_code_key_and_detail() {
+    let app = TestApp::spawn().await;
+
+    let (status, body) = app.get("/servers/missing").await;
+    assert_eq!(status, 404);
+    assert_eq!(body["code"], "SERVER_NOT_FOUND");
+    assert_eq!(body["key"], "servers.not_found");
+    assert_eq!(body["error"], "servers.not_found");
+    assert!(body["detail"].is_null());
+
+    let response = reqwest::Client::new().get(app.url("/servers")).send().await.expect("list servers");
+    assert_eq!(response.status().as_u16(), 401);
+    let body: Value = response.json().await.expect("body");
+    assert_eq!(body["code"], "AUTH_MISSING_AUTH_HEADER");
+
+    // The cause of a malformed upload comes as the detail
+    let response = app.client.post(app.url("/upload/image"))
+        .header("content-type", "multipart/form-data; boundary=draveur")
+        .body("not a multipart body")
+        .send()
+        .await
+        .expect("upload");
+    assert_eq!(response.status().as_u16(), 400);
+    let body: Value = response.json().await.expect("body");
+    assert_eq!(body["code"], "UPLOAD_INVALID_MULTIPART");
+    assert_eq!(body["key"], "upload.invalid_multipart");
+    assert!(body["detail"].as_str().is_some_and(|d| !d.is_empty()), "{}", body);
+}
+
+#[tokio::test]
+async fn a_bad_s3_endpoint_has_its_own_code() {
+    let app = TestApp::spawn().await;
+
+    let (status, body) = app.put("/settings", json!({ "s3_endpoint": "s3.example.com" })).await;
+    assert_eq!(status, 400);
+    assert_eq!(body["code"], "SETTINGS_INVALID_S3_ENDPOINT");
+    assert_eq!(body["key"], "settings.invalid_s3_endpoint");
+}
@@ -0,0 +1,60 @@
+mod common;
+
+use std::time::Duration;
+
+use common::TestApp;
+
+/// Read the stream until an event containing every `needles`, failing after 10 seconds
+async fn expect_event(response: &mut reqwest::Response, buffer: &mut String, needles: &[&str]) {
+    let found = tokio::time::timeout(Duration::from_secs(10), async {
+        loop {
+            if let Some(end) = buffer.find("\n\n") {
+                let event: String = buffer.drain(..end + 2).collect();
+                if needles.iter().all(|n| event.contains(n)) {
+                    return;
+                }
+                continue;
+            }
+            let chunk = response.chunk().await.expect("read stream").expect("stream ended");
+            buffer.push_str(&String::from_utf8_lossy(&chunk));
+        }
+    })
+    .await;
+    assert!(found.is_ok(), "no event with {:?}", needles);
+}
+
+#[tokio::test]
+async fn stream_reports_status_transitions() {
+    let app = TestApp::spawn().await;
+    let id = app.create_fake_server("Streamed").await;
+
+    // EventSource can't send headers, the session goes in the query
+    let url = format!("{}?token={}", app.url("/events"), app.admin_token);
+    let mut response = reqwest::Client::new()
+        .get(&url)
+        .header("Accept", "text/event-stream")
+        .send()
+        .await
+        .expect("open stream");
+    assert_eq!(response.status(), 200);
+    assert!(response.headers()["content-type"].to_str().unwrap().starts_with("text/event-stream"));
+
+    let mut buffer = String::new();
+    app.start_server(&id).await;
+    expect_event(&mut response, &mut buffer, &["event: status", &id, "\"running\""]).await;
+
+    app.post(&format!("/servers/{}/stop", id), serde_json::json!({})).await;
+    expect_event(&mut response, &mut buffer, &["event: status", &id, "\"stopped\""]).await;
+}
+
+#[tokio::test]
+async fn stream_requires_a_session() {
+    let app = TestApp::spawn().await;
+    let response = reqwest::Client::new()
+        .get(app.url("/events"))
+        .header("Accept", "text/event-stream")
+        .send()
+        .await
+        .expect("request");
+    assert_eq!(response.status(), 401);
+}
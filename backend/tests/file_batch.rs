@@ -0,0 +1,63 @@
+mod common;
+
+use std::path::PathBuf;
+
+use common::TestApp;
+use serde_json::json;
+
+#[tokio::test]
+async fn batch_file_operations_run_all_or_nothing() {
+    let app = TestApp::spawn().await;
+    let id = app.create_fake_server("tidy").await;
+    let (_, server) = app.get(&format!("/servers/{}", id)).await;
+    let root = PathBuf::from(server["working_dir"].as_str().expect("working dir"));
+    std::fs::create_dir_all(root.join("cleanup")).unwrap();
+    for name in ["a.txt", "b.txt", "c.txt", "d.txt", "e.txt"] {
+        std::fs::write(root.join("cleanup").join(name), name).unwrap();
+    }
+    let batch = format!("/servers/{}/files/batch", id);
+
+    let (status, body) = app.post(&batch, json!({ "operations": [
+        { "op": "delete", "path": "cleanup/a.txt" },
+        { "op": "move", "from": "cleanup/b.txt", "to": "archive/b.txt" },
+    ] })).await;
+    assert_eq!(status, 200, "{}", body);
+    assert_eq!((body["deleted"].as_u64(), body["moved"].as_u64()), (Some(1), Some(1)));
+    assert!(!root.join("cleanup/a.txt").exists());
+    assert_eq!(std::fs::read_to_string(root.join("archive/b.txt")).unwrap(), "b.txt");
+
+    // Checked before anything runs
+    let (status, body) = app.post(&batch, json!({ "operations": [
+        { "op": "delete", "path": "cleanup/c.txt" },
+        { "op": "delete", "path": "cleanup/missing.txt" },
+    ] })).await;
+    assert_eq!((status, body["key"].as_str(), body["detail"].as_str()), (404, Some("files.not_found"), Some("operation 1")));
+    assert!(root.join("cleanup/c.txt").exists());
+
+    let (status, body) = app.post(&batch, json!({ "operations": [
+        { "op": "move", "from": "cleanup/c.txt", "to": "cleanup/d.txt" },
+    ] })).await;
+    assert_eq!((status, body["code"].as_str()), (409, Some("FILE_ALREADY_EXISTS")));
+
+    let (status, body) = app.post(&batch, json!({ "operations": [
+        { "op": "delete", "path": "cleanup/c.txt" },
+        { "op": "move", "from": "cleanup/c.txt", "to": "cleanup/f.txt" },
+    ] })).await;
+    assert_eq!((status, body["code"].as_str()), (400, Some("FILE_BATCH_CONFLICT")));
+
+    // A step failing on disk puts back what the previous ones did
+    let (status, body) = app.post(&batch, json!({ "operations": [
+        { "op": "delete", "path": "cleanup/d.txt" },
+        { "op": "move", "from": "cleanup/e.txt", "to": "cleanup/old" },
+        { "op": "move", "from": "cleanup/c.txt", "to": "cleanup/old/c.txt" },
+    ] })).await;
+    assert_eq!((status, body["code"].as_str()), (409, Some("FILE_BATCH_FAILED")), "{}", body);
+    assert_eq!(std::fs::read_to_string(root.join("cleanup/d.txt")).unwrap(), "d.txt");
+    assert!(root.join("cleanup/e.txt").exists() && root.join("cleanup/c.txt").exists());
+    assert!(!root.join("cleanup/old").exists());
+    assert!(!std::fs::read_dir(&root).unwrap().flatten().any(|e| e.file_name().to_string_lossy().starts_with(".batch-")));
+
+    let (_, entries) = app.get("/system/audit-log").await;
+    assert_eq!(entries[0]["action"], "file_move");
+    assert!(entries[0]["detail"].as_str().unwrap().ends_with("cleanup/b.txt -> archive/b.txt"));
+}
@@ -0,0 +1,140 @@
+mod common;
+
+use common::TestApp;
+use serde_json::json;
+
+#[tokio::test]
+async fn write_read_list_and_delete_files() {
+    let app = TestApp::spawn().await;
+    let id = app.create_fake_server("Files").await;
+
+    let (status, body) = app.post(&format!("/servers/{}/files/write", id), json!({
+        "path": "notes.txt",
+        "content": "hello world",
+    })).await;
+    assert_eq!(status, 200, "write failed: {}", body);
+
+    let (status, body) = app.get(&format!("/servers/{}/files/read?path=notes.txt", id)).await;
+    assert_eq!(status, 200);
+    assert_eq!(body["content"], "hello world");
+
+    let (status, body) = app.get(&format!("/servers/{}/files", id)).await;
+    assert_eq!(status, 200);
+    let entries = body["entries"].as_array().expect("directory entries");
+    assert!(entries.iter().any(|e| e["name"] == "notes.txt" && e["is_dir"].as_bool() == Some(false)), "{}", body);
+
+    let (status, _) = app.post(&format!("/servers/{}/files/delete", id), json!({ "path": "notes.txt" })).await;
+    assert_eq!(status, 200);
+
+    let (status, _) = app.get(&format!("/servers/{}/files/read?path=notes.txt", id)).await;
+    assert_eq!(status, 404);
+}
+
+#[tokio::test]
+async fn paths_outside_the_server_are_rejected() {
+    let app = TestApp::spawn().await;
+    let id = app.create_fake_server("Escape").await;
+
+    let (status, _) = app.post(&format!("/servers/{}/files/write", id), json!({
+        "path": "/tmp/outside.txt",
+        "content": "nope",
+    })).await;
+    assert_eq!(status, 400);
+}
+
+#[tokio::test]
+async fn parent_components_cannot_leave_the_server() {
+    let app = TestApp::spawn().await;
+    let id = app.create_fake_server("Traversal").await;
+    std::fs::write(app.dir.path().join("secret.txt"), "secret").unwrap();
+
+    let (status, _) = app.get(&format!("/servers/{}/files/read?path=../../secret.txt", id)).await;
+    assert_eq!(status, 400);
+    let (status, _) = app.get(&format!("/servers/{}/files?path=../..", id)).await;
+    assert_eq!(status, 400);
+    let (status, _) = app.post(&format!("/servers/{}/files/write", id), json!({
+        "path": "../../planted.txt",
+        "content": "nope",
+    })).await;
+    assert_eq!(status, 400);
+    assert!(!app.dir.path().join("planted.txt").exists());
+    let (status, _) = app.post(&format!("/servers/{}/files/delete", id), json!({ "path": "../../secret.txt" })).await;
+    assert_eq!(status, 400);
+    assert!(app.dir.path().join("secret.txt").exists());
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn symlinks_cannot_leave_the_server() {
+    let app = TestApp::spawn().await;
+    let id = app.create_fake_server("Symlink").await;
+    let working_dir: String = sqlx::query_scalar("SELECT working_dir FROM servers WHERE id = ?")
+        .bind(&id)
+        .fetch_one(&app.state.pool)
+        .await
+        .unwrap();
+    let outside = app.dir.path().join("outside");
+    std::fs::create_dir_all(&outside).unwrap();
+    std::fs::write(outside.join("secret.txt"), "secret").unwrap();
+    std::fs::create_dir_all(&working_dir).unwrap();
+    std::os::unix::fs::symlink(&outside, std::path::Path::new(&working_dir).join("escape")).unwrap();
+    std::os::unix::fs::symlink(outside.join("missing.txt"), std::path::Path::new(&working_dir).join("dangling")).unwrap();
+
+    let (status, _) = app.get(&format!("/servers/{}/files?path=escape", id)).await;
+    assert_eq!(status, 400);
+    let (status, _) = app.get(&format!("/servers/{}/files/read?path=escape/secret.txt", id)).await;
+    assert_eq!(status, 400);
+    let (status, _) = app.post(&format!("/servers/{}/files/write", id), json!({
+        "path": "escape/planted.txt",
+        "content": "nope",
+    })).await;
+    assert_eq!(status, 400);
+    let (status, _) = app.post(&format!("/servers/{}/files/write", id), json!({
+        "path": "dangling",
+        "content": "nope",
+    })).await;
+    assert_eq!(status, 400);
+    assert!(!outside.join("planted.txt").exists() && !outside.join("missing.txt").exists());
+    let (status, _) = app.post(&format!("/servers/{}/files/delete", id), json!({ "path": "escape/secret.txt" })).await;
+    assert_eq!(status, 400);
+    assert!(outside.join("secret.txt").exists());
+}
+
+#[tokio::test]
+async fn protected_files_are_left_to_admins() {
+    let app = TestApp::spawn().await;
+    let id = app.create_fake_server("Protected").await;
+    let (editor, editor_id) = app.create_user("editor", "user").await;
+    let response = app.client
+        .put(app.url(&format!("/servers/{}/permissions/{}", id, editor_id)))
+        .json(&json!({ "permissions": ["files.edit"] }))
+        .send()
+        .await
+        .expect("grant");
+    assert_eq!(response.status().as_u16(), 200);
+
+    let write = |token: String, path: &'static str| {
+        let request = app.client
+            .post(app.url(&format!("/servers/{}/files/write", id)))
+            .bearer_auth(token)
+            .json(&json!({ "path": path, "content": "[]" }));
+        async move { request.send().await.expect("write").status().as_u16() }
+    };
+    // Not `.json` files: those are snapshotted first
+    assert_eq!(write(app.admin_token.clone(), "auth.enc").await, 200);
+    assert_eq!(write(editor.clone(), "notes.txt").await, 200);
+    assert_eq!(write(editor.clone(), "auth.enc").await, 403);
+    assert_eq!(write(editor.clone(), "./AUTH.enc").await, 403);
+    assert_eq!(write(editor.clone(), "permissions.json").await, 403);
+
+    let response = app.client
+        .post(app.url(&format!("/servers/{}/files/delete", id)))
+        .bearer_auth(&editor)
+        .json(&json!({ "path": "auth.enc" }))
+        .send()
+        .await
+        .expect("delete");
+    assert_eq!(response.status().as_u16(), 403);
+    let body: serde_json::Value = response.json().await.expect("error body");
+    assert_eq!(body["error"], "files.protected_path");
+}
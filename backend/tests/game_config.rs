@@ -0,0 +1,48 @@
+mod common;
+
+use std::path::PathBuf;
+
+use common::TestApp;
+use serde_json::{json, Value};
+
+#[tokio::test]
+async fn game_config_is_validated_and_written_to_config_json() {
+    let app = TestApp::spawn().await;
+    let id = app.create_fake_server("Typed").await;
+    let (_, server) = app.get(&format!("/servers/{}", id)).await;
+    let config_path = PathBuf::from(server["working_dir"].as_str().expect("working dir")).join("config.json");
+    let url = format!("/servers/{}/game-config", id);
+
+    let (status, config) = app.get(&url).await;
+    assert_eq!(status, 200);
+    assert_eq!((config["MaxPlayers"].as_u64(), config["Defaults"]["GameMode"].as_str()), (Some(100), Some("Adventure")));
+
+    let mut update = json!({
+        "MOTD": "Bienvenue",
+        "Password": "",
+        "MaxPlayers": 20,
+        "MaxViewRadius": 16,
+        "Defaults": { "World": "default", "GameMode": "Creative" },
+        "LogLevels": { "com.hypixel": "FINE" },
+    });
+    let (status, body) = app.put(&url, update.clone()).await;
+    assert_eq!(status, 200, "{}", body);
+
+    let file: Value = serde_json::from_str(&std::fs::read_to_string(&config_path).unwrap()).unwrap();
+    assert_eq!((file["MOTD"].as_str(), file["MaxPlayers"].as_u64()), (Some("Bienvenue"), Some(20)));
+    assert_eq!(file["LogLevels"]["com.hypixel"], "FINE");
+    // The rest of the generated file stays
+    assert_eq!(file["ServerName"], "Typed");
+    let (_, config) = app.get(&url).await;
+    assert_eq!(config, update);
+
+    update["Defaults"]["GameMode"] = json!("Hardcore");
+    let (status, body) = app.put(&url, update.clone()).await;
+    assert_eq!((status, body["code"].as_str(), body["detail"].as_str()), (400, Some("SERVER_INVALID_GAME_CONFIG"), Some("Defaults.GameMode")));
+    update["Defaults"]["GameMode"] = json!("Creative");
+    update["MaxPlayers"] = json!(0);
+    let (status, body) = app.put(&url, update).await;
+    assert_eq!((status, body["detail"].as_str()), (400, Some("MaxPlayers")));
+    let (_, config) = app.get(&url).await;
+    assert_eq!(config["MaxPlayers"], 20);
+}
@@ -0,0 +1,77 @@
+mod common;
+
+use common::{wait_for, TestApp};
+use draveur_manager::services::inbound_auth;
+use serde_json::{json, Value};
+
+async fn create_hook(app: &TestApp, admin: &str, body: Value) -> Value {
+    let response = app.client.post(app.url("/hooks")).bearer_auth(admin).json(&body).send().await.expect("create hook");
+    assert_eq!(response.status().as_u16(), 201);
+    response.json().await.expect("hook json")
+}
+
+async fn trigger(app: &TestApp, hook_id: &str, secret: &str, timestamp: i64, nonce: &str) -> u16 {
+    let body = br#"{"ref":"main"}"#;
+    app.client
+        .post(app.url(&format!("/hooks/{}/trigger", hook_id)))
+        .header(inbound_auth::HEADER_TIMESTAMP, timestamp.to_string())
+        .header(inbound_auth::HEADER_NONCE, nonce)
+        .header(inbound_auth::HEADER_SIGNATURE, format!("sha256={}", inbound_auth::sign(secret, timestamp, nonce, body)))
+        .body(body.to_vec())
+        .send()
+        .await
+        .expect("trigger hook")
+        .status()
+        .as_u16()
+}
+
+#[tokio::test]
+async fn signed_hook_starts_server_once_per_nonce() {
+    let app = TestApp::spawn().await;
+    let admin = app.admin_token.clone();
+    let id = app.create_fake_server("Hooked").await;
+
+    let hook = create_hook(&app, &admin, json!({ "name": "ci", "server_id": id, "action": "start" })).await;
+    let hook_id = hook["id"].as_str().expect("hook id");
+    let secret = hook["secret"].as_str().expect("secret shown on creation");
+    let now = chrono::Utc::now().timestamp();
+
+    assert_eq!(trigger(&app, hook_id, "dwh_wrong", now, "nonce-0001").await, 401);
+    assert_eq!(trigger(&app, hook_id, secret, now - 3600, "nonce-0002").await, 401);
+    assert_eq!(app.server_status(&id).await, "stopped");
+
+    assert_eq!(trigger(&app, hook_id, secret, now, "nonce-0003").await, 200);
+    wait_for("server running", || async { app.server_status(&id).await == "running" }).await;
+
+    // A captured call can't be replayed
+    assert_eq!(trigger(&app, hook_id, secret, now, "nonce-0003").await, 401);
+}
+
+#[tokio::test]
+async fn hook_rejects_callers_outside_its_allowlist() {
+    let app = TestApp::spawn().await;
+    let admin = app.admin_token.clone();
+    let id = app.create_fake_server("Restricted").await;
+
+    let hook = create_hook(&app, &admin, json!({
+        "name": "deploy",
+        "server_id": id,
+        "action": "start",
+        "allowed_ips": ["10.0.0.0/8"],
+    })).await;
+    let hook_id = hook["id"].as_str().expect("hook id");
+    let secret = hook["secret"].as_str().expect("secret");
+
+    let now = chrono::Utc::now().timestamp();
+    assert_eq!(trigger(&app, hook_id, secret, now, "nonce-0001").await, 401);
+
+    let response = app.client
+        .patch(app.url(&format!("/hooks/{}", hook_id)))
+        .bearer_auth(&admin)
+        .json(&json!({ "allowed_ips": ["127.0.0.1"] }))
+        .send()
+        .await
+        .expect("update hook");
+    assert_eq!(response.status().as_u16(), 200);
+    assert_eq!(trigger(&app, hook_id, secret, now, "nonce-0002").await, 200);
+}
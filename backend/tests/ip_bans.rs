@@ -0,0 +1,58 @@
+mod common;
+
+use common::TestApp;
+use draveur_manager::services::ip_bans::{self, Offense};
+use serde_json::json;
+
+// The ban cache is global: everything runs in one test so bans don't leak into another
+#[tokio::test]
+async fn bans_are_managed_enforced_and_placed_automatically() {
+    let app = TestApp::spawn().await;
+
+    let (status, body) = app.post("/ip-bans", json!({ "address": "203.0.113.9/24", "reason": "scanner" })).await;
+    assert_eq!(status, 201, "{}", body);
+    assert_eq!(body["address"], "203.0.113.0/24");
+    let ban_id = body["id"].as_str().unwrap().to_string();
+    assert!(ip_bans::is_banned("203.0.113.77".parse().unwrap()));
+
+    let (status, _) = app.post("/ip-bans", json!({ "address": "not-an-ip" })).await;
+    assert_eq!(status, 400);
+    let (status, body) = app.post("/ip-bans", json!({ "address": "127.0.0.0/8" })).await;
+    assert_eq!(status, 400);
+    assert_eq!(body["error"], "ip_bans.own_address");
+
+    let (status, _) = app.delete(&format!("/ip-bans/{}", ban_id)).await;
+    assert_eq!(status, 200);
+    assert!(!ip_bans::is_banned("203.0.113.77".parse().unwrap()));
+
+    // Path traversal weighs more than a failed login
+    let attacker = "198.51.100.7".parse().unwrap();
+    for _ in 0..6 {
+        ip_bans::record_offense(&app.state.pool, Some(attacker), Offense::PathTraversal).await;
+    }
+    assert!(ip_bans::is_banned(attacker));
+    let (status, body) = app.get("/ip-bans").await;
+    assert_eq!(status, 200);
+    assert!(body.as_array().unwrap().iter().any(|b| b["address"] == "198.51.100.7" && b["automatic"] == true && !b["expires_at"].is_null()), "{}", body);
+
+    // Loopback is never banned automatically, but an admin ban applies before routing
+    for _ in 0..50 {
+        ip_bans::record_offense(&app.state.pool, Some("127.0.0.1".parse().unwrap()), Offense::AuthFailure).await;
+    }
+    let (status, _) = app.get("/servers").await;
+    assert_eq!(status, 200);
+
+    sqlx::query("INSERT INTO ip_bans (id, address, created_at) VALUES ('local', '127.0.0.1', datetime('now'))")
+        .execute(&app.state.pool)
+        .await
+        .unwrap();
+    ip_bans::load(&app.state.pool).await.unwrap();
+    let (status, body) = app.get("/servers").await;
+    assert_eq!(status, 401);
+    assert_eq!(body["error"], "auth.ip_banned");
+
+    sqlx::query("DELETE FROM ip_bans WHERE id = 'local'").execute(&app.state.pool).await.unwrap();
+    ip_bans::load(&app.state.pool).await.unwrap();
+    let (status, _) = app.get("/servers").await;
+    assert_eq!(status, 200);
+}
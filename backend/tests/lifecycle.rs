@@ -0,0 +1,201 @@
+mod common;
+
+use common::{wait_for, TestApp};
+use draveur_manager::api::servers::handlers;
+use serde_json::json;
+
+#[tokio::test]
+async fn start_and_stop_server() {
+    let app = TestApp::spawn().await;
+    let id = app.create_fake_server("Lifecycle").await;
+    assert_eq!(app.server_status(&id).await, "stopped");
+
+    app.start_server(&id).await;
+    assert!(app.state.process_manager.is_running(&id).await);
+
+    let (status, body) = app.post(&format!("/servers/{}/stop", id), json!({})).await;
+    assert_eq!(status, 200, "stop failed: {}", body);
+    assert_eq!(app.server_status(&id).await, "stopped");
+
+    // The ready line was seen, so the boot time was recorded
+    wait_for("startup sample", || async {
+        let (_, body) = app.get(&format!("/servers/{}/startups", id)).await;
+        body.as_array().is_some_and(|samples| samples.len() == 1)
+    }).await;
+}
+
+#[tokio::test]
+async fn starting_twice_is_rejected() {
+    let app = TestApp::spawn().await;
+    let id = app.create_fake_server("Twice").await;
+    app.start_server(&id).await;
+
+    let (status, _) = app.post(&format!("/servers/{}/start", id), json!({})).await;
+    assert_eq!(status, 400);
+
+    app.post(&format!("/servers/{}/stop", id), json!({})).await;
+}
+
+#[tokio::test]
+async fn players_are_tracked_from_console_lines() {
+    let app = TestApp::spawn().await;
+    let id = app.create_fake_server("Players").await;
+    app.start_server(&id).await;
+
+    let player_online = |online: bool| {
+        let app = &app;
+        let id = &id;
+        async move {
+            let (_, body) = app.get(&format!("/servers/{}", id)).await;
+            body["players"].as_array().is_some_and(|players| {
+                players.iter().any(|p| p["name"] == "Alice" && p["is_online"] == online)
+            })
+        }
+    };
+
+    app.send_command(&id, "/join Alice").await;
+    wait_for("player join", || player_online(true)).await;
+
+    app.send_command(&id, "/leave Alice").await;
+    wait_for("player leave", || player_online(false)).await;
+
+    app.post(&format!("/servers/{}/stop", id), json!({})).await;
+}
+
+#[tokio::test]
+async fn exec_command_returns_console_output() {
+    let app = TestApp::spawn().await;
+    let id = app.create_fake_server("Exec").await;
+    app.start_server(&id).await;
+
+    let (status, body) = app.post(&format!("/servers/{}/command/exec", id), json!({ "command": "hello" })).await;
+    assert_eq!(status, 200, "exec failed: {}", body);
+    let lines = body["lines"].as_array().expect("captured lines");
+    assert!(lines.iter().any(|l| l.as_str().is_some_and(|l| l.contains("Received command: hello"))), "{}", body);
+
+    app.post(&format!("/servers/{}/stop", id), json!({})).await;
+}
+
+#[tokio::test]
+async fn crash_is_reported_in_inbox() {
+    let app = TestApp::spawn().await;
+    let id = app.create_fake_server("Crashy").await;
+    sqlx::query("UPDATE servers SET watchdog_enabled = 0 WHERE id = ?")
+        .bind(&id)
+        .execute(&app.state.pool)
+        .await
+        .expect("disable watchdog");
+    app.start_server(&id).await;
+
+    app.send_command(&id, "/crash").await;
+    wait_for("process exit", || async { app.server_status(&id).await == "stopped" }).await;
+    wait_for("exit code recorded", || async {
+        let (_, body) = app.get(&format!("/servers/{}", id)).await;
+        body["last_exit_code"] == 1
+    }).await;
+
+    wait_for("crash in inbox", || async {
+        let (_, body) = app.get("/inbox").await;
+        body.as_array().is_some_and(|items| {
+            items.iter().any(|i| i["server_id"] == id.as_str() && i["kind"] == "crashed")
+        })
+    }).await;
+}
+
+#[tokio::test]
+async fn watchdog_relaunches_crashed_server() {
+    let app = TestApp::spawn().await;
+    let id = app.create_fake_server("Watched").await;
+    app.start_server(&id).await;
+
+    app.send_command(&id, "/crash").await;
+    wait_for("process exit", || async { app.server_status(&id).await == "stopped" }).await;
+    wait_for("watchdog relaunch", || async { app.server_status(&id).await == "running" }).await;
+
+    let (_, body) = app.get(&format!("/servers/{}", id)).await;
+    assert_eq!(body["last_exit_code"], 1);
+
+    app.post(&format!("/servers/{}/stop", id), json!({})).await;
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn suspend_and_resume_server() {
+    let app = TestApp::spawn().await;
+    let id = app.create_fake_server("Frozen").await;
+    app.start_server(&id).await;
+
+    let (status, body) = app.post(&format!("/servers/{}/suspend", id), json!({})).await;
+    assert_eq!(status, 200, "suspend failed: {}", body);
+    assert_eq!(app.server_status(&id).await, "suspended");
+
+    let (status, _) = app.post(&format!("/servers/{}/suspend", id), json!({})).await;
+    assert_eq!(status, 400);
+
+    let (status, body) = app.post(&format!("/servers/{}/resume", id), json!({})).await;
+    assert_eq!(status, 200, "resume failed: {}", body);
+    assert_eq!(app.server_status(&id).await, "running");
+
+    // Stopping a frozen server thaws it first so it can shut down cleanly
+    app.post(&format!("/servers/{}/suspend", id), json!({})).await;
+    let (status, body) = app.post(&format!("/servers/{}/stop", id), json!({})).await;
+    assert_eq!(status, 200, "stop failed: {}", body);
+    assert_eq!(app.server_status(&id).await, "stopped");
+}
+
+#[tokio::test]
+async fn unknown_server_is_not_found() {
+    let app = TestApp::spawn().await;
+    let (status, _) = app.get("/servers/does-not-exist").await;
+    assert_eq!(status, 404);
+
+    let (status, _) = app.post("/servers/does-not-exist/start", json!({})).await;
+    assert_eq!(status, 404);
+}
+
+#[tokio::test]
+async fn start_keeps_hand_edits_to_config_json() {
+    let app = TestApp::spawn().await;
+    let id = app.create_fake_server("Edited").await;
+    let (_, server) = app.get(&format!("/servers/{}", id)).await;
+    let config_path = std::path::PathBuf::from(server["working_dir"].as_str().expect("working dir")).join("config.json");
+
+    let mut config: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(&config_path).unwrap()).unwrap();
+    config["MOTD"] = json!("Edited by hand");
+    config["RateLimit"] = json!({ "PacketsPerSecond": 500 });
+    config["Port"] = json!(1);
+    let (status, _) = app.post(&format!("/servers/{}/files/write", id), json!({
+        "path": "config.json",
+        "content": serde_json::to_string_pretty(&config).unwrap(),
+    })).await;
+    assert_eq!(status, 200);
+
+    app.start_server(&id).await;
+    let config: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(&config_path).unwrap()).unwrap();
+    assert_eq!(config["MOTD"], "Edited by hand");
+    assert_eq!(config["RateLimit"]["PacketsPerSecond"], 500);
+    // Owned by the manager
+    assert_eq!(config["Port"], server["port"]);
+
+    app.post(&format!("/servers/{}/stop", id), json!({})).await;
+}
+
+#[tokio::test]
+async fn auto_start_writes_the_managed_config_keys() {
+    let app = TestApp::spawn().await;
+    let id = app.create_fake_server("Auto").await;
+    let (_, server) = app.get(&format!("/servers/{}", id)).await;
+    let config_path = std::path::PathBuf::from(server["working_dir"].as_str().expect("working dir")).join("config.json");
+
+    let mut config: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(&config_path).unwrap()).unwrap();
+    config["Port"] = json!(1);
+    std::fs::write(&config_path, config.to_string()).unwrap();
+    sqlx::query("UPDATE servers SET auto_start = 1 WHERE id = ?").bind(&id).execute(&app.state.pool).await.unwrap();
+
+    handlers::auto_start_servers(&app.state.pool, &app.state.process_manager).await;
+    wait_for("auto-start", || async { app.server_status(&id).await == "running" }).await;
+    let config: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(&config_path).unwrap()).unwrap();
+    assert_eq!(config["Port"], server["port"]);
+
+    app.post(&format!("/servers/{}/stop", id), json!({})).await;
+}
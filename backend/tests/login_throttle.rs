@@ -0,0 +1,37 @@
+mod common;
+
+use common::TestApp;
+use serde_json::{json, Value};
+
+#[tokio::test]
+async fn repeated_login_failures_lock_the_username() {
+    let app = TestApp::spawn().await;
+    app.create_user("player", "user").await;
+
+    for _ in 0..5 {
+        let (status, body) = app.post("/auth/login", json!({ "username": "admin", "password": "wrong" })).await;
+        assert_eq!((status, body["error"].as_str()), (401, Some("auth.invalid_credentials")));
+    }
+
+    // Locked even with the right password, until Retry-After has passed
+    let response = app.client.post(app.url("/auth/login"))
+        .json(&json!({ "username": "admin", "password": "password123" }))
+        .send()
+        .await
+        .expect("login");
+    assert_eq!(response.status().as_u16(), 429);
+    let retry_after: u64 = response.headers()["retry-after"].to_str().ok().and_then(|v| v.parse().ok()).expect("retry-after");
+    assert!((1..=30).contains(&retry_after));
+    let body: Value = response.json().await.expect("body");
+    assert_eq!(body["error"], "auth.too_many_attempts");
+
+    // Other accounts behind the same address still log in
+    let (status, _) = app.post("/auth/login", json!({ "username": "player", "password": "password123" })).await;
+    assert_eq!(status, 200);
+
+    let (status, entries) = app.get("/system/audit-log").await;
+    assert_eq!(status, 200);
+    // Successful logins are audited too, the lockout is not the latest entry
+    let lockout = entries.as_array().unwrap().iter().find(|e| e["action"] == "login_lockout").expect("lockout entry");
+    assert_eq!(lockout["username"], "admin");
+}
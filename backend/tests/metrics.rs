@@ -0,0 +1,21 @@
+mod common;
+
+use common::TestApp;
+
+#[tokio::test]
+async fn metrics_list_servers_for_admins() {
+    let app = TestApp::spawn().await;
+    let id = app.create_fake_server("Metrics").await;
+    let url = app.base_url.replace("/api/v1", "/metrics");
+
+    let response = reqwest::get(&url).await.expect("request");
+    assert_eq!(response.status(), 401);
+
+    let response = app.client.get(&url).send().await.expect("request");
+    assert_eq!(response.status(), 200);
+    assert!(response.headers()["content-type"].to_str().unwrap().starts_with("text/plain"));
+    let body = response.text().await.expect("body");
+    assert!(body.contains(&format!("draveur_server_up{{server_id=\"{}\",server=\"Metrics\"", id)), "{}", body);
+    // The API calls made so far are counted
+    assert!(body.contains("draveur_http_requests_total{method=\"POST\",status=\"201\"}"), "{}", body);
+}
@@ -0,0 +1,47 @@
+mod common;
+
+use common::{TestApp, FAKE_SERVER_BIN};
+use serde_json::{json, Value};
+
+async fn create_server(app: &TestApp, name: &str, role: &str, parent: Option<&str>) -> (u16, Value) {
+    app.post("/servers", json!({
+        "name": name,
+        "game_type": "fake",
+        "executable_path": FAKE_SERVER_BIN,
+        "working_dir": app.servers_dir().to_string_lossy(),
+        "launch_mode": "command",
+        "command_template": format!("{} {{port}}", FAKE_SERVER_BIN),
+        "network_role": role,
+        "parent_proxy_id": parent,
+    })).await
+}
+
+#[tokio::test]
+async fn servers_are_grouped_behind_their_proxy() {
+    let app = TestApp::spawn().await;
+    let (status, proxy) = create_server(&app, "Proxy", "proxy", None).await;
+    assert_eq!(status, 201, "create proxy failed: {}", proxy);
+    let proxy_id = proxy["id"].as_str().expect("proxy id").to_string();
+
+    let (status, lobby) = create_server(&app, "Lobby", "lobby", Some(&proxy_id)).await;
+    assert_eq!(status, 201, "create lobby failed: {}", lobby);
+    let lobby_id = lobby["id"].as_str().expect("lobby id").to_string();
+
+    // Only proxies can have members, and proxies can't be members
+    let (status, _) = create_server(&app, "Game", "game", Some(&lobby_id)).await;
+    assert_eq!(status, 400);
+    let (status, _) = create_server(&app, "Edge", "proxy", Some(&proxy_id)).await;
+    assert_eq!(status, 400);
+
+    let (_, server) = app.get(&format!("/servers/{}", lobby_id)).await;
+    assert_eq!(server["network_role"], "lobby");
+    assert_eq!(server["parent_proxy_id"], proxy_id.as_str());
+
+    let (status, networks) = app.get("/servers/networks").await;
+    assert_eq!(status, 200);
+    let networks = networks.as_array().expect("network list");
+    assert_eq!(networks.len(), 1);
+    assert_eq!(networks[0]["proxy"]["id"], proxy_id.as_str());
+    assert_eq!(networks[0]["members"][0]["id"], lobby_id.as_str());
+    assert_eq!(networks[0]["online_players"], 0);
+}
@@ -0,0 +1,186 @@
+mod common;
+
+use std::sync::{Arc, Mutex};
+
+use axum::{extract::State, routing::{get, post}, Json, Router};
+use common::TestApp;
+use draveur_manager::services::totp;
+use serde_json::{json, Value};
+
+fn base64url(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+    let mut out = String::new();
+    for chunk in bytes.chunks(3) {
+        let n = chunk.iter().enumerate().fold(0u32, |n, (i, b)| n | (*b as u32) << (16 - 8 * i));
+        for i in 0..=chunk.len() {
+            out.push(ALPHABET[(n >> (18 - 6 * i) & 63) as usize] as char);
+        }
+    }
+    out
+}
+
+/// Identity provider vouching for `admin`, with the nonce of the login in progress
+#[derive(Clone)]
+struct Provider {
+    issuer: String,
+    nonce: Arc<Mutex<String>>,
+    key: Arc<rcgen::KeyPair>,
+}
+
+async fn fake_provider() -> Provider {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.expect("bind provider");
+    let provider = Provider {
+        issuer: format!("http://{}", listener.local_addr().expect("provider address")),
+        nonce: Arc::default(),
+        key: Arc::new(rcgen::KeyPair::generate().expect("provider key")),
+    };
+
+    async fn discovery(State(p): State<Provider>) -> Json<Value> {
+        Json(json!({
+            "issuer": p.issuer,
+            "authorization_endpoint": format!("{}/authorize", p.issuer),
+            "token_endpoint": format!("{}/token", p.issuer),
+            "jwks_uri": format!("{}/jwks", p.issuer),
+        }))
+    }
+    async fn jwks(State(p): State<Provider>) -> Json<Value> {
+        // Uncompressed point: 0x04, x, y
+        let point = p.key.public_key_raw();
+        Json(json!({ "keys": [{
+            "kty": "EC", "crv": "P-256", "kid": "test", "use": "sig", "alg": "ES256",
+            "x": base64url(&point[1..33]), "y": base64url(&point[33..]),
+        }] }))
+    }
+    async fn token(State(p): State<Provider>) -> Json<Value> {
+        let now = chrono::Utc::now().timestamp();
+        let claims = json!({
+            "iss": p.issuer, "aud": "draveur", "sub": "admin-subject", "preferred_username": "admin",
+            "nonce": *p.nonce.lock().unwrap(), "iat": now, "exp": now + 300,
+        });
+        let mut header = jsonwebtoken::Header::new(jsonwebtoken::Algorithm::ES256);
+        header.kid = Some("test".into());
+        let key = jsonwebtoken::EncodingKey::from_ec_pem(p.key.serialize_pem().as_bytes()).expect("signing key");
+        Json(json!({ "id_token": jsonwebtoken::encode(&header, &claims, &key).expect("ID token") }))
+    }
+
+    let router = Router::new()
+        .route("/.well-known/openid-configuration", get(discovery))
+        .route("/jwks", get(jwks))
+        .route("/token", post(token))
+        .with_state(provider.clone());
+    tokio::spawn(async move { axum::serve(listener, router).await });
+    provider
+}
+
+/// Run an SSO login through the provider, `with_cookie` from the browser that started it.
+/// Returns where the panel sends the browser back to.
+async fn sso_login(app: &TestApp, provider: &Provider, with_cookie: bool) -> String {
+    let browser = reqwest::Client::builder().redirect(reqwest::redirect::Policy::none()).build().expect("client");
+    let response = browser.get(app.url("/auth/oidc/login")).send().await.expect("start login");
+    assert_eq!(response.status().as_u16(), 303);
+    let cookie = response.headers()["set-cookie"].to_str().expect("cookie").to_string();
+    assert!(cookie.contains("HttpOnly"), "{}", cookie);
+    let location = reqwest::Url::parse(response.headers()["location"].to_str().expect("location")).expect("provider URL");
+    let param = |name: &str| location.query_pairs().find(|(k, _)| k == name).map(|(_, v)| v.to_string()).expect(name);
+    *provider.nonce.lock().unwrap() = param("nonce");
+
+    let mut callback = browser.get(app.url(&format!("/auth/oidc/callback?code=abc&state={}", param("state"))));
+    if with_cookie {
+        callback = callback.header("cookie", cookie.split(';').next().expect("cookie value"));
+    }
+    let response = callback.send().await.expect("callback");
+    assert_eq!(response.status().as_u16(), 303);
+    response.headers()["location"].to_str().expect("location").to_string()
+}
+
+async fn servers_status(app: &TestApp, token: &str) -> u16 {
+    app.client.get(app.url("/servers")).bearer_auth(token).send().await.expect("request").status().as_u16()
+}
+
+#[tokio::test]
+async fn sso_is_offered_once_an_admin_configures_a_provider() {
+    let app = TestApp::spawn().await;
+    let (status, config) = app.get("/auth/oidc/config").await;
+    assert_eq!(status, 200);
+    assert_eq!(config["enabled"], false);
+    let (status, _) = app.get("/auth/oidc/login").await;
+    assert_eq!(status, 404);
+
+    let provider = json!({
+        "oidc_issuer_url": "https://id.example.com/",
+        "oidc_client_id": "draveur",
+        "oidc_client_secret": "s3cret",
+        "oidc_redirect_url": "https://panel.example.com/api/v1/auth/oidc/callback",
+    });
+    let (player, _) = app.create_user("player", "user").await;
+    let response = app.client.put(app.url("/settings")).bearer_auth(&player).json(&provider).send().await.expect("request");
+    assert_eq!(response.status().as_u16(), 403);
+
+    let response = app.client.put(app.url("/settings")).json(&json!({ "oidc_default_role": "owner" })).send().await.expect("request");
+    assert_eq!(response.status().as_u16(), 400);
+    let response = app.client.put(app.url("/settings")).json(&provider).send().await.expect("request");
+    assert_eq!(response.status().as_u16(), 200);
+
+    let (_, config) = app.get("/auth/oidc/config").await;
+    assert_eq!(config["enabled"], true);
+    let (_, settings) = app.get("/settings").await;
+    assert_eq!(settings["oidc_client_secret_set"], true);
+    assert_eq!(settings["oidc_default_role"], "user");
+    assert!(settings.get("oidc_client_secret").is_none());
+
+    // A callback without a pending login goes back to the login page with an error
+    let response = reqwest::Client::builder()
+        .redirect(reqwest::redirect::Policy::none())
+        .build()
+        .expect("client")
+        .get(app.url("/auth/oidc/callback?code=abc&state=unknown"))
+        .send()
+        .await
+        .expect("request");
+    assert_eq!(response.status().as_u16(), 303);
+    assert_eq!(response.headers()["location"], "/login#sso_error=auth.oidc_failed");
+}
+
+#[tokio::test]
+async fn sso_logins_are_bound_to_the_browser_and_keep_the_panel_second_factor() {
+    let app = TestApp::spawn().await;
+    let provider = fake_provider().await;
+    let (status, body) = app.put("/settings", json!({
+        "oidc_issuer_url": provider.issuer,
+        "oidc_client_id": "draveur",
+        "oidc_client_secret": "s3cret",
+        "oidc_redirect_url": "http://panel.example.com/api/v1/auth/oidc/callback",
+        "oidc_link_existing": true,
+        "require_2fa_admin": true,
+    })).await;
+    assert_eq!(status, 200, "{}", body);
+
+    // A callback replayed in another browser (login CSRF) is refused
+    assert_eq!(sso_login(&app, &provider, false).await, "/login#sso_error=auth.oidc_failed");
+
+    // Admins without two-factor authentication only get to set it up
+    let location = sso_login(&app, &provider, true).await;
+    let token = location.strip_prefix("/login#sso_token=").unwrap_or_else(|| panic!("no session: {}", location));
+    assert_eq!(servers_status(&app, token).await, 401);
+
+    // With it, the code is asked for before any session opens
+    let secret = totp::generate_secret();
+    sqlx::query("UPDATE users SET totp_secret = ?, totp_enabled = 1 WHERE username = 'admin'")
+        .bind(&secret)
+        .execute(&app.state.pool)
+        .await
+        .expect("enable 2FA");
+    let location = sso_login(&app, &provider, true).await;
+    let ticket = location.strip_prefix("/login#sso_2fa=").unwrap_or_else(|| panic!("no ticket: {}", location)).to_string();
+
+    let (status, body) = app.post("/auth/oidc/2fa", json!({ "ticket": ticket, "code": "000000" })).await;
+    assert_eq!((status, body["error"].as_str()), (401, Some("auth.invalid_totp")));
+    let code = totp::current_code(&secret, chrono::Utc::now().timestamp()).expect("code");
+    let (status, body) = app.post("/auth/oidc/2fa", json!({ "ticket": ticket, "code": code })).await;
+    assert_eq!(status, 200, "{}", body);
+    assert_eq!(body["two_factor_setup_required"], false);
+    assert_eq!(servers_status(&app, body["token"].as_str().expect("token")).await, 200);
+
+    let (status, _) = app.post("/auth/oidc/2fa", json!({ "ticket": ticket, "code": code })).await;
+    assert_eq!(status, 401);
+}
@@ -0,0 +1,20 @@
+mod common;
+
+use common::TestApp;
+
+#[tokio::test]
+async fn specification_is_served_without_login() {
+    let app = TestApp::spawn().await;
+
+    let response = reqwest::get(app.url("/openapi.json")).await.expect("request");
+    assert_eq!(response.status(), 200);
+    let spec: serde_json::Value = response.json().await.expect("json");
+
+    assert_eq!(spec["servers"][0]["url"], "/api/v1");
+    assert!(spec["components"]["securitySchemes"]["bearer"].is_object(), "{}", spec["components"]);
+    // Each module's routes are merged in, public ones without the bearer requirement
+    assert!(spec["paths"]["/servers/{id}/start"]["post"].is_object());
+    assert!(spec["paths"]["/backups"].is_object());
+    assert_eq!(spec["paths"]["/auth/login"]["post"]["security"], serde_json::json!([{}]));
+    assert!(spec["components"]["schemas"]["ServerResponse"].is_object());
+}
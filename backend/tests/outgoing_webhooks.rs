@@ -0,0 +1,81 @@
+mod common;
+
+use std::sync::{Arc, Mutex};
+
+use axum::{body::Bytes, extract::State, http::HeaderMap, routing::post, Router};
+use common::{wait_for, TestApp};
+use draveur_manager::services::{inbound_auth, outgoing_webhooks};
+use serde_json::{json, Value};
+
+type Received = Arc<Mutex<Vec<(HeaderMap, Bytes)>>>;
+
+/// A receiver recording every delivery, returning its URL
+async fn receiver() -> (String, Received) {
+    let received = Received::default();
+    let app = Router::new()
+        .route("/events", post(|State(received): State<Received>, headers: HeaderMap, body: Bytes| async move {
+            received.lock().unwrap().push((headers, body));
+        }))
+        .with_state(received.clone());
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.expect("bind receiver");
+    let addr = listener.local_addr().expect("receiver address");
+    tokio::spawn(async move { axum::serve(listener, app).await });
+    (format!("http://{}/events", addr), received)
+}
+
+fn header<'a>(headers: &'a HeaderMap, name: &str) -> &'a str {
+    headers.get(name).and_then(|v| v.to_str().ok()).unwrap_or_default()
+}
+
+#[tokio::test]
+async fn server_start_is_delivered_signed() {
+    let app = TestApp::spawn().await;
+    let (url, received) = receiver().await;
+    let id = app.create_fake_server("Announced").await;
+
+    let (status, webhook) = app.post("/outgoing-webhooks", json!({
+        "name": "automation",
+        "url": url,
+        "events": ["server.started"],
+        "server_id": id,
+    })).await;
+    assert_eq!(status, 201, "{}", webhook);
+    let secret = webhook["secret"].as_str().expect("secret shown on creation").to_string();
+
+    app.start_server(&id).await;
+    wait_for("server.started delivery", || async { !received.lock().unwrap().is_empty() }).await;
+
+    let (headers, body) = received.lock().unwrap()[0].clone();
+    assert_eq!(header(&headers, outgoing_webhooks::HEADER_EVENT), "server.started");
+    let timestamp: i64 = header(&headers, inbound_auth::HEADER_TIMESTAMP).parse().expect("timestamp");
+    let nonce = header(&headers, inbound_auth::HEADER_NONCE);
+    assert_eq!(
+        header(&headers, inbound_auth::HEADER_SIGNATURE),
+        format!("sha256={}", inbound_auth::sign(&secret, timestamp, nonce, &body)),
+    );
+    let payload: Value = serde_json::from_slice(&body).expect("json payload");
+    assert_eq!(payload["server"]["id"], id.as_str());
+    assert_eq!(payload["server"]["name"], "Announced");
+}
+
+#[tokio::test]
+async fn webhooks_reject_unknown_events_and_can_be_tested() {
+    let app = TestApp::spawn().await;
+    let (url, received) = receiver().await;
+
+    let (status, body) = app.post("/outgoing-webhooks", json!({ "name": "bad", "url": url, "events": ["server.exploded"] })).await;
+    assert_eq!(status, 400);
+    assert_eq!(body["error"], "outgoing_webhooks.invalid_event");
+    let (status, _) = app.post("/outgoing-webhooks", json!({ "name": "bad", "url": "ftp://example.com", "events": ["backup.failed"] })).await;
+    assert_eq!(status, 400);
+
+    let (_, webhook) = app.post("/outgoing-webhooks", json!({ "name": "ops", "url": url, "events": ["backup.failed"] })).await;
+    let (status, result) = app.post(&format!("/outgoing-webhooks/{}/test", webhook["id"].as_str().unwrap()), json!({})).await;
+    assert_eq!(status, 200);
+    assert_eq!(result, json!({ "success": true, "status": 200 }));
+    assert_eq!(received.lock().unwrap().len(), 1);
+
+    let (_, webhooks) = app.get("/outgoing-webhooks").await;
+    assert_eq!(webhooks[0]["last_delivery_status"], "200");
+    assert!(webhooks[0].get("secret").is_none());
+}
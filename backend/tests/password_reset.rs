@@ -0,0 +1,136 @@
+mod common;
+
+use common::TestApp;
+use serde_json::json;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::TcpListener;
+use tokio::sync::mpsc;
+
+/// SMTP server accepting every message, handing over the data of each
+async fn fake_smtp() -> (u16, mpsc::UnboundedReceiver<String>) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.expect("bind SMTP");
+    let port = listener.local_addr().expect("SMTP address").port();
+    let (tx, rx) = mpsc::unbounded_channel();
+    tokio::spawn(async move {
+        while let Ok((stream, _)) = listener.accept().await {
+            let tx = tx.clone();
+            tokio::spawn(async move {
+                let (read, mut write) = stream.into_split();
+                let mut lines = BufReader::new(read).lines();
+                write.write_all(b"220 localhost ESMTP\r\n").await.ok();
+                while let Ok(Some(line)) = lines.next_line().await {
+                    let reply: &[u8] = match line.to_ascii_uppercase().get(..4).unwrap_or("") {
+                        "DATA" => {
+                            write.write_all(b"354 End data with <CR><LF>.<CR><LF>\r\n").await.ok();
+                            let mut data = String::new();
+                            while let Ok(Some(line)) = lines.next_line().await {
+                                if line == "." {
+                                    break;
+                                }
+                                data.push_str(&line);
+                                data.push('\n');
+                            }
+                            tx.send(data).ok();
+                            b"250 Queued\r\n"
+                        }
+                        "QUIT" => {
+                            write.write_all(b"221 Bye\r\n").await.ok();
+                            break;
+                        }
+                        _ => b"250 OK\r\n",
+                    };
+                    write.write_all(reply).await.ok();
+                }
+            });
+        }
+    });
+    (port, rx)
+}
+
+#[tokio::test]
+async fn admin_reset_link_sets_a_new_password_once() {
+    let app = TestApp::spawn().await;
+    let (user_token, user_id) = app.create_user("forgetful", "user").await;
+
+    // Only admins hand out reset links
+    let response = app.client.post(app.url(&format!("/users/{}/password-reset", user_id)))
+        .bearer_auth(&user_token)
+        .send()
+        .await
+        .expect("request");
+    assert_eq!(response.status().as_u16(), 403);
+
+    let (status, link) = app.post(&format!("/users/{}/password-reset", user_id), json!({})).await;
+    assert_eq!(status, 201, "reset link failed: {}", link);
+    let token = link["token"].as_str().expect("reset token").to_string();
+    assert_eq!(link["reset_path"], format!("/reset-password?token={}", token));
+
+    let (status, _) = app.post("/auth/password-reset", json!({ "token": token, "new_password": "short" })).await;
+    assert_eq!(status, 400);
+    let (status, body) = app.post("/auth/password-reset", json!({ "token": token, "new_password": "new-password-456" })).await;
+    assert_eq!(status, 200, "reset failed: {}", body);
+
+    // Whoever knew the old password is logged out
+    let response = app.client.get(app.url("/auth/me")).bearer_auth(&user_token).send().await.expect("request");
+    assert_eq!(response.status().as_u16(), 401);
+
+    let (status, _) = app.post("/auth/login", json!({ "username": "forgetful", "password": "password123" })).await;
+    assert_eq!(status, 401);
+    let (status, _) = app.post("/auth/login", json!({ "username": "forgetful", "password": "new-password-456" })).await;
+    assert_eq!(status, 200);
+
+    let (status, body) = app.post("/auth/password-reset", json!({ "token": token, "new_password": "another-password" })).await;
+    assert_eq!((status, body["error"].as_str()), (400, Some("auth.invalid_reset_token")));
+}
+
+#[tokio::test]
+async fn reset_links_are_emailed_once_smtp_is_configured() {
+    let app = TestApp::spawn().await;
+    let (status, body) = app.post("/users", json!({ "username": "forgetful", "password": "password123", "email": "forgetful@example.com" })).await;
+    assert_eq!(status, 201, "{}", body);
+    let (status, _) = app.post("/users", json!({ "username": "typo", "password": "password123", "email": "not an address" })).await;
+    assert_eq!(status, 400);
+
+    let (_, status_body) = app.get("/auth/status").await;
+    assert_eq!(status_body["email_reset"], false);
+    let (status, body) = app.post("/auth/password-reset/request", json!({ "login": "forgetful" })).await;
+    assert_eq!((status, body["error"].as_str()), (404, Some("auth.reset_email_disabled")));
+
+    let (port, mut mails) = fake_smtp().await;
+    let (status, body) = app.put("/settings", json!({
+        "smtp_host": "127.0.0.1",
+        "smtp_port": port,
+        "smtp_security": "none",
+        "smtp_from": "Panel <panel@example.com>",
+        "smtp_panel_url": "https://panel.example.com/",
+    })).await;
+    assert_eq!(status, 200, "{}", body);
+    let (_, status_body) = app.get("/auth/status").await;
+    assert_eq!(status_body["email_reset"], true);
+
+    // Unknown accounts get the same answer, and no email
+    let (status, unknown) = app.post("/auth/password-reset/request", json!({ "login": "nobody" })).await;
+    assert_eq!(status, 200);
+    let (status, body) = app.post("/auth/password-reset/request", json!({ "login": "forgetful@example.com" })).await;
+    assert_eq!(status, 200);
+    assert_eq!(body, unknown);
+
+    let mail = tokio::time::timeout(std::time::Duration::from_secs(10), mails.recv())
+        .await
+        .expect("reset email")
+        .expect("SMTP server");
+    assert!(mail.contains("To: forgetful@example.com"), "{}", mail);
+    // Quoted-printable body
+    let mail = mail.replace("=\n", "").replace("=3D", "=");
+    let link = "https://panel.example.com/reset-password?token=";
+    let token: String = mail[mail.find(link).expect("reset link") + link.len()..]
+        .chars()
+        .take_while(char::is_ascii_alphanumeric)
+        .collect();
+
+    let (status, body) = app.post("/auth/password-reset", json!({ "token": token, "new_password": "new-password-456" })).await;
+    assert_eq!(status, 200, "reset failed: {}", body);
+    let (status, _) = app.post("/auth/login", json!({ "username": "forgetful", "password": "new-password-456" })).await;
+    assert_eq!(status, 200);
+    assert!(mails.try_recv().is_err(), "email sent for an unknown account");
+}
@@ -0,0 +1,46 @@
+mod common;
+
+use common::TestApp;
+use serde_json::{json, Value};
+
+#[tokio::test]
+async fn command_bucket_answers_429_once_spent() {
+    let app = TestApp::spawn().await;
+    let id = app.create_fake_server("limited").await;
+
+    let response = app.client.put(app.url("/settings"))
+        .json(&json!({ "rate_limits": { "commands": 2 } }))
+        .send()
+        .await
+        .expect("update settings");
+    assert_eq!(response.status().as_u16(), 200);
+    let (_, settings) = app.get("/settings").await;
+    assert_eq!(settings["rate_limits"]["commands"], 2);
+    assert_eq!(settings["rate_limits"]["auth"], 20);
+
+    let response = app.client.put(app.url("/settings"))
+        .json(&json!({ "rate_limits": { "everything": 1 } }))
+        .send()
+        .await
+        .expect("update settings");
+    assert_eq!(response.status().as_u16(), 400);
+
+    let command = || app.client.post(app.url(&format!("/servers/{}/command", id))).json(&json!({ "command": "list" })).send();
+    for remaining in ["1", "0"] {
+        let response = command().await.expect("command");
+        assert_ne!(response.status().as_u16(), 429);
+        assert_eq!(response.headers()["x-ratelimit-limit"], "2");
+        assert_eq!(response.headers()["x-ratelimit-remaining"], remaining);
+    }
+
+    let response = command().await.expect("command");
+    assert_eq!(response.status().as_u16(), 429);
+    let retry_after: u64 = response.headers()["retry-after"].to_str().ok().and_then(|v| v.parse().ok()).expect("retry-after");
+    assert!((1..=30).contains(&retry_after));
+    let body: Value = response.json().await.expect("body");
+    assert_eq!(body["error"], "errors.rate_limited");
+
+    // Other endpoints aren't counted
+    let (status, _) = app.get(&format!("/servers/{}", id)).await;
+    assert_eq!(status, 200);
+}
@@ -0,0 +1,40 @@
+mod common;
+
+use common::TestApp;
+use serde_json::{json, Value};
+
+#[tokio::test]
+async fn request_id_reaches_headers_errors_and_audit_log() {
+    let app = TestApp::spawn().await;
+
+    let response = app.client.get(app.url("/servers")).send().await.expect("list servers");
+    let generated = response.headers()["x-request-id"].to_str().expect("request id").to_string();
+    assert_eq!(generated.len(), 32);
+
+    // Error bodies quote the ID of the response
+    let response = app.client.get(app.url("/servers/missing")).send().await.expect("get server");
+    assert_eq!(response.status().as_u16(), 404);
+    let id = response.headers()["x-request-id"].to_str().expect("request id").to_string();
+    assert_ne!(id, generated);
+    let body: Value = response.json().await.expect("body");
+    assert_eq!(body["request_id"], id);
+
+    // A proxy's ID is kept, a malformed one replaced
+    let response = app.client.get(app.url("/servers")).header("x-request-id", "proxy-42").send().await.expect("list servers");
+    assert_eq!(response.headers()["x-request-id"], "proxy-42");
+    let response = app.client.get(app.url("/servers")).header("x-request-id", "not an id").send().await.expect("list servers");
+    assert_ne!(response.headers()["x-request-id"], "not an id");
+
+    let response = reqwest::Client::new()
+        .post(app.url("/auth/login"))
+        .header("x-request-id", "login-7")
+        .json(&json!({ "username": "admin", "password": "password123" }))
+        .send()
+        .await
+        .expect("login");
+    assert_eq!(response.status().as_u16(), 200);
+    let (status, entries) = app.get("/system/audit-log").await;
+    assert_eq!(status, 200);
+    assert_eq!(entries[0]["action"], "login");
+    assert_eq!(entries[0]["request_id"], "login-7");
+}
@@ -0,0 +1,51 @@
+mod common;
+
+use common::TestApp;
+use serde_json::json;
+
+#[tokio::test]
+async fn exported_servers_are_recreated_in_the_local_servers_dir() {
+    let app = TestApp::spawn().await;
+    let id = app.create_fake_server("Survival").await;
+
+    let (status, export) = app.get(&format!("/servers/{}/export", id)).await;
+    assert_eq!(status, 200, "{}", export);
+    let mut definition = export["servers"][0].clone();
+    assert_eq!(definition["source_id"], id.as_str());
+    assert_eq!(definition["name"], "Survival");
+    assert_eq!(definition["working_dir"], "");
+
+    // Another panel: its own servers_dir, and a game server listed before its proxy
+    let imported_dir = app.dir.path().join("imported");
+    sqlx::query("UPDATE settings SET value = ? WHERE key = 'servers_dir'")
+        .bind(imported_dir.to_string_lossy().as_ref())
+        .execute(&app.state.pool)
+        .await
+        .unwrap();
+    let mut proxy = definition.clone();
+    proxy["source_id"] = json!("old-proxy");
+    proxy["name"] = json!("Proxy");
+    proxy["network_role"] = json!("proxy");
+    definition["network_role"] = json!("game");
+    definition["parent_proxy_id"] = json!("old-proxy");
+
+    let (status, body) = app.post("/servers/import", json!({
+        "version": 1,
+        "exported_at": export["exported_at"],
+        "servers": [definition, proxy],
+    })).await;
+    assert_eq!(status, 200, "{}", body);
+    assert_eq!(body["imported"], 2, "{}", body);
+    let new_id = |name: &str| body["results"].as_array().unwrap().iter()
+        .find(|r| r["name"] == name)
+        .and_then(|r| r["id"].as_str())
+        .expect("imported id")
+        .to_string();
+
+    let (_, server) = app.get(&format!("/servers/{}", new_id("Survival"))).await;
+    assert!(server["working_dir"].as_str().unwrap().starts_with(imported_dir.to_str().unwrap()), "{}", server);
+    assert_eq!(server["parent_proxy_id"], new_id("Proxy").as_str());
+
+    let (status, _) = app.post("/servers/import", json!({ "version": 2, "exported_at": "", "servers": [] })).await;
+    assert_eq!(status, 400);
+}
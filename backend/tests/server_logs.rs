@@ -0,0 +1,55 @@
+mod common;
+
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime};
+
+use common::TestApp;
+use serde_json::json;
+
+#[tokio::test]
+async fn logs_are_listed_tailed_downloaded_and_compressed() {
+    let app = TestApp::spawn().await;
+    let id = app.create_fake_server("logged").await;
+    let (_, server) = app.get(&format!("/servers/{}", id)).await;
+    let logs_dir = PathBuf::from(server["working_dir"].as_str().expect("working dir")).join("logs");
+    std::fs::create_dir_all(&logs_dir).unwrap();
+
+    let console: String = (1..=300).map(|i| format!("line {}\n", i)).collect();
+    std::fs::write(logs_dir.join("console.log"), console).unwrap();
+    std::fs::write(logs_dir.join("old.log"), "first\nsecond\nthird\n").unwrap();
+    std::fs::File::options()
+        .write(true)
+        .open(logs_dir.join("old.log"))
+        .unwrap()
+        .set_modified(SystemTime::now() - Duration::from_secs(2 * 3600))
+        .unwrap();
+
+    let (status, files) = app.get(&format!("/servers/{}/logs", id)).await;
+    assert_eq!(status, 200);
+    let names: Vec<&str> = files.as_array().unwrap().iter().filter_map(|f| f["name"].as_str()).collect();
+    assert_eq!(names, ["console.log", "old.log"]);
+
+    let (_, tail) = app.get(&format!("/servers/{}/logs/tail", id)).await;
+    let lines = tail["lines"].as_array().expect("lines");
+    assert_eq!((lines.len(), lines[0].as_str(), lines[199].as_str()), (200, Some("line 101"), Some("line 300")));
+
+    let response = app.client.get(app.url(&format!("/servers/{}/logs/download?file=old.log", id))).send().await.expect("download");
+    assert_eq!(response.status().as_u16(), 200);
+    assert!(response.headers()["content-disposition"].to_str().unwrap().contains("old.log"));
+    assert_eq!(response.text().await.unwrap(), "first\nsecond\nthird\n");
+
+    let (status, body) = app.post(&format!("/servers/{}/logs/compress", id), json!({ "file": "console.log" })).await;
+    assert_eq!((status, body["code"].as_str()), (400, Some("LOG_LIVE_FILE")));
+
+    // Only logs nobody writes to anymore are compressed
+    let (status, body) = app.post(&format!("/servers/{}/logs/compress", id), json!({})).await;
+    assert_eq!(status, 200, "{}", body);
+    assert_eq!(body["compressed"], json!(["old.log"]));
+    assert!(!logs_dir.join("old.log").exists());
+
+    let (_, tail) = app.get(&format!("/servers/{}/logs/tail?file=old.log.gz&lines=2", id)).await;
+    assert_eq!(tail["lines"], json!(["second", "third"]));
+
+    let (status, body) = app.get(&format!("/servers/{}/logs/tail?file=../../../../etc/passwd", id)).await;
+    assert_eq!((status, body["key"].as_str()), (400, Some("files.invalid_path")));
+}
@@ -0,0 +1,55 @@
+mod common;
+
+use common::{TestApp, FAKE_SERVER_BIN};
+use serde_json::{json, Value};
+
+async fn create_tagged(app: &TestApp, name: &str, tags: Value) -> String {
+    let (status, body) = app.post("/servers", json!({
+        "name": name,
+        "game_type": "fake",
+        "executable_path": FAKE_SERVER_BIN,
+        "working_dir": app.servers_dir().to_string_lossy(),
+        "launch_mode": "command",
+        "command_template": format!("{} {{port}}", FAKE_SERVER_BIN),
+        "tags": tags,
+    })).await;
+    assert_eq!(status, 201, "{}", body);
+    body["id"].as_str().unwrap().to_string()
+}
+
+fn names(list: &Value) -> Vec<&str> {
+    let mut names: Vec<&str> = list.as_array().unwrap().iter().map(|s| s["name"].as_str().unwrap()).collect();
+    names.sort();
+    names
+}
+
+#[tokio::test]
+async fn servers_are_found_by_text_and_tags() {
+    let app = TestApp::spawn().await;
+    let halloween = create_tagged(&app, "Halloween", json!([" Event ", "event", "PvP"])).await;
+    create_tagged(&app, "Creative", json!(["build"])).await;
+    create_tagged(&app, "Arena", json!(["pvp"])).await;
+
+    let (_, server) = app.get(&format!("/servers/{}", halloween)).await;
+    assert_eq!(server["tags"], json!(["event", "pvp"]));
+
+    let (_, list) = app.get("/servers?q=even").await;
+    assert_eq!(names(&list), ["Halloween"]);
+    let (_, list) = app.get("/servers?q=creat").await;
+    assert_eq!(names(&list), ["Creative"]);
+    let (_, list) = app.get("/servers?tags=pvp").await;
+    assert_eq!(names(&list), ["Arena", "Halloween"]);
+    let (_, list) = app.get("/servers?tags=pvp,event").await;
+    assert_eq!(names(&list), ["Halloween"]);
+    let (_, list) = app.get("/servers").await;
+    assert_eq!(list.as_array().unwrap().len(), 3);
+
+    let (status, _) = app.post("/servers", json!({
+        "name": "Too long",
+        "game_type": "fake",
+        "executable_path": FAKE_SERVER_BIN,
+        "working_dir": app.servers_dir().to_string_lossy(),
+        "tags": ["x".repeat(40)],
+    })).await;
+    assert_eq!(status, 400);
+}
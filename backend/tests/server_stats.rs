@@ -0,0 +1,47 @@
+mod common;
+
+use chrono::{Duration, Utc};
+use common::TestApp;
+
+#[tokio::test]
+async fn stats_return_the_stored_samples_of_the_range() {
+    let app = TestApp::spawn().await;
+    let id = app.create_fake_server("graphs").await;
+
+    for (minutes_ago, cpu, players, disk) in [(150, 10.0, 1, None), (10, 20.0, 3, Some(4096)), (5, 40.0, 2, Some(8192))] {
+        sqlx::query("INSERT INTO metrics_history (server_id, cpu, memory_bytes, players, recorded_at, disk_bytes) VALUES (?, ?, ?, ?, ?, ?)")
+            .bind(&id)
+            .bind(cpu)
+            .bind(1024_i64)
+            .bind(players)
+            .bind((Utc::now() - Duration::minutes(minutes_ago)).to_rfc3339())
+            .bind(disk)
+            .execute(&app.state.pool)
+            .await
+            .unwrap();
+    }
+
+    let (status, stats) = app.get(&format!("/servers/{}/stats?range=1h", id)).await;
+    assert_eq!(status, 200, "{}", stats);
+    assert_eq!(stats["interval_secs"], 60);
+    let points = stats["points"].as_array().expect("points");
+    assert_eq!(points.len(), 2);
+    assert_eq!(points[0]["cpu"], 20.0);
+    assert_eq!(points[0]["players"], 3);
+    assert_eq!(points[1]["disk_bytes"], 8192);
+
+    // A day averages five minutes per point
+    let (_, stats) = app.get(&format!("/servers/{}/stats", id)).await;
+    assert_eq!(stats["range"], "24h");
+    assert_eq!(stats["interval_secs"], 288);
+    let points = stats["points"].as_array().expect("points");
+    assert_eq!(points.len(), 3, "{}", stats);
+    assert!(points[0]["disk_bytes"].is_null());
+
+    for range in ["abc", "0h", "31d"] {
+        let (status, body) = app.get(&format!("/servers/{}/stats?range={}", id, range)).await;
+        assert_eq!((status, body["code"].as_str()), (400, Some("SERVER_INVALID_STATS_RANGE")));
+    }
+    let (status, _) = app.get("/servers/missing/stats").await;
+    assert_eq!(status, 404);
+}
@@ -0,0 +1,43 @@
+mod common;
+
+use common::{TestApp, FAKE_SERVER_BIN};
+use serde_json::json;
+
+#[tokio::test]
+async fn servers_are_created_from_a_template() {
+    let app = TestApp::spawn().await;
+
+    let (status, template) = app.post("/server-templates", json!({
+        "name": "Fake preset",
+        "settings": {
+            "name": "ignored",
+            "game_type": "fake",
+            "executable_path": FAKE_SERVER_BIN,
+            "launch_mode": "command",
+            "command_template": format!("{} {{port}}", FAKE_SERVER_BIN),
+            "max_memory": "6G",
+            "auth_mode": "offline",
+        },
+    })).await;
+    assert_eq!(status, 201, "{}", template);
+    assert!(template["settings"].get("name").is_none());
+    let template_id = template["id"].as_str().unwrap();
+
+    let (status, created) = app.post("/servers", json!({ "name": "From preset", "template_id": template_id })).await;
+    assert_eq!(status, 201, "{}", created);
+    let (_, server) = app.get(&format!("/servers/{}", created["id"].as_str().unwrap())).await;
+    assert_eq!(server["max_memory"], "6G");
+    assert_eq!(server["auth_mode"], "offline");
+    assert!(server["working_dir"].as_str().unwrap().starts_with(app.servers_dir().to_str().unwrap()));
+
+    // The request wins over the preset
+    let (status, created) = app.post("/servers", json!({ "name": "Bigger", "template_id": template_id, "max_memory": "8G" })).await;
+    assert_eq!(status, 201, "{}", created);
+    let (_, server) = app.get(&format!("/servers/{}", created["id"].as_str().unwrap())).await;
+    assert_eq!(server["max_memory"], "8G");
+
+    let (status, _) = app.post("/server-templates", json!({ "name": "Broken", "settings": { "port": "not a port" } })).await;
+    assert_eq!(status, 400);
+    let (status, _) = app.post("/servers", json!({ "name": "Orphan", "template_id": "unknown" })).await;
+    assert_eq!(status, 404);
+}
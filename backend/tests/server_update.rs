@@ -0,0 +1,37 @@
+mod common;
+
+use common::TestApp;
+use serde_json::{json, Value};
+
+#[tokio::test]
+async fn patch_only_changes_the_given_fields() {
+    let app = TestApp::spawn().await;
+    let id = app.create_fake_server("Patched").await;
+    let patch = |body: Value| {
+        let request = app.client.patch(app.url(&format!("/servers/{}", id))).json(&body);
+        async move { request.send().await.expect("PATCH request").status().as_u16() }
+    };
+
+    assert_eq!(patch(json!({ "max_memory": "6G", "tags": ["event"], "name": null })).await, 200);
+    let (_, server) = app.get(&format!("/servers/{}", id)).await;
+    assert_eq!(server["name"], "Patched");
+    assert_eq!(server["max_memory"], "6G");
+    assert_eq!(server["tags"], json!(["event"]));
+    assert_eq!(server["launch_mode"], "command");
+
+    assert_eq!(patch(json!({ "port": "high" })).await, 400);
+
+    // The launch mode is checked against the saved command template, and the other way round
+    assert_eq!(patch(json!({ "launch_mode": "command" })).await, 200);
+    assert_eq!(patch(json!({ "command_template": " " })).await, 400);
+    let (_, server) = app.get(&format!("/servers/{}", id)).await;
+    assert!(server["command_template"].as_str().is_some_and(|t| !t.trim().is_empty()));
+
+    // A running server can't move to another directory
+    app.start_server(&id).await;
+    let elsewhere = app.servers_dir().join("elsewhere");
+    assert_eq!(patch(json!({ "working_dir": elsewhere.to_string_lossy() })).await, 409);
+    assert_eq!(patch(json!({ "extra_args": "-Xss4M" })).await, 200);
+    let (status, _) = app.post(&format!("/servers/{}/stop", id), json!({})).await;
+    assert_eq!(status, 200);
+}
@@ -0,0 +1,104 @@
+mod common;
+
+use common::TestApp;
+use serde_json::json;
+
+fn session(secret: &str, expires_in_secs: i64) -> String {
+    let claims = json!({
+        "sub": "someone",
+        "username": "someone",
+        "role": "admin",
+        "exp": chrono::Utc::now().timestamp() + expires_in_secs,
+    });
+    jsonwebtoken::encode(&jsonwebtoken::Header::default(), &claims, &jsonwebtoken::EncodingKey::from_secret(secret.as_bytes()))
+        .expect("sign session")
+}
+
+async fn me_status(app: &TestApp, token: &str) -> u16 {
+    app.client.get(app.url("/auth/me")).bearer_auth(token).send().await.expect("request").status().as_u16()
+}
+
+#[tokio::test]
+async fn sessions_of_the_previous_secret_survive_a_rotation() {
+    let app = TestApp::spawn().await;
+    let old = session("old-secret", 3600);
+    assert_eq!(me_status(&app, &old).await, 401);
+
+    std::env::set_var("JWT_PREVIOUS_SECRET", "old-secret");
+    assert_eq!(me_status(&app, &old).await, 200);
+    // Expired or foreign sessions stay out
+    assert_eq!(me_status(&app, &session("old-secret", -3600)).await, 401);
+    assert_eq!(me_status(&app, &session("other-secret", 3600)).await, 401);
+    assert_eq!(me_status(&app, &app.admin_token).await, 200);
+}
+
+#[tokio::test]
+async fn sessions_are_listed_and_logged_out_remotely() {
+    let app = TestApp::spawn().await;
+    let login = app.client
+        .post(app.url("/auth/login"))
+        .header("user-agent", "Laptop browser")
+        .json(&json!({ "username": "admin", "password": "password123" }))
+        .send()
+        .await
+        .expect("login");
+    let laptop = login.json::<serde_json::Value>().await.expect("login response")["token"].as_str().expect("token").to_string();
+
+    let (status, body) = app.get("/auth/sessions").await;
+    assert_eq!(status, 200, "{}", body);
+    let sessions = body.as_array().unwrap();
+    assert_eq!(sessions.len(), 2, "{}", body);
+    assert_eq!(sessions.iter().filter(|s| s["current"] == true).count(), 1);
+    let laptop_session = sessions.iter().find(|s| s["user_agent"] == "Laptop browser").expect("laptop session");
+    assert_eq!(laptop_session["ip"], "127.0.0.1");
+    assert_eq!(laptop_session["current"], false);
+
+    let (status, _) = app.delete(&format!("/auth/sessions/{}", laptop_session["id"].as_str().unwrap())).await;
+    assert_eq!(status, 200);
+    let response = app.client.get(app.url("/auth/me")).bearer_auth(&laptop).send().await.expect("request");
+    assert_eq!(response.status().as_u16(), 401);
+    let body: serde_json::Value = response.json().await.expect("error body");
+    assert_eq!(body["error"], "auth.session_revoked");
+
+    // The session making the request stays open
+    let (status, body) = app.get("/auth/sessions").await;
+    assert_eq!(status, 200);
+    assert_eq!(body.as_array().unwrap().len(), 1, "{}", body);
+    assert_eq!(me_status(&app, &app.admin_token).await, 200);
+    let (status, _) = app.delete("/auth/sessions/unknown").await;
+    assert_eq!(status, 404);
+}
+
+#[tokio::test]
+async fn role_changes_and_deactivation_log_the_user_out() {
+    let app = TestApp::spawn().await;
+    let (status, body) = app.post("/users", json!({ "username": "operator", "password": "password123", "role": "user" })).await;
+    assert_eq!(status, 201, "{}", body);
+    let user_id = body["id"].as_str().expect("user id").to_string();
+    let login = || async {
+        let (_, body) = app.post("/auth/login", json!({ "username": "operator", "password": "password123" })).await;
+        body["token"].as_str().expect("token").to_string()
+    };
+
+    // Profile changes leave the sessions alone
+    let token = login().await;
+    let (status, _) = app.put(&format!("/users/{}", user_id), json!({ "language": "fr" })).await;
+    assert_eq!(status, 200);
+    assert_eq!(me_status(&app, &token).await, 200);
+
+    let (status, _) = app.put(&format!("/users/{}", user_id), json!({ "role": "viewer" })).await;
+    assert_eq!(status, 200);
+    assert_eq!(me_status(&app, &token).await, 401);
+
+    let token = login().await;
+    let (status, _) = app.put(&format!("/users/{}", user_id), json!({ "is_active": false })).await;
+    assert_eq!(status, 200);
+    assert_eq!(me_status(&app, &token).await, 401);
+
+    let (status, _) = app.put(&format!("/users/{}", user_id), json!({ "is_active": true })).await;
+    assert_eq!(status, 200);
+    let token = login().await;
+    let (status, _) = app.delete(&format!("/users/{}", user_id)).await;
+    assert_eq!(status, 200);
+    assert_eq!(me_status(&app, &token).await, 401);
+}
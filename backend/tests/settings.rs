@@ -0,0 +1,59 @@
+mod common;
+
+use common::TestApp;
+use serde_json::json;
+
+#[tokio::test]
+async fn only_admins_change_settings() {
+    let app = TestApp::spawn().await;
+    let (member, _) = app.create_user("member", "user").await;
+
+    let changes = [
+        // Un-hiding fields from their own role
+        json!({ "field_visibility": { "user": [] } }),
+        // Pointing the installer at a download run on the host
+        json!({ "downloader_url": "https://example.com/downloader.zip" }),
+        json!({ "offline_install": true }),
+        // Purging the audit trail and history early
+        json!({ "audit_retention_days": 1 }),
+        json!({ "metrics_retention_days": 1 }),
+        json!({ "events_retention_days": 1 }),
+        json!({ "auth_patterns": {} }),
+        json!({ "systemd_scopes": false }),
+        json!({ "metrics_interval_secs": 1 }),
+        json!({ "max_concurrent_starts": 100 }),
+        json!({ "auto_snapshots": false }),
+        json!({ "backup_compression": "none" }),
+        // Where reset links are sent from and point to
+        json!({ "smtp_panel_url": "https://phishing.example.com" }),
+    ];
+    for change in changes {
+        let response = app.client.put(app.url("/settings")).bearer_auth(&member).json(&change).send().await.expect("request");
+        assert_eq!(response.status().as_u16(), 403, "{} allowed", change);
+    }
+}
+
+#[tokio::test]
+async fn webhooks_and_offsite_logins_stay_with_admins() {
+    let app = TestApp::spawn().await;
+    let (member, _) = app.create_user("member", "user").await;
+    let (status, body) = app.put("/settings", json!({
+        "webhook_url": "https://discord.com/api/webhooks/1/secret",
+        "daily_summary_webhook_url": "https://discord.com/api/webhooks/2/secret",
+        "s3_access_key": "AKIAEXAMPLE",
+    })).await;
+    assert_eq!(status, 200, "{}", body);
+
+    let (_, settings) = app.get("/settings").await;
+    assert_eq!(settings["s3_access_key"], "AKIAEXAMPLE");
+    let (status, settings) = app.call(reqwest::Method::GET, "/settings", &member, None).await;
+    assert_eq!(status, 200);
+    assert!(settings["version"].is_string());
+    for field in ["webhook_url", "daily_summary_webhook_url", "s3_access_key"] {
+        assert!(settings[field].is_null(), "{} sent: {}", field, settings);
+    }
+
+    // The test message goes to any URL and lists every server
+    let (status, _) = app.call(reqwest::Method::POST, "/webhook/test", &member, Some(json!({ "webhook_url": "http://127.0.0.1:1/" }))).await;
+    assert_eq!(status, 403);
+}
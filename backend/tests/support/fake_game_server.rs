@@ -0,0 +1,52 @@
+//! Minimal stand-in for the Hytale server: prints the lines the process manager reacts to
+//! and answers a few console commands, so the panel can be exercised without a real build.
+//!
+//! Commands (one per stdin line):
+//! - `/join <name>` / `/leave <name>`: print the player join/leave lines
+//! - `/crash`: exit with code 1
+//! - `/shutdown`: exit cleanly (the default stop command)
+//! - anything else is echoed back
+
+use std::io::{BufRead, Write};
+
+fn log(line: &str) {
+    let now = timestamp();
+    println!("[{}   INFO] [FakeServer]: {}", now, line);
+    let _ = std::io::stdout().flush();
+}
+
+/// Seconds since the epoch, enough to look like the real log prefix
+fn timestamp() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+fn main() {
+    let port = std::env::args().nth(1).unwrap_or_else(|| "5520".to_string());
+    log(&format!("Starting fake server on port {}", port));
+    println!("[HytaleServer] Universe ready!");
+    let _ = std::io::stdout().flush();
+
+    for line in std::io::stdin().lock().lines() {
+        let Ok(line) = line else {
+            break;
+        };
+        let line = line.trim();
+
+        if let Some(name) = line.strip_prefix("/join ") {
+            log(&format!("{} joined the game", name));
+        } else if let Some(name) = line.strip_prefix("/leave ") {
+            log(&format!("{} left the game", name));
+        } else if line == "/crash" {
+            eprintln!("Fatal error, crashing on purpose");
+            std::process::exit(1);
+        } else if line == "/shutdown" {
+            log("Shutting down");
+            return;
+        } else if !line.is_empty() {
+            log(&format!("Received command: {}", line));
+        }
+    }
+}
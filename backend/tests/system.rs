@@ -0,0 +1,48 @@
+mod common;
+
+use common::TestApp;
+
+#[tokio::test]
+async fn panel_database_backup_is_staged_for_restore() {
+    let app = TestApp::spawn().await;
+    let token = app.admin_token.clone();
+
+    let (status, backup) = app.call(reqwest::Method::POST, "/system/database-backups", &token, None).await;
+    assert_eq!(status, 201, "database backup failed: {}", backup);
+    let name = backup["name"].as_str().expect("backup name").to_string();
+    assert!(backup["size_bytes"].as_u64().is_some_and(|size| size > 0));
+
+    let (status, list) = app.call(reqwest::Method::GET, "/system/database-backups", &token, None).await;
+    assert_eq!(status, 200);
+    assert!(list.as_array().expect("backup list").iter().any(|b| b["name"] == name.as_str()));
+
+    let (status, _) = app.call(reqwest::Method::POST, "/system/database-backups/database.db/restore", &token, None).await;
+    assert_eq!(status, 404);
+
+    let (status, body) = app.call(reqwest::Method::POST, &format!("/system/database-backups/{}/restore", name), &token, None).await;
+    assert_eq!(status, 200, "restore failed: {}", body);
+    assert_eq!(body["restart_required"], true);
+    assert!(app.dir.path().join("database.db.restore").exists());
+    assert!(app.backups_dir().join("panel").join(&name).exists());
+}
+
+#[tokio::test]
+async fn api_requires_a_session_outside_public_routes() {
+    let app = TestApp::spawn().await;
+    let anonymous = reqwest::Client::new();
+    let status = |path: &'static str| {
+        let request = anonymous.get(app.url(path));
+        async move { request.send().await.expect("request").status().as_u16() }
+    };
+
+    assert_eq!(status("/servers").await, 401);
+    assert_eq!(status("/settings").await, 401);
+    assert_eq!(status("/system/stats").await, 401);
+    assert_eq!(status("/auth/status").await, 200);
+    assert_eq!(status("/setup/status").await, 200);
+
+    let response = anonymous.get(app.url("/servers")).bearer_auth("not-a-jwt").send().await.expect("request");
+    assert_eq!(response.status().as_u16(), 401);
+    let (status, _) = app.get("/servers").await;
+    assert_eq!(status, 200);
+}
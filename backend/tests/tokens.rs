@@ -0,0 +1,44 @@
+mod common;
+
+use common::TestApp;
+use serde_json::json;
+
+#[tokio::test]
+async fn read_only_token_is_scoped_rate_limited_and_reported() {
+    let app = TestApp::spawn().await;
+    let session = app.admin_token.clone();
+
+    let (status, body) = app.call(reqwest::Method::POST, "/tokens", &session, Some(json!({
+        "name": "community bot",
+        "rate_limit_per_minute": 2,
+    }))).await;
+    assert_eq!(status, 201, "create token failed: {}", body);
+    assert_eq!(body["scopes"], json!(["read"]));
+    let token = body["token"].as_str().expect("token secret").to_string();
+    let token_id = body["id"].as_str().expect("token id").to_string();
+
+    let (status, _) = app.call(reqwest::Method::GET, "/servers", &token, None).await;
+    assert_eq!(status, 200);
+
+    // Writes need the write scope
+    let (status, _) = app.call(reqwest::Method::POST, "/servers/any/start", &token, None).await;
+    assert_eq!(status, 401);
+
+    let (status, _) = app.call(reqwest::Method::GET, "/servers", &token, None).await;
+    assert_eq!(status, 200);
+    let (status, _) = app.call(reqwest::Method::GET, "/servers", &token, None).await;
+    assert_eq!(status, 429);
+
+    let (status, body) = app.call(reqwest::Method::GET, &format!("/tokens/{}/usage", token_id), &session, None).await;
+    assert_eq!(status, 200, "usage failed: {}", body);
+    assert_eq!(body["requests_per_day"][0]["requests"], 4);
+    assert_eq!(body["requests_per_day"][0]["rejected"], 2);
+    // Endpoints are reported as route patterns, not raw paths
+    let endpoints = body["endpoints"].as_array().expect("endpoints");
+    assert!(endpoints.iter().any(|e| e["method"] == "POST" && e["endpoint"] == "/api/v1/servers/:id/start"), "{}", body);
+
+    let (status, _) = app.call(reqwest::Method::DELETE, &format!("/tokens/{}", token_id), &session, None).await;
+    assert_eq!(status, 200);
+    let (status, _) = app.call(reqwest::Method::GET, "/servers", &token, None).await;
+    assert_eq!(status, 401);
+}
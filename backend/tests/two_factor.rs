@@ -0,0 +1,81 @@
+mod common;
+
+use common::TestApp;
+use draveur_manager::services::totp;
+use serde_json::{json, Value};
+
+async fn login(app: &TestApp, totp_code: Option<&str>) -> (u16, Value) {
+    app.post("/auth/login", json!({ "username": "admin", "password": "password123", "totp_code": totp_code })).await
+}
+
+/// Provision and confirm 2FA for the admin, returning its secret, backup codes and new session
+async fn enable(app: &TestApp, session: &str) -> (String, Vec<String>, String) {
+    let (status, setup) = app.call(reqwest::Method::POST, "/auth/2fa/setup", session, None).await;
+    assert_eq!(status, 200, "setup failed: {}", setup);
+    let secret = setup["secret"].as_str().expect("secret").to_string();
+    assert!(setup["otpauth_uri"].as_str().is_some_and(|uri| uri.starts_with("otpauth://totp/") && uri.contains(&secret)));
+
+    let code = totp::current_code(&secret, chrono::Utc::now().timestamp()).expect("code");
+    let (status, enabled) = app.call(reqwest::Method::POST, "/auth/2fa/enable", session, Some(json!({ "code": code }))).await;
+    assert_eq!(status, 200, "enable failed: {}", enabled);
+    let backup_codes = enabled["backup_codes"].as_array().expect("backup codes")
+        .iter()
+        .filter_map(|c| c.as_str().map(str::to_string))
+        .collect();
+    (secret, backup_codes, enabled["token"].as_str().expect("token").to_string())
+}
+
+async fn admin_secret(app: &TestApp) -> String {
+    sqlx::query_scalar("SELECT totp_secret FROM users WHERE id = ?")
+        .bind(&app.admin_id)
+        .fetch_one(&app.state.pool)
+        .await
+        .expect("read secret")
+}
+
+#[tokio::test]
+async fn login_requires_the_second_factor_once_enabled() {
+    let app = TestApp::spawn().await;
+    let (secret, backup_codes, _) = enable(&app, &app.admin_token).await;
+    assert_eq!(backup_codes.len(), totp::BACKUP_CODE_COUNT);
+
+    let (status, body) = login(&app, None).await;
+    assert_eq!((status, body["error"].as_str()), (401, Some("auth.totp_required")));
+    let (status, _) = login(&app, Some("000000")).await;
+    assert_eq!(status, 401);
+
+    // The code used to enable 2FA can't be replayed, the next one logs in
+    let next = totp::current_code(&secret, chrono::Utc::now().timestamp() + 30).expect("code");
+    let (status, body) = login(&app, Some(&next)).await;
+    assert_eq!(status, 200, "login failed: {}", body);
+
+    // Backup codes work once
+    let (status, _) = login(&app, Some(&backup_codes[0])).await;
+    assert_eq!(status, 200);
+    let (status, _) = login(&app, Some(&backup_codes[0])).await;
+    assert_eq!(status, 401);
+}
+
+#[tokio::test]
+async fn admins_enroll_before_using_the_panel_when_required() {
+    let app = TestApp::spawn().await;
+    let (status, _) = app.call(reqwest::Method::PUT, "/settings", &app.admin_token, Some(json!({ "require_2fa_admin": true }))).await;
+    assert_eq!(status, 200);
+
+    let (status, body) = login(&app, None).await;
+    assert_eq!(status, 200);
+    assert_eq!(body["two_factor_setup_required"], true);
+    let pending = body["token"].as_str().expect("token").to_string();
+
+    let (status, body) = app.call(reqwest::Method::GET, "/servers", &pending, None).await;
+    assert_eq!((status, body["error"].as_str()), (401, Some("auth.two_factor_setup_required")));
+
+    let (_, _, session) = enable(&app, &pending).await;
+    let (status, _) = app.call(reqwest::Method::GET, "/servers", &session, None).await;
+    assert_eq!(status, 200);
+
+    // Required for admins, so it can't be turned off
+    let code = totp::current_code(&admin_secret(&app).await, chrono::Utc::now().timestamp() + 30).expect("code");
+    let (status, _) = app.call(reqwest::Method::POST, "/auth/2fa/disable", &session, Some(json!({ "code": code }))).await;
+    assert_eq!(status, 400);
+}
@@ -15,6 +15,8 @@ import UserSettings from './pages/UserSettings';
 import Setup from './pages/Setup';
 import CreateServer from './pages/CreateServer';
 import EditUser from './pages/EditUser';
+import ResetPassword from './pages/ResetPassword';
+import ForgotPassword from './pages/ForgotPassword';
 
 function SetupCheck({ children }: { children: React.ReactNode }) {
     const [isChecking, setIsChecking] = useState(true);
@@ -68,6 +70,8 @@ function App() {
                             <Routes>
                                 <Route path="/setup" element={<Setup />} />
                                 <Route path="/login" element={<Login />} />
+                                <Route path="/reset-password" element={<ResetPassword />} />
+                                <Route path="/forgot-password" element={<ForgotPassword />} />
                                 <Route element={<Layout />}>
                                     <Route path="/dashboard" element={<Dashboard />} />
                                     <Route path="/servers" element={<Servers />} />
@@ -60,8 +60,13 @@ const InstallationProgress: React.FC<InstallationProgressProps> = ({ logs, onClo
         // 2. "Or visit: https://.../verify?user_code=..."
         // We want to prioritize the second one if it exists.
 
-        // Find ALL matches
-        const allLinkMatches = fullLogClean.match(/(https:\/\/oauth\.accounts\.hytale\.com\/[^\s\u001b]+)/g);
+        // Find ALL matches: links flagged by the backend first, then known OAuth links
+        const flaggedLinks = logs
+            .filter(log => log.startsWith('[AUTH_URL]:'))
+            .map(log => log.replace('[AUTH_URL]:', '').trim());
+        const allLinkMatches = flaggedLinks.length > 0
+            ? flaggedLinks
+            : fullLogClean.match(/(https:\/\/oauth\.accounts\.hytale\.com\/[^\s\u001b]+)/g);
 
         let bestUrl: string | null = null;
         if (allLinkMatches && allLinkMatches.length > 0) {
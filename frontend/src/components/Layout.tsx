@@ -107,7 +107,7 @@ export default function Layout() {
                                     <div className="user-menu__info">
                                         <span className="user-menu__name">{user.username}</span>
                                         <span className="user-menu__role">
-                                            {user.role === 'admin' ? 'Administrateur' : 'Utilisateur'}
+                                            {user.role === 'admin' ? 'Administrateur' : user.role === 'viewer' ? 'Lecture seule' : 'Utilisateur'}
                                         </span>
                                     </div>
                                 </div>
@@ -9,6 +9,7 @@ interface ServerConfigProps {
     configSaving: boolean;
     configError: string;
     javaVersions: { path: string; version: string }[];
+    interfaceAddresses: { interface: string; address: string; loopback: boolean }[];
     updateConfigValue: (key: any, value: any) => void;
     toggleJvmArg: (arg: string) => void;
     handleSaveConfig: (e: React.FormEvent) => void;
@@ -58,6 +59,7 @@ export default function ServerConfig({
     configSaving,
     configError,
     javaVersions,
+    interfaceAddresses,
     updateConfigValue,
     toggleJvmArg,
     handleSaveConfig,
@@ -67,6 +69,18 @@ export default function ServerConfig({
     hasChanges = false
 }: ServerConfigProps) {
     console.log("DEBUG: ServerConfig rendered. hasChanges =", hasChanges);
+    const bindAddress = configFormData.bind_address || "0.0.0.0";
+    const bindAddressOptions = [
+        { label: "Toutes les interfaces (0.0.0.0)", value: "0.0.0.0" },
+        ...interfaceAddresses.map((i) => ({
+            label: `${i.address} (${i.interface})`,
+            value: i.address,
+        })),
+    ];
+    // Keep a saved address visible even if its interface is gone
+    if (!bindAddressOptions.some((o) => o.value === bindAddress)) {
+        bindAddressOptions.push({ label: `${bindAddress} (indisponible)`, value: bindAddress });
+    }
     return (
         <div className="config-wrapper">
             <form onSubmit={handleSaveConfig} className="config-form">
@@ -209,11 +223,10 @@ export default function ServerConfig({
                             <div className="form-column">
                                 <div className="form-group">
                                     <label>Adresse IP (--bind)</label>
-                                    <input
-                                        type="text"
-                                        value={configFormData.bind_address || "0.0.0.0"}
-                                        onChange={(e) => updateConfigValue("bind_address", e.target.value)}
-                                        className="input font-mono"
+                                    <Select
+                                        options={bindAddressOptions}
+                                        value={bindAddress}
+                                        onChange={(v) => updateConfigValue("bind_address", v)}
                                     />
                                 </div>
                                 <div className="form-group">
@@ -12,6 +12,7 @@ interface AuthContextType {
     token: string | null;
     isLoading: boolean;
     login: (username: string, password: string) => Promise<void>;
+    loginWithToken: (token: string) => Promise<void>;
     loginWithDiscord: () => void;
     logout: () => void;
     updateUser: (updates: Partial<User>) => void;
@@ -74,6 +75,25 @@ export function AuthProvider({ children }: { children: ReactNode }) {
         applyUserAccentColor(data.user.accent_color);
     };
 
+    // Session handed back by the SSO callback
+    const loginWithToken = async (sessionToken: string) => {
+        const response = await fetch('/api/v1/auth/me', {
+            headers: { Authorization: `Bearer ${sessionToken}` },
+        });
+
+        if (!response.ok) {
+            const error = await response.json();
+            throw new Error(error.error || 'Login failed');
+        }
+
+        const me = await response.json();
+        setToken(sessionToken);
+        setUser(me);
+        localStorage.setItem('token', sessionToken);
+        localStorage.setItem('user', JSON.stringify(me));
+        applyUserAccentColor(me.accent_color);
+    };
+
     const loginWithDiscord = () => {
         // Redirect to Discord OAuth2
         const clientId = import.meta.env.VITE_DISCORD_CLIENT_ID;
@@ -103,7 +123,7 @@ export function AuthProvider({ children }: { children: ReactNode }) {
     };
 
     return (
-        <AuthContext.Provider value={{ user, token, isLoading, login, loginWithDiscord, logout, updateUser }}>
+        <AuthContext.Provider value={{ user, token, isLoading, login, loginWithToken, loginWithDiscord, logout, updateUser }}>
             {children}
         </AuthContext.Provider>
     );
@@ -46,9 +46,18 @@ export function useBackups(initialServerId?: string): UseBackupsReturn {
 
     const createBackup = useCallback(async (serverId: string): Promise<Backup | null> => {
         try {
-            const backup = await apiService.createBackup(serverId);
-            setBackups(prev => [backup, ...prev]);
-            return backup;
+            // The backup runs in the background, poll its job until it finishes
+            const { job_id } = await apiService.createBackup(serverId);
+            for (;;) {
+                await new Promise(resolve => setTimeout(resolve, 1000));
+                const job = await apiService.getBackupJob(job_id);
+                if (job.status === 'failed') return null;
+                if (job.status === 'done') {
+                    const backup = await apiService.getBackup(job.backup_id);
+                    setBackups(prev => [backup, ...prev]);
+                    return backup;
+                }
+            }
         } catch {
             return null;
         }
@@ -39,7 +39,7 @@ export function useConsole(serverId: string): UseConsoleReturn {
         setError(null);
 
         const protocol = window.location.protocol === 'https:' ? 'wss:' : 'ws:';
-        const wsUrl = `${protocol}//${window.location.hostname}:8080/ws/console/${serverId}`;
+        const wsUrl = `${protocol}//${window.location.hostname}:8080/ws/console/${serverId}?token=${localStorage.getItem('token') ?? ''}`;
 
         const ws = new WebSocket(wsUrl);
         wsRef.current = ws;
@@ -22,7 +22,14 @@ export const en: TranslationType = {
     errors: {
         internal: "Internal server error. Please check server logs.",
         database: "Database error. Please try again later.",
-        network: "Network error. Please check your connection."
+        network: "Network error. Please check your connection.",
+        not_found: "Not found",
+        bad_request: "Invalid request",
+        unauthorized: "Not allowed",
+        forbidden: "Not allowed",
+        too_many_requests: "Too many requests, please wait a moment",
+        rate_limited: "Too many requests, please wait a moment",
+        conflict: "Another operation is in progress"
     },
     sidebar: {
         dashboard: "Dashboard",
@@ -68,6 +75,8 @@ export const en: TranslationType = {
         server_header: "Server",
         kill: "Kill",
         not_found: "Server not found",
+        already_running: "Server already running",
+        not_running: "Server not running",
         create_success_message: "Server structure created. Download files via hytale-downloader."
     },
     auth: {
@@ -86,7 +95,22 @@ export const en: TranslationType = {
         missing_auth_header: "Missing authorization header",
         invalid_auth_header: "Invalid authorization header",
         invalid_token: "Invalid or expired session",
-        password_updated: "Password updated successfully"
+        password_updated: "Password updated successfully",
+        reset_subtitle: "Choose a new password",
+        reset_submit: "Reset password",
+        invalid_reset_token: "Invalid or expired reset link",
+        forgot_password: "Forgot password?",
+        forgot_subtitle: "Get a reset link by email",
+        forgot_login: "Username or email",
+        forgot_submit: "Send the link",
+        reset_email_sent: "If an account with an email address matches, a reset link is on its way",
+        reset_email_disabled: "Password reset by email is not configured",
+        sso_login: "Sign in with SSO",
+        totp_code: "Authenticator or backup code",
+        invalid_totp: "Invalid code",
+        oidc_disabled: "SSO login is not configured",
+        oidc_failed: "SSO login failed",
+        oidc_account_exists: "A local account already uses this name, ask an administrator to link it"
     },
     settings: {
         language: "Language",
@@ -99,6 +123,7 @@ export const en: TranslationType = {
         profile: "Profile",
         role_admin: "Administrator",
         role_user: "User",
+        role_viewer: "Read-only",
         accent_color: "Accent Color",
         custom_color_title: "Custom Color",
         save_color: "Save Color",
@@ -146,6 +171,9 @@ export const en: TranslationType = {
         delete_user: "Delete",
         username: "Username",
         password_placeholder: "Leave empty to keep unchanged",
+        email: "Email",
+        email_placeholder: "Optional, for password reset links",
+        invalid_email: "Invalid email address",
         status: "Status",
         last_login: "Last Login",
         not_found: "User not found",
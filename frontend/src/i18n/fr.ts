@@ -20,7 +20,14 @@ export const fr = {
     errors: {
         internal: "Une erreur interne est survenue. Veuillez consulter les logs serveur.",
         database: "Erreur de base de données. Veuillez réessayer plus tard.",
-        network: "Erreur réseau. Veuillez vérifier votre connexion."
+        network: "Erreur réseau. Veuillez vérifier votre connexion.",
+        not_found: "Introuvable",
+        bad_request: "Requête invalide",
+        unauthorized: "Action non autorisée",
+        forbidden: "Action non autorisée",
+        too_many_requests: "Trop de requêtes, veuillez patienter un instant",
+        rate_limited: "Trop de requêtes, veuillez patienter un instant",
+        conflict: "Une autre opération est en cours"
     },
     sidebar: {
         dashboard: "Tableau de Bord",
@@ -66,6 +73,8 @@ export const fr = {
         server_header: "Serveur",
         kill: "Forcer l'arrêt",
         not_found: "Serveur non trouvé",
+        already_running: "Serveur déjà démarré",
+        not_running: "Serveur arrêté",
         create_success_message: "Structure du serveur créée. Téléchargez les fichiers via hytale-downloader."
     },
     auth: {
@@ -84,7 +93,22 @@ export const fr = {
         missing_auth_header: "Header d'authentification manquant",
         invalid_auth_header: "Header d'authentification invalide",
         invalid_token: "Session invalide ou expirée",
-        password_updated: "Mot de passe mis à jour avec succès"
+        password_updated: "Mot de passe mis à jour avec succès",
+        reset_subtitle: "Choisissez un nouveau mot de passe",
+        reset_submit: "Réinitialiser le mot de passe",
+        invalid_reset_token: "Lien de réinitialisation invalide ou expiré",
+        forgot_password: "Mot de passe oublié ?",
+        forgot_subtitle: "Recevez un lien de réinitialisation par email",
+        forgot_login: "Nom d'utilisateur ou email",
+        forgot_submit: "Envoyer le lien",
+        reset_email_sent: "Si un compte avec une adresse email correspond, un lien de réinitialisation est en route",
+        reset_email_disabled: "La réinitialisation par email n'est pas configurée",
+        sso_login: "Connexion SSO",
+        totp_code: "Code d'authentification ou code de secours",
+        invalid_totp: "Code invalide",
+        oidc_disabled: "La connexion SSO n'est pas configurée",
+        oidc_failed: "Échec de la connexion SSO",
+        oidc_account_exists: "Un compte local porte déjà ce nom, demandez à un administrateur de le lier"
     },
 
     user_settings: {
@@ -93,6 +117,7 @@ export const fr = {
         profile: "Profil",
         role_admin: "Administrateur",
         role_user: "Utilisateur",
+        role_viewer: "Lecture seule",
         accent_color: "Couleur d'accentuation",
         custom_color_title: "Couleur personnalisée",
         save_color: "Sauvegarder la couleur",
@@ -222,6 +247,9 @@ export const fr = {
         delete_user: "Supprimer",
         username: "Nom d'utilisateur",
         password_placeholder: "Laisser vide pour ne pas changer",
+        email: "Email",
+        email_placeholder: "Facultatif, pour les liens de réinitialisation",
+        invalid_email: "Adresse email invalide",
         status: "Statut",
         last_login: "Dernière connexion",
         not_found: "Utilisateur non trouvé",
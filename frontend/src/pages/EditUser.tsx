@@ -9,6 +9,7 @@ import {
     Server,
     Clock,
     Check,
+    Eye,
     Save
 } from 'lucide-react';
 import Select from '../components/Select';
@@ -20,7 +21,8 @@ import { PRESET_COLORS, LANGUAGES } from '../constants/theme';
 interface User {
     id: string;
     username: string;
-    role: 'admin' | 'user';
+    email: string | null;
+    role: 'admin' | 'user' | 'viewer';
     is_active: boolean;
     language: string;
     accent_color: string;
@@ -50,8 +52,9 @@ export default function EditUser() {
 
     const [formData, setFormData] = useState({
         username: '',
+        email: '',
         password: '',
-        role: 'user' as 'admin' | 'user',
+        role: 'user' as 'admin' | 'user' | 'viewer',
         is_active: true,
         language: 'fr',
         accent_color: '#3A82F6',
@@ -87,6 +90,7 @@ export default function EditUser() {
                     setUser(userData);
                     setFormData({
                         username: userData.username,
+                        email: userData.email || '',
                         password: '',
                         role: userData.role,
                         is_active: userData.is_active,
@@ -116,6 +120,7 @@ export default function EditUser() {
 
             const body: Record<string, unknown> = {
                 username: formData.username,
+                email: formData.email,
                 role: formData.role,
                 is_active: formData.is_active,
                 language: formData.language,
@@ -199,6 +204,18 @@ export default function EditUser() {
                                 />
                             </div>
 
+                            {/* Email, for password reset links */}
+                            <div className="form-group">
+                                <label>{t('users.email')}</label>
+                                <input
+                                    type="email"
+                                    value={formData.email}
+                                    onChange={(e) => setFormData({ ...formData, email: e.target.value })}
+                                    placeholder={t('users.email_placeholder')}
+                                    className="input"
+                                />
+                            </div>
+
                             {/* Password */}
                             <div className="form-group">
                                 <label className="form-label-icon">
@@ -230,10 +247,11 @@ export default function EditUser() {
                                     <Select
                                         options={[
                                             { label: t('user_settings.role_user'), value: 'user', icon: <UserIcon size={14} /> },
+                                            { label: t('user_settings.role_viewer'), value: 'viewer', icon: <Eye size={14} /> },
                                             { label: t('user_settings.role_admin'), value: 'admin', icon: <Shield size={14} /> }
                                         ]}
                                         value={formData.role}
-                                        onChange={(value) => setFormData({ ...formData, role: value as 'admin' | 'user' })}
+                                        onChange={(value) => setFormData({ ...formData, role: value as 'admin' | 'user' | 'viewer' })}
                                     />
                                 </div>
                                 <div className="form-group">
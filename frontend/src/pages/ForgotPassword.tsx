@@ -0,0 +1,96 @@
+import { useState, FormEvent } from 'react';
+import { Link } from 'react-router-dom';
+import { useLanguage } from '../contexts/LanguageContext';
+import { Mail, AlertCircle, CheckCircle } from 'lucide-react';
+
+export default function ForgotPassword() {
+  const { t } = useLanguage();
+  const [login, setLogin] = useState('');
+  const [error, setError] = useState('');
+  const [sent, setSent] = useState(false);
+  const [isLoading, setIsLoading] = useState(false);
+
+  const handleSubmit = async (e: FormEvent) => {
+    e.preventDefault();
+    setError('');
+    setIsLoading(true);
+    try {
+      const response = await fetch('/api/v1/auth/password-reset/request', {
+        method: 'POST',
+        headers: { 'Content-Type': 'application/json' },
+        body: JSON.stringify({ login }),
+      });
+      if (!response.ok) {
+        const data = await response.json();
+        throw new Error(data.error || 'common.error');
+      }
+      setSent(true);
+    } catch (err) {
+      setError(err instanceof Error ? t(err.message) : t('common.error'));
+    } finally {
+      setIsLoading(false);
+    }
+  };
+
+  return (
+    <div className="login-page">
+      <div className="card login-card">
+        <div className="login-header">
+          <img
+            src="/draveur-manager-logo.png"
+            alt="Draveur Manager"
+            className="login-header__logo"
+          />
+          <p className="text-muted">{t('auth.forgot_subtitle')}</p>
+        </div>
+
+        {sent ? (
+          <div className="login-form">
+            <div className="alert alert--success">
+              <CheckCircle size={16} />
+              {t('auth.reset_email_sent')}
+            </div>
+            <Link to="/login" className="btn btn--primary btn--lg btn--full">
+              {t('auth.login')}
+            </Link>
+          </div>
+        ) : (
+          <form onSubmit={handleSubmit} className="login-form">
+            {error && (
+              <div className="alert alert--error">
+                <AlertCircle size={16} />
+                {error}
+              </div>
+            )}
+
+            <div className="form-group">
+              <label className="form-label">{t('auth.forgot_login')}</label>
+              <input
+                type="text"
+                value={login}
+                onChange={(e) => setLogin(e.target.value)}
+                required
+                className="form-input"
+              />
+            </div>
+
+            <button
+              type="submit"
+              className="btn btn--primary btn--lg btn--full"
+              disabled={isLoading}
+            >
+              <span className="flex-center">
+                <Mail size={18} />
+                {isLoading ? t('common.loading') : t('auth.forgot_submit')}
+              </span>
+            </button>
+
+            <Link to="/login" className="btn btn--ghost btn--full">
+              {t('auth.login')}
+            </Link>
+          </form>
+        )}
+      </div>
+    </div>
+  );
+}
@@ -1,8 +1,8 @@
 import { useState, FormEvent, useEffect } from 'react';
-import { useNavigate, Navigate } from 'react-router-dom';
+import { useNavigate, Navigate, Link } from 'react-router-dom';
 import { useAuth } from '../contexts/AuthContext';
 import { useLanguage } from '../contexts/LanguageContext';
-import { LogIn, UserPlus, Rocket, AlertCircle } from 'lucide-react';
+import { LogIn, UserPlus, Rocket, AlertCircle, KeyRound } from 'lucide-react';
 
 interface LoginSettings {
   login_background_url?: string;
@@ -10,7 +10,7 @@ interface LoginSettings {
 }
 
 export default function Login() {
-  const { user, login } = useAuth();
+  const { user, login, loginWithToken } = useAuth();
   const { t } = useLanguage();
   const navigate = useNavigate();
   const [username, setUsername] = useState('');
@@ -21,10 +21,17 @@ export default function Login() {
   const [needsSetup, setNeedsSetup] = useState<boolean | null>(null);
   const [checkingStatus, setCheckingStatus] = useState(true);
   const [loginSettings, setLoginSettings] = useState<LoginSettings>({});
+  const [ssoEnabled, setSsoEnabled] = useState(false);
+  const [emailReset, setEmailReset] = useState(false);
+  // SSO login waiting for the authenticator code
+  const [ssoTicket, setSsoTicket] = useState('');
+  const [totpCode, setTotpCode] = useState('');
 
   useEffect(() => {
     checkSetupStatus();
     fetchLoginSettings();
+    fetchSsoConfig();
+    completeSsoLogin();
   }, []);
 
   // Apply custom background if set
@@ -62,12 +69,50 @@ export default function Login() {
     }
   };
 
+  const fetchSsoConfig = async () => {
+    try {
+      const response = await fetch('/api/v1/auth/oidc/config');
+      if (response.ok) {
+        const data = await response.json();
+        setSsoEnabled(data.enabled);
+      }
+    } catch (err) {
+      console.error('Failed to fetch SSO config:', err);
+    }
+  };
+
+  // The SSO callback comes back with #sso_token=..., #sso_2fa=... or #sso_error=...
+  const completeSsoLogin = async () => {
+    const params = new URLSearchParams(window.location.hash.slice(1));
+    const ssoToken = params.get('sso_token');
+    const ssoError = params.get('sso_error');
+    const ticket = params.get('sso_2fa');
+    if (!ssoToken && !ssoError && !ticket) return;
+    window.history.replaceState(null, '', window.location.pathname);
+
+    if (ssoError) {
+      setError(t(ssoError));
+      return;
+    }
+    if (ticket) {
+      setSsoTicket(ticket);
+      return;
+    }
+    try {
+      await loginWithToken(ssoToken!);
+      navigate('/dashboard');
+    } catch (err) {
+      setError(err instanceof Error ? t(err.message) : t('auth.login_failed'));
+    }
+  };
+
   const checkSetupStatus = async () => {
     try {
       const response = await fetch('/api/v1/auth/status');
       if (response.ok) {
         const data = await response.json();
         setNeedsSetup(data.needs_setup);
+        setEmailReset(data.email_reset);
       }
     } catch (err) {
       console.error('Failed to check setup status:', err);
@@ -89,7 +134,19 @@ export default function Login() {
     setIsLoading(true);
 
     try {
-      if (needsSetup) {
+      if (ssoTicket) {
+        const response = await fetch('/api/v1/auth/oidc/2fa', {
+          method: 'POST',
+          headers: { 'Content-Type': 'application/json' },
+          body: JSON.stringify({ ticket: ssoTicket, code: totpCode }),
+        });
+        const data = await response.json();
+        if (!response.ok) {
+          throw new Error(data.error || 'common.error');
+        }
+        await loginWithToken(data.token);
+        navigate('/dashboard');
+      } else if (needsSetup) {
         const response = await fetch('/api/v1/auth/register', {
           method: 'POST',
           headers: { 'Content-Type': 'application/json' },
@@ -165,42 +222,60 @@ export default function Login() {
             </div>
           )}
 
-          <div className="form-group">
-            <label className="form-label">{t('auth.username')}</label>
-            <input
-              type="text"
-              value={username}
-              onChange={(e) => setUsername(e.target.value)}
-              placeholder={needsSetup ? t('auth.username') : t('auth.username')}
-              required
-              className="form-input"
-            />
-          </div>
-
-          <div className="form-group">
-            <label className="form-label">{t('auth.password')}</label>
-            <input
-              type="password"
-              value={password}
-              onChange={(e) => setPassword(e.target.value)}
-              placeholder={needsSetup ? t('auth.password') : t('auth.password')}
-              required
-              className="form-input"
-            />
-          </div>
-
-          {needsSetup && (
+          {ssoTicket ? (
             <div className="form-group">
-              <label className="form-label">{t('auth.confirm_password')}</label>
+              <label className="form-label">{t('auth.totp_code')}</label>
               <input
-                type="password"
-                value={confirmPassword}
-                onChange={(e) => setConfirmPassword(e.target.value)}
-                placeholder={t('auth.confirm_password')}
+                type="text"
+                inputMode="numeric"
+                autoComplete="one-time-code"
+                value={totpCode}
+                onChange={(e) => setTotpCode(e.target.value)}
                 required
+                autoFocus
                 className="form-input"
               />
             </div>
+          ) : (
+            <>
+              <div className="form-group">
+                <label className="form-label">{t('auth.username')}</label>
+                <input
+                  type="text"
+                  value={username}
+                  onChange={(e) => setUsername(e.target.value)}
+                  placeholder={needsSetup ? t('auth.username') : t('auth.username')}
+                  required
+                  className="form-input"
+                />
+              </div>
+
+              <div className="form-group">
+                <label className="form-label">{t('auth.password')}</label>
+                <input
+                  type="password"
+                  value={password}
+                  onChange={(e) => setPassword(e.target.value)}
+                  placeholder={needsSetup ? t('auth.password') : t('auth.password')}
+                  required
+                  className="form-input"
+                />
+              </div>
+
+              {needsSetup && (
+                <div className="form-group">
+                  <label className="form-label">{t('auth.confirm_password')}</label>
+                  <input
+                    type="password"
+                    value={confirmPassword}
+                    onChange={(e) => setConfirmPassword(e.target.value)}
+                    placeholder={t('auth.confirm_password')}
+                    required
+                    className="form-input"
+                  />
+                </div>
+              )}
+            </>
           )}
 
           <button
@@ -222,6 +297,21 @@ export default function Login() {
           </button>
         </form>
 
+        {emailReset && !needsSetup && (
+          <Link to="/forgot-password" className="btn btn--ghost btn--full mt-4">
+            {t('auth.forgot_password')}
+          </Link>
+        )}
+
+        {ssoEnabled && !needsSetup && !ssoTicket && (
+          <a href="/api/v1/auth/oidc/login" className="btn btn--secondary btn--lg btn--full mt-4">
+            <span className="flex-center">
+              <KeyRound size={18} />
+              {t('auth.sso_login')}
+            </span>
+          </a>
+        )}
+
         {!needsSetup && (
           <div className="login-footer">
             <p>Draveur Manager v0.1.0</p>
@@ -27,7 +27,7 @@ interface LoginCustomization {
 interface User {
     id: string;
     username: string;
-    role: 'admin' | 'user';
+    role: 'admin' | 'user' | 'viewer';
     is_active: boolean;
     language: string;
     accent_color: string;
@@ -0,0 +1,113 @@
+import { useState, FormEvent } from 'react';
+import { Link, useSearchParams } from 'react-router-dom';
+import { useLanguage } from '../contexts/LanguageContext';
+import { KeyRound, AlertCircle, CheckCircle } from 'lucide-react';
+
+export default function ResetPassword() {
+  const { t } = useLanguage();
+  const [searchParams] = useSearchParams();
+  const token = searchParams.get('token') ?? '';
+  const [password, setPassword] = useState('');
+  const [confirmPassword, setConfirmPassword] = useState('');
+  const [error, setError] = useState('');
+  const [done, setDone] = useState(false);
+  const [isLoading, setIsLoading] = useState(false);
+
+  const handleSubmit = async (e: FormEvent) => {
+    e.preventDefault();
+    setError('');
+
+    if (password !== confirmPassword) {
+      setError(t('user_settings.password_mismatch'));
+      return;
+    }
+
+    setIsLoading(true);
+    try {
+      const response = await fetch('/api/v1/auth/password-reset', {
+        method: 'POST',
+        headers: { 'Content-Type': 'application/json' },
+        body: JSON.stringify({ token, new_password: password }),
+      });
+      if (!response.ok) {
+        const data = await response.json();
+        throw new Error(data.error || 'common.error');
+      }
+      setDone(true);
+    } catch (err) {
+      setError(err instanceof Error ? t(err.message) : t('common.error'));
+    } finally {
+      setIsLoading(false);
+    }
+  };
+
+  return (
+    <div className="login-page">
+      <div className="card login-card">
+        <div className="login-header">
+          <img
+            src="/draveur-manager-logo.png"
+            alt="Draveur Manager"
+            className="login-header__logo"
+          />
+          <p className="text-muted">{t('auth.reset_subtitle')}</p>
+        </div>
+
+        {done ? (
+          <div className="login-form">
+            <div className="alert alert--success">
+              <CheckCircle size={16} />
+              {t('auth.password_updated')}
+            </div>
+            <Link to="/login" className="btn btn--primary btn--lg btn--full">
+              {t('auth.login')}
+            </Link>
+          </div>
+        ) : (
+          <form onSubmit={handleSubmit} className="login-form">
+            {(error || !token) && (
+              <div className="alert alert--error">
+                <AlertCircle size={16} />
+                {error || t('auth.invalid_reset_token')}
+              </div>
+            )}
+
+            <div className="form-group">
+              <label className="form-label">{t('user_settings.new_password')}</label>
+              <input
+                type="password"
+                value={password}
+                onChange={(e) => setPassword(e.target.value)}
+                required
+                minLength={8}
+                className="form-input"
+              />
+            </div>
+
+            <div className="form-group">
+              <label className="form-label">{t('auth.confirm_password')}</label>
+              <input
+                type="password"
+                value={confirmPassword}
+                onChange={(e) => setConfirmPassword(e.target.value)}
+                required
+                className="form-input"
+              />
+            </div>
+
+            <button
+              type="submit"
+              className="btn btn--primary btn--lg btn--full"
+              disabled={isLoading || !token}
+            >
+              <span className="flex-center">
+                <KeyRound size={18} />
+                {isLoading ? t('common.loading') : t('auth.reset_submit')}
+              </span>
+            </button>
+          </form>
+        )}
+      </div>
+    </div>
+  );
+}
@@ -186,6 +186,9 @@ export default function ServerDetail() {
     const [javaVersions, setJavaVersions] = useState<
         { path: string; version: string }[]
     >([]);
+    const [interfaceAddresses, setInterfaceAddresses] = useState<
+        { interface: string; address: string; loopback: boolean }[]
+    >([]);
 
     // Players tab state
     const [activePlayerTab, setActivePlayerTab] = useState<
@@ -258,7 +261,7 @@ export default function ServerDetail() {
 
         const protocol = window.location.protocol === "https:" ? "wss:" : "ws:";
         // Fix: Backend WS endpoint is under /api/v1
-        const ws = new WebSocket(`${protocol}//${window.location.host}/api/v1/ws/console/${id}`);
+        const ws = new WebSocket(`${protocol}//${window.location.host}/api/v1/ws/console/${id}?token=${localStorage.getItem('token') ?? ''}`);
 
         ws.onopen = () => {
             setIsConnected(true);
@@ -314,7 +317,7 @@ export default function ServerDetail() {
         ws.onclose = () => {
             setIsConnected(false);
             wsRef.current = null;
-            const shouldRetry = shouldReconnectRef.current && (serverStatusRef.current === "running" || serverStatusRef.current === "installing" || serverStatusRef.current === "auth_required");
+            const shouldRetry = shouldReconnectRef.current && (serverStatusRef.current === "running" || serverStatusRef.current === "installing" || serverStatusRef.current === "auth_required" || serverStatusRef.current === "suspended" || serverStatusRef.current === "unresponsive");
             if (shouldRetry) {
                 const retryDelay = Math.min(1000 * Math.pow(1.5, retryCountRef.current), 10000);
                 reconnectTimeoutRef.current = setTimeout(() => {
@@ -403,7 +406,7 @@ export default function ServerDetail() {
             });
             if (!res.ok) {
                 const data = await res.json();
-                if (res.status === 400 && action === "start" && data.error === "Server already running") {
+                if (res.status === 400 && action === "start" && data.code === "SERVER_ALREADY_RUNNING") {
                     fetchServer();
                     return;
                 }
@@ -556,6 +559,15 @@ export default function ServerDetail() {
                 } catch (error) { }
             };
             fetchJavaVersions();
+            const fetchInterfaces = async () => {
+                try {
+                    const response = await fetch("/api/v1/system/interfaces", {
+                        headers: { Authorization: `Bearer ${localStorage.getItem("token")}` },
+                    });
+                    if (response.ok) setInterfaceAddresses(await response.json());
+                } catch (error) { }
+            };
+            fetchInterfaces();
             if (server) {
                 // If we haven't initialized yet, OR if the server ID changed (e.g. navigation)
                 const isNewServer = configFormData.id !== server.id;
@@ -854,6 +866,7 @@ export default function ServerDetail() {
                         configSaving={configSaving}
                         configError={configError}
                         javaVersions={javaVersions}
+                        interfaceAddresses={interfaceAddresses}
                         updateConfigValue={updateConfigValue}
                         toggleJvmArg={toggleJvmArg}
                         handleSaveConfig={handleSaveConfig}
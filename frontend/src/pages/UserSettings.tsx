@@ -130,7 +130,7 @@ export default function UserSettings() {
                         <div className="user-profile__info">
                             <span className="user-profile__name">{user?.username}</span>
                             <span className="user-profile__role">
-                                {user?.role === 'admin' ? t('user_settings.role_admin') : t('user_settings.role_user')}
+                                {t(`user_settings.role_${user?.role === 'admin' || user?.role === 'viewer' ? user.role : 'user'}`)}
                             </span>
                         </div>
                     </div>
@@ -128,12 +128,20 @@ class ApiService {
     }
 
     async createBackup(serverId: string) {
-        return this.request<any>('/backups', {
+        return this.request<{ success: boolean; job_id: string; backup_id: string; status: string }>('/backups', {
             method: 'POST',
             body: JSON.stringify({ server_id: serverId }),
         });
     }
 
+    async getBackupJob(jobId: string) {
+        return this.request<{ id: string; backup_id: string; status: string; error: string | null }>(`/backups/jobs/${jobId}`);
+    }
+
+    async getBackup(id: string) {
+        return this.request<any>(`/backups/${id}`);
+    }
+
     async deleteBackup(id: string) {
         return this.request<{ success: boolean }>(`/backups/${id}`, {
             method: 'DELETE',
@@ -166,6 +166,9 @@ HOST=0.0.0.0
 PORT=5500
 DATABASE_URL=sqlite:$DATA_DIR/data/database.db?mode=rwc
 JWT_SECRET=$(openssl rand -base64 32)
+JWT_TTL_HOURS=168
+TLS_CERT_PATH=$DATA_DIR/data/tls/cert.pem
+TLS_KEY_PATH=$DATA_DIR/data/tls/key.pem
 SERVERS_DIR=$DATA_DIR/servers
 BACKUPS_DIR=$DATA_DIR/backups
 RUST_LOG=info
@@ -211,7 +214,7 @@ print_success() {
     echo "╚═══════════════════════════════════════════════════════════╝"
     echo -e "${NC}"
     echo ""
-    echo -e "  ${BLUE}📡 Interface web:${NC} http://$IP:5500"
+    echo -e "  ${BLUE}📡 Interface web:${NC} https://$IP:5500"
     echo ""
     echo -e "  ${YELLOW}Commandes utiles:${NC}"
     echo "    sudo systemctl start draveur    # Démarrer"
@@ -221,7 +224,7 @@ print_success() {
     echo ""
     echo -e "  ${PURPLE}Premier démarrage:${NC}"
     echo "    1. Démarrez le service: sudo systemctl start draveur"
-    echo "    2. Accédez à http://$IP:5500"
+    echo "    2. Accédez à https://$IP:5500"
     echo "    3. Créez votre compte admin"
     echo ""
 }
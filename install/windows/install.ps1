@@ -128,10 +128,13 @@ HOST=0.0.0.0
 PORT=5500
 DATABASE_URL=sqlite:$DataDir\data\database.db?mode=rwc
 JWT_SECRET=$jwtSecret
+JWT_TTL_HOURS=168
+TLS_CERT_PATH=$DataDir\data\tls\cert.pem
+TLS_KEY_PATH=$DataDir\data\tls\key.pem
 SERVERS_DIR=$DataDir\servers
 BACKUPS_DIR=$DataDir\backups
 RUST_LOG=info
-"@ | Set-Content "$DataDir\.env"
+"@ | Set-Content "$InstallDir\backend\.env"
 }
 
 # Création du service Windows
@@ -146,18 +149,13 @@ function Create-WindowsService {
         sc.exe delete $serviceName
     }
     
-    # Utiliser NSSM pour créer le service
-    if (-not (Get-Command nssm -ErrorAction SilentlyContinue)) {
-        choco install nssm -y
-        $env:Path = [System.Environment]::GetEnvironmentVariable("Path","Machine")
-    }
-    
-    nssm install $serviceName "$InstallDir\backend\target\release\draveur.exe"
-    nssm set $serviceName AppDirectory "$InstallDir\backend"
-    nssm set $serviceName AppEnvironmentExtra "HOST=0.0.0.0" "PORT=5500" "DATABASE_URL=sqlite:$DataDir\data\database.db?mode=rwc"
-    nssm set $serviceName DisplayName "Draveur Manager"
-    nssm set $serviceName Description "Game Server Manager for Hytale"
-    nssm set $serviceName Start SERVICE_AUTO_START
+    # Le binaire embarque son propre wrapper de service (--service)
+    $exe = "$InstallDir\backend\target\release\draveur.exe"
+    $binPath = "`"$exe`" --service --workdir `"$InstallDir\backend`""
+    New-Service -Name $serviceName -BinaryPathName $binPath -DisplayName "Draveur Manager" -Description "Game Server Manager for Hytale" -StartupType Automatic | Out-Null
+
+    # Redémarrage automatique en cas de plantage
+    sc.exe failure $serviceName reset= 86400 actions= restart/60000 | Out-Null
 }
 
 # Ouverture du pare-feu
@@ -181,7 +179,7 @@ function Show-Success {
     Write-ColorOutput Green "║           ✅ Installation terminée avec succès !          ║"
     Write-ColorOutput Green "╚═══════════════════════════════════════════════════════════╝"
     Write-Host ""
-    Write-Host "  📡 Interface web: http://${ip}:5500"
+    Write-Host "  📡 Interface web: https://${ip}:5500"
     Write-Host ""
     Write-ColorOutput Yellow "  Commandes utiles:"
     Write-Host "    Start-Service DraveurManager    # Démarrer"
@@ -190,7 +188,7 @@ function Show-Success {
     Write-Host ""
     Write-ColorOutput Magenta "  Premier démarrage:"
     Write-Host "    1. Démarrez le service: Start-Service DraveurManager"
-    Write-Host "    2. Accédez à http://${ip}:5500"
+    Write-Host "    2. Accédez à https://${ip}:5500"
     Write-Host "    3. Créez votre compte admin"
     Write-Host ""
 }